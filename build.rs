@@ -0,0 +1,12 @@
+//! Generates the gRPC bindings from `proto/` when the `grpc` feature is enabled (requires `protoc`), and rebuilds the
+//! embedded Postgres migrations whenever `migrations/` changes.
+
+fn main() {
+    // `sqlx::migrate!` embeds the directory at compile time
+    #[cfg(feature = "postgres")]
+    println!("cargo:rerun-if-changed=migrations");
+
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/pump_fun_monitor.proto")
+        .expect("failed to compile proto/pump_fun_monitor.proto");
+}
@@ -0,0 +1,23 @@
+//! Prints every token launch with a given symbol from a running monitor.
+//!
+//! ```text
+//! cargo run --example watch_symbol -- ws://localhost:8080 PEPE
+//! ```
+
+use futures_util::StreamExt;
+use pump_fun_monitor_corrected::client::MonitorClient;
+use pump_fun_monitor_corrected::data_models::FilterCriteria;
+
+#[tokio::main]
+async fn main() -> pump_fun_monitor_corrected::error::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let url = args.next().unwrap_or_else(|| "ws://localhost:8080".to_string());
+    let symbol = args.next();
+
+    let mut client = MonitorClient::connect(&url).await?;
+    client.set_filter(FilterCriteria { symbol, ..Default::default() })?;
+    while let Some(event) = client.next().await {
+        println!("{} {} ({}) by {}", event.token.mint_address, event.token.name, event.token.symbol, event.token.creator);
+    }
+    Ok(())
+}
@@ -0,0 +1,29 @@
+-- One row per token launch, written by the `postgres` sink of every monitor instance.
+-- Unsigned 64-bit amounts are NUMERIC(20, 0) since they can exceed BIGINT.
+CREATE TABLE IF NOT EXISTS token_events (
+    mint_address          TEXT PRIMARY KEY,
+    transaction_signature TEXT NOT NULL,
+    slot                  BIGINT NOT NULL,
+    program_id            TEXT NOT NULL,
+    created_at            TIMESTAMPTZ NOT NULL,
+    name                  TEXT NOT NULL,
+    symbol                TEXT NOT NULL,
+    uri                   TEXT NOT NULL,
+    creator               TEXT NOT NULL,
+    bonding_curve         TEXT NOT NULL,
+    supply                NUMERIC(20, 0) NOT NULL,
+    decimals              SMALLINT NOT NULL,
+    price_sol             DOUBLE PRECISION NOT NULL,
+    market_cap_sol        DOUBLE PRECISION NOT NULL,
+    market_cap_usd        DOUBLE PRECISION,
+    initial_buy_lamports  NUMERIC(20, 0),
+    same_tx_buyers        BIGINT NOT NULL,
+    creator_tokens_seen   BIGINT,
+    similar_recent_count  BIGINT,
+    -- the event exactly as WebSocket clients receive it
+    event                 JSONB NOT NULL,
+    inserted_at           TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS token_events_creator_idx ON token_events (creator);
+CREATE INDEX IF NOT EXISTS token_events_created_at_idx ON token_events (created_at);
@@ -0,0 +1,6 @@
+-- Events processed at DETAIL_LEVEL=minimal come without the mint and bonding curve accounts.
+ALTER TABLE token_events
+    ALTER COLUMN supply DROP NOT NULL,
+    ALTER COLUMN decimals DROP NOT NULL,
+    ALTER COLUMN price_sol DROP NOT NULL,
+    ALTER COLUMN market_cap_sol DROP NOT NULL;
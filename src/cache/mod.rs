@@ -0,0 +1,264 @@
+//! # Bounded Caches
+//!
+//! Every in-memory cache the monitor keeps — recent signatures, broadcast mints, copycat names, creator stats, image
+//! hashes, social links, the token registry and the replay buffer — is a `BoundedCache`: a map bounded to a
+//! capacity, forgetting the entry inserted or refreshed longest ago first, and optionally to a TTL after which an entry
+//! counts as gone. Expired entries are dropped from the front whenever the cache is written to, so nothing outlives its
+//! TTL by more than one insert and nothing grows past its capacity however long the process runs.
+//!
+//! Each cache shares its size and eviction counts as `CacheStats`; registered with the metrics, they are exported as
+//! `pump_fun_monitor_cache_entries` and `pump_fun_monitor_cache_evictions_total`, and the janitor logs them every
+//! `CACHE_JANITOR_INTERVAL_SECS` at debug.
+
+use crate::metrics::Metrics;
+use std::borrow::Borrow;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// how many entries a cache keeps, and for how long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheLimits {
+    /// entries kept at most; a capacity of 0 is treated as 1
+    pub capacity: usize,
+    /// entries older than this count as gone; kept until evicted while `None`
+    pub ttl: Option<Duration>,
+}
+
+impl CacheLimits {
+    /// limits without a TTL.
+    pub fn capacity(capacity: usize) -> Self {
+        Self { capacity, ttl: None }
+    }
+}
+
+/// the size and eviction counts of one cache, shared with the metrics.
+#[derive(Debug, Default)]
+pub struct CacheStats {
+    entries: AtomicUsize,
+    /// entries dropped to make room for a newer one
+    evicted: AtomicU64,
+    /// entries dropped once their TTL was up
+    expired: AtomicU64,
+}
+
+impl CacheStats {
+    /// entries currently held, expired ones included until the next write drops them.
+    pub fn entries(&self) -> usize {
+        self.entries.load(Ordering::Relaxed)
+    }
+
+    pub fn evicted(&self) -> u64 {
+        self.evicted.load(Ordering::Relaxed)
+    }
+
+    pub fn expired(&self) -> u64 {
+        self.expired.load(Ordering::Relaxed)
+    }
+}
+
+/// an entry, with when it was inserted and the stamp of its place in the queue.
+struct Slot<V> {
+    value: V,
+    inserted_at: Instant,
+    stamp: u64,
+}
+
+/// Map bounded to a capacity and an optional TTL, forgetting the oldest entries first.
+///
+/// Not synchronized: owners keep it behind the lock they already hold. Refreshing an entry leaves its old place in
+/// the queue behind; such places are skipped when reached and the queue is compacted once they outnumber the entries.
+pub struct BoundedCache<K, V> {
+    limits: CacheLimits,
+    entries: HashMap<K, Slot<V>>,
+    /// keys oldest first, each with the stamp of the entry it was queued for
+    order: VecDeque<(K, u64)>,
+    next_stamp: u64,
+    stats: Arc<CacheStats>,
+}
+
+impl<K: Hash + Eq + Clone, V> BoundedCache<K, V> {
+    pub fn new(limits: CacheLimits) -> Self {
+        Self {
+            limits,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            next_stamp: 0,
+            stats: Arc::new(CacheStats::default()),
+        }
+    }
+
+    /// the counters to register with the metrics.
+    pub fn stats(&self) -> Arc<CacheStats> {
+        Arc::clone(&self.stats)
+    }
+
+    /// applies `limits` from the next insert on; nothing is forgotten before then.
+    pub fn set_limits(&mut self, limits: CacheLimits) {
+        self.limits = limits;
+    }
+
+    /// entries held, expired ones included until the next write drops them.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// the value under `key`, unless it expired by `now`.
+    pub fn get<Q>(&self, key: &Q, now: Instant) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries
+            .get(key)
+            .filter(|slot| !expired(slot, self.limits.ttl, now))
+            .map(|slot| &slot.value)
+    }
+
+    /// whether `key` is held and didn't expire by `now`.
+    pub fn contains_key<Q>(&self, key: &Q, now: Instant) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key, now).is_some()
+    }
+
+    /// takes the value under `key` out, expired or not.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let removed = self.entries.remove(key).map(|slot| slot.value);
+        self.stats.entries.store(self.entries.len(), Ordering::Relaxed);
+        removed
+    }
+
+    /// Inserts `value` under `key` at `now` as the newest entry, returning the value it replaced.
+    ///
+    /// Expired entries are dropped first, then the oldest ones until there is room.
+    pub fn insert(&mut self, key: K, value: V, now: Instant) -> Option<V> {
+        self.insert_evicting(key, value, now, |_, _| {})
+    }
+
+    /// `insert`, handing every entry dropped to make room or for having expired to `on_evict`.
+    pub fn insert_evicting(&mut self, key: K, value: V, now: Instant, mut on_evict: impl FnMut(K, V)) -> Option<V> {
+        self.prune_evicting(now, &mut on_evict);
+        let replaced = self.entries.remove(&key).map(|slot| slot.value);
+        // a lowered capacity can leave more than one entry to forget
+        while self.entries.len() >= self.limits.capacity.max(1) {
+            let Some((evicted, slot)) = self.pop_oldest() else {
+                break;
+            };
+            self.stats.evicted.fetch_add(1, Ordering::Relaxed);
+            on_evict(evicted, slot.value);
+        }
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        self.order.push_back((key.clone(), stamp));
+        self.entries.insert(key, Slot { value, inserted_at: now, stamp });
+        self.compact();
+        self.stats.entries.store(self.entries.len(), Ordering::Relaxed);
+        replaced
+    }
+
+    /// drops the entries expired by `now`, returning how many.
+    pub fn prune(&mut self, now: Instant) -> usize {
+        self.prune_evicting(now, &mut |_, _| {})
+    }
+
+    /// the held entries, oldest first; expired ones are included until pruned.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> {
+        self.order.iter().filter_map(move |(key, stamp)| {
+            self.entries
+                .get_key_value(key)
+                .filter(|(_, slot)| slot.stamp == *stamp)
+                .map(|(key, slot)| (key, &slot.value))
+        })
+    }
+
+    fn prune_evicting(&mut self, now: Instant, on_evict: &mut impl FnMut(K, V)) -> usize {
+        let ttl = self.limits.ttl;
+        if ttl.is_none() {
+            return 0;
+        }
+        let mut pruned = 0;
+        // entries are queued in insertion order, so the expired ones are all at the front
+        while self.oldest().is_some_and(|slot| expired(slot, ttl, now)) {
+            let Some((key, slot)) = self.pop_oldest() else {
+                break;
+            };
+            on_evict(key, slot.value);
+            pruned += 1;
+        }
+        if pruned > 0 {
+            self.stats.expired.fetch_add(pruned as u64, Ordering::Relaxed);
+            self.stats.entries.store(self.entries.len(), Ordering::Relaxed);
+        }
+        pruned
+    }
+
+    /// the oldest held entry, dropping the stale places in front of it.
+    fn oldest(&mut self) -> Option<&Slot<V>> {
+        while let Some((key, stamp)) = self.order.front() {
+            if self.entries.get(key).is_some_and(|slot| slot.stamp == *stamp) {
+                break;
+            }
+            self.order.pop_front();
+        }
+        let (key, _) = self.order.front()?;
+        self.entries.get(key)
+    }
+
+    fn pop_oldest(&mut self) -> Option<(K, Slot<V>)> {
+        while let Some((key, stamp)) = self.order.pop_front() {
+            if self.entries.get(&key).is_some_and(|slot| slot.stamp == stamp) {
+                return self.entries.remove_entry(&key);
+            }
+        }
+        None
+    }
+
+    /// drops the stale places once they make up most of the queue, keeping it within twice the entries.
+    fn compact(&mut self) {
+        if self.order.len() <= 2 * self.entries.len().max(16) {
+            return;
+        }
+        let entries = &self.entries;
+        self.order.retain(|(key, stamp)| entries.get(key).is_some_and(|slot| slot.stamp == *stamp));
+    }
+}
+
+/// whether `slot` is older than `ttl` at `now`.
+fn expired<V>(slot: &Slot<V>, ttl: Option<Duration>, now: Instant) -> bool {
+    ttl.is_some_and(|ttl| now.saturating_duration_since(slot.inserted_at) >= ttl)
+}
+
+/// Logs the size and evictions of every cache registered with `metrics` every `interval`, at debug.
+pub async fn run_janitor(metrics: Arc<Metrics>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        for (name, stats) in metrics.caches() {
+            debug!(
+                cache = name,
+                entries = stats.entries(),
+                evicted = stats.evicted(),
+                expired = stats.expired(),
+                "Cache size"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,125 @@
+//! Unit tests for the bounded cache.
+
+use super::*;
+
+/// function to build a cache holding at most `capacity` entries for `ttl_secs`, or without a TTL when 0.
+fn cache(capacity: usize, ttl_secs: u64) -> BoundedCache<u64, u64> {
+    BoundedCache::new(CacheLimits {
+        capacity,
+        ttl: (ttl_secs > 0).then_some(Duration::from_secs(ttl_secs)),
+    })
+}
+
+#[test]
+fn test_evicts_oldest_and_refresh_moves_to_back() {
+    let mut cache = cache(3, 0);
+    let now = Instant::now();
+    for key in 1..=3 {
+        assert_eq!(cache.insert(key, key * 10, now), None);
+    }
+    // refreshing 1 makes 2 the oldest
+    assert_eq!(cache.insert(1, 11, now), Some(10));
+    cache.insert(4, 40, now);
+
+    assert_eq!(cache.get(&2, now), None);
+    assert_eq!(cache.get(&1, now), Some(&11));
+    assert_eq!(cache.iter().map(|(key, _)| *key).collect::<Vec<_>>(), vec![3, 1, 4]);
+    assert_eq!(cache.iter().rev().map(|(key, _)| *key).collect::<Vec<_>>(), vec![4, 1, 3]);
+    assert_eq!(cache.stats().evicted(), 1);
+    assert_eq!(cache.stats().entries(), 3);
+}
+
+#[test]
+fn test_expired_entries_are_hidden_then_pruned() {
+    let mut cache = cache(10, 60);
+    let start = Instant::now();
+    cache.insert(1, 10, start);
+    cache.insert(2, 20, start + Duration::from_secs(30));
+
+    assert!(cache.contains_key(&1, start + Duration::from_secs(59)));
+    assert!(!cache.contains_key(&1, start + Duration::from_secs(60)));
+    // still held until something prunes it
+    assert_eq!(cache.len(), 2);
+
+    cache.insert(3, 30, start + Duration::from_secs(60));
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.prune(start + Duration::from_secs(90)), 1);
+    assert_eq!(cache.iter().map(|(key, _)| *key).collect::<Vec<_>>(), vec![3]);
+    assert_eq!(cache.stats().expired(), 2);
+    assert_eq!(cache.stats().evicted(), 0);
+}
+
+#[test]
+fn test_lowered_capacity_applies_on_next_insert() {
+    let mut cache = cache(5, 0);
+    let now = Instant::now();
+    for key in 0..5 {
+        cache.insert(key, key, now);
+    }
+    cache.set_limits(CacheLimits::capacity(2));
+    assert_eq!(cache.len(), 5);
+
+    let mut evicted = Vec::new();
+    cache.insert_evicting(5, 5, now, |key, _| evicted.push(key));
+    assert_eq!(evicted, vec![0, 1, 2, 3]);
+    assert_eq!(cache.iter().map(|(key, _)| *key).collect::<Vec<_>>(), vec![4, 5]);
+}
+
+#[test]
+fn test_millions_of_inserts_stay_within_capacity() {
+    let mut cache = cache(1_000, 0);
+    let now = Instant::now();
+    // a cheap LCG spreads the keys over a range many times the capacity, with plenty of repeats
+    let mut state: u64 = 42;
+    for _ in 0..2_000_000 {
+        state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+        let key = (state >> 33) % 50_000;
+        cache.insert(key, key, now);
+        assert!(cache.len() <= 1_000);
+        assert!(cache.order.len() <= 2 * cache.len().max(16) + 1);
+    }
+    assert_eq!(cache.len(), 1_000);
+    assert_eq!(cache.stats().entries(), 1_000);
+    assert!(cache.stats().evicted() > 1_000_000);
+}
+
+#[test]
+fn test_refreshing_the_same_keys_keeps_the_queue_bounded() {
+    let mut cache = cache(100_000, 0);
+    let now = Instant::now();
+    for round in 0..1_000_000u64 {
+        cache.insert(round % 8, round, now);
+    }
+    assert_eq!(cache.len(), 8);
+    assert!(cache.order.len() <= 2 * 16 + 1);
+    assert_eq!(cache.stats().evicted(), 0);
+}
+
+#[test]
+fn test_ttl_keeps_a_busy_cache_small() {
+    let mut cache = cache(1_000_000, 1);
+    let start = Instant::now();
+    // 100 inserts per simulated second for an hour; only the last second's are ever held
+    for tick in 0..360_000u64 {
+        cache.insert(tick, tick, start + Duration::from_millis(tick * 10));
+        assert!(cache.len() <= 100);
+    }
+    assert_eq!(cache.stats().expired(), 360_000 - 100);
+}
+
+#[test]
+fn test_evictions_are_exported_as_metrics() {
+    let metrics = Metrics::default();
+    let mut cache = cache(2, 0);
+    metrics.register_cache("mints", cache.stats());
+    let now = Instant::now();
+    for key in 0..5 {
+        cache.insert(key, key, now);
+    }
+
+    let text = metrics.render();
+    assert!(text.contains("# TYPE pump_fun_monitor_cache_entries gauge\npump_fun_monitor_cache_entries{cache=\"mints\"} 2\n"));
+    assert!(text.contains("pump_fun_monitor_cache_evictions_total{cache=\"mints\",reason=\"capacity\"} 3\n"));
+    assert!(text.contains("pump_fun_monitor_cache_evictions_total{cache=\"mints\",reason=\"ttl\"} 0\n"));
+    assert_eq!(metrics.caches().len(), 1);
+}
@@ -0,0 +1,444 @@
+//! # Command Line Interface
+//!
+//! Flags accepted by the binary. Values given here take precedence over environment variables, which in turn override the config file. `--dry-run` validates a deployment (config, RPC reachability, program account) without starting the monitor, `--reprocess-dead-letters` runs the transactions that failed processing through the parser again, `--convert-jsonl-to-csv` exports an events archive and `--dump-schema` prints the JSON Schema of the wire protocol, both without needing any configuration. `--daemonize`, `--pid-file` and `--log-file` run the monitor in the background on hosts without a service manager.
+//!
+//! The `export` and `import` subcommands move a time range of the events stored in the PostgreSQL database
+//! (`DATABASE_URL`) into a zstd-compressed archive and back, for sharing datasets between instances.
+
+use crate::client::{table_row, MonitorClient, TABLE_HEADER};
+use crate::config::Config;
+use crate::data_models::{EventPayload, FilterCriteria};
+use crate::dispatch::EventDispatcher;
+use crate::error::{MonitorError, Result};
+use crate::export::{self, HistoryStore};
+use crate::rpc_client::SolanaRpcMonitor;
+use crate::schema;
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand};
+use futures_util::StreamExt;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use crate::sinks;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio_tungstenite::connect_async;
+
+/// Real-time monitor for pump.fun token creations on Solana, streamed to WebSocket clients.
+///
+/// Configuration is layered: command line flags override environment variables (also read from `.env`), which override the config file.
+#[derive(Parser, Debug)]
+#[command(name = "pump_fun_monitor", version, about, long_about)]
+pub struct Cli {
+    /// Path to a TOML or YAML configuration file
+    #[arg(long, value_name = "PATH", env = "CONFIG_PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Port for the client-facing WebSocket server [overrides WEBSOCKET_SERVER_PORT]
+    #[arg(long, value_name = "PORT")]
+    pub ws_port: Option<u16>,
+
+    /// Solana HTTP RPC endpoint [overrides SOLANA_RPC_HTTP_URL]
+    #[arg(long, value_name = "URL")]
+    pub rpc_http: Option<String>,
+
+    /// Solana WebSocket RPC endpoint [overrides SOLANA_RPC_WSS_URL]
+    #[arg(long, value_name = "URL")]
+    pub rpc_wss: Option<String>,
+
+    /// Log filter, e.g. `info` or `pump_fun_monitor_corrected=debug` [overrides RUST_LOG]
+    #[arg(long, value_name = "FILTER")]
+    pub log_level: Option<String>,
+
+    /// Append logs to this file instead of stdout; SIGUSR1 opens it again, e.g. after logrotate moved it
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Detach from the terminal and keep running in the background (Unix only)
+    #[arg(long, requires = "log_file", conflicts_with_all = ["dry_run", "reprocess_dead_letters", "convert_jsonl_to_csv", "dump_schema", "watch"])]
+    pub daemonize: bool,
+
+    /// Write the process id to this file, refusing to start while it names a running process (Unix only)
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["dry_run", "reprocess_dead_letters", "convert_jsonl_to_csv", "dump_schema", "watch"])]
+    pub pid_file: Option<PathBuf>,
+
+    /// Load the configuration, check the RPC endpoints and program account, print a summary and exit
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Write every log notification, transaction and account seen to this directory [overrides RECORD_FIXTURES]
+    #[arg(long, value_name = "DIR", conflicts_with = "replay_fixtures")]
+    pub record_fixtures: Option<PathBuf>,
+
+    /// Replay a recorded directory instead of connecting to any RPC [overrides REPLAY_FIXTURES]
+    #[arg(long, value_name = "DIR")]
+    pub replay_fixtures: Option<PathBuf>,
+
+    /// Replay speed: 1 keeps the recorded timing, 0 replays as fast as possible [overrides REPLAY_SPEED]
+    #[arg(long, value_name = "FACTOR")]
+    pub replay_speed: Option<f64>,
+
+    /// Emit synthetic token creations at this rate instead of monitoring Solana [overrides SIMULATION_MODE and SIMULATION_EVENTS_PER_SEC]
+    #[arg(long, value_name = "EVENTS_PER_SECOND", conflicts_with_all = ["record_fixtures", "replay_fixtures"])]
+    pub simulate: Option<f64>,
+
+    /// Seed for simulated events, for reproducible runs [overrides SIMULATION_SEED]
+    #[arg(long, value_name = "SEED")]
+    pub simulation_seed: Option<u64>,
+
+    /// Write every active bonding curve to CURVE_SNAPSHOT_FILE before monitoring starts [overrides CURVE_SNAPSHOT]
+    #[arg(long, conflicts_with_all = ["simulate", "replay_fixtures"])]
+    pub snapshot: bool,
+
+    /// Run the transactions in DEAD_LETTER_DIR through the parser again, print the tokens they now produce as JSON lines and exit
+    #[arg(long, conflicts_with_all = ["dry_run", "simulate", "replay_fixtures"])]
+    pub reprocess_dead_letters: bool,
+
+    /// Convert an events archive (see EVENTS_LOG_PATH) to CSV on stdout and exit
+    #[arg(long, value_name = "JSONL")]
+    pub convert_jsonl_to_csv: Option<PathBuf>,
+
+    /// Print the JSON Schema of every event and message on the wire and exit; with a directory, write one file per type there
+    #[arg(long, value_name = "DIR", num_args = 0..=1)]
+    pub dump_schema: Option<Option<PathBuf>>,
+
+    /// Connect to a running monitor's WebSocket server, e.g. ws://localhost:8080, and print its events as a table
+    #[arg(long, value_name = "URL", conflicts_with_all = ["dry_run", "reprocess_dead_letters", "convert_jsonl_to_csv"])]
+    pub watch: Option<String>,
+
+    /// With --watch, only tokens by this creator
+    #[arg(long, value_name = "ADDRESS", requires = "watch")]
+    pub creator: Option<String>,
+
+    /// With --watch, only tokens with this symbol
+    #[arg(long, value_name = "SYMBOL", requires = "watch")]
+    pub symbol: Option<String>,
+
+    /// With --watch, only tokens whose name contains this text
+    #[arg(long, value_name = "TEXT", requires = "watch")]
+    pub name_contains: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// One-off jobs run against the configured database instead of the monitor.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Write the events created in a time range to a zstd-compressed JSON lines file, with a manifest next to it
+    Export {
+        /// Oldest creation time to export, in RFC 3339, e.g. 2024-05-01T00:00:00Z
+        #[arg(long, value_name = "TIMESTAMP")]
+        from: DateTime<Utc>,
+
+        /// Export the events created before this time
+        #[arg(long, value_name = "TIMESTAMP")]
+        to: DateTime<Utc>,
+
+        /// File to write, e.g. events.jsonl.zst; the manifest goes to the same path with `.manifest.json` added
+        #[arg(long, value_name = "PATH")]
+        out: PathBuf,
+    },
+    /// Insert the events of an exported file into the database, skipping the ones already stored
+    Import {
+        /// File written by `export`, with its manifest next to it
+        #[arg(value_name = "PATH")]
+        input: PathBuf,
+    },
+}
+
+impl Cli {
+    /// the filter given with `--creator`, `--symbol` and `--name-contains`.
+    pub fn watch_filter(&self) -> FilterCriteria {
+        FilterCriteria {
+            creator: self.creator.clone(),
+            symbol: self.symbol.clone(),
+            name_contains: self.name_contains.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// flag values keyed by the environment variable they override.
+    pub fn config_overrides(&self) -> HashMap<&'static str, String> {
+        let mut overrides = HashMap::new();
+        if let Some(port) = self.ws_port {
+            overrides.insert("WEBSOCKET_SERVER_PORT", port.to_string());
+        }
+        if let Some(url) = &self.rpc_http {
+            overrides.insert("SOLANA_RPC_HTTP_URL", url.clone());
+        }
+        if let Some(url) = &self.rpc_wss {
+            overrides.insert("SOLANA_RPC_WSS_URL", url.clone());
+        }
+        if let Some(dir) = &self.record_fixtures {
+            overrides.insert("RECORD_FIXTURES", dir.display().to_string());
+        }
+        if let Some(dir) = &self.replay_fixtures {
+            overrides.insert("REPLAY_FIXTURES", dir.display().to_string());
+        }
+        if let Some(speed) = self.replay_speed {
+            overrides.insert("REPLAY_SPEED", speed.to_string());
+        }
+        if let Some(rate) = self.simulate {
+            overrides.insert("SIMULATION_MODE", "true".to_string());
+            overrides.insert("SIMULATION_EVENTS_PER_SEC", rate.to_string());
+        }
+        if let Some(seed) = self.simulation_seed {
+            overrides.insert("SIMULATION_SEED", seed.to_string());
+        }
+        if self.snapshot {
+            overrides.insert("CURVE_SNAPSHOT", "true".to_string());
+        }
+        if let Some(level) = &self.log_level {
+            overrides.insert("RUST_LOG", level.clone());
+        }
+        overrides
+    }
+}
+
+/// Prints the events of the monitor at `url` that match `filter` as a table on stdout, until Ctrl+C.
+///
+/// Returns `false` when the first connection or the filter failed; later disconnections are retried.
+pub async fn watch(url: &str, filter: FilterCriteria) -> bool {
+    let mut client = match MonitorClient::connect(url).await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to {}: {}", url, e);
+            return false;
+        }
+    };
+    if let Err(e) = client.set_filter(filter) {
+        eprintln!("{}", e);
+        return false;
+    }
+    println!("{}", TABLE_HEADER);
+    loop {
+        tokio::select! {
+            event = client.next() => match event {
+                Some(event) => println!("{}", table_row(&event)),
+                None => return true,
+            },
+            _ = tokio::signal::ctrl_c() => return true,
+        }
+    }
+}
+
+/// Converts the events archive at `path` to CSV on stdout, reporting the row count on stderr.
+///
+/// Returns `true` when the whole file was read.
+pub fn convert_jsonl_to_csv(path: &Path) -> bool {
+    let input = match File::open(path) {
+        Ok(file) => BufReader::new(file),
+        Err(e) => {
+            eprintln!("Failed to open {}: {}", path.display(), e);
+            return false;
+        }
+    };
+    match sinks::convert_jsonl_to_csv(input, io::stdout().lock()) {
+        Ok(export) => {
+            eprintln!("Converted {} events ({} unreadable lines skipped)", export.rows, export.skipped);
+            true
+        }
+        Err(e) => {
+            eprintln!("Failed to convert {}: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+/// Prints the wire schema on stdout, or writes it into `dir`.
+///
+/// Returns `true` when everything was written.
+pub fn dump_schema(dir: Option<&Path>) -> bool {
+    match schema::dump_schema(dir) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("Failed to write the schema: {}", e);
+            false
+        }
+    }
+}
+
+/// Runs an `export` or `import` against the configured database, reporting the outcome on stderr.
+///
+/// Returns `true` when the command completed.
+pub async fn run_command(command: &Command, config: &Config) -> bool {
+    let store = match history_store(config).await {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    match command {
+        Command::Export { from, to, out } => match export::export(store.as_ref(), *from, *to, out).await {
+            Ok(manifest) => {
+                eprintln!(
+                    "Exported {} events to {}, manifest in {}",
+                    manifest.events,
+                    out.display(),
+                    export::manifest_path(out).display()
+                );
+                true
+            }
+            Err(e) => {
+                eprintln!("Failed to export: {}", e);
+                false
+            }
+        },
+        Command::Import { input } => match export::import(store.as_ref(), input).await {
+            Ok(summary) => {
+                eprintln!(
+                    "Imported {} events from {} ({} already stored)",
+                    summary.inserted,
+                    input.display(),
+                    summary.read - summary.inserted
+                );
+                true
+            }
+            Err(e) => {
+                eprintln!("Failed to import: {}", e);
+                false
+            }
+        },
+    }
+}
+
+/// the database `export` and `import` work on: the PostgreSQL sink's.
+#[cfg_attr(not(feature = "postgres"), allow(unused_variables))]
+async fn history_store(config: &Config) -> Result<Box<dyn HistoryStore>> {
+    #[cfg(feature = "postgres")]
+    if let Some(postgres) = &config.sinks.postgres {
+        return match sinks::PostgresArchive::connect(postgres).await {
+            Ok(archive) => Ok(Box::new(archive)),
+            Err(e) => Err(MonitorError::Archive(format!(
+                "failed to open PostgreSQL at {}: {}",
+                crate::websocket_server::redact_endpoint(&postgres.url),
+                e
+            ))),
+        };
+    }
+    Err(MonitorError::Config("export and import need DATABASE_URL set to a postgres:// URL".to_string()))
+}
+
+/// Runs the dead letters through the current parser, printing every token they now produce as a JSON line in the
+/// `EVENT_FORMAT` on stdout and a summary on stderr. Letters that went through are removed.
+///
+/// Returns `true` when no letter failed again.
+pub async fn reprocess_dead_letters(config: &Config) -> bool {
+    // nothing is broadcast, so the events go nowhere but stdout
+    let monitor = match SolanaRpcMonitor::new(config, EventDispatcher::new(tokio::sync::broadcast::channel(1).0)) {
+        Ok(monitor) => monitor,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    let reprocessed = match monitor.reprocess_dead_letters().await {
+        Ok(reprocessed) => reprocessed,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    for event in &reprocessed.events {
+        match serde_json::to_string(&event.formatted(config.server.event_format)) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Failed to serialize {}: {}", event.transaction_signature, e),
+        }
+    }
+    eprintln!(
+        "Reprocessed dead letters: {} tokens, {} not token creations, {} failed again",
+        reprocessed.events.len(),
+        reprocessed.ignored,
+        reprocessed.failed
+    );
+    reprocessed.failed == 0
+}
+
+/// Checks that the configured endpoints are reachable and the program account exists, printing one line per check.
+///
+/// Returns `true` when every required check passed.
+pub async fn dry_run(config: &Config) -> bool {
+    println!("Configuration loaded");
+    println!("  HTTP RPC:          {} ({})", config.rpc.http_url, config.rpc.http_label);
+    for endpoint in &config.rpc.wss_endpoints {
+        println!("  WSS RPC:           {} ({})", endpoint.url, endpoint.label);
+    }
+    for program in &config.rpc.programs {
+        println!("  Program ID:        {}", program.id);
+    }
+    println!("  WebSocket server:  {}", config.server.bind_addr);
+    println!();
+
+    // neither a replay nor a simulation connects to an RPC, so there is nothing further to check
+    if let Some(simulation) = &config.simulation {
+        println!("[ok]   Simulating {} events per second (no RPC checks)", simulation.events_per_sec);
+        println!();
+        println!("Dry run passed");
+        return true;
+    }
+    if let Some(replay) = &config.fixtures.replay {
+        println!("[ok]   Replaying fixtures from {} (no RPC checks)", replay.dir.display());
+        println!();
+        println!("Dry run passed");
+        return true;
+    }
+
+    let mut ok = true;
+    let rpc_client = RpcClient::new_with_timeout_and_commitment(
+        config.rpc.http_url.clone(),
+        config.rpc.request_timeout,
+        config.rpc.commitment.commitment_config(),
+    );
+
+    match rpc_client.get_version().await {
+        Ok(version) => println!("[ok]   HTTP RPC reachable (solana-core {})", version.solana_core),
+        Err(e) => {
+            ok = false;
+            println!("[fail] HTTP RPC unreachable: {}", e);
+        }
+    }
+
+    // not every provider implements getHealth, so a failure here is only a warning
+    match rpc_client.get_health().await {
+        Ok(()) => println!("[ok]   HTTP RPC reports healthy"),
+        Err(e) => println!("[warn] HTTP RPC health check failed: {}", e),
+    }
+
+    for endpoint in &config.rpc.wss_endpoints {
+        match tokio::time::timeout(Duration::from_secs(10), connect_async(&endpoint.url)).await {
+            Ok(Ok((mut ws_stream, _))) => {
+                let _ = ws_stream.close(None).await;
+                println!("[ok]   WSS RPC {} accepted a connection", endpoint.label);
+            }
+            Ok(Err(e)) => {
+                ok = false;
+                println!("[fail] WSS RPC {} connection failed: {}", endpoint.label, e);
+            }
+            Err(_) => {
+                ok = false;
+                println!("[fail] WSS RPC {} connection timed out", endpoint.label);
+            }
+        }
+    }
+
+    for program in &config.rpc.programs {
+        match rpc_client.get_account(&program.id).await {
+            Ok(account) if account.executable => println!("[ok]   Program account {} exists and is executable", program.id),
+            Ok(_) => {
+                ok = false;
+                println!("[fail] Program account {} exists but is not executable", program.id);
+            }
+            Err(e) => {
+                ok = false;
+                println!("[fail] Program account {} lookup failed: {}", program.id, e);
+            }
+        }
+    }
+
+    println!();
+    println!("{}", if ok { "Dry run passed" } else { "Dry run failed" });
+    ok
+}
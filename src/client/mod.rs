@@ -0,0 +1,250 @@
+//! # Client
+//!
+//! A client for this service's own WebSocket server, for consumers that would otherwise write the same reconnect and
+//! parse loop again. [`MonitorClient`] is a `Stream` of token events:
+//!
+//! ```no_run
+//! use futures_util::StreamExt;
+//! use pump_fun_monitor_corrected::client::MonitorClient;
+//! use pump_fun_monitor_corrected::data_models::FilterCriteria;
+//!
+//! # async fn watch() -> pump_fun_monitor_corrected::error::Result<()> {
+//! let mut client = MonitorClient::connect("ws://localhost:8080").await?;
+//! client.set_filter(FilterCriteria { symbol: Some("PEPE".to_string()), ..Default::default() })?;
+//! while let Some(event) = client.next().await {
+//!     println!("{} ({})", event.token.name, event.token.symbol);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! `examples/watch_symbol.rs` is the same loop as a program to run against a live server.
+//!
+//! A dropped connection is dialled again with exponential backoff. The filter is sent again on every new connection,
+//! followed by a `resumeFrom` for the sequence after the last event received, so a short outage loses nothing the
+//! server still buffers; replayed events can arrive after the first live ones, so order by `sequence` where it
+//! matters. Frames are read in either `EVENT_FORMAT`, and everything that isn't a token event is skipped, as is an event
+//! sent before the server applied a new filter that doesn't match it.
+//!
+//! `--watch` runs this client from the command line, printing events as a table.
+
+use crate::config::ReconnectPolicy;
+use crate::data_models::{event_payload, FilterCriteria, TokenCreatedEvent};
+use crate::error::{MonitorError, Result};
+use crate::filtering::{normalize_filter, CompiledFilter, MatchableEvent};
+use futures_util::{SinkExt, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, watch};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tracing::{info, warn};
+
+/// events received but not yet taken from the stream; beyond it the connection isn't read until the consumer catches up.
+const EVENT_BUFFER: usize = 1024;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// the backoff used by `MonitorClient::connect`.
+pub fn default_reconnect_policy() -> ReconnectPolicy {
+    ReconnectPolicy {
+        initial_delay: Duration::from_secs(1),
+        max_delay: Duration::from_secs(30),
+        multiplier: 2.0,
+    }
+}
+
+/// Stream of the token events a monitor's WebSocket server sends, kept connected in the background.
+///
+/// The connection task stops once the client is dropped.
+pub struct MonitorClient {
+    filter: watch::Sender<FilterCriteria>,
+    events: mpsc::Receiver<TokenCreatedEvent>,
+}
+
+impl MonitorClient {
+    /// Connects to the server at `url`, e.g. `ws://localhost:8080`.
+    ///
+    /// Only the first connection has to succeed; after that the client reconnects on its own.
+    pub async fn connect(url: &str) -> Result<Self> {
+        Self::connect_with(url, default_reconnect_policy()).await
+    }
+
+    /// Connects like `connect`, reconnecting with `reconnect` instead of the default backoff.
+    pub async fn connect_with(url: &str, reconnect: ReconnectPolicy) -> Result<Self> {
+        let (socket, _) = connect_async(url).await?;
+        let (filter, filter_rx) = watch::channel(FilterCriteria::default());
+        let (events_tx, events) = mpsc::channel(EVENT_BUFFER);
+        tokio::spawn(run(url.to_string(), socket, filter_rx, events_tx, reconnect));
+        Ok(Self { filter, events })
+    }
+
+    /// Replaces the filter the server applies to this client, now and on every reconnection.
+    ///
+    /// The filter is checked the way the server checks it, so a filter the server would reject is refused here.
+    pub fn set_filter(&self, filter: FilterCriteria) -> Result<()> {
+        let filter = normalize_filter(filter).map_err(MonitorError::InvalidFilter)?;
+        self.filter.send_replace(filter);
+        Ok(())
+    }
+}
+
+impl Stream for MonitorClient {
+    type Item = TokenCreatedEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<TokenCreatedEvent>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+/// why `follow` returned.
+enum Closed {
+    /// the client was dropped
+    Consumer,
+    /// the connection failed or the server closed it
+    Connection(String),
+}
+
+/// keeps a connection to `url` for as long as someone receives `events`.
+async fn run(
+    url: String,
+    mut socket: Socket,
+    mut filter: watch::Receiver<FilterCriteria>,
+    events: mpsc::Sender<TokenCreatedEvent>,
+    reconnect: ReconnectPolicy,
+) {
+    let mut last_sequence = None;
+    loop {
+        match follow(&mut socket, &mut filter, &events, &mut last_sequence).await {
+            Closed::Consumer => {
+                let _ = socket.close(None).await;
+                return;
+            }
+            Closed::Connection(reason) => warn!("Connection to {} lost: {}", url, reason),
+        }
+
+        let mut delay = reconnect.initial_delay;
+        socket = loop {
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = events.closed() => return,
+            }
+            match connect_async(&url).await {
+                Ok((socket, _)) => break socket,
+                Err(e) => warn!("Reconnecting to {} failed, retrying in {:?}: {}", url, delay, e),
+            }
+            delay = reconnect.next_delay(delay);
+        };
+        info!("Reconnected to {}", url);
+    }
+}
+
+/// sets up a fresh connection and forwards its token events until it closes or the client is dropped.
+async fn follow(
+    socket: &mut Socket,
+    filter: &mut watch::Receiver<FilterCriteria>,
+    events: &mpsc::Sender<TokenCreatedEvent>,
+    last_sequence: &mut Option<u64>,
+) -> Closed {
+    let mut compiled = CompiledFilter::from(&*filter.borrow_and_update());
+    if let Err(e) = socket.send(set_filter_request(compiled.criteria())).await {
+        return Closed::Connection(e.to_string());
+    }
+    if let Some(sequence) = *last_sequence {
+        let resume = json!({ "action": "resumeFrom", "sequence": sequence + 1 });
+        if let Err(e) = socket.send(Message::Text(resume.to_string())).await {
+            return Closed::Connection(e.to_string());
+        }
+    }
+
+    loop {
+        tokio::select! {
+            frame = socket.next() => {
+                let text = match frame {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return Closed::Connection("closed by the server".to_string()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Closed::Connection(e.to_string()),
+                };
+                let Some(event) = parse_token_event(&text) else {
+                    continue;
+                };
+                *last_sequence = (*last_sequence).max(Some(event.sequence));
+                if !compiled.matches(&MatchableEvent::new(&event)) {
+                    continue;
+                }
+                if events.send(event).await.is_err() {
+                    return Closed::Consumer;
+                }
+            }
+            changed = filter.changed() => {
+                if changed.is_err() {
+                    return Closed::Consumer;
+                }
+                compiled = CompiledFilter::from(&*filter.borrow_and_update());
+                if let Err(e) = socket.send(set_filter_request(compiled.criteria())).await {
+                    return Closed::Connection(e.to_string());
+                }
+            }
+        }
+    }
+}
+
+fn set_filter_request(filter: &FilterCriteria) -> Message {
+    Message::Text(json!({ "action": "setFilter", "filter": filter }).to_string())
+}
+
+/// the token event in a text frame, flat or enveloped; `None` for notices, replies and other event types.
+pub fn parse_token_event(text: &str) -> Option<TokenCreatedEvent> {
+    let frame: Value = serde_json::from_str(text).ok()?;
+    let payload = event_payload(&frame);
+    match payload.get("eventType").and_then(Value::as_str) {
+        Some("tokenCreated") => match TokenCreatedEvent::deserialize(payload) {
+            Ok(event) => Some(event),
+            Err(e) => {
+                warn!("Skipping a token event that doesn't parse: {}", e);
+                None
+            }
+        },
+        Some("error") => {
+            warn!("The server rejected a request: {}", payload["message"]);
+            None
+        }
+        _ => None,
+    }
+}
+
+/// column titles of `table_row`.
+pub const TABLE_HEADER: &str = "TIME      SYMBOL      NAME                      MCAP SOL  MINT                                          CREATOR";
+
+/// one event as a row of the `--watch` table; long names and symbols are cut short so the columns stay aligned.
+pub fn table_row(event: &TokenCreatedEvent) -> String {
+    // servers at `DETAIL_LEVEL=minimal` send no market cap
+    let market_cap = event.pump_data.market_cap_sol.map_or_else(|| "-".to_string(), |cap| format!("{:.2}", cap));
+    format!(
+        "{:<8}  {:<10}  {:<24}  {:>8}  {:<44}  {}",
+        event.timestamp.format("%H:%M:%S"),
+        truncated(&event.token.symbol, 10),
+        truncated(&event.token.name, 24),
+        market_cap,
+        event.token.mint_address,
+        event.token.creator
+    )
+}
+
+/// `text` cut to `width` characters, ending in `…` when it was cut.
+fn truncated(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width - 1).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,184 @@
+//! Unit tests for the WebSocket client: frame parsing, the table rows, reconnection, and a run against the real server.
+
+
+use super::*;
+use crate::config::{IpLimitConfig, ListenAddr, ServerConfig};
+use crate::data_models::{EventFormat, EventPayload};
+use crate::freshness::StalePolicy;
+use crate::metrics::Metrics;
+use crate::simulation::EventGenerator;
+use crate::websocket_server::WebSocketServer;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+
+/// function to create a simulated token event with the given name and sequence.
+fn event(name: &str, sequence: u64) -> TokenCreatedEvent {
+    let mut event = EventGenerator::new(sequence).next_event();
+    event.token.name = name.to_string();
+    event.sequence = sequence;
+    event
+}
+
+/// function to take the next event off the client, failing the test if none arrives in time.
+async fn next_event(client: &mut MonitorClient) -> TokenCreatedEvent {
+    tokio::time::timeout(Duration::from_secs(5), client.next())
+        .await
+        .expect("no event arrived")
+        .expect("client stopped")
+}
+
+/// function to read the next text frame a fake server receives, as JSON.
+async fn next_request(socket: &mut WebSocketStream<TcpStream>) -> Value {
+    loop {
+        match socket.next().await {
+            Some(Ok(Message::Text(text))) => return serde_json::from_str(&text).unwrap(),
+            Some(Ok(_)) => continue,
+            other => panic!("expected a request, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_token_events_are_parsed_in_both_formats() {
+    let event = event("Pepe", 3);
+    let flat = serde_json::to_string(&event).unwrap();
+    let enveloped = serde_json::to_string(&event.formatted(EventFormat::Envelope)).unwrap();
+    assert_eq!(parse_token_event(&flat).unwrap().token.name, "Pepe");
+    assert_eq!(parse_token_event(&enveloped).unwrap().sequence, 3);
+
+    assert!(parse_token_event(r#"{"eventType":"heartbeat","timestamp":"2024-05-01T12:00:00Z","rpcConnected":true}"#).is_none());
+    assert!(parse_token_event(r#"{"eventType":"tokenCreated","token":{}}"#).is_none());
+    assert!(parse_token_event("not json").is_none());
+}
+
+#[test]
+fn test_table_rows_stay_aligned() {
+    let mut event = event("Pepe", 1);
+    event.token.symbol = "PEPE".to_string();
+    let row = table_row(&event);
+    assert_eq!(row.find("PEPE"), TABLE_HEADER.find("SYMBOL"));
+    assert_eq!(row.find(&event.token.mint_address), TABLE_HEADER.find("MINT"));
+    assert_eq!(row.find(&event.token.creator), TABLE_HEADER.find("CREATOR"));
+    // without a market cap the column is kept
+    event.pump_data.market_cap_sol = None;
+    assert_eq!(table_row(&event).find(&event.token.mint_address), TABLE_HEADER.find("MINT"));
+
+    assert_eq!(truncated("A name far too long for its column", 24), "A name far too long for…");
+    assert_eq!(truncated("Pepe", 24), "Pepe");
+}
+
+#[tokio::test]
+async fn test_invalid_filter_is_refused_before_sending() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let _socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+        std::future::pending::<()>().await;
+    });
+    let client = MonitorClient::connect(&format!("ws://{}", addr)).await.unwrap();
+    let filter = FilterCriteria { creator: Some("not-a-key".to_string()), ..Default::default() };
+    let error = client.set_filter(filter).unwrap_err();
+    assert_eq!(error.code(), "invalid_filter");
+}
+
+#[tokio::test]
+async fn test_reconnects_with_filter_and_resume() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let reconnect = ReconnectPolicy {
+        initial_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(50),
+        multiplier: 2.0,
+    };
+    // the first connection's handshake has to be answered before `connect_with` returns
+    let accept = async {
+        let (stream, _) = listener.accept().await.unwrap();
+        tokio_tungstenite::accept_async(stream).await.unwrap()
+    };
+    let url = format!("ws://{}", addr);
+    let (client, mut socket) = tokio::join!(MonitorClient::connect_with(&url, reconnect), accept);
+    let mut client = client.unwrap();
+    client.set_filter(FilterCriteria { symbol: Some("PEPE".to_string()), ..Default::default() }).unwrap();
+
+    // the default filter may go out before the one just set
+    let mut request = next_request(&mut socket).await;
+    while request["filter"]["symbol"] != "PEPE" {
+        request = next_request(&mut socket).await;
+    }
+    let mut first = event("Pepe", 5);
+    first.token.symbol = "PEPE".to_string();
+    socket.send(Message::Text(serde_json::to_string(&first).unwrap())).await.unwrap();
+    assert_eq!(next_event(&mut client).await.sequence, 5);
+    drop(socket);
+
+    // the new connection gets the filter again, then asks for what it missed
+    let (stream, _) = listener.accept().await.unwrap();
+    let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+    let request = next_request(&mut socket).await;
+    assert_eq!(request["action"], "setFilter");
+    assert_eq!(request["filter"]["symbol"], "PEPE");
+    assert_eq!(next_request(&mut socket).await, json!({ "action": "resumeFrom", "sequence": 6 }));
+
+    // an event the server sent under an older filter is skipped
+    let mut other = event("Doge", 6);
+    other.token.symbol = "DOGE".to_string();
+    let mut second = event("Pepe Again", 7);
+    second.token.symbol = "PEPE".to_string();
+    for event in [&other, &second] {
+        socket.send(Message::Text(serde_json::to_string(&event.envelope()).unwrap())).await.unwrap();
+    }
+    assert_eq!(next_event(&mut client).await.token.name, "Pepe Again");
+}
+
+#[tokio::test]
+async fn test_client_against_the_websocket_server() {
+    let config = ServerConfig {
+        bind_addr: ListenAddr::Tcp("127.0.0.1:0".parse().unwrap()),
+        tls: None,
+        compression: false,
+        admin_token: None,
+        max_clients: 4,
+        ip_limits: IpLimitConfig {
+            max_connections_per_ip: 4,
+            connections_per_sec: 100.0,
+            burst: 100.0,
+        },
+        origins: Default::default(),
+        presets: Arc::default(),
+        replay_capacity: 100,
+        client_queue_capacity: 1_000,
+        raw_transactions: false,
+        heartbeat_interval: None,
+        sessions: None,
+        broadcast_shards: 1,
+        stale_events: StalePolicy::Tag,
+        event_format: EventFormat::Envelope,
+    };
+    let server = WebSocketServer::bind(&config).await.unwrap();
+    let addr = server.local_addr().tcp().unwrap();
+    let (tx, rx) = broadcast::channel(16);
+    tokio::spawn(async move {
+        let _ = server.run(rx, Arc::new(Metrics::default())).await;
+    });
+
+    let mut client = MonitorClient::connect(&format!("ws://{}", addr)).await.unwrap();
+    client.set_filter(FilterCriteria { name_contains: Some("pepe".to_string()), ..Default::default() }).unwrap();
+    // keep sending until the filter is in place and a match comes through
+    let received = tokio::time::timeout(Duration::from_secs(5), async {
+        let mut sequence = 0;
+        loop {
+            sequence += 2;
+            let _ = tx.send(event("Doge", sequence - 1));
+            let _ = tx.send(event("Pepe", sequence));
+            tokio::select! {
+                event = client.next() => return event.unwrap(),
+                _ = tokio::time::sleep(Duration::from_millis(50)) => {}
+            }
+        }
+    })
+    .await
+    .expect("no matching event arrived");
+    assert_eq!(received.token.name, "Pepe");
+}
@@ -0,0 +1,2270 @@
+//! # Configuration
+//!
+//! Loads the service configuration from an optional TOML or YAML file (passed with `--config path` or `CONFIG_PATH`) and lets environment variables, and then command line flags, override individual values. Every problem found is collected and reported together, so a broken deployment can be fixed in one pass instead of one panic at a time.
+//!
+//! ```toml
+//! [rpc]
+//! http_url = "https://api.mainnet-beta.solana.com"
+//! wss_url = "wss://api.mainnet-beta.solana.com"
+//!
+//! [server]
+//! port = 8080
+//!
+//! [reconnect]
+//! initial_delay_ms = 5000
+//! max_delay_ms = 60000
+//! ```
+
+use crate::error::{MonitorError, Result};
+use crate::price_feed::{PriceFeedConfig, PriceSource, DEFAULT_PYTH_SOL_USD_ACCOUNT};
+use crate::rpc_client::{Commitment, DetailLevel, ProgramConfig, SpamSuppression};
+use crate::data_models::{EventFormat, FilterCriteria};
+use crate::dispatch::{OverflowPolicy, Tier};
+use crate::filtering::normalize_filter;
+use crate::metadata::DEFAULT_IPFS_GATEWAY;
+use crate::simulation::{BurstConfig, SimulationConfig};
+use crate::signing::EventSigner;
+use crate::watchlist::Watchlist;
+use crate::websocket_server::{AllowedOrigin, OriginPolicy};
+use crate::freshness::StalePolicy;
+use crate::global_filter::{CreatorBlocklist, GlobalFilter, Pattern};
+use crate::sinks::{
+    DiscordConfig, DiscordRoute, EventsLogConfig, FsyncPolicy, KafkaConfig, LogRotation, NatsConfig, PostgresConfig, SinksConfig,
+    TelegramConfig, TelegramRoute,
+};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{BTreeMap, HashMap};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{env, fs};
+use url::Url;
+
+/// pump.fun program address used when none is configured.
+pub const DEFAULT_PUMP_FUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+
+/// label of the endpoint given by `SOLANA_RPC_WSS_URL`.
+pub const DEFAULT_WSS_ENDPOINT_LABEL: &str = "primary";
+
+/// label of the `SOLANA_RPC_HTTP_URL` endpoint, unless `SOLANA_RPC_HTTP_LABEL` names it.
+pub const DEFAULT_HTTP_ENDPOINT_LABEL: &str = "http";
+
+/// heartbeat interval for clients, unless configured otherwise or chosen by the client.
+pub const DEFAULT_HEARTBEAT_SECS: u64 = 30;
+
+/// shortest and longest heartbeat interval the server or a client may choose.
+pub const MIN_HEARTBEAT_SECS: u64 = 5;
+pub const MAX_HEARTBEAT_SECS: u64 = 300;
+
+/// how long a client may take over the TLS handshake, unless configured otherwise.
+pub const DEFAULT_TLS_HANDSHAKE_TIMEOUT_SECS: u64 = 10;
+
+/// how long a disconnected client may resume its session, unless configured otherwise.
+pub const DEFAULT_SESSION_TTL_SECS: u64 = 60;
+
+/// most signatures a single `getSignaturesForAddress` call returns.
+const MAX_LOOKBACK_SIGNATURES: usize = 1_000;
+
+/// longest a launch may wait for its migration before it counts as abandoned: a year.
+const MAX_ABANDONED_AFTER_SECS: u64 = 365 * 24 * 60 * 60;
+
+/// most rows in one Postgres insert; each takes 20 of the 65535 bind parameters a statement may have.
+const MAX_DATABASE_BATCH_SIZE: usize = 1_000;
+
+/// Fully validated service configuration.
+///
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub rpc: RpcConfig,
+    pub server: ServerConfig,
+    pub channels: ChannelConfig,
+    pub reconnect: ReconnectPolicy,
+    pub watchdog: WatchdogConfig,
+    pub supervisor: SupervisorConfig,
+    pub mint_dedupe: MintDedupeConfig,
+    pub caches: CacheConfig,
+    pub health: Option<HealthConfig>,
+    pub price_feed: Option<PriceFeedConfig>,
+    pub sinks: SinksConfig,
+    pub grpc: Option<GrpcConfig>,
+    pub http_api: Option<HttpApiConfig>,
+    pub metadata: MetadataConfig,
+    /// per-creator launch counts on events, present only when `CREATOR_HISTORY` is on
+    pub creator_history: Option<CreatorHistoryConfig>,
+    /// recent transactions parsed to catch changed discriminators, present only when `PARSER_SELF_TEST` is on
+    pub parser_self_test: Option<ParserSelfTestConfig>,
+    /// copycat detection before broadcast, present only when `SPAM_SUPPRESSION` is `tag` or `drop`
+    pub spam: Option<SpamConfig>,
+    /// bonding curve progress after creation, present only when `CURVE_TRACKING` is on
+    pub curve_tracking: Option<CurveTrackingConfig>,
+    /// every active bonding curve written to a file before monitoring starts, present only when `CURVE_SNAPSHOT` is on
+    pub curve_snapshot: Option<CurveSnapshotConfig>,
+    /// hashes of token images for duplicate detection, present only when `IMAGE_HASHING` is on
+    pub image_hashing: Option<ImageHashConfig>,
+    /// wallet labels read from `WATCHLIST_FILE`, read again on every reload; `None` when no file is set
+    pub watchlist: Option<Arc<Watchlist>>,
+    /// rules every event must pass before it is dispatched, read again on every reload; `None` when no rule is set
+    pub global_filter: Option<Arc<GlobalFilter>>,
+    /// events older than this when dispatched are marked stale, present only when `MAX_EVENT_AGE_SECS` is set
+    pub max_event_age: Option<Duration>,
+    /// key events are signed with, present only when `EVENT_SIGNING_KEY` is set
+    pub event_signer: Option<Arc<EventSigner>>,
+    /// transactions that failed processing, kept for `--reprocess-dead-letters`; present only when `DEAD_LETTER_DIR` is set
+    pub dead_letters: Option<DeadLetterConfig>,
+    pub fixtures: FixtureConfig,
+    /// synthetic events in place of the Solana monitor, for load testing
+    pub simulation: Option<SimulationConfig>,
+    /// log filter such as `info,pump_fun_monitor_corrected=debug`; the default `info` applies when unset
+    pub log_level: Option<String>,
+}
+
+/// Solana RPC endpoints and the program being monitored.
+///
+#[derive(Debug, Clone)]
+pub struct RpcConfig {
+    pub http_url: String,
+    /// names `http_url` in logs, RPC errors and the `endpoint` label of the error metrics
+    pub http_label: String,
+    /// the first of `wss_endpoints`; the curve tracker connects here
+    pub wss_url: String,
+    /// log subscriptions held at the same time, the first notification of a signature winning; only `wss_url` unless
+    /// `SOLANA_RPC_WSS_ENDPOINTS` is set, and empty while replaying or simulating
+    pub wss_endpoints: Vec<WssEndpoint>,
+    /// launchpad programs to monitor, pump.fun alone by default
+    pub programs: Vec<ProgramConfig>,
+    pub commitment: Commitment,
+    /// which accounts are fetched for a new token; `minimal` leaves supply, decimals and the curve out of events
+    pub detail_level: DetailLevel,
+    /// add the measured processing and chain latency to every event
+    pub latency_fields: bool,
+    /// publish a `tokenCreatedPreview` decoded from each creation's log notification, ahead of the fetches
+    pub fast_path: bool,
+    /// wait before reconnecting to an endpoint whose provider refused it for a plan limit, instead of the reconnect delay
+    pub provider_limit_backoff: Duration,
+    /// bound on a single HTTP RPC request
+    pub request_timeout: Duration,
+    /// bound on fetching and parsing one transaction, after which it goes back to the retry queue
+    pub processing_timeout: Duration,
+    /// `diagnostic` messages published a second at most; 0 publishes none
+    pub diagnostics_max_per_sec: u32,
+}
+
+impl RpcConfig {
+    /// whether migrations of completed curves are looked for, i.e. `PUMP_AMM_PROGRAM_ID` is set.
+    pub fn tracks_migrations(&self) -> bool {
+        self.programs.iter().any(|program| program.migration_program.is_some())
+    }
+}
+
+/// a WebSocket RPC endpoint the monitor subscribes on, named in logs and metrics by its label.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WssEndpoint {
+    pub label: String,
+    pub url: String,
+}
+
+/// client-facing WebSocket server settings.
+///
+/// A port of 0 binds an ephemeral port; the actual address is reported by the server once bound.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub bind_addr: ListenAddr,
+    pub tls: Option<TlsConfig>,
+    /// whether clients may opt into gzipped event frames
+    pub compression: bool,
+    /// token required for admin-only client messages such as `getAllStats`; those are refused when unset
+    pub admin_token: Option<String>,
+    /// upgrades beyond this many concurrent clients are rejected with 503
+    pub max_clients: usize,
+    pub ip_limits: IpLimitConfig,
+    /// the browser origins handshakes are accepted from, and whether non-browser clients are
+    pub origins: OriginPolicy,
+    /// filters clients apply by name with `usePreset`, from the file's `[presets.<name>]` tables
+    pub presets: Arc<BTreeMap<String, FilterCriteria>>,
+    /// recent events kept for clients resuming with `resumeFrom`
+    pub replay_capacity: usize,
+    /// frames waiting to be written to one client; beyond it the client misses frames until it catches up
+    pub client_queue_capacity: usize,
+    /// keep each creation's fetched transaction so clients can opt into it with `setRawMode`
+    pub raw_transactions: bool,
+    /// how often each client gets a `heartbeat` until it chooses otherwise with `setHeartbeat`; `None` when off by default
+    pub heartbeat_interval: Option<Duration>,
+    /// how long a disconnected client's settings are kept for `resume`; `None` when clients get no session
+    pub sessions: Option<SessionConfig>,
+    /// broadcast workers delivering events in parallel, each to its own share of the clients
+    pub broadcast_shards: usize,
+    /// what the server does with events marked stale
+    pub stale_events: StalePolicy,
+    /// flat or enveloped event frames, from `EVENT_FORMAT`; also how the CLI prints events
+    pub event_format: EventFormat,
+}
+
+/// where the WebSocket server listens: a TCP address, or a Unix socket for clients on the same host.
+///
+/// `WEBSOCKET_SERVER_HOST=unix:/run/monitor.sock` selects the socket, and no port is needed then.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListenAddr {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl ListenAddr {
+    /// the TCP address, `None` for a Unix socket.
+    pub fn tcp(&self) -> Option<SocketAddr> {
+        match self {
+            Self::Tcp(addr) => Some(*addr),
+            Self::Unix(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ListenAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{}", addr),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// per-IP limits enforced before a connection is handed to the WebSocket handshake.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct IpLimitConfig {
+    pub max_connections_per_ip: usize,
+    /// sustained rate of new connections allowed per IP
+    pub connections_per_sec: f64,
+    /// new connections an IP may open in a burst before the rate applies
+    pub burst: f64,
+}
+
+/// how sessions of disconnected clients are kept until they `resume`.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionConfig {
+    /// how long after disconnecting a client may resume its session
+    pub ttl: Duration,
+    /// sessions kept at once; beyond it the one detached longest ago is dropped
+    pub max_detached: usize,
+    /// matching events kept for one detached session; beyond it the oldest are dropped
+    pub buffer_capacity: usize,
+}
+
+/// certificate and key used to serve `wss://`; plain `ws://` is used when absent.
+///
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    /// connections that haven't finished the TLS handshake by then are closed
+    pub handshake_timeout: Duration,
+}
+
+/// capacities of the internal channels.
+///
+#[derive(Debug, Clone)]
+pub struct ChannelConfig {
+    pub broadcast_capacity: usize,
+    pub processor_capacity: usize,
+    /// signatures held back while the processor queue is full, before the oldest are dropped
+    pub overflow_capacity: usize,
+    /// events waiting for each durable subscriber, such as the persisting sinks
+    pub durable_capacity: usize,
+    /// what a full durable queue does with the next event
+    pub durable_overflow: OverflowPolicy,
+    /// longest a tier-0 sink's enqueue should take before it counts as slow
+    pub slow_enqueue: Duration,
+}
+
+/// exponential backoff applied between Solana WebSocket reconnect attempts.
+///
+/// The delay resets to `initial_delay` whenever a connection was successfully established.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+}
+
+impl ReconnectPolicy {
+    pub fn next_delay(&self, current: Duration) -> Duration {
+        current.mul_f64(self.multiplier).min(self.max_delay)
+    }
+}
+
+/// restarts of a long-running task (the Solana monitor or the WebSocket server) that failed or panicked.
+///
+/// `max_restarts` counts restarts in a row; a task that stays up for a while earns its full budget back.
+#[derive(Debug, Clone)]
+pub struct SupervisorConfig {
+    pub max_restarts: u32,
+    pub backoff: ReconnectPolicy,
+}
+
+/// how long, and for how many mints, a broadcast mint is remembered so it isn't broadcast again.
+///
+#[derive(Debug, Clone)]
+pub struct MintDedupeConfig {
+    pub ttl: Duration,
+    pub capacity: usize,
+}
+
+/// capacities of the in-memory caches not configured with the feature they serve, and how often their sizes are logged.
+///
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// recent signatures shared by the log subscriptions to process each transaction once
+    pub signatures: usize,
+    /// creators whose launch counts are kept with `CREATOR_HISTORY` on, the least recently active forgotten first
+    pub creators: usize,
+    /// Twitter/X links remembered to spot one reused by another token
+    pub socials: usize,
+    /// creation events the token registry answers lookups by mint from, the least recently used forgotten first
+    pub tokens: usize,
+    pub janitor_interval: Duration,
+}
+
+/// silence detection on the Solana log subscription.
+///
+/// After `silence_timeout` without any frame the monitor pings the server, and reconnects if nothing arrives within
+/// `ping_grace`. Independently, a ping goes out every `keepalive_interval` to keep NAT and load balancer mappings open.
+#[derive(Debug, Clone)]
+pub struct WatchdogConfig {
+    pub silence_timeout: Duration,
+    pub ping_grace: Duration,
+    pub keepalive_interval: Duration,
+}
+
+/// health endpoint settings, present only when a health port is configured.
+///
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+    /// where `/healthz`, `/readyz`, `/metrics` and `/reload` listen; the loopback unless `HEALTH_BIND` says otherwise
+    pub bind_addr: SocketAddr,
+    pub max_silence: Duration,
+}
+
+/// gRPC server settings, present only when a gRPC port is configured.
+///
+/// The server listens on the same host as the WebSocket server.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "grpc"), allow(dead_code))]
+pub struct GrpcConfig {
+    pub bind_addr: SocketAddr,
+}
+
+/// REST query API settings, present only when an API port is configured.
+///
+/// The API listens on the same host as the WebSocket server.
+#[derive(Debug, Clone)]
+pub struct HttpApiConfig {
+    pub bind_addr: SocketAddr,
+    /// most recent events kept in memory for queries
+    pub store_capacity: usize,
+}
+
+/// handling of token metadata taken from create instructions and the documents they link to.
+///
+#[derive(Debug, Clone)]
+pub struct MetadataConfig {
+    /// attach the unsanitized name, symbol and URI to events whose metadata had to be cleaned
+    pub keep_raw: bool,
+    /// fetch the metadata document behind each token's URI before broadcasting
+    pub enrichment: bool,
+    pub fetch_timeout: Duration,
+    /// base URL, ending in `/`, that `ipfs://` links are resolved through
+    pub ipfs_gateway: String,
+}
+
+/// counting the tokens each creator launched, to flag serial deployers.
+///
+#[derive(Debug, Clone)]
+pub struct CreatorHistoryConfig {
+    /// append-only file the counts are kept in across restarts; they start over on every start when unset
+    pub file: Option<PathBuf>,
+    /// signatures of a creator seen for the first time to look back through for earlier launches; 0 turns this off
+    pub lookback_signatures: usize,
+    /// a launch that hasn't migrated this long after it was seen counts as abandoned
+    pub abandoned_after: Duration,
+}
+
+/// checking that the parser still finds creations among the programs' recent transactions.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParserSelfTestConfig {
+    /// newest signatures of each program fetched and parsed per run
+    pub signatures: usize,
+    /// transactions parsed without a creation before the parser counts as possibly stale; fewer say nothing
+    pub min_transactions: usize,
+    /// time between runs; `None` runs once, at startup
+    pub interval: Option<Duration>,
+}
+
+/// tagging or dropping tokens whose name or symbol looks like one broadcast shortly before.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpamConfig {
+    /// `Tag` or `Drop`; `Off` turns the whole section off
+    pub suppression: SpamSuppression,
+    /// how long a broadcast name counts as recent
+    pub window: Duration,
+    /// most recent names compared against, the oldest are forgotten first
+    pub capacity: usize,
+}
+
+/// following the bonding curves of new tokens with `accountSubscribe` for `curveUpdate` messages.
+///
+#[derive(Debug, Clone)]
+pub struct CurveTrackingConfig {
+    /// only tokens matching this filter are tracked; every token when unset
+    pub filter: Option<FilterCriteria>,
+    /// most curves followed at once; tokens created while this many are tracked are skipped
+    pub max_subscriptions: usize,
+    /// how long a curve that hasn't completed is followed after its token was created
+    pub ttl: Duration,
+    /// least time between two updates for the same mint
+    pub update_interval: Duration,
+}
+
+/// listing every active bonding curve with `getProgramAccounts` before live monitoring starts.
+///
+#[derive(Debug, Clone)]
+pub struct CurveSnapshotConfig {
+    /// JSON lines file the curves are written to; the previous snapshot in it is compared against first
+    pub file: PathBuf,
+    /// split the listing into 256 requests by one byte of the curve data, so no single response holds every curve
+    pub partitioned: bool,
+}
+
+/// downloading and hashing token images to spot tokens that reuse another's image.
+///
+#[derive(Debug, Clone)]
+pub struct ImageHashConfig {
+    /// images larger than this are not hashed
+    pub max_bytes: usize,
+    /// bounds one download, including reading the body
+    pub fetch_timeout: Duration,
+    /// how long a token waits for its image hash before it is broadcast without one
+    pub wait: Duration,
+    /// most downloads running at once; images of tokens arriving while this many run are not hashed
+    pub concurrency: usize,
+    /// image hashes remembered, oldest forgotten first
+    pub capacity: usize,
+    /// send clients a `tokenEnriched` message with hashes that missed the wait
+    pub follow_ups: bool,
+}
+
+/// keeping the transactions that failed processing on disk, so they can be run through the parser again.
+///
+#[derive(Debug, Clone)]
+pub struct DeadLetterConfig {
+    /// directory holding one file per failed signature
+    pub dir: PathBuf,
+    /// most letters kept; the oldest are removed first
+    pub max_entries: usize,
+    /// letters older than this are removed
+    pub retention: Duration,
+}
+
+/// recording RPC traffic to fixture files, or replaying a recording instead of connecting to any RPC.
+///
+#[derive(Debug, Clone, Default)]
+pub struct FixtureConfig {
+    /// directory every log notification, transaction and account the monitor sees is written to
+    pub record_dir: Option<PathBuf>,
+    pub replay: Option<ReplayConfig>,
+}
+
+/// replay of a recorded fixture directory.
+///
+#[derive(Debug, Clone)]
+pub struct ReplayConfig {
+    pub dir: PathBuf,
+    /// 1.0 keeps the recorded timing, 2.0 replays twice as fast, 0 replays as fast as possible
+    pub speed: f64,
+}
+
+impl Config {
+    /// whether token metadata documents are fetched: with `METADATA_ENRICHMENT` on, or always at `DETAIL_LEVEL=full`.
+    pub fn fetches_metadata(&self) -> bool {
+        self.metadata.enrichment || self.rpc.detail_level == DetailLevel::Full
+    }
+
+    /// whether diagnostics are published: only admins may subscribe, so not without `WS_ADMIN_TOKEN`.
+    pub fn publishes_diagnostics(&self) -> bool {
+        self.server.admin_token.is_some() && self.rpc.diagnostics_max_per_sec > 0
+    }
+
+    /// Loads the configuration from `path` (if any) and applies overrides.
+    ///
+    /// `overrides` (typically command line flags) are keyed by environment variable name and win over the real environment.
+    pub fn load(path: Option<&Path>, overrides: &HashMap<&'static str, String>) -> Result<Self> {
+        let file = match path {
+            Some(path) => FileConfig::read(path)?,
+            None => FileConfig::default(),
+        };
+        Self::from_sources(file, |name| {
+            overrides.get(name).cloned().or_else(|| env::var(name).ok())
+        })
+    }
+
+    /// Merges `file` with overrides from `env` and validates the result, reporting every invalid field at once.
+    fn from_sources(mut file: FileConfig, env: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let mut errors = Vec::new();
+        file.apply_env(&env, &mut errors);
+        file.validate(errors)
+    }
+}
+
+/// raw configuration as written in the file; every field is optional so env overrides and defaults can fill the gaps.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    rpc: FileRpc,
+    server: FileServer,
+    channels: FileChannels,
+    reconnect: FileReconnect,
+    watchdog: FileWatchdog,
+    supervisor: FileSupervisor,
+    mint_dedupe: FileMintDedupe,
+    caches: FileCaches,
+    health: FileHealth,
+    price_feed: FilePriceFeed,
+    kafka: FileKafka,
+    nats: FileNats,
+    discord: FileDiscord,
+    telegram: FileTelegram,
+    events_log: FileEventsLog,
+    database: FileDatabase,
+    grpc: FileGrpc,
+    http_api: FileHttpApi,
+    metadata: FileMetadata,
+    creator_history: FileCreatorHistory,
+    parser_self_test: FileParserSelfTest,
+    spam: FileSpam,
+    curve_tracking: FileCurveTracking,
+    curve_snapshot: FileCurveSnapshot,
+    image_hashing: FileImageHashing,
+    watchlist: FileWatchlist,
+    global_filter: FileGlobalFilter,
+    freshness: FileFreshness,
+    events: FileEvents,
+    signing: FileSigning,
+    dead_letters: FileDeadLetters,
+    fixtures: FileFixtures,
+    simulation: FileSimulation,
+    logging: FileLogging,
+    /// filters clients apply by name with `usePreset`, file-only
+    presets: BTreeMap<String, FilterCriteria>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileRpc {
+    http_url: Option<String>,
+    http_label: Option<String>,
+    wss_url: Option<String>,
+    /// comma-separated `label=url` pairs, in place of `wss_url`
+    wss_endpoints: Option<String>,
+    /// comma-separated program ids
+    program_id: Option<String>,
+    /// AMM program completed curves migrate to; migrations are tracked only when it is set
+    pump_amm_program_id: Option<String>,
+    commitment: Option<String>,
+    /// `minimal`, `standard` or `full`
+    detail_level: Option<String>,
+    latency_fields: Option<bool>,
+    fast_path: Option<bool>,
+    provider_limit_backoff_secs: Option<u64>,
+    request_timeout_secs: Option<u64>,
+    processing_timeout_secs: Option<u64>,
+    diagnostics_max_per_sec: Option<u32>,
+    /// discriminator overrides keyed by program id, for launchpads whose Anchor names differ from pump.fun's
+    parsers: HashMap<String, FileParser>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileParser {
+    create_instruction: Option<String>,
+    buy_instruction: Option<String>,
+    create_event: Option<String>,
+    bonding_curve_account: Option<String>,
+    migrate_instruction: Option<String>,
+    migration_event: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileServer {
+    host: Option<String>,
+    port: Option<u16>,
+    tls_cert: Option<PathBuf>,
+    tls_key: Option<PathBuf>,
+    tls_handshake_timeout_secs: Option<u64>,
+    compression: Option<bool>,
+    admin_token: Option<String>,
+    max_clients: Option<usize>,
+    max_connections_per_ip: Option<usize>,
+    connections_per_ip_per_sec: Option<f64>,
+    connection_burst_per_ip: Option<u32>,
+    allowed_origins: Option<String>,
+    require_origin: Option<bool>,
+    replay_capacity: Option<usize>,
+    client_queue_capacity: Option<usize>,
+    raw_transactions: Option<bool>,
+    heartbeat_secs: Option<u64>,
+    session_ttl_secs: Option<u64>,
+    max_detached_sessions: Option<usize>,
+    session_buffer_capacity: Option<usize>,
+    broadcast_shards: Option<usize>,
+    stale_events: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileChannels {
+    broadcast_capacity: Option<usize>,
+    processor_capacity: Option<usize>,
+    overflow_capacity: Option<usize>,
+    durable_capacity: Option<usize>,
+    durable_overflow: Option<String>,
+    immediate_slow_enqueue_ms: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileReconnect {
+    initial_delay_ms: Option<u64>,
+    max_delay_ms: Option<u64>,
+    multiplier: Option<f64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileWatchdog {
+    silence_secs: Option<u64>,
+    ping_grace_secs: Option<u64>,
+    keepalive_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileSupervisor {
+    max_restarts: Option<u32>,
+    initial_delay_ms: Option<u64>,
+    max_delay_ms: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileMintDedupe {
+    ttl_secs: Option<u64>,
+    capacity: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileCaches {
+    signature_capacity: Option<usize>,
+    creator_capacity: Option<usize>,
+    social_capacity: Option<usize>,
+    token_capacity: Option<usize>,
+    janitor_interval_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileHealth {
+    bind: Option<String>,
+    port: Option<u16>,
+    max_silence_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FilePriceFeed {
+    source: Option<String>,
+    pyth_account: Option<String>,
+    refresh_secs: Option<u64>,
+    max_age_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileKafka {
+    brokers: Option<String>,
+    topic: Option<String>,
+    message_timeout_ms: Option<u64>,
+    filter: Option<FilterCriteria>,
+    stale: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileNats {
+    url: Option<String>,
+    subject_prefix: Option<String>,
+    jetstream: Option<bool>,
+    pending_capacity: Option<usize>,
+    filter: Option<FilterCriteria>,
+    stale: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileDiscord {
+    /// a catch-all route; only settable this way from the environment
+    webhook_url: Option<String>,
+    queue_capacity: Option<usize>,
+    filter: Option<FilterCriteria>,
+    stale: Option<String>,
+    tier: Option<u8>,
+    routes: Vec<FileDiscordRoute>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct FileDiscordRoute {
+    webhook_url: String,
+    #[serde(default)]
+    filter: FilterCriteria,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileTelegram {
+    bot_token: Option<String>,
+    /// a catch-all route; only settable this way from the environment
+    chat_id: Option<String>,
+    queue_capacity: Option<usize>,
+    filter: Option<FilterCriteria>,
+    stale: Option<String>,
+    tier: Option<u8>,
+    routes: Vec<FileTelegramRoute>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct FileTelegramRoute {
+    chat_id: String,
+    #[serde(default)]
+    filter: FilterCriteria,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileEventsLog {
+    path: Option<PathBuf>,
+    rotation: Option<String>,
+    max_bytes: Option<u64>,
+    fsync: Option<String>,
+    queue_capacity: Option<usize>,
+    filter: Option<FilterCriteria>,
+    stale: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileDatabase {
+    /// the scheme picks the backend
+    url: Option<String>,
+    max_connections: Option<u32>,
+    batch_size: Option<usize>,
+    filter: Option<FilterCriteria>,
+    stale: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileGrpc {
+    port: Option<u16>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileHttpApi {
+    port: Option<u16>,
+    store_capacity: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileMetadata {
+    keep_raw: Option<bool>,
+    enrichment: Option<bool>,
+    fetch_timeout_ms: Option<u64>,
+    ipfs_gateway: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileCreatorHistory {
+    enabled: Option<bool>,
+    file: Option<PathBuf>,
+    lookback_signatures: Option<usize>,
+    abandoned_after_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileParserSelfTest {
+    enabled: Option<bool>,
+    signatures: Option<usize>,
+    min_transactions: Option<usize>,
+    interval_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileSpam {
+    suppression: Option<String>,
+    window_secs: Option<u64>,
+    capacity: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileCurveTracking {
+    enabled: Option<bool>,
+    filter: Option<FilterCriteria>,
+    max_subscriptions: Option<usize>,
+    ttl_secs: Option<u64>,
+    update_interval_ms: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileImageHashing {
+    enabled: Option<bool>,
+    max_bytes: Option<usize>,
+    fetch_timeout_ms: Option<u64>,
+    wait_ms: Option<u64>,
+    concurrency: Option<usize>,
+    capacity: Option<usize>,
+    follow_ups: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileWatchlist {
+    file: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileGlobalFilter {
+    name_include: Option<String>,
+    name_exclude: Option<String>,
+    symbol_include: Option<String>,
+    symbol_exclude: Option<String>,
+    creator_blocklist: Option<PathBuf>,
+    /// in SOL
+    min_real_sol_reserves: Option<f64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileFreshness {
+    max_event_age_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileEvents {
+    format: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileSigning {
+    /// 64 hex digits of ed25519 seed, or a keypair file path
+    key: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileCurveSnapshot {
+    enabled: Option<bool>,
+    file: Option<PathBuf>,
+    partitioned: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileDeadLetters {
+    dir: Option<PathBuf>,
+    max_entries: Option<usize>,
+    retention_hours: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileFixtures {
+    record_dir: Option<PathBuf>,
+    replay_dir: Option<PathBuf>,
+    replay_speed: Option<f64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileSimulation {
+    enabled: Option<bool>,
+    events_per_sec: Option<f64>,
+    seed: Option<u64>,
+    burst_events: Option<u32>,
+    burst_interval_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FileLogging {
+    level: Option<String>,
+}
+
+impl FileConfig {
+    fn read(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            MonitorError::Config(format!("failed to read config file {}: {}", path.display(), e))
+        })?;
+
+        let is_yaml = matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("yaml") | Some("yml")
+        );
+        let parsed = if is_yaml {
+            serde_yaml::from_str(&contents).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&contents).map_err(|e| e.to_string())
+        };
+
+        parsed.map_err(|e| MonitorError::Config(format!("failed to parse config file {}: {}", path.display(), e)))
+    }
+
+    fn apply_env(&mut self, env: &impl Fn(&str) -> Option<String>, errors: &mut Vec<String>) {
+        env_override(&mut self.rpc.http_url, "SOLANA_RPC_HTTP_URL", env, errors);
+        env_override(&mut self.rpc.http_label, "SOLANA_RPC_HTTP_LABEL", env, errors);
+        env_override(&mut self.rpc.wss_url, "SOLANA_RPC_WSS_URL", env, errors);
+        env_override(&mut self.rpc.wss_endpoints, "SOLANA_RPC_WSS_ENDPOINTS", env, errors);
+        env_override(&mut self.rpc.program_id, "PUMP_FUN_PROGRAM_ID", env, errors);
+        env_override(&mut self.rpc.pump_amm_program_id, "PUMP_AMM_PROGRAM_ID", env, errors);
+        env_override(&mut self.rpc.commitment, "COMMITMENT_LEVEL", env, errors);
+        env_override(&mut self.rpc.detail_level, "DETAIL_LEVEL", env, errors);
+        env_override(&mut self.rpc.latency_fields, "EVENT_LATENCY_FIELDS", env, errors);
+        env_override(&mut self.rpc.fast_path, "FAST_PATH", env, errors);
+        env_override(&mut self.rpc.provider_limit_backoff_secs, "PROVIDER_LIMIT_BACKOFF_SECS", env, errors);
+        env_override(&mut self.rpc.request_timeout_secs, "RPC_REQUEST_TIMEOUT_SECS", env, errors);
+        env_override(&mut self.rpc.processing_timeout_secs, "PROCESSING_TIMEOUT_SECS", env, errors);
+        env_override(&mut self.rpc.diagnostics_max_per_sec, "DIAGNOSTICS_MAX_PER_SEC", env, errors);
+        env_override(&mut self.server.host, "WEBSOCKET_SERVER_HOST", env, errors);
+        env_override(&mut self.server.port, "WEBSOCKET_SERVER_PORT", env, errors);
+        env_override(&mut self.server.tls_cert, "WS_TLS_CERT", env, errors);
+        env_override(&mut self.server.tls_key, "WS_TLS_KEY", env, errors);
+        env_override(&mut self.server.tls_handshake_timeout_secs, "WS_TLS_HANDSHAKE_TIMEOUT_SECS", env, errors);
+        env_override(&mut self.server.compression, "WS_COMPRESSION", env, errors);
+        env_override(&mut self.server.admin_token, "WS_ADMIN_TOKEN", env, errors);
+        env_override(&mut self.server.max_clients, "WS_MAX_CLIENTS", env, errors);
+        env_override(&mut self.server.replay_capacity, "WS_REPLAY_CAPACITY", env, errors);
+        env_override(&mut self.server.client_queue_capacity, "WS_CLIENT_QUEUE_CAPACITY", env, errors);
+        env_override(&mut self.server.raw_transactions, "WS_RAW_TRANSACTIONS", env, errors);
+        env_override(&mut self.server.heartbeat_secs, "WS_HEARTBEAT_SECS", env, errors);
+        env_override(&mut self.server.session_ttl_secs, "WS_SESSION_TTL_SECS", env, errors);
+        env_override(&mut self.server.max_detached_sessions, "WS_MAX_DETACHED_SESSIONS", env, errors);
+        env_override(&mut self.server.session_buffer_capacity, "WS_SESSION_BUFFER_CAPACITY", env, errors);
+        env_override(&mut self.server.broadcast_shards, "WS_BROADCAST_SHARDS", env, errors);
+        env_override(&mut self.server.stale_events, "WS_STALE_EVENTS", env, errors);
+        env_override(&mut self.server.max_connections_per_ip, "WS_MAX_CONNECTIONS_PER_IP", env, errors);
+        env_override(&mut self.server.connections_per_ip_per_sec, "WS_CONNECTIONS_PER_IP_PER_SEC", env, errors);
+        env_override(&mut self.server.connection_burst_per_ip, "WS_CONNECTION_BURST_PER_IP", env, errors);
+        env_override(&mut self.server.allowed_origins, "WS_ALLOWED_ORIGINS", env, errors);
+        env_override(&mut self.server.require_origin, "WS_REQUIRE_ORIGIN", env, errors);
+        env_override(&mut self.channels.broadcast_capacity, "BROADCAST_CAPACITY", env, errors);
+        env_override(&mut self.channels.processor_capacity, "PROCESSOR_QUEUE_CAPACITY", env, errors);
+        env_override(&mut self.channels.overflow_capacity, "PROCESSOR_OVERFLOW_CAPACITY", env, errors);
+        env_override(&mut self.channels.durable_capacity, "DURABLE_QUEUE_CAPACITY", env, errors);
+        env_override(&mut self.channels.durable_overflow, "DURABLE_QUEUE_OVERFLOW", env, errors);
+        env_override(&mut self.channels.immediate_slow_enqueue_ms, "IMMEDIATE_SLOW_ENQUEUE_MS", env, errors);
+        env_override(&mut self.reconnect.initial_delay_ms, "RECONNECT_INITIAL_DELAY_MS", env, errors);
+        env_override(&mut self.reconnect.max_delay_ms, "RECONNECT_MAX_DELAY_MS", env, errors);
+        env_override(&mut self.reconnect.multiplier, "RECONNECT_MULTIPLIER", env, errors);
+        env_override(&mut self.watchdog.silence_secs, "WATCHDOG_SILENCE_SECS", env, errors);
+        env_override(&mut self.watchdog.ping_grace_secs, "WATCHDOG_PING_GRACE_SECS", env, errors);
+        env_override(&mut self.watchdog.keepalive_secs, "WATCHDOG_KEEPALIVE_SECS", env, errors);
+        env_override(&mut self.supervisor.max_restarts, "SUPERVISOR_MAX_RESTARTS", env, errors);
+        env_override(&mut self.supervisor.initial_delay_ms, "SUPERVISOR_INITIAL_DELAY_MS", env, errors);
+        env_override(&mut self.supervisor.max_delay_ms, "SUPERVISOR_MAX_DELAY_MS", env, errors);
+        env_override(&mut self.mint_dedupe.ttl_secs, "MINT_DEDUPE_TTL_SECS", env, errors);
+        env_override(&mut self.mint_dedupe.capacity, "MINT_DEDUPE_CAPACITY", env, errors);
+        env_override(&mut self.caches.signature_capacity, "SIGNATURE_CACHE_CAPACITY", env, errors);
+        env_override(&mut self.caches.creator_capacity, "CREATOR_CACHE_CAPACITY", env, errors);
+        env_override(&mut self.caches.social_capacity, "SOCIAL_CACHE_CAPACITY", env, errors);
+        env_override(&mut self.caches.token_capacity, "TOKEN_REGISTRY_CAPACITY", env, errors);
+        env_override(&mut self.caches.janitor_interval_secs, "CACHE_JANITOR_INTERVAL_SECS", env, errors);
+        env_override(&mut self.health.bind, "HEALTH_BIND", env, errors);
+        env_override(&mut self.health.port, "HEALTH_PORT", env, errors);
+        env_override(&mut self.health.max_silence_secs, "HEALTH_MAX_SILENCE_SECS", env, errors);
+        env_override(&mut self.price_feed.source, "SOL_PRICE_SOURCE", env, errors);
+        env_override(&mut self.price_feed.pyth_account, "PYTH_SOL_USD_ACCOUNT", env, errors);
+        env_override(&mut self.price_feed.refresh_secs, "SOL_PRICE_REFRESH_SECS", env, errors);
+        env_override(&mut self.price_feed.max_age_secs, "SOL_PRICE_MAX_AGE_SECS", env, errors);
+        env_override(&mut self.kafka.brokers, "KAFKA_BROKERS", env, errors);
+        env_override(&mut self.kafka.topic, "KAFKA_TOPIC", env, errors);
+        env_override(&mut self.kafka.message_timeout_ms, "KAFKA_MESSAGE_TIMEOUT_MS", env, errors);
+        env_override(&mut self.nats.url, "NATS_URL", env, errors);
+        env_override(&mut self.nats.subject_prefix, "NATS_SUBJECT_PREFIX", env, errors);
+        env_override(&mut self.nats.jetstream, "NATS_JETSTREAM", env, errors);
+        env_override(&mut self.nats.pending_capacity, "NATS_PENDING_CAPACITY", env, errors);
+        env_override(&mut self.discord.webhook_url, "DISCORD_WEBHOOK_URL", env, errors);
+        env_override(&mut self.discord.queue_capacity, "DISCORD_QUEUE_CAPACITY", env, errors);
+        env_override(&mut self.telegram.bot_token, "TELEGRAM_BOT_TOKEN", env, errors);
+        env_override(&mut self.telegram.chat_id, "TELEGRAM_CHAT_ID", env, errors);
+        env_override(&mut self.telegram.queue_capacity, "TELEGRAM_QUEUE_CAPACITY", env, errors);
+        env_override(&mut self.events_log.path, "EVENTS_LOG_PATH", env, errors);
+        env_override(&mut self.events_log.rotation, "EVENTS_LOG_ROTATION", env, errors);
+        env_override(&mut self.events_log.max_bytes, "EVENTS_LOG_MAX_BYTES", env, errors);
+        env_override(&mut self.events_log.fsync, "EVENTS_LOG_FSYNC", env, errors);
+        env_override(&mut self.events_log.queue_capacity, "EVENTS_LOG_QUEUE_CAPACITY", env, errors);
+        env_override(&mut self.database.url, "DATABASE_URL", env, errors);
+        env_override(&mut self.database.max_connections, "DATABASE_MAX_CONNECTIONS", env, errors);
+        env_override(&mut self.database.batch_size, "DATABASE_BATCH_SIZE", env, errors);
+        env_override(&mut self.grpc.port, "GRPC_PORT", env, errors);
+        env_override(&mut self.http_api.port, "HTTP_API_PORT", env, errors);
+        env_override(&mut self.http_api.store_capacity, "EVENT_STORE_CAPACITY", env, errors);
+        env_override(&mut self.metadata.keep_raw, "KEEP_RAW_METADATA", env, errors);
+        env_override(&mut self.metadata.enrichment, "METADATA_ENRICHMENT", env, errors);
+        env_override(&mut self.metadata.fetch_timeout_ms, "METADATA_FETCH_TIMEOUT_MS", env, errors);
+        env_override(&mut self.metadata.ipfs_gateway, "IPFS_GATEWAY", env, errors);
+        env_override(&mut self.creator_history.enabled, "CREATOR_HISTORY", env, errors);
+        env_override(&mut self.creator_history.file, "CREATOR_HISTORY_FILE", env, errors);
+        env_override(&mut self.creator_history.lookback_signatures, "CREATOR_HISTORY_LOOKBACK", env, errors);
+        env_override(&mut self.creator_history.abandoned_after_secs, "CREATOR_ABANDONED_AFTER_SECS", env, errors);
+        env_override(&mut self.parser_self_test.enabled, "PARSER_SELF_TEST", env, errors);
+        env_override(&mut self.parser_self_test.signatures, "PARSER_SELF_TEST_SIGNATURES", env, errors);
+        env_override(&mut self.parser_self_test.min_transactions, "PARSER_SELF_TEST_MIN_TRANSACTIONS", env, errors);
+        env_override(&mut self.parser_self_test.interval_secs, "PARSER_SELF_TEST_INTERVAL_SECS", env, errors);
+        env_override(&mut self.spam.suppression, "SPAM_SUPPRESSION", env, errors);
+        env_override(&mut self.spam.window_secs, "SPAM_WINDOW_SECS", env, errors);
+        env_override(&mut self.spam.capacity, "SPAM_WINDOW_CAPACITY", env, errors);
+        env_override(&mut self.curve_tracking.enabled, "CURVE_TRACKING", env, errors);
+        env_override(&mut self.curve_tracking.max_subscriptions, "CURVE_TRACKING_MAX_SUBSCRIPTIONS", env, errors);
+        env_override(&mut self.curve_tracking.ttl_secs, "CURVE_TRACKING_TTL_SECS", env, errors);
+        env_override(&mut self.curve_tracking.update_interval_ms, "CURVE_UPDATE_INTERVAL_MS", env, errors);
+        env_override(&mut self.curve_snapshot.enabled, "CURVE_SNAPSHOT", env, errors);
+        env_override(&mut self.curve_snapshot.file, "CURVE_SNAPSHOT_FILE", env, errors);
+        env_override(&mut self.curve_snapshot.partitioned, "CURVE_SNAPSHOT_PARTITIONED", env, errors);
+        env_override(&mut self.image_hashing.enabled, "IMAGE_HASHING", env, errors);
+        env_override(&mut self.image_hashing.max_bytes, "IMAGE_MAX_BYTES", env, errors);
+        env_override(&mut self.image_hashing.fetch_timeout_ms, "IMAGE_FETCH_TIMEOUT_MS", env, errors);
+        env_override(&mut self.image_hashing.wait_ms, "IMAGE_HASH_WAIT_MS", env, errors);
+        env_override(&mut self.image_hashing.concurrency, "IMAGE_DOWNLOAD_CONCURRENCY", env, errors);
+        env_override(&mut self.image_hashing.capacity, "IMAGE_HASH_CAPACITY", env, errors);
+        env_override(&mut self.image_hashing.follow_ups, "IMAGE_HASH_FOLLOW_UPS", env, errors);
+        env_override(&mut self.watchlist.file, "WATCHLIST_FILE", env, errors);
+        env_override(&mut self.global_filter.name_include, "GLOBAL_FILTER_NAME_INCLUDE", env, errors);
+        env_override(&mut self.global_filter.name_exclude, "GLOBAL_FILTER_NAME_EXCLUDE", env, errors);
+        env_override(&mut self.global_filter.symbol_include, "GLOBAL_FILTER_SYMBOL_INCLUDE", env, errors);
+        env_override(&mut self.global_filter.symbol_exclude, "GLOBAL_FILTER_SYMBOL_EXCLUDE", env, errors);
+        env_override(&mut self.global_filter.creator_blocklist, "GLOBAL_FILTER_CREATOR_BLOCKLIST", env, errors);
+        env_override(&mut self.global_filter.min_real_sol_reserves, "GLOBAL_FILTER_MIN_REAL_SOL_RESERVES", env, errors);
+        env_override(&mut self.freshness.max_event_age_secs, "MAX_EVENT_AGE_SECS", env, errors);
+        env_override(&mut self.events.format, "EVENT_FORMAT", env, errors);
+        env_override(&mut self.signing.key, "EVENT_SIGNING_KEY", env, errors);
+        env_override(&mut self.dead_letters.dir, "DEAD_LETTER_DIR", env, errors);
+        env_override(&mut self.dead_letters.max_entries, "DEAD_LETTER_MAX_ENTRIES", env, errors);
+        env_override(&mut self.dead_letters.retention_hours, "DEAD_LETTER_RETENTION_HOURS", env, errors);
+        env_override(&mut self.logging.level, "RUST_LOG", env, errors);
+        env_override(&mut self.fixtures.record_dir, "RECORD_FIXTURES", env, errors);
+        env_override(&mut self.fixtures.replay_dir, "REPLAY_FIXTURES", env, errors);
+        env_override(&mut self.fixtures.replay_speed, "REPLAY_SPEED", env, errors);
+        env_override(&mut self.simulation.enabled, "SIMULATION_MODE", env, errors);
+        env_override(&mut self.simulation.events_per_sec, "SIMULATION_EVENTS_PER_SEC", env, errors);
+        env_override(&mut self.simulation.seed, "SIMULATION_SEED", env, errors);
+        env_override(&mut self.simulation.burst_events, "SIMULATION_BURST_EVENTS", env, errors);
+        env_override(&mut self.simulation.burst_interval_secs, "SIMULATION_BURST_INTERVAL_SECS", env, errors);
+    }
+
+    fn validate(self, mut errors: Vec<String>) -> Result<Config> {
+        // neither a replay nor a simulation talks to an RPC, so the endpoints may be left unset
+        let replaying = self.fixtures.replay_dir.is_some();
+        let simulating = self.simulation.enabled.unwrap_or(false);
+        let http_url = match self.rpc.http_url {
+            None if replaying || simulating => Some(String::new()),
+            url => required(url, "SOLANA_RPC_HTTP_URL", &mut errors)
+                .filter(|url| validate_url(url, "SOLANA_RPC_HTTP_URL", &["http", "https"], &mut errors)),
+        };
+        let http_label = self.rpc.http_label.unwrap_or_else(|| DEFAULT_HTTP_ENDPOINT_LABEL.to_string());
+        if !is_endpoint_label(&http_label) {
+            errors.push(format!("SOLANA_RPC_HTTP_LABEL '{}' may only use letters, digits, '-' and '_'", http_label));
+        }
+        let wss_endpoints = match (self.rpc.wss_url, self.rpc.wss_endpoints) {
+            (None, None) if replaying || simulating => Some(Vec::new()),
+            (Some(_), Some(_)) => {
+                errors.push("set either SOLANA_RPC_WSS_URL or SOLANA_RPC_WSS_ENDPOINTS, not both".to_string());
+                None
+            }
+            (None, Some(list)) => parse_wss_endpoints(&list, &mut errors),
+            (url, None) => required(url, "SOLANA_RPC_WSS_URL", &mut errors)
+                .filter(|url| validate_url(url, "SOLANA_RPC_WSS_URL", &["ws", "wss"], &mut errors))
+                .map(|url| vec![WssEndpoint { label: DEFAULT_WSS_ENDPOINT_LABEL.to_string(), url }]),
+        };
+        let migration_program = self
+            .rpc
+            .pump_amm_program_id
+            .as_deref()
+            .and_then(|id| parse_pubkey(id, "PUMP_AMM_PROGRAM_ID", &mut errors));
+        let programs = parse_programs(
+            self.rpc.program_id.as_deref().unwrap_or(DEFAULT_PUMP_FUN_PROGRAM_ID),
+            self.rpc.parsers,
+            migration_program,
+            &mut errors,
+        );
+        let commitment = match self.rpc.commitment.as_deref().map(str::parse::<Commitment>) {
+            None => Commitment::default(),
+            Some(Ok(commitment)) => commitment,
+            Some(Err(e)) => {
+                errors.push(error_message(e));
+                Commitment::default()
+            }
+        };
+        let detail_level = match self.rpc.detail_level.as_deref().map(str::parse::<DetailLevel>) {
+            None => DetailLevel::default(),
+            Some(Ok(detail_level)) => detail_level,
+            Some(Err(e)) => {
+                errors.push(error_message(e));
+                DetailLevel::default()
+            }
+        };
+        let host = self.server.host.as_deref().unwrap_or("127.0.0.1");
+        let bind_addr = match host.trim().strip_prefix("unix:") {
+            Some(path) => parse_socket_path(path, &mut errors),
+            None => {
+                let port = required(self.server.port, "WEBSOCKET_SERVER_PORT", &mut errors);
+                port.and_then(|port| parse_bind_addr(host, port, "WEBSOCKET_SERVER_HOST", &mut errors)).map(ListenAddr::Tcp)
+            }
+        };
+        // the gRPC server and the REST API still need TCP; they stay on the loopback next to a Unix socket
+        let api_ip = match &bind_addr {
+            Some(ListenAddr::Tcp(addr)) => Some(addr.ip()),
+            Some(ListenAddr::Unix(_)) => Some(std::net::Ipv4Addr::LOCALHOST.into()),
+            None => None,
+        };
+        let ws_port = bind_addr.as_ref().and_then(ListenAddr::tcp).map(|addr| addr.port());
+        let tls = match (self.server.tls_cert, self.server.tls_key) {
+            (Some(_), Some(_)) if matches!(bind_addr, Some(ListenAddr::Unix(_))) => {
+                errors.push("WS_TLS_CERT and WS_TLS_KEY cannot be used with a unix: WEBSOCKET_SERVER_HOST".to_string());
+                None
+            }
+            (Some(cert_path), Some(key_path)) => {
+                for (path, name) in [(&cert_path, "WS_TLS_CERT"), (&key_path, "WS_TLS_KEY")] {
+                    if !path.is_file() {
+                        errors.push(format!("{} '{}' does not point to a readable file", name, path.display()));
+                    }
+                }
+                let handshake_timeout_secs = positive(
+                    self.server.tls_handshake_timeout_secs.unwrap_or(DEFAULT_TLS_HANDSHAKE_TIMEOUT_SECS),
+                    "WS_TLS_HANDSHAKE_TIMEOUT_SECS",
+                    &mut errors,
+                );
+                Some(TlsConfig {
+                    cert_path,
+                    key_path,
+                    handshake_timeout: Duration::from_secs(handshake_timeout_secs),
+                })
+            }
+            (Some(_), None) => {
+                errors.push("WS_TLS_CERT is set but WS_TLS_KEY is not".to_string());
+                None
+            }
+            (None, Some(_)) => {
+                errors.push("WS_TLS_KEY is set but WS_TLS_CERT is not".to_string());
+                None
+            }
+            (None, None) => None,
+        };
+
+        let admin_token = self.server.admin_token.filter(|token| !token.trim().is_empty());
+        let max_clients = positive(self.server.max_clients.unwrap_or(1024), "WS_MAX_CLIENTS", &mut errors);
+        let replay_capacity = capacity(self.server.replay_capacity.unwrap_or(1_000), "WS_REPLAY_CAPACITY", &mut errors);
+        let client_queue_capacity =
+            capacity(self.server.client_queue_capacity.unwrap_or(10_000), "WS_CLIENT_QUEUE_CAPACITY", &mut errors);
+        // 0 turns heartbeats off until a client asks for them
+        let heartbeat_secs = self.server.heartbeat_secs.unwrap_or(DEFAULT_HEARTBEAT_SECS);
+        if heartbeat_secs != 0 && !(MIN_HEARTBEAT_SECS..=MAX_HEARTBEAT_SECS).contains(&heartbeat_secs) {
+            errors.push(format!(
+                "WS_HEARTBEAT_SECS {} must be 0 or between {} and {}",
+                heartbeat_secs, MIN_HEARTBEAT_SECS, MAX_HEARTBEAT_SECS
+            ));
+        }
+        let heartbeat_interval = (heartbeat_secs != 0).then(|| Duration::from_secs(heartbeat_secs));
+        let max_detached_sessions =
+            capacity(self.server.max_detached_sessions.unwrap_or(1_000), "WS_MAX_DETACHED_SESSIONS", &mut errors);
+        let session_buffer_capacity =
+            capacity(self.server.session_buffer_capacity.unwrap_or(500), "WS_SESSION_BUFFER_CAPACITY", &mut errors);
+        // 0 gives clients no session to resume
+        let sessions = match self.server.session_ttl_secs.unwrap_or(DEFAULT_SESSION_TTL_SECS) {
+            0 => None,
+            secs => Some(SessionConfig {
+                ttl: Duration::from_secs(secs),
+                max_detached: max_detached_sessions,
+                buffer_capacity: session_buffer_capacity,
+            }),
+        };
+        // one worker per core unless told otherwise
+        let broadcast_shards = positive(
+            self.server
+                .broadcast_shards
+                .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |cores| cores.get())),
+            "WS_BROADCAST_SHARDS",
+            &mut errors,
+        );
+        let stale_events = stale_policy(self.server.stale_events, "WS_STALE_EVENTS", &mut errors);
+        let origins = OriginPolicy {
+            allowed: self
+                .server
+                .allowed_origins
+                .as_deref()
+                .map(|list| parse_allowed_origins(list, &mut errors))
+                .unwrap_or_default(),
+            require: self.server.require_origin.unwrap_or(false),
+        };
+        let connections_per_sec = self.server.connections_per_ip_per_sec.unwrap_or(2.0);
+        if !connections_per_sec.is_finite() || connections_per_sec <= 0.0 {
+            errors.push(format!("WS_CONNECTIONS_PER_IP_PER_SEC {} must be a positive number", connections_per_sec));
+        }
+        let ip_limits = IpLimitConfig {
+            max_connections_per_ip: positive(
+                self.server.max_connections_per_ip.unwrap_or(32),
+                "WS_MAX_CONNECTIONS_PER_IP",
+                &mut errors,
+            ),
+            connections_per_sec,
+            burst: f64::from(positive(
+                self.server.connection_burst_per_ip.unwrap_or(10),
+                "WS_CONNECTION_BURST_PER_IP",
+                &mut errors,
+            )),
+        };
+        let presets = self
+            .presets
+            .into_iter()
+            .filter_map(|(name, filter)| {
+                let filter = checked_filter(Some(filter), &format!("presets.{}", name), &mut errors)?;
+                Some((name, filter))
+            })
+            .collect();
+
+        let channels = ChannelConfig {
+            broadcast_capacity: capacity(self.channels.broadcast_capacity.unwrap_or(100), "BROADCAST_CAPACITY", &mut errors),
+            processor_capacity: capacity(self.channels.processor_capacity.unwrap_or(100), "PROCESSOR_QUEUE_CAPACITY", &mut errors),
+            overflow_capacity: capacity(self.channels.overflow_capacity.unwrap_or(10_000), "PROCESSOR_OVERFLOW_CAPACITY", &mut errors),
+            durable_capacity: capacity(self.channels.durable_capacity.unwrap_or(10_000), "DURABLE_QUEUE_CAPACITY", &mut errors),
+            durable_overflow: match self.channels.durable_overflow.as_deref().map(str::parse::<OverflowPolicy>) {
+                None => OverflowPolicy::Block,
+                Some(Ok(policy)) => policy,
+                Some(Err(e)) => {
+                    errors.push(error_message(e));
+                    OverflowPolicy::Block
+                }
+            },
+            slow_enqueue: Duration::from_millis(positive(
+                self.channels.immediate_slow_enqueue_ms.unwrap_or(50),
+                "IMMEDIATE_SLOW_ENQUEUE_MS",
+                &mut errors,
+            )),
+        };
+
+        let initial_delay_ms = positive(self.reconnect.initial_delay_ms.unwrap_or(5_000), "RECONNECT_INITIAL_DELAY_MS", &mut errors);
+        let max_delay_ms = self.reconnect.max_delay_ms.unwrap_or(60_000);
+        if max_delay_ms < initial_delay_ms {
+            errors.push(format!(
+                "RECONNECT_MAX_DELAY_MS {} is smaller than RECONNECT_INITIAL_DELAY_MS {}",
+                max_delay_ms, initial_delay_ms
+            ));
+        }
+        let multiplier = self.reconnect.multiplier.unwrap_or(2.0);
+        if !multiplier.is_finite() || multiplier < 1.0 {
+            errors.push(format!("RECONNECT_MULTIPLIER {} must be a finite number >= 1.0", multiplier));
+        }
+        let reconnect = ReconnectPolicy {
+            initial_delay: Duration::from_millis(initial_delay_ms),
+            max_delay: Duration::from_millis(max_delay_ms),
+            multiplier,
+        };
+
+        let provider_limit_backoff = Duration::from_secs(positive(
+            self.rpc.provider_limit_backoff_secs.unwrap_or(300),
+            "PROVIDER_LIMIT_BACKOFF_SECS",
+            &mut errors,
+        ));
+        let request_timeout =
+            Duration::from_secs(positive(self.rpc.request_timeout_secs.unwrap_or(10), "RPC_REQUEST_TIMEOUT_SECS", &mut errors));
+        let processing_timeout = Duration::from_secs(positive(
+            self.rpc.processing_timeout_secs.unwrap_or(30),
+            "PROCESSING_TIMEOUT_SECS",
+            &mut errors,
+        ));
+
+        let watchdog = WatchdogConfig {
+            silence_timeout: Duration::from_secs(positive(self.watchdog.silence_secs.unwrap_or(60), "WATCHDOG_SILENCE_SECS", &mut errors)),
+            ping_grace: Duration::from_secs(positive(self.watchdog.ping_grace_secs.unwrap_or(10), "WATCHDOG_PING_GRACE_SECS", &mut errors)),
+            keepalive_interval: Duration::from_secs(positive(self.watchdog.keepalive_secs.unwrap_or(30), "WATCHDOG_KEEPALIVE_SECS", &mut errors)),
+        };
+
+        let restart_delay_ms = positive(self.supervisor.initial_delay_ms.unwrap_or(1_000), "SUPERVISOR_INITIAL_DELAY_MS", &mut errors);
+        let max_restart_delay_ms = self.supervisor.max_delay_ms.unwrap_or(60_000);
+        if max_restart_delay_ms < restart_delay_ms {
+            errors.push(format!(
+                "SUPERVISOR_MAX_DELAY_MS {} is smaller than SUPERVISOR_INITIAL_DELAY_MS {}",
+                max_restart_delay_ms, restart_delay_ms
+            ));
+        }
+        let supervisor = SupervisorConfig {
+            max_restarts: self.supervisor.max_restarts.unwrap_or(5),
+            backoff: ReconnectPolicy {
+                initial_delay: Duration::from_millis(restart_delay_ms),
+                max_delay: Duration::from_millis(max_restart_delay_ms),
+                multiplier: 2.0,
+            },
+        };
+
+        let mint_dedupe = MintDedupeConfig {
+            ttl: Duration::from_secs(positive(self.mint_dedupe.ttl_secs.unwrap_or(3_600), "MINT_DEDUPE_TTL_SECS", &mut errors)),
+            capacity: capacity(self.mint_dedupe.capacity.unwrap_or(100_000), "MINT_DEDUPE_CAPACITY", &mut errors),
+        };
+        let caches = CacheConfig {
+            signatures: capacity(self.caches.signature_capacity.unwrap_or(4_096), "SIGNATURE_CACHE_CAPACITY", &mut errors),
+            creators: capacity(self.caches.creator_capacity.unwrap_or(100_000), "CREATOR_CACHE_CAPACITY", &mut errors),
+            socials: capacity(self.caches.social_capacity.unwrap_or(10_000), "SOCIAL_CACHE_CAPACITY", &mut errors),
+            tokens: capacity(self.caches.token_capacity.unwrap_or(10_000), "TOKEN_REGISTRY_CAPACITY", &mut errors),
+            janitor_interval: Duration::from_secs(positive(
+                self.caches.janitor_interval_secs.unwrap_or(60),
+                "CACHE_JANITOR_INTERVAL_SECS",
+                &mut errors,
+            )),
+        };
+
+        let health_host = self.health.bind.as_deref().unwrap_or("127.0.0.1");
+        let health_addr = self.health.port.and_then(|port| parse_bind_addr(health_host, port, "HEALTH_BIND", &mut errors));
+        let health = health_addr.map(|bind_addr| HealthConfig {
+            bind_addr,
+            max_silence: Duration::from_secs(positive(
+                self.health.max_silence_secs.unwrap_or(60),
+                "HEALTH_MAX_SILENCE_SECS",
+                &mut errors,
+            )),
+        });
+
+        let price_feed = self.price_feed.source.as_deref().and_then(|source| {
+            let source = match source.parse::<PriceSource>() {
+                Ok(source) => source,
+                Err(e) => {
+                    errors.push(error_message(e));
+                    return None;
+                }
+            };
+            Some(PriceFeedConfig {
+                source,
+                pyth_account: parse_pubkey(
+                    self.price_feed.pyth_account.as_deref().unwrap_or(DEFAULT_PYTH_SOL_USD_ACCOUNT),
+                    "PYTH_SOL_USD_ACCOUNT",
+                    &mut errors,
+                )
+                .unwrap_or_default(),
+                refresh_interval: Duration::from_secs(positive(
+                    self.price_feed.refresh_secs.unwrap_or(30),
+                    "SOL_PRICE_REFRESH_SECS",
+                    &mut errors,
+                )),
+                max_age: Duration::from_secs(positive(
+                    self.price_feed.max_age_secs.unwrap_or(120),
+                    "SOL_PRICE_MAX_AGE_SECS",
+                    &mut errors,
+                )),
+            })
+        });
+
+        let kafka = match (self.kafka.brokers, self.kafka.topic) {
+            (Some(_), Some(_)) if !cfg!(feature = "kafka") => {
+                errors.push("KAFKA_BROKERS is set but this build does not include the `kafka` feature".to_string());
+                None
+            }
+            (Some(brokers), Some(topic)) => Some(KafkaConfig {
+                brokers,
+                topic,
+                message_timeout: Duration::from_millis(positive(
+                    self.kafka.message_timeout_ms.unwrap_or(30_000),
+                    "KAFKA_MESSAGE_TIMEOUT_MS",
+                    &mut errors,
+                )),
+                filter: checked_filter(self.kafka.filter, "kafka", &mut errors),
+                stale: stale_policy(self.kafka.stale, "kafka.stale", &mut errors),
+            }),
+            (Some(_), None) => {
+                errors.push("KAFKA_BROKERS is set but KAFKA_TOPIC is not".to_string());
+                None
+            }
+            (None, Some(_)) => {
+                errors.push("KAFKA_TOPIC is set but KAFKA_BROKERS is not".to_string());
+                None
+            }
+            (None, None) => None,
+        };
+
+        let nats = match self.nats.url {
+            Some(_) if !cfg!(feature = "nats") => {
+                errors.push("NATS_URL is set but this build does not include the `nats` feature".to_string());
+                None
+            }
+            Some(url) => {
+                let subject_prefix = self.nats.subject_prefix.unwrap_or_else(|| "pumpfun.token.created".to_string());
+                let valid_prefix = !subject_prefix.is_empty()
+                    && subject_prefix.split('.').all(|token| {
+                        !token.is_empty() && token != "*" && token != ">" && !token.contains(char::is_whitespace)
+                    });
+                if !valid_prefix {
+                    errors.push(format!(
+                        "NATS_SUBJECT_PREFIX '{}' must be dot-separated tokens without wildcards or whitespace",
+                        subject_prefix
+                    ));
+                }
+                Some(NatsConfig {
+                    url,
+                    subject_prefix,
+                    jetstream: self.nats.jetstream.unwrap_or(false),
+                    pending_capacity: positive(self.nats.pending_capacity.unwrap_or(2048), "NATS_PENDING_CAPACITY", &mut errors),
+                    filter: checked_filter(self.nats.filter, "nats", &mut errors),
+                    stale: stale_policy(self.nats.stale, "nats.stale", &mut errors),
+                })
+            }
+            None => None,
+        };
+
+        let mut discord_routes: Vec<DiscordRoute> = self
+            .discord
+            .webhook_url
+            .into_iter()
+            .map(|webhook_url| DiscordRoute {
+                webhook_url,
+                filter: FilterCriteria::default(),
+            })
+            .collect();
+        for (index, route) in self.discord.routes.into_iter().enumerate() {
+            let filter = checked_filter(Some(route.filter), &format!("discord.routes[{}]", index), &mut errors);
+            discord_routes.push(DiscordRoute {
+                webhook_url: route.webhook_url,
+                filter: filter.unwrap_or_default(),
+            });
+        }
+        for route in &discord_routes {
+            // the URL itself is a secret, so problems are reported without it
+            if !Url::parse(&route.webhook_url).is_ok_and(|url| url.scheme() == "https") {
+                errors.push("a Discord webhook URL is not a valid https URL".to_string());
+            }
+        }
+        let discord_filter = checked_filter(self.discord.filter, "discord", &mut errors);
+        let discord_stale = stale_policy(self.discord.stale, "discord.stale", &mut errors);
+        let discord_tier = sink_tier(self.discord.tier, "discord.tier", &mut errors);
+        let discord = (!discord_routes.is_empty()).then(|| DiscordConfig {
+            routes: discord_routes,
+            queue_capacity: positive(self.discord.queue_capacity.unwrap_or(100), "DISCORD_QUEUE_CAPACITY", &mut errors),
+            filter: discord_filter,
+            stale: discord_stale,
+            tier: discord_tier,
+        });
+
+        let mut telegram_routes: Vec<TelegramRoute> = self
+            .telegram
+            .chat_id
+            .into_iter()
+            .map(|chat_id| TelegramRoute {
+                chat_id,
+                filter: FilterCriteria::default(),
+            })
+            .collect();
+        for (index, route) in self.telegram.routes.into_iter().enumerate() {
+            let filter = checked_filter(Some(route.filter), &format!("telegram.routes[{}]", index), &mut errors);
+            telegram_routes.push(TelegramRoute {
+                chat_id: route.chat_id,
+                filter: filter.unwrap_or_default(),
+            });
+        }
+        for route in &telegram_routes {
+            let chat_id = route.chat_id.trim();
+            if chat_id.parse::<i64>().is_err() && !(chat_id.starts_with('@') && chat_id.len() > 1) {
+                errors.push(format!("Telegram chat id '{}' must be numeric or an @channel username", route.chat_id));
+            }
+        }
+        let telegram_filter = checked_filter(self.telegram.filter, "telegram", &mut errors);
+        let telegram_stale = stale_policy(self.telegram.stale, "telegram.stale", &mut errors);
+        let telegram_tier = sink_tier(self.telegram.tier, "telegram.tier", &mut errors);
+        let telegram = match (self.telegram.bot_token, telegram_routes.is_empty()) {
+            (Some(bot_token), false) => Some(TelegramConfig {
+                bot_token,
+                routes: telegram_routes,
+                queue_capacity: positive(self.telegram.queue_capacity.unwrap_or(100), "TELEGRAM_QUEUE_CAPACITY", &mut errors),
+                filter: telegram_filter,
+                stale: telegram_stale,
+                tier: telegram_tier,
+            }),
+            (Some(_), true) => {
+                errors.push("TELEGRAM_BOT_TOKEN is set but no chat is configured (TELEGRAM_CHAT_ID or telegram.routes)".to_string());
+                None
+            }
+            (None, false) => {
+                errors.push("Telegram chats are configured but TELEGRAM_BOT_TOKEN is not set".to_string());
+                None
+            }
+            (None, true) => None,
+        };
+
+        let events_log = self.events_log.path.map(|path| {
+            if path.file_name().is_none() {
+                errors.push(format!(
+                    "EVENTS_LOG_PATH '{}' must name a file, e.g. /var/lib/pump_fun_monitor/events.jsonl",
+                    path.display()
+                ));
+            }
+            let rotation = match self.events_log.rotation.as_deref().map(str::parse::<LogRotation>) {
+                None => LogRotation::Daily,
+                Some(Ok(rotation)) => rotation,
+                Some(Err(e)) => {
+                    errors.push(error_message(e));
+                    LogRotation::Daily
+                }
+            };
+            let fsync = match self.events_log.fsync.as_deref().map(str::parse::<FsyncPolicy>) {
+                None => FsyncPolicy::Interval,
+                Some(Ok(fsync)) => fsync,
+                Some(Err(e)) => {
+                    errors.push(error_message(e));
+                    FsyncPolicy::Interval
+                }
+            };
+            EventsLogConfig {
+                path,
+                rotation,
+                max_bytes: self.events_log.max_bytes.map(|max_bytes| positive(max_bytes, "EVENTS_LOG_MAX_BYTES", &mut errors)),
+                fsync,
+                queue_capacity: positive(self.events_log.queue_capacity.unwrap_or(1000), "EVENTS_LOG_QUEUE_CAPACITY", &mut errors),
+                filter: checked_filter(self.events_log.filter, "events_log", &mut errors),
+                stale: stale_policy(self.events_log.stale, "events_log.stale", &mut errors),
+            }
+        });
+
+        // only the scheme is ever reported, the URL may carry a password
+        let postgres = match self.database.url {
+            Some(url) => match Url::parse(&url).map(|parsed| parsed.scheme().to_string()) {
+                Ok(scheme) if scheme == "postgres" || scheme == "postgresql" => {
+                    if !cfg!(feature = "postgres") {
+                        errors.push("DATABASE_URL is a postgres URL but this build does not include the `postgres` feature".to_string());
+                    }
+                    let batch_size = positive(self.database.batch_size.unwrap_or(100), "DATABASE_BATCH_SIZE", &mut errors);
+                    if batch_size > MAX_DATABASE_BATCH_SIZE {
+                        errors.push(format!("DATABASE_BATCH_SIZE {} is above the limit of {}", batch_size, MAX_DATABASE_BATCH_SIZE));
+                    }
+                    Some(PostgresConfig {
+                        url,
+                        max_connections: positive(self.database.max_connections.unwrap_or(5), "DATABASE_MAX_CONNECTIONS", &mut errors),
+                        batch_size,
+                        filter: checked_filter(self.database.filter, "database", &mut errors),
+                        stale: stale_policy(self.database.stale, "database.stale", &mut errors),
+                    })
+                }
+                Ok(scheme) if scheme == "sqlite" => {
+                    errors.push("DATABASE_URL uses sqlite, which has no sink in this build; use a postgres:// URL".to_string());
+                    None
+                }
+                Ok(scheme) => {
+                    errors.push(format!("DATABASE_URL scheme '{}' is not one of: postgres, postgresql", scheme));
+                    None
+                }
+                Err(_) => {
+                    errors.push("DATABASE_URL is not a valid URL".to_string());
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let grpc = match self.grpc.port {
+            Some(_) if !cfg!(feature = "grpc") => {
+                errors.push("GRPC_PORT is set but this build does not include the `grpc` feature".to_string());
+                None
+            }
+            Some(port) => {
+                if port != 0 && Some(port) == ws_port {
+                    errors.push(format!("GRPC_PORT {} is already used by WEBSOCKET_SERVER_PORT", port));
+                }
+                api_ip.map(|ip| GrpcConfig {
+                    bind_addr: SocketAddr::new(ip, port),
+                })
+            }
+            None => None,
+        };
+
+        let http_api = self.http_api.port.and_then(|port| {
+            if port != 0 && Some(port) == ws_port {
+                errors.push(format!("HTTP_API_PORT {} is already used by WEBSOCKET_SERVER_PORT", port));
+            }
+            let store_capacity = positive(self.http_api.store_capacity.unwrap_or(10_000), "EVENT_STORE_CAPACITY", &mut errors);
+            api_ip.map(|ip| HttpApiConfig {
+                bind_addr: SocketAddr::new(ip, port),
+                store_capacity,
+            })
+        });
+
+        let metadata = MetadataConfig {
+            keep_raw: self.metadata.keep_raw.unwrap_or(false),
+            enrichment: self.metadata.enrichment.unwrap_or(false),
+            fetch_timeout: Duration::from_millis(positive(
+                self.metadata.fetch_timeout_ms.unwrap_or(3000),
+                "METADATA_FETCH_TIMEOUT_MS",
+                &mut errors,
+            )),
+            ipfs_gateway: match self.metadata.ipfs_gateway {
+                Some(gateway) if validate_url(&gateway, "IPFS_GATEWAY", &["https", "http"], &mut errors) => {
+                    // a CID is appended as the last path segment
+                    if gateway.ends_with('/') {
+                        gateway
+                    } else {
+                        format!("{}/", gateway)
+                    }
+                }
+                Some(_) => String::new(),
+                None => DEFAULT_IPFS_GATEWAY.to_string(),
+            },
+        };
+
+        if metadata.enrichment && detail_level == DetailLevel::Minimal {
+            errors.push("METADATA_ENRICHMENT cannot be combined with DETAIL_LEVEL=minimal".to_string());
+        }
+
+        let lookback_signatures = self.creator_history.lookback_signatures.unwrap_or(0);
+        if lookback_signatures > MAX_LOOKBACK_SIGNATURES {
+            errors.push(format!(
+                "CREATOR_HISTORY_LOOKBACK {} exceeds the {} signatures getSignaturesForAddress returns",
+                lookback_signatures, MAX_LOOKBACK_SIGNATURES
+            ));
+        }
+        let abandoned_after_secs = self.creator_history.abandoned_after_secs.unwrap_or(86_400);
+        if abandoned_after_secs > MAX_ABANDONED_AFTER_SECS {
+            errors.push(format!(
+                "CREATOR_ABANDONED_AFTER_SECS {} is above the limit of {}",
+                abandoned_after_secs, MAX_ABANDONED_AFTER_SECS
+            ));
+        }
+        let abandoned_after =
+            Duration::from_secs(positive(abandoned_after_secs, "CREATOR_ABANDONED_AFTER_SECS", &mut errors));
+        let creator_history = self.creator_history.enabled.unwrap_or(false).then_some(CreatorHistoryConfig {
+            file: self.creator_history.file,
+            lookback_signatures,
+            abandoned_after,
+        });
+
+        let parser_self_test = self.parser_self_test.enabled.unwrap_or(false).then(|| {
+            if replaying || simulating {
+                errors.push(
+                    "PARSER_SELF_TEST needs an RPC and cannot be combined with REPLAY_FIXTURES or SIMULATION_MODE"
+                        .to_string(),
+                );
+            }
+            let signatures = positive(
+                self.parser_self_test.signatures.unwrap_or(MAX_LOOKBACK_SIGNATURES),
+                "PARSER_SELF_TEST_SIGNATURES",
+                &mut errors,
+            );
+            if signatures > MAX_LOOKBACK_SIGNATURES {
+                errors.push(format!(
+                    "PARSER_SELF_TEST_SIGNATURES {} exceeds the {} signatures getSignaturesForAddress returns",
+                    signatures, MAX_LOOKBACK_SIGNATURES
+                ));
+            }
+            let min_transactions = positive(
+                self.parser_self_test.min_transactions.unwrap_or(500),
+                "PARSER_SELF_TEST_MIN_TRANSACTIONS",
+                &mut errors,
+            );
+            if min_transactions > signatures {
+                errors.push(format!(
+                    "PARSER_SELF_TEST_MIN_TRANSACTIONS {} exceeds the {} PARSER_SELF_TEST_SIGNATURES fetched",
+                    min_transactions, signatures
+                ));
+            }
+            ParserSelfTestConfig {
+                signatures,
+                min_transactions,
+                // 0 only checks at startup
+                interval: match self.parser_self_test.interval_secs.unwrap_or(3_600) {
+                    0 => None,
+                    secs => Some(Duration::from_secs(secs)),
+                },
+            }
+        });
+
+        let spam = match self.spam.suppression.as_deref().map(str::parse::<SpamSuppression>) {
+            None | Some(Ok(SpamSuppression::Off)) => None,
+            Some(Ok(suppression)) => Some(SpamConfig {
+                suppression,
+                window: Duration::from_secs(positive(self.spam.window_secs.unwrap_or(300), "SPAM_WINDOW_SECS", &mut errors)),
+                capacity: capacity(self.spam.capacity.unwrap_or(2_000), "SPAM_WINDOW_CAPACITY", &mut errors),
+            }),
+            Some(Err(e)) => {
+                errors.push(error_message(e));
+                None
+            }
+        };
+
+        let curve_tracking = self.curve_tracking.enabled.unwrap_or(false).then(|| {
+            if replaying || simulating {
+                errors.push("CURVE_TRACKING needs an RPC and cannot be combined with REPLAY_FIXTURES or SIMULATION_MODE".to_string());
+            }
+            CurveTrackingConfig {
+                filter: checked_filter(self.curve_tracking.filter, "curve_tracking", &mut errors),
+                max_subscriptions: positive(
+                    self.curve_tracking.max_subscriptions.unwrap_or(100),
+                    "CURVE_TRACKING_MAX_SUBSCRIPTIONS",
+                    &mut errors,
+                ),
+                ttl: Duration::from_secs(positive(
+                    self.curve_tracking.ttl_secs.unwrap_or(3_600),
+                    "CURVE_TRACKING_TTL_SECS",
+                    &mut errors,
+                )),
+                update_interval: Duration::from_millis(self.curve_tracking.update_interval_ms.unwrap_or(1_000)),
+            }
+        });
+
+        let curve_snapshot = self.curve_snapshot.enabled.unwrap_or(false).then(|| {
+            if replaying || simulating {
+                errors.push("CURVE_SNAPSHOT needs an RPC and cannot be combined with REPLAY_FIXTURES or SIMULATION_MODE".to_string());
+            }
+            CurveSnapshotConfig {
+                file: self.curve_snapshot.file.unwrap_or_else(|| PathBuf::from("curve_snapshot.jsonl")),
+                partitioned: self.curve_snapshot.partitioned.unwrap_or(true),
+            }
+        });
+
+        let image_hashing = self.image_hashing.enabled.unwrap_or(false).then(|| {
+            // the image link comes from the fetched metadata document
+            if !metadata.enrichment && detail_level != DetailLevel::Full {
+                errors.push("IMAGE_HASHING needs METADATA_ENRICHMENT".to_string());
+            }
+            ImageHashConfig {
+                max_bytes: positive(self.image_hashing.max_bytes.unwrap_or(5 * 1024 * 1024), "IMAGE_MAX_BYTES", &mut errors),
+                fetch_timeout: Duration::from_millis(positive(
+                    self.image_hashing.fetch_timeout_ms.unwrap_or(5_000),
+                    "IMAGE_FETCH_TIMEOUT_MS",
+                    &mut errors,
+                )),
+                wait: Duration::from_millis(self.image_hashing.wait_ms.unwrap_or(250)),
+                concurrency: positive(self.image_hashing.concurrency.unwrap_or(8), "IMAGE_DOWNLOAD_CONCURRENCY", &mut errors),
+                capacity: capacity(self.image_hashing.capacity.unwrap_or(10_000), "IMAGE_HASH_CAPACITY", &mut errors),
+                follow_ups: self.image_hashing.follow_ups.unwrap_or(false),
+            }
+        });
+
+        let watchlist = self.watchlist.file.filter(|file| !file.as_os_str().is_empty()).and_then(|file| {
+            match Watchlist::load(&file) {
+                Ok(watchlist) => Some(Arc::new(watchlist)),
+                Err(problems) => {
+                    errors.extend(problems.iter().map(|problem| format!("WATCHLIST_FILE {}: {}", file.display(), problem)));
+                    None
+                }
+            }
+        });
+
+        let blocklist_file = self.global_filter.creator_blocklist.filter(|file| !file.as_os_str().is_empty());
+        let creator_blocklist = blocklist_file.and_then(|file| match CreatorBlocklist::load(&file) {
+            Ok(blocklist) => Some(blocklist),
+            Err(problems) => {
+                let name = format!("GLOBAL_FILTER_CREATOR_BLOCKLIST {}", file.display());
+                errors.extend(problems.iter().map(|problem| format!("{}: {}", name, problem)));
+                None
+            }
+        });
+        let min_real_sol_reserves = self.global_filter.min_real_sol_reserves;
+        if min_real_sol_reserves.is_some() && detail_level == DetailLevel::Minimal {
+            errors.push("GLOBAL_FILTER_MIN_REAL_SOL_RESERVES needs DETAIL_LEVEL standard or full".to_string());
+        }
+        if let Some(min) = min_real_sol_reserves.filter(|min| !min.is_finite() || *min < 0.0) {
+            errors.push(format!("GLOBAL_FILTER_MIN_REAL_SOL_RESERVES ({}) must be a non-negative number", min));
+        }
+        let global_filter = GlobalFilter {
+            name_include: pattern(self.global_filter.name_include, "GLOBAL_FILTER_NAME_INCLUDE", &mut errors),
+            name_exclude: pattern(self.global_filter.name_exclude, "GLOBAL_FILTER_NAME_EXCLUDE", &mut errors),
+            symbol_include: pattern(self.global_filter.symbol_include, "GLOBAL_FILTER_SYMBOL_INCLUDE", &mut errors),
+            symbol_exclude: pattern(self.global_filter.symbol_exclude, "GLOBAL_FILTER_SYMBOL_EXCLUDE", &mut errors),
+            creator_blocklist,
+            min_real_sol_reserves,
+        };
+        let global_filter = (!global_filter.is_empty()).then(|| Arc::new(global_filter));
+
+        let max_event_age = self
+            .freshness
+            .max_event_age_secs
+            .map(|secs| Duration::from_secs(positive(secs, "MAX_EVENT_AGE_SECS", &mut errors)));
+        let event_format = match self.events.format.as_deref().map(str::parse::<EventFormat>) {
+            None => EventFormat::Flat,
+            Some(Ok(format)) => format,
+            Some(Err(e)) => {
+                errors.push(format!("EVENT_FORMAT {}", error_message(e)));
+                EventFormat::Flat
+            }
+        };
+
+        // the key itself never goes into an error message
+        let event_signer = self.signing.key.filter(|key| !key.trim().is_empty()).and_then(|key| {
+            match EventSigner::load(&key) {
+                Ok(signer) => Some(Arc::new(signer)),
+                Err(e) => {
+                    errors.push(format!("EVENT_SIGNING_KEY {}", e));
+                    None
+                }
+            }
+        });
+
+        let log_level = self.logging.level.filter(|level| !level.trim().is_empty());
+        if let Some(Err(e)) = log_level.as_deref().map(tracing_subscriber::EnvFilter::try_new) {
+            errors.push(format!("RUST_LOG is not a valid log filter: {}", e));
+        }
+
+        let dead_letters = self.dead_letters.dir.filter(|dir| !dir.as_os_str().is_empty()).map(|dir| DeadLetterConfig {
+            dir,
+            max_entries: positive(self.dead_letters.max_entries.unwrap_or(10_000), "DEAD_LETTER_MAX_ENTRIES", &mut errors),
+            retention: Duration::from_secs(
+                3_600 * positive(self.dead_letters.retention_hours.unwrap_or(168), "DEAD_LETTER_RETENTION_HOURS", &mut errors),
+            ),
+        });
+
+        if self.fixtures.record_dir.is_some() && replaying {
+            errors.push("RECORD_FIXTURES and REPLAY_FIXTURES cannot be used together".to_string());
+        }
+        let replay_speed = self.fixtures.replay_speed.unwrap_or(1.0);
+        if !replay_speed.is_finite() || replay_speed < 0.0 {
+            errors.push(format!("REPLAY_SPEED {} must be zero or a positive number", replay_speed));
+        }
+        let replay = self.fixtures.replay_dir.map(|dir| {
+            if !dir.join("notifications.jsonl").is_file() {
+                errors.push(format!(
+                    "REPLAY_FIXTURES '{}' does not contain a notifications.jsonl recording",
+                    dir.display()
+                ));
+            }
+            ReplayConfig {
+                dir,
+                speed: replay_speed,
+            }
+        });
+        let fixtures = FixtureConfig {
+            record_dir: self.fixtures.record_dir,
+            replay,
+        };
+
+        let simulation = simulating.then(|| {
+            if fixtures.record_dir.is_some() || fixtures.replay.is_some() {
+                errors.push("SIMULATION_MODE cannot be combined with RECORD_FIXTURES or REPLAY_FIXTURES".to_string());
+            }
+            // generated events have no log notification to preview
+            if self.rpc.fast_path.unwrap_or(false) {
+                errors.push("SIMULATION_MODE cannot be combined with FAST_PATH".to_string());
+            }
+            let events_per_sec = self.simulation.events_per_sec.unwrap_or(1.0);
+            if !events_per_sec.is_finite() || events_per_sec <= 0.0 {
+                errors.push(format!("SIMULATION_EVENTS_PER_SEC {} must be a positive number", events_per_sec));
+            }
+            let burst = self.simulation.burst_events.filter(|&events| events > 0).map(|events| BurstConfig {
+                events,
+                interval: Duration::from_secs(positive(
+                    self.simulation.burst_interval_secs.unwrap_or(60),
+                    "SIMULATION_BURST_INTERVAL_SECS",
+                    &mut errors,
+                )),
+            });
+            SimulationConfig {
+                events_per_sec,
+                seed: self.simulation.seed,
+                burst,
+            }
+        });
+
+        match (http_url, wss_endpoints, bind_addr) {
+            (Some(http_url), Some(wss_endpoints), Some(bind_addr)) if errors.is_empty() => Ok(Config {
+                rpc: RpcConfig {
+                    http_url,
+                    http_label,
+                    wss_url: wss_endpoints.first().map_or_else(String::new, |endpoint| endpoint.url.clone()),
+                    wss_endpoints,
+                    programs,
+                    commitment,
+                    detail_level,
+                    latency_fields: self.rpc.latency_fields.unwrap_or(false),
+                    fast_path: self.rpc.fast_path.unwrap_or(false),
+                    provider_limit_backoff,
+                    request_timeout,
+                    processing_timeout,
+                    diagnostics_max_per_sec: self.rpc.diagnostics_max_per_sec.unwrap_or(20),
+                },
+                server: ServerConfig {
+                    bind_addr,
+                    tls,
+                    compression: self.server.compression.unwrap_or(true),
+                    admin_token,
+                    max_clients,
+                    ip_limits,
+                    origins,
+                    presets: Arc::new(presets),
+                    replay_capacity,
+                    client_queue_capacity,
+                    raw_transactions: self.server.raw_transactions.unwrap_or(false),
+                    heartbeat_interval,
+                    sessions,
+                    broadcast_shards,
+                    stale_events,
+                    event_format,
+                },
+                channels,
+                reconnect,
+                watchdog,
+                supervisor,
+                mint_dedupe,
+                caches,
+                health,
+                price_feed,
+                sinks: SinksConfig {
+                    kafka,
+                    nats,
+                    discord,
+                    telegram,
+                    events_log,
+                    postgres,
+                    event_format,
+                },
+                grpc,
+                http_api,
+                metadata,
+                creator_history,
+                parser_self_test,
+                spam,
+                curve_tracking,
+                curve_snapshot,
+                image_hashing,
+                watchlist,
+                global_filter,
+                max_event_age,
+                event_signer,
+                dead_letters,
+                fixtures,
+                simulation,
+                log_level,
+            }),
+            _ => Err(MonitorError::Config(format_errors(&errors))),
+        }
+    }
+}
+
+/// replaces `target` with the parsed value of environment variable `name`, recording a message if it does not parse.
+fn env_override<T: FromStr>(
+    target: &mut Option<T>,
+    name: &str,
+    env: &impl Fn(&str) -> Option<String>,
+    errors: &mut Vec<String>,
+) {
+    let Some(value) = env(name) else {
+        return;
+    };
+    match value.trim().parse::<T>() {
+        Ok(parsed) => *target = Some(parsed),
+        Err(_) => errors.push(format!(
+            "{} '{}' is not a valid {}",
+            name,
+            value,
+            std::any::type_name::<T>()
+        )),
+    }
+}
+
+/// records a "not set" message for a missing value, unless its override was already reported as invalid.
+fn required<T>(value: Option<T>, name: &str, errors: &mut Vec<String>) -> Option<T> {
+    let already_reported = errors.iter().any(|error| error.starts_with(&format!("{} ", name)));
+    if value.is_none() && !already_reported {
+        errors.push(format!("{} is not set", name));
+    }
+    value
+}
+
+fn positive<T: PartialEq + Default + Copy>(value: T, name: &str, errors: &mut Vec<String>) -> T {
+    if value == T::default() {
+        errors.push(format!("{} must be greater than zero", name));
+    }
+    value
+}
+
+/// largest capacity any queue or buffer may be given; the broadcast channel allocates its slots up front, so a typo
+/// with a few extra zeros would otherwise take the memory with it.
+const MAX_CAPACITY: usize = 10_000_000;
+
+/// a queue or buffer capacity, which must be at least one and at most `MAX_CAPACITY`.
+fn capacity(value: usize, name: &str, errors: &mut Vec<String>) -> usize {
+    if value > MAX_CAPACITY {
+        errors.push(format!("{} must be at most {}", name, MAX_CAPACITY));
+    }
+    positive(value, name, errors)
+}
+
+/// the stale policy `name` is set to, `tag` when unset; a value that isn't one is recorded as a problem.
+fn stale_policy(value: Option<String>, name: &str, errors: &mut Vec<String>) -> StalePolicy {
+    match value.as_deref().map(str::parse::<StalePolicy>) {
+        None => StalePolicy::Tag,
+        Some(Ok(policy)) => policy,
+        Some(Err(e)) => {
+            errors.push(format!("{} {}", name, error_message(e)));
+            StalePolicy::Tag
+        }
+    }
+}
+
+/// the tier `name` puts a sink in, tier 1 when unset; anything but 0 or 1 is recorded as a problem.
+fn sink_tier(value: Option<u8>, name: &str, errors: &mut Vec<String>) -> Tier {
+    match value {
+        None | Some(1) => Tier::Queued,
+        Some(0) => Tier::Immediate,
+        Some(other) => {
+            errors.push(format!("{} {} is not one of: 0, 1", name, other));
+            Tier::Queued
+        }
+    }
+}
+
+/// normalizes a sink's filter, recording every problem with it under the name of the sink it belongs to.
+fn checked_filter(filter: Option<FilterCriteria>, sink: &str, errors: &mut Vec<String>) -> Option<FilterCriteria> {
+    let filter = filter?;
+    match normalize_filter(filter.clone()) {
+        Ok(filter) => Some(filter),
+        Err(problems) => {
+            for problem in problems {
+                errors.push(format!("{} filter: {}", sink, problem));
+            }
+            Some(filter)
+        }
+    }
+}
+
+/// checks that `value` is an absolute URL using one of `schemes`, recording a message otherwise.
+fn validate_url(value: &str, name: &str, schemes: &[&str], errors: &mut Vec<String>) -> bool {
+    match Url::parse(value) {
+        Ok(url) if schemes.contains(&url.scheme()) => true,
+        Ok(url) => {
+            errors.push(format!(
+                "{} '{}' uses scheme '{}', expected {}",
+                name,
+                value,
+                url.scheme(),
+                schemes.join(" or ")
+            ));
+            false
+        }
+        Err(e) => {
+            errors.push(format!("{} '{}' is not a valid URL ({})", name, value, e));
+            false
+        }
+    }
+}
+
+/// resolves `host` and `port` into a socket address; IPv6 hosts may be given with or without brackets.
+fn parse_bind_addr(host: &str, port: u16, name: &str, errors: &mut Vec<String>) -> Option<SocketAddr> {
+    let host = host.trim();
+    let bare_host = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = bare_host.parse::<std::net::IpAddr>() {
+        return Some(SocketAddr::new(ip, port));
+    }
+
+    match (host, port).to_socket_addrs().map(|mut addrs| addrs.next()) {
+        Ok(Some(addr)) => Some(addr),
+        _ => {
+            errors.push(format!("{} '{}' is not a valid IP address or resolvable host", name, host));
+            None
+        }
+    }
+}
+
+/// the Unix socket path of a `unix:` host, which must name a file in an existing directory.
+fn parse_socket_path(path: &str, errors: &mut Vec<String>) -> Option<ListenAddr> {
+    if !cfg!(unix) {
+        errors.push("WEBSOCKET_SERVER_HOST unix: sockets are not supported on this platform".to_string());
+        return None;
+    }
+    let path = PathBuf::from(path.trim());
+    if path.file_name().is_none() {
+        errors.push(format!("WEBSOCKET_SERVER_HOST unix:{} does not name a socket file", path.display()));
+        return None;
+    }
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    if !dir.is_dir() {
+        errors.push(format!("WEBSOCKET_SERVER_HOST unix:{}: directory {} does not exist", path.display(), dir.display()));
+        return None;
+    }
+    Some(ListenAddr::Unix(path))
+}
+
+fn parse_pubkey(value: &str, name: &str, errors: &mut Vec<String>) -> Option<Pubkey> {
+    match Pubkey::from_str(value.trim()) {
+        Ok(pubkey) => Some(pubkey),
+        Err(_) => {
+            errors.push(format!("{} '{}' is not a valid base58 public key", name, value));
+            None
+        }
+    }
+}
+
+/// parses the comma-separated `WS_ALLOWED_ORIGINS`; an empty list accepts every origin.
+fn parse_allowed_origins(list: &str, errors: &mut Vec<String>) -> Vec<AllowedOrigin> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| match entry.parse() {
+            Ok(origin) => Some(origin),
+            Err(e) => {
+                errors.push(format!("WS_ALLOWED_ORIGINS entry {}", e));
+                None
+            }
+        })
+        .collect()
+}
+
+/// whether `label` can name an RPC endpoint: not empty, only letters, digits, '-' and '_'.
+fn is_endpoint_label(label: &str) -> bool {
+    !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// parses the comma-separated `label=url` pairs of `SOLANA_RPC_WSS_ENDPOINTS`; `None` when no endpoint was usable.
+fn parse_wss_endpoints(list: &str, errors: &mut Vec<String>) -> Option<Vec<WssEndpoint>> {
+    let name = "SOLANA_RPC_WSS_ENDPOINTS";
+    let reported = errors.len();
+    let mut endpoints: Vec<WssEndpoint> = Vec::new();
+    for entry in list.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let Some((label, url)) = entry.split_once('=').map(|(label, url)| (label.trim(), url.trim())) else {
+            errors.push(format!("{} entry '{}' is not label=url", name, entry));
+            continue;
+        };
+        if !is_endpoint_label(label) {
+            errors.push(format!("{} label '{}' may only use letters, digits, '-' and '_'", name, label));
+            continue;
+        }
+        if endpoints.iter().any(|endpoint| endpoint.label == label) {
+            errors.push(format!("{} lists label '{}' more than once", name, label));
+            continue;
+        }
+        if validate_url(url, name, &["ws", "wss"], errors) {
+            endpoints.push(WssEndpoint {
+                label: label.to_string(),
+                url: url.to_string(),
+            });
+        }
+    }
+    if endpoints.is_empty() {
+        if errors.len() == reported {
+            errors.push(format!("{} lists no endpoints", name));
+        }
+        return None;
+    }
+    Some(endpoints)
+}
+
+/// parses the comma-separated `PUMP_FUN_PROGRAM_ID` list, every program migrating to `migration_program`, and applies
+/// the discriminator overrides from `[rpc.parsers]`.
+fn parse_programs(
+    list: &str,
+    parsers: HashMap<String, FileParser>,
+    migration_program: Option<Pubkey>,
+    errors: &mut Vec<String>,
+) -> Vec<ProgramConfig> {
+    let mut programs: Vec<ProgramConfig> = Vec::new();
+    for value in list.split(',').map(str::trim) {
+        let Some(id) = parse_pubkey(value, "PUMP_FUN_PROGRAM_ID", errors) else {
+            continue;
+        };
+        if programs.iter().any(|program| program.id == id) {
+            errors.push(format!("PUMP_FUN_PROGRAM_ID lists {} more than once", id));
+            continue;
+        }
+        programs.push(ProgramConfig { migration_program, ..ProgramConfig::new(id) });
+    }
+
+    for (key, parser) in parsers {
+        let name = format!("rpc.parsers.{}", key);
+        let Some(program) = programs.iter_mut().find(|program| program.id.to_string() == key) else {
+            errors.push(format!("{} is not one of the programs in PUMP_FUN_PROGRAM_ID", name));
+            continue;
+        };
+        let overrides = [
+            (parser.create_instruction, "create_instruction", &mut program.create_instruction),
+            (parser.buy_instruction, "buy_instruction", &mut program.buy_instruction),
+            (parser.create_event, "create_event", &mut program.create_event),
+            (parser.bonding_curve_account, "bonding_curve_account", &mut program.bonding_curve_account),
+            (parser.migrate_instruction, "migrate_instruction", &mut program.migrate_instruction),
+            (parser.migration_event, "migration_event", &mut program.migration_event),
+        ];
+        for (value, field, target) in overrides {
+            if let Some(discriminator) = value.and_then(|value| parse_discriminator(&value, &format!("{}.{}", name, field), errors)) {
+                *target = discriminator;
+            }
+        }
+    }
+    programs
+}
+
+/// parses an 8-byte Anchor discriminator written as 16 hex digits.
+fn parse_discriminator(value: &str, name: &str, errors: &mut Vec<String>) -> Option<[u8; 8]> {
+    let hex = value.trim();
+    let mut discriminator = [0u8; 8];
+    let valid = hex.len() == 16
+        && hex.is_ascii()
+        && discriminator.iter_mut().enumerate().all(|(index, byte)| {
+            u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16)
+                .map(|parsed| *byte = parsed)
+                .is_ok()
+        });
+    if !valid {
+        errors.push(format!("{} '{}' is not an 8-byte discriminator in hex", name, value));
+        return None;
+    }
+    Some(discriminator)
+}
+
+/// renders the collected problems as one human-readable message, one problem per line.
+fn format_errors(errors: &[String]) -> String {
+    let count = if errors.len() == 1 {
+        "1 problem".to_string()
+    } else {
+        format!("{} problems", errors.len())
+    };
+    let lines: String = errors.iter().map(|error| format!("\n  - {}", error)).collect();
+    format!("{} found:{}", count, lines)
+}
+
+/// compiles the regex of setting `name`, recording a message if it doesn't compile; an empty one is no rule at all.
+fn pattern(source: Option<String>, name: &str, errors: &mut Vec<String>) -> Option<Pattern> {
+    let source = source.filter(|source| !source.is_empty())?;
+    match source.parse() {
+        Ok(pattern) => Some(pattern),
+        Err(e) => {
+            errors.push(format!("{} {}", name, error_message(e)));
+            None
+        }
+    }
+}
+
+/// unwraps the message of a `MonitorError::Config` so it can be merged into the aggregated list.
+fn error_message(error: MonitorError) -> String {
+    match error {
+        MonitorError::Config(message) => message,
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,439 @@
+//! # Curve Tracker
+//!
+//! Follows the bonding curves of new tokens after their creation and reports their progress as `curveUpdate`
+//! messages, so clients can watch a token fill up without polling the RPC. Tracking is off unless `CURVE_TRACKING` is
+//! on, and only WebSocket clients that sent `setCurveUpdates` receive the updates.
+//!
+//! Each tracked curve is one `accountSubscribe` on a connection of its own to the RPC, so at most `max_subscriptions`
+//! curves are followed at once; tokens created while the tracker is full are skipped. A curve is unsubscribed once it
+//! completes, or `ttl` after its token was created. Updates for the same mint go out at most once per
+//! `update_interval`: the latest state of a burst is held back and sent when the interval has passed. Each update is
+//! named after its token from the token registry as it goes out.
+
+use crate::config::{Config, CurveTrackingConfig, ReconnectPolicy, RpcConfig};
+use crate::data_models::{CurveUpdateEvent, TokenCreatedEvent};
+use crate::error::Result;
+use crate::filtering::matches_filter;
+use crate::metrics::Metrics;
+use crate::registry::TokenRegistry;
+use crate::rpc_client::{curve_progress_pct, parse_bonding_curve, Commitment};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::{self, protocol::Message};
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tracing::{debug, info, warn};
+
+/// how often held back updates and expired curves are looked at.
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// updates to publish and requests to send to the RPC after the tracker handled something.
+///
+#[derive(Debug, Default, PartialEq)]
+pub struct Step {
+    pub updates: Vec<CurveUpdateEvent>,
+    pub requests: Vec<String>,
+}
+
+/// one followed bonding curve.
+struct TrackedCurve {
+    mint: String,
+    /// prefix of the curve account data for the launchpad that created the token
+    discriminator: [u8; 8],
+    /// `None` until the RPC confirms the subscription
+    subscription: Option<u64>,
+    expires_at: Instant,
+    /// when the last update for this mint was published
+    last_sent: Option<Instant>,
+    /// latest update the throttle held back
+    held: Option<CurveUpdateEvent>,
+}
+
+/// Which curves are followed and what to do with each frame the RPC sends about them.
+///
+/// The tracker does no I/O of its own: every method returns the requests to send and the updates to publish, and
+/// takes the current time so the throttle and the TTL can be tested without waiting.
+pub struct CurveTracker {
+    config: CurveTrackingConfig,
+    commitment: Commitment,
+    /// curve account discriminator by launchpad program id
+    discriminators: HashMap<String, [u8; 8]>,
+    /// tracked curves by bonding curve address
+    curves: HashMap<String, TrackedCurve>,
+    /// bonding curve address by subscription id
+    subscriptions: HashMap<u64, String>,
+    /// bonding curve address by the id of the `accountSubscribe` waiting for its answer
+    pending: HashMap<u64, String>,
+    next_request_id: u64,
+}
+
+impl CurveTracker {
+    pub fn new(config: CurveTrackingConfig, rpc: &RpcConfig) -> Self {
+        Self {
+            config,
+            commitment: rpc.commitment,
+            discriminators: rpc
+                .programs
+                .iter()
+                .map(|program| (program.id.to_string(), program.bonding_curve_account))
+                .collect(),
+            curves: HashMap::new(),
+            subscriptions: HashMap::new(),
+            pending: HashMap::new(),
+            next_request_id: 1,
+        }
+    }
+
+    /// curves currently followed, subscribed or not.
+    pub fn len(&self) -> usize {
+        self.curves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.curves.is_empty()
+    }
+
+    /// Starts following the curve of `event`'s token, returning the `accountSubscribe` to send.
+    ///
+    /// Returns `None` for tokens the filter rejects, curves that already completed or are already followed, and every
+    /// token while `max_subscriptions` curves are followed.
+    pub fn track(&mut self, event: &TokenCreatedEvent, now: Instant) -> Option<String> {
+        let curve = &event.pump_data.bonding_curve;
+        if event.pump_data.complete || self.curves.contains_key(curve) {
+            return None;
+        }
+        if self.config.filter.as_ref().is_some_and(|filter| !matches_filter(event, filter)) {
+            return None;
+        }
+        let discriminator = *self.discriminators.get(&event.program_id)?;
+        if self.curves.len() >= self.config.max_subscriptions {
+            debug!("Not tracking the curve of {}: {} curves already tracked", event.token.mint_address, self.curves.len());
+            return None;
+        }
+
+        self.curves.insert(
+            curve.clone(),
+            TrackedCurve {
+                mint: event.token.mint_address.clone(),
+                discriminator,
+                subscription: None,
+                expires_at: now + self.config.ttl,
+                last_sent: None,
+                held: None,
+            },
+        );
+        Some(self.subscribe(curve.clone()))
+    }
+
+    /// Handles a text frame from the RPC: a subscription confirmed or refused, or a curve account that changed.
+    pub fn handle(&mut self, text: &str, now: Instant) -> Step {
+        let mut step = Step::default();
+        let Ok(frame) = serde_json::from_str::<Value>(text) else {
+            return step;
+        };
+
+        if frame["method"] == "accountNotification" {
+            let Some(subscription) = frame["params"]["subscription"].as_u64() else {
+                return step;
+            };
+            let result = &frame["params"]["result"];
+            let Some(data) = result["value"]["data"][0].as_str() else {
+                return step;
+            };
+            let slot = result["context"]["slot"].as_u64().unwrap_or_default();
+            self.account_changed(subscription, data, slot, now, &mut step);
+            return step;
+        }
+
+        // unsubscribe answers and anything else without a pending subscribe request behind it are ignored
+        let Some(curve) = frame["id"].as_u64().and_then(|request_id| self.pending.remove(&request_id)) else {
+            return step;
+        };
+        match frame["result"].as_u64() {
+            Some(subscription) => {
+                if let Some(tracked) = self.curves.get_mut(&curve) {
+                    tracked.subscription = Some(subscription);
+                    self.subscriptions.insert(subscription, curve);
+                } else {
+                    // the curve expired before the subscription was confirmed
+                    step.requests.push(self.unsubscribe(subscription));
+                }
+            }
+            None => {
+                warn!("accountSubscribe for bonding curve {} refused: {}", curve, frame["error"]);
+                self.curves.remove(&curve);
+            }
+        }
+        step
+    }
+
+    /// Publishes updates whose throttle interval has passed and stops following curves past their TTL.
+    pub fn tick(&mut self, now: Instant) -> Step {
+        let mut step = Step::default();
+        let interval = self.config.update_interval;
+        for tracked in self.curves.values_mut() {
+            let throttled = tracked.last_sent.is_some_and(|last_sent| now.duration_since(last_sent) < interval);
+            if !throttled || now >= tracked.expires_at {
+                if let Some(update) = tracked.held.take() {
+                    tracked.last_sent = Some(now);
+                    step.updates.push(update);
+                }
+            }
+        }
+
+        let expired: Vec<String> = self
+            .curves
+            .iter()
+            .filter(|(_, tracked)| now >= tracked.expires_at)
+            .map(|(curve, _)| curve.clone())
+            .collect();
+        for curve in expired {
+            debug!("Bonding curve {} reached its tracking TTL", curve);
+            self.stop(&curve, &mut step);
+        }
+        step
+    }
+
+    /// Forgets every subscription of a lost connection and returns the `accountSubscribe` for each curve still followed.
+    pub fn resubscribe(&mut self) -> Vec<String> {
+        self.subscriptions.clear();
+        self.pending.clear();
+        for tracked in self.curves.values_mut() {
+            tracked.subscription = None;
+        }
+        let curves: Vec<String> = self.curves.keys().cloned().collect();
+        curves.into_iter().map(|curve| self.subscribe(curve)).collect()
+    }
+
+    /// decodes a changed curve account and publishes it now, holds it back, or publishes it and stops on completion.
+    fn account_changed(&mut self, subscription: u64, data: &str, slot: u64, now: Instant, step: &mut Step) {
+        let Some(curve) = self.subscriptions.get(&subscription).cloned() else {
+            debug!("Ignoring account notification for unknown subscription {}", subscription);
+            return;
+        };
+        let Some(tracked) = self.curves.get_mut(&curve) else {
+            return;
+        };
+        let decoded = STANDARD
+            .decode(data)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| parse_bonding_curve(&bytes, &tracked.discriminator).map_err(|e| e.to_string()));
+        let account = match decoded {
+            Ok(account) => account,
+            Err(e) => {
+                warn!("Cannot decode bonding curve {}: {}", curve, e);
+                return;
+            }
+        };
+
+        let update = CurveUpdateEvent {
+            mint: tracked.mint.clone(),
+            // named on the way out, from the token registry
+            name: None,
+            symbol: None,
+            virtual_sol_reserves: account.virtual_sol_reserves,
+            virtual_token_reserves: account.virtual_token_reserves,
+            progress_pct: curve_progress_pct(&account),
+            complete: account.complete,
+            slot,
+            timestamp: Utc::now(),
+        };
+        let interval = self.config.update_interval;
+        let throttled = tracked.last_sent.is_some_and(|last_sent| now.duration_since(last_sent) < interval);
+        if account.complete {
+            // the final state always goes out, in place of anything held back
+            tracked.held = None;
+            step.updates.push(update);
+            info!("Bonding curve of {} completed", tracked.mint);
+            self.stop(&curve, step);
+        } else if !throttled {
+            tracked.held = None;
+            tracked.last_sent = Some(now);
+            step.updates.push(update);
+        } else {
+            tracked.held = Some(update);
+        }
+    }
+
+    /// stops following `curve`, unsubscribing if the RPC already confirmed its subscription.
+    fn stop(&mut self, curve: &str, step: &mut Step) {
+        let Some(tracked) = self.curves.remove(curve) else {
+            return;
+        };
+        if let Some(subscription) = tracked.subscription {
+            self.subscriptions.remove(&subscription);
+            step.requests.push(self.unsubscribe(subscription));
+        }
+    }
+
+    fn subscribe(&mut self, curve: String) -> String {
+        let request_id = self.request_id();
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request_id,
+            "method": "accountSubscribe",
+            "params": [
+                curve,
+                { "encoding": "base64", "commitment": self.commitment.as_str() }
+            ]
+        })
+        .to_string();
+        self.pending.insert(request_id, curve);
+        request
+    }
+
+    fn unsubscribe(&mut self, subscription: u64) -> String {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": self.request_id(),
+            "method": "accountUnsubscribe",
+            "params": [subscription]
+        })
+        .to_string()
+    }
+
+    fn request_id(&mut self) -> u64 {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        request_id
+    }
+}
+
+/// Starts tracking curves when `CURVE_TRACKING` is on, returning the channel the updates are published on.
+///
+/// The tracker subscribes to `events` before returning, so no token broadcast afterwards is missed.
+pub fn spawn_curve_tracker(
+    config: &Config,
+    events: &broadcast::Sender<TokenCreatedEvent>,
+    registry: &Arc<TokenRegistry>,
+    metrics: &Arc<Metrics>,
+) -> Option<broadcast::Sender<CurveUpdateEvent>> {
+    let tracking = config.curve_tracking.clone()?;
+    info!(
+        "Tracking bonding curves, up to {} at a time for {:?} each",
+        tracking.max_subscriptions, tracking.ttl
+    );
+    let (updates, _) = broadcast::channel(config.channels.broadcast_capacity);
+    tokio::spawn(run_curve_tracker(
+        CurveTracker::new(tracking, &config.rpc),
+        config.rpc.wss_url.clone(),
+        config.reconnect.clone(),
+        events.subscribe(),
+        updates.clone(),
+        Arc::clone(registry),
+        Arc::clone(metrics),
+    ));
+    Some(updates)
+}
+
+/// Follows curves over a connection to `wss_url`, reconnecting with backoff, until `events` closes.
+///
+/// Tokens created while disconnected are still tracked; their curves are subscribed once the connection is back.
+async fn run_curve_tracker(
+    mut tracker: CurveTracker,
+    wss_url: String,
+    reconnect: ReconnectPolicy,
+    mut events: broadcast::Receiver<TokenCreatedEvent>,
+    updates: broadcast::Sender<CurveUpdateEvent>,
+    registry: Arc<TokenRegistry>,
+    metrics: Arc<Metrics>,
+) {
+    let mut delay = reconnect.initial_delay;
+    loop {
+        let result = match connect_async(&wss_url).await {
+            Ok((ws_stream, _)) => {
+                delay = reconnect.initial_delay;
+                follow_curves(&mut tracker, ws_stream, &mut events, &updates, &registry, &metrics).await
+            }
+            Err(e) => Err(e.into()),
+        };
+        let Err(e) = result else {
+            return;
+        };
+        warn!("Curve tracker connection failed: {}. Reconnecting in {:?}...", e, delay);
+
+        let sleep = tokio::time::sleep(delay);
+        tokio::pin!(sleep);
+        loop {
+            tokio::select! {
+                _ = &mut sleep => break,
+                event = events.recv() => match event {
+                    Ok(event) => {
+                        tracker.track(&event, Instant::now());
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+            }
+        }
+        delay = reconnect.next_delay(delay);
+    }
+}
+
+/// Subscribes every tracked curve on `ws_stream` and follows them until the connection fails, which is always an
+/// error, or `events` closes, which returns `Ok`.
+async fn follow_curves(
+    tracker: &mut CurveTracker,
+    ws_stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    events: &mut broadcast::Receiver<TokenCreatedEvent>,
+    updates: &broadcast::Sender<CurveUpdateEvent>,
+    registry: &TokenRegistry,
+    metrics: &Metrics,
+) -> Result<()> {
+    let (mut write, mut read) = ws_stream.split();
+    for request in tracker.resubscribe() {
+        write.send(Message::Text(request)).await?;
+    }
+    info!("Curve tracker connected, following {} curves", tracker.len());
+
+    let mut flush = tokio::time::interval(FLUSH_INTERVAL);
+    flush.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        let step = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => Step {
+                    updates: Vec::new(),
+                    requests: tracker.track(&event, Instant::now()).into_iter().collect(),
+                },
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Curve tracker lagged, {} tokens were not tracked", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            message = read.next() => match message {
+                Some(Ok(Message::Text(text))) => tracker.handle(&text, Instant::now()),
+                Some(Ok(Message::Close(_))) | None => {
+                    return Err(tungstenite::Error::ConnectionClosed.into());
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(e.into()),
+            },
+            _ = flush.tick() => tracker.tick(Instant::now()),
+        };
+
+        for mut update in step.updates {
+            metrics.curve_updates.fetch_add(1, Ordering::Relaxed);
+            if let Some(created) = registry.get(&update.mint) {
+                update.name = Some(created.token.name.clone());
+                update.symbol = Some(created.token.symbol.clone());
+            }
+            // no receiver just means no client asked for updates yet
+            let _ = updates.send(update);
+        }
+        for request in step.requests {
+            write.send(Message::Text(request)).await?;
+        }
+        metrics.curve_subscriptions.store(tracker.len(), Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,267 @@
+//! Unit tests for the curve tracker: what gets tracked, how updates are throttled and when curves are dropped.
+
+
+use super::*;
+use crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID;
+use crate::data_models::{FilterCriteria, PumpFunData, TokenDetails};
+use crate::rpc_client::{DetailLevel, ProgramConfig, INITIAL_REAL_TOKEN_RESERVES};
+use chrono::Utc;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+/// function to create a tracker over pump.fun with room for `max_subscriptions` curves.
+fn tracker(max_subscriptions: usize, filter: Option<FilterCriteria>) -> CurveTracker {
+    let config = CurveTrackingConfig {
+        filter,
+        max_subscriptions,
+        ttl: Duration::from_secs(60),
+        update_interval: Duration::from_secs(1),
+    };
+    let rpc = RpcConfig {
+        http_url: "https://api.mainnet-beta.solana.com".to_string(),
+        http_label: "http".to_string(),
+        wss_url: "wss://api.mainnet-beta.solana.com".to_string(),
+        wss_endpoints: Vec::new(),
+        programs: vec![pump_fun()],
+        commitment: Commitment::Confirmed,
+        detail_level: DetailLevel::Standard,
+        latency_fields: false,
+        fast_path: false,
+        provider_limit_backoff: Duration::from_secs(300),
+        request_timeout: Duration::from_secs(10),
+        processing_timeout: Duration::from_secs(30),
+        diagnostics_max_per_sec: 20,
+    };
+    CurveTracker::new(config, &rpc)
+}
+
+fn pump_fun() -> ProgramConfig {
+    ProgramConfig::new(Pubkey::from_str(DEFAULT_PUMP_FUN_PROGRAM_ID).unwrap())
+}
+
+/// function to create a fresh token event whose mint and curve are named after `name`.
+fn create_test_event(name: &str, symbol: &str) -> TokenCreatedEvent {
+    TokenCreatedEvent {
+        event_type: "tokenCreated".to_string(),
+        timestamp: Utc::now(),
+        sequence: 0,
+        transaction_signature: format!("sig_{}", name),
+        slot: 280_000_000,
+        program_id: DEFAULT_PUMP_FUN_PROGRAM_ID.to_string(),
+        token: TokenDetails {
+            mint_address: format!("mint_{}", name),
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            uri: "https://test.example.com/metadata.json".to_string(),
+            creator: "creator_A".to_string(),
+            supply: Some(1_000_000_000_000_000),
+            decimals: Some(6),
+            mint_authority: None,
+            freeze_authority: None,
+            authorities_standard: Some(true),
+            sanitized: false,
+            uri_valid: true,
+            raw: None,
+        },
+        pump_data: PumpFunData {
+            bonding_curve: format!("curve_{}", name),
+            virtual_sol_reserves: Some(30_000_000_000),
+            virtual_token_reserves: Some(1_073_000_000_000_000),
+            real_sol_reserves: Some(0),
+            real_token_reserves: Some(INITIAL_REAL_TOKEN_RESERVES),
+            complete: false,
+            price_sol: Some(0.000000028),
+            market_cap_sol: Some(28.0),
+            price_usd: None,
+            market_cap_usd: None,
+        },
+        metadata: None,
+        initial_buy: None,
+        same_tx_buyers: 0,
+        same_tx_buy_sol: 0,
+        tx_fee_lamports: 5_000,
+        priority_fee_lamports: 0,
+        compute_units: None,
+        creator_stats: None,
+        creator_label: None,
+        creator_tags: Vec::new(),
+        similar_recent_count: None,
+        processing_latency_ms: None,
+        chain_latency_ms: None,
+        stale: false,
+        signer_pubkey: None,
+        signature: None,
+        raw_transaction: None,
+    }
+}
+
+/// function to answer the subscribe request `request` with subscription id `subscription`.
+fn confirm(tracker: &mut CurveTracker, request: &str, subscription: u64, now: Instant) {
+    let request: Value = serde_json::from_str(request).unwrap();
+    let response = serde_json::json!({ "jsonrpc": "2.0", "result": subscription, "id": request["id"] });
+    assert_eq!(tracker.handle(&response.to_string(), now), Step::default());
+}
+
+/// function to create an `accountNotification` for a curve with `sold` tokens sold.
+fn notification(subscription: u64, sold: u64, complete: bool, slot: u64) -> String {
+    let mut data = pump_fun().bonding_curve_account.to_vec();
+    for field in [
+        1_073_000_000_000_000 - sold,
+        30_000_000_000 + sold / 10_000,
+        INITIAL_REAL_TOKEN_RESERVES - sold,
+        sold / 10_000,
+        1_000_000_000_000_000,
+    ] {
+        data.extend(field.to_le_bytes());
+    }
+    data.push(complete as u8);
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "accountNotification",
+        "params": {
+            "result": {
+                "context": { "slot": slot },
+                "value": { "data": [STANDARD.encode(data), "base64"], "executable": false, "lamports": 1_000_000 }
+            },
+            "subscription": subscription
+        }
+    })
+    .to_string()
+}
+
+#[test]
+fn test_track_subscribes_to_the_bonding_curve() {
+    let mut tracker = tracker(10, None);
+    let request = tracker.track(&create_test_event("Doge", "DOGE"), Instant::now()).unwrap();
+
+    let request: Value = serde_json::from_str(&request).unwrap();
+    assert_eq!(request["method"], "accountSubscribe");
+    assert_eq!(request["params"][0], "curve_Doge");
+    assert_eq!(request["params"][1]["encoding"], "base64");
+    assert_eq!(request["params"][1]["commitment"], "confirmed");
+    assert_eq!(tracker.len(), 1);
+}
+
+#[test]
+fn test_track_skips_filtered_duplicate_and_over_capacity_tokens() {
+    let filter = FilterCriteria {
+        symbol: Some("DOGE".to_string()),
+        ..Default::default()
+    };
+    let mut tracker = tracker(1, Some(filter));
+    let now = Instant::now();
+
+    assert!(tracker.track(&create_test_event("Pepe", "PEPE"), now).is_none());
+    assert!(tracker.track(&create_test_event("Doge", "DOGE"), now).is_some());
+    assert!(tracker.track(&create_test_event("Doge", "DOGE"), now).is_none());
+    assert!(tracker.track(&create_test_event("Doge2", "DOGE"), now).is_none());
+    assert_eq!(tracker.len(), 1);
+}
+
+#[test]
+fn test_account_updates_are_decoded_and_throttled_per_mint() {
+    let mut tracker = tracker(10, None);
+    let start = Instant::now();
+    let request = tracker.track(&create_test_event("Doge", "DOGE"), start).unwrap();
+    confirm(&mut tracker, &request, 7, start);
+
+    let step = tracker.handle(&notification(7, INITIAL_REAL_TOKEN_RESERVES / 4, false, 100), start);
+    assert_eq!(step.updates.len(), 1);
+    let update = &step.updates[0];
+    assert_eq!(update.mint, "mint_Doge");
+    assert_eq!(update.slot, 100);
+    assert_eq!(update.progress_pct, 25.0);
+    assert!(!update.complete);
+
+    // two more within the interval: only the latest is kept, and sent once the interval has passed
+    let soon = start + Duration::from_millis(300);
+    assert!(tracker.handle(&notification(7, INITIAL_REAL_TOKEN_RESERVES / 3, false, 101), soon).updates.is_empty());
+    assert!(tracker.handle(&notification(7, INITIAL_REAL_TOKEN_RESERVES / 2, false, 102), soon).updates.is_empty());
+    assert!(tracker.tick(start + Duration::from_millis(900)).updates.is_empty());
+    let step = tracker.tick(start + Duration::from_secs(1));
+    assert_eq!(step.updates.len(), 1);
+    assert_eq!(step.updates[0].slot, 102);
+    assert_eq!(step.updates[0].progress_pct, 50.0);
+    assert!(tracker.tick(start + Duration::from_secs(3)).updates.is_empty());
+}
+
+#[test]
+fn test_completed_curve_is_reported_at_once_and_unsubscribed() {
+    let mut tracker = tracker(10, None);
+    let now = Instant::now();
+    let request = tracker.track(&create_test_event("Doge", "DOGE"), now).unwrap();
+    confirm(&mut tracker, &request, 7, now);
+    tracker.handle(&notification(7, 1_000, false, 100), now);
+
+    let step = tracker.handle(&notification(7, INITIAL_REAL_TOKEN_RESERVES, true, 101), now);
+
+    assert_eq!(step.updates.len(), 1);
+    assert!(step.updates[0].complete);
+    assert_eq!(step.updates[0].progress_pct, 100.0);
+    assert_eq!(step.requests.len(), 1);
+    let unsubscribe: Value = serde_json::from_str(&step.requests[0]).unwrap();
+    assert_eq!(unsubscribe["method"], "accountUnsubscribe");
+    assert_eq!(unsubscribe["params"][0], 7);
+    assert!(tracker.is_empty());
+    assert_eq!(tracker.handle(&notification(7, INITIAL_REAL_TOKEN_RESERVES, true, 102), now), Step::default());
+}
+
+#[test]
+fn test_curves_are_dropped_after_their_ttl() {
+    let mut tracker = tracker(1, None);
+    let start = Instant::now();
+    let request = tracker.track(&create_test_event("Doge", "DOGE"), start).unwrap();
+    confirm(&mut tracker, &request, 7, start);
+
+    assert_eq!(tracker.tick(start + Duration::from_secs(59)), Step::default());
+    let step = tracker.tick(start + Duration::from_secs(60));
+    assert_eq!(step.requests.len(), 1);
+    assert!(step.requests[0].contains("accountUnsubscribe"));
+    assert!(tracker.is_empty());
+
+    // the freed slot goes to the next token
+    assert!(tracker.track(&create_test_event("Pepe", "PEPE"), start + Duration::from_secs(60)).is_some());
+}
+
+#[test]
+fn test_refused_and_late_subscriptions_are_cleaned_up() {
+    let mut tracker = tracker(10, None);
+    let start = Instant::now();
+    let refused: Value = serde_json::from_str(&tracker.track(&create_test_event("Doge", "DOGE"), start).unwrap()).unwrap();
+    let late = tracker.track(&create_test_event("Pepe", "PEPE"), start).unwrap();
+
+    let error = serde_json::json!({
+        "jsonrpc": "2.0",
+        "error": { "code": -32602, "message": "Invalid param" },
+        "id": refused["id"]
+    });
+    assert_eq!(tracker.handle(&error.to_string(), start), Step::default());
+    assert_eq!(tracker.len(), 1);
+
+    // the curve expired before its subscription was confirmed
+    tracker.tick(start + Duration::from_secs(60));
+    let late: Value = serde_json::from_str(&late).unwrap();
+    let response = serde_json::json!({ "jsonrpc": "2.0", "result": 9, "id": late["id"] });
+    let step = tracker.handle(&response.to_string(), start + Duration::from_secs(60));
+    assert_eq!(step.requests.len(), 1);
+    assert!(step.requests[0].contains("accountUnsubscribe"));
+}
+
+#[test]
+fn test_resubscribe_after_reconnect_covers_every_curve() {
+    let mut tracker = tracker(10, None);
+    let now = Instant::now();
+    let request = tracker.track(&create_test_event("Doge", "DOGE"), now).unwrap();
+    confirm(&mut tracker, &request, 7, now);
+    tracker.track(&create_test_event("Pepe", "PEPE"), now);
+
+    let mut curves: Vec<String> = tracker
+        .resubscribe()
+        .iter()
+        .map(|request| serde_json::from_str::<Value>(request).unwrap()["params"][0].as_str().unwrap().to_string())
+        .collect();
+    curves.sort();
+    assert_eq!(curves, ["curve_Doge", "curve_Pepe"]);
+    // the old subscription died with the connection
+    assert_eq!(tracker.handle(&notification(7, 1_000, false, 100), now), Step::default());
+}
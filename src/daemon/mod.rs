@@ -0,0 +1,230 @@
+//! # Daemon Mode
+//!
+//! For hosts without a service manager. `--daemonize` detaches the monitor from the terminal, `--pid-file` records its
+//! process id and refuses to start while the process it names is still alive, and `--log-file` appends the logs to a
+//! file that is opened again on `SIGUSR1`, so logrotate can move it away and signal the monitor to start a fresh one.
+//!
+//! Detaching forks, which is only sound before the Tokio runtime starts its threads, so `main` does it before building
+//! the runtime. Windows has no fork: `--daemonize` and `--pid-file` are refused there, `--log-file` works everywhere.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use thiserror::Error;
+use tracing_subscriber::fmt::MakeWriter;
+#[cfg(unix)]
+use tracing::{info, warn};
+
+/// why the monitor can't detach or take its pid file.
+#[derive(Error, Debug)]
+pub enum DaemonError {
+    #[error("pid file {} names process {pid}, which is still running; is another monitor using it?", .path.display())]
+    AlreadyRunning { path: PathBuf, pid: u32 },
+    #[error("cannot use pid file {}: {source}", .path.display())]
+    PidFile { path: PathBuf, source: io::Error },
+    #[error("cannot detach from the terminal: {0}")]
+    Detach(io::Error),
+    #[error("--daemonize and --pid-file are only supported on Unix; run the monitor as a Windows service instead")]
+    Unsupported,
+}
+
+/// Checks the pid file, detaches when `daemonize` is set and then writes the pid file, in that order so a monitor that
+/// is already running is reported on the terminal rather than in the log.
+///
+/// Must run before any thread is started.
+pub fn start(daemonize: bool, pid_file: Option<&Path>) -> Result<Option<PidFile>, DaemonError> {
+    #[cfg(not(unix))]
+    if daemonize || pid_file.is_some() {
+        return Err(DaemonError::Unsupported);
+    }
+    if let Some(path) = pid_file {
+        PidFile::check(path)?;
+    }
+    #[cfg(unix)]
+    if daemonize {
+        detach()?;
+    }
+    pid_file.map(PidFile::create).transpose()
+}
+
+/// The pid file of this monitor, removed again when dropped.
+///
+/// A file left behind by a monitor that is gone, or holding no pid at all, is taken over.
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+    pid: u32,
+}
+
+impl PidFile {
+    /// fails with `AlreadyRunning` when `path` names another process that is still alive.
+    pub fn check(path: &Path) -> Result<(), DaemonError> {
+        match read_pid(path)? {
+            Some(pid) if pid != std::process::id() && process_alive(pid) => {
+                Err(DaemonError::AlreadyRunning { path: path.to_path_buf(), pid })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks `path` like `check`, then writes this process's id to it.
+    pub fn create(path: &Path) -> Result<Self, DaemonError> {
+        Self::check(path)?;
+        let pid = std::process::id();
+        fs::write(path, format!("{}\n", pid))
+            .map_err(|source| DaemonError::PidFile { path: path.to_path_buf(), source })?;
+        Ok(Self { path: path.to_path_buf(), pid })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        // a monitor started after this one was given up on may have taken the file over
+        if matches!(read_pid(&self.path), Ok(Some(pid)) if pid == self.pid) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// the pid in the file at `path`; `None` when there is no file or it holds something else.
+fn read_pid(path: &Path) -> Result<Option<u32>, DaemonError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(contents.trim().parse().ok().filter(|&pid| pid != 0)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(DaemonError::PidFile { path: path.to_path_buf(), source }),
+    }
+}
+
+/// whether a process with id `pid` exists.
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // signal 0 only checks for the process; EPERM means it exists but belongs to another user
+    (unsafe { libc::kill(pid, 0) } == 0) || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// without a way to check, a recorded process is assumed to be running.
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+/// forks twice with a new session in between, so the process left has no controlling terminal and can't get one, and
+/// points stdin, stdout and stderr at `/dev/null`. The working directory is kept so relative paths still resolve.
+#[cfg(unix)]
+fn detach() -> Result<(), DaemonError> {
+    use std::os::unix::io::AsRawFd;
+
+    fork_and_exit_parent()?;
+    if unsafe { libc::setsid() } == -1 {
+        return Err(DaemonError::Detach(io::Error::last_os_error()));
+    }
+    fork_and_exit_parent()?;
+
+    let dev_null = OpenOptions::new().read(true).write(true).open("/dev/null").map_err(DaemonError::Detach)?;
+    for fd in [libc::STDIN_FILENO, libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+        if unsafe { libc::dup2(dev_null.as_raw_fd(), fd) } == -1 {
+            return Err(DaemonError::Detach(io::Error::last_os_error()));
+        }
+    }
+    Ok(())
+}
+
+/// continues in a child process, ending the current one.
+#[cfg(unix)]
+fn fork_and_exit_parent() -> Result<(), DaemonError> {
+    match unsafe { libc::fork() } {
+        -1 => Err(DaemonError::Detach(io::Error::last_os_error())),
+        0 => Ok(()),
+        // `_exit` skips the destructors, which belong to the child now
+        _ => unsafe { libc::_exit(0) },
+    }
+}
+
+/// A log file that can be opened again at its path after log rotation moved it away.
+///
+/// Clones share the file, so the copy handed to the log subscriber sees every reopen.
+#[derive(Debug, Clone)]
+pub struct LogFile {
+    path: PathBuf,
+    file: Arc<Mutex<File>>,
+}
+
+impl LogFile {
+    /// opens `path` for appending, creating it when missing.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Arc::new(Mutex::new(open_append(path)?)),
+        })
+    }
+
+    /// Opens the path again, so later lines go to a new file once the old one was renamed.
+    ///
+    /// When the path can't be opened the old file stays in use.
+    pub fn reopen(&self) -> io::Result<()> {
+        let file = open_append(&self.path)?;
+        *self.lock() = file;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// a panic while writing leaves nothing half-updated behind, so a poisoned lock is taken anyway.
+    fn lock(&self) -> MutexGuard<'_, File> {
+        self.file.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+impl<'a> MakeWriter<'a> for LogFile {
+    type Writer = LogFileWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogFileWriter(self.lock())
+    }
+}
+
+/// the log file, locked while one log line is written so lines never interleave.
+pub struct LogFileWriter<'a>(MutexGuard<'a, File>);
+
+impl Write for LogFileWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Reopens `log_file` on every `SIGUSR1` until the process exits.
+#[cfg(unix)]
+pub async fn reopen_on_sigusr1(log_file: LogFile) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut signals = match signal(SignalKind::user_defined1()) {
+        Ok(signals) => signals,
+        Err(e) => {
+            warn!("Cannot listen for SIGUSR1, {} won't be reopened after rotation: {}", log_file.path().display(), e);
+            return;
+        }
+    };
+    while signals.recv().await.is_some() {
+        match log_file.reopen() {
+            Ok(()) => info!("Reopened log file {}", log_file.path().display()),
+            Err(e) => warn!("Cannot reopen log file {}, still writing to the old one: {}", log_file.path().display(), e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,65 @@
+//! Unit tests for daemon mode: pid file collisions and reopening the log file.
+
+
+use super::*;
+
+/// function to create an empty scratch directory for pid and log files.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pump_fun_monitor_daemon_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pid_file_of_a_running_process_is_refused() {
+    let path = scratch_dir("running").join("monitor.pid");
+    let mut other = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+    fs::write(&path, format!("{}\n", other.id())).unwrap();
+
+    let error = PidFile::create(&path).unwrap_err();
+    assert!(matches!(error, DaemonError::AlreadyRunning { pid, .. } if pid == other.id()));
+    assert_eq!(fs::read_to_string(&path).unwrap().trim(), other.id().to_string());
+
+    // once the process is gone the file is stale and taken over
+    other.kill().unwrap();
+    other.wait().unwrap();
+    let pid_file = PidFile::create(&path).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap().trim(), std::process::id().to_string());
+    drop(pid_file);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_pid_file_without_a_pid_is_taken_over() {
+    let path = scratch_dir("garbage").join("monitor.pid");
+    fs::write(&path, "not a pid\n").unwrap();
+    let pid_file = PidFile::create(&path).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap().trim(), std::process::id().to_string());
+
+    // a file another monitor took over is left alone
+    fs::write(&path, "1\n").unwrap();
+    drop(pid_file);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "1\n");
+}
+
+#[test]
+fn test_log_file_is_reopened_after_rotation() {
+    let dir = scratch_dir("log");
+    let path = dir.join("monitor.log");
+    let log_file = LogFile::open(&path).unwrap();
+    let subscriber = log_file.clone();
+    writeln!(subscriber.make_writer(), "before rotation").unwrap();
+
+    // logrotate renames the file; lines keep going to it until the monitor reopens the path
+    let rotated = dir.join("monitor.log.1");
+    fs::rename(&path, &rotated).unwrap();
+    writeln!(subscriber.make_writer(), "while rotating").unwrap();
+    assert!(!path.exists());
+
+    log_file.reopen().unwrap();
+    writeln!(subscriber.make_writer(), "after rotation").unwrap();
+    assert_eq!(fs::read_to_string(&rotated).unwrap(), "before rotation\nwhile rotating\n");
+    assert_eq!(fs::read_to_string(&path).unwrap(), "after rotation\n");
+}
@@ -0,0 +1,1067 @@
+//! # Data Models
+//! This module defines the data structures used throughout the pump.fun monitor service.
+//!
+//! ## Event Schema
+//!
+//! Every event the monitor publishes is a [`MonitorEvent`]. With `EVENT_FORMAT=envelope` it leaves the process wrapped
+//! as `{"schemaVersion": 1, "eventType": "tokenCreated", "data": {...}, "sequence": 42, "timestamp": "..."}`, where
+//! `data` is exactly the object the flat format sends on its own. The flat format is the default for now and is
+//! deprecated; it will be removed once consumers have moved to the envelope.
+//!
+//! [`SCHEMA_VERSION`] only goes up when an existing field is removed or renamed, or changes type or meaning. New event
+//! types and new fields in `data` are added under the same version, so a consumer should skip event types it doesn't
+//! know and ignore unknown fields. An envelope with a newer version than this build supports is refused rather than
+//! misread.
+//!
+//! The event structs read back what they write, so archived and transmitted events parse into the same types. No
+//! struct denies unknown fields, and every field added after the first release is an `Option` or `#[serde(default)]`,
+//! so events written by older and newer versions both still parse. `tests.rs` pins the wire format against the
+//! captured events in `fixtures/`.
+
+
+use crate::error::MonitorError;
+use crate::filtering::FilterProblem;
+use borsh::BorshDeserialize;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize};
+use solana_transaction_status::UiTransactionStatusMeta;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// The main event structure broadcast to WebSocket clients when a new token is created.
+///
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenCreatedEvent {
+    pub event_type: String,
+    pub timestamp: DateTime<Utc>,
+    /// position in broadcast order, starting at 1 and going up by one per event; 0 until the event is broadcast
+    #[serde(default)]
+    pub sequence: u64,
+    pub transaction_signature: String,
+    /// slot the transaction landed in
+    #[serde(default)]
+    pub slot: u64,
+    /// launchpad program that created the token
+    #[serde(default)]
+    pub program_id: String,
+    pub token: TokenDetails,
+    pub pump_data: PumpFunData,
+    /// off-chain metadata behind `token.uri`; only present when enrichment is enabled and the fetch succeeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<TokenMetadata>,
+    /// the creator's buy bundled into the creation transaction; absent when the creator didn't buy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_buy: Option<InitialBuy>,
+    /// distinct wallets buying in the creation transaction, the creator included; more than one means a bundled launch
+    #[serde(default)]
+    pub same_tx_buyers: u32,
+    /// lamports those wallets paid into the bonding curve, fees excluded
+    #[serde(default)]
+    pub same_tx_buy_sol: u64,
+    /// fee paid for the creation transaction, base and priority fee together
+    #[serde(default)]
+    pub tx_fee_lamports: u64,
+    /// the part of that fee bid through the ComputeBudget program; 0 when the creator paid no priority fee
+    #[serde(default)]
+    pub priority_fee_lamports: u64,
+    /// compute units the creation transaction used; absent when the RPC node doesn't report them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compute_units: Option<u64>,
+    /// launches by the same creator seen by this monitor; only present when `CREATOR_HISTORY` is on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator_stats: Option<CreatorStats>,
+    /// the creator's label on the `WATCHLIST_FILE`; only present when the creator is listed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator_label: Option<String>,
+    /// the creator's tags on the `WATCHLIST_FILE`, lower case
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub creator_tags: Vec<String>,
+    /// tokens with a similar name or symbol broadcast within the spam window; only present when `SPAM_SUPPRESSION` is on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similar_recent_count: Option<u32>,
+    /// milliseconds from the log notification to this broadcast; only present when `EVENT_LATENCY_FIELDS` is on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processing_latency_ms: Option<u64>,
+    /// milliseconds from the block time of the creation transaction to its log notification; block times have
+    /// one-second resolution, so this is approximate. Only present when `EVENT_LATENCY_FIELDS` is on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_latency_ms: Option<u64>,
+    /// older than `MAX_EVENT_AGE_SECS` when it was dispatched; only present when true, see `freshness`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
+    /// base58 public key the event is signed with; only present when `EVENT_SIGNING_KEY` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer_pubkey: Option<String>,
+    /// base58 ed25519 signature over the event's canonical form, see `signing`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// the creation transaction as fetched; only kept when `WS_RAW_TRANSACTIONS` is on, and only sent to WebSocket clients in raw mode
+    #[serde(skip)]
+    pub raw_transaction: Option<Arc<RawTransaction>>,
+}
+
+/// detailed information about a newly created token.
+///
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenDetails {
+    pub mint_address: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub creator: String,
+    /// read from the mint account; absent at `DETAIL_LEVEL=minimal`, which doesn't fetch it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supply: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u8>,
+    /// who can mint more of the token; absent once revoked, as well as at `DETAIL_LEVEL=minimal`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mint_authority: Option<String>,
+    /// who can freeze holders' token accounts; absent when nobody can, as well as at `DETAIL_LEVEL=minimal`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freeze_authority: Option<String>,
+    /// the mint authority is revoked or the creating program's PDA, and there is no freeze authority; absent at
+    /// `DETAIL_LEVEL=minimal`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorities_standard: Option<bool>,
+    /// the name, symbol or URI was truncated or had hidden characters removed
+    #[serde(default)]
+    pub sanitized: bool,
+    /// `uri` is a well-formed http(s), ipfs or ar URL
+    #[serde(default)]
+    pub uri_valid: bool,
+    /// metadata exactly as it appeared in the instruction; only present for sanitized tokens when `KEEP_RAW_METADATA` is on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<RawTokenMetadata>,
+}
+
+/// unsanitized token metadata, kept for forensic consumers.
+///
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTokenMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+/// a creation transaction exactly as `getTransaction` returned it, for clients that decode it themselves.
+///
+/// Serialized with `eventType: "rawTransaction"` so it can share a connection with decoded events.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "eventType", rename = "rawTransaction")]
+pub struct RawTransaction {
+    pub signature: String,
+    pub slot: u64,
+    /// the serialized transaction, base64 encoded
+    pub transaction: String,
+    pub meta: UiTransactionStatusMeta,
+}
+
+/// progress of a tracked token's bonding curve, sent to clients that opted in with `setCurveUpdates`.
+///
+/// Serialized with `eventType: "curveUpdate"` so it can share a connection with decoded events.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "eventType", rename = "curveUpdate")]
+pub struct CurveUpdateEvent {
+    pub mint: String,
+    /// the token's name and symbol from its creation, absent when the token registry no longer holds it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    /// share of the curve's tokens sold so far, from 0 to 100
+    pub progress_pct: f64,
+    /// the curve has completed; no further updates follow for this mint
+    pub complete: bool,
+    /// slot of the account update
+    pub slot: u64,
+    /// when the account update arrived
+    pub timestamp: DateTime<Utc>,
+}
+
+/// a bonding curve that was still active when the startup snapshot was taken.
+///
+/// Serialized with `eventType: "curveSnapshot"`, one per line of the snapshot file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "eventType", rename = "curveSnapshot")]
+pub struct CurveSnapshotEvent {
+    pub bonding_curve: String,
+    /// launchpad program that owns the curve
+    pub program_id: String,
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub token_total_supply: u64,
+    /// share of the curve's tokens sold so far, from 0 to 100
+    pub progress_pct: f64,
+    pub snapshot_at: DateTime<Utc>,
+}
+
+/// hash of a token's image that wasn't ready when the token was broadcast, sent to the clients that got the token.
+///
+/// Serialized with `eventType: "tokenEnriched"` so it can share a connection with decoded events.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "eventType", rename = "tokenEnriched")]
+pub struct TokenEnrichedEvent {
+    /// sequence of the token this completes
+    pub sequence: u64,
+    pub timestamp: DateTime<Utc>,
+    pub mint: String,
+    pub transaction_signature: String,
+    pub image_sha256: String,
+    pub image_seen_before: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_first_seen_mint: Option<String>,
+    /// the token as it was broadcast, to match against client filters
+    #[serde(skip)]
+    pub event: Box<TokenCreatedEvent>,
+}
+
+/// a graduated token's liquidity moving from its bonding curve into an AMM pool, sent to every client.
+///
+/// Serialized with `eventType: "tokenMigrated"` so it can share a connection with decoded events.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "eventType", rename = "tokenMigrated")]
+pub struct TokenMigratedEvent {
+    pub timestamp: DateTime<Utc>,
+    pub transaction_signature: String,
+    pub slot: u64,
+    /// launchpad program the token graduated from
+    pub program_id: String,
+    pub mint: String,
+    /// the token's name and symbol from its creation, absent when neither the token registry nor its database has it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    pub bonding_curve: String,
+    /// the pool the migration created
+    pub pool: String,
+    /// AMM program that owns `pool`
+    pub pool_program: String,
+    /// tokens in the pool once the migration went through; `None` when the meta reports no balance for it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_token_reserves: Option<u64>,
+    /// lamports of wrapped SOL in the pool once the migration went through
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_sol_reserves: Option<u64>,
+}
+
+/// a token creation decoded from its log notification alone, sent to clients that opted in with `setPreviews` ahead of
+/// the `tokenCreated` event.
+///
+/// Serialized with `eventType: "tokenCreatedPreview"`. Nothing was fetched for it, so it carries no sequence: the
+/// `tokenCreated` that follows has the same `transactionSignature` and `mintAddress`, and none follows when processing
+/// drops the token or fails.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "eventType", rename = "tokenCreatedPreview")]
+pub struct TokenCreatedPreviewEvent {
+    /// when the log notification arrived
+    pub timestamp: DateTime<Utc>,
+    pub transaction_signature: String,
+    /// launchpad program that created the token
+    pub program_id: String,
+    pub mint_address: String,
+    /// sanitized the same way as the `tokenCreated` event's
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub creator: String,
+    pub bonding_curve: String,
+}
+
+/// one of the monitor's decisions about a transaction, sent to admins that opted in with `setDiagnostics`.
+///
+/// Serialized with `eventType: "diagnostic"` and the decision in `type`. Diagnostics aren't events: they carry no
+/// sequence and are never enveloped.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "eventType", rename = "diagnostic")]
+pub struct DiagnosticEvent {
+    pub timestamp: DateTime<Utc>,
+    pub signature: String,
+    #[serde(flatten)]
+    pub kind: DiagnosticKind,
+}
+
+/// what the monitor decided about a transaction.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum DiagnosticKind {
+    /// processed, but nothing was broadcast for it
+    Skipped { reason: String },
+    /// processing failed for good; `code` is the `MonitorError::code`
+    ParseError { code: String, error: String },
+    /// the transaction couldn't be fetched before its retries ran out
+    Missed { attempts: u32, error: String },
+}
+
+/// version of the envelope written around every event with `EVENT_FORMAT=envelope`; see the module docs for when it
+/// changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// an event as the monitor publishes it, serialized in the versioned envelope.
+///
+/// The internal channels carry the payloads themselves; this is the shape events leave the process in.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(test), allow(dead_code))]
+pub enum MonitorEvent {
+    TokenCreated(Box<TokenCreatedEvent>),
+    TokenEnriched(TokenEnrichedEvent),
+    CurveUpdate(CurveUpdateEvent),
+    TokenMigrated(TokenMigratedEvent),
+    TokenCreatedPreview(TokenCreatedPreviewEvent),
+}
+
+impl MonitorEvent {
+    /// the envelope's `eventType`.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            Self::TokenCreated(_) => TokenCreatedEvent::EVENT_TYPE,
+            Self::TokenEnriched(_) => TokenEnrichedEvent::EVENT_TYPE,
+            Self::CurveUpdate(_) => CurveUpdateEvent::EVENT_TYPE,
+            Self::TokenMigrated(_) => TokenMigratedEvent::EVENT_TYPE,
+            Self::TokenCreatedPreview(_) => TokenCreatedPreviewEvent::EVENT_TYPE,
+        }
+    }
+}
+
+impl Serialize for MonitorEvent {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::TokenCreated(event) => event.envelope().serialize(serializer),
+            Self::TokenEnriched(event) => event.envelope().serialize(serializer),
+            Self::CurveUpdate(event) => event.envelope().serialize(serializer),
+            Self::TokenMigrated(event) => event.envelope().serialize(serializer),
+            Self::TokenCreatedPreview(event) => event.envelope().serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MonitorEvent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct RawEnvelope {
+            schema_version: u32,
+            event_type: String,
+            data: serde_json::Value,
+        }
+
+        let raw = RawEnvelope::deserialize(deserializer)?;
+        if raw.schema_version > SCHEMA_VERSION {
+            return Err(de::Error::custom(format!(
+                "schemaVersion {} is newer than the supported {}",
+                raw.schema_version, SCHEMA_VERSION
+            )));
+        }
+        let event = match raw.event_type.as_str() {
+            "tokenCreated" => serde_json::from_value(raw.data).map(Self::TokenCreated),
+            "tokenEnriched" => serde_json::from_value(raw.data).map(Self::TokenEnriched),
+            "curveUpdate" => serde_json::from_value(raw.data).map(Self::CurveUpdate),
+            "tokenMigrated" => serde_json::from_value(raw.data).map(Self::TokenMigrated),
+            "tokenCreatedPreview" => serde_json::from_value(raw.data).map(Self::TokenCreatedPreview),
+            other => {
+                return Err(de::Error::unknown_variant(
+                    other,
+                    &["tokenCreated", "tokenEnriched", "curveUpdate", "tokenMigrated", "tokenCreatedPreview"],
+                ))
+            }
+        };
+        event.map_err(de::Error::custom)
+    }
+}
+
+/// a payload of `MonitorEvent`, which can be wrapped in the envelope without being cloned.
+pub trait EventPayload: Serialize + Sized {
+    /// the envelope's `eventType`, the same as the payload's own
+    const EVENT_TYPE: &'static str;
+
+    /// the envelope's `sequence`.
+    fn sequence(&self) -> u64;
+
+    /// the envelope's `timestamp`.
+    fn timestamp(&self) -> DateTime<Utc>;
+
+    /// this payload in the versioned envelope.
+    fn envelope(&self) -> Envelope<'_, Self> {
+        Envelope::new(Self::EVENT_TYPE, self.sequence(), self.timestamp(), self)
+    }
+
+    /// this payload as `format` writes it.
+    fn formatted(&self, format: EventFormat) -> Formatted<'_, Self> {
+        match format {
+            EventFormat::Flat => Formatted::Flat(self),
+            EventFormat::Envelope => Formatted::Envelope(self.envelope()),
+        }
+    }
+}
+
+impl EventPayload for TokenCreatedEvent {
+    const EVENT_TYPE: &'static str = "tokenCreated";
+
+    fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+}
+
+impl EventPayload for TokenEnrichedEvent {
+    const EVENT_TYPE: &'static str = "tokenEnriched";
+
+    fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+}
+
+impl EventPayload for CurveUpdateEvent {
+    const EVENT_TYPE: &'static str = "curveUpdate";
+
+    /// curve updates aren't ordered with tokens, so always 0.
+    fn sequence(&self) -> u64 {
+        0
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+}
+
+impl EventPayload for TokenMigratedEvent {
+    const EVENT_TYPE: &'static str = "tokenMigrated";
+
+    /// migrations aren't ordered with tokens, so always 0.
+    fn sequence(&self) -> u64 {
+        0
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+}
+
+impl EventPayload for TokenCreatedPreviewEvent {
+    const EVENT_TYPE: &'static str = "tokenCreatedPreview";
+
+    /// previews go out before their token has a sequence, so always 0.
+    fn sequence(&self) -> u64 {
+        0
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+}
+
+/// the versioned envelope around a borrowed payload; `data` can be any serializable form of it, e.g. a pruned one.
+///
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope<'a, T> {
+    schema_version: u32,
+    event_type: &'static str,
+    data: &'a T,
+    sequence: u64,
+    timestamp: DateTime<Utc>,
+}
+
+impl<'a, T> Envelope<'a, T> {
+    pub fn new(event_type: &'static str, sequence: u64, timestamp: DateTime<Utc>, data: &'a T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            event_type,
+            data,
+            sequence,
+            timestamp,
+        }
+    }
+}
+
+/// a payload written flat or in the envelope.
+///
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum Formatted<'a, T> {
+    Flat(&'a T),
+    Envelope(Envelope<'a, T>),
+}
+
+/// how events are written for WebSocket clients, sinks and the CLI, chosen with `EVENT_FORMAT`.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EventFormat {
+    /// each payload on its own, tagged with `eventType`; deprecated, kept while consumers move to the envelope
+    #[default]
+    Flat,
+    /// every event in the versioned `MonitorEvent` envelope
+    Envelope,
+}
+
+impl FromStr for EventFormat {
+    type Err = MonitorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "flat" => Ok(Self::Flat),
+            "envelope" => Ok(Self::Envelope),
+            other => Err(MonitorError::Config(format!("'{}' is not one of: flat, envelope", other))),
+        }
+    }
+}
+
+/// the payload of an event line written in either format: `data` of an envelope, or the line itself.
+pub fn event_payload(line: &serde_json::Value) -> &serde_json::Value {
+    match line.get("schemaVersion") {
+        Some(_) => line.get("data").unwrap_or(line),
+        None => line,
+    }
+}
+
+/// fields taken from the token's off-chain metadata document.
+///
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// the image link as the document has it, often `ipfs://`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// `image` resolved to an `https` link through the IPFS or Arweave gateway
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+    /// the token's `uri` resolved the same way; where the document was fetched from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata_url: Option<String>,
+    /// hex SHA-256 of the downloaded image; only present when `IMAGE_HASHING` is on and the download finished in time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_sha256: Option<String>,
+    /// another recent token used the exact same image; present together with `image_sha256`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_seen_before: Option<bool>,
+    /// mint of the first recent token with this image, when it wasn't this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_first_seen_mint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub twitter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telegram: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website: Option<String>,
+    /// `twitter` links an X community, which anyone can open, instead of an account
+    #[serde(default)]
+    pub twitter_is_community_handle: bool,
+    /// the document links no website
+    #[serde(default)]
+    pub website_missing: bool,
+    /// the description carries links of its own
+    #[serde(default)]
+    pub description_contains_urls: bool,
+    /// another recent token linked the same Twitter/X account or community first
+    #[serde(default)]
+    pub socials_reused: bool,
+}
+
+/// the creator's own buy of a new token, made in the transaction that created it.
+///
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct InitialBuy {
+    /// lamports paid into the bonding curve, fees excluded
+    pub sol_amount: u64,
+    /// tokens received, in base units
+    pub token_amount: u64,
+}
+
+/// the creator's launch history as far as this monitor has seen it, this token included.
+///
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatorStats {
+    /// tokens created by this wallet, so 1 for a first launch
+    pub tokens_created_seen: u32,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// earlier tokens of this wallet seen migrating to an AMM pool
+    #[serde(default)]
+    pub tokens_graduated: u32,
+    /// earlier tokens of this wallet that hadn't migrated `CREATOR_ABANDONED_AFTER_SECS` after their launch
+    #[serde(default)]
+    pub tokens_abandoned: u32,
+    /// `tokens_graduated` out of those graduated or abandoned; absent until one of them is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graduation_rate: Option<f32>,
+}
+
+/// pump.fun specific data extracted from the bonding curve and transaction.
+///
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PumpFunData {
+    pub bonding_curve: String,
+    /// reserves and prices are read from the curve account, so they are absent at `DETAIL_LEVEL=minimal`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub virtual_sol_reserves: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub virtual_token_reserves: Option<u64>,
+    /// SOL actually deposited in the curve, in lamports
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub real_sol_reserves: Option<u64>,
+    /// tokens still available to buy from the curve
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub real_token_reserves: Option<u64>,
+    /// the curve has graduated and trading moved off pump.fun; always false when the curve wasn't fetched
+    #[serde(default)]
+    pub complete: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_sol: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market_cap_sol: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_usd: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market_cap_usd: Option<f64>,
+}
+
+
+/// raw bonding curve account data structure for Borsh deserialization.
+///
+/// Field order matches the on-chain account after its 8-byte discriminator.
+#[derive(BorshDeserialize, Debug, PartialEq)]
+pub struct BondingCurveAccountData {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    pub complete: bool,
+}
+
+/// instruction data for pump.fun's Create instruction.
+///
+#[derive(BorshDeserialize, Debug)]
+pub struct CreateInstructionData {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+/// pump.fun's `CreateEvent`, emitted in the program log as a "Program data:" line.
+///
+/// Field order matches the event after its 8-byte discriminator; newer program versions append more fields after these.
+#[derive(BorshDeserialize, Debug, PartialEq)]
+pub struct CreateEventData {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub mint: [u8; 32],
+    pub bonding_curve: [u8; 32],
+    pub user: [u8; 32],
+}
+
+
+/// pump.fun's `CompletePumpAmmMigrationEvent`, emitted in the program log when a completed curve moves into a PumpSwap
+/// pool.
+///
+/// Field order matches the event after its 8-byte discriminator; newer program versions append more fields after these.
+/// Only the accounts are read, the rest is declared to reach `pool`.
+#[derive(BorshDeserialize, Debug, PartialEq)]
+#[allow(dead_code)]
+pub struct MigrationEventData {
+    pub user: [u8; 32],
+    pub mint: [u8; 32],
+    /// tokens moved into the pool
+    pub mint_amount: u64,
+    /// lamports moved into the pool
+    pub sol_amount: u64,
+    pub pool_migration_fee: u64,
+    pub bonding_curve: [u8; 32],
+    pub timestamp: i64,
+    pub pool: [u8; 32],
+}
+
+/// client-side filtering criteria for token creation events.
+///
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterCriteria {
+    pub creator: Option<String>,
+    /// a tag the creator has on the watchlist, e.g. "insider"; matched case-insensitively
+    pub creator_tag: Option<String>,
+    pub symbol: Option<String>,
+    pub name_contains: Option<String>,
+    /// host of `token.uri`, e.g. "ipfs.io"; subdomains match too
+    pub uri_host: Option<String>,
+    /// scheme of `token.uri`, e.g. "https" or "ipfs"
+    pub uri_scheme: Option<String>,
+    /// whether the fetched metadata has an image; events without metadata count as having none
+    pub has_image: Option<bool>,
+    /// whether the fetched metadata links a Twitter/X account; events without metadata count as having none
+    pub has_twitter: Option<bool>,
+    /// whether another recent token used the exact same image; events without an image hash count as not seen before
+    pub image_seen_before: Option<bool>,
+    /// only tokens linking a Twitter/X account of their own: not a community, and not one another recent token linked
+    #[serde(default)]
+    pub require_twitter: bool,
+    /// only tokens whose metadata links a website
+    #[serde(default)]
+    pub require_website: bool,
+    /// only tokens whose mint authority is revoked or the program's, and that nobody can freeze; events without
+    /// `token.authoritiesStandard` never match
+    #[serde(default)]
+    pub require_standard_authorities: bool,
+    /// id of the launchpad program that created the token
+    pub program: Option<String>,
+    /// lowest slot to deliver, so a client joining late can skip anything older
+    pub min_slot: Option<u64>,
+    /// least tokens the creator must have launched, this one included; events without creator stats count as none
+    pub min_creator_tokens: Option<u32>,
+    /// most tokens the creator may have launched, this one included, e.g. 1 for first-time creators only
+    pub max_creator_tokens: Option<u32>,
+    /// least share of the creator's earlier tokens that graduated, from 0 to 1; events without a graduation rate never
+    /// match
+    pub min_creator_graduation_rate: Option<f32>,
+    /// least SOL the creator bought in the creation transaction; events without an initial buy count as 0
+    pub min_initial_buy_sol: Option<f64>,
+    /// most wallets allowed to buy in the creation transaction, the creator included
+    pub max_same_tx_buyers: Option<u32>,
+    /// least priority fee paid for the creation transaction, in lamports
+    pub min_priority_fee_lamports: Option<u64>,
+    /// least tokens with a similar name seen recently, to hunt copycat waves; events without the count count as 0
+    pub min_similar_recent_count: Option<u32>,
+    /// most tokens with a similar name seen recently, e.g. 0 to avoid copycats
+    pub max_similar_recent_count: Option<u32>,
+}
+
+/// messages that clients can send to the WebSocket server.
+///
+#[derive(Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase", tag = "action")]
+pub enum ClientMessage {
+    SetFilter {
+        filter: FilterCriteria
+    },
+    /// apply the filter preset `name` from the server's configuration, with any criteria set in `overrides` replacing
+    /// the preset's
+    UsePreset {
+        name: String,
+        #[serde(default)]
+        overrides: FilterCriteria
+    },
+    /// ask for every filter preset the server offers
+    ListPresets,
+    /// ask for the filter the server currently applies to this connection
+    GetFilter,
+    /// drop the filter so every event is received again
+    ClearFilter,
+    /// receive events gzipped in binary frames instead of uncompressed frames
+    SetCompression {
+        enabled: bool
+    },
+    /// switch the serialization used for events sent to this client
+    SetEncoding {
+        encoding: WireEncoding
+    },
+    /// receive only these fields of every token event, as dot paths like `token.name`; an empty list restores full events
+    SetFields {
+        fields: Vec<String>
+    },
+    /// also receive the raw transaction behind every matching event; refused unless the server keeps raw transactions
+    SetRawMode {
+        enabled: bool
+    },
+    /// also receive `curveUpdate` messages for the bonding curves the server tracks; refused unless tracking is on
+    SetCurveUpdates {
+        enabled: bool
+    },
+    /// also receive a `tokenCreatedPreview` for every creation ahead of its `tokenCreated`; refused unless `FAST_PATH` is on
+    SetPreviews {
+        enabled: bool
+    },
+    /// receive a `heartbeat` every `interval_secs`, or the server's default interval when omitted; `enabled: false` stops them
+    #[serde(rename_all = "camelCase")]
+    SetHeartbeat {
+        enabled: bool,
+        interval_secs: Option<u64>
+    },
+    /// receive at most `max_per_second` matching token events a second; `maxPerSecond: 0` lifts the limit
+    #[serde(rename_all = "camelCase")]
+    SetRateLimit {
+        max_per_second: u32,
+        #[serde(default)]
+        overflow: RateLimitOverflow
+    },
+    /// replay buffered events from `sequence` on, i.e. the last sequence received plus one
+    ResumeFrom {
+        sequence: u64
+    },
+    /// take over the settings of the session `token` named before its connection dropped, with the events it missed
+    Resume {
+        token: String
+    },
+    /// ask for up to `limit` tokens created since `since` that match `filter`, newest first; `id` is echoed in the replies
+    Query {
+        filter: FilterCriteria,
+        since: DateTime<Utc>,
+        limit: usize,
+        #[serde(default)]
+        id: Option<String>
+    },
+    /// name this connection in `listClients` and the send latency metrics, e.g. "alerts-bot"; its address until then
+    Identify {
+        name: String
+    },
+    /// ask for this connection's own statistics
+    GetStats,
+    /// ask for every connection's statistics; requires the server's admin token
+    GetAllStats {
+        token: String
+    },
+    /// list every connection with its filter and outgoing queue; requires the server's admin token
+    ListClients {
+        token: String
+    },
+    /// disconnect the client connected from `addr`, e.g. "203.0.113.7:51234"; requires the server's admin token
+    KickClient {
+        token: String,
+        addr: String
+    },
+    /// ask for the state of the Solana monitor feeding this server; requires the server's admin token
+    GetMonitorStatus {
+        token: String
+    },
+    /// also receive a `diagnostic` for every transaction the monitor skipped or failed on; requires the server's admin token
+    SetDiagnostics {
+        token: String,
+        enabled: bool
+    },
+    /// replace the log filter, e.g. "debug" or "info,pump_fun_monitor_corrected=trace"; requires the server's admin token
+    SetLogLevel {
+        token: String,
+        level: String
+    },
+}
+
+/// notices the server pushes to WebSocket clients alongside token events.
+///
+/// They share the `eventType` discriminator with `TokenCreatedEvent` so clients can dispatch on one field.
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase", tag = "eventType")]
+pub enum ServerMessage {
+    /// the server fell behind and `skipped` events were never sent to this client
+    Lagged {
+        skipped: u64,
+        timestamp: DateTime<Utc>,
+    },
+    /// sent periodically so clients can tell an idle server from a hung one
+    #[serde(rename_all = "camelCase")]
+    Heartbeat {
+        timestamp: DateTime<Utc>,
+        /// seconds since the last event was broadcast; absent before the first one
+        #[serde(skip_serializing_if = "Option::is_none")]
+        last_event_age_secs: Option<u64>,
+        rpc_connected: bool,
+    },
+    /// reply to `getStats`
+    Stats {
+        client: ClientStatsReport,
+    },
+    /// reply to `getAllStats`
+    AllStats {
+        clients: Vec<ClientStatsReport>,
+    },
+    /// reply to `listClients`
+    Clients {
+        clients: Vec<ClientDetails>,
+    },
+    /// reply to `getMonitorStatus`
+    MonitorStatus {
+        status: MonitorStatusReport,
+    },
+    /// reply to `getFilter`
+    Filter {
+        filter: FilterCriteria,
+    },
+    /// reply to `listPresets`, every preset by name
+    Presets {
+        presets: BTreeMap<String, FilterCriteria>,
+    },
+    /// a client request that has no other reply was applied; `action` names it
+    Ack {
+        action: String,
+    },
+    /// `count` matching events were held back by the client's rate limit since the last summary
+    #[serde(rename_all = "camelCase")]
+    Summary {
+        count: u64,
+        /// the most frequent symbols among them, most frequent first
+        top_symbols: Vec<String>,
+        timestamp: DateTime<Utc>,
+    },
+    /// sequences `from..=to` of a `resumeFrom` range are no longer buffered and were not replayed
+    Gap {
+        from: u64,
+        to: u64,
+    },
+    /// sent on connecting: the token to `resume` this session with for `ttlSecs` after the connection drops
+    #[serde(rename_all = "camelCase")]
+    Session {
+        token: String,
+        ttl_secs: u64,
+    },
+    /// ends a `resume`: `replayed` missed events were sent, `dropped` more didn't fit the session's buffer
+    Resumed {
+        replayed: usize,
+        dropped: u64,
+    },
+    /// one token answering a `query`
+    QueryResult {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        token: Box<TokenCreatedEvent>,
+    },
+    /// ends the answer to a `query`; `count` results were sent
+    #[serde(rename_all = "camelCase")]
+    QueryComplete {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        count: usize,
+        /// the answer came from the replay buffer, which doesn't reach back to `since`
+        partial: bool,
+        /// when `partial`, the time from which on every broadcast token was searched
+        #[serde(skip_serializing_if = "Option::is_none")]
+        covered_from: Option<DateTime<Utc>>,
+    },
+    /// a client request was rejected; `code` is the `MonitorError::code` of the reason
+    Error {
+        code: String,
+        message: String,
+        /// what is wrong with each offending field of a rejected filter
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        fields: Vec<FilterProblem>,
+    },
+}
+
+impl From<MonitorError> for ServerMessage {
+    fn from(error: MonitorError) -> Self {
+        let fields = match &error {
+            MonitorError::InvalidFilter(problems) => problems.clone(),
+            _ => Vec::new(),
+        };
+        Self::Error {
+            code: error.code().to_string(),
+            message: error.to_string(),
+            fields,
+        }
+    }
+}
+
+/// statistics for one WebSocket connection.
+///
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientStatsReport {
+    pub addr: String,
+    pub connected_at: DateTime<Utc>,
+    pub last_activity: DateTime<Utc>,
+    pub messages_sent: u64,
+    pub messages_dropped: u64,
+    pub filter_matches: u64,
+}
+
+/// one connection as listed for admins.
+///
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientDetails {
+    pub addr: String,
+    /// the name the client gave with `identify`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub connected_at: DateTime<Utc>,
+    pub filter: FilterCriteria,
+    /// frames queued for the client but not yet written to its socket
+    pub queue_depth: u64,
+    /// median milliseconds from queueing a frame to writing it, over the latest sends; absent before the first
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_latency_p50_ms: Option<f64>,
+    /// 95th percentile of the same
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_latency_p95_ms: Option<f64>,
+}
+
+/// state of the Solana monitor as seen by the WebSocket server.
+///
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorStatusReport {
+    /// WebSocket endpoint of the Solana RPC, without credentials or query string; absent in simulation mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpc_endpoint: Option<String>,
+    pub rpc_connected: bool,
+    /// timestamp of the latest event broadcast to clients
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_event_at: Option<DateTime<Utc>>,
+    /// seconds since the last log notification from the RPC
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_notification_age_secs: Option<u64>,
+    /// signatures waiting to be fetched and parsed
+    pub processor_queue_depth: u64,
+    /// mints currently remembered for dedupe
+    pub dedupe_cache_size: u64,
+}
+
+/// serialization used for events sent to a WebSocket client.
+///
+#[derive(Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WireEncoding {
+    /// JSON in text frames
+    #[default]
+    Json,
+    /// MessagePack (map-encoded, same field names as JSON) in binary frames
+    Msgpack,
+}
+
+/// what happens to matching events beyond a client's rate limit.
+///
+#[derive(Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitOverflow {
+    /// discard them
+    #[default]
+    Drop,
+    /// discard them but send a `summary` of them every second
+    Summarize,
+}
+
+impl std::str::FromStr for WireEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "msgpack" => Ok(Self::Msgpack),
+            other => Err(format!("unsupported encoding '{}', expected json or msgpack", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,197 @@
+//! Unit tests for the wire format: captured events round-tripping, older events parsing, and the versioned envelope.
+
+
+use super::*;
+use chrono::TimeZone;
+use serde_json::json;
+
+/// function to create a token event with the fields the envelope reads set.
+fn token_event() -> TokenCreatedEvent {
+    TokenCreatedEvent {
+        event_type: "tokenCreated".to_string(),
+        timestamp: Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap(),
+        sequence: 42,
+        transaction_signature: "sig_1".to_string(),
+        slot: 280_000_000,
+        token: TokenDetails {
+            mint_address: "mint_A".to_string(),
+            name: "Pepe".to_string(),
+            symbol: "PEPE".to_string(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// function to parse a captured event from `fixtures/`.
+fn fixture(json: &str) -> serde_json::Value {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn test_captured_event_round_trips() {
+    let captured = fixture(include_str!("fixtures/token_created.json"));
+    let event: TokenCreatedEvent = serde_json::from_value(captured.clone()).unwrap();
+    assert_eq!(event.token.raw.as_ref().unwrap().name, "Pepe Classic\u{200b}");
+    assert_eq!(event.creator_tags, ["insider"]);
+    assert!(event.stale);
+
+    // written back exactly as captured, and read back into the same event
+    let written = serde_json::to_value(&event).unwrap();
+    assert_eq!(written, captured);
+    assert_eq!(serde_json::from_value::<TokenCreatedEvent>(written).unwrap(), event);
+}
+
+#[test]
+fn test_events_from_older_and_newer_versions_parse() {
+    // the first release's shape, before sequences, slots and every later field
+    let event: TokenCreatedEvent = serde_json::from_str(include_str!("fixtures/token_created_v0.json")).unwrap();
+    assert_eq!(event.token.name, "MyAwesomeToken");
+    assert_eq!((event.sequence, event.slot, event.same_tx_buyers), (0, 0, 0));
+    assert!(event.program_id.is_empty() && !event.token.uri_valid && !event.pump_data.complete && !event.stale);
+    assert_eq!(event.pump_data.price_sol, None);
+    assert!(event.metadata.is_none() && event.creator_tags.is_empty());
+
+    // a field added by a later version is ignored
+    let mut newer = fixture(include_str!("fixtures/token_created.json"));
+    newer["tradeCount"] = json!(12);
+    newer["token"]["mutable"] = json!(false);
+    let event: TokenCreatedEvent = serde_json::from_value(newer).unwrap();
+    assert_eq!(event.sequence, 42);
+}
+
+#[test]
+fn test_envelope_wraps_the_flat_payload() {
+    let event = token_event();
+    let envelope = serde_json::to_value(MonitorEvent::TokenCreated(Box::new(event.clone()))).unwrap();
+    assert_eq!(envelope["schemaVersion"], SCHEMA_VERSION);
+    assert_eq!(envelope["eventType"], "tokenCreated");
+    assert_eq!(envelope["sequence"], 42);
+    assert_eq!(envelope["timestamp"], "2024-05-01T12:00:00Z");
+    assert_eq!(envelope["data"], serde_json::to_value(&event).unwrap());
+
+    // borrowing the payload writes the same envelope
+    assert_eq!(serde_json::to_value(event.envelope()).unwrap(), envelope);
+    assert_eq!(serde_json::to_value(event.formatted(EventFormat::Envelope)).unwrap(), envelope);
+    assert_eq!(
+        serde_json::to_value(event.formatted(EventFormat::Flat)).unwrap(),
+        serde_json::to_value(&event).unwrap()
+    );
+}
+
+#[test]
+fn test_every_event_type_round_trips() {
+    let token = token_event();
+    let enriched = TokenEnrichedEvent {
+        sequence: 42,
+        timestamp: token.timestamp,
+        mint: "mint_A".to_string(),
+        transaction_signature: "sig_1".to_string(),
+        image_sha256: "ab".repeat(32),
+        image_seen_before: true,
+        image_first_seen_mint: Some("mint_B".to_string()),
+        event: Box::new(token.clone()),
+    };
+    let update = CurveUpdateEvent {
+        mint: "mint_A".to_string(),
+        name: Some(token.token.name.clone()),
+        symbol: Some(token.token.symbol.clone()),
+        virtual_sol_reserves: 40_000_000_000,
+        virtual_token_reserves: 800_000_000_000_000,
+        progress_pct: 34.5,
+        complete: false,
+        slot: 280_000_100,
+        timestamp: token.timestamp,
+    };
+    let migrated = TokenMigratedEvent {
+        timestamp: token.timestamp,
+        transaction_signature: "sig_2".to_string(),
+        slot: 280_000_200,
+        program_id: token.program_id.clone(),
+        mint: "mint_A".to_string(),
+        name: None,
+        symbol: None,
+        bonding_curve: "curve_A".to_string(),
+        pool: "pool_A".to_string(),
+        pool_program: "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA".to_string(),
+        pool_token_reserves: Some(206_900_000_000_000),
+        pool_sol_reserves: None,
+    };
+    let preview = TokenCreatedPreviewEvent {
+        timestamp: token.timestamp,
+        transaction_signature: token.transaction_signature.clone(),
+        program_id: token.program_id.clone(),
+        mint_address: token.token.mint_address.clone(),
+        name: token.token.name.clone(),
+        symbol: token.token.symbol.clone(),
+        uri: token.token.uri.clone(),
+        creator: token.token.creator.clone(),
+        bonding_curve: token.pump_data.bonding_curve.clone(),
+    };
+
+    for event in [
+        MonitorEvent::TokenCreated(Box::new(token)),
+        MonitorEvent::TokenEnriched(enriched),
+        MonitorEvent::CurveUpdate(update),
+        MonitorEvent::TokenMigrated(migrated),
+        MonitorEvent::TokenCreatedPreview(preview),
+    ] {
+        let json = serde_json::to_string(&event).unwrap();
+        let decoded: MonitorEvent = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.event_type(), event.event_type());
+        assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+
+        // MessagePack carries the same names
+        let bytes = rmp_serde::to_vec_named(&event).unwrap();
+        let decoded: MonitorEvent = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+    }
+}
+
+#[test]
+fn test_curve_updates_have_no_sequence() {
+    let update = CurveUpdateEvent {
+        mint: "mint_A".to_string(),
+        name: None,
+        symbol: None,
+        virtual_sol_reserves: 1,
+        virtual_token_reserves: 1,
+        progress_pct: 0.0,
+        complete: true,
+        slot: 1,
+        timestamp: Utc::now(),
+    };
+    let envelope = serde_json::to_value(update.envelope()).unwrap();
+    assert_eq!(envelope["eventType"], "curveUpdate");
+    assert_eq!(envelope["sequence"], 0);
+    assert_eq!(envelope["data"]["eventType"], "curveUpdate");
+}
+
+#[test]
+fn test_newer_schema_and_unknown_types_are_refused() {
+    let mut envelope = serde_json::to_value(token_event().envelope()).unwrap();
+    envelope["schemaVersion"] = json!(SCHEMA_VERSION + 1);
+    let error = serde_json::from_value::<MonitorEvent>(envelope).unwrap_err().to_string();
+    assert!(error.contains("schemaVersion 2 is newer than the supported 1"), "{}", error);
+
+    let unknown = json!({ "schemaVersion": 1, "eventType": "tokenTraded", "data": {}, "sequence": 0, "timestamp": "2024-05-01T12:00:00Z" });
+    let error = serde_json::from_value::<MonitorEvent>(unknown).unwrap_err().to_string();
+    assert!(error.contains("unknown variant `tokenTraded`"), "{}", error);
+}
+
+#[test]
+fn test_event_payload_reads_both_formats() {
+    let event = token_event();
+    let flat = serde_json::to_value(&event).unwrap();
+    let enveloped = serde_json::to_value(event.envelope()).unwrap();
+    assert_eq!(event_payload(&flat), &flat);
+    assert_eq!(event_payload(&enveloped), &flat);
+}
+
+#[test]
+fn test_event_format_parsing() {
+    assert_eq!(" Envelope ".parse::<EventFormat>().unwrap(), EventFormat::Envelope);
+    assert_eq!(EventFormat::default(), EventFormat::Flat);
+    let error = "nested".parse::<EventFormat>().unwrap_err().to_string();
+    assert!(error.contains("'nested' is not one of: flat, envelope"), "{}", error);
+}
@@ -0,0 +1,518 @@
+//! # Event Dispatch
+//!
+//! Hands every sequenced event to subscribers in two tiers, the first before the second:
+//!
+//! - **tier 0, immediate**: served on the dispatching task itself, before anything can wait. The broadcast channel the
+//!   WebSocket server, gRPC streams and the in-memory stores read from is always here: a receiver that falls more than
+//!   `BROADCAST_CAPACITY` events behind loses the oldest ones, so a slow client never slows the monitor down. Sinks
+//!   configured with `tier = 0` are here too, each behind an `ImmediateSubscriber` whose `enqueue` only hands the event
+//!   to the sink's own queue. `enqueue` isn't async, so it can't wait; one that keeps taking longer than
+//!   `IMMEDIATE_SLOW_ENQUEUE_MS` anyway is demoted to tier 1: from its next event on it gets a durable queue of its own,
+//!   filled after the other durable queues, and a blocking task that hands each queued event to its `enqueue`.
+//! - **tier 1, durable**: subscribers that must see every event, such as the sinks writing to PostgreSQL, Kafka or the
+//!   events log. Each gets its own bounded queue of `DURABLE_QUEUE_CAPACITY` events. When one is full,
+//!   `DURABLE_QUEUE_OVERFLOW` decides: `block` (the default) makes the dispatch wait for room, which slows the processor
+//!   down and lets signatures pile up in the processor queue instead; `drop` drops the event for that queue and counts
+//!   it.
+//!
+//! Durable queues are filled one after the other in registration order once tier 0 has the event, so a full durable
+//! queue never delays an event for tier 0, and with `block` nothing is lost for them as long as their consumers
+//! eventually keep up. A durable consumer that has gone away no longer holds anything up. How long each tier took to
+//! have an event is kept in `DispatchLatency`.
+
+use crate::data_models::TokenCreatedEvent;
+use crate::error::{MonitorError, Result};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{error, warn};
+
+/// longest an immediate subscriber's `enqueue` should take unless configured otherwise; anything slower is counted and
+/// logged. Well above the scheduler jitter of a loaded runtime, so only a subscriber that really blocks is slow.
+const DEFAULT_SLOW_ENQUEUE: Duration = Duration::from_millis(50);
+
+/// slow enqueues in a row after which an immediate subscriber is demoted to the durable tier.
+const MAX_SLOW_ENQUEUES: u32 = 10;
+
+/// events a demoted subscriber's queue holds unless configured otherwise.
+const DEFAULT_DEMOTED_CAPACITY: usize = 1_024;
+
+/// which tier a sink is served in.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Tier {
+    /// tier 0: handed each event on the dispatching task, next to the broadcast
+    Immediate,
+    /// tier 1: fed by the sink manager from the broadcast or its durable queue
+    #[default]
+    Queued,
+}
+
+/// what a durable queue does with an event that finds it full.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// wait for room, holding up the events behind it
+    #[default]
+    Block,
+    /// drop the event for this queue and count it
+    Drop,
+}
+
+impl FromStr for OverflowPolicy {
+    type Err = MonitorError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "block" => Ok(Self::Block),
+            "drop" => Ok(Self::Drop),
+            other => Err(MonitorError::Config(format!(
+                "DURABLE_QUEUE_OVERFLOW '{}' is not one of: block, drop",
+                other
+            ))),
+        }
+    }
+}
+
+/// counters of one durable queue, also exported on `/metrics`.
+#[derive(Debug)]
+pub struct DurableQueueStats {
+    name: String,
+    waits: AtomicU64,
+    dropped: AtomicU64,
+    /// the consumer has gone away; logged once
+    closed: AtomicBool,
+}
+
+impl DurableQueueStats {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            waits: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// events that found the queue full and waited for room.
+    pub fn waits(&self) -> u64 {
+        self.waits.load(Ordering::Relaxed)
+    }
+
+    /// events dropped because the queue was full under `OverflowPolicy::Drop`.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// one durable subscriber's queue.
+struct DurableQueue {
+    sender: mpsc::Sender<TokenCreatedEvent>,
+    overflow: OverflowPolicy,
+    stats: Arc<DurableQueueStats>,
+}
+
+impl DurableQueue {
+    /// queues `event` per the overflow policy; `true` once it is queued.
+    async fn push(&self, event: TokenCreatedEvent) -> bool {
+        let event = match self.sender.try_send(event) {
+            Ok(()) => return true,
+            Err(mpsc::error::TrySendError::Full(event)) => event,
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                if !self.stats.closed.swap(true, Ordering::Relaxed) {
+                    warn!("Durable subscriber {} has stopped, no longer queueing events for it", self.stats.name);
+                }
+                return false;
+            }
+        };
+        match self.overflow {
+            OverflowPolicy::Block => {
+                self.stats.waits.fetch_add(1, Ordering::Relaxed);
+                self.sender.send(event).await.is_ok()
+            }
+            OverflowPolicy::Drop => {
+                self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+                warn!("Durable queue {} is full, dropping {}", self.stats.name, event.transaction_signature);
+                false
+            }
+        }
+    }
+}
+
+/// A subscriber served in tier 0, on the dispatching task before any durable queue.
+///
+/// `enqueue` is called for every event while the processor waits for it, so it must only hand the event off, e.g. with
+/// a `try_send`, and never block or do I/O.
+pub trait ImmediateSubscriber: Send + Sync {
+    /// short label for logs and the `subscriber` metric label.
+    fn name(&self) -> &str;
+
+    /// hands `event` off without waiting; `false` when it was dropped instead.
+    fn enqueue(&self, event: &TokenCreatedEvent) -> bool;
+}
+
+/// counters of one immediate subscriber, also exported on `/metrics`.
+#[derive(Debug)]
+pub struct ImmediateStats {
+    name: String,
+    dropped: AtomicU64,
+    slow: AtomicU64,
+    /// slow enqueues since the last fast one
+    slow_in_a_row: AtomicU32,
+    /// handed events through a durable queue of its own instead; logged once
+    demoted: AtomicBool,
+}
+
+impl ImmediateStats {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            dropped: AtomicU64::new(0),
+            slow: AtomicU64::new(0),
+            slow_in_a_row: AtomicU32::new(0),
+            demoted: AtomicBool::new(false),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// events the subscriber didn't take, because its queue was full or its consumer has stopped.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// enqueues that took longer than the dispatcher's slow enqueue threshold.
+    pub fn slow(&self) -> u64 {
+        self.slow.load(Ordering::Relaxed)
+    }
+
+    /// whether the subscriber was moved to the durable tier for being slow `MAX_SLOW_ENQUEUES` times in a row; the
+    /// events it drops include those its durable queue dropped.
+    pub fn demoted(&self) -> bool {
+        self.demoted.load(Ordering::Relaxed)
+    }
+
+    /// counts an enqueue that took `elapsed`, demoting the subscriber once it has been slower than `slow_enqueue` too
+    /// often in a row.
+    fn record_enqueue(&self, elapsed: Duration, slow_enqueue: Duration) {
+        if elapsed <= slow_enqueue {
+            self.slow_in_a_row.store(0, Ordering::Relaxed);
+            return;
+        }
+        self.slow.fetch_add(1, Ordering::Relaxed);
+        let in_a_row = self.slow_in_a_row.fetch_add(1, Ordering::Relaxed) + 1;
+        if in_a_row < MAX_SLOW_ENQUEUES {
+            warn!("Immediate subscriber {} took {}us to enqueue an event", self.name, elapsed.as_micros());
+        } else if !self.demoted.swap(true, Ordering::Relaxed) {
+            error!(
+                "Immediate subscriber {} was slow to enqueue {} times in a row, moving it to a durable queue of its own",
+                self.name, in_a_row
+            );
+        }
+    }
+}
+
+/// one sink's queue in the immediate tier, which drops rather than waits when full.
+struct ImmediateQueue {
+    name: String,
+    sender: mpsc::Sender<TokenCreatedEvent>,
+    /// the consumer has gone away; logged once
+    closed: AtomicBool,
+}
+
+impl ImmediateSubscriber for ImmediateQueue {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn enqueue(&self, event: &TokenCreatedEvent) -> bool {
+        match self.sender.try_send(event.clone()) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(event)) => {
+                warn!("Immediate queue {} is full, dropping {}", self.name, event.transaction_signature);
+                false
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                if !self.closed.swap(true, Ordering::Relaxed) {
+                    warn!("Immediate subscriber {} has stopped, no longer queueing events for it", self.name);
+                }
+                false
+            }
+        }
+    }
+}
+
+/// how long dispatches took to reach one tier, from the start of the dispatch.
+#[derive(Debug, Default)]
+pub struct TierLatency {
+    count: AtomicU64,
+    total_micros: AtomicU64,
+    max_micros: AtomicU64,
+}
+
+impl TierLatency {
+    fn observe(&self, elapsed: Duration) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_micros.fetch_add(micros, Ordering::Relaxed);
+        self.max_micros.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    /// dispatches measured.
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// microseconds taken by all of them together.
+    pub fn total_micros(&self) -> u64 {
+        self.total_micros.load(Ordering::Relaxed)
+    }
+
+    /// the slowest of them.
+    pub fn max(&self) -> Duration {
+        Duration::from_micros(self.max_micros.load(Ordering::Relaxed))
+    }
+}
+
+/// dispatch latency of both tiers, also exported on `/metrics`.
+///
+#[derive(Debug, Default)]
+pub struct DispatchLatency {
+    /// until the immediate subscribers and the broadcast had the event
+    pub immediate: TierLatency,
+    /// until every durable queue and demoted immediate subscriber had it too; only measured while there are any
+    pub durable: TierLatency,
+}
+
+/// where a subscriber reads events from.
+///
+pub enum Subscription {
+    /// the shared broadcast, which skips events for a receiver that falls behind
+    BestEffort(broadcast::Receiver<TokenCreatedEvent>),
+    /// a queue of its own, which never skips
+    Durable(mpsc::Receiver<TokenCreatedEvent>),
+}
+
+impl Subscription {
+    /// the next event, with the broadcast's errors: `Lagged` only ever comes from a best-effort subscription.
+    pub async fn recv(&mut self) -> std::result::Result<TokenCreatedEvent, broadcast::error::RecvError> {
+        match self {
+            Self::BestEffort(receiver) => receiver.recv().await,
+            Self::Durable(receiver) => receiver.recv().await.ok_or(broadcast::error::RecvError::Closed),
+        }
+    }
+}
+
+impl From<broadcast::Receiver<TokenCreatedEvent>> for Subscription {
+    fn from(receiver: broadcast::Receiver<TokenCreatedEvent>) -> Self {
+        Self::BestEffort(receiver)
+    }
+}
+
+/// an immediate subscriber and its counters.
+struct Immediate {
+    subscriber: Arc<dyn ImmediateSubscriber>,
+    stats: Arc<ImmediateStats>,
+    /// where the subscriber's events go once it has been demoted
+    demoted_queue: OnceLock<DurableQueue>,
+}
+
+impl Immediate {
+    /// hands `event` to the subscriber, counting a drop and timing the call; `true` when it took the event.
+    fn enqueue(&self, event: &TokenCreatedEvent, slow_enqueue: Duration) -> bool {
+        let enqueue_started = Instant::now();
+        let taken = self.subscriber.enqueue(event);
+        if !taken {
+            self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        self.stats.record_enqueue(enqueue_started.elapsed(), slow_enqueue);
+        taken
+    }
+}
+
+/// Hands the events of a demoted subscriber's queue to its `enqueue` until the dispatcher is dropped; runs on a
+/// blocking thread, as the subscriber has shown that `enqueue` may block.
+fn serve_demoted(
+    subscriber: Arc<dyn ImmediateSubscriber>,
+    stats: Arc<ImmediateStats>,
+    mut receiver: mpsc::Receiver<TokenCreatedEvent>,
+    slow_enqueue: Duration,
+) {
+    let immediate = Immediate {
+        subscriber,
+        stats,
+        demoted_queue: OnceLock::new(),
+    };
+    while let Some(event) = receiver.blocking_recv() {
+        immediate.enqueue(&event, slow_enqueue);
+    }
+}
+
+/// Sends every event to the immediate tier, the broadcast included, then to the durable queues.
+///
+/// Immediate subscribers and durable queues must be added before the dispatcher is handed to the monitor; best-effort
+/// receivers can subscribe at any time.
+pub struct EventDispatcher {
+    broadcast: broadcast::Sender<TokenCreatedEvent>,
+    immediate: Vec<Immediate>,
+    durable: Vec<DurableQueue>,
+    latency: Arc<DispatchLatency>,
+    /// longest an immediate subscriber's `enqueue` should take
+    slow_enqueue: Duration,
+    /// size and overflow policy of the queue a demoted immediate subscriber gets
+    demoted_capacity: usize,
+    demoted_overflow: OverflowPolicy,
+}
+
+impl EventDispatcher {
+    pub fn new(broadcast: broadcast::Sender<TokenCreatedEvent>) -> Self {
+        Self {
+            broadcast,
+            immediate: Vec::new(),
+            durable: Vec::new(),
+            latency: Arc::default(),
+            slow_enqueue: DEFAULT_SLOW_ENQUEUE,
+            demoted_capacity: DEFAULT_DEMOTED_CAPACITY,
+            demoted_overflow: OverflowPolicy::default(),
+        }
+    }
+
+    /// Counts an immediate subscriber's enqueue as slow once it takes longer than `slow_enqueue`.
+    pub fn with_slow_enqueue(mut self, slow_enqueue: Duration) -> Self {
+        self.slow_enqueue = slow_enqueue;
+        self
+    }
+
+    /// Gives an immediate subscriber demoted to the durable tier a queue of `capacity` events that overflows per
+    /// `overflow`, like any other durable queue.
+    pub fn with_demoted_queues(mut self, capacity: usize, overflow: OverflowPolicy) -> Self {
+        self.demoted_capacity = capacity;
+        self.demoted_overflow = overflow;
+        self
+    }
+
+    /// Adds a subscriber to the immediate tier, returning its counters.
+    pub fn add_immediate(&mut self, subscriber: Arc<dyn ImmediateSubscriber>) -> Arc<ImmediateStats> {
+        let stats = Arc::new(ImmediateStats::new(subscriber.name()));
+        self.immediate.push(Immediate {
+            subscriber,
+            stats: Arc::clone(&stats),
+            demoted_queue: OnceLock::new(),
+        });
+        stats
+    }
+
+    /// Adds a queue of `capacity` events named `name` to the immediate tier, returning it and its counters.
+    pub fn add_immediate_queue(
+        &mut self,
+        name: &str,
+        capacity: usize,
+    ) -> (mpsc::Receiver<TokenCreatedEvent>, Arc<ImmediateStats>) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        let queue = ImmediateQueue {
+            name: name.to_string(),
+            sender,
+            closed: AtomicBool::new(false),
+        };
+        (receiver, self.add_immediate(Arc::new(queue)))
+    }
+
+    /// Adds a durable subscriber named `name`, returning its queue and counters.
+    pub fn add_durable(
+        &mut self,
+        name: &str,
+        capacity: usize,
+        overflow: OverflowPolicy,
+    ) -> (mpsc::Receiver<TokenCreatedEvent>, Arc<DurableQueueStats>) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        let stats = Arc::new(DurableQueueStats::new(name));
+        self.durable.push(DurableQueue {
+            sender,
+            overflow,
+            stats: Arc::clone(&stats),
+        });
+        (receiver, stats)
+    }
+
+    /// a best-effort receiver of every event dispatched from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<TokenCreatedEvent> {
+        self.broadcast.subscribe()
+    }
+
+    /// how long dispatches take to reach each tier.
+    pub fn latency(&self) -> Arc<DispatchLatency> {
+        Arc::clone(&self.latency)
+    }
+
+    /// the durable queue of `immediate` once it has been demoted, started with its serving task on the first call
+    /// after the demotion.
+    fn demoted_queue<'a>(&self, immediate: &'a Immediate) -> Option<&'a DurableQueue> {
+        if !immediate.stats.demoted() {
+            return None;
+        }
+        Some(immediate.demoted_queue.get_or_init(|| {
+            let (sender, receiver) = mpsc::channel(self.demoted_capacity.max(1));
+            let subscriber = Arc::clone(&immediate.subscriber);
+            let stats = Arc::clone(&immediate.stats);
+            let slow_enqueue = self.slow_enqueue;
+            tokio::task::spawn_blocking(move || serve_demoted(subscriber, stats, receiver, slow_enqueue));
+            DurableQueue {
+                sender,
+                overflow: self.demoted_overflow,
+                stats: Arc::new(DurableQueueStats::new(immediate.subscriber.name())),
+            }
+        }))
+    }
+
+    /// Hands `event` to every immediate subscriber and the broadcast, then queues it for every durable subscriber,
+    /// demoted immediate subscribers last; returns how many subscribers got it.
+    pub async fn dispatch(&self, event: TokenCreatedEvent) -> usize {
+        let started = Instant::now();
+        let mut reached = 0;
+        let mut any_demoted = false;
+        for immediate in &self.immediate {
+            // a subscriber demoted by this very enqueue moves to its queue with the next event, so it gets this one once
+            if self.demoted_queue(immediate).is_some() {
+                any_demoted = true;
+            } else if immediate.enqueue(&event, self.slow_enqueue) {
+                reached += 1;
+            }
+        }
+        if self.durable.is_empty() && !any_demoted {
+            reached += self.broadcast.send(event).unwrap_or(0);
+            self.latency.immediate.observe(started.elapsed());
+            return reached;
+        }
+        reached += self.broadcast.send(event.clone()).unwrap_or(0);
+        self.latency.immediate.observe(started.elapsed());
+
+        for queue in &self.durable {
+            if queue.push(event.clone()).await {
+                reached += 1;
+            }
+        }
+        for immediate in &self.immediate {
+            let Some(queue) = immediate.demoted_queue.get() else {
+                continue;
+            };
+            if queue.push(event.clone()).await {
+                reached += 1;
+            } else {
+                immediate.stats.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.latency.durable.observe(started.elapsed());
+        reached
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,255 @@
+//! Unit tests for the two dispatch tiers: immediate subscribers next to the lossy broadcast, and durable queues that
+//! wait or drop as configured.
+
+
+use super::*;
+use crate::rpc_client::EventSequencer;
+use crate::simulation::EventGenerator;
+use std::time::Duration;
+
+/// function to create a dispatcher whose broadcast holds only two events.
+fn dispatcher() -> (EventDispatcher, broadcast::Receiver<TokenCreatedEvent>) {
+    let (sender, receiver) = broadcast::channel(2);
+    (EventDispatcher::new(sender), receiver)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_slow_durable_consumer_gets_every_event() {
+    const EVENTS: u64 = 200;
+    let (mut dispatcher, mut best_effort) = dispatcher();
+    let (mut queue, stats) = dispatcher.add_durable("archive", 4, OverflowPolicy::Block);
+    let sequencer = EventSequencer::new(dispatcher);
+
+    let consumer = tokio::spawn(async move {
+        let mut sequences = Vec::new();
+        while let Some(event) = queue.recv().await {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            sequences.push(event.sequence);
+        }
+        sequences
+    });
+    let mut generator = EventGenerator::new(3);
+    for _ in 0..EVENTS {
+        sequencer.send(generator.next_event()).await;
+    }
+    drop(sequencer);
+
+    // every event arrived, in order, although the consumer was 50 times slower than the queue is long
+    assert_eq!(consumer.await.unwrap(), (1..=EVENTS).collect::<Vec<_>>());
+    assert!(stats.waits() > 0);
+    assert_eq!(stats.dropped(), 0);
+    // the broadcast receiver that never read kept only the last two
+    assert!(matches!(best_effort.recv().await, Err(broadcast::error::RecvError::Lagged(198))));
+}
+
+#[tokio::test]
+async fn test_drop_policy_never_waits() {
+    let (mut dispatcher, _best_effort) = dispatcher();
+    let (mut queue, stats) = dispatcher.add_durable("archive", 2, OverflowPolicy::Drop);
+    let mut generator = EventGenerator::new(3);
+
+    let dispatched = async {
+        for sequence in 1..=5 {
+            let mut event = generator.next_event();
+            event.sequence = sequence;
+            dispatcher.dispatch(event).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(1), dispatched).await.unwrap();
+
+    assert_eq!(stats.dropped(), 3);
+    assert_eq!(stats.waits(), 0);
+    assert_eq!(queue.recv().await.unwrap().sequence, 1);
+    assert_eq!(queue.recv().await.unwrap().sequence, 2);
+}
+
+#[tokio::test]
+async fn test_stopped_durable_subscriber_is_skipped() {
+    let (mut dispatcher, mut best_effort) = dispatcher();
+    let (queue, _) = dispatcher.add_durable("archive", 1, OverflowPolicy::Block);
+    drop(queue);
+
+    let mut generator = EventGenerator::new(3);
+    for _ in 0..3 {
+        let reached = tokio::time::timeout(Duration::from_secs(1), dispatcher.dispatch(generator.next_event()))
+            .await
+            .unwrap();
+        assert_eq!(reached, 1);
+    }
+    assert!(best_effort.recv().await.is_err());
+}
+
+#[tokio::test]
+async fn test_subscription_reads_either_tier() {
+    let (mut dispatcher, best_effort) = dispatcher();
+    let (queue, _) = dispatcher.add_durable("archive", 4, OverflowPolicy::Block);
+    let mut best_effort = Subscription::from(best_effort);
+    let mut durable = Subscription::Durable(queue);
+    let event = EventGenerator::new(3).next_event();
+
+    assert_eq!(dispatcher.dispatch(event.clone()).await, 2);
+    drop(dispatcher);
+    for subscription in [&mut best_effort, &mut durable] {
+        assert_eq!(subscription.recv().await.unwrap().token.mint_address, event.token.mint_address);
+        assert!(matches!(subscription.recv().await, Err(broadcast::error::RecvError::Closed)));
+    }
+}
+
+#[tokio::test]
+async fn test_full_durable_queue_never_delays_the_immediate_tier() {
+    let (mut dispatcher, mut best_effort) = dispatcher();
+    let (mut immediate, immediate_stats) = dispatcher.add_immediate_queue("discord:0", 4);
+    // nobody reads the durable queue, so the second event waits for room forever
+    let (_durable, durable_stats) = dispatcher.add_durable("archive", 1, OverflowPolicy::Block);
+    let latency = dispatcher.latency();
+    let mut generator = EventGenerator::new(3);
+
+    assert_eq!(dispatcher.dispatch(generator.next_event()).await, 3);
+    let blocked = tokio::time::timeout(Duration::from_millis(50), dispatcher.dispatch(generator.next_event())).await;
+    assert!(blocked.is_err());
+
+    // both events reached tier 0 although the second never got into the durable queue
+    for _ in 0..2 {
+        assert!(immediate.try_recv().is_ok());
+        assert!(best_effort.try_recv().is_ok());
+    }
+    assert_eq!(durable_stats.waits(), 1);
+    assert_eq!(immediate_stats.dropped(), 0);
+    assert_eq!((latency.immediate.count(), latency.durable.count()), (2, 1));
+}
+
+#[tokio::test]
+async fn test_full_immediate_queue_drops() {
+    let (mut dispatcher, _best_effort) = dispatcher();
+    let (mut immediate, stats) = dispatcher.add_immediate_queue("discord:0", 2);
+    let mut generator = EventGenerator::new(3);
+    for _ in 0..5 {
+        dispatcher.dispatch(generator.next_event()).await;
+    }
+
+    assert_eq!(stats.dropped(), 3);
+    assert!(immediate.try_recv().is_ok() && immediate.try_recv().is_ok());
+    assert!(immediate.try_recv().is_err());
+}
+
+/// an immediate subscriber that breaks the contract by sleeping in `enqueue`.
+struct SleepingSubscriber {
+    calls: AtomicU64,
+    sleep: Duration,
+}
+
+impl SleepingSubscriber {
+    fn new(sleep: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            calls: AtomicU64::new(0),
+            sleep,
+        })
+    }
+}
+
+impl ImmediateSubscriber for SleepingSubscriber {
+    fn name(&self) -> &str {
+        "sleeping"
+    }
+
+    fn enqueue(&self, _event: &TokenCreatedEvent) -> bool {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        std::thread::sleep(self.sleep);
+        true
+    }
+}
+
+/// function to wait until `done` holds, polling it every millisecond for up to five seconds.
+async fn eventually(done: impl Fn() -> bool) {
+    let waited = tokio::time::timeout(Duration::from_secs(5), async {
+        while !done() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    });
+    waited.await.expect("condition never held");
+}
+
+#[tokio::test]
+async fn test_slow_immediate_subscriber_is_demoted() {
+    let (dispatcher, mut best_effort) = dispatcher();
+    let mut dispatcher = dispatcher.with_slow_enqueue(Duration::from_millis(1));
+    let subscriber = SleepingSubscriber::new(Duration::from_millis(2));
+    let stats = dispatcher.add_immediate(Arc::clone(&subscriber) as Arc<dyn ImmediateSubscriber>);
+    // nobody reads the durable queue, so the second event waits for room forever
+    let (_durable, _) = dispatcher.add_durable("archive", 1, OverflowPolicy::Block);
+    let mut generator = EventGenerator::new(3);
+    // the first event fills the durable queue; a subscriber still in tier 0 gets the second before it blocks
+    dispatcher.dispatch(generator.next_event()).await;
+    let blocked = tokio::time::timeout(Duration::from_millis(50), dispatcher.dispatch(generator.next_event())).await;
+    assert!(blocked.is_err());
+    assert_eq!(subscriber.calls.load(Ordering::Relaxed), 2);
+
+    // never demoted on ordinary scheduler jitter at the default threshold
+    for _ in 0..MAX_SLOW_ENQUEUES {
+        stats.record_enqueue(Duration::from_millis(5), DEFAULT_SLOW_ENQUEUE);
+    }
+    assert!(!stats.demoted());
+
+    for _ in 0..MAX_SLOW_ENQUEUES {
+        stats.record_enqueue(Duration::from_millis(2), Duration::from_millis(1));
+    }
+    assert!(stats.demoted());
+    while best_effort.try_recv().is_ok() {}
+
+    // demoted, it is now behind the full durable queue: the broadcast gets the event, the subscriber doesn't yet
+    let blocked = tokio::time::timeout(Duration::from_millis(50), dispatcher.dispatch(generator.next_event())).await;
+    assert!(blocked.is_err());
+    assert!(best_effort.try_recv().is_ok());
+    assert_eq!(subscriber.calls.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+async fn test_demoted_subscriber_still_gets_every_event() {
+    let (dispatcher, _best_effort) = dispatcher();
+    let mut dispatcher = dispatcher.with_slow_enqueue(Duration::from_millis(1));
+    let subscriber = SleepingSubscriber::new(Duration::from_millis(2));
+    let stats = dispatcher.add_immediate(Arc::clone(&subscriber) as Arc<dyn ImmediateSubscriber>);
+    let mut generator = EventGenerator::new(3);
+    for _ in 0..MAX_SLOW_ENQUEUES + 5 {
+        assert_eq!(dispatcher.dispatch(generator.next_event()).await, 2);
+    }
+
+    assert!(stats.demoted());
+    // the last five reach it through its own queue, on a task of its own
+    eventually(|| stats.slow() == u64::from(MAX_SLOW_ENQUEUES) + 5).await;
+    assert_eq!(subscriber.calls.load(Ordering::Relaxed), u64::from(MAX_SLOW_ENQUEUES) + 5);
+    assert_eq!(stats.dropped(), 0);
+    assert_eq!(dispatcher.latency().durable.count(), 5);
+}
+
+#[tokio::test]
+async fn test_demoted_subscriber_no_longer_holds_up_the_dispatch() {
+    let (dispatcher, _best_effort) = dispatcher();
+    let mut dispatcher = dispatcher.with_demoted_queues(2, OverflowPolicy::Drop);
+    let subscriber = SleepingSubscriber::new(Duration::from_millis(200));
+    let stats = dispatcher.add_immediate(Arc::clone(&subscriber) as Arc<dyn ImmediateSubscriber>);
+    for _ in 0..MAX_SLOW_ENQUEUES {
+        stats.record_enqueue(Duration::from_millis(100), DEFAULT_SLOW_ENQUEUE);
+    }
+    assert!(stats.demoted());
+
+    // five events, each of which would take the subscriber 200ms, are dispatched at once; its queue of two drops
+    // whatever doesn't fit while its task is busy
+    let mut generator = EventGenerator::new(3);
+    let dispatched = async {
+        for _ in 0..5 {
+            dispatcher.dispatch(generator.next_event()).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_millis(100), dispatched).await.unwrap();
+    assert!(stats.dropped() >= 2, "{}", stats.dropped());
+    eventually(|| subscriber.calls.load(Ordering::Relaxed) + stats.dropped() == 5).await;
+}
+
+#[test]
+fn test_overflow_policy_parsing() {
+    assert_eq!(" Block ".parse::<OverflowPolicy>().unwrap(), OverflowPolicy::Block);
+    assert_eq!("drop".parse::<OverflowPolicy>().unwrap(), OverflowPolicy::Drop);
+    let error = "spill".parse::<OverflowPolicy>().unwrap_err().to_string();
+    assert!(error.contains("DURABLE_QUEUE_OVERFLOW 'spill' is not one of: block, drop"), "{}", error);
+}
@@ -1,8 +1,29 @@
 //! # Error Handling
 //! This module defines the error types used throughout the pump.fun monitor service.
+//!
+//! Every variant has a stable `code`, used as the `code` label of the error metrics and in the `code` of `error`
+//! replies to WebSocket clients, and says whether the failed request is worth repeating (`is_retryable`). Errors from
+//! the Solana client are sorted into the specific variants when converted, so retry loops can branch on the variant.
+//!
+//! A rate limit keeps the wait the provider asked for, whether it came as a `Retry-After` header or spelled out in the
+//! error's message, capped at `MAX_RETRY_AFTER`. reqwest keeps the status of a `429` but not its headers, and the
+//! Solana client has already waited out the header between its own attempts by the time it gives up, so for the HTTP
+//! RPC the hint is read from the messages along the error's source chain and from JSON-RPC rate limit messages.
+//!
+//! A call to the HTTP RPC that fails is wrapped in `Rpc`, naming the endpoint and the request id of the call around
+//! the sorted error; classification looks through it.
 
-
+use crate::filtering::FilterProblem;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::rpc_request::RpcError;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::str::FromStr;
+use std::time::Duration;
 use thiserror::Error;
+use tokio_tungstenite::tungstenite::{self, http};
+
+/// longest wait a provider's rate limit hint is taken for; a longer one is most likely a misreading.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
 
 /// Comprehensive error type for all possible failures in the monitor service.
 ///
@@ -12,11 +33,42 @@ pub enum MonitorError {
     #[error("Configuration error: {0}")]
     Config(String),
 
+    /// an RPC failure that none of the more specific variants below describes
     #[error("RPC client error: {0}")]
-    RpcClient(#[from] solana_client::client_error::ClientError),
+    RpcClient(Box<ClientError>),
+
+    /// a failed call to a named RPC endpoint; classified as the failure it wraps, so it retries and counts as that
+    #[error("{source} [endpoint {endpoint}, request {request_id}]")]
+    Rpc {
+        endpoint: String,
+        request_id: String,
+        source: Box<MonitorError>,
+    },
+
+    /// the RPC provider refused the request for exceeding its rate limit
+    #[error("Rate limited by the RPC provider{}", retry_after_note(.retry_after))]
+    RateLimited { retry_after: Option<Duration> },
+
+    #[error("RPC request timed out")]
+    Timeout,
+
+    /// the node doesn't have the transaction, usually because it hasn't caught up with the notification yet
+    #[error("Transaction {0} not found")]
+    TransactionNotFound(Signature),
+
+    #[error("Account {0} not found")]
+    AccountNotFound(Pubkey),
 
     #[error("WebSocket connection error: {0}")]
-    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    WebSocket(Box<tungstenite::Error>),
+
+    #[error("Log subscription rejected: {0}")]
+    Subscription(String),
+
+    /// the RPC provider refused the subscription or connection because the plan's limits are used up; retrying soon
+    /// can't help, so it isn't retryable
+    #[error("RPC provider limit reached: {0}")]
+    ProviderLimit(String),
 
     #[error("JSON serialization/deserialization error: {0}")]
     Json(#[from] serde_json::Error),
@@ -27,13 +79,218 @@ pub enum MonitorError {
     #[error("Failed to parse string to Pubkey")]
     PubkeyParse,
 
+    /// account or instruction data that doesn't start with the discriminator it is read as
+    #[error("Data does not start with the expected discriminator")]
+    InvalidDiscriminator,
+
     #[error("Transaction parsing failed: {0}")]
     TransactionParse(String),
 
     #[error("Required data not found in transaction: {0}")]
     DataNotFound(String),
+
+    #[error("HTTP request error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Sink delivery failed: {0}")]
+    Sink(String),
+
+    /// the archive answering history queries couldn't be read
+    #[error("Event archive query failed: {0}")]
+    Archive(String),
+
+    /// an exported history file that can't be written, read or imported
+    #[error("History archive error: {0}")]
+    ArchiveFile(String),
+
+    /// a client request that needs the admin token came without it
+    #[error("invalid admin token")]
+    Unauthorized,
+
+    /// a client request that can't be carried out as asked
+    #[error("{0}")]
+    InvalidRequest(String),
+
+    /// a filter with criteria that are malformed or can never match; one problem per offending field
+    #[error("invalid filter: {}", join_problems(.0))]
+    InvalidFilter(Vec<FilterProblem>),
+
+    /// a `usePreset` naming a preset the server doesn't have
+    #[error("unknown filter preset '{0}'; listPresets names the available ones")]
+    UnknownPreset(String),
+
+    /// a `resume` naming a session that expired or never existed
+    #[error("session expired or unknown; this connection goes on as a fresh session")]
+    SessionExpired,
+
+    /// a client request for something this server doesn't offer
+    #[error("{0}")]
+    Unsupported(String),
+}
+
+impl MonitorError {
+    /// Whether the same request may succeed later: the data isn't on the node yet, the provider asked to slow down or
+    /// the request never got an answer.
+    ///
+    /// Parse failures are permanent; the data they were read from won't change.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::RateLimited { .. } | Self::Timeout | Self::TransactionNotFound(_) | Self::AccountNotFound(_) => true,
+            Self::RpcClient(e) => match e.kind() {
+                ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => true,
+                // the client asks for the node's version before some calls and keeps only the text of that failure
+                ClientErrorKind::RpcError(RpcError::RpcRequestError(message)) => {
+                    message.starts_with("cluster version query failed")
+                }
+                _ => false,
+            },
+            Self::Rpc { source, .. } => source.is_retryable(),
+            Self::WebSocket(_) => true,
+            Self::Http(e) => e.is_timeout() || e.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// how long the provider asked to wait before the next request, if it said.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after } => *retry_after,
+            Self::Rpc { source, .. } => source.retry_after(),
+            _ => None,
+        }
+    }
+
+    /// the RPC endpoint a failed call went to, for the `endpoint` label of the error metrics.
+    pub fn endpoint(&self) -> Option<&str> {
+        match self {
+            Self::Rpc { endpoint, .. } => Some(endpoint),
+            _ => None,
+        }
+    }
+
+    /// stable identifier of the variant, for metric labels and client error replies.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Config(_) => "config",
+            Self::RpcClient(_) => "rpc",
+            Self::Rpc { source, .. } => source.code(),
+            Self::RateLimited { .. } => "rate_limited",
+            Self::Timeout => "timeout",
+            Self::TransactionNotFound(_) => "transaction_not_found",
+            Self::AccountNotFound(_) => "account_not_found",
+            Self::WebSocket(_) => "websocket",
+            Self::Subscription(_) => "subscription_rejected",
+            Self::ProviderLimit(_) => "provider_limit",
+            Self::Json(_) => "json",
+            Self::Borsh(_) => "borsh",
+            Self::PubkeyParse => "invalid_pubkey",
+            Self::InvalidDiscriminator => "invalid_discriminator",
+            Self::TransactionParse(_) => "transaction_parse",
+            Self::DataNotFound(_) => "data_not_found",
+            Self::Http(_) => "http",
+            Self::Sink(_) => "sink",
+            Self::Archive(_) => "archive",
+            Self::ArchiveFile(_) => "archive_file",
+            Self::Unauthorized => "unauthorized",
+            Self::InvalidRequest(_) => "invalid_request",
+            Self::InvalidFilter(_) => "invalid_filter",
+            Self::UnknownPreset(_) => "unknown_preset",
+            Self::SessionExpired => "session_expired",
+            Self::Unsupported(_) => "unsupported",
+        }
+    }
+}
+
+impl From<ClientError> for MonitorError {
+    /// Sorts a Solana client error into rate limits, timeouts and missing accounts, leaving the rest as `RpcClient`.
+    fn from(error: ClientError) -> Self {
+        match error.kind() {
+            ClientErrorKind::Io(e) if e.kind() == std::io::ErrorKind::TimedOut => Self::Timeout,
+            ClientErrorKind::Reqwest(e) if e.is_timeout() => Self::Timeout,
+            ClientErrorKind::Reqwest(e) if e.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) => {
+                Self::RateLimited { retry_after: bounded(retry_after_in_chain(e)) }
+            }
+            ClientErrorKind::RpcError(RpcError::RpcResponseError { code, message, .. })
+                if *code == 429 || is_rate_limit_message(message) =>
+            {
+                Self::RateLimited { retry_after: bounded(retry_after_hint(message)) }
+            }
+            ClientErrorKind::RpcError(RpcError::ForUser(message)) => {
+                // the client's `getAccountInfo` wrapper reports a missing account as "AccountNotFound: pubkey=<address>"
+                match message.strip_prefix("AccountNotFound: pubkey=").map(Pubkey::from_str) {
+                    Some(Ok(pubkey)) => Self::AccountNotFound(pubkey),
+                    _ => Self::RpcClient(Box::new(error)),
+                }
+            }
+            _ => Self::RpcClient(Box::new(error)),
+        }
+    }
+}
+
+impl From<tungstenite::Error> for MonitorError {
+    /// Picks out a `429` answer to the WebSocket upgrade, keeping its `Retry-After` in seconds.
+    fn from(error: tungstenite::Error) -> Self {
+        match &error {
+            tungstenite::Error::Http(response) if response.status() == http::StatusCode::TOO_MANY_REQUESTS => {
+                let retry_after = response
+                    .headers()
+                    .get(http::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse().ok())
+                    .map(Duration::from_secs);
+                Self::RateLimited { retry_after: bounded(retry_after) }
+            }
+            _ => Self::WebSocket(Box::new(error)),
+        }
+    }
+}
+
+fn join_problems(problems: &[FilterProblem]) -> String {
+    problems.iter().map(FilterProblem::to_string).collect::<Vec<_>>().join("; ")
+}
+
+fn retry_after_note(retry_after: &Option<Duration>) -> String {
+    retry_after.map(|after| format!(", retry after {:?}", after)).unwrap_or_default()
+}
+
+/// providers that rate limit with a JSON-RPC error rather than a `429` say so in the message.
+fn is_rate_limit_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("too many requests") || message.contains("rate limit")
+}
+
+/// Reads a wait spelled out in an error message, such as "retry after 2s", "Retry-After: 1.5" or "try again in 500ms".
+///
+/// A number without a unit is in seconds, like the header's.
+pub fn retry_after_hint(message: &str) -> Option<Duration> {
+    let message = message.to_lowercase();
+    let rest = ["retry after", "retry-after", "retry_after", "try again in"]
+        .iter()
+        .find_map(|phrase| message.find(phrase).map(|at| &message[at + phrase.len()..]))?;
+    let rest = rest.trim_start_matches(|c: char| c.is_whitespace() || matches!(c, ':' | '=' | '"'));
+    let number_len = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+    let amount: f64 = rest[..number_len].parse().ok()?;
+    if !amount.is_finite() || amount < 0.0 {
+        return None;
+    }
+    let unit = rest[number_len..].trim_start();
+    Some(if unit.starts_with("ms") || unit.starts_with("millisecond") {
+        Duration::from_secs_f64(amount / 1000.0)
+    } else {
+        Duration::from_secs_f64(amount)
+    })
+}
+
+/// the first wait spelled out along the source chain of `error`, starting with `error` itself.
+pub fn retry_after_in_chain(error: &(dyn std::error::Error + 'static)) -> Option<Duration> {
+    std::iter::successors(Some(error), |error| error.source()).find_map(|error| retry_after_hint(&error.to_string()))
+}
+
+/// `retry_after` capped at `MAX_RETRY_AFTER`.
+fn bounded(retry_after: Option<Duration>) -> Option<Duration> {
+    retry_after.map(|after| after.min(MAX_RETRY_AFTER))
 }
 
 /// type alias for Results using error type.
 ///
-pub type Result<T> = std::result::Result<T, MonitorError>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, MonitorError>;
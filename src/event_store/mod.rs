@@ -0,0 +1,168 @@
+//! # Event Store
+//!
+//! In-memory store of the most recent token creation events, backing the REST query API. Events are kept ordered by `(timestamp, signature)` so pagination cursors stay stable while new events arrive, and the oldest events are evicted once the configured capacity is reached.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Duration, Utc};
+use futures_util::StreamExt;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::data_models::{FilterCriteria, TokenCreatedEvent};
+use crate::filtering::{CompiledFilter, MatchableEvent};
+use crate::rpc_client::{StreamItem, TokenEventStream};
+
+/// position of an event in the store's ordering.
+type EventKey = (DateTime<Utc>, String);
+
+/// a filtered, paginated query over stored events.
+///
+#[derive(Debug, Clone, Default)]
+pub struct EventQuery {
+    pub filter: FilterCriteria,
+    /// only events at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// continue after the last event of a previous page
+    pub cursor: Option<String>,
+    pub limit: usize,
+}
+
+/// one page of query results, newest first.
+///
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EventPage {
+    pub tokens: Vec<TokenCreatedEvent>,
+    /// pass as `cursor` to fetch the next (older) page; absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// number of stored events created within one hour.
+///
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HourlyCount {
+    pub hour: DateTime<Utc>,
+    pub count: usize,
+}
+
+/// Bounded, ordered, in-memory event store.
+#[derive(Debug)]
+pub struct EventStore {
+    events: RwLock<VecDeque<TokenCreatedEvent>>,
+    capacity: usize,
+}
+
+impl EventStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: RwLock::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    /// Inserts an event at its place in the ordering, evicting the oldest event when full.
+    ///
+    /// The raw transaction is dropped first; the REST API never serves it and it would dwarf the event.
+    pub fn insert(&self, mut event: TokenCreatedEvent) {
+        event.raw_transaction = None;
+        let mut events = self.events.write().unwrap_or_else(|e| e.into_inner());
+        let key = event_key(&event);
+        // events usually arrive in order, so this is almost always the back
+        let position = events.partition_point(|stored| event_key(stored) <= key);
+        events.insert(position, event);
+        if events.len() > self.capacity {
+            events.pop_front();
+        }
+    }
+
+    /// Returns the newest events matching `query`, continuing after `query.cursor` if given.
+    pub fn query(&self, query: &EventQuery) -> Result<EventPage, String> {
+        let cursor = query.cursor.as_deref().map(decode_cursor).transpose()?;
+        let filter = CompiledFilter::from(&query.filter);
+        let events = self.events.read().unwrap_or_else(|e| e.into_inner());
+
+        let mut matching = events
+            .iter()
+            .rev()
+            .filter(|event| match &cursor {
+                Some(cursor) => &event_key(event) < cursor,
+                None => true,
+            })
+            .take_while(|event| match query.since {
+                Some(since) => event.timestamp >= since,
+                None => true,
+            })
+            .filter(|event| filter.matches(&MatchableEvent::new(event)));
+
+        let tokens: Vec<TokenCreatedEvent> = matching.by_ref().take(query.limit).cloned().collect();
+        let next_cursor = match (tokens.last(), matching.next()) {
+            (Some(last), Some(_)) => Some(encode_cursor(&event_key(last))),
+            _ => None,
+        };
+
+        Ok(EventPage { tokens, next_cursor })
+    }
+
+    /// Returns the stored event for `mint`, if any.
+    pub fn get(&self, mint: &str) -> Option<TokenCreatedEvent> {
+        let events = self.events.read().unwrap_or_else(|e| e.into_inner());
+        events.iter().rev().find(|event| event.token.mint_address == mint).cloned()
+    }
+
+    /// Counts stored events per hour for the 24 hours up to and including the hour containing `now`, oldest first.
+    pub fn hourly_counts(&self, now: DateTime<Utc>) -> Vec<HourlyCount> {
+        let current_hour = now.timestamp().div_euclid(3600);
+        let first_hour = current_hour - 23;
+        let mut counts = vec![0usize; 24];
+
+        let events = self.events.read().unwrap_or_else(|e| e.into_inner());
+        for event in events.iter() {
+            let hour = event.timestamp.timestamp().div_euclid(3600);
+            if (first_hour..=current_hour).contains(&hour) {
+                counts[(hour - first_hour) as usize] += 1;
+            }
+        }
+
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(offset, count)| HourlyCount {
+                hour: DateTime::<Utc>::UNIX_EPOCH + Duration::hours(first_hour + offset as i64),
+                count,
+            })
+            .collect()
+    }
+}
+
+/// Feeds every broadcast event into `store` until the channel closes.
+pub async fn record_events(store: Arc<EventStore>, mut events: TokenEventStream) {
+    while let Some(event) = events.next().await {
+        match event {
+            StreamItem::Token(event) => store.insert(*event),
+            StreamItem::Gap { skipped } => warn!("Event store lagged, {} events were not stored", skipped),
+        }
+    }
+}
+
+fn event_key(event: &TokenCreatedEvent) -> EventKey {
+    (event.timestamp, event.transaction_signature.clone())
+}
+
+/// cursors are `<unix nanos>:<signature>`; signatures are base58 so the separator is unambiguous.
+fn encode_cursor((timestamp, signature): &EventKey) -> String {
+    format!("{}:{}", timestamp.timestamp_nanos_opt().unwrap_or_default(), signature)
+}
+
+fn decode_cursor(cursor: &str) -> Result<EventKey, String> {
+    let invalid = || format!("invalid cursor '{}'", cursor);
+    let (nanos, signature) = cursor.split_once(':').ok_or_else(invalid)?;
+    let nanos = nanos.parse::<i64>().map_err(|_| invalid())?;
+    Ok((DateTime::from_timestamp_nanos(nanos), signature.to_string()))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,195 @@
+//! Unit tests for the in-memory event store.
+
+
+use super::*;
+use crate::data_models::{PumpFunData, TokenDetails};
+use chrono::TimeZone;
+
+/// function to create a dummy event created `secs` seconds after a fixed base time.
+fn create_test_event(secs: i64, signature: &str, symbol: &str) -> TokenCreatedEvent {
+    TokenCreatedEvent {
+        event_type: "tokenCreated".to_string(),
+        timestamp: Utc.timestamp_opt(1_705_312_800 + secs, 0).unwrap(),
+        sequence: 0,
+        transaction_signature: signature.to_string(),
+        slot: 280_000_000,
+        program_id: "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P".to_string(),
+        token: TokenDetails {
+            mint_address: format!("mint_{}", signature),
+            name: "My Token".to_string(),
+            symbol: symbol.to_string(),
+            uri: "https://test.example.com/metadata.json".to_string(),
+            creator: "creator_A".to_string(),
+            supply: Some(1_000_000),
+            decimals: Some(6),
+            mint_authority: None,
+            freeze_authority: None,
+            authorities_standard: Some(true),
+            sanitized: false,
+            uri_valid: true,
+            raw: None,
+        },
+        pump_data: PumpFunData {
+            bonding_curve: "test_curve_GHI789jkl012".to_string(),
+            virtual_sol_reserves: Some(30_000_000_000),
+            virtual_token_reserves: Some(1_073_000_000_000_000),
+            real_sol_reserves: Some(0),
+            real_token_reserves: Some(793_100_000_000_000),
+            complete: false,
+            price_sol: Some(0.000000028),
+            market_cap_sol: Some(0.000028),
+            price_usd: None,
+            market_cap_usd: None,
+        },
+        metadata: None,
+        initial_buy: None,
+        same_tx_buyers: 0,
+        same_tx_buy_sol: 0,
+        tx_fee_lamports: 5_000,
+        priority_fee_lamports: 0,
+        compute_units: None,
+        creator_stats: None,
+        creator_label: None,
+        creator_tags: Vec::new(),
+        similar_recent_count: None,
+        processing_latency_ms: None,
+        chain_latency_ms: None,
+        stale: false,
+        signer_pubkey: None,
+        signature: None,
+        raw_transaction: None,
+    }
+}
+
+fn signatures(page: &EventPage) -> Vec<&str> {
+    page.tokens.iter().map(|event| event.transaction_signature.as_str()).collect()
+}
+
+fn query(limit: usize) -> EventQuery {
+    EventQuery {
+        limit,
+        ..EventQuery::default()
+    }
+}
+
+#[test]
+fn test_query_returns_newest_first() {
+    let store = EventStore::new(10);
+    store.insert(create_test_event(1, "a", "TKN"));
+    store.insert(create_test_event(3, "c", "TKN"));
+    store.insert(create_test_event(2, "b", "TKN"));
+
+    let page = store.query(&query(10)).unwrap();
+    assert_eq!(signatures(&page), vec!["c", "b", "a"]);
+    assert_eq!(page.next_cursor, None);
+}
+
+#[test]
+fn test_capacity_evicts_oldest() {
+    let store = EventStore::new(2);
+    store.insert(create_test_event(1, "a", "TKN"));
+    store.insert(create_test_event(2, "b", "TKN"));
+    store.insert(create_test_event(3, "c", "TKN"));
+
+    assert_eq!(signatures(&store.query(&query(10)).unwrap()), vec!["c", "b"]);
+    assert!(store.get("mint_a").is_none());
+}
+
+#[test]
+fn test_cursor_pages_are_stable_while_events_arrive() {
+    let store = EventStore::new(10);
+    for (secs, signature) in [(1, "a"), (2, "b"), (3, "c"), (4, "d")] {
+        store.insert(create_test_event(secs, signature, "TKN"));
+    }
+
+    let first = store.query(&query(2)).unwrap();
+    assert_eq!(signatures(&first), vec!["d", "c"]);
+
+    // a new event must not shift the next page
+    store.insert(create_test_event(5, "e", "TKN"));
+    let second = store
+        .query(&EventQuery {
+            cursor: first.next_cursor.clone(),
+            ..query(2)
+        })
+        .unwrap();
+    assert_eq!(signatures(&second), vec!["b", "a"]);
+    assert_eq!(second.next_cursor, None);
+}
+
+#[test]
+fn test_cursor_breaks_timestamp_ties_by_signature() {
+    let store = EventStore::new(10);
+    store.insert(create_test_event(1, "a", "TKN"));
+    store.insert(create_test_event(1, "b", "TKN"));
+    store.insert(create_test_event(1, "c", "TKN"));
+
+    let first = store.query(&query(1)).unwrap();
+    let second = store
+        .query(&EventQuery {
+            cursor: first.next_cursor.clone(),
+            ..query(5)
+        })
+        .unwrap();
+    assert_eq!(signatures(&first), vec!["c"]);
+    assert_eq!(signatures(&second), vec!["b", "a"]);
+}
+
+#[test]
+fn test_query_applies_filter_and_since() {
+    let store = EventStore::new(10);
+    store.insert(create_test_event(1, "a", "PEPE"));
+    store.insert(create_test_event(2, "b", "DOGE"));
+    store.insert(create_test_event(3, "c", "PEPE"));
+    store.insert(create_test_event(4, "d", "PEPE"));
+
+    let page = store
+        .query(&EventQuery {
+            filter: FilterCriteria {
+                symbol: Some("PEPE".to_string()),
+                ..FilterCriteria::default()
+            },
+            since: Some(Utc.timestamp_opt(1_705_312_800 + 2, 0).unwrap()),
+            ..query(10)
+        })
+        .unwrap();
+    assert_eq!(signatures(&page), vec!["d", "c"]);
+}
+
+#[test]
+fn test_invalid_cursor_rejected() {
+    let store = EventStore::new(10);
+    let result = store.query(&EventQuery {
+        cursor: Some("not-a-cursor".to_string()),
+        ..query(10)
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_get_by_mint() {
+    let store = EventStore::new(10);
+    store.insert(create_test_event(1, "a", "TKN"));
+
+    assert_eq!(store.get("mint_a").unwrap().transaction_signature, "a");
+    assert!(store.get("mint_missing").is_none());
+}
+
+#[test]
+fn test_hourly_counts_cover_last_24_hours() {
+    let store = EventStore::new(10);
+    store.insert(create_test_event(0, "a", "TKN"));
+    store.insert(create_test_event(10, "b", "TKN"));
+    store.insert(create_test_event(3_600, "c", "TKN"));
+    // older than 24 hours relative to `now`
+    store.insert(create_test_event(-30 * 3_600, "d", "TKN"));
+
+    let now = Utc.timestamp_opt(1_705_312_800 + 3_600 + 120, 0).unwrap();
+    let counts = store.hourly_counts(now);
+
+    assert_eq!(counts.len(), 24);
+    assert_eq!(counts[23].hour, Utc.timestamp_opt(1_705_312_800 + 3_600, 0).unwrap());
+    assert_eq!(counts[23].count, 1);
+    assert_eq!(counts[22].count, 2);
+    assert_eq!(counts.iter().map(|hour| hour.count).sum::<usize>(), 3);
+}
@@ -0,0 +1,179 @@
+//! # History Export
+//!
+//! Moves stored events between databases as a zstd-compressed JSON lines file, for sharing a dataset or seeding a
+//! new instance. `export` streams the events created in a time range out of the persistence backend, oldest first,
+//! through the encoder and into the file, so nothing but the current event is held in memory, and then writes a
+//! manifest next to it: the event count, the time range and the schema version of the events. `import` reads the
+//! manifest first and refuses a file written with another schema version, then inserts the events in batches,
+//! skipping the ones the database already has, and fails if the file doesn't hold as many events as its manifest says.
+//!
+//! Events are archived in the flat format, exactly as `schema::wire_schemas` describes `TokenCreatedEvent`.
+
+use crate::data_models::TokenCreatedEvent;
+use crate::error::{MonitorError, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// version of the archived event format; bumped whenever a change to `TokenCreatedEvent` would break reading old files.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// zstd's default level, fast enough to keep up with a database cursor.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// events inserted by one call to the backend during an import.
+const IMPORT_BATCH: usize = 500;
+
+/// a persistence backend events can be exported from and imported into.
+#[async_trait]
+pub trait HistoryStore: Send + Sync {
+    /// the events created from `from` up to, but not including, `to`, oldest first.
+    fn events(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> BoxStream<'_, Result<TokenCreatedEvent>>;
+
+    /// stores `events`, skipping mints that are already stored, and returns how many were new.
+    async fn insert(&self, events: &[TokenCreatedEvent]) -> Result<u64>;
+}
+
+/// what an archive holds, written next to it as `<archive>.manifest.json`.
+///
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub schema_version: u32,
+    pub events: u64,
+    /// the range that was asked for; `from` included, `to` not
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// timestamps of the oldest and newest event in the file, absent when it is empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_event_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_event_at: Option<DateTime<Utc>>,
+    pub exported_at: DateTime<Utc>,
+    /// version of the monitor that wrote the file
+    pub monitor_version: String,
+}
+
+/// the outcome of an import.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub read: u64,
+    pub inserted: u64,
+}
+
+/// the manifest path of the archive at `archive`.
+pub fn manifest_path(archive: &Path) -> PathBuf {
+    let mut path = archive.as_os_str().to_owned();
+    path.push(".manifest.json");
+    PathBuf::from(path)
+}
+
+/// Writes the events created in `from..to` to a zstd-compressed JSON lines file at `out`, and its manifest next to
+/// it.
+///
+/// The manifest is written last, so an export that failed half way leaves no manifest and can't be imported.
+pub async fn export(store: &dyn HistoryStore, from: DateTime<Utc>, to: DateTime<Utc>, out: &Path) -> Result<Manifest> {
+    if from >= to {
+        return Err(MonitorError::ArchiveFile(format!("--from {} is not before --to {}", from, to)));
+    }
+    let file = File::create(out).map_err(|e| archive_error(out, e))?;
+    let mut encoder = zstd::Encoder::new(BufWriter::new(file), COMPRESSION_LEVEL).map_err(|e| archive_error(out, e))?;
+    let mut manifest = Manifest {
+        schema_version: SCHEMA_VERSION,
+        events: 0,
+        from,
+        to,
+        first_event_at: None,
+        last_event_at: None,
+        exported_at: Utc::now(),
+        monitor_version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+
+    let mut events = store.events(from, to);
+    while let Some(event) = events.try_next().await? {
+        serde_json::to_writer(&mut encoder, &event)?;
+        encoder.write_all(b"\n").map_err(|e| archive_error(out, e))?;
+        manifest.events += 1;
+        manifest.first_event_at.get_or_insert(event.timestamp);
+        manifest.last_event_at = Some(event.timestamp);
+    }
+    encoder
+        .finish()
+        .and_then(|mut writer| writer.flush())
+        .map_err(|e| archive_error(out, e))?;
+
+    let manifest_path = manifest_path(out);
+    let json = serde_json::to_vec_pretty(&manifest)?;
+    std::fs::write(&manifest_path, json).map_err(|e| archive_error(&manifest_path, e))?;
+    Ok(manifest)
+}
+
+/// Reads the manifest of the archive at `archive`, refusing one written with another schema version.
+pub fn read_manifest(archive: &Path) -> Result<Manifest> {
+    let path = manifest_path(archive);
+    let json = std::fs::read(&path).map_err(|e| archive_error(&path, e))?;
+    let manifest: Manifest = serde_json::from_slice(&json)
+        .map_err(|e| MonitorError::ArchiveFile(format!("{}: {}", path.display(), e)))?;
+    if manifest.schema_version != SCHEMA_VERSION {
+        return Err(MonitorError::ArchiveFile(format!(
+            "{} was written with schema version {}, this monitor reads version {}",
+            archive.display(),
+            manifest.schema_version,
+            SCHEMA_VERSION
+        )));
+    }
+    Ok(manifest)
+}
+
+/// Inserts every event of the archive at `archive` into `store`, skipping the ones it already has.
+///
+/// The manifest is checked before anything is inserted; a file that doesn't hold the manifest's count fails the import
+/// after the events it did hold went in, which a repeated import of a complete copy then skips.
+pub async fn import(store: &dyn HistoryStore, archive: &Path) -> Result<ImportSummary> {
+    let manifest = read_manifest(archive)?;
+    let file = File::open(archive).map_err(|e| archive_error(archive, e))?;
+    let decoder = zstd::Decoder::new(file).map_err(|e| archive_error(archive, e))?;
+
+    let mut summary = ImportSummary { read: 0, inserted: 0 };
+    let mut batch = Vec::with_capacity(IMPORT_BATCH);
+    for (index, line) in BufReader::new(decoder).lines().enumerate() {
+        let line = line.map_err(|e| archive_error(archive, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event: TokenCreatedEvent = serde_json::from_str(&line)
+            .map_err(|e| MonitorError::ArchiveFile(format!("{} line {}: {}", archive.display(), index + 1, e)))?;
+        batch.push(event);
+        summary.read += 1;
+        if batch.len() == IMPORT_BATCH {
+            summary.inserted += store.insert(&batch).await?;
+            batch.clear();
+        }
+    }
+    if !batch.is_empty() {
+        summary.inserted += store.insert(&batch).await?;
+    }
+
+    if summary.read != manifest.events {
+        return Err(MonitorError::ArchiveFile(format!(
+            "{} holds {} events, its manifest says {}",
+            archive.display(),
+            summary.read,
+            manifest.events
+        )));
+    }
+    Ok(summary)
+}
+
+fn archive_error(path: &Path, error: std::io::Error) -> MonitorError {
+    MonitorError::ArchiveFile(format!("{}: {}", path.display(), error))
+}
+
+#[cfg(test)]
+mod tests;
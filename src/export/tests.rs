@@ -0,0 +1,147 @@
+//! Unit tests for history export and import, against an in-memory store.
+
+use super::*;
+use crate::simulation::EventGenerator;
+use futures_util::StreamExt;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// stands in for a database: events keyed by mint, inserts skip stored mints.
+#[derive(Default)]
+struct MemoryStore {
+    stored: Mutex<BTreeMap<String, TokenCreatedEvent>>,
+}
+
+#[async_trait]
+impl HistoryStore for MemoryStore {
+    fn events(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> BoxStream<'_, Result<TokenCreatedEvent>> {
+        let mut events: Vec<TokenCreatedEvent> = self
+            .stored
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|event| event.timestamp >= from && event.timestamp < to)
+            .cloned()
+            .collect();
+        events.sort_by(|a, b| (a.timestamp, &a.token.mint_address).cmp(&(b.timestamp, &b.token.mint_address)));
+        futures_util::stream::iter(events.into_iter().map(Ok)).boxed()
+    }
+
+    async fn insert(&self, events: &[TokenCreatedEvent]) -> Result<u64> {
+        let mut stored = self.stored.lock().unwrap();
+        let mut inserted = 0;
+        for event in events {
+            if !stored.contains_key(&event.token.mint_address) {
+                stored.insert(event.token.mint_address.clone(), event.clone());
+                inserted += 1;
+            }
+        }
+        Ok(inserted)
+    }
+}
+
+/// function to return the time `secs` seconds into the synthetic history.
+fn at(secs: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(1_718_000_000 + secs, 0).unwrap()
+}
+
+/// function to fill a store with `count` synthetic events, one a second.
+fn synthetic_store(count: i64) -> MemoryStore {
+    let mut generator = EventGenerator::new(7);
+    let store = MemoryStore::default();
+    let mut stored = store.stored.lock().unwrap();
+    for secs in 0..count {
+        let mut event = generator.next_event();
+        event.timestamp = at(secs);
+        stored.insert(event.token.mint_address.clone(), event);
+    }
+    drop(stored);
+    store
+}
+
+/// function to return a scratch directory for `name`, emptied first.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pump_fun_monitor_export_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[tokio::test]
+async fn test_export_then_import_round_trip() {
+    let dir = scratch_dir("round_trip");
+    let out = dir.join("events.jsonl.zst");
+    let source = synthetic_store(3_000);
+
+    let manifest = export(&source, at(500), at(2_500), &out).await.unwrap();
+    assert_eq!(manifest.schema_version, SCHEMA_VERSION);
+    assert_eq!(manifest.events, 2_000);
+    assert_eq!((manifest.first_event_at, manifest.last_event_at), (Some(at(500)), Some(at(2_499))));
+    assert_eq!(read_manifest(&out).unwrap(), manifest);
+    let expected: Vec<TokenCreatedEvent> = source.events(at(500), at(2_500)).map(Result::unwrap).collect().await;
+    // a zstd frame, smaller than the JSON it holds
+    let archive = std::fs::read(&out).unwrap();
+    assert!(archive.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]));
+    let json_len: usize = expected.iter().map(|event| serde_json::to_vec(event).unwrap().len() + 1).sum();
+    assert!(archive.len() < json_len);
+
+    let target = MemoryStore::default();
+    assert_eq!(import(&target, &out).await.unwrap(), ImportSummary { read: 2_000, inserted: 2_000 });
+    let imported: Vec<TokenCreatedEvent> = target.events(at(0), at(3_000)).map(Result::unwrap).collect().await;
+    assert_eq!(imported, expected);
+
+    // importing the same file again only finds stored mints
+    assert_eq!(import(&target, &out).await.unwrap(), ImportSummary { read: 2_000, inserted: 0 });
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_export_of_an_empty_range() {
+    let dir = scratch_dir("empty");
+    let out = dir.join("events.jsonl.zst");
+
+    let manifest = export(&synthetic_store(10), at(100), at(200), &out).await.unwrap();
+    assert_eq!(manifest.events, 0);
+    assert_eq!(manifest.first_event_at, None);
+    let target = MemoryStore::default();
+    assert_eq!(import(&target, &out).await.unwrap(), ImportSummary { read: 0, inserted: 0 });
+
+    assert!(export(&synthetic_store(10), at(200), at(100), &out).await.is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_import_refuses_another_schema_version() {
+    let dir = scratch_dir("schema_version");
+    let out = dir.join("events.jsonl.zst");
+    export(&synthetic_store(50), at(0), at(50), &out).await.unwrap();
+
+    let path = manifest_path(&out);
+    let mut manifest: serde_json::Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    manifest["schemaVersion"] = serde_json::json!(SCHEMA_VERSION + 1);
+    std::fs::write(&path, manifest.to_string()).unwrap();
+
+    let target = MemoryStore::default();
+    let error = import(&target, &out).await.unwrap_err();
+    assert!(error.to_string().contains("schema version 2"), "{}", error);
+    // nothing went in
+    assert!(target.stored.lock().unwrap().is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_import_checks_the_manifest_count() {
+    let dir = scratch_dir("manifest_count");
+    let out = dir.join("events.jsonl.zst");
+    let mut manifest = export(&synthetic_store(50), at(0), at(50), &out).await.unwrap();
+
+    manifest.events += 1;
+    std::fs::write(manifest_path(&out), serde_json::to_vec(&manifest).unwrap()).unwrap();
+    let error = import(&MemoryStore::default(), &out).await.unwrap_err();
+    assert!(error.to_string().contains("holds 50 events, its manifest says 51"), "{}", error);
+
+    // an archive without its manifest can't be imported at all
+    std::fs::remove_file(manifest_path(&out)).unwrap();
+    assert!(import(&MemoryStore::default(), &out).await.is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,403 @@
+//! # Filtering
+//!
+//! `FilterCriteria` evaluation shared by everything that delivers a subset of the event stream: WebSocket clients, gRPC
+//! subscriptions, the REST query API and the sinks. Every filter passes through `normalize_filter` where it enters the
+//! service, whether from a client or the config file: malformed or impossible criteria are rejected there, naming the
+//! field, instead of silently matching nothing, and the per-event checks don't have to clean up the criteria again.
+//!
+//! Matching itself runs on a `CompiledFilter`, built once per filter, against a `MatchableEvent`, built once per event,
+//! so checking one event against thousands of clients compares prepared strings instead of upper-casing both sides for
+//! every client.
+
+use crate::data_models::{FilterCriteria, TokenCreatedEvent};
+use schemars::JsonSchema;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use std::cell::OnceCell;
+use std::fmt;
+use std::str::FromStr;
+
+/// longest `symbol` a filter may ask for, in characters.
+pub const MAX_SYMBOL_LEN: usize = 10;
+/// longest `nameContains` a filter may ask for, in characters.
+pub const MAX_NAME_FILTER_LEN: usize = 100;
+
+/// one thing wrong with a filter.
+///
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct FilterProblem {
+    /// the criterion at fault, as it is spelled in JSON, e.g. "creator"
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl FilterProblem {
+    fn new(field: &'static str, message: String) -> Self {
+        Self { field, message }
+    }
+}
+
+impl fmt::Display for FilterProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Checks if a token creation event matches the specified filter criteria.
+///
+/// Compiles `filter` for this one event; code matching many events against one filter, or one event against many
+/// filters, keeps the `CompiledFilter` or `MatchableEvent` instead.
+pub fn matches_filter(event: &TokenCreatedEvent, filter: &FilterCriteria) -> bool {
+    CompiledFilter::from(filter).matches(&MatchableEvent::new(event))
+}
+
+/// `FilterCriteria` prepared for matching: the case-insensitive needles are upper-cased and the URI host and creator tag
+/// lower-cased once, when the filter is set, so matching an event allocates nothing.
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompiledFilter {
+    criteria: FilterCriteria,
+    symbol: Option<String>,
+    name_contains: Option<String>,
+    /// `uriHost` in lower case, without leading dots
+    uri_host: Option<String>,
+    /// `creatorTag` in lower case, the way watchlist tags are stored
+    creator_tag: Option<String>,
+}
+
+impl From<&FilterCriteria> for CompiledFilter {
+    fn from(criteria: &FilterCriteria) -> Self {
+        Self {
+            criteria: criteria.clone(),
+            symbol: criteria.symbol.as_deref().map(str::to_uppercase),
+            name_contains: criteria.name_contains.as_deref().map(str::to_uppercase),
+            uri_host: criteria.uri_host.as_deref().map(|host| host.trim_start_matches('.').to_ascii_lowercase()),
+            creator_tag: criteria.creator_tag.as_deref().map(str::to_lowercase),
+        }
+    }
+}
+
+impl CompiledFilter {
+    /// the criteria as the client sent them, e.g. to echo them back.
+    pub fn criteria(&self) -> &FilterCriteria {
+        &self.criteria
+    }
+
+    /// whether `event` meets every criterion.
+    pub fn matches(&self, event: &MatchableEvent) -> bool {
+        let filter = &self.criteria;
+        let token = &event.event.token;
+
+        // check creator filter
+        if filter.creator.as_ref().is_some_and(|creator| token.creator != *creator) {
+            return false;
+        }
+
+        // check watchlist tag filter
+        if self.creator_tag.as_ref().is_some_and(|tag| !event.event.creator_tags.contains(tag)) {
+            return false;
+        }
+
+        // check symbol filter
+        if self.symbol.as_ref().is_some_and(|symbol| event.symbol != *symbol) {
+            return false;
+        }
+
+        // check name contains filter
+        if self.name_contains.as_ref().is_some_and(|name| !event.name.contains(name.as_str())) {
+            return false;
+        }
+
+        // check URI filters; a URI that doesn't parse never matches them
+        if self.uri_host.is_some() || filter.uri_scheme.is_some() {
+            let Some((scheme, host)) = event.uri() else {
+                return false;
+            };
+            if filter.uri_scheme.as_ref().is_some_and(|expected| !scheme.eq_ignore_ascii_case(expected)) {
+                return false;
+            }
+            if let Some(expected) = &self.uri_host {
+                if !host.as_deref().is_some_and(|host| host_matches(host, expected)) {
+                    return false;
+                }
+            }
+        }
+
+        // check program filter
+        if filter.program.as_ref().is_some_and(|program| event.event.program_id != *program) {
+            return false;
+        }
+
+        let event = event.event;
+
+        // check mint authorities filter
+        if filter.require_standard_authorities && event.token.authorities_standard != Some(true) {
+            return false;
+        }
+
+        // check slot filter
+        if filter.min_slot.is_some_and(|min_slot| event.slot < min_slot) {
+            return false;
+        }
+
+        // check creator history filters
+        let tokens_created = event.creator_stats.as_ref().map_or(0, |stats| stats.tokens_created_seen);
+        if filter.min_creator_tokens.is_some_and(|min| tokens_created < min) {
+            return false;
+        }
+        if filter.max_creator_tokens.is_some_and(|max| tokens_created > max) {
+            return false;
+        }
+        if let Some(min) = filter.min_creator_graduation_rate {
+            let rate = event.creator_stats.as_ref().and_then(|stats| stats.graduation_rate);
+            if !rate.is_some_and(|rate| rate >= min) {
+                return false;
+            }
+        }
+
+        // check initial buy filter
+        if let Some(min) = filter.min_initial_buy_sol {
+            let bought = event.initial_buy.map_or(0, |buy| buy.sol_amount) as f64 / 1_000_000_000.0;
+            if bought < min {
+                return false;
+            }
+        }
+
+        // check bundled buyers filter
+        if filter.max_same_tx_buyers.is_some_and(|max| event.same_tx_buyers > max) {
+            return false;
+        }
+
+        // check priority fee filter
+        if filter.min_priority_fee_lamports.is_some_and(|min| event.priority_fee_lamports < min) {
+            return false;
+        }
+
+        // check copycat filters
+        let similar = event.similar_recent_count.unwrap_or(0);
+        if filter.min_similar_recent_count.is_some_and(|min| similar < min) {
+            return false;
+        }
+        if filter.max_similar_recent_count.is_some_and(|max| similar > max) {
+            return false;
+        }
+
+        // check metadata filters
+        let metadata = event.metadata.as_ref();
+        if let Some(has_image) = filter.has_image {
+            if metadata.is_some_and(|metadata| metadata.image.is_some()) != has_image {
+                return false;
+            }
+        }
+        if let Some(has_twitter) = filter.has_twitter {
+            if metadata.is_some_and(|metadata| metadata.twitter.is_some()) != has_twitter {
+                return false;
+            }
+        }
+        if filter.require_twitter
+            && !metadata.is_some_and(|metadata| {
+                metadata.twitter.is_some() && !metadata.twitter_is_community_handle && !metadata.socials_reused
+            })
+        {
+            return false;
+        }
+        if filter.require_website && metadata.is_none_or(|metadata| metadata.website.is_none()) {
+            return false;
+        }
+        if let Some(seen_before) = filter.image_seen_before {
+            if metadata.and_then(|metadata| metadata.image_seen_before).unwrap_or(false) != seen_before {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// an event with the fields filters compare case-insensitively normalized once, before it is matched against every
+/// client's filter.
+///
+pub struct MatchableEvent<'a> {
+    event: &'a TokenCreatedEvent,
+    symbol: String,
+    name: String,
+    /// scheme and lower-cased host of `token.uri`, parsed the first time a filter asks; `None` when it doesn't parse
+    uri: OnceCell<Option<(String, Option<String>)>>,
+}
+
+impl<'a> MatchableEvent<'a> {
+    pub fn new(event: &'a TokenCreatedEvent) -> Self {
+        Self {
+            event,
+            symbol: event.token.symbol.to_uppercase(),
+            name: event.token.name.to_uppercase(),
+            uri: OnceCell::new(),
+        }
+    }
+
+    fn uri(&self) -> Option<&(String, Option<String>)> {
+        self.uri
+            .get_or_init(|| {
+                let uri = url::Url::parse(&self.event.token.uri).ok()?;
+                Some((uri.scheme().to_string(), uri.host_str().map(str::to_ascii_lowercase)))
+            })
+            .as_ref()
+    }
+}
+
+/// whether `host` is `expected` or one of its subdomains; both are lower case.
+fn host_matches(host: &str, expected: &str) -> bool {
+    host == expected || host.strip_suffix(expected).is_some_and(|subdomain| subdomain.ends_with('.'))
+}
+
+/// Brings `filter` into the form it is stored and echoed in, once, when it is set: string criteria trimmed and the
+/// symbol in upper case.
+///
+/// Fails with every problem `validate_filter` finds in the trimmed filter.
+pub fn normalize_filter(mut filter: FilterCriteria) -> Result<FilterCriteria, Vec<FilterProblem>> {
+    let fields = [
+        &mut filter.creator,
+        &mut filter.creator_tag,
+        &mut filter.symbol,
+        &mut filter.name_contains,
+        &mut filter.uri_host,
+        &mut filter.uri_scheme,
+        &mut filter.program,
+    ];
+    for value in fields.into_iter().flatten() {
+        if value.trim().len() != value.len() {
+            *value = value.trim().to_string();
+        }
+    }
+
+    let problems = validate_filter(&filter);
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+    filter.symbol = filter.symbol.map(|symbol| symbol.to_uppercase());
+    Ok(filter)
+}
+
+/// `preset` with every criterion `overrides` sets taken from `overrides` instead; `requireTwitter`, `requireWebsite` and
+/// `requireStandardAuthorities` can only be switched on, not off again.
+pub fn merge_filters(preset: FilterCriteria, overrides: FilterCriteria) -> FilterCriteria {
+    // destructured, so a new criterion can't be forgotten here
+    let FilterCriteria {
+        creator,
+        creator_tag,
+        symbol,
+        name_contains,
+        uri_host,
+        uri_scheme,
+        has_image,
+        has_twitter,
+        image_seen_before,
+        require_twitter,
+        require_website,
+        require_standard_authorities,
+        program,
+        min_slot,
+        min_creator_tokens,
+        max_creator_tokens,
+        min_creator_graduation_rate,
+        min_initial_buy_sol,
+        max_same_tx_buyers,
+        min_priority_fee_lamports,
+        min_similar_recent_count,
+        max_similar_recent_count,
+    } = overrides;
+    FilterCriteria {
+        creator: creator.or(preset.creator),
+        creator_tag: creator_tag.or(preset.creator_tag),
+        symbol: symbol.or(preset.symbol),
+        name_contains: name_contains.or(preset.name_contains),
+        uri_host: uri_host.or(preset.uri_host),
+        uri_scheme: uri_scheme.or(preset.uri_scheme),
+        has_image: has_image.or(preset.has_image),
+        has_twitter: has_twitter.or(preset.has_twitter),
+        image_seen_before: image_seen_before.or(preset.image_seen_before),
+        require_twitter: require_twitter || preset.require_twitter,
+        require_website: require_website || preset.require_website,
+        require_standard_authorities: require_standard_authorities || preset.require_standard_authorities,
+        program: program.or(preset.program),
+        min_slot: min_slot.or(preset.min_slot),
+        min_creator_tokens: min_creator_tokens.or(preset.min_creator_tokens),
+        max_creator_tokens: max_creator_tokens.or(preset.max_creator_tokens),
+        min_creator_graduation_rate: min_creator_graduation_rate.or(preset.min_creator_graduation_rate),
+        min_initial_buy_sol: min_initial_buy_sol.or(preset.min_initial_buy_sol),
+        max_same_tx_buyers: max_same_tx_buyers.or(preset.max_same_tx_buyers),
+        min_priority_fee_lamports: min_priority_fee_lamports.or(preset.min_priority_fee_lamports),
+        min_similar_recent_count: min_similar_recent_count.or(preset.min_similar_recent_count),
+        max_similar_recent_count: max_similar_recent_count.or(preset.max_similar_recent_count),
+    }
+}
+
+/// Lists what makes `filter` unable to ever match, or malformed; empty when it is usable.
+pub fn validate_filter(filter: &FilterCriteria) -> Vec<FilterProblem> {
+    let mut problems = Vec::new();
+    if let Some(creator) = &filter.creator {
+        if Pubkey::from_str(creator).is_err() {
+            problems.push(FilterProblem::new("creator", format!("creator '{}' is not a base58 public key", creator)));
+        }
+    }
+    if filter.creator_tag.as_ref().is_some_and(|tag| tag.is_empty()) {
+        problems.push(FilterProblem::new("creatorTag", "creatorTag must not be empty".to_string()));
+    }
+    if let Some(symbol) = &filter.symbol {
+        if symbol.chars().count() > MAX_SYMBOL_LEN {
+            problems.push(FilterProblem::new(
+                "symbol",
+                format!("symbol '{}' is longer than {} characters", symbol, MAX_SYMBOL_LEN),
+            ));
+        }
+    }
+    if let Some(name) = &filter.name_contains {
+        if name.chars().count() > MAX_NAME_FILTER_LEN {
+            problems.push(FilterProblem::new(
+                "nameContains",
+                format!("nameContains is longer than {} characters", MAX_NAME_FILTER_LEN),
+            ));
+        }
+    }
+    if let (Some(min), Some(max)) = (filter.min_creator_tokens, filter.max_creator_tokens) {
+        if min > max {
+            problems.push(FilterProblem::new(
+                "minCreatorTokens",
+                format!("minCreatorTokens ({}) is above maxCreatorTokens ({})", min, max),
+            ));
+        }
+    }
+    if let (Some(min), Some(max)) = (filter.min_similar_recent_count, filter.max_similar_recent_count) {
+        if min > max {
+            problems.push(FilterProblem::new(
+                "minSimilarRecentCount",
+                format!("minSimilarRecentCount ({}) is above maxSimilarRecentCount ({})", min, max),
+            ));
+        }
+    }
+    if let Some(min) = filter.min_creator_graduation_rate {
+        if !(0.0..=1.0).contains(&min) {
+            problems.push(FilterProblem::new(
+                "minCreatorGraduationRate",
+                format!("minCreatorGraduationRate ({}) must be between 0 and 1", min),
+            ));
+        }
+    }
+    if let Some(min) = filter.min_initial_buy_sol {
+        if !min.is_finite() || min < 0.0 {
+            problems.push(FilterProblem::new(
+                "minInitialBuySol",
+                format!("minInitialBuySol ({}) must be a non-negative number", min),
+            ));
+        }
+    }
+    if let Some(program) = &filter.program {
+        if Pubkey::from_str(program).is_err() {
+            problems.push(FilterProblem::new("program", format!("program '{}' is not a base58 public key", program)));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,1086 @@
+//! Unit tests for filter matching and validation.
+
+
+use super::*;
+use crate::data_models::{CreatorStats, InitialBuy, PumpFunData, TokenDetails};
+use chrono::Utc;
+
+/// function to create a dummy token creation event for testing.
+fn create_test_event(creator: &str, name: &str, symbol: &str) -> TokenCreatedEvent {
+    TokenCreatedEvent {
+        event_type: "tokenCreated".to_string(),
+        timestamp: Utc::now(),
+        sequence: 0,
+        transaction_signature: "test_sig_123456789".to_string(),
+        slot: 280_000_000,
+        program_id: "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P".to_string(),
+        token: TokenDetails {
+            mint_address: "test_mint_ABC123def456".to_string(),
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            uri: "https://test.example.com/metadata.json".to_string(),
+            creator: creator.to_string(),
+            supply: Some(1_000_000),
+            decimals: Some(6),
+            mint_authority: None,
+            freeze_authority: None,
+            authorities_standard: Some(true),
+            sanitized: false,
+            uri_valid: true,
+            raw: None,
+        },
+        pump_data: PumpFunData {
+            bonding_curve: "test_curve_GHI789jkl012".to_string(),
+            virtual_sol_reserves: Some(30_000_000_000),
+            virtual_token_reserves: Some(1_073_000_000_000_000),
+            real_sol_reserves: Some(0),
+            real_token_reserves: Some(793_100_000_000_000),
+            complete: false,
+            price_sol: Some(0.000000028),
+            market_cap_sol: Some(0.000028),
+            price_usd: None,
+            market_cap_usd: None,
+        },
+        metadata: None,
+        initial_buy: None,
+        same_tx_buyers: 0,
+        same_tx_buy_sol: 0,
+        tx_fee_lamports: 5_000,
+        priority_fee_lamports: 0,
+        compute_units: None,
+        creator_stats: None,
+        creator_label: None,
+        creator_tags: Vec::new(),
+        similar_recent_count: None,
+        processing_latency_ms: None,
+        chain_latency_ms: None,
+        stale: false,
+        signer_pubkey: None,
+        signature: None,
+        raw_transaction: None,
+    }
+}
+
+#[test]
+fn test_no_filter_matches_all() {
+    let event = create_test_event("creator_A", "My Token", "TKN");
+    let filter = FilterCriteria::default();
+    assert!(matches_filter(&event, &filter));
+}
+
+#[test]
+fn test_filter_by_creator_exact_match() {
+    let event = create_test_event("creator_A", "My Token", "TKN");
+    let filter = FilterCriteria {
+        creator: Some("creator_A".to_string()),
+        ..Default::default()
+    };
+    assert!(matches_filter(&event, &filter));
+}
+
+#[test]
+fn test_filter_by_creator_no_match() {
+    let event = create_test_event("creator_A", "My Token", "TKN");
+    let filter = FilterCriteria {
+        creator: Some("creator_B".to_string()),
+        ..Default::default()
+    };
+    assert!(!matches_filter(&event, &filter));
+}
+
+#[test]
+fn test_filter_by_symbol_case_insensitive_match() {
+    let event = create_test_event("creator_A", "My Token", "TKN");
+    
+    // test lowercase filter against uppercase symbol, as it is stored once set
+    let filter_lower = normalize_filter(FilterCriteria {
+        symbol: Some("tkn".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+    
+    // test uppercase filter against uppercase symbol
+    let filter_upper = FilterCriteria {
+        symbol: Some("TKN".to_string()),
+        ..Default::default()
+    };
+    
+    assert!(matches_filter(&event, &filter_lower));
+    assert!(matches_filter(&event, &filter_upper));
+}
+
+#[test]
+fn test_filter_by_symbol_no_match() {
+    let event = create_test_event("creator_A", "My Token", "TKN");
+    let filter = FilterCriteria {
+        symbol: Some("DOGE".to_string()),
+        ..Default::default()
+    };
+    assert!(!matches_filter(&event, &filter));
+}
+
+#[test]
+fn test_filter_by_name_contains_case_insensitive_match() {
+    let event = create_test_event("creator_A", "My Awesome Token", "TKN");
+    
+    // test various case combinations
+    let filters = vec![
+        "Awesome",
+        "awesome", 
+        "AWESOME",
+        "Token",
+        "token",
+        "My",
+        "my"
+    ];
+    
+    for filter_text in filters {
+        let filter = FilterCriteria {
+            name_contains: Some(filter_text.to_string()),
+            ..Default::default()
+        };
+        assert!(matches_filter(&event, &filter), "Failed to match '{}'", filter_text);
+    }
+}
+
+#[test]
+fn test_filter_by_name_contains_no_match() {
+    let event = create_test_event("creator_A", "My Awesome Token", "TKN");
+    let filter = FilterCriteria {
+        name_contains: Some("Boring".to_string()),
+        ..Default::default()
+    };
+    assert!(!matches_filter(&event, &filter));
+}
+
+#[test]
+fn test_filter_by_multiple_criteria_all_match() {
+    let event = create_test_event("creator_A", "My Awesome Token", "TKN");
+    let filter = FilterCriteria {
+        creator: Some("creator_A".to_string()),
+        symbol: Some("TKN".to_string()),
+        name_contains: Some("Awesome".to_string()),
+        ..Default::default()
+    };
+    assert!(matches_filter(&event, &filter));
+}
+
+#[test]
+fn test_filter_by_multiple_criteria_partial_match_fails() {
+    let event = create_test_event("creator_A", "My Token", "TKN");
+    
+    // creator matches but symbol doesn't
+    let filter1 = FilterCriteria {
+        creator: Some("creator_A".to_string()),
+        symbol: Some("FAIL".to_string()),
+        ..Default::default()
+    };
+    assert!(!matches_filter(&event, &filter1));
+    
+    // symbol matches but creator doesn't
+    let filter2 = FilterCriteria {
+        creator: Some("creator_B".to_string()),
+        symbol: Some("TKN".to_string()),
+        ..Default::default()
+    };
+    assert!(!matches_filter(&event, &filter2));
+}
+
+#[test]
+fn test_filter_edge_cases() {
+    let event = create_test_event("", "Token", "");
+    
+    let filter_empty_creator = FilterCriteria {
+        creator: Some("".to_string()),
+        ..Default::default()
+    };
+    assert!(matches_filter(&event, &filter_empty_creator));
+    
+    let filter_empty_symbol = FilterCriteria {
+        symbol: Some("".to_string()),
+        ..Default::default()
+    };
+    assert!(matches_filter(&event, &filter_empty_symbol));
+    
+    let filter_empty_name = FilterCriteria {
+        name_contains: Some("".to_string()),
+        ..Default::default()
+    };
+    assert!(matches_filter(&event, &filter_empty_name));
+}
+
+#[test]
+fn test_filter_real_world_scenarios() {
+    // Test realistic pump.fun token scenarios
+    let doge_token = create_test_event(
+        "DEF456ghi789JKL012mno345PQR678stu901VWX234yza567BCD890efg123",
+        "DogeToTheMoon",
+        "DOGE"
+    );
+    
+    let pepe_token = create_test_event(
+        "ABC123def456GHI789jkl012MNO345pqr678STU901vwx234YZA567bcd890",
+        "PepeCoin Classic",
+        "PEPE"
+    );
+    
+    // filter for DOGE tokens
+    let doge_filter = FilterCriteria {
+        symbol: Some("DOGE".to_string()),
+        ..Default::default()
+    };
+    assert!(matches_filter(&doge_token, &doge_filter));
+    assert!(!matches_filter(&pepe_token, &doge_filter));
+    
+    // filter for tokens with "moon" in name
+    let moon_filter = FilterCriteria {
+        name_contains: Some("moon".to_string()),
+        ..Default::default()
+    };
+    assert!(matches_filter(&doge_token, &moon_filter));
+    assert!(!matches_filter(&pepe_token, &moon_filter));
+    
+    // filter for specific creator
+    let creator_filter = FilterCriteria {
+        creator: Some("DEF456ghi789JKL012mno345PQR678stu901VWX234yza567BCD890efg123".to_string()),
+        ..Default::default()
+    };
+    assert!(matches_filter(&doge_token, &creator_filter));
+    assert!(!matches_filter(&pepe_token, &creator_filter));
+}
+
+#[test]
+fn test_filter_by_uri_host() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    event.token.uri = "https://QmHash.ipfs.w3s.link/metadata.json".to_string();
+
+    for (host, expected) in [("ipfs.w3s.link", true), ("IPFS.W3S.LINK", true), ("w3s.link", true), ("ipfs.io", false), ("s.link", false)] {
+        let filter = FilterCriteria {
+            uri_host: Some(host.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(matches_filter(&event, &filter), expected, "{}", host);
+    }
+}
+
+#[test]
+fn test_filter_by_uri_scheme() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    event.token.uri = "ipfs://QmHash/metadata.json".to_string();
+
+    let ipfs_filter = FilterCriteria {
+        uri_scheme: Some("IPFS".to_string()),
+        ..Default::default()
+    };
+    let https_filter = FilterCriteria {
+        uri_scheme: Some("https".to_string()),
+        ..Default::default()
+    };
+    assert!(matches_filter(&event, &ipfs_filter));
+    assert!(!matches_filter(&event, &https_filter));
+}
+
+#[test]
+fn test_filter_malformed_uri_does_not_match() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    event.token.uri = "not a uri at all".to_string();
+
+    let filter = FilterCriteria {
+        uri_host: Some("ipfs.io".to_string()),
+        ..Default::default()
+    };
+    assert!(!matches_filter(&event, &filter));
+    // URI filters are only consulted when set
+    assert!(matches_filter(&event, &FilterCriteria::default()));
+}
+
+#[test]
+fn test_filter_by_metadata_presence() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    let wants_image = FilterCriteria {
+        has_image: Some(true),
+        ..Default::default()
+    };
+    let wants_no_twitter = FilterCriteria {
+        has_twitter: Some(false),
+        ..Default::default()
+    };
+
+    // without enrichment the event has neither
+    assert!(!matches_filter(&event, &wants_image));
+    assert!(matches_filter(&event, &wants_no_twitter));
+
+    event.metadata = Some(crate::data_models::TokenMetadata {
+        image: Some("https://example.com/cat.png".to_string()),
+        twitter: Some("https://x.com/cat".to_string()),
+        ..Default::default()
+    });
+    assert!(matches_filter(&event, &wants_image));
+    assert!(!matches_filter(&event, &wants_no_twitter));
+}
+
+#[test]
+fn test_filter_requiring_socials() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    let wants_twitter = FilterCriteria { require_twitter: true, ..Default::default() };
+    let wants_website = FilterCriteria { require_website: true, ..Default::default() };
+    assert!(!matches_filter(&event, &wants_twitter));
+    assert!(!matches_filter(&event, &wants_website));
+
+    event.metadata = Some(crate::data_models::TokenMetadata {
+        twitter: Some("https://x.com/cat".to_string()),
+        website_missing: true,
+        ..Default::default()
+    });
+    assert!(matches_filter(&event, &wants_twitter));
+    assert!(!matches_filter(&event, &wants_website));
+
+    // a community or a recycled account isn't the token's own
+    let metadata = event.metadata.as_mut().unwrap();
+    metadata.twitter_is_community_handle = true;
+    assert!(!matches_filter(&event, &wants_twitter));
+    let metadata = event.metadata.as_mut().unwrap();
+    metadata.twitter_is_community_handle = false;
+    metadata.socials_reused = true;
+    assert!(!matches_filter(&event, &wants_twitter));
+
+    let metadata = event.metadata.as_mut().unwrap();
+    metadata.website = Some("https://cat.example".to_string());
+    metadata.website_missing = false;
+    assert!(matches_filter(&event, &wants_website));
+    // leaving them out asks for nothing
+    let parsed: FilterCriteria = serde_json::from_str(r#"{"symbol":"TKN"}"#).unwrap();
+    assert!(!parsed.require_twitter && !parsed.require_website);
+}
+
+#[test]
+fn test_filter_requiring_standard_authorities() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    let standard = FilterCriteria { require_standard_authorities: true, ..Default::default() };
+    assert!(matches_filter(&event, &standard));
+
+    event.token.freeze_authority = Some("GkzUqR1dxyB3mUyZCK9KkoLx7CrXaFYRoDNK9bFpnw1R".to_string());
+    event.token.authorities_standard = Some(false);
+    assert!(!matches_filter(&event, &standard));
+    assert!(matches_filter(&event, &FilterCriteria::default()));
+
+    // at DETAIL_LEVEL=minimal the mint isn't read, so nothing vouches for it
+    event.token.authorities_standard = None;
+    assert!(!matches_filter(&event, &standard));
+}
+
+#[test]
+fn test_filter_by_creator_tag() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    let insiders = FilterCriteria {
+        creator_tag: Some("Insider".to_string()),
+        ..Default::default()
+    };
+    assert!(!matches_filter(&event, &insiders));
+
+    event.creator_label = Some("serial dev".to_string());
+    event.creator_tags = vec!["dev".to_string(), "insider".to_string()];
+    assert!(matches_filter(&event, &insiders));
+
+    event.creator_tags = vec!["dev".to_string()];
+    assert!(!matches_filter(&event, &insiders));
+}
+
+#[test]
+fn test_blank_creator_tag_is_rejected() {
+    let problems = normalize_filter(FilterCriteria {
+        creator_tag: Some("  ".to_string()),
+        ..Default::default()
+    })
+    .unwrap_err();
+    assert_eq!(problems[0].field, "creatorTag");
+}
+
+#[test]
+fn test_filter_by_image_seen_before() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    let copied = FilterCriteria {
+        image_seen_before: Some(true),
+        ..Default::default()
+    };
+    let original = FilterCriteria {
+        image_seen_before: Some(false),
+        ..Default::default()
+    };
+    // without a hash the image counts as not seen before
+    assert!(!matches_filter(&event, &copied));
+    assert!(matches_filter(&event, &original));
+
+    event.metadata = Some(crate::data_models::TokenMetadata {
+        image_sha256: Some("ab".repeat(32)),
+        image_seen_before: Some(true),
+        ..Default::default()
+    });
+    assert!(matches_filter(&event, &copied));
+    assert!(!matches_filter(&event, &original));
+}
+
+#[test]
+fn test_filter_by_min_slot() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    let recent = FilterCriteria {
+        min_slot: Some(280_000_000),
+        ..Default::default()
+    };
+    assert!(matches_filter(&event, &recent));
+
+    event.slot = 279_999_999;
+    assert!(!matches_filter(&event, &recent));
+}
+
+#[test]
+fn test_filter_by_creator_tokens() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    let serial = FilterCriteria {
+        min_creator_tokens: Some(3),
+        ..Default::default()
+    };
+    let first_launch = FilterCriteria {
+        max_creator_tokens: Some(1),
+        ..Default::default()
+    };
+    // without creator history every creator counts as having launched nothing
+    assert!(!matches_filter(&event, &serial));
+    assert!(matches_filter(&event, &first_launch));
+
+    event.creator_stats = Some(CreatorStats {
+        tokens_created_seen: 3,
+        first_seen: Utc::now(),
+        last_seen: Utc::now(),
+        tokens_graduated: 0,
+        tokens_abandoned: 0,
+        graduation_rate: None,
+    });
+    assert!(matches_filter(&event, &serial));
+    assert!(!matches_filter(&event, &first_launch));
+}
+
+#[test]
+fn test_filter_by_creator_graduation_rate() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    let proven = FilterCriteria {
+        min_creator_graduation_rate: Some(0.5),
+        ..Default::default()
+    };
+    // a creator none of whose launches has an outcome yet has no rate to clear the bar
+    assert!(!matches_filter(&event, &proven));
+
+    let mut stats = CreatorStats {
+        tokens_created_seen: 4,
+        first_seen: Utc::now(),
+        last_seen: Utc::now(),
+        tokens_graduated: 1,
+        tokens_abandoned: 2,
+        graduation_rate: Some(1.0 / 3.0),
+    };
+    event.creator_stats = Some(stats.clone());
+    assert!(!matches_filter(&event, &proven));
+
+    stats.tokens_abandoned = 1;
+    stats.graduation_rate = Some(0.5);
+    event.creator_stats = Some(stats);
+    assert!(matches_filter(&event, &proven));
+}
+
+#[test]
+fn test_filter_by_initial_buy() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    let dev_bought = FilterCriteria {
+        min_initial_buy_sol: Some(1.5),
+        ..Default::default()
+    };
+    // a token its creator didn't buy counts as a 0 SOL buy
+    assert!(!matches_filter(&event, &dev_bought));
+
+    event.initial_buy = Some(InitialBuy {
+        sol_amount: 1_000_000_000,
+        token_amount: 34_612_903_225_806,
+    });
+    assert!(!matches_filter(&event, &dev_bought));
+
+    event.initial_buy = Some(InitialBuy {
+        sol_amount: 1_500_000_000,
+        token_amount: 51_202_749_140_893,
+    });
+    assert!(matches_filter(&event, &dev_bought));
+}
+
+#[test]
+fn test_filter_by_same_tx_buyers() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    let not_bundled = FilterCriteria {
+        max_same_tx_buyers: Some(1),
+        ..Default::default()
+    };
+    assert!(matches_filter(&event, &not_bundled));
+
+    event.same_tx_buyers = 1;
+    assert!(matches_filter(&event, &not_bundled));
+
+    event.same_tx_buyers = 4;
+    assert!(!matches_filter(&event, &not_bundled));
+}
+
+#[test]
+fn test_filter_by_priority_fee() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    let prioritized = FilterCriteria {
+        min_priority_fee_lamports: Some(100_000),
+        ..Default::default()
+    };
+    assert!(!matches_filter(&event, &prioritized));
+
+    event.priority_fee_lamports = 100_000;
+    assert!(matches_filter(&event, &prioritized));
+}
+
+#[test]
+fn test_filter_by_similar_recent_count() {
+    let mut event = create_test_event("creator_A", "TRUMP 2.0", "TRUMP2");
+    let originals = FilterCriteria {
+        max_similar_recent_count: Some(0),
+        ..Default::default()
+    };
+    let copycats = FilterCriteria {
+        min_similar_recent_count: Some(3),
+        ..Default::default()
+    };
+    // without spam detection every event counts as an original
+    assert!(matches_filter(&event, &originals));
+    assert!(!matches_filter(&event, &copycats));
+
+    event.similar_recent_count = Some(0);
+    assert!(matches_filter(&event, &originals));
+
+    event.similar_recent_count = Some(5);
+    assert!(!matches_filter(&event, &originals));
+    assert!(matches_filter(&event, &copycats));
+}
+
+#[test]
+fn test_filter_by_program() {
+    let mut event = create_test_event("creator_A", "My Token", "TKN");
+    let pump_fun = FilterCriteria {
+        program: Some("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P".to_string()),
+        ..Default::default()
+    };
+    assert!(matches_filter(&event, &pump_fun));
+
+    event.program_id = "LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj".to_string();
+    assert!(!matches_filter(&event, &pump_fun));
+}
+
+#[test]
+fn test_validate_filter() {
+    assert!(validate_filter(&FilterCriteria::default()).is_empty());
+
+    let impossible = FilterCriteria {
+        min_creator_tokens: Some(3),
+        max_creator_tokens: Some(2),
+        min_similar_recent_count: Some(1),
+        max_similar_recent_count: Some(0),
+        min_creator_graduation_rate: Some(1.5),
+        min_initial_buy_sol: Some(f64::NAN),
+        program: Some("not-a-program".to_string()),
+        ..Default::default()
+    };
+    let problems = validate_filter(&impossible);
+    assert_eq!(
+        problems.iter().map(|problem| problem.field).collect::<Vec<_>>(),
+        [
+            "minCreatorTokens",
+            "minSimilarRecentCount",
+            "minCreatorGraduationRate",
+            "minInitialBuySol",
+            "program"
+        ]
+    );
+    assert_eq!(
+        problems.iter().map(FilterProblem::to_string).collect::<Vec<_>>(),
+        vec![
+            "minCreatorTokens (3) is above maxCreatorTokens (2)",
+            "minSimilarRecentCount (1) is above maxSimilarRecentCount (0)",
+            "minCreatorGraduationRate (1.5) must be between 0 and 1",
+            "minInitialBuySol (NaN) must be a non-negative number",
+            "program 'not-a-program' is not a base58 public key",
+        ]
+    );
+
+    let usable = FilterCriteria {
+        min_creator_tokens: Some(2),
+        max_creator_tokens: Some(2),
+        min_initial_buy_sol: Some(0.0),
+        program: Some("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P".to_string()),
+        ..Default::default()
+    };
+    assert!(validate_filter(&usable).is_empty());
+}
+
+#[test]
+fn test_validate_filter_rejects_bad_creator() {
+    let filter = FilterCriteria {
+        creator: Some("creator_A".to_string()),
+        ..Default::default()
+    };
+    let problems = validate_filter(&filter);
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].field, "creator");
+    assert_eq!(problems[0].message, "creator 'creator_A' is not a base58 public key");
+}
+
+#[test]
+fn test_validate_filter_rejects_long_symbol_and_name() {
+    let at_limit = FilterCriteria {
+        symbol: Some("ABCDEFGHIJ".to_string()),
+        name_contains: Some("n".repeat(MAX_NAME_FILTER_LEN)),
+        ..Default::default()
+    };
+    assert!(validate_filter(&at_limit).is_empty());
+
+    let too_long = FilterCriteria {
+        symbol: Some("ABCDEFGHIJK".to_string()),
+        name_contains: Some("n".repeat(MAX_NAME_FILTER_LEN + 1)),
+        ..Default::default()
+    };
+    let problems = validate_filter(&too_long);
+    assert_eq!(problems.iter().map(|problem| problem.field).collect::<Vec<_>>(), ["symbol", "nameContains"]);
+    assert_eq!(problems[0].message, "symbol 'ABCDEFGHIJK' is longer than 10 characters");
+    assert_eq!(problems[1].message, "nameContains is longer than 100 characters");
+}
+
+#[test]
+fn test_normalize_filter_trims_and_uppercases() {
+    let creator = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+    let filter = normalize_filter(FilterCriteria {
+        creator: Some(format!("  {}\n", creator)),
+        symbol: Some(" pepe ".to_string()),
+        name_contains: Some(" Moon ".to_string()),
+        uri_host: Some(" ipfs.io".to_string()),
+        uri_scheme: Some("https ".to_string()),
+        program: Some(format!("\t{}", creator)),
+        min_slot: Some(5),
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_eq!(filter.creator.as_deref(), Some(creator));
+    assert_eq!(filter.symbol.as_deref(), Some("PEPE"));
+    assert_eq!(filter.name_contains.as_deref(), Some("Moon"));
+    assert_eq!(filter.uri_host.as_deref(), Some("ipfs.io"));
+    assert_eq!(filter.uri_scheme.as_deref(), Some("https"));
+    assert_eq!(filter.program.as_deref(), Some(creator));
+    assert_eq!(filter.min_slot, Some(5));
+}
+
+#[test]
+fn test_normalize_filter_checks_the_trimmed_values() {
+    // the padding doesn't count towards the symbol length
+    assert!(normalize_filter(FilterCriteria {
+        symbol: Some("  ABCDEFGHIJ  ".to_string()),
+        ..Default::default()
+    })
+    .is_ok());
+
+    let problems = normalize_filter(FilterCriteria {
+        creator: Some(" not a key ".to_string()),
+        symbol: Some("ABCDEFGHIJK".to_string()),
+        ..Default::default()
+    })
+    .unwrap_err();
+    assert_eq!(problems.iter().map(|problem| problem.field).collect::<Vec<_>>(), ["creator", "symbol"]);
+    assert_eq!(problems[0].message, "creator 'not a key' is not a base58 public key");
+}
+
+/// the matcher as it was before `CompiledFilter`, upper-casing both sides on every call; kept to check the compiled
+/// one against.
+fn reference_matches_filter(event: &TokenCreatedEvent, filter: &FilterCriteria) -> bool {
+    fn host_matches(host: &str, expected: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        let expected = expected.trim_start_matches('.').to_ascii_lowercase();
+        host == expected || host.ends_with(&format!(".{}", expected))
+    }
+
+    if filter
+        .creator
+        .as_ref()
+        .is_some_and(|creator| &event.token.creator != creator)
+    {
+        return false;
+    }
+    if filter
+        .creator_tag
+        .as_ref()
+        .is_some_and(|tag| !event.creator_tags.iter().any(|creator_tag| creator_tag.to_lowercase() == tag.to_lowercase()))
+    {
+        return false;
+    }
+    if filter
+        .symbol
+        .as_ref()
+        .is_some_and(|symbol| event.token.symbol.to_uppercase() != symbol.to_uppercase())
+    {
+        return false;
+    }
+    if filter
+        .name_contains
+        .as_ref()
+        .is_some_and(|name| !event.token.name.to_uppercase().contains(&name.to_uppercase()))
+    {
+        return false;
+    }
+    if filter.uri_host.is_some() || filter.uri_scheme.is_some() {
+        let Ok(uri) = url::Url::parse(&event.token.uri) else {
+            return false;
+        };
+        if filter
+            .uri_scheme
+            .as_ref()
+            .is_some_and(|scheme| !uri.scheme().eq_ignore_ascii_case(scheme))
+        {
+            return false;
+        }
+        if let Some(host_filter) = &filter.uri_host {
+            if !uri.host_str().is_some_and(|host| host_matches(host, host_filter)) {
+                return false;
+            }
+        }
+    }
+    if filter
+        .program
+        .as_ref()
+        .is_some_and(|program| &event.program_id != program)
+    {
+        return false;
+    }
+    if filter.require_standard_authorities && event.token.authorities_standard != Some(true) {
+        return false;
+    }
+    if filter.min_slot.is_some_and(|min_slot| event.slot < min_slot) {
+        return false;
+    }
+    let tokens_created = event
+        .creator_stats
+        .as_ref()
+        .map_or(0, |stats| stats.tokens_created_seen);
+    if filter.min_creator_tokens.is_some_and(|min| tokens_created < min)
+        || filter.max_creator_tokens.is_some_and(|max| tokens_created > max)
+    {
+        return false;
+    }
+    if let Some(min) = filter.min_creator_graduation_rate {
+        let rate = event.creator_stats.as_ref().and_then(|stats| stats.graduation_rate);
+        if !rate.is_some_and(|rate| rate >= min) {
+            return false;
+        }
+    }
+    if let Some(min) = filter.min_initial_buy_sol {
+        if (event.initial_buy.map_or(0, |buy| buy.sol_amount) as f64 / 1_000_000_000.0) < min {
+            return false;
+        }
+    }
+    if filter.max_same_tx_buyers.is_some_and(|max| event.same_tx_buyers > max) {
+        return false;
+    }
+    if filter.min_priority_fee_lamports.is_some_and(|min| event.priority_fee_lamports < min) {
+        return false;
+    }
+    let similar = event.similar_recent_count.unwrap_or(0);
+    if filter.min_similar_recent_count.is_some_and(|min| similar < min)
+        || filter.max_similar_recent_count.is_some_and(|max| similar > max)
+    {
+        return false;
+    }
+    let metadata = event.metadata.as_ref();
+    if filter
+        .has_image
+        .is_some_and(|has_image| metadata.is_some_and(|metadata| metadata.image.is_some()) != has_image)
+    {
+        return false;
+    }
+    if filter
+        .has_twitter
+        .is_some_and(|has_twitter| metadata.is_some_and(|metadata| metadata.twitter.is_some()) != has_twitter)
+    {
+        return false;
+    }
+    if filter
+        .image_seen_before
+        .is_some_and(|seen_before| metadata.and_then(|metadata| metadata.image_seen_before).unwrap_or(false) != seen_before)
+    {
+        return false;
+    }
+    true
+}
+
+/// function to create 64 events that between them vary every field a filter looks at.
+fn varied_events() -> Vec<TokenCreatedEvent> {
+    let names = ["My Token", "moon shot", "Straße Coin", ""];
+    let symbols = ["TKN", "tkn", "straße", "MOON"];
+    let uris = [
+        "https://IPFS.io/ipfs/bafy",
+        "ipfs://bafy/metadata.json",
+        "https://cdn.ipfs.io/metadata.json",
+        "not a uri",
+        "https://notipfs.io/x",
+    ];
+    (0..64usize)
+        .map(|i| {
+            let mut event = create_test_event(["creator_A", "creator_B"][i % 2], names[i % 4], symbols[(i / 4) % 4]);
+            event.token.uri = uris[i % 5].to_string();
+            event.slot = 279_999_999 + (i % 3) as u64;
+            event.token.authorities_standard = [Some(true), Some(false), None][i % 3];
+            if i % 3 == 0 {
+                event.program_id = "LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj".to_string();
+            }
+            if i % 2 == 1 {
+                let decided = (i % 4) as u32;
+                event.creator_stats = Some(CreatorStats {
+                    tokens_created_seen: (i % 5) as u32,
+                    first_seen: Utc::now(),
+                    last_seen: Utc::now(),
+                    tokens_graduated: decided / 2,
+                    tokens_abandoned: decided - decided / 2,
+                    graduation_rate: (decided > 0).then(|| (decided / 2) as f32 / decided as f32),
+                });
+            }
+            if i % 3 != 2 {
+                event.initial_buy = Some(InitialBuy {
+                    sol_amount: (i as u64 % 4) * 500_000_000,
+                    token_amount: 1,
+                });
+            }
+            event.same_tx_buyers = (i % 4) as u32;
+            event.priority_fee_lamports = (i as u64 % 3) * 100_000;
+            if i % 5 == 2 {
+                event.creator_label = Some("serial dev".to_string());
+                event.creator_tags = vec!["dev".to_string(), "insider".to_string()];
+            }
+            event.similar_recent_count = (i % 7 != 0).then_some((i % 4) as u32);
+            if i % 4 == 1 {
+                event.metadata = Some(crate::data_models::TokenMetadata {
+                    image: (i % 8 == 1).then(|| "https://example.com/cat.png".to_string()),
+                    twitter: Some("https://x.com/cat".to_string()),
+                    image_seen_before: (i % 8 == 1).then_some(i % 16 == 1),
+                    ..Default::default()
+                });
+            }
+            event
+        })
+        .collect()
+}
+
+/// function to create filters covering every criterion, in several spellings, alone and combined.
+fn varied_filters() -> Vec<FilterCriteria> {
+    let mut filters = vec![FilterCriteria::default()];
+    for creator in ["creator_A", "creator_C"] {
+        filters.push(FilterCriteria {
+            creator: Some(creator.to_string()),
+            ..Default::default()
+        });
+    }
+    for tag in ["insider", "Insider", "influencer"] {
+        filters.push(FilterCriteria {
+            creator_tag: Some(tag.to_string()),
+            ..Default::default()
+        });
+    }
+    for symbol in ["tkn", "TKN", "STRASSE", "straße", "moon", ""] {
+        filters.push(FilterCriteria {
+            symbol: Some(symbol.to_string()),
+            ..Default::default()
+        });
+    }
+    for name in ["TOKEN", "Moon", "ß", "SS", "", "missing"] {
+        filters.push(FilterCriteria {
+            name_contains: Some(name.to_string()),
+            ..Default::default()
+        });
+    }
+    for host in ["ipfs.io", ".IPFS.IO", "cdn.ipfs.io", "io", ""] {
+        filters.push(FilterCriteria {
+            uri_host: Some(host.to_string()),
+            ..Default::default()
+        });
+    }
+    for scheme in ["https", "IPFS", "ftp"] {
+        filters.push(FilterCriteria {
+            uri_scheme: Some(scheme.to_string()),
+            ..Default::default()
+        });
+    }
+    filters.push(FilterCriteria {
+        program: Some("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P".to_string()),
+        ..Default::default()
+    });
+    filters.push(FilterCriteria {
+        min_slot: Some(280_000_000),
+        ..Default::default()
+    });
+    filters.push(FilterCriteria {
+        require_standard_authorities: true,
+        ..Default::default()
+    });
+    filters.push(FilterCriteria {
+        min_creator_tokens: Some(2),
+        max_creator_tokens: Some(3),
+        ..Default::default()
+    });
+    filters.push(FilterCriteria {
+        max_creator_tokens: Some(0),
+        ..Default::default()
+    });
+    for min_creator_graduation_rate in [Some(0.0), Some(0.5)] {
+        filters.push(FilterCriteria {
+            min_creator_graduation_rate,
+            ..Default::default()
+        });
+    }
+    filters.push(FilterCriteria {
+        min_initial_buy_sol: Some(0.5),
+        ..Default::default()
+    });
+    filters.push(FilterCriteria {
+        min_initial_buy_sol: Some(0.0),
+        ..Default::default()
+    });
+    filters.push(FilterCriteria {
+        max_same_tx_buyers: Some(1),
+        ..Default::default()
+    });
+    filters.push(FilterCriteria {
+        min_priority_fee_lamports: Some(100_000),
+        ..Default::default()
+    });
+    filters.push(FilterCriteria {
+        min_similar_recent_count: Some(1),
+        max_similar_recent_count: Some(2),
+        ..Default::default()
+    });
+    for (has_image, has_twitter) in [(Some(true), None), (Some(false), Some(true)), (None, Some(false))] {
+        filters.push(FilterCriteria {
+            has_image,
+            has_twitter,
+            ..Default::default()
+        });
+    }
+    for image_seen_before in [Some(true), Some(false)] {
+        filters.push(FilterCriteria {
+            image_seen_before,
+            ..Default::default()
+        });
+    }
+    filters.push(FilterCriteria {
+        creator: Some("creator_B".to_string()),
+        symbol: Some("Tkn".to_string()),
+        uri_host: Some("ipfs.io".to_string()),
+        uri_scheme: Some("HTTPS".to_string()),
+        min_initial_buy_sol: Some(0.5),
+        ..Default::default()
+    });
+    filters
+}
+
+#[test]
+fn test_compiled_filter_agrees_with_the_reference_matcher() {
+    let events = varied_events();
+    let mut matched = 0;
+    for filter in varied_filters() {
+        let compiled = CompiledFilter::from(&filter);
+        for event in &events {
+            let expected = reference_matches_filter(event, &filter);
+            assert_eq!(
+                compiled.matches(&MatchableEvent::new(event)),
+                expected,
+                "{:?} against {:?}",
+                filter,
+                event.token
+            );
+            assert_eq!(matches_filter(event, &filter), expected);
+            matched += usize::from(expected);
+        }
+    }
+    // the cases are only worth something if both outcomes occur
+    assert!(matched > 0 && matched < events.len() * varied_filters().len());
+}
+
+#[test]
+fn test_compiled_filter_keeps_the_criteria() {
+    let filter = FilterCriteria {
+        symbol: Some("pepe".to_string()),
+        uri_host: Some(".IPFS.io".to_string()),
+        ..Default::default()
+    };
+    let compiled = CompiledFilter::from(&filter);
+    assert_eq!(compiled.criteria(), &filter);
+    assert_eq!(
+        CompiledFilter::default(),
+        CompiledFilter::from(&FilterCriteria::default())
+    );
+}
+
+/// Benchmark: run with `cargo test --release filter_benchmark -- --ignored --nocapture`.
+///
+/// Times 10k filter evaluations, one event against 10k client filters as the broadcast does, with the reference
+/// matcher and with compiled filters.
+#[test]
+#[ignore]
+fn test_filter_benchmark() {
+    const EVALUATIONS: usize = 10_000;
+    let events = varied_events();
+    let filters: Vec<FilterCriteria> = varied_filters().into_iter().cycle().take(EVALUATIONS).collect();
+    let compiled: Vec<CompiledFilter> = filters.iter().map(CompiledFilter::from).collect();
+
+    let started = std::time::Instant::now();
+    let mut reference_matches = 0;
+    for event in &events {
+        reference_matches += filters
+            .iter()
+            .filter(|filter| reference_matches_filter(event, filter))
+            .count();
+    }
+    let reference = started.elapsed();
+
+    let started = std::time::Instant::now();
+    let mut compiled_matches = 0;
+    for event in &events {
+        let event = MatchableEvent::new(event);
+        compiled_matches += compiled.iter().filter(|filter| filter.matches(&event)).count();
+    }
+    let elapsed = started.elapsed();
+
+    assert_eq!(compiled_matches, reference_matches);
+    println!(
+        "{} evaluations per event: reference {:?}, compiled {:?} (averaged over {} events)",
+        EVALUATIONS,
+        reference / events.len() as u32,
+        elapsed / events.len() as u32,
+        events.len()
+    );
+}
+
+#[test]
+fn test_merge_filters_prefers_overrides() {
+    let preset = FilterCriteria {
+        symbol: Some("MOON".to_string()),
+        max_creator_tokens: Some(1),
+        require_twitter: true,
+        ..Default::default()
+    };
+    let overrides = FilterCriteria {
+        symbol: Some("PEPE".to_string()),
+        min_slot: Some(42),
+        ..Default::default()
+    };
+
+    let merged = merge_filters(preset, overrides);
+    assert_eq!(merged.symbol.as_deref(), Some("PEPE"));
+    assert_eq!(merged.max_creator_tokens, Some(1));
+    assert_eq!(merged.min_slot, Some(42));
+    // an override leaving it unset doesn't switch the preset's requirement off
+    assert!(merged.require_twitter);
+}
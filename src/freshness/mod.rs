@@ -0,0 +1,61 @@
+//! # Freshness
+//!
+//! A token creation that reaches a consumer minutes after it landed is rarely worth acting on, and a backlog after an
+//! RPC hiccup or a slow enrichment can produce exactly that. With `MAX_EVENT_AGE_SECS` set the monitor measures every
+//! event's age right before dispatching it: from the block time of its creation transaction, or from when its
+//! notification arrived when the node reported no block time. An event older than the limit is marked `stale: true`
+//! and counted on `pump_fun_monitor_stale_events_total`.
+//!
+//! What a stale event becomes is up to each consumer:
+//!
+//! - `tag` (the default) delivers it as is, so the client decides
+//! - `drop` never delivers it, counted on `pump_fun_monitor_ws_stale_dropped_total` for the WebSocket server and on
+//!   `pump_fun_monitor_sink_stale_dropped_total` for a sink
+//!
+//! The WebSocket server follows `WS_STALE_EVENTS`, every sink the `stale` key of its own config section. Block times
+//! have one-second resolution, so an age is only ever accurate to the second.
+
+use crate::error::{MonitorError, Result};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// what a consumer does with an event older than `MAX_EVENT_AGE_SECS`.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StalePolicy {
+    /// deliver it with `stale: true`
+    #[default]
+    Tag,
+    /// don't deliver it, and count it
+    Drop,
+}
+
+impl StalePolicy {
+    /// whether a consumer following this policy skips `event`.
+    pub fn drops(self, stale: bool) -> bool {
+        stale && self == Self::Drop
+    }
+}
+
+impl FromStr for StalePolicy {
+    type Err = MonitorError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "tag" => Ok(Self::Tag),
+            "drop" => Ok(Self::Drop),
+            other => Err(MonitorError::Config(format!("'{}' is not one of: tag, drop", other))),
+        }
+    }
+}
+
+/// How old an event is when `since_received` has passed since its notification arrived.
+///
+/// `chain_latency_ms` is the time from the block to the notification; without it the event counts as first seen when
+/// the notification arrived.
+pub fn event_age(chain_latency_ms: Option<u64>, since_received: Duration) -> Duration {
+    Duration::from_millis(chain_latency_ms.unwrap_or(0)).saturating_add(since_received)
+}
+
+#[cfg(test)]
+mod tests;
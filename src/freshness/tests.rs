@@ -0,0 +1,28 @@
+//! Unit tests for measuring an event's age and for the stale policies.
+
+
+use super::*;
+
+#[test]
+fn test_age_counts_from_the_block_when_known() {
+    let age = event_age(Some(40_000), Duration::from_millis(1_500));
+    assert_eq!(age, Duration::from_millis(41_500));
+    // without a block time the event is as old as its notification
+    assert_eq!(event_age(None, Duration::from_secs(3)), Duration::from_secs(3));
+}
+
+#[test]
+fn test_only_drop_skips_stale_events() {
+    assert!(StalePolicy::Drop.drops(true));
+    assert!(!StalePolicy::Drop.drops(false));
+    assert!(!StalePolicy::Tag.drops(true));
+    assert_eq!(StalePolicy::default(), StalePolicy::Tag);
+}
+
+#[test]
+fn test_stale_policy_parsing() {
+    assert_eq!(" Drop ".parse::<StalePolicy>().unwrap(), StalePolicy::Drop);
+    assert_eq!("tag".parse::<StalePolicy>().unwrap(), StalePolicy::Tag);
+    let error = "hide".parse::<StalePolicy>().unwrap_err().to_string();
+    assert!(error.contains("'hide' is not one of: tag, drop"), "{}", error);
+}
@@ -0,0 +1,204 @@
+//! # Global Filter
+//!
+//! Rules the monitor applies to every token creation right before dispatching it, so obvious junk never reaches a
+//! client, a sink or the database. Client and sink filters pick what one consumer receives; an event this filter
+//! rejects is gone for everyone. Without any rule configured in `[global_filter]` everything passes.
+//!
+//! The rules are checked in a fixed order, and the first one that rejects an event is counted on
+//! `pump_fun_monitor_global_filter_suppressed_total`, labelled with its name:
+//!
+//! 1. `creator_blocklist`: the creator is listed in the `GLOBAL_FILTER_CREATOR_BLOCKLIST` file
+//! 2. `name_exclude`, `symbol_exclude`: the name or symbol matches the exclude regex
+//! 3. `name_include`, `symbol_include`: the name or symbol doesn't match the include regex
+//! 4. `min_real_sol_reserves`: less SOL than the minimum was deposited in the curve when the token was created
+//!
+//! Excludes are checked before includes, so a name matching both is suppressed. Regexes match anywhere in the name or
+//! symbol unless anchored, and are case-sensitive unless they start with `(?i)`.
+//!
+//! The filter is reloadable. The blocklist file is read again on every reload and holds one base58 wallet address per
+//! line, with blank lines and `#` comments skipped; a file with any malformed line is rejected, listing each by number.
+
+use crate::data_models::TokenCreatedEvent;
+use crate::error::{MonitorError, Result};
+use regex::Regex;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// the rule that suppressed an event.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    CreatorBlocklist,
+    NameExclude,
+    SymbolExclude,
+    NameInclude,
+    SymbolInclude,
+    MinRealSolReserves,
+}
+
+impl Rule {
+    /// label of the rule on `/metrics`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::CreatorBlocklist => "creator_blocklist",
+            Self::NameExclude => "name_exclude",
+            Self::SymbolExclude => "symbol_exclude",
+            Self::NameInclude => "name_include",
+            Self::SymbolInclude => "symbol_include",
+            Self::MinRealSolReserves => "min_real_sol_reserves",
+        }
+    }
+}
+
+/// a compiled regex that compares and prints as its source, so a config holding one can be compared on reload.
+///
+#[derive(Clone)]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    pub fn is_match(&self, text: &str) -> bool {
+        self.0.is_match(text)
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0.as_str())
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = MonitorError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Regex::new(s)
+            .map(Self)
+            .map_err(|e| MonitorError::Config(format!("'{}' is not a valid regex: {}", s, e)))
+    }
+}
+
+/// Wallets whose tokens are never dispatched, keyed by their base58 address.
+///
+#[derive(Clone, PartialEq, Default)]
+pub struct CreatorBlocklist {
+    /// the file the list was read from
+    path: PathBuf,
+    creators: HashSet<String>,
+}
+
+impl fmt::Debug for CreatorBlocklist {
+    // the entries themselves would drown everything else a `Config` prints
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CreatorBlocklist")
+            .field("path", &self.path)
+            .field("creators", &self.creators.len())
+            .finish()
+    }
+}
+
+impl CreatorBlocklist {
+    /// Reads the blocklist at `path`.
+    ///
+    /// Fails with every problem in the file, each naming the line it is on.
+    pub fn load(path: &Path) -> std::result::Result<Self, Vec<String>> {
+        let contents = fs::read_to_string(path).map_err(|e| vec![format!("cannot be read: {}", e)])?;
+        let mut problems = Vec::new();
+        let mut creators = HashSet::new();
+        for (index, line) in contents.lines().enumerate() {
+            let creator = line.trim();
+            if creator.is_empty() || creator.starts_with('#') {
+                continue;
+            }
+            if Pubkey::from_str(creator).is_err() {
+                problems.push(format!("line {}: '{}' is not a base58 public key", index + 1, creator));
+                continue;
+            }
+            creators.insert(creator.to_string());
+        }
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            creators,
+        })
+    }
+
+    pub fn contains(&self, creator: &str) -> bool {
+        self.creators.contains(creator)
+    }
+
+    pub fn len(&self) -> usize {
+        self.creators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.creators.is_empty()
+    }
+}
+
+/// Rules every event must pass before it is dispatched; the default passes everything.
+///
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GlobalFilter {
+    /// only names matching this are dispatched
+    pub name_include: Option<Pattern>,
+    /// names matching this are never dispatched, even when they match `name_include`
+    pub name_exclude: Option<Pattern>,
+    /// only symbols matching this are dispatched
+    pub symbol_include: Option<Pattern>,
+    /// symbols matching this are never dispatched, even when they match `symbol_include`
+    pub symbol_exclude: Option<Pattern>,
+    /// creators whose tokens are never dispatched, read from `GLOBAL_FILTER_CREATOR_BLOCKLIST`
+    pub creator_blocklist: Option<CreatorBlocklist>,
+    /// SOL the curve must hold when the token is created, the creator's initial buy included
+    pub min_real_sol_reserves: Option<f64>,
+}
+
+impl GlobalFilter {
+    /// whether no rule is set, so every event passes.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The first rule `event` fails, or `None` when it may be dispatched.
+    pub fn check(&self, event: &TokenCreatedEvent) -> Option<Rule> {
+        let token = &event.token;
+        if self.creator_blocklist.as_ref().is_some_and(|blocklist| blocklist.contains(&token.creator)) {
+            return Some(Rule::CreatorBlocklist);
+        }
+        if self.name_exclude.as_ref().is_some_and(|exclude| exclude.is_match(&token.name)) {
+            return Some(Rule::NameExclude);
+        }
+        if self.symbol_exclude.as_ref().is_some_and(|exclude| exclude.is_match(&token.symbol)) {
+            return Some(Rule::SymbolExclude);
+        }
+        if self.name_include.as_ref().is_some_and(|include| !include.is_match(&token.name)) {
+            return Some(Rule::NameInclude);
+        }
+        if self.symbol_include.as_ref().is_some_and(|include| !include.is_match(&token.symbol)) {
+            return Some(Rule::SymbolInclude);
+        }
+        // the config refuses this rule at `DETAIL_LEVEL=minimal`, where events come without reserves
+        if let (Some(min), Some(lamports)) = (self.min_real_sol_reserves, event.pump_data.real_sol_reserves) {
+            let deposited = lamports as f64 / 1_000_000_000.0;
+            if deposited < min {
+                return Some(Rule::MinRealSolReserves);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests;
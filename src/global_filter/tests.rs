@@ -0,0 +1,100 @@
+//! Unit tests for the global filter: rule precedence, the reserves minimum and reading the creator blocklist.
+
+
+use super::*;
+use crate::simulation::EventGenerator;
+
+const PUMP: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+
+/// function to create an event named `name` with symbol `symbol`.
+fn event(name: &str, symbol: &str) -> TokenCreatedEvent {
+    let mut event = EventGenerator::new(7).next_event();
+    event.token.name = name.to_string();
+    event.token.symbol = symbol.to_string();
+    event
+}
+
+fn pattern(source: &str) -> Option<Pattern> {
+    Some(source.parse().unwrap())
+}
+
+#[test]
+fn test_default_passes_everything() {
+    let filter = GlobalFilter::default();
+    assert!(filter.is_empty());
+    assert_eq!(filter.check(&event("anything", "ANY")), None);
+}
+
+#[test]
+fn test_exclude_beats_include() {
+    let filter = GlobalFilter {
+        name_include: pattern("(?i)dog"),
+        name_exclude: pattern("(?i)scam|rug"),
+        ..Default::default()
+    };
+    assert_eq!(filter.check(&event("Doge Moon", "DOGE")), None);
+    assert_eq!(filter.check(&event("Rug Dog", "RDOG")), Some(Rule::NameExclude));
+    assert_eq!(filter.check(&event("Cat Coin", "CAT")), Some(Rule::NameInclude));
+
+    // the symbol's exclude comes before the name's include too
+    let filter = GlobalFilter {
+        name_include: pattern("Dog"),
+        symbol_exclude: pattern("^TEST"),
+        ..Default::default()
+    };
+    assert_eq!(filter.check(&event("Cat", "TESTCAT")), Some(Rule::SymbolExclude));
+}
+
+#[test]
+fn test_blocklisted_creator_is_checked_first() {
+    let mut suppressed = event("Scam Dog", "SCAM");
+    suppressed.token.creator = PUMP.to_string();
+    let filter = GlobalFilter {
+        name_exclude: pattern("Scam"),
+        creator_blocklist: Some(CreatorBlocklist {
+            path: PathBuf::from("blocklist.txt"),
+            creators: HashSet::from([PUMP.to_string()]),
+        }),
+        ..Default::default()
+    };
+    assert_eq!(filter.check(&suppressed), Some(Rule::CreatorBlocklist));
+}
+
+#[test]
+fn test_min_real_sol_reserves() {
+    let filter = GlobalFilter {
+        min_real_sol_reserves: Some(0.5),
+        ..Default::default()
+    };
+    let mut poor = event("Dog", "DOG");
+    poor.pump_data.real_sol_reserves = Some(100_000_000);
+    assert_eq!(filter.check(&poor), Some(Rule::MinRealSolReserves));
+    poor.pump_data.real_sol_reserves = Some(500_000_000);
+    assert_eq!(filter.check(&poor), None);
+    // reserves that weren't fetched can't fail the rule
+    poor.pump_data.real_sol_reserves = None;
+    assert_eq!(filter.check(&poor), None);
+}
+
+#[test]
+fn test_patterns_compare_by_source() {
+    assert_eq!(pattern("a+"), pattern("a+"));
+    assert_ne!(pattern("a+"), pattern("a*"));
+    let error = "(unclosed".parse::<Pattern>().unwrap_err().to_string();
+    assert!(error.contains("'(unclosed' is not a valid regex"), "{}", error);
+}
+
+#[test]
+fn test_load_blocklist() {
+    let dir = std::env::temp_dir().join(format!("pump_fun_monitor_global_filter_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("blocklist.txt");
+    fs::write(&path, format!("# serial ruggers\n{}\n\n  {}  \n", PUMP, PUMP)).unwrap();
+    let blocklist = CreatorBlocklist::load(&path).unwrap();
+    assert_eq!(blocklist.len(), 1);
+    assert!(blocklist.contains(PUMP));
+
+    fs::write(&path, format!("{}\nnot-a-key\n", PUMP)).unwrap();
+    let problems = CreatorBlocklist::load(&path).unwrap_err();
+    assert_eq!(problems, vec!["line 2: 'not-a-key' is not a base58 public key"]);
+}
@@ -0,0 +1,317 @@
+//! # gRPC API
+//!
+//! Optional tonic server (feature `grpc`) exposing `SubscribeTokenCreations`, a server-streaming alternative to the WebSocket API for typed consumers. Each subscription gets its own broadcast receiver and is filtered with the same `CompiledFilter` logic as WebSocket clients; the proto messages in `proto/pump_fun_monitor.proto` mirror `data_models` field for field.
+
+use crate::data_models::{CreatorStats, FilterCriteria, InitialBuy, PumpFunData, RawTokenMetadata, TokenCreatedEvent, TokenDetails, TokenMetadata};
+use crate::error::{MonitorError, Result};
+use crate::filtering::{normalize_filter, CompiledFilter, MatchableEvent};
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use tokio::sync::broadcast;
+use tonic::{Request, Response, Status};
+use tracing::warn;
+
+/// code generated from `proto/pump_fun_monitor.proto` by `build.rs`.
+pub mod proto {
+    tonic::include_proto!("pumpfun.v1");
+}
+
+use proto::token_monitor_server::{TokenMonitor, TokenMonitorServer};
+
+type EventStream = Pin<Box<dyn Stream<Item = std::result::Result<proto::TokenCreatedEvent, Status>> + Send>>;
+
+/// serves subscriptions from the shared event broadcast.
+pub struct TokenMonitorService {
+    events: broadcast::Sender<TokenCreatedEvent>,
+}
+
+#[tonic::async_trait]
+impl TokenMonitor for TokenMonitorService {
+    type SubscribeTokenCreationsStream = EventStream;
+
+    async fn subscribe_token_creations(
+        &self,
+        request: Request<proto::FilterRequest>,
+    ) -> std::result::Result<Response<Self::SubscribeTokenCreationsStream>, Status> {
+        let filter = normalize_filter(FilterCriteria::from(request.into_inner()))
+            .map_err(|problems| Status::invalid_argument(MonitorError::InvalidFilter(problems).to_string()))?;
+        let filter = CompiledFilter::from(&filter);
+        let receiver = self.events.subscribe();
+
+        // the stream (and with it the receiver) is dropped by tonic when the client goes away
+        let stream = futures_util::stream::unfold((receiver, filter), |(mut receiver, filter)| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if filter.matches(&MatchableEvent::new(&event)) => {
+                        return Some((Ok(proto::TokenCreatedEvent::from(event)), (receiver, filter)));
+                    }
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("gRPC subscriber lagged, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// Runs the gRPC server on `addr` until it fails.
+///
+/// # Arguments
+/// * `addr` - address to listen on
+/// * `events` - sender of the event broadcast; every subscription subscribes to it
+pub async fn start_grpc_server(
+    addr: SocketAddr,
+    events: broadcast::Sender<TokenCreatedEvent>,
+) -> std::result::Result<(), tonic::transport::Error> {
+    tonic::transport::Server::builder()
+        .add_service(TokenMonitorServer::new(TokenMonitorService { events }))
+        .serve(addr)
+        .await
+}
+
+impl From<proto::FilterRequest> for FilterCriteria {
+    fn from(request: proto::FilterRequest) -> Self {
+        Self {
+            creator: request.creator,
+            creator_tag: request.creator_tag,
+            symbol: request.symbol,
+            name_contains: request.name_contains,
+            uri_host: request.uri_host,
+            uri_scheme: request.uri_scheme,
+            has_image: request.has_image,
+            has_twitter: request.has_twitter,
+            image_seen_before: request.image_seen_before,
+            require_twitter: request.require_twitter,
+            require_website: request.require_website,
+            require_standard_authorities: request.require_standard_authorities,
+            program: request.program,
+            min_slot: request.min_slot,
+            min_creator_tokens: request.min_creator_tokens,
+            max_creator_tokens: request.max_creator_tokens,
+            min_creator_graduation_rate: request.min_creator_graduation_rate,
+            min_initial_buy_sol: request.min_initial_buy_sol,
+            max_same_tx_buyers: request.max_same_tx_buyers,
+            min_priority_fee_lamports: request.min_priority_fee_lamports,
+            min_similar_recent_count: request.min_similar_recent_count,
+            max_similar_recent_count: request.max_similar_recent_count,
+        }
+    }
+}
+
+impl From<TokenCreatedEvent> for proto::TokenCreatedEvent {
+    fn from(event: TokenCreatedEvent) -> Self {
+        Self {
+            event_type: event.event_type,
+            timestamp: Some(to_timestamp(event.timestamp)),
+            sequence: event.sequence,
+            transaction_signature: event.transaction_signature,
+            slot: event.slot,
+            program_id: event.program_id,
+            token: Some(proto::TokenDetails {
+                mint_address: event.token.mint_address,
+                name: event.token.name,
+                symbol: event.token.symbol,
+                uri: event.token.uri,
+                creator: event.token.creator,
+                supply: event.token.supply,
+                decimals: event.token.decimals.map(u32::from),
+                mint_authority: event.token.mint_authority,
+                freeze_authority: event.token.freeze_authority,
+                authorities_standard: event.token.authorities_standard,
+                sanitized: event.token.sanitized,
+                uri_valid: event.token.uri_valid,
+                raw: event.token.raw.map(|raw| proto::RawTokenMetadata {
+                    name: raw.name,
+                    symbol: raw.symbol,
+                    uri: raw.uri,
+                }),
+            }),
+            pump_data: Some(proto::PumpFunData {
+                bonding_curve: event.pump_data.bonding_curve,
+                virtual_sol_reserves: event.pump_data.virtual_sol_reserves,
+                virtual_token_reserves: event.pump_data.virtual_token_reserves,
+                real_sol_reserves: event.pump_data.real_sol_reserves,
+                real_token_reserves: event.pump_data.real_token_reserves,
+                complete: event.pump_data.complete,
+                price_sol: event.pump_data.price_sol,
+                market_cap_sol: event.pump_data.market_cap_sol,
+                price_usd: event.pump_data.price_usd,
+                market_cap_usd: event.pump_data.market_cap_usd,
+            }),
+            metadata: event.metadata.map(|metadata| proto::TokenMetadata {
+                description: metadata.description,
+                image: metadata.image,
+                image_url: metadata.image_url,
+                metadata_url: metadata.metadata_url,
+                image_sha256: metadata.image_sha256,
+                image_seen_before: metadata.image_seen_before,
+                image_first_seen_mint: metadata.image_first_seen_mint,
+                twitter: metadata.twitter,
+                telegram: metadata.telegram,
+                website: metadata.website,
+                twitter_is_community_handle: metadata.twitter_is_community_handle,
+                website_missing: metadata.website_missing,
+                description_contains_urls: metadata.description_contains_urls,
+                socials_reused: metadata.socials_reused,
+            }),
+            initial_buy: event.initial_buy.map(|buy| proto::InitialBuy {
+                sol_amount: buy.sol_amount,
+                token_amount: buy.token_amount,
+            }),
+            same_tx_buyers: event.same_tx_buyers,
+            same_tx_buy_sol: event.same_tx_buy_sol,
+            tx_fee_lamports: event.tx_fee_lamports,
+            priority_fee_lamports: event.priority_fee_lamports,
+            compute_units: event.compute_units,
+            creator_stats: event.creator_stats.map(|stats| proto::CreatorStats {
+                tokens_created_seen: stats.tokens_created_seen,
+                first_seen: Some(to_timestamp(stats.first_seen)),
+                last_seen: Some(to_timestamp(stats.last_seen)),
+                tokens_graduated: stats.tokens_graduated,
+                tokens_abandoned: stats.tokens_abandoned,
+                graduation_rate: stats.graduation_rate,
+            }),
+            creator_label: event.creator_label,
+            creator_tags: event.creator_tags,
+            similar_recent_count: event.similar_recent_count,
+            processing_latency_ms: event.processing_latency_ms,
+            chain_latency_ms: event.chain_latency_ms,
+            stale: event.stale,
+            signer_pubkey: event.signer_pubkey,
+            signature: event.signature,
+        }
+    }
+}
+
+impl TryFrom<proto::TokenCreatedEvent> for TokenCreatedEvent {
+    type Error = MonitorError;
+
+    fn try_from(event: proto::TokenCreatedEvent) -> Result<Self> {
+        let timestamp = from_timestamp(event.timestamp, "event timestamp")?;
+        let token = event
+            .token
+            .ok_or_else(|| MonitorError::DataNotFound("token details".to_string()))?;
+        let pump_data = event
+            .pump_data
+            .ok_or_else(|| MonitorError::DataNotFound("pump.fun data".to_string()))?;
+        let decimals = token
+            .decimals
+            .map(|decimals| {
+                u8::try_from(decimals)
+                    .map_err(|_| MonitorError::TransactionParse(format!("token decimals {} exceed u8", decimals)))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            event_type: event.event_type,
+            timestamp,
+            sequence: event.sequence,
+            transaction_signature: event.transaction_signature,
+            slot: event.slot,
+            program_id: event.program_id,
+            token: TokenDetails {
+                mint_address: token.mint_address,
+                name: token.name,
+                symbol: token.symbol,
+                uri: token.uri,
+                creator: token.creator,
+                supply: token.supply,
+                decimals,
+                mint_authority: token.mint_authority,
+                freeze_authority: token.freeze_authority,
+                authorities_standard: token.authorities_standard,
+                sanitized: token.sanitized,
+                uri_valid: token.uri_valid,
+                raw: token.raw.map(|raw| RawTokenMetadata {
+                    name: raw.name,
+                    symbol: raw.symbol,
+                    uri: raw.uri,
+                }),
+            },
+            pump_data: PumpFunData {
+                bonding_curve: pump_data.bonding_curve,
+                virtual_sol_reserves: pump_data.virtual_sol_reserves,
+                virtual_token_reserves: pump_data.virtual_token_reserves,
+                real_sol_reserves: pump_data.real_sol_reserves,
+                real_token_reserves: pump_data.real_token_reserves,
+                complete: pump_data.complete,
+                price_sol: pump_data.price_sol,
+                market_cap_sol: pump_data.market_cap_sol,
+                price_usd: pump_data.price_usd,
+                market_cap_usd: pump_data.market_cap_usd,
+            },
+            metadata: event.metadata.map(|metadata| TokenMetadata {
+                description: metadata.description,
+                image: metadata.image,
+                image_url: metadata.image_url,
+                metadata_url: metadata.metadata_url,
+                image_sha256: metadata.image_sha256,
+                image_seen_before: metadata.image_seen_before,
+                image_first_seen_mint: metadata.image_first_seen_mint,
+                twitter: metadata.twitter,
+                telegram: metadata.telegram,
+                website: metadata.website,
+                twitter_is_community_handle: metadata.twitter_is_community_handle,
+                website_missing: metadata.website_missing,
+                description_contains_urls: metadata.description_contains_urls,
+                socials_reused: metadata.socials_reused,
+            }),
+            initial_buy: event.initial_buy.map(|buy| InitialBuy {
+                sol_amount: buy.sol_amount,
+                token_amount: buy.token_amount,
+            }),
+            same_tx_buyers: event.same_tx_buyers,
+            same_tx_buy_sol: event.same_tx_buy_sol,
+            tx_fee_lamports: event.tx_fee_lamports,
+            priority_fee_lamports: event.priority_fee_lamports,
+            compute_units: event.compute_units,
+            creator_stats: event
+                .creator_stats
+                .map(|stats| -> Result<CreatorStats> {
+                    Ok(CreatorStats {
+                        tokens_created_seen: stats.tokens_created_seen,
+                        first_seen: from_timestamp(stats.first_seen, "creator first seen")?,
+                        last_seen: from_timestamp(stats.last_seen, "creator last seen")?,
+                        tokens_graduated: stats.tokens_graduated,
+                        tokens_abandoned: stats.tokens_abandoned,
+                        graduation_rate: stats.graduation_rate,
+                    })
+                })
+                .transpose()?,
+            creator_label: event.creator_label,
+            creator_tags: event.creator_tags,
+            similar_recent_count: event.similar_recent_count,
+            processing_latency_ms: event.processing_latency_ms,
+            chain_latency_ms: event.chain_latency_ms,
+            stale: event.stale,
+            signer_pubkey: event.signer_pubkey,
+            signature: event.signature,
+            raw_transaction: None,
+        })
+    }
+}
+
+fn to_timestamp(time: DateTime<Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: time.timestamp(),
+        nanos: time.timestamp_subsec_nanos() as i32,
+    }
+}
+
+/// converts a required proto timestamp; `what` names it in errors.
+fn from_timestamp(timestamp: Option<prost_types::Timestamp>, what: &str) -> Result<DateTime<Utc>> {
+    let timestamp = timestamp.ok_or_else(|| MonitorError::DataNotFound(what.to_string()))?;
+    u32::try_from(timestamp.nanos)
+        .ok()
+        .and_then(|nanos| DateTime::from_timestamp(timestamp.seconds, nanos))
+        .ok_or_else(|| MonitorError::TransactionParse(format!("{} is out of range", what)))
+}
+
+#[cfg(test)]
+mod tests;
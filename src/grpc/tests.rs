@@ -0,0 +1,184 @@
+//! Unit tests for the conversions between proto messages and data models.
+
+
+use super::*;
+use chrono::{TimeZone, Utc};
+
+/// function to create an event whose numeric fields sit at their extremes.
+fn create_test_event() -> TokenCreatedEvent {
+    TokenCreatedEvent {
+        event_type: "tokenCreated".to_string(),
+        timestamp: Utc.timestamp_opt(1_705_314_645, 123_456_789).unwrap(),
+        sequence: 42,
+        transaction_signature: "test_sig_123456789".to_string(),
+        slot: u64::MAX - 2,
+        program_id: "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P".to_string(),
+        token: TokenDetails {
+            mint_address: "test_mint_ABC123def456".to_string(),
+            name: "My Token 🚀".to_string(),
+            symbol: "TKN".to_string(),
+            uri: "https://test.example.com/metadata.json".to_string(),
+            creator: "creator_A".to_string(),
+            supply: Some(u64::MAX),
+            decimals: Some(u8::MAX),
+            mint_authority: Some("TSLvdd1pWpHVjahSpsvCXUbgwsL3JAcvokwaKt1eokM".to_string()),
+            freeze_authority: None,
+            authorities_standard: Some(false),
+            sanitized: true,
+            uri_valid: false,
+            raw: Some(RawTokenMetadata {
+                name: "My Token 🚀\u{202E}".to_string(),
+                symbol: "TKN".to_string(),
+                uri: "not a uri".to_string(),
+            }),
+        },
+        pump_data: PumpFunData {
+            bonding_curve: "test_curve_GHI789jkl012".to_string(),
+            virtual_sol_reserves: Some(u64::MAX - 1),
+            virtual_token_reserves: Some(1_073_000_000_000_000),
+            real_sol_reserves: Some(0),
+            real_token_reserves: Some(793_100_000_000_000),
+            complete: false,
+            price_sol: Some(0.000000028),
+            market_cap_sol: Some(28.0),
+            price_usd: Some(0.0000042),
+            market_cap_usd: None,
+        },
+        metadata: Some(TokenMetadata {
+            image: Some("ipfs://QmHash".to_string()),
+            image_url: Some("https://ipfs.io/ipfs/QmHash".to_string()),
+            twitter: Some("https://x.com/token".to_string()),
+            ..TokenMetadata::default()
+        }),
+        initial_buy: Some(InitialBuy {
+            sol_amount: 5_000_000_000,
+            token_amount: u64::MAX,
+        }),
+        same_tx_buyers: u32::MAX,
+        same_tx_buy_sol: 12_500_000_000,
+        tx_fee_lamports: u64::MAX,
+        priority_fee_lamports: 1_500_000,
+        compute_units: Some(118_432),
+        creator_stats: Some(CreatorStats {
+            tokens_created_seen: u32::MAX,
+            first_seen: Utc.timestamp_opt(1_705_000_000, 1).unwrap(),
+            last_seen: Utc.timestamp_opt(1_705_314_645, 123_456_789).unwrap(),
+            tokens_graduated: u32::MAX,
+            tokens_abandoned: 0,
+            graduation_rate: Some(0.25),
+        }),
+        creator_label: Some("serial dev".to_string()),
+        creator_tags: vec!["dev".to_string(), "insider".to_string()],
+        similar_recent_count: Some(u32::MAX),
+        processing_latency_ms: Some(u64::MAX),
+        chain_latency_ms: Some(0),
+        stale: false,
+        signer_pubkey: Some("4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R".to_string()),
+        signature: Some("5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW".to_string()),
+        raw_transaction: None,
+    }
+}
+
+#[test]
+fn test_round_trip_is_lossless() {
+    let event = create_test_event();
+    let round_tripped = TokenCreatedEvent::try_from(proto::TokenCreatedEvent::from(event.clone())).unwrap();
+
+    // compare through JSON since the data models only derive Serialize
+    assert_eq!(
+        serde_json::to_value(&round_tripped).unwrap(),
+        serde_json::to_value(&event).unwrap()
+    );
+}
+
+#[test]
+fn test_u64_fields_keep_full_range() {
+    let message = proto::TokenCreatedEvent::from(create_test_event());
+    assert_eq!(message.token.as_ref().unwrap().supply, Some(u64::MAX));
+    assert_eq!(message.pump_data.as_ref().unwrap().virtual_sol_reserves, Some(u64::MAX - 1));
+    assert_eq!(message.slot, u64::MAX - 2);
+    assert_eq!(message.tx_fee_lamports, u64::MAX);
+}
+
+#[test]
+fn test_fields_not_fetched_stay_unset() {
+    // what the server sends at DETAIL_LEVEL=minimal
+    let mut event = create_test_event();
+    (event.token.supply, event.token.decimals) = (None, None);
+    event.pump_data = PumpFunData { bonding_curve: event.pump_data.bonding_curve, ..Default::default() };
+
+    let message = proto::TokenCreatedEvent::from(event.clone());
+    assert_eq!(message.token.as_ref().unwrap().decimals, None);
+    assert_eq!(message.pump_data.as_ref().unwrap().market_cap_sol, None);
+    let round_tripped = TokenCreatedEvent::try_from(message).unwrap();
+    assert_eq!(round_tripped.token.supply, None);
+    assert_eq!(round_tripped.pump_data, event.pump_data);
+}
+
+#[test]
+fn test_timestamp_keeps_nanoseconds() {
+    let event = create_test_event();
+    let timestamp = proto::TokenCreatedEvent::from(event.clone()).timestamp.unwrap();
+    assert_eq!(timestamp.seconds, 1_705_314_645);
+    assert_eq!(timestamp.nanos, 123_456_789);
+
+    let round_tripped = TokenCreatedEvent::try_from(proto::TokenCreatedEvent::from(event.clone())).unwrap();
+    assert_eq!(round_tripped.timestamp, event.timestamp);
+}
+
+#[test]
+fn test_missing_parts_are_rejected() {
+    let mut message = proto::TokenCreatedEvent::from(create_test_event());
+    message.token = None;
+    assert!(TokenCreatedEvent::try_from(message).is_err());
+
+    let mut message = proto::TokenCreatedEvent::from(create_test_event());
+    message.timestamp = None;
+    assert!(TokenCreatedEvent::try_from(message).is_err());
+}
+
+#[test]
+fn test_out_of_range_values_are_rejected() {
+    let mut message = proto::TokenCreatedEvent::from(create_test_event());
+    message.token.as_mut().unwrap().decimals = Some(256);
+    assert!(TokenCreatedEvent::try_from(message).is_err());
+
+    let mut message = proto::TokenCreatedEvent::from(create_test_event());
+    message.timestamp.as_mut().unwrap().nanos = -1;
+    assert!(TokenCreatedEvent::try_from(message).is_err());
+
+    let mut message = proto::TokenCreatedEvent::from(create_test_event());
+    message.creator_stats.as_mut().unwrap().first_seen = None;
+    assert!(TokenCreatedEvent::try_from(message).is_err());
+}
+
+#[test]
+fn test_filter_request_maps_to_filter_criteria() {
+    let filter = FilterCriteria::from(proto::FilterRequest {
+        creator: Some("creator_A".to_string()),
+        creator_tag: Some("insider".to_string()),
+        symbol: None,
+        name_contains: Some("doge".to_string()),
+        program: Some("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P".to_string()),
+        min_slot: Some(280_000_000),
+        max_creator_tokens: Some(1),
+        min_initial_buy_sol: Some(0.5),
+        max_same_tx_buyers: Some(1),
+        min_priority_fee_lamports: Some(100_000),
+        max_similar_recent_count: Some(0),
+        ..Default::default()
+    });
+    assert_eq!(filter.creator.as_deref(), Some("creator_A"));
+    assert_eq!(filter.creator_tag.as_deref(), Some("insider"));
+    assert_eq!(filter.symbol, None);
+    assert_eq!(filter.name_contains.as_deref(), Some("doge"));
+    assert_eq!(filter.program.as_deref(), Some("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P"));
+    assert_eq!(filter.min_slot, Some(280_000_000));
+    assert_eq!(filter.max_creator_tokens, Some(1));
+    assert_eq!(filter.min_creator_tokens, None);
+    assert_eq!(filter.min_initial_buy_sol, Some(0.5));
+    assert_eq!(filter.max_same_tx_buyers, Some(1));
+    assert_eq!(filter.min_priority_fee_lamports, Some(100_000));
+    assert_eq!(filter.max_similar_recent_count, Some(0));
+    assert_eq!(filter.min_similar_recent_count, None);
+}
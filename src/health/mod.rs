@@ -0,0 +1,196 @@
+//! # Health Endpoints
+//!
+//! Tiny HTTP server exposing liveness and readiness probes for orchestrators such as Kubernetes.
+//!
+//! * `/healthz` - 200 whenever the process is up
+//! * `/readyz` - 200 only while the Solana log subscription is connected and has received a message recently, 503 otherwise; both cases return a JSON body describing each component, including the endpoints an RPC provider is refusing for its plan limits and whether the parser self-test found the parser possibly stale, which doesn't affect readiness since a restart wouldn't fix it
+//! * `/metrics` - Prometheus text exposition of the process metrics, plus the age of the last log notification
+//! * `POST /reload` - re-reads the configuration and applies what can change without a restart; needs the admin token as `Authorization: Bearer <token>`
+
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use tracing::info;
+use serde::Serialize;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+
+use crate::error::MonitorError;
+use crate::metrics::{write_metric, Metrics};
+use crate::reload::Reloader;
+use crate::rpc_client::{ConnectionStatus, ParserStatus};
+
+/// shared state read by the health handlers.
+///
+#[derive(Clone)]
+pub struct HealthState {
+    pub connection: watch::Receiver<ConnectionStatus>,
+    pub metrics: Arc<Metrics>,
+    pub max_silence: Duration,
+    /// answers `POST /reload`; without it the endpoint is not found
+    pub reloader: Option<Arc<Reloader>>,
+}
+
+/// JSON body returned by `/readyz`.
+///
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HealthReport {
+    pub ready: bool,
+    pub components: ComponentStatus,
+}
+
+/// per-component state included in the readiness report.
+///
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ComponentStatus {
+    pub rpc_ws_connected: bool,
+    pub last_event_age_secs: Option<u64>,
+    /// age of the last `logsNotification`; unlike `last_event_age_secs`, pings and pongs don't reset it
+    pub last_notification_age_secs: Option<u64>,
+    pub ws_clients: usize,
+    /// endpoints refused by their provider for a plan limit, by label, with the provider's reason
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub rpc_provider_limits: BTreeMap<String, String>,
+    /// `parser_possibly_stale` once the parser self-test finds recent transactions but no creations among them
+    pub parser: ParserStatus,
+}
+
+impl HealthState {
+    /// Builds the readiness report as of `now`.
+    ///
+    /// The service is ready when the subscription is connected and the last message arrived no more than `max_silence` ago.
+    pub fn report(&self, now: Instant) -> HealthReport {
+        let connection = self.connection.borrow().clone();
+        let last_event_age = connection
+            .last_message_at
+            .map(|at| now.saturating_duration_since(at));
+
+        let last_notification_age = connection
+            .last_notification_at
+            .map(|at| now.saturating_duration_since(at));
+
+        let ready = connection.connected
+            && last_event_age.is_some_and(|age| age <= self.max_silence);
+
+        HealthReport {
+            ready,
+            components: ComponentStatus {
+                rpc_ws_connected: connection.connected,
+                last_event_age_secs: last_event_age.map(|age| age.as_secs()),
+                last_notification_age_secs: last_notification_age.map(|age| age.as_secs()),
+                ws_clients: self.metrics.ws_clients.load(Ordering::Relaxed),
+                rpc_provider_limits: connection.provider_limits,
+                parser: connection.parser,
+            },
+        }
+    }
+
+    /// Prometheus exposition of the process metrics and the subscription state as of `now`.
+    ///
+    pub fn render_metrics(&self, now: Instant) -> String {
+        let mut out = self.metrics.render();
+        let components = self.report(now).components;
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_rpc_provider_limited_endpoints",
+            "gauge",
+            "RPC endpoints refused by their provider for a plan limit",
+            components.rpc_provider_limits.len() as u64,
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_parser_possibly_stale",
+            "gauge",
+            "1 while the parser self-test finds recent transactions but no token creations among them",
+            u64::from(components.parser == ParserStatus::PossiblyStale),
+        );
+        if let Some(age) = components.last_notification_age_secs {
+            write_metric(
+                &mut out,
+                "pump_fun_monitor_rpc_last_notification_age_seconds",
+                "gauge",
+                "Seconds since the last log notification from the Solana RPC",
+                age,
+            );
+        }
+        out
+    }
+
+    /// Reloads the configuration for a caller presenting `authorization`, returning the status and JSON body to answer
+    /// with: the reload report, or an error with the same `code` and `message` WebSocket clients get.
+    pub fn reload(&self, authorization: Option<&str>) -> (StatusCode, serde_json::Value) {
+        let Some(reloader) = &self.reloader else {
+            return (StatusCode::NOT_FOUND, error_body(&MonitorError::Unsupported("reloading is not enabled".to_string())));
+        };
+        let token = authorization.and_then(|value| value.strip_prefix("Bearer ")).unwrap_or_default();
+        if !reloader.is_admin(token.trim()) {
+            return (StatusCode::UNAUTHORIZED, error_body(&MonitorError::Unauthorized));
+        }
+        match reloader.reload_logged() {
+            Ok(report) => (StatusCode::OK, serde_json::json!(report)),
+            Err(e) => (StatusCode::CONFLICT, error_body(&e)),
+        }
+    }
+}
+
+fn error_body(error: &MonitorError) -> serde_json::Value {
+    serde_json::json!({ "code": error.code(), "message": error.to_string() })
+}
+
+/// Serves the health endpoints on `addr` until the process exits.
+///
+pub async fn start_health_server(addr: SocketAddr, state: HealthState) -> std::io::Result<()> {
+    let app = Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/metrics", get(metrics))
+        .route("/reload", post(reload))
+        .with_state(state);
+
+    let listener = TcpListener::bind(addr).await?;
+    info!("Health endpoints listening on {}", addr);
+    axum::serve(listener, app).await
+}
+
+async fn healthz() -> StatusCode {
+    StatusCode::OK
+}
+
+async fn readyz(State(state): State<HealthState>) -> (StatusCode, Json<HealthReport>) {
+    let report = state.report(Instant::now());
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+async fn metrics(State(state): State<HealthState>) -> ([(header::HeaderName, &'static str); 1], String) {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.render_metrics(Instant::now()),
+    )
+}
+
+async fn reload(State(state): State<HealthState>, headers: HeaderMap) -> (StatusCode, Json<serde_json::Value>) {
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    // reading the config file and resolving its bind hosts block
+    let (status, body) = tokio::task::spawn_blocking(move || state.reload(authorization.as_deref()))
+        .await
+        .unwrap_or_else(|e| (StatusCode::INTERNAL_SERVER_ERROR, serde_json::json!({ "message": e.to_string() })));
+    (status, Json(body))
+}
+
+#[cfg(test)]
+mod tests;
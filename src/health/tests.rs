@@ -0,0 +1,187 @@
+//! Unit tests for readiness evaluation and the reload endpoint.
+
+
+use super::*;
+
+/// function to create a health state backed by a fresh connection status channel.
+fn create_test_state() -> (watch::Sender<ConnectionStatus>, HealthState) {
+    let (tx, rx) = watch::channel(ConnectionStatus::default());
+    let state = HealthState {
+        connection: rx,
+        metrics: Arc::new(Metrics::default()),
+        max_silence: Duration::from_secs(60),
+        reloader: None,
+    };
+    state.metrics.ws_clients.store(2, Ordering::Relaxed);
+    (tx, state)
+}
+
+#[test]
+fn test_not_ready_before_first_connection() {
+    let (_tx, state) = create_test_state();
+    let report = state.report(Instant::now());
+
+    assert!(!report.ready);
+    assert!(!report.components.rpc_ws_connected);
+    assert_eq!(report.components.last_event_age_secs, None);
+    assert_eq!(report.components.last_notification_age_secs, None);
+}
+
+#[test]
+fn test_ready_when_connected_and_recent() {
+    let (tx, state) = create_test_state();
+    let now = Instant::now();
+    tx.send_replace(ConnectionStatus { connected: true, last_message_at: Some(now), ..Default::default() });
+
+    let report = state.report(now + Duration::from_secs(5));
+    assert!(report.ready);
+    assert_eq!(report.components.last_event_age_secs, Some(5));
+    assert_eq!(report.components.ws_clients, 2);
+}
+
+#[test]
+fn test_ready_to_not_ready_when_subscription_drops() {
+    let (tx, state) = create_test_state();
+    let now = Instant::now();
+    tx.send_replace(ConnectionStatus { connected: true, last_message_at: Some(now), ..Default::default() });
+    assert!(state.report(now).ready);
+
+    // subscription drops, the last message time is kept for diagnostics
+    tx.send_modify(|status| status.connected = false);
+
+    let report = state.report(now);
+    assert!(!report.ready);
+    assert!(!report.components.rpc_ws_connected);
+    assert_eq!(report.components.last_event_age_secs, Some(0));
+}
+
+#[test]
+fn test_not_ready_when_silent_too_long() {
+    let (tx, state) = create_test_state();
+    let now = Instant::now();
+    tx.send_replace(ConnectionStatus { connected: true, last_message_at: Some(now), ..Default::default() });
+
+    assert!(state.report(now + Duration::from_secs(60)).ready);
+    assert!(!state.report(now + Duration::from_secs(61)).ready);
+}
+
+#[test]
+fn test_report_serializes_component_names() {
+    let (_tx, state) = create_test_state();
+    let json = serde_json::to_value(state.report(Instant::now())).unwrap();
+
+    assert_eq!(json["ready"], false);
+    assert_eq!(json["components"]["rpc_ws_connected"], false);
+    assert!(json["components"]["last_event_age_secs"].is_null());
+    assert!(json["components"]["last_notification_age_secs"].is_null());
+    assert_eq!(json["components"]["ws_clients"], 2);
+}
+
+#[test]
+fn test_notification_age_reported_separately() {
+    let (tx, state) = create_test_state();
+    let now = Instant::now();
+    tx.send_replace(ConnectionStatus {
+        connected: true,
+        last_message_at: Some(now + Duration::from_secs(40)),
+        last_notification_at: Some(now),
+        provider_limits: Default::default(),
+        parser: Default::default(),
+    });
+
+    let later = now + Duration::from_secs(45);
+    let report = state.report(later);
+    assert!(report.ready);
+    assert_eq!(report.components.last_event_age_secs, Some(5));
+    assert_eq!(report.components.last_notification_age_secs, Some(45));
+
+    let metrics = state.render_metrics(later);
+    assert!(metrics.contains("# TYPE pump_fun_monitor_rpc_last_notification_age_seconds gauge"));
+    assert!(metrics.contains("\npump_fun_monitor_rpc_last_notification_age_seconds 45\n"));
+}
+
+#[test]
+fn test_notification_age_metric_absent_before_first_notification() {
+    let (_tx, state) = create_test_state();
+    assert!(!state.render_metrics(Instant::now()).contains("last_notification_age_seconds"));
+}
+
+#[test]
+fn test_provider_limits_reported() {
+    let (tx, state) = create_test_state();
+    let now = Instant::now();
+    let json = serde_json::to_value(state.report(now)).unwrap();
+    assert!(json["components"].get("rpc_provider_limits").is_none());
+    assert!(state.render_metrics(now).contains("\npump_fun_monitor_rpc_provider_limited_endpoints 0\n"));
+
+    tx.send_modify(|status| {
+        status.provider_limits.insert("helius".to_string(), "Too many subscriptions (code -32603)".to_string());
+    });
+    let json = serde_json::to_value(state.report(now)).unwrap();
+    assert_eq!(json["components"]["rpc_provider_limits"]["helius"], "Too many subscriptions (code -32603)");
+    assert!(state.render_metrics(now).contains("\npump_fun_monitor_rpc_provider_limited_endpoints 1\n"));
+}
+
+/// function to create a health state that reloads a config with admin token `secret`.
+fn create_reloading_state() -> HealthState {
+    let overrides = std::collections::HashMap::from([
+        ("SOLANA_RPC_HTTP_URL", "https://api.mainnet-beta.solana.com".to_string()),
+        ("SOLANA_RPC_WSS_URL", "wss://api.mainnet-beta.solana.com".to_string()),
+        ("WEBSOCKET_SERVER_PORT", "8080".to_string()),
+        ("WS_ADMIN_TOKEN", "secret".to_string()),
+    ]);
+    let config = crate::config::Config::load(None, &overrides).unwrap();
+    let (_tx, state) = create_test_state();
+    HealthState {
+        reloader: Some(Arc::new(Reloader::new(config, None, overrides))),
+        ..state
+    }
+}
+
+#[test]
+fn test_reload_needs_a_reloader() {
+    let (_tx, state) = create_test_state();
+    let (status, body) = state.reload(Some("Bearer secret"));
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "unsupported");
+}
+
+#[test]
+fn test_reload_needs_the_admin_token() {
+    let state = create_reloading_state();
+
+    for authorization in [None, Some("Bearer wrong"), Some("secret")] {
+        let (status, body) = state.reload(authorization);
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "unauthorized");
+    }
+}
+
+#[test]
+fn test_reload_reports_what_changed() {
+    let state = create_reloading_state();
+    let (status, body) = state.reload(Some("Bearer secret"));
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, serde_json::json!({ "changed": [] }));
+}
+
+#[test]
+fn test_possibly_stale_parser_reported_without_affecting_readiness() {
+    let (tx, state) = create_test_state();
+    let now = Instant::now();
+    tx.send_replace(ConnectionStatus { connected: true, last_message_at: Some(now), ..Default::default() });
+    let json = serde_json::to_value(state.report(now)).unwrap();
+    assert_eq!(json["components"]["parser"], "unchecked");
+
+    tx.send_modify(|status| status.parser = ParserStatus::PossiblyStale);
+    let report = state.report(now);
+    assert!(report.ready);
+    let json = serde_json::to_value(report).unwrap();
+    assert_eq!(json["components"]["parser"], "parser_possibly_stale");
+    assert!(state.render_metrics(now).contains("\npump_fun_monitor_parser_possibly_stale 1\n"));
+
+    tx.send_modify(|status| status.parser = ParserStatus::Ok);
+    assert!(state.render_metrics(now).contains("\npump_fun_monitor_parser_possibly_stale 0\n"));
+}
@@ -0,0 +1,172 @@
+//! # REST Query API
+//!
+//! Small HTTP API over the event store for consumers that want history rather than a live stream.
+//!
+//! * `GET /tokens?creator=&symbol=&nameContains=&minSlot=&since=<iso8601>&limit=100&cursor=` - recent events, newest first; filters use the same semantics as the WebSocket `setFilter` message, and `nextCursor` in the response fetches the next page
+//! * `GET /tokens/{mint}` - the event for a single token from the token registry, which asks the postgres sink's table
+//!   about mints it doesn't hold, or else from the store; 404 if none of them has it
+//! * `GET /stats` - number of stored events per hour for the last 24 hours
+//! * `GET /schema` - JSON Schema of every event and WebSocket message, keyed by type name; see `schema`
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::{FromRef, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+use crate::data_models::FilterCriteria;
+use crate::error::MonitorError;
+use crate::event_store::{EventQuery, EventStore};
+use crate::filtering::normalize_filter;
+use crate::registry::TokenRegistry;
+use crate::schema;
+
+/// page size used when `limit` is not given.
+const DEFAULT_LIMIT: usize = 100;
+
+/// largest page a single request may ask for.
+const MAX_LIMIT: usize = 1_000;
+
+/// query string accepted by `GET /tokens`.
+///
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TokensQuery {
+    pub creator: Option<String>,
+    pub creator_tag: Option<String>,
+    pub symbol: Option<String>,
+    pub name_contains: Option<String>,
+    pub uri_host: Option<String>,
+    pub uri_scheme: Option<String>,
+    pub has_image: Option<bool>,
+    pub has_twitter: Option<bool>,
+    pub image_seen_before: Option<bool>,
+    #[serde(default)]
+    pub require_twitter: bool,
+    #[serde(default)]
+    pub require_website: bool,
+    #[serde(default)]
+    pub require_standard_authorities: bool,
+    pub program: Option<String>,
+    pub min_slot: Option<u64>,
+    pub min_creator_tokens: Option<u32>,
+    pub max_creator_tokens: Option<u32>,
+    pub min_creator_graduation_rate: Option<f32>,
+    pub min_initial_buy_sol: Option<f64>,
+    pub max_same_tx_buyers: Option<u32>,
+    pub min_priority_fee_lamports: Option<u64>,
+    pub min_similar_recent_count: Option<u32>,
+    pub max_similar_recent_count: Option<u32>,
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+impl From<TokensQuery> for EventQuery {
+    fn from(query: TokensQuery) -> Self {
+        Self {
+            filter: FilterCriteria {
+                creator: query.creator,
+                creator_tag: query.creator_tag,
+                symbol: query.symbol,
+                name_contains: query.name_contains,
+                uri_host: query.uri_host,
+                uri_scheme: query.uri_scheme,
+                has_image: query.has_image,
+                has_twitter: query.has_twitter,
+                image_seen_before: query.image_seen_before,
+                require_twitter: query.require_twitter,
+                require_website: query.require_website,
+                require_standard_authorities: query.require_standard_authorities,
+                program: query.program,
+                min_slot: query.min_slot,
+                min_creator_tokens: query.min_creator_tokens,
+                max_creator_tokens: query.max_creator_tokens,
+                min_creator_graduation_rate: query.min_creator_graduation_rate,
+                min_initial_buy_sol: query.min_initial_buy_sol,
+                max_same_tx_buyers: query.max_same_tx_buyers,
+                min_priority_fee_lamports: query.min_priority_fee_lamports,
+                min_similar_recent_count: query.min_similar_recent_count,
+                max_similar_recent_count: query.max_similar_recent_count,
+            },
+            since: query.since,
+            cursor: query.cursor,
+            limit: query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+        }
+    }
+}
+
+/// what the handlers read from.
+#[derive(Clone)]
+struct ApiState {
+    store: Arc<EventStore>,
+    registry: Arc<TokenRegistry>,
+}
+
+impl FromRef<ApiState> for Arc<EventStore> {
+    fn from_ref(state: &ApiState) -> Self {
+        Arc::clone(&state.store)
+    }
+}
+
+/// Serves the query API on `addr` until the process exits.
+///
+pub async fn start_http_api(
+    addr: SocketAddr,
+    store: Arc<EventStore>,
+    registry: Arc<TokenRegistry>,
+) -> std::io::Result<()> {
+    let app = Router::new()
+        .route("/tokens", get(list_tokens))
+        .route("/tokens/:mint", get(get_token))
+        .route("/stats", get(stats))
+        .route("/schema", get(wire_schema))
+        .with_state(ApiState { store, registry });
+
+    let listener = TcpListener::bind(addr).await?;
+    info!("REST query API listening on {}", listener.local_addr()?);
+    axum::serve(listener, app).await
+}
+
+async fn list_tokens(State(store): State<Arc<EventStore>>, Query(query): Query<TokensQuery>) -> Response {
+    let mut query = EventQuery::from(query);
+    query.filter = match normalize_filter(query.filter) {
+        Ok(filter) => filter,
+        Err(problems) => return (StatusCode::BAD_REQUEST, MonitorError::InvalidFilter(problems).to_string()).into_response(),
+    };
+    match store.query(&query) {
+        Ok(page) => Json(page).into_response(),
+        Err(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+    }
+}
+
+async fn get_token(State(state): State<ApiState>, Path(mint): Path<String>) -> Response {
+    match state.registry.lookup(&mint).await {
+        Ok(Some(event)) => return Json(event.as_ref()).into_response(),
+        Ok(None) => {}
+        Err(e) => warn!("Cannot look up {} in the archive: {}", mint, e),
+    }
+    // the store may still hold a token the registry already forgot
+    match state.store.get(&mint) {
+        Some(event) => Json(event).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn wire_schema() -> Response {
+    Json(schema::wire_schema_document()).into_response()
+}
+
+async fn stats(State(store): State<Arc<EventStore>>) -> Response {
+    Json(store.hourly_counts(Utc::now())).into_response()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,44 @@
+//! Unit tests for the REST query parameter handling.
+
+
+use super::*;
+
+#[test]
+fn test_query_defaults() {
+    let query = EventQuery::from(TokensQuery::default());
+    assert_eq!(query.limit, DEFAULT_LIMIT);
+    assert!(query.since.is_none());
+    assert!(query.cursor.is_none());
+}
+
+#[test]
+fn test_limit_is_clamped() {
+    let too_large = EventQuery::from(TokensQuery {
+        limit: Some(1_000_000),
+        ..TokensQuery::default()
+    });
+    let zero = EventQuery::from(TokensQuery {
+        limit: Some(0),
+        ..TokensQuery::default()
+    });
+    assert_eq!(too_large.limit, MAX_LIMIT);
+    assert_eq!(zero.limit, 1);
+}
+
+#[test]
+fn test_query_string_maps_to_filter() {
+    let query: TokensQuery = serde_json::from_value(serde_json::json!({
+        "creator": "creator_A",
+        "nameContains": "doge",
+        "since": "2024-01-15T10:00:00Z",
+        "limit": 5,
+    }))
+    .unwrap();
+    let query = EventQuery::from(query);
+
+    assert_eq!(query.filter.creator.as_deref(), Some("creator_A"));
+    assert_eq!(query.filter.name_contains.as_deref(), Some("doge"));
+    assert_eq!(query.filter.symbol, None);
+    assert_eq!(query.since.unwrap().to_rfc3339(), "2024-01-15T10:00:00+00:00");
+    assert_eq!(query.limit, 5);
+}
@@ -0,0 +1,38 @@
+//! # Pump.fun Token Monitor
+//!
+//! A real-time monitoring service for pump.fun token creation events on Solana, as a library: the
+//! `pump_fun_monitor_corrected` binary runs it, and other programs can embed the parts they need, such as
+//! [`client::MonitorClient`] for consuming a running monitor or [`rpc_client::TokenEventStream`] for the events of one
+//! running in-process.
+//!
+pub mod cache;
+pub mod cli;
+pub mod client;
+pub mod config;
+pub mod curve_tracker;
+pub mod daemon;
+pub mod data_models;
+pub mod dispatch;
+pub mod error;
+pub mod event_store;
+pub mod export;
+pub mod filtering;
+pub mod freshness;
+pub mod global_filter;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod health;
+pub mod http_api;
+pub mod metadata;
+pub mod metrics;
+pub mod price_feed;
+pub mod registry;
+pub mod reload;
+pub mod rpc_client;
+pub mod schema;
+pub mod signing;
+pub mod simulation;
+pub mod sinks;
+pub mod supervisor;
+pub mod watchlist;
+pub mod websocket_server;
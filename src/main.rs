@@ -2,61 +2,475 @@
 //!
 //! A real-time monitoring service for pump.fun token creation events on Solana.
 //!
-mod data_models;
-mod error;
-mod rpc_client;
-mod websocket_server;
+use pump_fun_monitor_corrected::{
+    cache, cli, config, curve_tracker, daemon, dispatch, error, event_store, health, http_api, metrics, price_feed,
+    registry, reload, rpc_client, simulation, sinks, supervisor, websocket_server,
+};
+#[cfg(feature = "grpc")]
+use pump_fun_monitor_corrected::grpc;
 
+use clap::Parser;
+use cli::Cli;
+use config::Config;
+use daemon::LogFile;
+use dispatch::{EventDispatcher, ImmediateSubscriber};
+use metrics::Metrics;
 use dotenv::dotenv;
-use log::info;
-use rpc_client::SolanaRpcMonitor;
+use price_feed::SolPriceCell;
+use registry::TokenRegistry;
+use reload::Reloader;
+use rpc_client::{run_curve_snapshot, LagPolicy, SolanaRpcMonitor, TokenEventStream};
+use simulation::Simulator;
 use std::env;
+use std::sync::Arc;
+use supervisor::{supervise, TaskExit};
 use tokio::sync::broadcast;
+use tracing::{error, info};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use websocket_server::{redact_endpoint, AdminContext, EventArchive, LogLevelHandle, WebSocketServer};
 
 /// Main entry point for the pump.fun token monitor service.
 ///
 /// This function:
-/// 1. Loads configuration from environment variables
-/// 2. Sets up logging
-/// 3. Creates a broadcast channel for token events
-/// 4. Spawns the RPC monitor and WebSocket server tasks
-/// 5. Runs both tasks concurrently until one exits
-#[tokio::main]
-async fn main() {
+/// 1. Sets up logging (`LOG_FORMAT=json|text`, filtered by `RUST_LOG`), to stdout or the `--log-file`
+/// 2. Loads configuration from the config file, environment variables and command line flags
+/// 3. Detaches from the terminal with `--daemonize` and writes the `--pid-file`, before the Tokio runtime starts
+///
+/// and then runs the service on the runtime.
+fn main() {
     dotenv().ok();
+    let cli = Cli::parse();
+
+    // exporting an archive or the schema needs neither configuration nor logging
+    if let Some(path) = &cli.convert_jsonl_to_csv {
+        std::process::exit(if cli::convert_jsonl_to_csv(path) { 0 } else { 1 });
+    }
+    if let Some(dir) = &cli.dump_schema {
+        std::process::exit(if cli::dump_schema(dir.as_deref()) { 0 } else { 1 });
+    }
 
-    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+    let log_file = match &cli.log_file {
+        Some(path) => match LogFile::open(path) {
+            Ok(log_file) => Some(log_file),
+            Err(e) => {
+                eprintln!("Cannot open log file {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let log_level = init_logging(cli.log_level.as_deref(), log_file.as_ref());
+
+    // watching another monitor needs no configuration either
+    if let Some(url) = &cli.watch {
+        let watched = runtime().block_on(cli::watch(url, cli.watch_filter()));
+        std::process::exit(if watched { 0 } else { 1 });
+    }
 
     info!("Starting pump.fun monitor service...");
 
-    // load configuration from environment variables
-    let http_url = env::var("SOLANA_RPC_HTTP_URL").expect("SOLANA_RPC_HTTP_URL must be set");
-    let wss_url = env::var("SOLANA_RPC_WSS_URL").expect("SOLANA_RPC_WSS_URL must be set");
-    let pump_fun_id = env::var("PUMP_FUN_PROGRAM_ID").expect("PUMP_FUN_PROGRAM_ID must be set");
-    let ws_port = env::var("WEBSOCKET_SERVER_PORT")
-        .expect("WEBSOCKET_SERVER_PORT must be set")
-        .parse::<u16>()
-        .expect("Invalid WebSocket port number");
+    let config = match load_config(&cli) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // forking is only sound while the process has a single thread, so this comes before the runtime
+    let pid_file = match daemon::start(cli.daemonize, cli.pid_file.as_deref()) {
+        Ok(pid_file) => pid_file,
+        Err(e) => {
+            // once detached, the log file is the only place left to report to
+            if log_file.is_some() {
+                error!("{}", e);
+            }
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    runtime().block_on(run(cli, config, log_level, log_file));
+    drop(pid_file);
+}
+
+/// the runtime `#[tokio::main]` would start, built by hand so `--daemonize` can fork first.
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed building the Tokio runtime")
+}
+
+/// Runs the service:
+/// 1. Creates the event dispatcher: a durable queue for the persisting sinks, and a broadcast channel for everything else
+/// 2. Spawns the RPC monitor and WebSocket server tasks, each restarted by a supervisor when it fails or panics
+/// 3. Runs both tasks concurrently until one stops for good: on Ctrl+C, or once its restart budget is exhausted
+///
+/// Sink filters, per-IP limits, copycat suppression and the log level are reloaded from the config on `SIGHUP` or a
+/// `POST /reload` to the health port, and the `--log-file` is opened again on `SIGUSR1`.
+async fn run(cli: Cli, config: Config, log_level: LogLevelHandle, log_file: Option<LogFile>) {
+    if cli.dry_run {
+        let passed = cli::dry_run(&config).await;
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+    if cli.reprocess_dead_letters {
+        let passed = cli::reprocess_dead_letters(&config).await;
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+    if let Some(command) = &cli.command {
+        let passed = cli::run_command(command, &config).await;
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+
+    // a level set only in the config file wasn't known when logging started
+    if let Some(level) = &config.log_level {
+        if let Err(e) = log_level.set(level) {
+            error!("Cannot apply log level '{}': {}", level, e);
+        }
+    }
+    let reloader = Arc::new(
+        Reloader::new(config.clone(), cli.config.clone(), cli.config_overrides()).with_log_level(log_level.clone()),
+    );
+    #[cfg(unix)]
+    tokio::spawn(reload::reload_on_sighup(Arc::clone(&reloader)));
+    #[cfg(unix)]
+    if let Some(log_file) = log_file {
+        tokio::spawn(daemon::reopen_on_sigusr1(log_file));
+    }
+    #[cfg(not(unix))]
+    drop(log_file);
+
+    if let Some(watchlist) = &config.watchlist {
+        info!("Watchlist loaded with {} wallets", watchlist.len());
+    }
+    if let Some(global_filter) = &config.global_filter {
+        let blocklisted = global_filter.creator_blocklist.as_ref().map_or(0, |blocklist| blocklist.len());
+        info!("Global filter on, {} creators blocklisted", blocklisted);
+    }
+    if let Some(max_event_age) = config.max_event_age {
+        info!("Events older than {}s are marked stale", max_event_age.as_secs());
+    }
+    if let Some(signer) = &config.event_signer {
+        info!("Signing events as {}", signer.pubkey());
+    }
+
+    info!(
+        "Capacities: broadcast {}, processor queue {} (+{} overflow), durable queue {}, client queue {}, replay {}",
+        config.channels.broadcast_capacity,
+        config.channels.processor_capacity,
+        config.channels.overflow_capacity,
+        config.channels.durable_capacity,
+        config.server.client_queue_capacity,
+        config.server.replay_capacity
+    );
+    let (tx, rx) = broadcast::channel(config.channels.broadcast_capacity);
+    let mut dispatcher = EventDispatcher::new(tx.clone())
+        .with_slow_enqueue(config.channels.slow_enqueue)
+        .with_demoted_queues(config.channels.durable_capacity, config.channels.durable_overflow);
+
+    let metrics = Arc::new(Metrics::default());
+    metrics.set_dispatch_latency(dispatcher.latency());
+    // caches prune themselves as they are written to; this only reports how big they are
+    tokio::spawn(cache::run_janitor(Arc::clone(&metrics), config.caches.janitor_interval));
+
+    // `query` requests and token lookups read the postgres sink's table when there is one
+    #[cfg(feature = "postgres")]
+    let postgres_archive = match &config.sinks.postgres {
+        Some(postgres) => match sinks::PostgresArchive::connect(postgres).await {
+            Ok(archive) => Some(Arc::new(archive)),
+            Err(e) => {
+                error!(
+                    "Failed to open the PostgreSQL archive at {}, queries fall back to the replay buffer: {}",
+                    redact_endpoint(&postgres.url),
+                    e
+                );
+                None
+            }
+        },
+        None => None,
+    };
+
+    // the creation of every broadcast token by mint, filled on the dispatching task ahead of the broadcast
+    let registry = TokenRegistry::new(config.caches.tokens);
+    #[cfg(feature = "postgres")]
+    let registry = match &postgres_archive {
+        Some(archive) => registry.with_fallback(Arc::clone(archive) as Arc<dyn registry::TokenSource>),
+        None => registry,
+    };
+    let registry = Arc::new(registry);
+    metrics.register_cache("tokens", registry.cache_stats());
+    let registry_stats = dispatcher.add_immediate(Arc::clone(&registry) as Arc<dyn ImmediateSubscriber>);
+    metrics.add_immediate_subscriber(registry_stats);
 
-    let (tx, rx) = broadcast::channel(100);
+    // external sinks subscribe before the monitor takes the dispatcher, durable queues can't be added later
+    sinks::spawn_sinks(&config.sinks, &config.channels, &mut dispatcher, &metrics, reloader.sink_filters());
+    // bonding curves of new tokens are followed on a connection of their own, off unless CURVE_TRACKING is on
+    let curve_updates = curve_tracker::spawn_curve_tracker(&config, &tx, &registry, &metrics);
+    // image hashes that miss a token's broadcast follow it, off unless IMAGE_HASH_FOLLOW_UPS is on
+    let enrichments = config
+        .image_hashing
+        .as_ref()
+        .filter(|image_hashing| image_hashing.follow_ups)
+        .map(|_| broadcast::channel(config.channels.broadcast_capacity).0);
+    // migrations of completed curves into their AMM pool, off unless PUMP_AMM_PROGRAM_ID is set
+    let migrations = config
+        .rpc
+        .tracks_migrations()
+        .then(|| broadcast::channel(config.channels.broadcast_capacity).0);
+    // creations decoded from their log notification ahead of the fetches, off unless FAST_PATH is on
+    let previews = config.rpc.fast_path.then(|| broadcast::channel(config.channels.broadcast_capacity).0);
+    // skipped and failed transactions for admins, off unless WS_ADMIN_TOKEN is set and DIAGNOSTICS_MAX_PER_SEC isn't 0
+    let diagnostics = config
+        .publishes_diagnostics()
+        .then(|| broadcast::channel(config.channels.broadcast_capacity).0);
 
-    let monitor = SolanaRpcMonitor::new(http_url, wss_url, pump_fun_id, tx)
-        .expect("Failed to create Solana Monitor");
+    if let Some(http_api_config) = config.http_api.clone() {
+        let store = Arc::new(event_store::EventStore::new(http_api_config.store_capacity));
+        let events = TokenEventStream::new(tx.subscribe(), LagPolicy::Gap);
+        tokio::spawn(event_store::record_events(Arc::clone(&store), events));
+        let registry = Arc::clone(&registry);
+        tokio::spawn(async move {
+            if let Err(e) = http_api::start_http_api(http_api_config.bind_addr, store, registry).await {
+                error!("REST query API error: {}", e);
+            }
+        });
+    }
+
+    #[cfg(feature = "grpc")]
+    if let Some(grpc_config) = config.grpc.clone() {
+        let events = tx.clone();
+        tokio::spawn(async move {
+            info!("gRPC server listening on {}", grpc_config.bind_addr);
+            if let Err(e) = grpc::start_grpc_server(grpc_config.bind_addr, events).await {
+                error!("gRPC server error: {}", e);
+            }
+        });
+    }
+    // the dispatcher keeps its own sender, so the broadcast still closes with the monitor
+    drop(tx);
+
+    // events come from the Solana monitor, or from the generator in simulation mode
+    let supervisor_config = config.supervisor.clone();
+    let monitor_metrics = Arc::clone(&metrics);
+    let (connection, monitor_handle) = if let Some(simulation_config) = config.simulation.clone() {
+        let simulator = Arc::new(Simulator::new(simulation_config, dispatcher).with_signer(config.event_signer.clone()));
+        let connection = simulator.connection_status();
+        (
+            connection,
+            tokio::spawn(async move {
+                supervise("Simulator", &supervisor_config, &monitor_metrics.monitor_restarts, || {
+                    let simulator = Arc::clone(&simulator);
+                    async move {
+                        simulator.run().await;
+                        Ok::<(), error::MonitorError>(())
+                    }
+                })
+                .await
+            }),
+        )
+    } else {
+        let mut monitor = match SolanaRpcMonitor::new(&config, dispatcher) {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        };
 
-    let monitor_handle = tokio::spawn(async move {
-        monitor.start().await;
-    });
+        monitor = monitor
+            .with_metrics(Arc::clone(&metrics))
+            .with_registry(Arc::clone(&registry))
+            .with_spam(reloader.spam())
+            .with_watchlist(reloader.watchlist())
+            .with_global_filter(reloader.global_filter());
+        if let Some(enrichments) = enrichments.clone() {
+            monitor = monitor.with_enrichments(enrichments);
+        }
+        if let Some(migrations) = migrations.clone() {
+            monitor = monitor.with_migrations(migrations);
+        }
+        if let Some(previews) = previews.clone() {
+            monitor = monitor.with_previews(previews);
+        }
+        if let Some(diagnostics) = diagnostics.clone() {
+            monitor = monitor.with_diagnostics(diagnostics);
+        }
 
-    let ws_addr = format!("127.0.0.1:{}", ws_port);
-    let server_handle = tokio::spawn(async move {
-        if let Err(e) = websocket_server::start_websocket_server(&ws_addr, rx).await {
-            log::error!("WebSocket server error: {}", e);
+        // optional USD enrichment, off unless SOL_PRICE_SOURCE is set
+        if let Some(price_feed_config) = config.price_feed.clone() {
+            let sol_price = Arc::new(SolPriceCell::new(price_feed_config.max_age));
+            monitor = monitor.with_sol_price(Arc::clone(&sol_price));
+            tokio::spawn(price_feed::run_price_feed(price_feed_config, monitor.rpc_client(), sol_price));
         }
-    });
 
-    // run both tasks concurrently until one exits
-    tokio::select! {
-        _ = monitor_handle => info!("Solana RPC monitor task exited."),
-        _ = server_handle => info!("WebSocket server task exited."),
+        // optional listing of the curves launched before startup, taken before the subscription so none fall between
+        if let Some(curve_snapshot) = &config.curve_snapshot {
+            if let Err(e) = run_curve_snapshot(monitor.rpc_client().as_ref(), &config.rpc.programs, curve_snapshot).await {
+                error!("Curve snapshot failed: {}", e);
+            }
+        }
+
+        // Ctrl+C cancels the log subscription before the monitor task exits
+        let connection = monitor.connection_status();
+        let monitor = Arc::new(monitor);
+
+        // optional check that recent program activity still parses into creations
+        if let Some(self_test) = config.parser_self_test.clone() {
+            let monitor = Arc::clone(&monitor);
+            tokio::spawn(async move { monitor.run_parser_self_test(&self_test).await });
+        }
+        (
+            connection,
+            tokio::spawn(async move {
+                supervise("Solana monitor", &supervisor_config, &monitor_metrics.monitor_restarts, || {
+                    let monitor = Arc::clone(&monitor);
+                    async move {
+                        monitor
+                            .start_until(async {
+                                let _ = tokio::signal::ctrl_c().await;
+                            })
+                            .await;
+                        Ok::<(), error::MonitorError>(())
+                    }
+                })
+                .await
+            }),
+        )
+    };
+
+    // admins see the endpoint's host only, never the API key in its path or query
+    let admin = AdminContext {
+        connection: Some(connection.clone()),
+        rpc_endpoint: config.simulation.is_none().then(|| redact_endpoint(&config.rpc.wss_url)),
+        log_level: Some(log_level),
+    };
+
+    if let Some(health_config) = config.health.clone() {
+        let health_state = health::HealthState {
+            connection,
+            metrics: Arc::clone(&metrics),
+            max_silence: health_config.max_silence,
+            reloader: Some(Arc::clone(&reloader)),
+        };
+        tokio::spawn(async move {
+            if let Err(e) = health::start_health_server(health_config.bind_addr, health_state).await {
+                error!("Health server error: {}", e);
+            }
+        });
     }
-}
\ No newline at end of file
+
+    // `query` requests read the replay buffer without the postgres sink's table
+    #[cfg(feature = "postgres")]
+    let archive = postgres_archive.map(|archive| archive as Arc<dyn EventArchive>);
+    #[cfg(not(feature = "postgres"))]
+    let archive: Option<Arc<dyn EventArchive>> = None;
+
+    let server = match WebSocketServer::bind(&config.server).await {
+        Ok(server) => {
+            let server = server
+                .with_admin(admin)
+                .with_ip_limits(reloader.ip_limits())
+                .with_presets(reloader.presets());
+            let server = match archive {
+                Some(archive) => server.with_archive(archive),
+                None => server,
+            };
+            let server = match curve_updates {
+                Some(curve_updates) => server.with_curve_updates(curve_updates),
+                None => server,
+            };
+            let server = match previews {
+                Some(previews) => server.with_previews(previews),
+                None => server,
+            };
+            let server = match enrichments {
+                Some(enrichments) => server.with_enrichments(enrichments),
+                None => server,
+            };
+            let server = match migrations {
+                Some(migrations) => server.with_migrations(migrations),
+                None => server,
+            };
+            match diagnostics {
+                Some(diagnostics) => server.with_diagnostics(diagnostics),
+                None => server,
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to bind WebSocket server to {}: {}", config.server.bind_addr, e);
+            std::process::exit(1);
+        }
+    };
+    info!("🚀 WebSocket server listening on {}://{}", server.scheme(), server.local_addr());
+    let supervisor_config = config.supervisor.clone();
+    let server_handle = tokio::spawn(async move { server.run_supervised(rx, metrics, &supervisor_config).await });
+
+    // run both tasks concurrently until one stops for good
+    let (component, exit) = tokio::select! {
+        exit = monitor_handle => ("Solana RPC monitor", exit),
+        exit = server_handle => ("WebSocket server", exit),
+    };
+    match exit {
+        Ok(TaskExit::Completed) => info!("{} task exited.", component),
+        Ok(TaskExit::GaveUp) => {
+            error!("{} could not be restarted, shutting down.", component);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            error!("{} supervisor failed: {}", component, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Loads configuration from the optional config file, overridden by environment variables and then flags.
+///
+/// Every missing or invalid setting is collected into a single `MonitorError::Config`.
+fn load_config(cli: &Cli) -> error::Result<Config> {
+    Config::load(cli.config.as_deref(), &cli.config_overrides())
+}
+
+/// Installs the global tracing subscriber.
+///
+/// `log_level` (from `--log-level`) takes precedence over `RUST_LOG`. `LOG_FORMAT=json` emits one JSON object per line including the current span's fields, anything else keeps the human-readable text format. `log` records from dependencies are bridged into tracing. Lines go to `log_file` without colors when there is one, and to stdout otherwise.
+fn init_logging(log_level: Option<&str>, log_file: Option<&LogFile>) -> LogLevelHandle {
+    let filter = match log_level {
+        Some(level) => tracing_subscriber::EnvFilter::new(level),
+        None => tracing_subscriber::EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(reload::DEFAULT_LOG_LEVEL)),
+    };
+    let writer = match log_file {
+        Some(log_file) => BoxMakeWriter::new(log_file.clone()),
+        None => BoxMakeWriter::new(std::io::stdout),
+    };
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .with_ansi(log_file.is_none());
+
+    let json = env::var("LOG_FORMAT")
+        .map(|format| format.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+    // the filter stays reloadable so admins can change it at runtime
+    if json {
+        let builder = builder.json().with_current_span(true).with_span_list(false).with_filter_reloading();
+        let handle = builder.reload_handle();
+        builder.init();
+        LogLevelHandle::new(move |level| reload_filter(&handle, level))
+    } else {
+        let builder = builder.with_filter_reloading();
+        let handle = builder.reload_handle();
+        builder.init();
+        LogLevelHandle::new(move |level| reload_filter(&handle, level))
+    }
+}
+
+/// parses `level` as an `EnvFilter` and swaps it in through `handle`.
+fn reload_filter<S>(
+    handle: &tracing_subscriber::reload::Handle<tracing_subscriber::EnvFilter, S>,
+    level: &str,
+) -> std::result::Result<(), String> {
+    let filter = tracing_subscriber::EnvFilter::try_new(level).map_err(|e| e.to_string())?;
+    handle.reload(filter).map_err(|e| e.to_string())
+}
@@ -0,0 +1,165 @@
+//! # Image Hashing
+//!
+//! Copycat tokens often reuse another token's image byte for byte. With `IMAGE_HASHING` on, the image linked by the
+//! metadata document is downloaded on a task of its own and its SHA-256 compared with the images of recent tokens.
+//! Downloads are bounded in size, time and number running at once, and a token only waits `IMAGE_HASH_WAIT_MS` for
+//! its hash: a slow gateway never holds up the broadcast, the hash just arrives later in a `tokenEnriched` message.
+
+use crate::cache::{BoundedCache, CacheLimits, CacheStats};
+use crate::config::ImageHashConfig;
+use crate::data_models::TokenMetadata;
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+/// the hash of a token's image, and the first recent token that had the same image.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageHash {
+    /// lowercase hex SHA-256 of the image bytes
+    pub sha256: String,
+    /// `None` when no other recent token used the image
+    pub first_seen_mint: Option<String>,
+}
+
+impl ImageHash {
+    /// copies the hash onto the metadata of the token it belongs to.
+    pub fn apply(&self, metadata: &mut TokenMetadata) {
+        metadata.image_sha256 = Some(self.sha256.clone());
+        metadata.image_seen_before = Some(self.first_seen_mint.is_some());
+        metadata.image_first_seen_mint = self.first_seen_mint.clone();
+    }
+}
+
+/// Downloads and hashes token images on a bounded pool of tasks.
+///
+pub struct ImageHasher {
+    client: reqwest::Client,
+    max_bytes: usize,
+    fetch_timeout: Duration,
+    wait: Duration,
+    downloads: Arc<Semaphore>,
+    recent: Arc<Mutex<RecentImages>>,
+}
+
+impl ImageHasher {
+    pub fn new(config: &ImageHashConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            max_bytes: config.max_bytes,
+            fetch_timeout: config.fetch_timeout,
+            wait: config.wait,
+            downloads: Arc::new(Semaphore::new(config.concurrency)),
+            recent: Arc::new(Mutex::new(RecentImages::new(config.capacity))),
+        }
+    }
+
+    /// Starts hashing the image at `image_url` for `mint` on a task of its own.
+    ///
+    /// Returns `None` without downloading anything when the pool is busy with `IMAGE_DOWNLOAD_CONCURRENCY` downloads.
+    /// The hash is remembered once the download finishes, even if nobody waits for it any more.
+    pub fn start(&self, image_url: &str, mint: &str) -> Option<PendingImageHash> {
+        let permit = Arc::clone(&self.downloads).try_acquire_owned().ok()?;
+        let client = self.client.clone();
+        let (image_url, mint) = (image_url.to_string(), mint.to_string());
+        let (max_bytes, fetch_timeout) = (self.max_bytes, self.fetch_timeout);
+        let recent = Arc::clone(&self.recent);
+        let handle = tokio::spawn(async move {
+            let _permit = permit;
+            let bytes = match tokio::time::timeout(fetch_timeout, download(&client, &image_url, max_bytes)).await {
+                Ok(Ok(bytes)) => bytes,
+                Ok(Err(e)) => {
+                    debug!(%image_url, "Image download failed: {}", e);
+                    return None;
+                }
+                Err(_) => {
+                    debug!(%image_url, "Image download timed out after {:?}", fetch_timeout);
+                    return None;
+                }
+            };
+            let sha256 = sha256_hex(&bytes);
+            let first_seen_mint = recent.lock().unwrap_or_else(|e| e.into_inner()).record(&sha256, &mint);
+            Some(ImageHash { sha256, first_seen_mint })
+        });
+        Some(PendingImageHash { handle, wait: self.wait })
+    }
+
+    pub fn cache_stats(&self) -> Arc<CacheStats> {
+        self.recent.lock().unwrap_or_else(|e| e.into_inner()).first_mints.stats()
+    }
+}
+
+/// an image hash being computed.
+///
+pub struct PendingImageHash {
+    handle: JoinHandle<Option<ImageHash>>,
+    wait: Duration,
+}
+
+impl PendingImageHash {
+    /// the hash, or `None` if the image couldn't be hashed, when the download ends within `IMAGE_HASH_WAIT_MS`;
+    /// otherwise the pending hash is handed back to be finished later.
+    pub async fn ready(mut self) -> Result<Option<ImageHash>, PendingImageHash> {
+        let finished = tokio::time::timeout(self.wait, &mut self.handle).await;
+        match finished {
+            Ok(hash) => Ok(hash.ok().flatten()),
+            Err(_) => Err(self),
+        }
+    }
+
+    /// the hash once the download ends, however long that takes.
+    pub async fn finish(self) -> Option<ImageHash> {
+        self.handle.await.ok().flatten()
+    }
+}
+
+/// the body behind `url`, failing once it grows past `max_bytes`.
+async fn download(client: &reqwest::Client, url: &str, max_bytes: usize) -> Result<Vec<u8>, String> {
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?;
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        if body.len() + chunk.len() > max_bytes {
+            return Err(format!("image exceeds {} bytes", max_bytes));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// lowercase hex SHA-256 of `bytes`.
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Image hashes of recent tokens with the mint that first had each, bounded to `capacity`, forgetting the oldest first.
+///
+pub(crate) struct RecentImages {
+    first_mints: BoundedCache<String, String>,
+}
+
+impl RecentImages {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            first_mints: BoundedCache::new(CacheLimits::capacity(capacity)),
+        }
+    }
+
+    /// remembers `sha256` for `mint`, returning the other mint that had the same image first, if any.
+    pub fn record(&mut self, sha256: &str, mint: &str) -> Option<String> {
+        let now = Instant::now();
+        if let Some(first_mint) = self.first_mints.get(sha256, now) {
+            return (first_mint != mint).then(|| first_mint.clone());
+        }
+        self.first_mints.insert(sha256.to_string(), mint.to_string(), now);
+        None
+    }
+}
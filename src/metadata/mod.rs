@@ -0,0 +1,189 @@
+//! # Metadata Enrichment
+//!
+//! Optional lookup of the off-chain metadata JSON a token's `uri` points to, so events can carry the image and social links without every consumer fetching the document itself. Enrichment is disabled unless `METADATA_ENRICHMENT` is set; fetches are bounded in time and size, and any failure simply leaves the event without metadata.
+//!
+//! ## Gateways
+//!
+//! Most documents and images live on IPFS or Arweave and are linked as `ipfs://<cid>/<path>` or `ar://<tx id>/<path>`, which browsers can't load. Those are resolved to `https` links through the `IPFS_GATEWAY` (a public gateway by default) or `arweave.net`; the metadata document itself is fetched through the same link.
+//!
+//! ## Socials
+//!
+//! Social links are normalized and checked for red flags as the document is parsed; see the `socials` module. Whether a
+//! Twitter/X account was already linked by another token is checked against the last `SOCIAL_CACHE_CAPACITY` tokens
+//! with metadata, 10,000 by default.
+//!
+//! ## Images
+//!
+//! With `IMAGE_HASHING` on, the resolved image is also downloaded and hashed to spot tokens reusing another token's image; see the `images` module.
+
+use crate::cache::CacheStats;
+use crate::data_models::TokenMetadata;
+use crate::rpc_client::sanitize::clean;
+use serde_json::Value;
+use socials::{apply_socials, RecentSocials};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::debug;
+
+/// largest metadata document read; pump.fun documents are well under 2 KB.
+const MAX_METADATA_BYTES: usize = 64 * 1024;
+
+/// longest string kept from any metadata field, in characters.
+const MAX_FIELD_CHARS: usize = 512;
+
+/// gateway `ipfs://` links are resolved through unless `IPFS_GATEWAY` is set.
+pub const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+
+/// gateway `ar://` links are resolved through.
+const ARWEAVE_GATEWAY: &str = "https://arweave.net/";
+
+/// Fetches and parses token metadata documents.
+///
+#[derive(Debug, Clone)]
+pub struct MetadataFetcher {
+    client: reqwest::Client,
+    timeout: Duration,
+    /// base URL ending in `/` that a CID and path are appended to
+    ipfs_gateway: String,
+    recent_socials: Arc<Mutex<RecentSocials>>,
+}
+
+impl MetadataFetcher {
+    /// `timeout` bounds the whole fetch, including reading the body; `socials_capacity` Twitter/X accounts are
+    /// remembered to spot one reused by another token.
+    pub fn new(timeout: Duration, ipfs_gateway: &str, socials_capacity: usize) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            timeout,
+            ipfs_gateway: ipfs_gateway.to_string(),
+            recent_socials: Arc::new(Mutex::new(RecentSocials::new(socials_capacity))),
+        }
+    }
+
+    pub fn cache_stats(&self) -> Arc<CacheStats> {
+        self.recent_socials.lock().unwrap_or_else(|e| e.into_inner()).cache_stats()
+    }
+
+    /// Sets `socials_reused` on the metadata of `mint`, remembering its Twitter/X link for the tokens after it.
+    ///
+    /// Called once the mint is known to be new, so a duplicate doesn't count as reusing its own link.
+    pub fn flag_reused_socials(&self, metadata: &mut TokenMetadata, mint: &str) {
+        metadata.socials_reused = self.recent_socials.lock().unwrap_or_else(|e| e.into_inner()).record(metadata, mint);
+    }
+
+    /// Fetches the document behind `uri`, through a gateway for IPFS and Arweave links; returns `None` for URIs that
+    /// can't be resolved to HTTP and on any fetch or parse failure.
+    pub async fn fetch(&self, uri: &str) -> Option<TokenMetadata> {
+        let Some(url) = resolve_uri(uri, &self.ipfs_gateway) else {
+            debug!(uri, "Skipping metadata fetch for non-HTTP URI");
+            return None;
+        };
+        match tokio::time::timeout(self.timeout, self.fetch_document(&url)).await {
+            Ok(Ok(document)) => {
+                let mut metadata = parse_metadata(&document);
+                metadata.image_url = metadata.image.as_deref().and_then(|image| resolve_uri(image, &self.ipfs_gateway));
+                metadata.metadata_url = Some(url);
+                Some(metadata)
+            }
+            Ok(Err(e)) => {
+                debug!(uri, "Metadata fetch failed: {}", e);
+                None
+            }
+            Err(_) => {
+                debug!(uri, "Metadata fetch timed out after {:?}", self.timeout);
+                None
+            }
+        }
+    }
+
+    async fn fetch_document(&self, uri: &str) -> Result<Value, String> {
+        let mut response = self
+            .client
+            .get(uri)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?;
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+            if body.len() + chunk.len() > MAX_METADATA_BYTES {
+                return Err(format!("document exceeds {} bytes", MAX_METADATA_BYTES));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        serde_json::from_slice(&body).map_err(|e| e.to_string())
+    }
+}
+
+/// Extracts the fields events carry from a metadata document.
+///
+/// Social links are read from the top level, where pump.fun puts them, falling back to the `extensions` object other launchpads use,
+/// and normalized; a link that isn't a web link is left out.
+pub fn parse_metadata(document: &Value) -> TokenMetadata {
+    let field = |name: &str| {
+        [&document[name], &document["extensions"][name]]
+            .into_iter()
+            .filter_map(Value::as_str)
+            .map(|value| clean(value, MAX_FIELD_CHARS))
+            .find(|value| !value.is_empty())
+    };
+
+    let mut metadata = TokenMetadata {
+        description: field("description"),
+        image: field("image"),
+        twitter: field("twitter"),
+        telegram: field("telegram"),
+        website: field("website"),
+        ..TokenMetadata::default()
+    };
+    apply_socials(&mut metadata);
+    metadata
+}
+
+/// Resolves `uri` to a link a browser can load.
+///
+/// `ipfs://<cid>/<path>` (also written `ipfs://ipfs/<cid>/<path>`) goes through `ipfs_gateway`, which must end in `/`,
+/// and `ar://<tx id>/<path>` through arweave.net. HTTP links are returned as they are; anything else, including an
+/// IPFS link without a valid CIDv0 or CIDv1, is `None`.
+pub fn resolve_uri(uri: &str, ipfs_gateway: &str) -> Option<String> {
+    let uri = uri.trim();
+    let (scheme, rest) = uri.split_once("://")?;
+    match scheme.to_ascii_lowercase().as_str() {
+        "https" | "http" if !rest.is_empty() => Some(uri.to_string()),
+        "ipfs" => {
+            let rest = rest.strip_prefix("ipfs/").unwrap_or(rest);
+            let cid = rest.split(['/', '?', '#']).next()?;
+            is_cid(cid).then(|| format!("{}{}", ipfs_gateway, rest))
+        }
+        "ar" => {
+            let id = rest.split(['/', '?', '#']).next()?;
+            is_arweave_id(id).then(|| format!("{}{}", ARWEAVE_GATEWAY, rest))
+        }
+        _ => None,
+    }
+}
+
+/// a CIDv0 (base58 multihash starting `Qm`) or a base32 CIDv1 (`b` followed by lowercase base32, such as `bafy...`).
+fn is_cid(value: &str) -> bool {
+    let cid_v0 = value.len() == 46
+        && value.starts_with("Qm")
+        && value.chars().all(|c| c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l'));
+    let cid_v1 = value.len() > 8
+        && value.starts_with('b')
+        && value[1..].chars().all(|c| c.is_ascii_lowercase() || ('2'..='7').contains(&c));
+    cid_v0 || cid_v1
+}
+
+/// an Arweave transaction id: 43 characters of base64url.
+fn is_arweave_id(value: &str) -> bool {
+    value.len() == 43 && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+mod images;
+mod socials;
+
+pub use images::{ImageHasher, PendingImageHash};
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,149 @@
+//! # Socials
+//!
+//! The Twitter/X, Telegram and website links of a metadata document, cleaned up so consumers get a loadable `https`
+//! link without the tracking parameters share buttons add, and a few red flags scam launches tend to show: a Twitter
+//! link to an X community anyone can create instead of an account, no website, links pushed in the description, and a
+//! Twitter account another recent token already linked.
+
+use crate::cache::{BoundedCache, CacheLimits, CacheStats};
+use crate::data_models::TokenMetadata;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+use url::Url;
+
+/// query parameters that only record where a click came from.
+const TRACKING_PARAMS: &[&str] = &["fbclid", "gclid", "igshid", "mc_cid", "mc_eid", "ref", "ref_src", "ref_url", "si"];
+
+/// query parameters x.com adds to shared links, meaningless anywhere else.
+const TWITTER_SHARE_PARAMS: &[&str] = &["s", "t"];
+
+/// hosts serving Twitter/X profiles.
+const TWITTER_HOSTS: &[&str] = &["x.com", "twitter.com", "www.x.com", "www.twitter.com", "mobile.x.com", "mobile.twitter.com"];
+
+/// path prefix of X community links.
+const COMMUNITY_PATH: &str = "/i/communities/";
+
+/// `link` as an `https` link without tracking parameters; `None` when it isn't a web link.
+///
+/// A link pasted without a scheme gets `https://`. A bare `@handle` becomes a link under `handle_base` when one is
+/// given, e.g. `https://x.com/` for the `twitter` field.
+pub fn normalize_link(link: &str, handle_base: Option<&str>) -> Option<String> {
+    let link = link.trim();
+    if let (Some(handle), Some(handle_base)) = (link.strip_prefix('@'), handle_base) {
+        let valid = !handle.is_empty() && handle.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        return valid.then(|| format!("{}{}", handle_base, handle));
+    }
+    if link.is_empty() || link.chars().any(char::is_whitespace) {
+        return None;
+    }
+    let mut url = match link.split_once("://") {
+        Some(_) => Url::parse(link).ok()?,
+        None => Url::parse(&format!("https://{}", link.trim_start_matches('/'))).ok()?,
+    };
+    // credentials only turn up in links made to look like another site, e.g. `x.com@scam.example`
+    let credentials = !url.username().is_empty() || url.password().is_some();
+    if !matches!(url.scheme(), "https" | "http") || credentials || !url.host_str()?.contains('.') {
+        return None;
+    }
+
+    let twitter = is_twitter(&url);
+    let kept: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| {
+            let name = name.to_ascii_lowercase();
+            let tracking = name.starts_with("utm_") || TRACKING_PARAMS.contains(&name.as_str());
+            let share = twitter && TWITTER_SHARE_PARAMS.contains(&name.as_str());
+            !(tracking || share)
+        })
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    if kept.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(kept);
+    }
+
+    let mut normalized = url.to_string();
+    // `Url` adds a `/` to a bare host, which the link as pasted didn't have
+    if url.path() == "/" && url.query().is_none() && url.fragment().is_none() && !link.ends_with('/') {
+        normalized.pop();
+    }
+    Some(normalized)
+}
+
+/// whether `url` is on a Twitter/X host.
+fn is_twitter(url: &Url) -> bool {
+    url.host_str().is_some_and(|host| TWITTER_HOSTS.contains(&host))
+}
+
+/// the account or community a Twitter/X link points at, the same however the link was written; `None` for other links.
+fn twitter_key(link: &str) -> Option<String> {
+    let url = Url::parse(link).ok()?;
+    if !is_twitter(&url) {
+        return None;
+    }
+    let path = url.path().trim_end_matches('/').to_lowercase();
+    (!path.is_empty()).then_some(path)
+}
+
+/// whether `description` carries a link of its own.
+pub fn contains_url(description: &str) -> bool {
+    description.split_whitespace().any(|word| {
+        let word = word.to_ascii_lowercase();
+        word.contains("://") || word.starts_with("www.") || word.starts_with("t.me/")
+    })
+}
+
+/// Normalizes the social links of freshly parsed `metadata` and sets the flags that need nothing but the document.
+pub fn apply_socials(metadata: &mut TokenMetadata) {
+    metadata.twitter = metadata.twitter.as_deref().and_then(|link| normalize_link(link, Some("https://x.com/")));
+    metadata.telegram = metadata.telegram.as_deref().and_then(|link| normalize_link(link, Some("https://t.me/")));
+    metadata.website = metadata.website.as_deref().and_then(|link| normalize_link(link, None));
+
+    metadata.twitter_is_community_handle = metadata
+        .twitter
+        .as_deref()
+        .and_then(|link| Url::parse(link).ok())
+        .is_some_and(|url| is_twitter(&url) && url.path().starts_with(COMMUNITY_PATH));
+    metadata.website_missing = metadata.website.is_none();
+    metadata.description_contains_urls = metadata.description.as_deref().is_some_and(contains_url);
+}
+
+/// Twitter/X accounts linked by recent tokens with the mint that first linked each, bounded to `capacity`, forgetting
+/// the oldest first.
+///
+pub(crate) struct RecentSocials {
+    first_mints: BoundedCache<String, String>,
+}
+
+impl fmt::Debug for RecentSocials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecentSocials").field("accounts", &self.first_mints.len()).finish()
+    }
+}
+
+impl RecentSocials {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            first_mints: BoundedCache::new(CacheLimits::capacity(capacity)),
+        }
+    }
+
+    pub fn cache_stats(&self) -> Arc<CacheStats> {
+        self.first_mints.stats()
+    }
+
+    /// remembers the Twitter/X link of `metadata` for `mint`, returning whether another mint linked it first.
+    pub fn record(&mut self, metadata: &TokenMetadata, mint: &str) -> bool {
+        let Some(key) = metadata.twitter.as_deref().and_then(twitter_key) else {
+            return false;
+        };
+        let now = Instant::now();
+        if let Some(first_mint) = self.first_mints.get(&key, now) {
+            return first_mint != mint;
+        }
+        self.first_mints.insert(key, mint.to_string(), now);
+        false
+    }
+}
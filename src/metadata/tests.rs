@@ -0,0 +1,312 @@
+//! Unit tests for metadata document parsing, social link normalization and red flags, IPFS/Arweave link resolution and image hashing.
+
+
+use super::*;
+use images::{ImageHash, RecentImages};
+use socials::{contains_url, normalize_link};
+use crate::config::ImageHashConfig;
+use serde_json::json;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+#[test]
+fn test_parse_pump_fun_document() {
+    let document = json!({
+        "name": "Moon Cat",
+        "symbol": "MCAT",
+        "description": "the cat that went to the moon",
+        "image": "https://ipfs.io/ipfs/QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG",
+        "showName": true,
+        "createdOn": "https://pump.fun",
+        "twitter": "https://x.com/mooncat",
+        "telegram": "https://t.me/mooncat",
+        "website": "https://mooncat.xyz"
+    });
+
+    assert_eq!(
+        parse_metadata(&document),
+        TokenMetadata {
+            description: Some("the cat that went to the moon".to_string()),
+            image: Some("https://ipfs.io/ipfs/QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_string()),
+            twitter: Some("https://x.com/mooncat".to_string()),
+            telegram: Some("https://t.me/mooncat".to_string()),
+            website: Some("https://mooncat.xyz".to_string()),
+            ..TokenMetadata::default()
+        }
+    );
+}
+
+#[test]
+fn test_parse_reads_extensions() {
+    let document = json!({
+        "image": "ipfs://QmHash",
+        "twitter": "",
+        "extensions": { "twitter": "https://x.com/other" }
+    });
+    let metadata = parse_metadata(&document);
+
+    assert_eq!(metadata.image.as_deref(), Some("ipfs://QmHash"));
+    // an empty top-level value falls through to the extension
+    assert_eq!(metadata.twitter.as_deref(), Some("https://x.com/other"));
+}
+
+#[test]
+fn test_parse_ignores_missing_and_non_string_fields() {
+    let nothing = TokenMetadata { website_missing: true, ..TokenMetadata::default() };
+    let metadata = parse_metadata(&json!({ "image": 42, "twitter": null, "website": ["a"] }));
+    assert_eq!(metadata, nothing);
+
+    assert_eq!(parse_metadata(&json!("not an object")), nothing);
+}
+
+#[test]
+fn test_parse_bounds_field_length() {
+    let metadata = parse_metadata(&json!({ "description": "x".repeat(10_000) }));
+    assert_eq!(metadata.description.unwrap().chars().count(), MAX_FIELD_CHARS);
+}
+
+/// function to parse a captured metadata document.
+fn captured(document: &str) -> TokenMetadata {
+    parse_metadata(&serde_json::from_str(document).expect("fixture is not JSON"))
+}
+
+#[test]
+fn test_shared_links_lose_their_tracking_parameters() {
+    let metadata = captured(include_str!("fixtures/pump_fun_shared_links.json"));
+    assert_eq!(metadata.twitter.as_deref(), Some("https://x.com/pepeclassic"));
+    assert_eq!(metadata.telegram.as_deref(), Some("https://t.me/pepeclassic"));
+    // parameters the site itself reads stay
+    assert_eq!(metadata.website.as_deref(), Some("https://pepeclassic.example/?lang=en"));
+    assert!(!metadata.twitter_is_community_handle && !metadata.website_missing && !metadata.description_contains_urls);
+}
+
+#[test]
+fn test_scam_patterns_are_flagged() {
+    let metadata = captured(include_str!("fixtures/community_scam.json"));
+    assert_eq!(metadata.twitter.as_deref(), Some("https://x.com/i/communities/1869704532156198912"));
+    assert!(metadata.twitter_is_community_handle);
+    assert!(metadata.website_missing);
+    assert!(metadata.description_contains_urls);
+}
+
+#[test]
+fn test_bare_handles_and_hosts_become_links() {
+    let metadata = captured(include_str!("fixtures/bare_handles.json"));
+    assert_eq!(metadata.twitter.as_deref(), Some("https://x.com/MoonCatSol"));
+    assert_eq!(metadata.telegram.as_deref(), Some("https://t.me/mooncat_portal"));
+    assert_eq!(metadata.website.as_deref(), Some("https://www.mooncat.example"));
+    assert!(!metadata.description_contains_urls);
+}
+
+#[test]
+fn test_malformed_socials_are_left_out() {
+    let metadata = captured(include_str!("fixtures/malformed_socials.json"));
+    assert_eq!((metadata.twitter, metadata.telegram, metadata.website, metadata.description), (None, None, None, None));
+    assert!(metadata.website_missing);
+
+    for link in ["", "@", "@not a handle", "mailto:dev@example.com", "ftp://example.com", "https://localhost", "not a link"] {
+        assert_eq!(normalize_link(link, Some("https://x.com/")), None, "{}", link);
+    }
+    // a website has no handles
+    assert_eq!(normalize_link("@pepe", None), None);
+    assert_eq!(normalize_link("http://pepe.example/#roadmap", None).as_deref(), Some("http://pepe.example/#roadmap"));
+    // share parameters only mean tracking on x.com
+    assert_eq!(normalize_link("https://pepe.example/?s=1", None).as_deref(), Some("https://pepe.example/?s=1"));
+
+    assert!(contains_url("buy at PEPE.example via https://t.co/abc"));
+    assert!(contains_url("join t.me/pepe"));
+    assert!(!contains_url("the frog. not a link"));
+}
+
+#[test]
+fn test_reused_twitter_accounts_are_flagged() {
+    let fetcher = MetadataFetcher::new(Duration::from_secs(1), DEFAULT_IPFS_GATEWAY, 100);
+    let mut first = captured(include_str!("fixtures/bare_handles.json"));
+    fetcher.flag_reused_socials(&mut first, "mint1");
+    assert!(!first.socials_reused);
+    // the same token seen again doesn't reuse its own link
+    fetcher.flag_reused_socials(&mut first, "mint1");
+    assert!(!first.socials_reused);
+
+    // the same account however it's written
+    let mut copy = parse_metadata(&json!({ "twitter": "https://mobile.twitter.com/mooncatsol/" }));
+    fetcher.flag_reused_socials(&mut copy, "mint2");
+    assert!(copy.socials_reused);
+
+    let mut other = captured(include_str!("fixtures/pump_fun_shared_links.json"));
+    fetcher.flag_reused_socials(&mut other, "mint3");
+    assert!(!other.socials_reused);
+    let mut without = parse_metadata(&json!({ "website": "https://pepe.example" }));
+    fetcher.flag_reused_socials(&mut without, "mint4");
+    assert!(!without.socials_reused);
+}
+
+#[tokio::test]
+async fn test_malformed_document_leaves_no_metadata() {
+    let url = serve_image(b"{\"name\": \"Broken\", \"twitter\": ".to_vec()).await;
+    let fetcher = MetadataFetcher::new(Duration::from_secs(5), DEFAULT_IPFS_GATEWAY, 100);
+    assert_eq!(fetcher.fetch(&url).await, None);
+
+    let url = serve_image(include_bytes!("fixtures/community_scam.json").to_vec()).await;
+    let metadata = fetcher.fetch(&url).await.unwrap();
+    assert!(metadata.twitter_is_community_handle);
+    assert_eq!(metadata.metadata_url, Some(url));
+}
+
+const CID_V0: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const CID_V1: &str = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+
+#[test]
+fn test_resolve_ipfs_cids_through_the_gateway() {
+    assert_eq!(
+        resolve_uri(&format!("ipfs://{}", CID_V0), DEFAULT_IPFS_GATEWAY),
+        Some(format!("https://ipfs.io/ipfs/{}", CID_V0))
+    );
+    assert_eq!(
+        resolve_uri(&format!("ipfs://{}", CID_V1), "https://gateway.pinata.cloud/ipfs/"),
+        Some(format!("https://gateway.pinata.cloud/ipfs/{}", CID_V1))
+    );
+    // the older form repeats `ipfs/` after the scheme
+    assert_eq!(
+        resolve_uri(&format!("ipfs://ipfs/{}", CID_V0), DEFAULT_IPFS_GATEWAY),
+        Some(format!("https://ipfs.io/ipfs/{}", CID_V0))
+    );
+}
+
+#[test]
+fn test_resolve_keeps_nested_paths() {
+    assert_eq!(
+        resolve_uri(&format!(" ipfs://{}/images/cat.png ", CID_V1), DEFAULT_IPFS_GATEWAY),
+        Some(format!("https://ipfs.io/ipfs/{}/images/cat.png", CID_V1))
+    );
+    assert_eq!(
+        resolve_uri("ar://bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_U/metadata.json", DEFAULT_IPFS_GATEWAY),
+        Some("https://arweave.net/bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_U/metadata.json".to_string())
+    );
+}
+
+#[test]
+fn test_resolve_leaves_http_links_alone() {
+    let gateway_link = format!("https://cf-ipfs.com/ipfs/{}", CID_V0);
+    assert_eq!(resolve_uri(&gateway_link, DEFAULT_IPFS_GATEWAY), Some(gateway_link));
+    assert_eq!(
+        resolve_uri("http://example.com/cat.png", DEFAULT_IPFS_GATEWAY),
+        Some("http://example.com/cat.png".to_string())
+    );
+}
+
+#[test]
+fn test_resolve_rejects_unusable_links() {
+    for uri in [
+        "",
+        "https://",
+        "ipfs://",
+        "ipfs://QmHash",
+        "ipfs://not-a-cid/cat.png",
+        "ar://short",
+        "data:image/png;base64,AAAA",
+        "ftp://example.com/cat.png",
+        CID_V0,
+    ] {
+        assert_eq!(resolve_uri(uri, DEFAULT_IPFS_GATEWAY), None, "{}", uri);
+    }
+}
+
+/// function to serve `body` to every request on a local port, returning the URL of the image.
+async fn serve_image(body: Vec<u8>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let body = body.clone();
+            tokio::spawn(async move {
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).await;
+                let head = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n", body.len());
+                let _ = stream.write_all(head.as_bytes()).await;
+                let _ = stream.write_all(&body).await;
+            });
+        }
+    });
+    format!("http://{}/image.png", addr)
+}
+
+/// function to build image hashing settings that wait long enough for a local download.
+fn image_hash_config(max_bytes: usize) -> ImageHashConfig {
+    ImageHashConfig {
+        max_bytes,
+        fetch_timeout: Duration::from_secs(5),
+        wait: Duration::from_secs(5),
+        concurrency: 2,
+        capacity: 16,
+        follow_ups: false,
+    }
+}
+
+#[test]
+fn test_recent_images_report_the_first_mint() {
+    let mut recent = RecentImages::new(16);
+    assert_eq!(recent.record("aaaa", "mint1"), None);
+    assert_eq!(recent.record("aaaa", "mint2"), Some("mint1".to_string()));
+    assert_eq!(recent.record("aaaa", "mint3"), Some("mint1".to_string()));
+    // the same token hashed twice is not a copy of itself
+    assert_eq!(recent.record("aaaa", "mint1"), None);
+    assert_eq!(recent.record("bbbb", "mint4"), None);
+}
+
+#[test]
+fn test_recent_images_forget_the_oldest() {
+    let mut recent = RecentImages::new(2);
+    recent.record("aaaa", "mint1");
+    recent.record("bbbb", "mint2");
+    recent.record("cccc", "mint3");
+    assert_eq!(recent.record("aaaa", "mint4"), None);
+    assert_eq!(recent.record("cccc", "mint5"), Some("mint3".to_string()));
+}
+
+#[test]
+fn test_image_hash_applies_to_metadata() {
+    let mut metadata = TokenMetadata::default();
+    ImageHash { sha256: "ab".repeat(32), first_seen_mint: Some("mint1".to_string()) }.apply(&mut metadata);
+    assert_eq!(metadata.image_sha256, Some("ab".repeat(32)));
+    assert_eq!(metadata.image_seen_before, Some(true));
+    assert_eq!(metadata.image_first_seen_mint.as_deref(), Some("mint1"));
+
+    ImageHash { sha256: "cd".repeat(32), first_seen_mint: None }.apply(&mut metadata);
+    assert_eq!(metadata.image_seen_before, Some(false));
+    assert_eq!(metadata.image_first_seen_mint, None);
+}
+
+#[tokio::test]
+async fn test_image_hasher_spots_a_reused_image() {
+    let url = serve_image(b"not really a png".to_vec()).await;
+    let hasher = ImageHasher::new(&image_hash_config(1024));
+
+    let first = hasher.start(&url, "mint1").unwrap().ready().await.ok().flatten().unwrap();
+    assert_eq!(first.sha256, images::sha256_hex(b"not really a png"));
+    assert_eq!(first.first_seen_mint, None);
+
+    let copy = hasher.start(&url, "mint2").unwrap().finish().await.unwrap();
+    assert_eq!(copy.sha256, first.sha256);
+    assert_eq!(copy.first_seen_mint.as_deref(), Some("mint1"));
+}
+
+#[tokio::test]
+async fn test_image_hasher_skips_images_over_the_size_cap() {
+    let url = serve_image(vec![0u8; 4096]).await;
+    let hasher = ImageHasher::new(&image_hash_config(1024));
+    assert_eq!(hasher.start(&url, "mint1").unwrap().finish().await, None);
+}
+
+#[tokio::test]
+async fn test_image_hasher_declines_when_the_pool_is_full() {
+    // nothing answers on this listener, so the downloads hold their permits until the timeout
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/image.png", listener.local_addr().unwrap());
+    let hasher = ImageHasher::new(&image_hash_config(1024));
+
+    let pending: Vec<_> = (0..2).map(|i| hasher.start(&url, &format!("mint{}", i))).collect();
+    assert!(pending.iter().all(Option::is_some));
+    assert!(hasher.start(&url, "mint3").is_none());
+}
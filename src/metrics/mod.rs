@@ -0,0 +1,607 @@
+//! # Metrics
+//!
+//! Process-wide counters and gauges, rendered in the Prometheus text exposition format on the health server's `/metrics` endpoint. Everything is a plain atomic so hot paths only pay for a relaxed increment.
+
+use crate::cache::CacheStats;
+use crate::dispatch::{DispatchLatency, DurableQueueStats, ImmediateStats};
+use crate::sinks::SinkStats;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// shared metric values, one instance per process.
+///
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// currently connected WebSocket clients
+    pub ws_clients: AtomicUsize,
+    /// WebSocket connections refused because `WS_MAX_CLIENTS` was reached
+    pub ws_connections_rejected: AtomicU64,
+    /// connections dropped by the per-IP connection cap or rate limit
+    pub ws_connections_ip_limited: AtomicU64,
+    /// WebSocket handshakes refused for their `Origin` header
+    pub ws_connections_origin_rejected: AtomicU64,
+    /// failed `accept` calls the WebSocket listener recovered from
+    pub ws_accept_errors: AtomicU64,
+    /// times the WebSocket server was restarted after failing or panicking
+    pub ws_server_restarts: AtomicU64,
+    /// times the Solana monitor (or simulator) was restarted after failing or panicking
+    pub monitor_restarts: AtomicU64,
+    /// token creations dropped because their mint was already broadcast
+    pub mint_dedupe_hits: AtomicU64,
+    /// token creations whose mint was seen for the first time
+    pub mint_dedupe_misses: AtomicU64,
+    /// signatures waiting in the processor queue, as of the last one taken off it
+    pub processor_queue_depth: AtomicUsize,
+    /// signatures waiting in the overflow buffer for room in the processor queue
+    pub processor_overflow_depth: AtomicUsize,
+    /// signatures dropped because the processor queue and its overflow buffer were both full
+    pub processor_signatures_dropped: AtomicU64,
+    /// signatures waiting to fetch a transaction the RPC didn't have yet
+    pub fetch_retries_waiting: AtomicUsize,
+    /// transactions still unavailable when their retries ran out
+    pub transactions_missed: AtomicU64,
+    /// transaction fetches cut off by `PROCESSING_TIMEOUT_SECS`, each sent back to the retry queue
+    pub processing_timeouts: AtomicU64,
+    /// mints currently remembered for dedupe
+    pub mint_dedupe_entries: AtomicUsize,
+    /// token creations dropped by `SPAM_SUPPRESSION=drop` as copycats of a recent token
+    pub spam_suppressed: AtomicU64,
+    /// token creations older than `MAX_EVENT_AGE_SECS` when dispatched
+    pub stale_events: AtomicU64,
+    /// stale events the WebSocket server didn't deliver under `WS_STALE_EVENTS=drop`
+    pub ws_stale_dropped: AtomicU64,
+    /// matching events not delivered to a client because of the rate limit it set
+    pub ws_rate_limited: AtomicU64,
+    /// images not hashed because `IMAGE_DOWNLOAD_CONCURRENCY` downloads were already running
+    pub image_downloads_skipped: AtomicU64,
+    /// tokens broadcast before their image hash was ready
+    pub image_hashes_late: AtomicU64,
+    /// bonding curves the curve tracker currently follows
+    pub curve_subscriptions: AtomicUsize,
+    /// `curveUpdate` messages published by the curve tracker
+    pub curve_updates: AtomicU64,
+    /// migrations of completed curves into an AMM pool, with `PUMP_AMM_PROGRAM_ID` set
+    pub migrations: AtomicU64,
+    /// `diagnostic` messages published for admins
+    pub diagnostics_sent: AtomicU64,
+    /// diagnostics not published because they exceeded `DIAGNOSTICS_MAX_PER_SEC`
+    pub diagnostics_dropped: AtomicU64,
+    /// milliseconds from a creation's log notification to its broadcast
+    pub processing_latency: LatencyHistogram,
+    /// milliseconds from a creation's block time to its log notification
+    pub chain_latency: LatencyHistogram,
+    /// `tokenCreatedPreview` messages published with `FAST_PATH` on
+    pub previews_sent: AtomicU64,
+    /// milliseconds a creation's preview went out ahead of its broadcast
+    pub preview_lead: LatencyHistogram,
+    /// delivery counters of every running event sink
+    sinks: Mutex<Vec<Arc<SinkStats>>>,
+    /// counters of every durable queue of the dispatcher
+    durable_queues: Mutex<Vec<Arc<DurableQueueStats>>>,
+    /// counters of every immediate subscriber of the dispatcher
+    immediate_subscribers: Mutex<Vec<Arc<ImmediateStats>>>,
+    /// how long the dispatcher takes to reach each tier, once it exists
+    dispatch_latency: Mutex<Option<Arc<DispatchLatency>>>,
+    /// failed transaction processing attempts by `MonitorError::code` and RPC endpoint, empty when no call failed
+    errors: Mutex<BTreeMap<(&'static str, String), u64>>,
+    /// transactions written to the dead-letter directory by `MonitorError::code`
+    dead_letters: Mutex<BTreeMap<&'static str, u64>>,
+    /// token creations the global filter kept from being dispatched, by the rule that rejected them
+    global_filter_suppressed: Mutex<BTreeMap<&'static str, u64>>,
+    /// signatures each WebSocket RPC endpoint delivered before any other, by endpoint label
+    first_deliveries: Mutex<BTreeMap<String, u64>>,
+    /// milliseconds from queueing a frame for a WebSocket client to writing it, by client name or address
+    ws_send_latency: Mutex<BTreeMap<String, Arc<LatencyHistogram>>>,
+    /// size and evictions of every in-memory cache, by cache name
+    caches: Mutex<BTreeMap<&'static str, Arc<CacheStats>>>,
+}
+
+impl Metrics {
+    /// exports a sink's counters, labelled with its name.
+    pub fn add_sink(&self, stats: Arc<SinkStats>) {
+        self.sinks.lock().unwrap_or_else(|e| e.into_inner()).push(stats);
+    }
+
+    /// exports a durable queue's counters, labelled with its name.
+    pub fn add_durable_queue(&self, stats: Arc<DurableQueueStats>) {
+        self.durable_queues.lock().unwrap_or_else(|e| e.into_inner()).push(stats);
+    }
+
+    /// exports an immediate subscriber's counters, labelled with its name.
+    pub fn add_immediate_subscriber(&self, stats: Arc<ImmediateStats>) {
+        self.immediate_subscribers.lock().unwrap_or_else(|e| e.into_inner()).push(stats);
+    }
+
+    /// exports the dispatcher's latency per tier.
+    pub fn set_dispatch_latency(&self, latency: Arc<DispatchLatency>) {
+        *self.dispatch_latency.lock().unwrap_or_else(|e| e.into_inner()) = Some(latency);
+    }
+
+    /// counts a failed processing attempt under its error code and the RPC endpoint of the call that failed, if any.
+    pub fn record_error(&self, code: &'static str, endpoint: Option<&str>) {
+        let key = (code, endpoint.unwrap_or_default().to_string());
+        *self.errors.lock().unwrap_or_else(|e| e.into_inner()).entry(key).or_default() += 1;
+    }
+
+    /// counts a transaction written to the dead-letter directory under the code of its error.
+    pub fn record_dead_letter(&self, code: &'static str) {
+        *self.dead_letters.lock().unwrap_or_else(|e| e.into_inner()).entry(code).or_default() += 1;
+    }
+
+    /// counts a token creation the global filter suppressed under the rule that rejected it.
+    pub fn record_global_filter_suppressed(&self, rule: &'static str) {
+        *self.global_filter_suppressed.lock().unwrap_or_else(|e| e.into_inner()).entry(rule).or_default() += 1;
+    }
+
+    /// exports an endpoint's first delivery count, starting from zero until it first wins.
+    pub fn add_rpc_endpoint(&self, label: &str) {
+        self.first_deliveries.lock().unwrap_or_else(|e| e.into_inner()).entry(label.to_string()).or_default();
+    }
+
+    /// counts a signature `label` delivered before every other endpoint.
+    pub fn record_first_delivery(&self, label: &str) {
+        let mut first_deliveries = self.first_deliveries.lock().unwrap_or_else(|e| e.into_inner());
+        match first_deliveries.get_mut(label) {
+            Some(count) => *count += 1,
+            None => {
+                first_deliveries.insert(label.to_string(), 1);
+            }
+        }
+    }
+
+    /// the send latency histogram of the WebSocket clients labelled `label`, created on first use.
+    pub fn ws_send_latency(&self, label: &str) -> Arc<LatencyHistogram> {
+        let mut histograms = self.ws_send_latency.lock().unwrap_or_else(|e| e.into_inner());
+        Arc::clone(histograms.entry(label.to_string()).or_default())
+    }
+
+    /// stops exporting the send latency histogram labelled `label`.
+    pub fn remove_ws_send_latency(&self, label: &str) {
+        self.ws_send_latency.lock().unwrap_or_else(|e| e.into_inner()).remove(label);
+    }
+
+    /// exports a cache's size and evictions under `name`, replacing the cache registered under it before.
+    pub fn register_cache(&self, name: &'static str, stats: Arc<CacheStats>) {
+        self.caches.lock().unwrap_or_else(|e| e.into_inner()).insert(name, stats);
+    }
+
+    /// every registered cache, by name.
+    pub fn caches(&self) -> Vec<(&'static str, Arc<CacheStats>)> {
+        let caches = self.caches.lock().unwrap_or_else(|e| e.into_inner());
+        caches.iter().map(|(name, stats)| (*name, Arc::clone(stats))).collect()
+    }
+
+    /// Renders every metric in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_ws_clients",
+            "gauge",
+            "Currently connected WebSocket clients",
+            self.ws_clients.load(Ordering::Relaxed) as u64,
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_ws_connections_rejected_total",
+            "counter",
+            "WebSocket connections rejected because the client limit was reached",
+            self.ws_connections_rejected.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_ws_connections_ip_limited_total",
+            "counter",
+            "Connections dropped by the per-IP connection cap or rate limit",
+            self.ws_connections_ip_limited.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_ws_connections_origin_rejected_total",
+            "counter",
+            "WebSocket handshakes refused because their Origin isn't in WS_ALLOWED_ORIGINS, or is missing",
+            self.ws_connections_origin_rejected.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_ws_accept_errors_total",
+            "counter",
+            "Failed accepts the WebSocket listener retried",
+            self.ws_accept_errors.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_ws_server_restarts_total",
+            "counter",
+            "WebSocket server restarts after it failed or panicked",
+            self.ws_server_restarts.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_monitor_restarts_total",
+            "counter",
+            "Solana monitor restarts after it failed or panicked",
+            self.monitor_restarts.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_mint_dedupe_hits_total",
+            "counter",
+            "Token creations dropped because their mint was already broadcast",
+            self.mint_dedupe_hits.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_mint_dedupe_misses_total",
+            "counter",
+            "Token creations whose mint was seen for the first time",
+            self.mint_dedupe_misses.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_processor_queue_depth",
+            "gauge",
+            "Signatures waiting to be fetched and parsed",
+            self.processor_queue_depth.load(Ordering::Relaxed) as u64,
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_processor_overflow_depth",
+            "gauge",
+            "Signatures waiting for room in the processor queue",
+            self.processor_overflow_depth.load(Ordering::Relaxed) as u64,
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_processor_signatures_dropped_total",
+            "counter",
+            "Signatures dropped because the processor queue and its overflow buffer were full",
+            self.processor_signatures_dropped.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_fetch_retries_waiting",
+            "gauge",
+            "Signatures waiting to fetch a transaction the RPC didn't have yet",
+            self.fetch_retries_waiting.load(Ordering::Relaxed) as u64,
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_transactions_missed_total",
+            "counter",
+            "Transactions still unavailable from the RPC when their retries ran out",
+            self.transactions_missed.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_processing_timeouts_total",
+            "counter",
+            "Transaction fetches that took longer than the processing timeout",
+            self.processing_timeouts.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_mint_dedupe_entries",
+            "gauge",
+            "Mints currently remembered for dedupe",
+            self.mint_dedupe_entries.load(Ordering::Relaxed) as u64,
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_spam_suppressed_total",
+            "counter",
+            "Token creations dropped as copycats of a recently broadcast token",
+            self.spam_suppressed.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_stale_events_total",
+            "counter",
+            "Token creations older than the maximum event age when dispatched",
+            self.stale_events.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_ws_stale_dropped_total",
+            "counter",
+            "Stale token creations the WebSocket server did not deliver",
+            self.ws_stale_dropped.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_ws_rate_limited_total",
+            "counter",
+            "Matching token events held back by a client's rate limit",
+            self.ws_rate_limited.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_image_downloads_skipped_total",
+            "counter",
+            "Token images not hashed because the download pool was full",
+            self.image_downloads_skipped.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_image_hashes_late_total",
+            "counter",
+            "Tokens broadcast before their image hash was ready",
+            self.image_hashes_late.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_curve_subscriptions",
+            "gauge",
+            "Bonding curves followed for curve updates",
+            self.curve_subscriptions.load(Ordering::Relaxed) as u64,
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_curve_updates_total",
+            "counter",
+            "Curve updates published for tracked bonding curves",
+            self.curve_updates.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_migrations_total",
+            "counter",
+            "Completed bonding curves migrated into an AMM pool",
+            self.migrations.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_diagnostics_sent_total",
+            "counter",
+            "Diagnostics about skipped and failed transactions published for admins",
+            self.diagnostics_sent.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_diagnostics_dropped_total",
+            "counter",
+            "Diagnostics not published because they exceeded DIAGNOSTICS_MAX_PER_SEC",
+            self.diagnostics_dropped.load(Ordering::Relaxed),
+        );
+        write_metric(
+            &mut out,
+            "pump_fun_monitor_previews_sent_total",
+            "counter",
+            "Token creation previews published from log notifications",
+            self.previews_sent.load(Ordering::Relaxed),
+        );
+
+        self.processing_latency.render(
+            &mut out,
+            "pump_fun_monitor_processing_latency_ms",
+            "Milliseconds from a token creation's log notification to its broadcast",
+        );
+        self.chain_latency.render(
+            &mut out,
+            "pump_fun_monitor_chain_latency_ms",
+            "Milliseconds from a token creation's block time to its log notification",
+        );
+        self.preview_lead.render(
+            &mut out,
+            "pump_fun_monitor_preview_lead_ms",
+            "Milliseconds a token creation's preview went out ahead of its broadcast",
+        );
+
+        let errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+        if !errors.is_empty() {
+            let name = "pump_fun_monitor_processing_errors_total";
+            let help = "Failed transaction processing attempts by error code and RPC endpoint";
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for ((code, endpoint), count) in errors.iter() {
+                let _ = writeln!(out, "{}{{code=\"{}\",endpoint=\"{}\"}} {}", name, code, endpoint, count);
+            }
+        }
+        drop(errors);
+
+        let dead_letters = self.dead_letters.lock().unwrap_or_else(|e| e.into_inner());
+        if !dead_letters.is_empty() {
+            let name = "pump_fun_monitor_dead_letters_total";
+            let _ = writeln!(out, "# HELP {} Failed transactions written to the dead-letter directory by error code", name);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for (code, count) in dead_letters.iter() {
+                let _ = writeln!(out, "{}{{code=\"{}\"}} {}", name, code, count);
+            }
+        }
+        drop(dead_letters);
+
+        let global_filter_suppressed = self.global_filter_suppressed.lock().unwrap_or_else(|e| e.into_inner());
+        if !global_filter_suppressed.is_empty() {
+            let name = "pump_fun_monitor_global_filter_suppressed_total";
+            let _ = writeln!(out, "# HELP {} Token creations the global filter kept from being dispatched, by rule", name);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for (rule, count) in global_filter_suppressed.iter() {
+                let _ = writeln!(out, "{}{{rule=\"{}\"}} {}", name, rule, count);
+            }
+        }
+        drop(global_filter_suppressed);
+
+        let first_deliveries = self.first_deliveries.lock().unwrap_or_else(|e| e.into_inner());
+        if !first_deliveries.is_empty() {
+            let name = "pump_fun_monitor_rpc_first_deliveries_total";
+            let _ = writeln!(out, "# HELP {} Signatures each WebSocket RPC endpoint delivered before any other", name);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for (label, count) in first_deliveries.iter() {
+                let _ = writeln!(out, "{}{{endpoint=\"{}\"}} {}", name, label_value(label), count);
+            }
+        }
+        drop(first_deliveries);
+
+        let ws_send_latency = self.ws_send_latency.lock().unwrap_or_else(|e| e.into_inner());
+        if !ws_send_latency.is_empty() {
+            let name = "pump_fun_monitor_ws_send_latency_ms";
+            let _ = writeln!(out, "# HELP {} Milliseconds from queueing a frame for a WebSocket client to writing it", name);
+            let _ = writeln!(out, "# TYPE {} histogram", name);
+            for (label, histogram) in ws_send_latency.iter() {
+                histogram.render_series(&mut out, name, &format!("client=\"{}\",", label_value(label)));
+            }
+        }
+        drop(ws_send_latency);
+
+        let caches = self.caches();
+        if !caches.is_empty() {
+            let name = "pump_fun_monitor_cache_entries";
+            let _ = writeln!(out, "# HELP {} Entries held by each in-memory cache", name);
+            let _ = writeln!(out, "# TYPE {} gauge", name);
+            for (cache, stats) in caches.iter() {
+                let _ = writeln!(out, "{}{{cache=\"{}\"}} {}", name, cache, stats.entries());
+            }
+            let name = "pump_fun_monitor_cache_evictions_total";
+            let _ = writeln!(out, "# HELP {} Entries each in-memory cache dropped, for room or because their TTL was up", name);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            for (cache, stats) in caches.iter() {
+                let _ = writeln!(out, "{}{{cache=\"{}\",reason=\"capacity\"}} {}", name, cache, stats.evicted());
+                let _ = writeln!(out, "{}{{cache=\"{}\",reason=\"ttl\"}} {}", name, cache, stats.expired());
+            }
+        }
+
+        let sinks = self.sinks.lock().unwrap_or_else(|e| e.into_inner());
+        if !sinks.is_empty() {
+            let counters: [Counter<SinkStats>; 5] = [
+                ("delivered", "Events each sink delivered", SinkStats::delivered),
+                ("failed", "Events each sink gave up on after its last retry", SinkStats::failed),
+                ("retried", "Deliveries each sink attempted again after a failure", SinkStats::retried),
+                ("dropped", "Events never handed to a sink because its queue was full", SinkStats::dropped),
+                ("stale_dropped", "Stale events never handed to a sink whose stale policy is drop", SinkStats::stale_dropped),
+            ];
+            for (counter, help, value) in counters {
+                let name = format!("pump_fun_monitor_sink_{}_total", counter);
+                let _ = writeln!(out, "# HELP {} {}", name, help);
+                let _ = writeln!(out, "# TYPE {} counter", name);
+                for stats in sinks.iter() {
+                    let _ = writeln!(out, "{}{{sink=\"{}\"}} {}", name, label_value(stats.name()), value(stats));
+                }
+            }
+        }
+        drop(sinks);
+
+        let durable_queues = self.durable_queues.lock().unwrap_or_else(|e| e.into_inner());
+        if !durable_queues.is_empty() {
+            let counters: [Counter<DurableQueueStats>; 2] = [
+                ("waits", "Events that found a durable queue full and waited for room", DurableQueueStats::waits),
+                ("dropped", "Events a full durable queue dropped under DURABLE_QUEUE_OVERFLOW=drop", DurableQueueStats::dropped),
+            ];
+            for (counter, help, value) in counters {
+                let name = format!("pump_fun_monitor_durable_queue_{}_total", counter);
+                let _ = writeln!(out, "# HELP {} {}", name, help);
+                let _ = writeln!(out, "# TYPE {} counter", name);
+                for stats in durable_queues.iter() {
+                    let _ = writeln!(out, "{}{{queue=\"{}\"}} {}", name, label_value(stats.name()), value(stats));
+                }
+            }
+        }
+        drop(durable_queues);
+
+        let immediate_subscribers = self.immediate_subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        if !immediate_subscribers.is_empty() {
+            let counters: [Counter<ImmediateStats>; 2] = [
+                ("dropped", "Events an immediate subscriber didn't take because its queue was full", ImmediateStats::dropped),
+                ("slow_enqueues", "Enqueues of an immediate subscriber that took longer than IMMEDIATE_SLOW_ENQUEUE_MS", ImmediateStats::slow),
+            ];
+            for (counter, help, value) in counters {
+                let name = format!("pump_fun_monitor_immediate_{}_total", counter);
+                let _ = writeln!(out, "# HELP {} {}", name, help);
+                let _ = writeln!(out, "# TYPE {} counter", name);
+                for stats in immediate_subscribers.iter() {
+                    let _ = writeln!(out, "{}{{subscriber=\"{}\"}} {}", name, label_value(stats.name()), value(stats));
+                }
+            }
+            let name = "pump_fun_monitor_immediate_demoted";
+            let _ = writeln!(out, "# HELP {} Whether an immediate subscriber was moved to the durable tier for slow enqueues", name);
+            let _ = writeln!(out, "# TYPE {} gauge", name);
+            for stats in immediate_subscribers.iter() {
+                let _ = writeln!(out, "{}{{subscriber=\"{}\"}} {}", name, label_value(stats.name()), stats.demoted() as u8);
+            }
+        }
+        drop(immediate_subscribers);
+
+        if let Some(latency) = self.dispatch_latency.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            let name = "pump_fun_monitor_dispatch_latency_us";
+            let _ = writeln!(out, "# HELP {} Microseconds from the start of a dispatch until a tier had the event", name);
+            let _ = writeln!(out, "# TYPE {} summary", name);
+            for (tier, latency) in [("0", &latency.immediate), ("1", &latency.durable)] {
+                let _ = writeln!(out, "{}_sum{{tier=\"{}\"}} {}", name, tier, latency.total_micros());
+                let _ = writeln!(out, "{}_count{{tier=\"{}\"}} {}", name, tier, latency.count());
+            }
+            let name = "pump_fun_monitor_dispatch_latency_max_us";
+            let _ = writeln!(out, "# HELP {} Slowest dispatch until a tier had the event, in microseconds", name);
+            let _ = writeln!(out, "# TYPE {} gauge", name);
+            for (tier, latency) in [("0", &latency.immediate), ("1", &latency.durable)] {
+                let _ = writeln!(out, "{}{{tier=\"{}\"}} {}", name, tier, latency.max().as_micros());
+            }
+        }
+        out
+    }
+}
+
+/// a per-stats counter family: its name suffix, help text and how to read it.
+type Counter<T> = (&'static str, &'static str, fn(&T) -> u64);
+
+/// upper bounds, in milliseconds, of the latency histogram buckets.
+const LATENCY_BUCKETS_MS: [u64; 9] = [50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000];
+
+/// Prometheus histogram of millisecond latencies over fixed buckets.
+///
+#[derive(Debug, Default)]
+pub struct LatencyHistogram {
+    /// observations per bucket, not cumulative; the last slot counts those above every bound
+    buckets: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+    sum: AtomicU64,
+    count: AtomicU64,
+}
+
+impl LatencyHistogram {
+    /// records one latency.
+    pub fn observe(&self, ms: u64) {
+        let bucket = LATENCY_BUCKETS_MS.iter().position(|&bound| ms <= bound).unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(ms, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// writes the cumulative `_bucket` series, `_sum` and `_count`.
+    fn render(&self, out: &mut String, name: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        self.render_series(out, name, "");
+    }
+
+    /// writes the series of one histogram among several, `labels` being `key="value",` pairs to tell it apart.
+    fn render_series(&self, out: &mut String, name: &str, labels: &str) {
+        let mut cumulative = 0;
+        for (index, bucket) in self.buckets.iter().enumerate() {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let le = LATENCY_BUCKETS_MS.get(index).map_or_else(|| "+Inf".to_string(), u64::to_string);
+            let _ = writeln!(out, "{}_bucket{{{}le=\"{}\"}} {}", name, labels, le, cumulative);
+        }
+        let labels = labels.trim_end_matches(',');
+        if labels.is_empty() {
+            let _ = writeln!(out, "{}_sum {}", name, self.sum.load(Ordering::Relaxed));
+            let _ = writeln!(out, "{}_count {}", name, self.count.load(Ordering::Relaxed));
+        } else {
+            let _ = writeln!(out, "{}_sum{{{}}} {}", name, labels, self.sum.load(Ordering::Relaxed));
+            let _ = writeln!(out, "{}_count{{{}}} {}", name, labels, self.count.load(Ordering::Relaxed));
+        }
+    }
+}
+
+/// escapes a Prometheus label value.
+fn label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+pub(crate) fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, value: u64) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,166 @@
+//! Unit tests for the Prometheus rendering.
+
+
+use super::*;
+use crate::dispatch::{EventDispatcher, OverflowPolicy};
+
+#[test]
+fn test_render_includes_help_type_and_value() {
+    let metrics = Metrics::default();
+    metrics.ws_clients.store(3, Ordering::Relaxed);
+    metrics.ws_connections_rejected.fetch_add(2, Ordering::Relaxed);
+    let text = metrics.render();
+
+    assert!(text.contains("# TYPE pump_fun_monitor_ws_clients gauge\npump_fun_monitor_ws_clients 3\n"));
+    assert!(text.contains("# TYPE pump_fun_monitor_ws_connections_rejected_total counter\npump_fun_monitor_ws_connections_rejected_total 2\n"));
+    assert!(text.contains("# HELP pump_fun_monitor_ws_clients Currently connected WebSocket clients\n"));
+}
+
+#[test]
+fn test_every_line_is_comment_or_sample() {
+    let text = Metrics::default().render();
+    for line in text.lines() {
+        assert!(line.starts_with("# ") || line.split(' ').count() == 2, "bad line: {}", line);
+    }
+}
+
+#[test]
+fn test_render_labels_sink_counters() {
+    let metrics = Metrics::default();
+    assert!(!metrics.render().contains("pump_fun_monitor_sink_"));
+
+    metrics.add_sink(Arc::new(SinkStats::new("kafka")));
+    metrics.add_sink(Arc::new(SinkStats::new("telegram:@\"quoted\"")));
+    let text = metrics.render();
+
+    assert!(text.contains("# TYPE pump_fun_monitor_sink_delivered_total counter\npump_fun_monitor_sink_delivered_total{sink=\"kafka\"} 0\n"));
+    assert!(text.contains("pump_fun_monitor_sink_dropped_total{sink=\"telegram:@\\\"quoted\\\"\"} 0\n"));
+    assert!(text.contains("pump_fun_monitor_sink_stale_dropped_total{sink=\"kafka\"} 0\n"));
+}
+
+#[tokio::test]
+async fn test_render_labels_durable_queue_counters() {
+    let metrics = Metrics::default();
+    assert!(!metrics.render().contains("pump_fun_monitor_durable_queue_"));
+
+    let mut dispatcher = EventDispatcher::new(tokio::sync::broadcast::channel(1).0);
+    let (_queue, stats) = dispatcher.add_durable("sinks", 1, OverflowPolicy::Drop);
+    metrics.add_durable_queue(stats);
+    let mut generator = crate::simulation::EventGenerator::new(1);
+    dispatcher.dispatch(generator.next_event()).await;
+    dispatcher.dispatch(generator.next_event()).await;
+    let text = metrics.render();
+
+    assert!(text.contains("# TYPE pump_fun_monitor_durable_queue_waits_total counter\npump_fun_monitor_durable_queue_waits_total{queue=\"sinks\"} 0\n"));
+    assert!(text.contains("\npump_fun_monitor_durable_queue_dropped_total{queue=\"sinks\"} 1\n"));
+}
+
+#[tokio::test]
+async fn test_render_dispatch_tiers() {
+    let metrics = Metrics::default();
+    assert!(!metrics.render().contains("pump_fun_monitor_dispatch_latency_us"));
+
+    let mut dispatcher = EventDispatcher::new(tokio::sync::broadcast::channel(1).0);
+    let (_queue, stats) = dispatcher.add_immediate_queue("discord:0", 1);
+    metrics.add_immediate_subscriber(stats);
+    metrics.set_dispatch_latency(dispatcher.latency());
+    let mut generator = crate::simulation::EventGenerator::new(1);
+    dispatcher.dispatch(generator.next_event()).await;
+    dispatcher.dispatch(generator.next_event()).await;
+    let text = metrics.render();
+
+    assert!(text.contains("\npump_fun_monitor_immediate_dropped_total{subscriber=\"discord:0\"} 1\n"));
+    assert!(text.contains("\npump_fun_monitor_immediate_demoted{subscriber=\"discord:0\"} 0\n"));
+    assert!(text.contains("# TYPE pump_fun_monitor_dispatch_latency_us summary\n"));
+    assert!(text.contains("\npump_fun_monitor_dispatch_latency_us_count{tier=\"0\"} 2\n"));
+    // without durable queues nothing is measured for tier 1
+    assert!(text.contains("\npump_fun_monitor_dispatch_latency_us_count{tier=\"1\"} 0\n"));
+}
+
+#[test]
+fn test_render_latency_histograms() {
+    let metrics = Metrics::default();
+    metrics.processing_latency.observe(40);
+    metrics.processing_latency.observe(100);
+    metrics.processing_latency.observe(700);
+    metrics.processing_latency.observe(60_000);
+    let text = metrics.render();
+
+    assert!(text.contains("# TYPE pump_fun_monitor_processing_latency_ms histogram\n"));
+    // buckets are cumulative and inclusive of their bound
+    assert!(text.contains("pump_fun_monitor_processing_latency_ms_bucket{le=\"50\"} 1\n"));
+    assert!(text.contains("pump_fun_monitor_processing_latency_ms_bucket{le=\"100\"} 2\n"));
+    assert!(text.contains("pump_fun_monitor_processing_latency_ms_bucket{le=\"1000\"} 3\n"));
+    assert!(text.contains("pump_fun_monitor_processing_latency_ms_bucket{le=\"30000\"} 3\n"));
+    assert!(text.contains("pump_fun_monitor_processing_latency_ms_bucket{le=\"+Inf\"} 4\n"));
+    assert!(text.contains("pump_fun_monitor_processing_latency_ms_sum 60840\n"));
+    assert!(text.contains("pump_fun_monitor_processing_latency_ms_count 4\n"));
+    assert!(text.contains("pump_fun_monitor_chain_latency_ms_count 0\n"));
+}
+
+#[test]
+fn test_render_labels_processing_errors_by_code_and_endpoint() {
+    let metrics = Metrics::default();
+    assert!(!metrics.render().contains("pump_fun_monitor_processing_errors_total"));
+
+    metrics.record_error("timeout", Some("helius"));
+    metrics.record_error("transaction_not_found", Some("helius"));
+    metrics.record_error("timeout", Some("helius"));
+    metrics.record_error("timeout", Some("triton"));
+    metrics.record_error("transaction_parse", None);
+    let text = metrics.render();
+
+    assert!(text.contains(
+        "# TYPE pump_fun_monitor_processing_errors_total counter\n\
+         pump_fun_monitor_processing_errors_total{code=\"timeout\",endpoint=\"helius\"} 2\n"
+    ));
+    assert!(text.contains("pump_fun_monitor_processing_errors_total{code=\"timeout\",endpoint=\"triton\"} 1\n"));
+    assert!(text.contains(
+        "pump_fun_monitor_processing_errors_total{code=\"transaction_not_found\",endpoint=\"helius\"} 1\n"
+    ));
+    assert!(text.contains("pump_fun_monitor_processing_errors_total{code=\"transaction_parse\",endpoint=\"\"} 1\n"));
+}
+
+#[test]
+fn test_render_labels_dead_letters_by_code() {
+    let metrics = Metrics::default();
+    assert!(!metrics.render().contains("pump_fun_monitor_dead_letters_total"));
+
+    metrics.record_dead_letter("transaction_parse");
+    metrics.record_dead_letter("transaction_parse");
+    metrics.record_dead_letter("account_not_found");
+    let text = metrics.render();
+
+    assert!(text.contains("pump_fun_monitor_dead_letters_total{code=\"account_not_found\"} 1\n"));
+    assert!(text.contains("pump_fun_monitor_dead_letters_total{code=\"transaction_parse\"} 2\n"));
+}
+
+#[test]
+fn test_render_labels_global_filter_suppressions_by_rule() {
+    let metrics = Metrics::default();
+    assert!(!metrics.render().contains("pump_fun_monitor_global_filter_suppressed_total"));
+
+    metrics.record_global_filter_suppressed("name_exclude");
+    metrics.record_global_filter_suppressed("creator_blocklist");
+    metrics.record_global_filter_suppressed("name_exclude");
+    let text = metrics.render();
+
+    assert!(text.contains("pump_fun_monitor_global_filter_suppressed_total{rule=\"creator_blocklist\"} 1\n"));
+    assert!(text.contains("pump_fun_monitor_global_filter_suppressed_total{rule=\"name_exclude\"} 2\n"));
+}
+
+#[test]
+fn test_render_counts_first_deliveries_per_endpoint() {
+    let metrics = Metrics::default();
+    assert!(!metrics.render().contains("pump_fun_monitor_rpc_first_deliveries_total"));
+
+    metrics.add_rpc_endpoint("helius");
+    metrics.add_rpc_endpoint("triton");
+    metrics.record_first_delivery("helius");
+    metrics.record_first_delivery("helius");
+    metrics.add_rpc_endpoint("helius");
+    let text = metrics.render();
+
+    assert!(text.contains("pump_fun_monitor_rpc_first_deliveries_total{endpoint=\"helius\"} 2\n"));
+    assert!(text.contains("pump_fun_monitor_rpc_first_deliveries_total{endpoint=\"triton\"} 0\n"));
+}
@@ -0,0 +1,201 @@
+//! # SOL Price Feed
+//!
+//! Optional background task that keeps a cached SOL/USD price so token creation events can carry USD values. The feed is disabled unless a price source (`SOL_PRICE_SOURCE`) is configured, and a price older than the configured maximum age is never used.
+
+use crate::error::{MonitorError, Result};
+use crate::rpc_client::SolanaRpc;
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Pyth sponsored SOL/USD price feed account (`PriceUpdateV2`) on mainnet.
+pub const DEFAULT_PYTH_SOL_USD_ACCOUNT: &str = "7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE";
+
+const COINGECKO_SOL_PRICE_URL: &str =
+    "https://api.coingecko.com/api/v3/simple/price?ids=solana&vs_currencies=usd&include_last_updated_at=true";
+
+/// where the SOL/USD price is read from.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceSource {
+    /// on-chain Pyth account, falling back to CoinGecko when the read fails
+    Pyth,
+    /// CoinGecko HTTP API only
+    CoinGecko,
+}
+
+impl FromStr for PriceSource {
+    type Err = MonitorError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "pyth" => Ok(Self::Pyth),
+            "coingecko" => Ok(Self::CoinGecko),
+            other => Err(MonitorError::Config(format!(
+                "SOL_PRICE_SOURCE '{}' is not one of: pyth, coingecko",
+                other
+            ))),
+        }
+    }
+}
+
+/// settings for the SOL/USD price refresh task.
+///
+#[derive(Debug, Clone)]
+pub struct PriceFeedConfig {
+    pub source: PriceSource,
+    pub pyth_account: Pubkey,
+    pub refresh_interval: Duration,
+    pub max_age: Duration,
+}
+
+/// Lock-free cell holding the latest SOL/USD price and the unix time it was published.
+///
+/// Readers only ever see a price younger than `max_age`; anything older is treated as missing.
+#[derive(Debug)]
+pub struct SolPriceCell {
+    price_bits: AtomicU64,
+    published_at: AtomicI64,
+    max_age: Duration,
+}
+
+impl SolPriceCell {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            price_bits: AtomicU64::new(0),
+            published_at: AtomicI64::new(0),
+            max_age,
+        }
+    }
+
+    pub fn store(&self, price: f64, published_at: i64) {
+        self.price_bits.store(price.to_bits(), Ordering::Relaxed);
+        self.published_at.store(published_at, Ordering::Release);
+    }
+
+    /// returns the cached price if one exists and is not stale.
+    pub fn fresh_price(&self) -> Option<f64> {
+        let published_at = self.published_at.load(Ordering::Acquire);
+        if published_at == 0 {
+            return None;
+        }
+
+        let age_secs = (chrono::Utc::now().timestamp() - published_at).max(0) as u64;
+        if age_secs > self.max_age.as_secs() {
+            return None;
+        }
+
+        Some(f64::from_bits(self.price_bits.load(Ordering::Relaxed)))
+    }
+}
+
+/// Periodically refreshes the SOL/USD price into `cell` until the process exits.
+///
+/// Failed refreshes are logged and the previous value is kept; it simply ages out once it passes the staleness threshold.
+pub async fn run_price_feed(config: PriceFeedConfig, rpc_client: Arc<dyn SolanaRpc>, cell: Arc<SolPriceCell>) {
+    info!(
+        "Starting SOL/USD price feed ({:?}, refresh every {}s)",
+        config.source,
+        config.refresh_interval.as_secs()
+    );
+
+    let http_client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_default();
+    let mut interval = tokio::time::interval(config.refresh_interval);
+
+    loop {
+        interval.tick().await;
+        match fetch_price(&config, rpc_client.as_ref(), &http_client).await {
+            Ok((price, published_at)) => {
+                debug!("SOL/USD price updated: {:.4} (published at {})", price, published_at);
+                cell.store(price, published_at);
+            }
+            Err(e) => warn!("Failed to refresh SOL/USD price: {}", e),
+        }
+    }
+}
+
+async fn fetch_price(
+    config: &PriceFeedConfig,
+    rpc_client: &dyn SolanaRpc,
+    http_client: &reqwest::Client,
+) -> Result<(f64, i64)> {
+    match config.source {
+        PriceSource::Pyth => match fetch_pyth_price(rpc_client, &config.pyth_account).await {
+            Ok(price) => Ok(price),
+            Err(e) => {
+                warn!("Pyth price read failed ({}), falling back to CoinGecko", e);
+                fetch_coingecko_price(http_client).await
+            }
+        },
+        PriceSource::CoinGecko => fetch_coingecko_price(http_client).await,
+    }
+}
+
+async fn fetch_pyth_price(rpc_client: &dyn SolanaRpc, account: &Pubkey) -> Result<(f64, i64)> {
+    let account = rpc_client.get_account(account).await?;
+    parse_pyth_price_update(&account.data)
+}
+
+/// Decodes price and publish time from a Pyth `PriceUpdateV2` account.
+///
+/// Layout: 8-byte discriminator, 32-byte write authority, a borsh `VerificationLevel` enum (1 or 2 bytes), then the `PriceFeedMessage` (feed id, price, conf, exponent, publish time, ...).
+fn parse_pyth_price_update(data: &[u8]) -> Result<(f64, i64)> {
+    let message_offset = match data.get(40) {
+        Some(0) => 42, // Partial { num_signatures: u8 }
+        Some(1) => 41, // Full
+        _ => {
+            return Err(MonitorError::TransactionParse(
+                "Unrecognized Pyth verification level".to_string(),
+            ))
+        }
+    };
+
+    let price = i64::from_le_bytes(read_bytes(data, message_offset + 32)?);
+    let exponent = i32::from_le_bytes(read_bytes(data, message_offset + 48)?);
+    let publish_time = i64::from_le_bytes(read_bytes(data, message_offset + 52)?);
+
+    if price <= 0 {
+        return Err(MonitorError::TransactionParse(format!("Pyth reported non-positive price {}", price)));
+    }
+
+    Ok((price as f64 * 10f64.powi(exponent), publish_time))
+}
+
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N]> {
+    data.get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| MonitorError::TransactionParse("Pyth price account data too short".to_string()))
+}
+
+#[derive(Deserialize)]
+struct CoinGeckoResponse {
+    solana: CoinGeckoPrice,
+}
+
+#[derive(Deserialize)]
+struct CoinGeckoPrice {
+    usd: f64,
+    last_updated_at: i64,
+}
+
+async fn fetch_coingecko_price(http_client: &reqwest::Client) -> Result<(f64, i64)> {
+    let response = http_client
+        .get(COINGECKO_SOL_PRICE_URL)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<CoinGeckoResponse>()
+        .await?;
+
+    Ok((response.solana.usd, response.solana.last_updated_at))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,79 @@
+//! Unit tests for the SOL/USD price feed: decoding the Pyth account and keeping stale prices out.
+
+use super::*;
+
+/// function to build `PriceUpdateV2` account data with `verification` as the borsh `VerificationLevel` bytes.
+fn price_update(verification: &[u8], price: i64, exponent: i32, publish_time: i64) -> Vec<u8> {
+    let mut data = vec![0x22; 8]; // discriminator
+    data.extend_from_slice(&[0x11; 32]); // write authority
+    data.extend_from_slice(verification);
+    data.extend_from_slice(&[0xef; 32]); // feed id
+    data.extend_from_slice(&price.to_le_bytes());
+    data.extend_from_slice(&7_000_000u64.to_le_bytes()); // conf
+    data.extend_from_slice(&exponent.to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes());
+    data.extend_from_slice(&(publish_time - 1).to_le_bytes()); // prev publish time
+    data.extend_from_slice(&price.to_le_bytes()); // ema price
+    data.extend_from_slice(&7_000_000u64.to_le_bytes()); // ema conf
+    data.extend_from_slice(&280_000_000u64.to_le_bytes()); // posted slot
+    data
+}
+
+#[test]
+fn test_pyth_full_verification_is_decoded() {
+    let data = price_update(&[1], 14_512_345_678, -8, 1_714_564_800);
+    let (price, published_at) = parse_pyth_price_update(&data).unwrap();
+    assert!((price - 145.12345678).abs() < 1e-9, "{}", price);
+    assert_eq!(published_at, 1_714_564_800);
+}
+
+#[test]
+fn test_pyth_partial_verification_is_decoded() {
+    // Partial carries its signature count, moving the message one byte further
+    let data = price_update(&[0, 3], 9_876_543, -5, 1_714_564_801);
+    let (price, published_at) = parse_pyth_price_update(&data).unwrap();
+    assert!((price - 98.76543).abs() < 1e-9, "{}", price);
+    assert_eq!(published_at, 1_714_564_801);
+}
+
+#[test]
+fn test_pyth_rejects_unusable_accounts() {
+    let unknown_level = price_update(&[2], 14_512_345_678, -8, 1_714_564_800);
+    assert!(parse_pyth_price_update(&unknown_level).is_err());
+
+    let full = price_update(&[1], 14_512_345_678, -8, 1_714_564_800);
+    // cut inside the publish time
+    assert!(parse_pyth_price_update(&full[..41 + 56]).is_err());
+    assert!(parse_pyth_price_update(&full[..40]).is_err());
+
+    for price in [0, -14_512_345_678] {
+        assert!(parse_pyth_price_update(&price_update(&[1], price, -8, 1_714_564_800)).is_err());
+    }
+}
+
+#[test]
+fn test_price_cell_is_empty_until_stored() {
+    let cell = SolPriceCell::new(Duration::from_secs(60));
+    assert_eq!(cell.fresh_price(), None);
+
+    cell.store(145.0, chrono::Utc::now().timestamp() - 10);
+    assert_eq!(cell.fresh_price(), Some(145.0));
+}
+
+#[test]
+fn test_stale_price_is_not_used() {
+    let cell = SolPriceCell::new(Duration::from_secs(60));
+    cell.store(145.0, chrono::Utc::now().timestamp() - 120);
+    assert_eq!(cell.fresh_price(), None);
+
+    // a newer publish makes it usable again
+    cell.store(146.0, chrono::Utc::now().timestamp());
+    assert_eq!(cell.fresh_price(), Some(146.0));
+}
+
+#[test]
+fn test_price_source_names() {
+    assert_eq!(" Pyth ".parse::<PriceSource>().unwrap(), PriceSource::Pyth);
+    assert_eq!("coingecko".parse::<PriceSource>().unwrap(), PriceSource::CoinGecko);
+    assert!("binance".parse::<PriceSource>().is_err());
+}
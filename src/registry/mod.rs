@@ -0,0 +1,116 @@
+//! # Token Registry
+//!
+//! Answers "what do we know about mint X" without a scan or a database round trip: the creation event of every
+//! broadcast token, keyed by mint. The registry is an immediate subscriber of the dispatcher, so a token is in it
+//! before any consumer of the broadcast hears of it, and curve updates, migrations and `GET /tokens/{mint}` can look
+//! it up by the time they need it.
+//!
+//! At most `TOKEN_REGISTRY_CAPACITY` events are held, the least recently used forgotten first: a lookup counts as a
+//! use, so tokens something keeps asking about stay while quiet ones make room. A mint missing from memory is looked
+//! up in the fallback source when there is one, the postgres sink's table, and kept for the next lookup.
+
+use crate::cache::{BoundedCache, CacheLimits, CacheStats};
+use crate::data_models::TokenCreatedEvent;
+use crate::dispatch::ImmediateSubscriber;
+use crate::error::Result;
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+/// where the registry looks for tokens it doesn't hold, such as a database of past events.
+#[async_trait]
+pub trait TokenSource: Send + Sync {
+    /// the creation event of `mint`, `None` when the source doesn't have it.
+    async fn token(&self, mint: &str) -> Result<Option<TokenCreatedEvent>>;
+}
+
+/// Creation events by mint, bounded to a capacity and forgetting the least recently used first.
+///
+/// Shared as `Arc<TokenRegistry>`; every method takes `&self` and holds the lock only for the map operation itself.
+pub struct TokenRegistry {
+    tokens: Mutex<BoundedCache<String, Arc<TokenCreatedEvent>>>,
+    fallback: Option<Arc<dyn TokenSource>>,
+}
+
+impl TokenRegistry {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            tokens: Mutex::new(BoundedCache::new(CacheLimits::capacity(capacity))),
+            fallback: None,
+        }
+    }
+
+    /// looks mints missing from memory up in `fallback`.
+    pub fn with_fallback(mut self, fallback: Arc<dyn TokenSource>) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    /// records `event` as the creation of its mint, replacing an earlier one.
+    pub fn insert(&self, event: TokenCreatedEvent) {
+        let mint = event.token.mint_address.clone();
+        self.lock().insert(mint, Arc::new(event), Instant::now());
+    }
+
+    /// The creation event of `mint` if it is held in memory, marking it as the most recently used.
+    ///
+    /// Never waits on anything but the lock, so it can be called from the processor and the curve tracker.
+    pub fn get(&self, mint: &str) -> Option<Arc<TokenCreatedEvent>> {
+        let mut tokens = self.lock();
+        // taken out and put back as the newest entry; nothing is evicted, there is room for the one taken out
+        let event = tokens.remove(mint)?;
+        tokens.insert(mint.to_string(), Arc::clone(&event), Instant::now());
+        Some(event)
+    }
+
+    /// The creation event of `mint`, from memory or else from the fallback source, which it is then kept from.
+    ///
+    /// Fails only when the fallback source does.
+    pub async fn lookup(&self, mint: &str) -> Result<Option<Arc<TokenCreatedEvent>>> {
+        if let Some(event) = self.get(mint) {
+            return Ok(Some(event));
+        }
+        let Some(fallback) = &self.fallback else {
+            return Ok(None);
+        };
+        let Some(event) = fallback.token(mint).await? else {
+            return Ok(None);
+        };
+        let event = Arc::new(event);
+        self.lock().insert(mint.to_string(), Arc::clone(&event), Instant::now());
+        Ok(Some(event))
+    }
+
+    /// events currently held.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn cache_stats(&self) -> Arc<CacheStats> {
+        self.lock().stats()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BoundedCache<String, Arc<TokenCreatedEvent>>> {
+        self.tokens.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl ImmediateSubscriber for TokenRegistry {
+    fn name(&self) -> &str {
+        "registry"
+    }
+
+    fn enqueue(&self, event: &TokenCreatedEvent) -> bool {
+        self.insert(event.clone());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests;
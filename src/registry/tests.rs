@@ -0,0 +1,208 @@
+//! Unit tests for the token registry: least recently used eviction, the fallback source and concurrent use.
+
+use super::*;
+use crate::dispatch::EventDispatcher;
+use crate::error::MonitorError;
+use crate::simulation::EventGenerator;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use tokio::sync::broadcast;
+
+/// stands in for the postgres sink's table, counting the lookups it answers.
+#[derive(Default)]
+struct MemorySource {
+    events: HashMap<String, TokenCreatedEvent>,
+    lookups: AtomicUsize,
+    failing: bool,
+}
+
+#[async_trait]
+impl TokenSource for MemorySource {
+    async fn token(&self, mint: &str) -> Result<Option<TokenCreatedEvent>> {
+        self.lookups.fetch_add(1, Ordering::Relaxed);
+        if self.failing {
+            return Err(MonitorError::Archive("connection refused".to_string()));
+        }
+        Ok(self.events.get(mint).cloned())
+    }
+}
+
+/// function to generate `count` events with distinct mints.
+fn events(seed: u64, count: usize) -> Vec<TokenCreatedEvent> {
+    let mut generator = EventGenerator::new(seed);
+    (0..count).map(|_| generator.next_event()).collect()
+}
+
+/// function to return the mint of `event`.
+fn mint(event: &TokenCreatedEvent) -> &str {
+    &event.token.mint_address
+}
+
+#[test]
+fn test_least_recently_used_token_is_evicted() {
+    let registry = TokenRegistry::new(3);
+    let events = events(1, 5);
+    for event in &events[..3] {
+        registry.insert(event.clone());
+    }
+
+    // the oldest is looked up, so the next oldest makes room instead
+    assert_eq!(registry.get(mint(&events[0])).as_deref(), Some(&events[0]));
+    registry.insert(events[3].clone());
+    assert!(registry.get(mint(&events[1])).is_none());
+    assert!(registry.get(mint(&events[0])).is_some());
+    assert!(registry.get(mint(&events[2])).is_some());
+    assert!(registry.get(mint(&events[3])).is_some());
+    assert_eq!(registry.len(), 3);
+
+    // now the least recently used is the one looked up first above
+    registry.insert(events[4].clone());
+    assert!(registry.get(mint(&events[0])).is_none());
+    assert_eq!(registry.len(), 3);
+    assert_eq!(registry.cache_stats().evicted(), 2);
+}
+
+#[test]
+fn test_reinserted_token_replaces_the_old_event() {
+    let registry = TokenRegistry::new(2);
+    let mut event = events(2, 1).remove(0);
+    registry.insert(event.clone());
+    event.sequence = 42;
+    registry.insert(event.clone());
+
+    assert_eq!(registry.len(), 1);
+    assert_eq!(registry.get(mint(&event)).unwrap().sequence, 42);
+    assert_eq!(registry.cache_stats().evicted(), 0);
+}
+
+#[tokio::test]
+async fn test_lookup_falls_back_to_the_source_once() {
+    let events = events(3, 2);
+    let source = Arc::new(MemorySource {
+        events: HashMap::from([(mint(&events[0]).to_string(), events[0].clone())]),
+        ..MemorySource::default()
+    });
+    let registry = TokenRegistry::new(10).with_fallback(Arc::clone(&source) as Arc<dyn TokenSource>);
+
+    assert!(registry.get(mint(&events[0])).is_none());
+    assert_eq!(registry.lookup(mint(&events[0])).await.unwrap().as_deref(), Some(&events[0]));
+    // kept from the first lookup on
+    assert!(registry.get(mint(&events[0])).is_some());
+    registry.lookup(mint(&events[0])).await.unwrap();
+    assert_eq!(source.lookups.load(Ordering::Relaxed), 1);
+
+    // a mint the source doesn't have either is asked for every time
+    assert!(registry.lookup(mint(&events[1])).await.unwrap().is_none());
+    assert!(registry.lookup(mint(&events[1])).await.unwrap().is_none());
+    assert_eq!(source.lookups.load(Ordering::Relaxed), 3);
+    assert_eq!(registry.len(), 1);
+}
+
+#[tokio::test]
+async fn test_lookup_without_a_source_or_with_a_failing_one() {
+    let events = events(4, 2);
+    let registry = TokenRegistry::new(10);
+    registry.insert(events[0].clone());
+    assert!(registry.lookup(mint(&events[0])).await.unwrap().is_some());
+    assert!(registry.lookup(mint(&events[1])).await.unwrap().is_none());
+
+    let failing = Arc::new(MemorySource {
+        failing: true,
+        ..MemorySource::default()
+    });
+    let registry = TokenRegistry::new(10).with_fallback(failing);
+    registry.insert(events[0].clone());
+    // held tokens never reach the source
+    assert!(registry.lookup(mint(&events[0])).await.unwrap().is_some());
+    assert!(registry.lookup(mint(&events[1])).await.is_err());
+}
+
+#[test]
+fn test_concurrent_inserts_and_lookups_stay_within_capacity() {
+    const THREADS: u64 = 8;
+    const PER_THREAD: usize = 2_000;
+    const CAPACITY: usize = 5_000;
+    let registry = Arc::new(TokenRegistry::new(CAPACITY));
+
+    let writers: Vec<_> = (0..THREADS)
+        .map(|seed| {
+            let registry = Arc::clone(&registry);
+            thread::spawn(move || {
+                let events = events(100 + seed, PER_THREAD);
+                for (index, event) in events.iter().enumerate() {
+                    registry.insert(event.clone());
+                    // whatever comes back for a mint is that mint's event
+                    let earlier = &events[index / 2];
+                    if let Some(found) = registry.get(mint(earlier)) {
+                        assert_eq!(*found, *earlier);
+                    }
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+
+    let inserted = THREADS as usize * PER_THREAD;
+    assert_eq!(registry.len(), CAPACITY);
+    assert_eq!(registry.cache_stats().entries(), CAPACITY);
+    assert_eq!(registry.cache_stats().evicted(), (inserted - CAPACITY) as u64);
+}
+
+#[test]
+fn test_concurrent_readers_see_every_token_once_inserted() {
+    const THREADS: u64 = 4;
+    const PER_THREAD: usize = 1_000;
+    let registry = Arc::new(TokenRegistry::new(THREADS as usize * PER_THREAD));
+    let all: Vec<Vec<TokenCreatedEvent>> = (0..THREADS).map(|seed| events(200 + seed, PER_THREAD)).collect();
+
+    let writers: Vec<_> = all
+        .iter()
+        .cloned()
+        .map(|events| {
+            let registry = Arc::clone(&registry);
+            thread::spawn(move || events.into_iter().for_each(|event| registry.insert(event)))
+        })
+        .collect();
+    // readers race the writers: a mint is either not there yet or there with its own event
+    let readers: Vec<_> = all
+        .iter()
+        .cloned()
+        .map(|events| {
+            let registry = Arc::clone(&registry);
+            thread::spawn(move || {
+                for event in &events {
+                    if let Some(found) = registry.get(mint(event)) {
+                        assert_eq!(*found, *event);
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in writers.into_iter().chain(readers) {
+        handle.join().unwrap();
+    }
+
+    // nothing was evicted, so every token is there once the writers are done
+    for event in all.iter().flatten() {
+        assert_eq!(registry.get(mint(event)).as_deref(), Some(event));
+    }
+    assert_eq!(registry.cache_stats().evicted(), 0);
+}
+
+#[tokio::test]
+async fn test_registry_has_the_token_before_the_broadcast() {
+    let (sender, mut receiver) = broadcast::channel(4);
+    let mut dispatcher = EventDispatcher::new(sender);
+    let registry = Arc::new(TokenRegistry::new(10));
+    dispatcher.add_immediate(Arc::clone(&registry) as Arc<dyn ImmediateSubscriber>);
+
+    let mut event = events(5, 1).remove(0);
+    event.sequence = 7;
+    // the registry counts as one of the subscribers that got it
+    assert_eq!(dispatcher.dispatch(event.clone()).await, 2);
+    let broadcast = receiver.recv().await.unwrap();
+    assert_eq!(registry.get(mint(&broadcast)).unwrap().sequence, 7);
+}
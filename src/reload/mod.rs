@@ -0,0 +1,316 @@
+//! # Config Reload
+//!
+//! Re-reads the configuration on `SIGHUP` or a `POST /reload` to the health port and applies the settings that can
+//! change without a restart: sink filters, the per-IP connection limits, filter presets, copycat suppression, the wallet
+//! watchlist, the global filter and the log level. Each of them is published on a `watch` channel that the task using it reads from,
+//! so a reload takes effect from the next event or connection on and no client is dropped. The watchlist and creator
+//! blocklist files are read again even when their paths are unchanged, so editing a file and reloading is enough.
+//!
+//! A reload is all or nothing. When the new configuration doesn't validate, or changes anything else (bind addresses,
+//! RPC endpoints, a sink's destination), nothing is applied and the error lists every setting that needs a restart.
+
+use crate::config::{Config, IpLimitConfig, SpamConfig};
+use crate::data_models::FilterCriteria;
+use crate::watchlist::Watchlist;
+use crate::global_filter::GlobalFilter;
+use crate::error::{MonitorError, Result};
+use crate::sinks::{SinkFilters, SinksConfig};
+use crate::websocket_server::{constant_time_eq, LogLevelHandle};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// log filter in effect when none is configured.
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// What a successful reload changed.
+///
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct ReloadReport {
+    /// the reloaded settings that differ from before, e.g. `sinks.discord:0.filter`; empty when nothing changed
+    pub changed: Vec<String>,
+}
+
+/// Owns the configuration in effect and publishes its reloadable settings.
+///
+pub struct Reloader {
+    /// where the configuration was loaded from, read again on every reload
+    path: Option<PathBuf>,
+    overrides: HashMap<&'static str, String>,
+    current: Mutex<Config>,
+    sink_filters: watch::Sender<SinkFilters>,
+    ip_limits: watch::Sender<IpLimitConfig>,
+    presets: watch::Sender<Arc<BTreeMap<String, FilterCriteria>>>,
+    spam: watch::Sender<Option<SpamConfig>>,
+    watchlist: watch::Sender<Option<Arc<Watchlist>>>,
+    global_filter: watch::Sender<Option<Arc<GlobalFilter>>>,
+    log_level: Option<LogLevelHandle>,
+}
+
+impl Reloader {
+    /// Starts from `config`, which was loaded from `path` and `overrides` the way `Config::load` does it.
+    pub fn new(config: Config, path: Option<PathBuf>, overrides: HashMap<&'static str, String>) -> Self {
+        Self {
+            path,
+            overrides,
+            sink_filters: watch::channel(config.sinks.filters()).0,
+            ip_limits: watch::channel(config.server.ip_limits.clone()).0,
+            presets: watch::channel(Arc::clone(&config.server.presets)).0,
+            spam: watch::channel(config.spam.clone()).0,
+            watchlist: watch::channel(config.watchlist.clone()).0,
+            global_filter: watch::channel(config.global_filter.clone()).0,
+            current: Mutex::new(config),
+            log_level: None,
+        }
+    }
+
+    /// Swaps the log filter through `handle` when a reload changes the log level.
+    ///
+    pub fn with_log_level(mut self, handle: LogLevelHandle) -> Self {
+        self.log_level = Some(handle);
+        self
+    }
+
+    /// filters of every built-in sink, keyed by sink name.
+    pub fn sink_filters(&self) -> watch::Receiver<SinkFilters> {
+        self.sink_filters.subscribe()
+    }
+
+    pub fn ip_limits(&self) -> watch::Receiver<IpLimitConfig> {
+        self.ip_limits.subscribe()
+    }
+
+    /// filter presets clients apply with `usePreset`, by name.
+    pub fn presets(&self) -> watch::Receiver<Arc<BTreeMap<String, FilterCriteria>>> {
+        self.presets.subscribe()
+    }
+
+    /// copycat detection settings; `None` while detection is off.
+    pub fn spam(&self) -> watch::Receiver<Option<SpamConfig>> {
+        self.spam.subscribe()
+    }
+
+    /// labelled wallets, read again from `WATCHLIST_FILE` on every reload; `None` without one.
+    pub fn watchlist(&self) -> watch::Receiver<Option<Arc<Watchlist>>> {
+        self.watchlist.subscribe()
+    }
+
+    /// rules every event must pass before it is dispatched; `None` while no rule is set.
+    pub fn global_filter(&self) -> watch::Receiver<Option<Arc<GlobalFilter>>> {
+        self.global_filter.subscribe()
+    }
+
+    /// whether `token` is the admin token; always `false` when none is configured.
+    pub fn is_admin(&self, token: &str) -> bool {
+        self.lock()
+            .server
+            .admin_token
+            .as_deref()
+            .is_some_and(|admin_token| constant_time_eq(admin_token.as_bytes(), token.as_bytes()))
+    }
+
+    /// Loads the configuration again, from the same file, environment and flags, and applies it.
+    pub fn reload(&self) -> Result<ReloadReport> {
+        let config = Config::load(self.path.as_deref(), &self.overrides)?;
+        self.apply(config)
+    }
+
+    /// Like `reload`, logging what changed or why nothing was applied.
+    pub fn reload_logged(&self) -> Result<ReloadReport> {
+        let reloaded = self.reload();
+        match &reloaded {
+            Ok(report) if report.changed.is_empty() => info!("Configuration reloaded, nothing changed"),
+            Ok(report) => info!("Configuration reloaded, changed: {}", report.changed.join(", ")),
+            Err(e) => warn!("Configuration reload rejected: {}", e),
+        }
+        reloaded
+    }
+
+    /// Applies the reloadable settings of `config`, or nothing at all if it changes a setting that needs a restart.
+    pub fn apply(&self, config: Config) -> Result<ReloadReport> {
+        let mut current = self.lock();
+        let fixed = restart_required(&current, &config);
+        if !fixed.is_empty() {
+            return Err(MonitorError::Config(format!(
+                "these settings only take effect on restart and cannot be reloaded: {}",
+                fixed.join(", ")
+            )));
+        }
+
+        let mut changed = Vec::new();
+        // the log level goes first: it is the only setting whose swap can fail
+        if current.log_level != config.log_level {
+            if let Some(handle) = &self.log_level {
+                handle
+                    .set(config.log_level.as_deref().unwrap_or(DEFAULT_LOG_LEVEL))
+                    .map_err(|e| MonitorError::Config(format!("RUST_LOG is not a valid log filter: {}", e)))?;
+            }
+            changed.push("log_level".to_string());
+        }
+
+        let old_filters = current.sinks.filters();
+        let new_filters = config.sinks.filters();
+        let mut changed_sinks: Vec<&String> = new_filters
+            .iter()
+            .filter(|(name, filters)| old_filters.get(*name) != Some(*filters))
+            .map(|(name, _)| name)
+            .collect();
+        changed_sinks.sort();
+        changed.extend(changed_sinks.iter().map(|name| format!("sinks.{}.filter", name)));
+        if !changed_sinks.is_empty() {
+            self.sink_filters.send_replace(new_filters);
+        }
+
+        if current.server.ip_limits != config.server.ip_limits {
+            self.ip_limits.send_replace(config.server.ip_limits.clone());
+            changed.push("server.ip_limits".to_string());
+        }
+        if current.server.presets != config.server.presets {
+            self.presets.send_replace(Arc::clone(&config.server.presets));
+            changed.push("server.presets".to_string());
+        }
+        if current.spam != config.spam {
+            self.spam.send_replace(config.spam.clone());
+            changed.push("spam".to_string());
+        }
+        if current.watchlist != config.watchlist {
+            self.watchlist.send_replace(config.watchlist.clone());
+            changed.push("watchlist".to_string());
+        }
+        if current.global_filter != config.global_filter {
+            self.global_filter.send_replace(config.global_filter.clone());
+            changed.push("global_filter".to_string());
+        }
+
+        *current = config;
+        Ok(ReloadReport { changed })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Config> {
+        self.current.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Reloads `reloader` on every `SIGHUP` until the process exits.
+#[cfg(unix)]
+pub async fn reload_on_sighup(reloader: Arc<Reloader>) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(e) => {
+            warn!("Cannot listen for SIGHUP, reload through POST /reload instead: {}", e);
+            return;
+        }
+    };
+    while hangups.recv().await.is_some() {
+        let _ = reloader.reload_logged();
+    }
+}
+
+/// The settings of `new` that differ from `old` but are only read at startup.
+pub(crate) fn restart_required(old: &Config, new: &Config) -> Vec<&'static str> {
+    fixed_settings(old)
+        .into_iter()
+        .zip(fixed_settings(new))
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((name, _), _)| name)
+        .collect()
+}
+
+/// every setting that is only read at startup, by name, in a form that compares equal exactly when the setting does.
+fn fixed_settings(config: &Config) -> Vec<(&'static str, String)> {
+    let sinks = without_filters(&config.sinks);
+    vec![
+        ("rpc.http_url", format!("{:?}", config.rpc.http_url)),
+        ("rpc.http_label", format!("{:?}", config.rpc.http_label)),
+        ("rpc.wss_url", format!("{:?}", config.rpc.wss_url)),
+        ("rpc.wss_endpoints", format!("{:?}", config.rpc.wss_endpoints)),
+        ("rpc.programs", format!("{:?}", config.rpc.programs)),
+        ("rpc.commitment", format!("{:?}", config.rpc.commitment)),
+        ("rpc.detail_level", format!("{:?}", config.rpc.detail_level)),
+        ("rpc.latency_fields", format!("{:?}", config.rpc.latency_fields)),
+        ("rpc.fast_path", format!("{:?}", config.rpc.fast_path)),
+        ("rpc.provider_limit_backoff", format!("{:?}", config.rpc.provider_limit_backoff)),
+        ("rpc.request_timeout", format!("{:?}", config.rpc.request_timeout)),
+        ("rpc.processing_timeout", format!("{:?}", config.rpc.processing_timeout)),
+        ("rpc.diagnostics_max_per_sec", format!("{:?}", config.rpc.diagnostics_max_per_sec)),
+        ("server.bind_addr", format!("{:?}", config.server.bind_addr)),
+        ("server.tls", format!("{:?}", config.server.tls)),
+        ("server.compression", format!("{:?}", config.server.compression)),
+        ("server.admin_token", format!("{:?}", config.server.admin_token)),
+        ("server.max_clients", format!("{:?}", config.server.max_clients)),
+        ("server.origins", format!("{:?}", config.server.origins)),
+        ("server.replay_capacity", format!("{:?}", config.server.replay_capacity)),
+        ("server.client_queue_capacity", format!("{:?}", config.server.client_queue_capacity)),
+        ("server.raw_transactions", format!("{:?}", config.server.raw_transactions)),
+        ("server.heartbeat_interval", format!("{:?}", config.server.heartbeat_interval)),
+        ("server.sessions", format!("{:?}", config.server.sessions)),
+        ("server.broadcast_shards", format!("{:?}", config.server.broadcast_shards)),
+        ("server.stale_events", format!("{:?}", config.server.stale_events)),
+        ("server.event_format", format!("{:?}", config.server.event_format)),
+        ("channels", format!("{:?}", config.channels)),
+        ("reconnect", format!("{:?}", config.reconnect)),
+        ("watchdog", format!("{:?}", config.watchdog)),
+        ("supervisor", format!("{:?}", config.supervisor)),
+        ("mint_dedupe", format!("{:?}", config.mint_dedupe)),
+        ("caches", format!("{:?}", config.caches)),
+        ("health", format!("{:?}", config.health)),
+        ("price_feed", format!("{:?}", config.price_feed)),
+        ("kafka", format!("{:?}", sinks.kafka)),
+        ("nats", format!("{:?}", sinks.nats)),
+        ("discord", format!("{:?}", sinks.discord)),
+        ("telegram", format!("{:?}", sinks.telegram)),
+        ("events_log", format!("{:?}", sinks.events_log)),
+        ("database", format!("{:?}", sinks.postgres)),
+        ("grpc", format!("{:?}", config.grpc)),
+        ("http_api", format!("{:?}", config.http_api)),
+        ("metadata", format!("{:?}", config.metadata)),
+        ("creator_history", format!("{:?}", config.creator_history)),
+        ("parser_self_test", format!("{:?}", config.parser_self_test)),
+        ("curve_tracking", format!("{:?}", config.curve_tracking)),
+        ("curve_snapshot", format!("{:?}", config.curve_snapshot)),
+        ("image_hashing", format!("{:?}", config.image_hashing)),
+        ("max_event_age", format!("{:?}", config.max_event_age)),
+        ("event_signer", format!("{:?}", config.event_signer)),
+        ("dead_letters", format!("{:?}", config.dead_letters)),
+        ("fixtures", format!("{:?}", config.fixtures)),
+        ("simulation", format!("{:?}", config.simulation)),
+    ]
+}
+
+/// `sinks` with every filter cleared, leaving what each sink delivers to and how.
+fn without_filters(sinks: &SinksConfig) -> SinksConfig {
+    let mut sinks = sinks.clone();
+    if let Some(kafka) = &mut sinks.kafka {
+        kafka.filter = None;
+    }
+    if let Some(nats) = &mut sinks.nats {
+        nats.filter = None;
+    }
+    if let Some(postgres) = &mut sinks.postgres {
+        postgres.filter = None;
+    }
+    if let Some(events_log) = &mut sinks.events_log {
+        events_log.filter = None;
+    }
+    if let Some(discord) = &mut sinks.discord {
+        discord.filter = None;
+        for route in &mut discord.routes {
+            route.filter = Default::default();
+        }
+    }
+    if let Some(telegram) = &mut sinks.telegram {
+        telegram.filter = None;
+        for route in &mut telegram.routes {
+            route.filter = Default::default();
+        }
+    }
+    sinks
+}
+
+#[cfg(test)]
+mod tests;
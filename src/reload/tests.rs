@@ -0,0 +1,257 @@
+//! Unit tests for config reloads: what is applied, what is rejected and what gets reported.
+
+
+use super::*;
+use crate::config::{CurveSnapshotConfig, DeadLetterConfig, ListenAddr, ParserSelfTestConfig, SpamConfig};
+use crate::data_models::{EventFormat, FilterCriteria, TokenCreatedEvent};
+use crate::global_filter::Rule;
+use crate::signing::EventSigner;
+use crate::simulation::EventGenerator;
+use crate::rpc_client::SpamSuppression;
+use solana_sdk::signature::Keypair;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// function to create the overrides every test config needs, so the real environment can't make it invalid.
+fn test_overrides() -> HashMap<&'static str, String> {
+    HashMap::from([
+        ("SOLANA_RPC_HTTP_URL", "https://api.mainnet-beta.solana.com".to_string()),
+        ("SOLANA_RPC_WSS_URL", "wss://api.mainnet-beta.solana.com".to_string()),
+        ("WEBSOCKET_SERVER_PORT", "8080".to_string()),
+    ])
+}
+
+/// function to write `contents` to a config file of its own for test `name`.
+fn write_config(name: &str, contents: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pump_fun_monitor_reload_{}_{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.toml");
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+/// function to write a config with one Discord route filtered on `name_contains`.
+fn discord_config(path: &Path, name_contains: &str) {
+    let contents = format!(
+        r#"
+        [[discord.routes]]
+        webhook_url = "https://discord.com/api/webhooks/1/launches"
+        filter = {{ nameContains = "{}" }}
+        "#,
+        name_contains
+    );
+    fs::write(path, contents).unwrap();
+}
+
+/// function to load the config `reloader` would start from.
+fn load(path: Option<&Path>) -> Config {
+    Config::load(path, &test_overrides()).unwrap()
+}
+
+#[test]
+fn test_reload_publishes_a_changed_sink_filter() {
+    let path = write_config("sink_filter", "");
+    discord_config(&path, "DOGE");
+    let reloader = Reloader::new(load(Some(&path)), Some(path.clone()), test_overrides());
+    let mut filters = reloader.sink_filters();
+
+    discord_config(&path, "PEPE");
+    let report = reloader.reload().unwrap();
+
+    assert_eq!(report.changed, vec!["sinks.discord:0.filter"]);
+    assert!(filters.has_changed().unwrap());
+    let expected = FilterCriteria {
+        name_contains: Some("PEPE".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(filters.borrow_and_update()["discord:0"], vec![expected]);
+}
+
+#[test]
+fn test_reload_publishes_changed_presets() {
+    let path = write_config("presets", "[presets.moon]\nnameContains = \"MOON\"");
+    let reloader = Reloader::new(load(Some(&path)), Some(path.clone()), test_overrides());
+    let mut presets = reloader.presets();
+
+    fs::write(&path, "[presets.moon]\nnameContains = \"MOON\"\nmaxSameTxBuyers = 1\n\n[presets.pepe]\nnameContains = \"PEPE\"").unwrap();
+    let report = reloader.reload().unwrap();
+
+    assert_eq!(report.changed, vec!["server.presets"]);
+    assert!(presets.has_changed().unwrap());
+    let presets = presets.borrow_and_update();
+    assert_eq!(presets["moon"].max_same_tx_buyers, Some(1));
+    assert_eq!(presets["pepe"].name_contains.as_deref(), Some("PEPE"));
+}
+
+#[test]
+fn test_reload_without_changes_reports_nothing() {
+    let path = write_config("unchanged", "");
+    discord_config(&path, "DOGE");
+    let reloader = Reloader::new(load(Some(&path)), Some(path), test_overrides());
+    let filters = reloader.sink_filters();
+
+    assert_eq!(reloader.reload().unwrap(), ReloadReport::default());
+    assert!(!filters.has_changed().unwrap());
+}
+
+#[test]
+fn test_reload_applies_limits_spam_and_log_level() {
+    let config = load(None);
+    let levels = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&levels);
+    let reloader = Reloader::new(config.clone(), None, test_overrides()).with_log_level(LogLevelHandle::new(move |level| {
+        recorded.lock().unwrap().push(level.to_string());
+        Ok(())
+    }));
+    let ip_limits = reloader.ip_limits();
+    let spam = reloader.spam();
+
+    let mut reloaded = config;
+    reloaded.server.ip_limits.max_connections_per_ip = 2;
+    reloaded.spam = Some(SpamConfig {
+        suppression: SpamSuppression::Drop,
+        window: Duration::from_secs(60),
+        capacity: 100,
+    });
+    reloaded.log_level = Some("debug".to_string());
+    let report = reloader.apply(reloaded).unwrap();
+
+    assert_eq!(report.changed, vec!["log_level", "server.ip_limits", "spam"]);
+    assert_eq!(ip_limits.borrow().max_connections_per_ip, 2);
+    assert_eq!(spam.borrow().as_ref().map(|spam| spam.suppression), Some(SpamSuppression::Drop));
+    assert_eq!(*levels.lock().unwrap(), vec!["debug"]);
+}
+
+#[test]
+fn test_reload_rejects_startup_settings_and_applies_nothing() {
+    let config = load(None);
+    let reloader = Reloader::new(config.clone(), None, test_overrides());
+    let ip_limits = reloader.ip_limits();
+
+    let mut reloaded = config;
+    reloaded.rpc.http_url = "https://rpc.example.com".to_string();
+    reloaded.server.bind_addr = ListenAddr::Tcp("127.0.0.1:9090".parse().unwrap());
+    reloaded.server.ip_limits.max_connections_per_ip = 2;
+    let error = reloader.apply(reloaded).unwrap_err().to_string();
+
+    assert!(error.contains("cannot be reloaded: rpc.http_url, server.bind_addr"), "{}", error);
+    assert!(!ip_limits.has_changed().unwrap());
+    // the rejected config didn't become the one in effect either
+    assert_eq!(reloader.reload().unwrap(), ReloadReport::default());
+}
+
+#[test]
+fn test_sink_destinations_need_a_restart_but_filters_do_not() {
+    let path = write_config("destinations", "");
+    discord_config(&path, "DOGE");
+    let config = load(Some(&path));
+
+    let mut refiltered = config.clone();
+    if let Some(discord) = &mut refiltered.sinks.discord {
+        discord.filter = Some(FilterCriteria::default());
+        discord.routes[0].filter.name_contains = None;
+    }
+    assert!(restart_required(&config, &refiltered).is_empty());
+
+    let mut redirected = config.clone();
+    if let Some(discord) = &mut redirected.sinks.discord {
+        discord.routes[0].webhook_url = "https://discord.com/api/webhooks/2/elsewhere".to_string();
+    }
+    assert_eq!(restart_required(&config, &redirected), vec!["discord"]);
+}
+
+#[test]
+fn test_startup_only_settings_need_a_restart() {
+    let config = load(None);
+
+    let mut changed = config.clone();
+    changed.event_signer = Some(Arc::new(EventSigner::new(Keypair::new())));
+    assert_eq!(restart_required(&config, &changed), vec!["event_signer"]);
+
+    let mut changed = config.clone();
+    changed.dead_letters = Some(DeadLetterConfig {
+        dir: PathBuf::from("dead_letters"),
+        max_entries: 100,
+        retention: Duration::from_secs(3_600),
+    });
+    assert_eq!(restart_required(&config, &changed), vec!["dead_letters"]);
+
+    let mut changed = config.clone();
+    changed.curve_snapshot = Some(CurveSnapshotConfig { file: PathBuf::from("curves.jsonl"), partitioned: false });
+    assert_eq!(restart_required(&config, &changed), vec!["curve_snapshot"]);
+
+    let mut changed = config.clone();
+    changed.server.event_format = EventFormat::Envelope;
+    assert_eq!(restart_required(&config, &changed), vec!["server.event_format"]);
+
+    let mut changed = config.clone();
+    changed.rpc.http_label = "primary".to_string();
+    assert_eq!(restart_required(&config, &changed), vec!["rpc.http_label"]);
+
+    let mut changed = config.clone();
+    changed.parser_self_test = Some(ParserSelfTestConfig { signatures: 20, min_transactions: 5, interval: None });
+    assert_eq!(restart_required(&config, &changed), vec!["parser_self_test"]);
+}
+
+#[test]
+fn test_reload_reads_the_watchlist_again() {
+    let path = write_config("watchlist", "");
+    let watchlist_path = path.with_file_name("watchlist.csv");
+    fs::write(&watchlist_path, "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P,pump dev,dev\n").unwrap();
+    fs::write(&path, format!("[watchlist]\nfile = {:?}\n", watchlist_path)).unwrap();
+    let reloader = Reloader::new(load(Some(&path)), Some(path.clone()), test_overrides());
+    let mut watchlist = reloader.watchlist();
+    let label = |watchlist: &watch::Receiver<Option<Arc<Watchlist>>>| {
+        watchlist.borrow().as_ref().and_then(|list| list.get("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P").cloned())
+    };
+    assert_eq!(label(&watchlist).unwrap().label, "pump dev");
+
+    fs::write(&watchlist_path, "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P,pump insider,insider\n").unwrap();
+    assert_eq!(reloader.reload().unwrap().changed, vec!["watchlist"]);
+    assert!(watchlist.has_changed().unwrap());
+    assert_eq!(label(&watchlist).unwrap().tags, vec!["insider"]);
+    watchlist.borrow_and_update();
+
+    // a broken file leaves the last good list in effect
+    fs::write(&watchlist_path, "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P,pump dev\nnot-a-key,someone\n").unwrap();
+    let error = reloader.reload().unwrap_err().to_string();
+    assert!(error.contains("line 2: 'not-a-key' is not a base58 public key"), "{}", error);
+    assert!(!watchlist.has_changed().unwrap());
+    assert_eq!(label(&watchlist).unwrap().label, "pump insider");
+}
+
+#[test]
+fn test_reload_swaps_the_global_filter() {
+    let path = write_config("global_filter", "");
+    let blocklist_path = path.with_file_name("blocklist.txt");
+    fs::write(&blocklist_path, "").unwrap();
+    fs::write(&path, format!("[global_filter]\nname_exclude = \"(?i)rug\"\ncreator_blocklist = {:?}\n", blocklist_path))
+        .unwrap();
+    let reloader = Reloader::new(load(Some(&path)), Some(path.clone()), test_overrides());
+    let mut global_filter = reloader.global_filter();
+    let mut event = EventGenerator::new(5).next_event();
+    event.token.name = "Rug Pull".to_string();
+    let check = |global_filter: &watch::Receiver<Option<Arc<GlobalFilter>>>, event: &TokenCreatedEvent| {
+        global_filter.borrow().as_ref().and_then(|filter| filter.check(event))
+    };
+    assert_eq!(check(&global_filter, &event), Some(Rule::NameExclude));
+
+    // a creator added to the blocklist file is picked up without the config changing
+    fs::write(&blocklist_path, format!("{}\n", event.token.creator)).unwrap();
+    assert_eq!(reloader.reload().unwrap().changed, vec!["global_filter"]);
+    assert!(global_filter.has_changed().unwrap());
+    assert_eq!(check(&global_filter, &event), Some(Rule::CreatorBlocklist));
+    global_filter.borrow_and_update();
+
+    // a regex that doesn't compile leaves the last good filter in effect
+    fs::write(&path, "[global_filter]\nname_exclude = \"(rug\"\n").unwrap();
+    let error = reloader.reload().unwrap_err().to_string();
+    assert!(error.contains("GLOBAL_FILTER_NAME_EXCLUDE '(rug' is not a valid regex"), "{}", error);
+    assert!(!global_filter.has_changed().unwrap());
+
+    // removing every rule lets everything through again
+    fs::write(&path, "").unwrap();
+    assert_eq!(reloader.reload().unwrap().changed, vec!["global_filter"]);
+    assert_eq!(check(&global_filter, &event), None);
+}
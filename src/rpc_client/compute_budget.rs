@@ -0,0 +1,81 @@
+//! # Compute Budget
+//!
+//! What a creation transaction paid to land, a hint at how hard the launch was pushed: bots outbid each other with
+//! priority fees, most hand-made launches pay none. `meta.fee` is the whole fee, the base fee of 5,000 lamports per
+//! signature plus the priority fee. The priority fee itself is read from the ComputeBudget program's instructions:
+//! `SetComputeUnitPrice` bids micro-lamports per compute unit, charged for every unit requested with
+//! `SetComputeUnitLimit`, or for the runtime's default of 200,000 per other instruction when no limit is set. The
+//! runtime only reads top-level ComputeBudget instructions, so inner ones are ignored here too.
+
+use solana_sdk::{compute_budget, transaction::VersionedTransaction};
+use solana_transaction_status::UiTransactionStatusMeta;
+
+/// tags of the ComputeBudget instructions read here, the first byte of their data.
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// compute units an instruction is given when the transaction sets no limit.
+const DEFAULT_INSTRUCTION_COMPUTE_UNITS: u64 = 200_000;
+
+/// the most compute units a transaction may request.
+const MAX_COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// the fees and compute units of one transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TransactionCost {
+    /// everything the fee payer was charged, base and priority fee together
+    pub fee_lamports: u64,
+    /// the compute unit price times the compute unit limit; 0 without a `SetComputeUnitPrice`
+    pub priority_fee_lamports: u64,
+    /// compute units the transaction used; `None` when the node doesn't report them
+    pub compute_units: Option<u64>,
+}
+
+/// Reads the fees and compute units of `transaction` from its instructions and status `meta`.
+pub fn transaction_cost(transaction: &VersionedTransaction, meta: &UiTransactionStatusMeta) -> TransactionCost {
+    let account_keys = transaction.message.static_account_keys();
+    let mut unit_limit = None;
+    let mut unit_price = 0;
+    let mut other_instructions = 0;
+    for instruction in transaction.message.instructions() {
+        // program ids are never loaded from lookup tables, so the static keys are enough
+        if account_keys.get(usize::from(instruction.program_id_index)) != Some(&compute_budget::id()) {
+            other_instructions += 1;
+            continue;
+        }
+        match instruction.data.split_first() {
+            Some((&SET_COMPUTE_UNIT_LIMIT, args)) => {
+                if let Some(limit) = read_u32(args) {
+                    unit_limit = Some(u64::from(limit));
+                }
+            }
+            Some((&SET_COMPUTE_UNIT_PRICE, args)) => {
+                if let Some(price) = read_u64(args) {
+                    unit_price = price;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let unit_limit = unit_limit
+        .unwrap_or(other_instructions * DEFAULT_INSTRUCTION_COMPUTE_UNITS)
+        .min(MAX_COMPUTE_UNIT_LIMIT);
+    // the runtime rounds a fraction of a lamport up
+    let priority_fee = (u128::from(unit_price) * u128::from(unit_limit)).div_ceil(MICRO_LAMPORTS_PER_LAMPORT);
+    TransactionCost {
+        fee_lamports: meta.fee,
+        priority_fee_lamports: u64::try_from(priority_fee).unwrap_or(u64::MAX),
+        compute_units: meta.compute_units_consumed.clone().into(),
+    }
+}
+
+fn read_u32(data: &[u8]) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(..4)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8]) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(..8)?.try_into().ok()?))
+}
@@ -0,0 +1,343 @@
+//! # Creator History
+//!
+//! Counts the tokens each creator wallet launched, as seen by this monitor, so events can flag serial deployers. Every
+//! launch is appended to `CREATOR_HISTORY_FILE` as a `{"creator", "mint", "seenAt"}` line and the counts are rebuilt
+//! from it on startup, for at most `CREATOR_CACHE_CAPACITY` creators, the least recently active forgotten first. A
+//! creator seen for the first time can optionally be looked up on chain: the newest `CREATOR_HISTORY_LOOKBACK`
+//! signatures of the wallet are fetched and scanned for earlier creations, which costs one `getTransaction` per
+//! signature.
+//!
+//! The outcome of every launch is tracked as well: a token seen migrating to an AMM pool graduated, and is appended as a
+//! `{"creator", "mint", "graduatedAt"}` line, while one that hasn't `CREATOR_ABANDONED_AFTER_SECS` after its launch
+//! counts as abandoned. Both counts are kept up to date as launches and migrations come in, never looked up per
+//! creation, and a token migrating after it was counted abandoned moves over to graduated.
+
+use super::transport::SolanaRpc;
+use super::{find_create_event, Commitment, ProgramConfig};
+use crate::cache::{BoundedCache, CacheLimits, CacheStats};
+use crate::config::CreatorHistoryConfig;
+use crate::data_models::CreatorStats;
+use crate::error::{MonitorError, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::UiTransactionEncoding;
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tracing::{debug, warn};
+
+/// launches of one creator still waiting for an outcome; older ones no longer count towards either.
+const MAX_PENDING_LAUNCHES: usize = 1_000;
+
+/// one launch by a creator, as stored in the history file.
+///
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Sighting {
+    creator: String,
+    /// missing from lines written before outcomes were tracked; such launches never get one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mint: Option<String>,
+    seen_at: DateTime<Utc>,
+}
+
+/// a launched token that migrated to an AMM pool, as stored in the history file.
+///
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Graduation {
+    creator: String,
+    mint: String,
+    graduated_at: DateTime<Utc>,
+}
+
+/// one line of the history file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HistoryLine {
+    Sighting(Sighting),
+    Graduation(Graduation),
+}
+
+/// a creator's stats, with the launches whose outcome isn't known yet.
+#[derive(Debug, Clone)]
+struct CreatorRecord {
+    stats: CreatorStats,
+    /// mints launched less than `abandoned_after` ago that haven't graduated, oldest first
+    pending: VecDeque<(String, DateTime<Utc>)>,
+}
+
+impl CreatorRecord {
+    /// counts the pending launches older than `abandoned_after` at `now` as abandoned.
+    fn settle(&mut self, now: DateTime<Utc>, abandoned_after: chrono::Duration) {
+        while self.pending.front().is_some_and(|(_, seen_at)| *seen_at + abandoned_after <= now) {
+            self.pending.pop_front();
+            self.stats.tokens_abandoned = self.stats.tokens_abandoned.saturating_add(1);
+        }
+        self.stats.graduation_rate = graduation_rate(&self.stats);
+    }
+}
+
+/// `tokens_graduated` out of the tokens graduated or abandoned, `None` while there are none.
+fn graduation_rate(stats: &CreatorStats) -> Option<f32> {
+    let decided = stats.tokens_graduated + stats.tokens_abandoned;
+    (decided > 0).then(|| stats.tokens_graduated as f32 / decided as f32)
+}
+
+/// everything the history keeps in memory, behind one lock.
+struct Creators {
+    records: BoundedCache<String, CreatorRecord>,
+    /// the creator of every launch with a known mint, for when it graduates
+    mints: BoundedCache<String, String>,
+    abandoned_after: chrono::Duration,
+}
+
+impl Creators {
+    /// folds one launch into its creator's record and returns the creator's updated stats, now the most recently
+    /// active.
+    fn add_sighting(&mut self, sighting: Sighting) -> CreatorStats {
+        let now = Instant::now();
+        let mut record = match self.records.remove(&sighting.creator) {
+            Some(mut record) => {
+                record.stats.tokens_created_seen = record.stats.tokens_created_seen.saturating_add(1);
+                record.stats.first_seen = record.stats.first_seen.min(sighting.seen_at);
+                record.stats.last_seen = record.stats.last_seen.max(sighting.seen_at);
+                record
+            }
+            None => CreatorRecord {
+                stats: CreatorStats {
+                    tokens_created_seen: 1,
+                    first_seen: sighting.seen_at,
+                    last_seen: sighting.seen_at,
+                    tokens_graduated: 0,
+                    tokens_abandoned: 0,
+                    graduation_rate: None,
+                },
+                pending: VecDeque::new(),
+            },
+        };
+        if let Some(mint) = sighting.mint {
+            // launches found by the lookback come in older than the ones already recorded
+            let index = record.pending.partition_point(|(_, seen_at)| *seen_at <= sighting.seen_at);
+            record.pending.insert(index, (mint.clone(), sighting.seen_at));
+            if record.pending.len() > MAX_PENDING_LAUNCHES {
+                record.pending.pop_front();
+            }
+            self.mints.insert(mint, sighting.creator.clone(), now);
+        }
+        record.settle(record.stats.last_seen, self.abandoned_after);
+        let stats = record.stats.clone();
+        self.records.insert(sighting.creator, record, now);
+        stats
+    }
+
+    /// folds the graduation of a launch into its creator's record, returning the creator's updated stats; `None` when
+    /// the creator has been forgotten.
+    fn add_graduation(&mut self, graduation: Graduation) -> Option<CreatorStats> {
+        let now = Instant::now();
+        self.mints.remove(&graduation.mint);
+        let mut record = self.records.remove(&graduation.creator)?;
+        match record.pending.iter().position(|(mint, _)| *mint == graduation.mint) {
+            Some(index) => {
+                record.pending.remove(index);
+            }
+            // past the window, so it was counted abandoned
+            None => record.stats.tokens_abandoned = record.stats.tokens_abandoned.saturating_sub(1),
+        }
+        record.stats.tokens_graduated = record.stats.tokens_graduated.saturating_add(1);
+        record.settle(graduation.graduated_at, self.abandoned_after);
+        let stats = record.stats.clone();
+        self.records.insert(graduation.creator, record, now);
+        Some(stats)
+    }
+}
+
+/// Launch counts and outcomes per creator, optionally persisted to an append-only file.
+///
+/// Write failures are logged and otherwise ignored, like fixture recording: a full disk must not stop broadcasting.
+pub struct CreatorHistory {
+    creators: Mutex<Creators>,
+    file: Option<Mutex<File>>,
+    lookback_signatures: usize,
+}
+
+impl CreatorHistory {
+    /// loads the history file, if one is configured, and keeps it open for appending; stats are kept for at most
+    /// `capacity` creators, and the creators of at most `capacity` mints.
+    pub fn open(config: &CreatorHistoryConfig, capacity: usize) -> Result<Self> {
+        let mut creators = Creators {
+            records: BoundedCache::new(CacheLimits::capacity(capacity)),
+            mints: BoundedCache::new(CacheLimits::capacity(capacity)),
+            abandoned_after: chrono::Duration::seconds(config.abandoned_after.as_secs() as i64),
+        };
+        let file = match &config.file {
+            Some(path) => {
+                let io_error =
+                    |e: std::io::Error| MonitorError::Config(format!("CREATOR_HISTORY_FILE '{}': {}", path.display(), e));
+                if path.exists() {
+                    let reader = BufReader::new(File::open(path).map_err(io_error)?);
+                    for (index, line) in reader.lines().enumerate() {
+                        let line = line.map_err(io_error)?;
+                        if line.trim().is_empty() {
+                            continue;
+                        }
+                        match serde_json::from_str::<HistoryLine>(&line) {
+                            Ok(HistoryLine::Sighting(sighting)) => {
+                                creators.add_sighting(sighting);
+                            }
+                            Ok(HistoryLine::Graduation(graduation)) => {
+                                creators.add_graduation(graduation);
+                            }
+                            Err(e) => warn!("Skipping {} line {}: {}", path.display(), index + 1, e),
+                        }
+                    }
+                }
+                let file = OpenOptions::new().create(true).append(true).open(path).map_err(io_error)?;
+                Some(Mutex::new(file))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            creators: Mutex::new(creators),
+            file,
+            lookback_signatures: config.lookback_signatures,
+        })
+    }
+
+    /// Records the launch of `mint` by `creator` and returns the creator's stats including it.
+    ///
+    /// A creator without any recorded launch is first looked up on chain when the lookback is on; `signature` is the
+    /// launch being recorded, so the lookback doesn't count it twice.
+    pub async fn record(
+        &self,
+        rpc: &dyn SolanaRpc,
+        programs: &[ProgramConfig],
+        creator: &str,
+        mint: &str,
+        signature: &str,
+        seen_at: DateTime<Utc>,
+    ) -> CreatorStats {
+        if self.lookback_signatures > 0 && !self.lock().records.contains_key(creator, Instant::now()) {
+            for earlier in self.lookback(rpc, programs, creator, signature).await {
+                self.add(earlier);
+            }
+        }
+        self.add(Sighting {
+            creator: creator.to_string(),
+            mint: Some(mint.to_string()),
+            seen_at,
+        })
+    }
+
+    /// Records that `mint` migrated to an AMM pool at `graduated_at` and returns its creator's updated stats; `None`
+    /// when the launch wasn't seen by this monitor or its creator has been forgotten since.
+    pub fn record_graduation(&self, mint: &str, graduated_at: DateTime<Utc>) -> Option<CreatorStats> {
+        let mut creators = self.lock();
+        let creator = creators.mints.get(mint, Instant::now())?.clone();
+        let graduation = Graduation {
+            creator,
+            mint: mint.to_string(),
+            graduated_at,
+        };
+        self.append(&graduation);
+        creators.add_graduation(graduation)
+    }
+
+    /// earlier launches by `creator` among its newest signatures; lookup failures just end the lookback early.
+    async fn lookback(&self, rpc: &dyn SolanaRpc, programs: &[ProgramConfig], creator: &str, signature: &str) -> Vec<Sighting> {
+        let Ok(creator_key) = Pubkey::from_str(creator) else {
+            return Vec::new();
+        };
+        let statuses = match rpc.get_signatures_for_address(&creator_key, self.lookback_signatures).await {
+            Ok(statuses) => statuses,
+            Err(e) => {
+                warn!("Creator history lookback for {} failed: {}", creator, e);
+                return Vec::new();
+            }
+        };
+
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(Commitment::Confirmed.transaction_commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+        let mut sightings = Vec::new();
+        for status in statuses.into_iter().filter(|status| status.err.is_none() && status.signature != signature) {
+            let Ok(earlier) = Signature::from_str(&status.signature) else {
+                continue;
+            };
+            let transaction = match rpc.get_transaction(&earlier, config).await {
+                Ok(transaction) => transaction,
+                Err(e) => {
+                    warn!("Creator history lookback for {} stopped at {}: {}", creator, earlier, e);
+                    break;
+                }
+            };
+            let logs: Option<Vec<String>> = transaction
+                .transaction
+                .meta
+                .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages));
+            let created = logs.as_deref().and_then(|logs| {
+                programs
+                    .iter()
+                    .filter_map(|program| find_create_event(logs, program))
+                    .find(|event| event.user == creator_key.to_bytes())
+            });
+            let seen_at = transaction
+                .block_time
+                .or(status.block_time)
+                .and_then(|block_time| DateTime::from_timestamp(block_time, 0));
+            if let (Some(event), Some(seen_at)) = (created, seen_at) {
+                sightings.push(Sighting {
+                    creator: creator.to_string(),
+                    mint: Some(Pubkey::new_from_array(event.mint).to_string()),
+                    seen_at,
+                });
+            }
+        }
+        debug!(
+            "Creator {} launched {} tokens within its last {} signatures",
+            creator,
+            sightings.len(),
+            self.lookback_signatures
+        );
+        sightings
+    }
+
+    fn add(&self, sighting: Sighting) -> CreatorStats {
+        self.append(&sighting);
+        self.lock().add_sighting(sighting)
+    }
+
+    /// appends `line` to the history file, if there is one.
+    fn append(&self, line: &impl Serialize) {
+        if let Some(file) = &self.file {
+            let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+            let written = serde_json::to_string(line)
+                .map_err(std::io::Error::from)
+                .and_then(|json| writeln!(file, "{}", json));
+            if let Err(e) = written {
+                warn!("Failed to write creator history: {}", e);
+            }
+        }
+    }
+
+    pub fn cache_stats(&self) -> Arc<CacheStats> {
+        self.lock().records.stats()
+    }
+
+    /// the counters of the mints kept for graduations, to register with the metrics.
+    pub fn mint_cache_stats(&self) -> Arc<CacheStats> {
+        self.lock().mints.stats()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Creators> {
+        self.creators.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,292 @@
+//! # Dead Letters
+//!
+//! Transactions the processor gave up on, kept on disk so they can be run through the parser again after a fix. The
+//! dead-letter directory holds one `<signature>.json` per failed signature with the error, when it failed and, when the
+//! transaction had been fetched, the `getTransaction` response (base64 encoded transaction).
+//!
+//! The directory is bounded: letters older than `DEAD_LETTER_RETENTION_HOURS` are removed, and past
+//! `DEAD_LETTER_MAX_ENTRIES` the oldest go first. `--reprocess-dead-letters` serves each letter's stored transaction to
+//! the current parser in place of a fresh fetch; account lookups still go to the RPC.
+
+use super::transport::SolanaRpc;
+use crate::config::DeadLetterConfig;
+use crate::error::{MonitorError, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::UiDataSliceConfig;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_client::rpc_filter::RpcFilterType;
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::warn;
+
+/// a transaction that failed processing, as stored on disk.
+///
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetter {
+    pub signature: String,
+    /// the error's message
+    pub error: String,
+    /// the error's `MonitorError::code`
+    pub code: String,
+    pub failed_at: DateTime<Utc>,
+    /// the `getTransaction` response; absent when the fetch itself failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<EncodedConfirmedTransactionWithStatusMeta>,
+}
+
+impl DeadLetter {
+    pub fn new(signature: Signature, error: &MonitorError, transaction: Option<EncodedConfirmedTransactionWithStatusMeta>) -> Self {
+        Self {
+            signature: signature.to_string(),
+            error: error.to_string(),
+            code: error.code().to_string(),
+            failed_at: Utc::now(),
+            transaction,
+        }
+    }
+}
+
+/// The dead-letter directory.
+///
+/// Write failures are logged and otherwise ignored: a full disk must never take the live monitor down.
+pub struct DeadLetters {
+    dir: PathBuf,
+    max_entries: usize,
+    retention: Duration,
+}
+
+impl DeadLetters {
+    /// creates the directory if it doesn't exist yet.
+    pub fn open(config: &DeadLetterConfig) -> Result<Self> {
+        fs::create_dir_all(&config.dir)
+            .map_err(|e| MonitorError::Config(format!("DEAD_LETTER_DIR '{}': {}", config.dir.display(), e)))?;
+        Ok(Self {
+            dir: config.dir.clone(),
+            max_entries: config.max_entries,
+            retention: config.retention,
+        })
+    }
+
+    /// stores `letter`, replacing an earlier one for the same signature, and removes whatever is now past the bounds.
+    pub fn write(&self, letter: &DeadLetter) {
+        let path = self.path(&letter.signature);
+        let written = serde_json::to_vec_pretty(letter)
+            .map_err(std::io::Error::from)
+            .and_then(|json| fs::write(&path, json));
+        if let Err(e) = written {
+            warn!("Failed to write dead letter {}: {}", path.display(), e);
+        }
+        self.prune();
+    }
+
+    /// every stored letter, oldest first; files that can't be read are logged and skipped.
+    pub fn read_all(&self) -> Vec<DeadLetter> {
+        let mut letters: Vec<DeadLetter> = self
+            .files()
+            .into_iter()
+            .filter_map(|path| match read_letter(&path) {
+                Ok(letter) => Some(letter),
+                Err(e) => {
+                    warn!("Skipping dead letter {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .collect();
+        letters.sort_by_key(|letter| letter.failed_at);
+        letters
+    }
+
+    /// deletes the letter for `signature`, once it went through.
+    pub fn remove(&self, signature: &str) {
+        let path = self.path(signature);
+        if let Err(e) = fs::remove_file(&path) {
+            warn!("Failed to remove dead letter {}: {}", path.display(), e);
+        }
+    }
+
+    /// removes letters older than the retention, then the oldest of those beyond `max_entries`.
+    ///
+    /// Ages come from the files' modification times, so pruning doesn't have to parse every letter.
+    fn prune(&self) {
+        let now = std::time::SystemTime::now();
+        let mut files: Vec<(std::time::SystemTime, PathBuf)> = self
+            .files()
+            .into_iter()
+            .filter_map(|path| Some((fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?, path)))
+            .collect();
+        files.sort();
+        let expired = files
+            .iter()
+            .take_while(|(modified, _)| now.duration_since(*modified).unwrap_or_default() > self.retention)
+            .count();
+        let excess = files.len().saturating_sub(self.max_entries);
+        for (_, path) in files.iter().take(expired.max(excess)) {
+            if let Err(e) = fs::remove_file(path) {
+                warn!("Failed to remove dead letter {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    fn files(&self) -> Vec<PathBuf> {
+        match fs::read_dir(&self.dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+                .collect(),
+            Err(e) => {
+                warn!("Failed to list dead letters in {}: {}", self.dir.display(), e);
+                Vec::new()
+            }
+        }
+    }
+
+    fn path(&self, signature: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", signature))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+fn read_letter(path: &Path) -> std::result::Result<DeadLetter, String> {
+    let contents = fs::read(path).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&contents).map_err(|e| e.to_string())
+}
+
+/// `SolanaRpc` that forwards to `inner` and remembers the last transaction fetched, so a failure can be filed with it.
+///
+pub struct CapturingRpc {
+    inner: Arc<dyn SolanaRpc>,
+    last: Mutex<Option<(Signature, EncodedConfirmedTransactionWithStatusMeta)>>,
+}
+
+impl CapturingRpc {
+    pub fn new(inner: Arc<dyn SolanaRpc>) -> Self {
+        Self {
+            inner,
+            last: Mutex::new(None),
+        }
+    }
+
+    /// the transaction fetched for `signature`, if it was the last one fetched.
+    pub fn take(&self, signature: &Signature) -> Option<EncodedConfirmedTransactionWithStatusMeta> {
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        match last.take() {
+            Some((fetched, transaction)) if fetched == *signature => Some(transaction),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl SolanaRpc for CapturingRpc {
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        let transaction = self.inner.get_transaction(signature, config).await?;
+        *self.last.lock().unwrap_or_else(|e| e.into_inner()) = Some((*signature, copy_transaction(&transaction)));
+        Ok(transaction)
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Account> {
+        self.inner.get_account(pubkey).await
+    }
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.inner.get_multiple_accounts(pubkeys).await
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.inner.get_signatures_for_address(address, limit).await
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program: &Pubkey,
+        filters: Vec<RpcFilterType>,
+        data_slice: Option<UiDataSliceConfig>,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        self.inner.get_program_accounts(program, filters, data_slice).await
+    }
+}
+
+/// `SolanaRpc` serving a dead letter's stored transaction and forwarding everything else to `inner`.
+///
+pub struct StoredTransactionRpc {
+    inner: Arc<dyn SolanaRpc>,
+    signature: Signature,
+    transaction: EncodedConfirmedTransactionWithStatusMeta,
+}
+
+impl StoredTransactionRpc {
+    pub fn new(inner: Arc<dyn SolanaRpc>, signature: Signature, transaction: EncodedConfirmedTransactionWithStatusMeta) -> Self {
+        Self {
+            inner,
+            signature,
+            transaction,
+        }
+    }
+}
+
+#[async_trait]
+impl SolanaRpc for StoredTransactionRpc {
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        if *signature == self.signature {
+            return Ok(copy_transaction(&self.transaction));
+        }
+        self.inner.get_transaction(signature, config).await
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Account> {
+        self.inner.get_account(pubkey).await
+    }
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.inner.get_multiple_accounts(pubkeys).await
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.inner.get_signatures_for_address(address, limit).await
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program: &Pubkey,
+        filters: Vec<RpcFilterType>,
+        data_slice: Option<UiDataSliceConfig>,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        self.inner.get_program_accounts(program, filters, data_slice).await
+    }
+}
+
+/// `EncodedConfirmedTransactionWithStatusMeta` is not `Clone`, though every field is.
+pub fn copy_transaction(transaction: &EncodedConfirmedTransactionWithStatusMeta) -> EncodedConfirmedTransactionWithStatusMeta {
+    EncodedConfirmedTransactionWithStatusMeta {
+        slot: transaction.slot,
+        transaction: transaction.transaction.clone(),
+        block_time: transaction.block_time,
+    }
+}
@@ -0,0 +1,93 @@
+//! # Dedupe
+//!
+//! A transaction that mentions several monitored programs is notified once per subscription, and once per endpoint when
+//! several are subscribed at the same time. The readers share the most recent signatures so each transaction is only
+//! processed once, on whichever endpoint delivered it first.
+//!
+//! Signatures don't catch everything: a reconnect replays notifications into a fresh reader, and a retried or duplicate
+//! transaction can report the same creation again. `SeenMints` is consulted right before broadcasting, for the whole
+//! process lifetime, so each mint goes out once within its TTL whichever path found it.
+
+use crate::cache::{BoundedCache, CacheLimits, CacheStats};
+use crate::config::MintDedupeConfig;
+use solana_sdk::signature::Signature;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Bounded set of recently seen signatures, forgetting the oldest first; `SIGNATURE_CACHE_CAPACITY` of them, a few
+/// seconds of traffic at pump.fun's peak rates by default.
+///
+pub struct RecentSignatures {
+    seen: BoundedCache<Signature, ()>,
+}
+
+impl RecentSignatures {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            seen: BoundedCache::new(CacheLimits::capacity(capacity)),
+        }
+    }
+
+    /// `true` the first time `signature` is seen within the window.
+    pub fn insert(&mut self, signature: Signature) -> bool {
+        let now = Instant::now();
+        if self.seen.contains_key(&signature, now) {
+            return false;
+        }
+        self.seen.insert(signature, (), now);
+        true
+    }
+
+    pub fn cache_stats(&self) -> Arc<CacheStats> {
+        self.seen.stats()
+    }
+}
+
+/// Mint addresses broadcast within the last `ttl`, bounded to `capacity` entries.
+///
+pub struct SeenMints {
+    seen: Mutex<BoundedCache<String, ()>>,
+}
+
+impl SeenMints {
+    pub fn new(config: &MintDedupeConfig) -> Self {
+        Self {
+            seen: Mutex::new(BoundedCache::new(CacheLimits {
+                capacity: config.capacity,
+                ttl: Some(config.ttl),
+            })),
+        }
+    }
+
+    /// `true` if `mint` wasn't seen within the TTL, recording it as seen at `now`.
+    ///
+    /// a repeated sighting doesn't extend the window; the mint may go out again once the TTL after its first sighting
+    /// has passed, or once `capacity` newer mints pushed it out.
+    pub fn first_sighting(&self, mint: &str, now: Instant) -> bool {
+        let mut seen = self.lock();
+        if seen.contains_key(mint, now) {
+            return false;
+        }
+        seen.insert(mint.to_string(), (), now);
+        true
+    }
+
+    /// whether `mint` was seen within the TTL, without recording it; previews are checked with this, so only the
+    /// broadcast records a mint.
+    pub fn contains(&self, mint: &str, now: Instant) -> bool {
+        self.lock().contains_key(mint, now)
+    }
+
+    /// mints currently remembered, expired ones included until the next sighting clears them.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn cache_stats(&self) -> Arc<CacheStats> {
+        self.lock().stats()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BoundedCache<String, ()>> {
+        self.seen.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,82 @@
+//! # Diagnostics
+//!
+//! The processor's decisions that never reach clients as events: transactions skipped because they create no token,
+//! repeat a mint or fail a filter, and transactions that failed processing or couldn't be fetched. They go out on a
+//! broadcast channel of their own, which never waits and so can't hold up an event, metered to
+//! `DIAGNOSTICS_MAX_PER_SEC` so a flood of failures costs the server little; what is over the rate is only counted.
+
+use crate::data_models::{DiagnosticEvent, DiagnosticKind};
+use crate::error::MonitorError;
+use crate::metrics::Metrics;
+use chrono::Utc;
+use solana_sdk::signature::Signature;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+
+/// Publishes the processor's diagnostics, at most `max_per_sec` a second with bursts of as many.
+///
+/// Without a channel, or while nobody subscribes, nothing is built or counted.
+pub(crate) struct Diagnostics {
+    sender: Option<broadcast::Sender<DiagnosticEvent>>,
+    max_per_sec: f64,
+    tokens: f64,
+    refilled_at: Instant,
+    metrics: Arc<Metrics>,
+}
+
+impl Diagnostics {
+    pub fn new(sender: Option<broadcast::Sender<DiagnosticEvent>>, max_per_sec: u32, metrics: Arc<Metrics>) -> Self {
+        let max_per_sec = f64::from(max_per_sec);
+        Self {
+            sender,
+            max_per_sec,
+            tokens: max_per_sec,
+            refilled_at: Instant::now(),
+            metrics,
+        }
+    }
+
+    /// `signature` was processed but nothing was broadcast, for `reason`.
+    pub fn skipped(&mut self, signature: &Signature, reason: impl FnOnce() -> String) {
+        self.publish(signature, || DiagnosticKind::Skipped { reason: reason() });
+    }
+
+    /// processing `signature` failed with an error that won't go away.
+    pub fn parse_error(&mut self, signature: &Signature, error: &MonitorError) {
+        self.publish(signature, || DiagnosticKind::ParseError {
+            code: error.code().to_string(),
+            error: error.to_string(),
+        });
+    }
+
+    /// `signature` couldn't be fetched in `attempts` tries, the last failing with `error`.
+    pub fn missed(&mut self, signature: &Signature, attempts: u32, error: &MonitorError) {
+        self.publish(signature, || DiagnosticKind::Missed { attempts, error: error.to_string() });
+    }
+
+    fn publish(&mut self, signature: &Signature, kind: impl FnOnce() -> DiagnosticKind) {
+        let Some(sender) = self.sender.as_ref().filter(|sender| sender.receiver_count() > 0) else {
+            return;
+        };
+        let now = Instant::now();
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.max_per_sec).min(self.max_per_sec);
+        self.refilled_at = now;
+        if self.tokens < 1.0 {
+            self.metrics.diagnostics_dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        self.tokens -= 1.0;
+        let diagnostic = DiagnosticEvent {
+            timestamp: Utc::now(),
+            signature: signature.to_string(),
+            kind: kind(),
+        };
+        // subscribers that unsubscribed since the check just miss it
+        if sender.send(diagnostic).is_ok() {
+            self.metrics.diagnostics_sent.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! # Initial Buy Detection
+//!
+//! Most creations are bundled with a Buy of the new token by its creator, and the size of that dev buy is a strong signal.
+//! Other wallets buying inside the very same transaction is a stronger one: that only happens when the launch was
+//! bundled with snipers. Buys are found among the top-level and inner instructions of the creation transaction by
+//! their discriminator and mint, and attributed to the wallet in their user account.
+//!
+//! A Buy's `amount` argument is the token amount asked for, while the SOL paid into the bonding curve only shows up as
+//! the System transfers the Buy makes from the buyer to the curve; the pump.fun fee goes to the fee recipient and isn't
+//! counted. The creator's token balance change in the transaction meta is the final word on the tokens received, since
+//! the instruction only states what was asked for.
+
+use super::ProgramConfig;
+use crate::data_models::InitialBuy;
+use solana_sdk::{pubkey::Pubkey, system_program, transaction::VersionedTransaction};
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{UiInstruction, UiTransactionStatusMeta, UiTransactionTokenBalance};
+use std::collections::HashMap;
+use std::str::FromStr;
+use tracing::debug;
+
+/// positions of the mint and the buyer in the Buy instruction's account list.
+const BUY_ACCOUNT_MINT: usize = 2;
+const BUY_ACCOUNT_USER: usize = 6;
+
+/// the System program's `Transfer` instruction, a little-endian u32 tag followed by the lamports.
+const SYSTEM_TRANSFER_TAG: [u8; 4] = [2, 0, 0, 0];
+
+/// one instruction with its accounts resolved, whether top-level or inner.
+pub(crate) struct ResolvedInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<Pubkey>,
+    pub data: Vec<u8>,
+}
+
+/// the buys of a new token made within its creation transaction.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CreationBuys {
+    /// the creator's own buy, if any
+    pub initial_buy: Option<InitialBuy>,
+    /// distinct wallets that bought, the creator included
+    pub buyers: u32,
+    /// lamports all those wallets paid into the bonding curve
+    pub sol: u64,
+}
+
+/// Finds every Buy of `mint` in its creation transaction, singling out the creator's.
+///
+/// # Arguments
+/// * `transaction` - the decoded creation transaction
+/// * `meta` - its status meta, for inner instructions, loaded addresses and token balances
+/// * `program` - the launchpad program that created the token
+/// * `mint`, `bonding_curve`, `creator` - the accounts of the creation
+pub fn find_creation_buys(
+    transaction: &VersionedTransaction,
+    meta: &UiTransactionStatusMeta,
+    program: &ProgramConfig,
+    mint: &Pubkey,
+    bonding_curve: &Pubkey,
+    creator: &Pubkey,
+) -> CreationBuys {
+    let instructions = resolve_instructions(transaction, meta);
+
+    // tokens asked for per buyer
+    let mut requested: HashMap<Pubkey, u64> = HashMap::new();
+    for instruction in &instructions {
+        if instruction.program_id != program.id
+            || !instruction.data.starts_with(&program.buy_instruction)
+            || instruction.accounts.get(BUY_ACCOUNT_MINT) != Some(mint)
+        {
+            continue;
+        }
+        let buyer = instruction.accounts.get(BUY_ACCOUNT_USER);
+        let (Some(&buyer), Some(amount)) = (buyer, read_u64(&instruction.data, 8)) else {
+            continue;
+        };
+        let total = requested.entry(buyer).or_default();
+        *total = total.saturating_add(amount);
+    }
+
+    // lamports paid into the curve per buyer
+    let mut paid: HashMap<Pubkey, u64> = HashMap::new();
+    for instruction in &instructions {
+        if instruction.program_id != system_program::ID
+            || !instruction.data.starts_with(&SYSTEM_TRANSFER_TAG)
+            || instruction.accounts.get(1) != Some(bonding_curve)
+        {
+            continue;
+        }
+        let (Some(&from), Some(lamports)) = (instruction.accounts.first(), read_u64(&instruction.data, 4)) else {
+            continue;
+        };
+        if requested.contains_key(&from) {
+            let total = paid.entry(from).or_default();
+            *total = total.saturating_add(lamports);
+        }
+    }
+
+    let initial_buy = requested.get(creator).map(|&requested_tokens| {
+        let token_amount = match token_balance_change(meta, mint, creator) {
+            Some(received) if received != requested_tokens => {
+                debug!(requested_tokens, received, "Initial buy token amount differs from the Buy instruction");
+                received
+            }
+            _ => requested_tokens,
+        };
+        InitialBuy {
+            sol_amount: paid.get(creator).copied().unwrap_or(0),
+            token_amount,
+        }
+    });
+    CreationBuys {
+        initial_buy,
+        buyers: u32::try_from(requested.len()).unwrap_or(u32::MAX),
+        sol: paid.values().fold(0u64, |total, &lamports| total.saturating_add(lamports)),
+    }
+}
+
+/// every top-level and inner instruction of the transaction, skipping any that reference unknown accounts.
+pub(crate) fn resolve_instructions(transaction: &VersionedTransaction, meta: &UiTransactionStatusMeta) -> Vec<ResolvedInstruction> {
+    // v0 transactions index into the static keys followed by the loaded writable, then readonly, addresses
+    let mut keys = transaction.message.static_account_keys().to_vec();
+    if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+        keys.extend(
+            loaded
+                .writable
+                .iter()
+                .chain(&loaded.readonly)
+                .filter_map(|address| Pubkey::from_str(address).ok()),
+        );
+    }
+    let resolve = |program_id_index: u8, accounts: &[u8], data: Vec<u8>| {
+        Some(ResolvedInstruction {
+            program_id: *keys.get(program_id_index as usize)?,
+            accounts: accounts
+                .iter()
+                .map(|&index| keys.get(index as usize).copied())
+                .collect::<Option<_>>()?,
+            data,
+        })
+    };
+
+    let mut instructions: Vec<ResolvedInstruction> = transaction
+        .message
+        .instructions()
+        .iter()
+        .filter_map(|instruction| resolve(instruction.program_id_index, &instruction.accounts, instruction.data.clone()))
+        .collect();
+    if let OptionSerializer::Some(inner) = &meta.inner_instructions {
+        for instruction in inner.iter().flat_map(|inner| &inner.instructions) {
+            let UiInstruction::Compiled(compiled) = instruction else {
+                continue;
+            };
+            let Ok(data) = bs58::decode(&compiled.data).into_vec() else {
+                continue;
+            };
+            instructions.extend(resolve(compiled.program_id_index, &compiled.accounts, data));
+        }
+    }
+    instructions
+}
+
+/// how many tokens of `mint` the creator's token accounts gained in the transaction, when the meta reports balances.
+fn token_balance_change(meta: &UiTransactionStatusMeta, mint: &Pubkey, creator: &Pubkey) -> Option<u64> {
+    let (mint, creator) = (mint.to_string(), creator.to_string());
+    let held = |balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>| -> Option<u64> {
+        let OptionSerializer::Some(balances) = balances else {
+            return None;
+        };
+        Some(
+            balances
+                .iter()
+                .filter(|balance| balance.mint == mint && balance.owner == OptionSerializer::Some(creator.clone()))
+                .filter_map(|balance| balance.ui_token_amount.amount.parse::<u64>().ok())
+                .sum(),
+        )
+    };
+    let after = held(&meta.post_token_balances)?;
+    let before = held(&meta.pre_token_balances).unwrap_or(0);
+    after.checked_sub(before).filter(|&received| received > 0)
+}
+
+/// the little-endian u64 at `offset`, if the data is long enough.
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
@@ -0,0 +1,77 @@
+//! # Program Log Parsing
+//!
+//! pump.fun (and launchpads forked from it) emit an Anchor `CreateEvent` as a base64 "Program data:" log line carrying the mint, bonding curve, creator and metadata of a new token. Reading it from the logs works no matter how the Create instruction was reached (directly or through another program's CPI) and without relying on account positions in the instruction. Migrations log a `CompletePumpAmmMigrationEvent` the same way.
+
+use super::ProgramConfig;
+use crate::data_models::{CreateEventData, MigrationEventData};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use borsh::BorshDeserialize;
+use solana_sdk::pubkey::Pubkey;
+use tracing::debug;
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+/// Finds and decodes the first `CreateEvent` logged by `program`, recognised by its `create_event` discriminator.
+///
+/// The invocation stack is tracked from the "invoke"/"success"/"failed" lines so that data logged by any other program, which could carry a forged event, is ignored.
+pub fn find_create_event(logs: &[String], program: &ProgramConfig) -> Option<CreateEventData> {
+    find_program_event(logs, &program.id, &program.create_event, "CreateEvent")
+}
+
+/// Finds and decodes the first migration event logged by `program`, recognised by its `migration_event` discriminator.
+pub fn find_migration_event(logs: &[String], program: &ProgramConfig) -> Option<MigrationEventData> {
+    find_program_event(logs, &program.id, &program.migration_event, "CompletePumpAmmMigrationEvent")
+}
+
+/// the first well-formed event starting with `discriminator` among the data logged by `program_id` itself.
+fn find_program_event<T: BorshDeserialize>(
+    logs: &[String],
+    program_id: &Pubkey,
+    discriminator: &[u8; 8],
+    name: &str,
+) -> Option<T> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+
+    for line in logs {
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA_PREFIX) {
+            if stack.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            if let Some(event) = decode_event(data, discriminator, name) {
+                return Some(event);
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split_whitespace();
+            let (Some(program), Some(action)) = (words.next(), words.next()) else {
+                continue;
+            };
+            // "Program log:", "Program return:" and the like are output of the running program, not invocations
+            if program.ends_with(':') {
+                continue;
+            }
+            match action {
+                "invoke" => stack.push(program),
+                "success" | "failed:" => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+/// decodes one "Program data:" payload, `None` unless it is a well-formed event with `discriminator`.
+fn decode_event<T: BorshDeserialize>(data: &str, discriminator: &[u8; 8], name: &str) -> Option<T> {
+    let bytes = BASE64.decode(data.trim()).ok()?;
+    let payload = bytes.strip_prefix(&discriminator[..])?;
+    // not `try_from_slice`: trailing fields added by newer program versions are expected
+    match T::deserialize(&mut &payload[..]) {
+        Ok(event) => Some(event),
+        Err(e) => {
+            debug!("Malformed {} in program logs: {}", name, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! # Migration Detection
+//!
+//! Once a bonding curve completes, pump.fun's Migrate instruction moves its tokens and SOL into a fresh PumpSwap pool,
+//! creating the pool in the same transaction. The launchpad logs a `CompletePumpAmmMigrationEvent` naming the mint,
+//! the curve and the pool, which is read first; without it the Migrate instruction itself is read, top-level or inner,
+//! relying on its account order. Either way the pool's opening reserves come from the post token balances of the
+//! accounts the pool owns, since the instruction carries no amounts.
+
+use super::initial_buy::resolve_instructions;
+use super::logs::find_migration_event;
+use super::ProgramConfig;
+use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::UiTransactionStatusMeta;
+use tracing::debug;
+
+/// positions of the accounts the monitor reads in the Migrate instruction's account list, used only when the logs
+/// carry no migration event.
+const MIGRATE_ACCOUNT_MINT: usize = 2;
+const MIGRATE_ACCOUNT_BONDING_CURVE: usize = 3;
+const MIGRATE_ACCOUNT_POOL_PROGRAM: usize = 8;
+const MIGRATE_ACCOUNT_POOL: usize = 9;
+
+/// a completed curve moved into an AMM pool, as read from the migration transaction.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migration {
+    /// the monitored program the curve belonged to
+    pub program: ProgramConfig,
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub pool: Pubkey,
+    pub pool_program: Pubkey,
+    /// tokens the pool holds after the transaction
+    pub token_reserves: Option<u64>,
+    /// lamports of wrapped SOL the pool holds after the transaction
+    pub sol_reserves: Option<u64>,
+}
+
+/// Finds the migration of a curve of one of `programs` that track migrations, `None` when the transaction isn't one.
+pub fn find_migration(
+    transaction: &VersionedTransaction,
+    meta: &UiTransactionStatusMeta,
+    programs: &[ProgramConfig],
+) -> Option<Migration> {
+    let tracked = || programs.iter().filter_map(|program| Some((program, program.migration_program?)));
+
+    let logs: Option<Vec<String>> = meta.log_messages.clone().into();
+    let logged = logs.as_deref().and_then(|logs| {
+        tracked().find_map(|(program, pool_program)| {
+            let event = find_migration_event(logs, program)?;
+            Some(Migration {
+                program: *program,
+                mint: Pubkey::new_from_array(event.mint),
+                bonding_curve: Pubkey::new_from_array(event.bonding_curve),
+                pool: Pubkey::new_from_array(event.pool),
+                pool_program,
+                token_reserves: None,
+                sol_reserves: None,
+            })
+        })
+    });
+    let mut migration = match logged {
+        Some(migration) => {
+            debug!("Migration event decoded from program logs");
+            migration
+        }
+        None => {
+            let migration = resolve_instructions(transaction, meta).iter().find_map(|instruction| {
+                let (program, pool_program) = tracked().find(|(program, pool_program)| {
+                    program.id == instruction.program_id
+                        && instruction.data.starts_with(&program.migrate_instruction)
+                        && instruction.accounts.get(MIGRATE_ACCOUNT_POOL_PROGRAM) == Some(pool_program)
+                })?;
+                let account = |position: usize| instruction.accounts.get(position).copied();
+                Some(Migration {
+                    program: *program,
+                    mint: account(MIGRATE_ACCOUNT_MINT)?,
+                    bonding_curve: account(MIGRATE_ACCOUNT_BONDING_CURVE)?,
+                    pool: account(MIGRATE_ACCOUNT_POOL)?,
+                    pool_program,
+                    token_reserves: None,
+                    sol_reserves: None,
+                })
+            })?;
+            debug!("Migrate instruction parsed");
+            migration
+        }
+    };
+
+    migration.token_reserves = pool_balance(meta, &migration.pool, &migration.mint);
+    migration.sol_reserves = pool_balance(meta, &migration.pool, &spl_token::native_mint::ID);
+    Some(migration)
+}
+
+/// what the accounts `pool` owns hold of `mint` after the transaction, when the meta reports any.
+fn pool_balance(meta: &UiTransactionStatusMeta, pool: &Pubkey, mint: &Pubkey) -> Option<u64> {
+    let OptionSerializer::Some(balances) = &meta.post_token_balances else {
+        return None;
+    };
+    let (pool, mint) = (pool.to_string(), mint.to_string());
+    let held: Vec<u64> = balances
+        .iter()
+        .filter(|balance| balance.mint == mint && balance.owner == OptionSerializer::Some(pool.clone()))
+        .filter_map(|balance| balance.ui_token_amount.amount.parse::<u64>().ok())
+        .collect();
+    (!held.is_empty()).then(|| held.iter().fold(0u64, |total, &amount| total.saturating_add(amount)))
+}
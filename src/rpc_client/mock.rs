@@ -0,0 +1,173 @@
+//! # Mock RPC
+//!
+//! In-memory `SolanaRpc` serving transactions and accounts registered by a test; a transaction that isn't registered is reported as not on the node yet, an account as an RPC error or a missing account.
+
+use super::dead_letters::copy_transaction;
+use super::transport::SolanaRpc;
+use async_trait::async_trait;
+use solana_account_decoder::UiDataSliceConfig;
+use crate::error::{MonitorError, Result};
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_client::rpc_filter::RpcFilterType;
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+#[derive(Default)]
+pub struct MockRpc {
+    transactions: HashMap<Signature, EncodedConfirmedTransactionWithStatusMeta>,
+    accounts: HashMap<Pubkey, Account>,
+    signatures: HashMap<Pubkey, Vec<String>>,
+    /// accounts `getProgramAccounts` searches, by owning program
+    program_accounts: HashMap<Pubkey, Vec<(Pubkey, Account)>>,
+    /// fetches of a registered transaction still to report as not found
+    unavailable: Mutex<HashMap<Signature, usize>>,
+    /// transactions whose fetch never completes, like an RPC hanging mid-request
+    hanging: Vec<Signature>,
+    /// number of RPC calls served, successful or not
+    pub calls: AtomicUsize,
+}
+
+impl MockRpc {
+    /// registers a transaction in `getTransaction` JSON form (base64 encoded) under `signature`.
+    pub fn with_transaction(mut self, signature: &str, json: &str) -> Self {
+        let transaction = serde_json::from_str(json).expect("fixture is not a getTransaction response");
+        self.transactions
+            .insert(Signature::from_str(signature).expect("invalid fixture signature"), transaction);
+        self
+    }
+
+    /// reports the transaction registered under `signature` as not found for its first `fetches` fetches.
+    pub fn unavailable_for(self, signature: &str, fetches: usize) -> Self {
+        self.unavailable
+            .lock()
+            .unwrap()
+            .insert(Signature::from_str(signature).expect("invalid fixture signature"), fetches);
+        self
+    }
+
+    /// makes every fetch of the transaction under `signature` wait forever.
+    pub fn hanging_on(mut self, signature: &str) -> Self {
+        self.hanging.push(Signature::from_str(signature).expect("invalid fixture signature"));
+        self
+    }
+
+    pub fn with_account(mut self, pubkey: &str, account: Account) -> Self {
+        self.accounts
+            .insert(Pubkey::from_str(pubkey).expect("invalid fixture pubkey"), account);
+        self
+    }
+
+    /// registers the signatures `getSignaturesForAddress` returns for `address`, newest first.
+    pub fn with_signatures(mut self, address: &str, signatures: &[&str]) -> Self {
+        self.signatures.insert(
+            Pubkey::from_str(address).expect("invalid fixture pubkey"),
+            signatures.iter().map(|signature| signature.to_string()).collect(),
+        );
+        self
+    }
+
+    /// registers an account `getProgramAccounts` finds for `program`.
+    pub fn with_program_account(mut self, program: &str, address: Pubkey, account: Account) -> Self {
+        self.program_accounts
+            .entry(Pubkey::from_str(program).expect("invalid fixture pubkey"))
+            .or_default()
+            .push((address, account));
+        self
+    }
+
+    fn record_call(&self) {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[async_trait]
+impl SolanaRpc for MockRpc {
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+        _config: RpcTransactionConfig,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        self.record_call();
+        if self.hanging.contains(signature) {
+            std::future::pending::<()>().await;
+        }
+        if let Some(remaining) = self.unavailable.lock().unwrap().get_mut(signature).filter(|remaining| **remaining > 0) {
+            *remaining -= 1;
+            return Err(MonitorError::TransactionNotFound(*signature));
+        }
+        self.transactions
+            .get(signature)
+            .map(copy_transaction)
+            .ok_or(MonitorError::TransactionNotFound(*signature))
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Account> {
+        self.record_call();
+        self.accounts
+            .get(pubkey)
+            .cloned()
+            .ok_or_else(|| ClientError::from(ClientErrorKind::Custom(format!("account {} not found", pubkey))).into())
+    }
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.record_call();
+        Ok(pubkeys.iter().map(|pubkey| self.accounts.get(pubkey).cloned()).collect())
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.record_call();
+        let signatures = self.signatures.get(address).map_or(&[][..], Vec::as_slice);
+        Ok(signatures
+            .iter()
+            .take(limit)
+            .map(|signature| RpcConfirmedTransactionStatusWithSignature {
+                signature: signature.clone(),
+                slot: 0,
+                err: None,
+                memo: None,
+                block_time: None,
+                confirmation_status: None,
+            })
+            .collect())
+    }
+
+    /// applies `memcmp` and `dataSize` filters and the data slice the way a node does.
+    async fn get_program_accounts(
+        &self,
+        program: &Pubkey,
+        filters: Vec<RpcFilterType>,
+        data_slice: Option<UiDataSliceConfig>,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        self.record_call();
+        let accounts = self.program_accounts.get(program).map_or(&[][..], Vec::as_slice);
+        Ok(accounts
+            .iter()
+            .filter(|(_, account)| {
+                filters.iter().all(|filter| match filter {
+                    RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(&account.data),
+                    RpcFilterType::DataSize(size) => account.data.len() as u64 == *size,
+                    _ => true,
+                })
+            })
+            .map(|(address, account)| {
+                let mut account = account.clone();
+                if let Some(slice) = &data_slice {
+                    let start = slice.offset.min(account.data.len());
+                    let end = (start + slice.length).min(account.data.len());
+                    account.data = account.data[start..end].to_vec();
+                }
+                (*address, account)
+            })
+            .collect())
+    }
+}
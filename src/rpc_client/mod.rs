@@ -0,0 +1,1788 @@
+//! # Solana RPC Client
+//!
+//! This module handles the connection to Solana's RPC WebSocket endpoint and monitors the pump.fun program for token creation events. It processes transactions in real-time and extracts relevant token metadata for broadcasting to connected clients.
+//!
+//! ## Commitment
+//!
+//! `COMMITMENT_LEVEL` applies to the log subscription, the HTTP client and transaction fetches:
+//!
+//! - `processed`: notifications arrive roughly as soon as the leader executes the transaction, a few hundred milliseconds ahead of `confirmed`, but about 5% of processed slots are later skipped and their tokens never exist. `getTransaction` doesn't accept `processed`, so transactions are still fetched at `confirmed`, and that fetch and the account lookups retry longer because the data usually isn't visible yet when the notification arrives.
+//! - `confirmed` (default): a supermajority has voted on the block. Forks at this level are practically unheard of on mainnet and latency stays well under a second.
+//! - `finalized`: the block is rooted and can't be rolled back, at the cost of roughly 13 seconds of extra delay; meant for analytics that must never see forked data.
+
+use crate::data_models::{
+    BondingCurveAccountData, CreateEventData, CreateInstructionData, DiagnosticEvent, PumpFunData, RawTransaction,
+    TokenCreatedEvent, TokenCreatedPreviewEvent, TokenDetails, TokenEnrichedEvent, TokenMigratedEvent,
+};
+use crate::config::{
+    Config, ParserSelfTestConfig, ReconnectPolicy, ReplayConfig, SpamConfig, WatchdogConfig, WssEndpoint,
+};
+use crate::dispatch::EventDispatcher;
+use compute_budget::transaction_cost;
+use creators::CreatorHistory;
+use dead_letters::{copy_transaction, CapturingRpc, DeadLetter, DeadLetters, StoredTransactionRpc};
+use dedupe::{RecentSignatures, SeenMints};
+use diagnostics::Diagnostics;
+use initial_buy::find_creation_buys;
+use logs::find_create_event;
+use migration::{find_migration, Migration};
+use overflow::ProcessorQueue;
+use preview::preview_from_logs;
+use provider_limits::{is_limit_rejection, limit_in_close};
+use recording::{read_notifications, FixtureRecorder, RecordingRpc, ReplayRpc};
+use retry::RetryQueue;
+use sanitize::sanitize_metadata;
+use similarity::RecentNames;
+use subscription::{parse_frame, subscribe_request, unsubscribe_request, SubscriptionFrame};
+use watchdog::{Watchdog, WatchdogAction};
+pub use self_test::ParserStatus;
+pub use sequencer::EventSequencer;
+pub use similarity::SpamSuppression;
+pub use snapshot::{run_curve_snapshot, SnapshotDiff};
+pub use stream::{LagPolicy, StreamItem, TokenEventStream};
+pub use transport::SolanaRpc;
+use transport::EndpointRpc;
+use crate::error::{MonitorError, Result};
+use crate::metadata::{ImageHasher, MetadataFetcher, PendingImageHash};
+use crate::metrics::Metrics;
+use crate::price_feed::SolPriceCell;
+use crate::registry::TokenRegistry;
+use crate::watchlist::Watchlist;
+use crate::freshness::event_age;
+use crate::global_filter::GlobalFilter;
+use borsh::BorshDeserialize;
+use chrono::{DateTime, Utc};
+use futures_util::{FutureExt, SinkExt, StreamExt};
+use tracing::{debug, error, info, info_span, warn, Instrument, Span};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_client::{client_error::{ClientError, ClientErrorKind}, rpc_request::RpcError};
+use solana_sdk::account::Account;
+use solana_program::program_pack::Pack;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, TransactionBinaryEncoding,
+    UiTransactionEncoding,
+};
+use spl_token::state::Mint;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{future::Future, pin::Pin, str::FromStr, sync::{Arc, Mutex}, time::{Duration, Instant}};
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+
+/// 8-byte prefix identifies token creation transactions (Anchor's `sha256("global:create")[..8]`).
+const PUMP_FUN_CREATE_DISCRIMINATOR: [u8; 8] = [0x18, 0x1e, 0xc8, 0x28, 0x05, 0x1c, 0x07, 0x77];
+
+/// 8-byte prefix of Buy instructions (Anchor's `sha256("global:buy")[..8]`).
+const PUMP_FUN_BUY_DISCRIMINATOR: [u8; 8] = [0x66, 0x06, 0x3d, 0x12, 0x01, 0xda, 0xeb, 0xea];
+
+/// 8-byte prefix of the `CreateEvent` payload (Anchor's `sha256("event:CreateEvent")[..8]`).
+const PUMP_FUN_CREATE_EVENT_DISCRIMINATOR: [u8; 8] = [0x1b, 0x72, 0xa9, 0x4d, 0xde, 0xeb, 0x63, 0x76];
+
+/// 8-byte prefix of the Migrate instruction moving a completed curve into a PumpSwap pool (Anchor's `sha256("global:migrate")[..8]`).
+const PUMP_FUN_MIGRATE_DISCRIMINATOR: [u8; 8] = [0x9b, 0xea, 0xe7, 0x92, 0xec, 0x9e, 0xa2, 0x1e];
+
+/// 8-byte prefix of the `CompletePumpAmmMigrationEvent` payload (Anchor's `sha256("event:CompletePumpAmmMigrationEvent")[..8]`).
+const PUMP_FUN_MIGRATION_EVENT_DISCRIMINATOR: [u8; 8] = [0xbd, 0xe9, 0x5d, 0xb9, 0x5c, 0x94, 0xea, 0x94];
+
+/// positions of the accounts the monitor reads in the Create instruction's account list, used only when the logs carry no `CreateEvent`.
+const CREATE_ACCOUNT_MINT: usize = 0;
+const CREATE_ACCOUNT_BONDING_CURVE: usize = 2;
+const CREATE_ACCOUNT_USER: usize = 7;
+
+/// seed of the PDA pump.fun creates mints with as their mint authority, before revoking it.
+const PUMP_FUN_MINT_AUTHORITY_SEED: &[u8] = b"mint-authority";
+
+/// identify and parse bonding curve account data (Anchor's `sha256("account:BondingCurve")[..8]`).
+const PUMP_FUN_BONDING_CURVE_DISCRIMINATOR: [u8; 8] = [0x17, 0xb7, 0xf8, 0x37, 0x60, 0xd8, 0xac, 0x60];
+
+/// discriminator plus five u64 reserve/supply fields and the `complete` flag; newer curves append more fields after these.
+pub(crate) const BONDING_CURVE_MIN_LEN: usize = 8 + 5 * 8 + 1;
+
+
+/// how token creations of one monitored program are recognised.
+///
+/// Every discriminator defaults to pump.fun's, which launchpads forked from its Anchor program share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramConfig {
+    pub id: Pubkey,
+    /// prefix of the Create instruction data
+    pub create_instruction: [u8; 8],
+    /// prefix of the Buy instruction data
+    pub buy_instruction: [u8; 8],
+    /// prefix of the `CreateEvent` logged on creation
+    pub create_event: [u8; 8],
+    /// prefix of the bonding curve account data
+    pub bonding_curve_account: [u8; 8],
+    /// prefix of the Migrate instruction data
+    pub migrate_instruction: [u8; 8],
+    /// prefix of the migration event logged once the pool exists
+    pub migration_event: [u8; 8],
+    /// AMM program completed curves migrate to; migrations aren't looked for while `None`
+    pub migration_program: Option<Pubkey>,
+    /// the only mint authority a token of this program may have left, the program's `mint-authority` PDA
+    pub mint_authority: Pubkey,
+}
+
+impl ProgramConfig {
+    pub fn new(id: Pubkey) -> Self {
+        Self {
+            id,
+            create_instruction: PUMP_FUN_CREATE_DISCRIMINATOR,
+            buy_instruction: PUMP_FUN_BUY_DISCRIMINATOR,
+            create_event: PUMP_FUN_CREATE_EVENT_DISCRIMINATOR,
+            bonding_curve_account: PUMP_FUN_BONDING_CURVE_DISCRIMINATOR,
+            migrate_instruction: PUMP_FUN_MIGRATE_DISCRIMINATOR,
+            migration_event: PUMP_FUN_MIGRATION_EVENT_DISCRIMINATOR,
+            migration_program: None,
+            mint_authority: Pubkey::find_program_address(&[PUMP_FUN_MINT_AUTHORITY_SEED], &id).0,
+        }
+    }
+}
+
+/// commitment level the monitor subscribes and reads at.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Commitment {
+    Processed,
+    #[default]
+    Confirmed,
+    Finalized,
+}
+
+impl Commitment {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Processed => "processed",
+            Self::Confirmed => "confirmed",
+            Self::Finalized => "finalized",
+        }
+    }
+
+    pub fn commitment_config(self) -> CommitmentConfig {
+        match self {
+            Self::Processed => CommitmentConfig::processed(),
+            Self::Confirmed => CommitmentConfig::confirmed(),
+            Self::Finalized => CommitmentConfig::finalized(),
+        }
+    }
+
+    /// commitment for `getTransaction`, which doesn't support anything below `confirmed`.
+    pub fn transaction_commitment(self) -> CommitmentConfig {
+        match self {
+            Self::Processed => CommitmentConfig::confirmed(),
+            other => other.commitment_config(),
+        }
+    }
+
+    /// how often a transaction or account lookup is retried; at `processed` the data routinely lags the notification.
+    fn retries(self) -> u64 {
+        match self {
+            Self::Processed => 5,
+            Self::Confirmed | Self::Finalized => 3,
+        }
+    }
+}
+
+impl FromStr for Commitment {
+    type Err = MonitorError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "processed" => Ok(Self::Processed),
+            "confirmed" => Ok(Self::Confirmed),
+            "finalized" => Ok(Self::Finalized),
+            other => Err(MonitorError::Config(format!(
+                "COMMITMENT_LEVEL '{}' is not one of: processed, confirmed, finalized",
+                other
+            ))),
+        }
+    }
+}
+
+/// how much of a new token the monitor looks up beyond its creation transaction.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DetailLevel {
+    /// only what the transaction carries; supply, decimals and the curve's reserves and prices are left out
+    Minimal,
+    /// the mint and bonding curve accounts as well, plus metadata when `METADATA_ENRICHMENT` is on
+    #[default]
+    Standard,
+    /// the accounts and the off-chain metadata, whatever `METADATA_ENRICHMENT` says
+    Full,
+}
+
+impl FromStr for DetailLevel {
+    type Err = MonitorError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "minimal" => Ok(Self::Minimal),
+            "standard" => Ok(Self::Standard),
+            "full" => Ok(Self::Full),
+            other => Err(MonitorError::Config(format!(
+                "DETAIL_LEVEL '{}' is not one of: minimal, standard, full",
+                other
+            ))),
+        }
+    }
+}
+
+/// state of the Solana log subscription and the parser, published for the health endpoints.
+///
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionStatus {
+    /// at least one endpoint has every subscription confirmed
+    pub connected: bool,
+    /// last frame of any kind, pings and pongs included
+    pub last_message_at: Option<Instant>,
+    /// last `logsNotification` for any monitored program
+    pub last_notification_at: Option<Instant>,
+    /// endpoints held off by their provider's plan limits, by label, with the reason the provider gave
+    pub provider_limits: BTreeMap<String, String>,
+    /// what the latest conclusive parser self-test found
+    pub parser: ParserStatus,
+}
+
+/// where the connections to every endpoint hand over their signatures.
+///
+struct Intake {
+    processor: mpsc::Sender<PendingSignature>,
+    /// shared by the connections, so only the first delivery of a signature reaches the processor
+    recent_signatures: Mutex<RecentSignatures>,
+}
+
+impl Intake {
+    /// `true` for the first delivery of `signature` on any connection.
+    fn first_delivery(&self, signature: Signature) -> bool {
+        self.recent_signatures.lock().unwrap_or_else(|e| e.into_inner()).insert(signature)
+    }
+}
+
+/// a signature waiting for the processor, with when its notification arrived.
+///
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PendingSignature {
+    pub signature: Signature,
+    /// monotonic arrival time, for the processing latency
+    pub received_at: Instant,
+    /// wall-clock arrival time, compared with the block time for the chain latency
+    pub observed_at: DateTime<Utc>,
+    /// fetches of the transaction that failed so far
+    pub fetch_failures: u32,
+    /// when its `tokenCreatedPreview` went out; `None` without one
+    pub previewed_at: Option<Instant>,
+}
+
+impl PendingSignature {
+    /// a signature whose notification arrived just now.
+    pub fn new(signature: Signature) -> Self {
+        Self {
+            signature,
+            received_at: Instant::now(),
+            observed_at: Utc::now(),
+            fetch_failures: 0,
+            previewed_at: None,
+        }
+    }
+}
+
+/// Main monitor struct that handles Solana RPC connections and pump.fun event processing.
+///
+pub struct SolanaRpcMonitor {
+    rpc_client: Arc<dyn SolanaRpc>,
+    /// subscribed on at the same time, each reconnecting on its own
+    wss_endpoints: Arc<[WssEndpoint]>,
+    programs: Arc<[ProgramConfig]>,
+    commitment: Commitment,
+    event_sender: Arc<EventSequencer>,
+    sol_price: Option<Arc<SolPriceCell>>,
+    status: watch::Sender<ConnectionStatus>,
+    /// endpoints whose subscriptions are all confirmed; the monitor counts as connected while any is
+    connected_endpoints: AtomicUsize,
+    reconnect: ReconnectPolicy,
+    /// reconnect delay after a provider refused an endpoint for its plan limits
+    provider_limit_backoff: Duration,
+    /// how long one transaction may take to fetch and parse before it goes back to the retry queue
+    processing_timeout: Duration,
+    watchdog: WatchdogConfig,
+    processor_capacity: usize,
+    /// signatures held back while the processor queue is full
+    overflow_capacity: usize,
+    /// what processing looks up and keeps beyond the transaction
+    processing: ProcessingOptions,
+    metadata_fetcher: Option<MetadataFetcher>,
+    /// downloads token images and hashes them; `None` while `IMAGE_HASHING` is off
+    image_hasher: Option<Arc<ImageHasher>>,
+    /// hashes that missed the wait go here as `tokenEnriched` messages, when follow-ups are on
+    enrichments: Option<broadcast::Sender<TokenEnrichedEvent>>,
+    /// migrations of completed curves go here as `tokenMigrated` messages, when migrations are tracked
+    migrations: Option<broadcast::Sender<TokenMigratedEvent>>,
+    /// skipped and failed transactions go here as `diagnostic` messages, when diagnostics are published
+    diagnostics: Option<broadcast::Sender<DiagnosticEvent>>,
+    diagnostics_max_per_sec: u32,
+    /// creations decoded from their log notification go here as `tokenCreatedPreview` messages, when `FAST_PATH` is on
+    previews: Option<broadcast::Sender<TokenCreatedPreviewEvent>>,
+    creator_history: Option<Arc<CreatorHistory>>,
+    /// names broadcast recently, compared against while copycat detection is on
+    recent_names: Arc<RecentNames>,
+    /// copycat detection settings, replaced on a config reload; `None` while detection is off
+    spam: watch::Receiver<Option<SpamConfig>>,
+    /// labelled wallets, replaced on a config reload; `None` without a `WATCHLIST_FILE`
+    watchlist: watch::Receiver<Option<Arc<Watchlist>>>,
+    /// rules every event must pass before it is dispatched, replaced on a config reload; `None` while no rule is set
+    global_filter: watch::Receiver<Option<Arc<GlobalFilter>>>,
+    /// mints already broadcast, shared by every connection and the replay
+    seen_mints: Arc<SeenMints>,
+    /// creation events by mint, to name migrations after their token; `None` leaves them unnamed
+    registry: Option<Arc<TokenRegistry>>,
+    /// signatures remembered across the connections so each transaction is processed once
+    signature_cache_capacity: usize,
+    /// put the measured latencies on events as well as in the metrics
+    latency_fields: bool,
+    /// events older than this when dispatched are marked stale; `None` while `MAX_EVENT_AGE_SECS` is unset
+    max_event_age: Option<Duration>,
+    metrics: Arc<Metrics>,
+    recorder: Option<Arc<FixtureRecorder>>,
+    replay: Option<ReplayConfig>,
+    /// transactions that failed processing are filed here, when `DEAD_LETTER_DIR` is set
+    dead_letters: Option<Arc<DeadLetters>>,
+}
+
+impl SolanaRpcMonitor {
+    /// Creates a new Solana RPC monitor instance from the validated configuration.
+    ///
+    /// Fails only when the `RECORD_FIXTURES` or `DEAD_LETTER_DIR` directory can't be created or the
+    /// `CREATOR_HISTORY_FILE` can't be read.
+    pub fn new(config: &Config, event_sender: EventDispatcher) -> Result<Self> {
+        let mut recorder = None;
+        let rpc_client: Arc<dyn SolanaRpc> = match (&config.fixtures.replay, &config.fixtures.record_dir) {
+            (Some(replay), _) => Arc::new(ReplayRpc::new(&replay.dir)),
+            (None, record_dir) => {
+                let client = RpcClient::new_with_timeout_and_commitment(
+                    config.rpc.http_url.clone(),
+                    config.rpc.request_timeout,
+                    config.rpc.commitment.commitment_config(),
+                );
+                let live: Arc<dyn SolanaRpc> = Arc::new(EndpointRpc::new(client, &config.rpc.http_label));
+                match record_dir {
+                    Some(dir) => {
+                        let fixture_recorder = Arc::new(FixtureRecorder::create(dir)?);
+                        info!("Recording RPC traffic to {}", dir.display());
+                        recorder = Some(Arc::clone(&fixture_recorder));
+                        Arc::new(RecordingRpc::new(live, fixture_recorder))
+                    }
+                    None => live,
+                }
+            }
+        };
+
+        let creator_history = match &config.creator_history {
+            Some(creator_history) => Some(Arc::new(CreatorHistory::open(creator_history, config.caches.creators)?)),
+            None => None,
+        };
+        let dead_letters = match &config.dead_letters {
+            Some(dead_letters) => Some(Arc::new(DeadLetters::open(dead_letters)?)),
+            None => None,
+        };
+
+        Ok(Self {
+            rpc_client,
+            wss_endpoints: config.rpc.wss_endpoints.clone().into(),
+            programs: config.rpc.programs.clone().into(),
+            commitment: config.rpc.commitment,
+            event_sender: Arc::new(EventSequencer::new(event_sender).with_signer(config.event_signer.clone())),
+            sol_price: None,
+            status: watch::channel(ConnectionStatus::default()).0,
+            connected_endpoints: AtomicUsize::new(0),
+            reconnect: config.reconnect.clone(),
+            provider_limit_backoff: config.rpc.provider_limit_backoff,
+            processing_timeout: config.rpc.processing_timeout,
+            watchdog: config.watchdog.clone(),
+            processor_capacity: config.channels.processor_capacity,
+            overflow_capacity: config.channels.overflow_capacity,
+            processing: ProcessingOptions {
+                detail_level: config.rpc.detail_level,
+                keep_raw_metadata: config.metadata.keep_raw,
+                keep_raw_transaction: config.server.raw_transactions,
+            },
+            metadata_fetcher: config.fetches_metadata().then(|| {
+                MetadataFetcher::new(config.metadata.fetch_timeout, &config.metadata.ipfs_gateway, config.caches.socials)
+            }),
+            image_hasher: config.image_hashing.as_ref().map(|image_hashing| Arc::new(ImageHasher::new(image_hashing))),
+            enrichments: None,
+            migrations: None,
+            diagnostics: None,
+            diagnostics_max_per_sec: config.rpc.diagnostics_max_per_sec,
+            previews: None,
+            creator_history,
+            // the limits are set from `spam` before every use
+            recent_names: Arc::new(RecentNames::new(Duration::ZERO, 1)),
+            spam: watch::channel(config.spam.clone()).1,
+            watchlist: watch::channel(config.watchlist.clone()).1,
+            global_filter: watch::channel(config.global_filter.clone()).1,
+            seen_mints: Arc::new(SeenMints::new(&config.mint_dedupe)),
+            registry: None,
+            signature_cache_capacity: config.caches.signatures,
+            latency_fields: config.rpc.latency_fields,
+            max_event_age: config.max_event_age,
+            metrics: Arc::new(Metrics::default()),
+            recorder,
+            replay: config.fixtures.replay.clone(),
+            dead_letters,
+        })
+    }
+
+    /// Enables USD enrichment of events using the shared SOL/USD price cell.
+    ///
+    pub fn with_sol_price(mut self, sol_price: Arc<SolPriceCell>) -> Self {
+        self.sol_price = Some(sol_price);
+        self
+    }
+
+    /// Reports the mint dedupe counters and the sizes of the monitor's caches through the shared process metrics.
+    ///
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        metrics.register_cache("seen_mints", self.seen_mints.cache_stats());
+        metrics.register_cache("recent_names", self.recent_names.cache_stats());
+        if let Some(creator_history) = &self.creator_history {
+            metrics.register_cache("creators", creator_history.cache_stats());
+            metrics.register_cache("creator_mints", creator_history.mint_cache_stats());
+        }
+        if let Some(metadata_fetcher) = &self.metadata_fetcher {
+            metrics.register_cache("socials", metadata_fetcher.cache_stats());
+        }
+        if let Some(image_hasher) = &self.image_hasher {
+            metrics.register_cache("images", image_hasher.cache_stats());
+        }
+        self.metrics = metrics;
+        self
+    }
+
+    /// Sends image hashes that weren't ready in time for the token's broadcast to `enrichments` as they finish.
+    ///
+    pub fn with_enrichments(mut self, enrichments: broadcast::Sender<TokenEnrichedEvent>) -> Self {
+        self.enrichments = Some(enrichments);
+        self
+    }
+
+    /// Sends the migrations of completed curves to `migrations`, when `PUMP_AMM_PROGRAM_ID` is set.
+    ///
+    pub fn with_migrations(mut self, migrations: broadcast::Sender<TokenMigratedEvent>) -> Self {
+        self.migrations = Some(migrations);
+        self
+    }
+
+    /// Names migrations after their token's creation, looked up in `registry`.
+    ///
+    pub fn with_registry(mut self, registry: Arc<TokenRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Sends a `diagnostic` for every transaction skipped or failed to `diagnostics`, up to `DIAGNOSTICS_MAX_PER_SEC`.
+    ///
+    pub fn with_diagnostics(mut self, diagnostics: broadcast::Sender<DiagnosticEvent>) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+
+    /// Sends a `tokenCreatedPreview` to `previews` for every creation logged in a notification, before it is fetched.
+    ///
+    pub fn with_previews(mut self, previews: broadcast::Sender<TokenCreatedPreviewEvent>) -> Self {
+        self.previews = Some(previews);
+        self
+    }
+
+    /// Takes the copycat detection settings from `spam` instead of the fixed ones in the config, so reloads apply.
+    ///
+    pub fn with_spam(mut self, spam: watch::Receiver<Option<SpamConfig>>) -> Self {
+        self.spam = spam;
+        self
+    }
+
+    /// Takes the watchlist from `watchlist` instead of the one loaded with the config, so reloads apply.
+    ///
+    pub fn with_watchlist(mut self, watchlist: watch::Receiver<Option<Arc<Watchlist>>>) -> Self {
+        self.watchlist = watchlist;
+        self
+    }
+
+    /// Takes the global filter from `global_filter` instead of the one in the config, so reloads apply.
+    ///
+    pub fn with_global_filter(mut self, global_filter: watch::Receiver<Option<Arc<GlobalFilter>>>) -> Self {
+        self.global_filter = global_filter;
+        self
+    }
+
+    /// receiver tracking whether the log subscription is connected and when it last heard from the server.
+    pub fn connection_status(&self) -> watch::Receiver<ConnectionStatus> {
+        self.status.subscribe()
+    }
+
+    /// stream of every event broadcast from now on, with lag reported as `StreamItem::Gap` items.
+    ///
+    /// Subscribing before `start_until` is called guarantees no event is missed.
+    pub fn subscribe(&self) -> TokenEventStream {
+        self.subscribe_with(LagPolicy::Gap)
+    }
+
+    /// like `subscribe`, handling lag according to `policy`.
+    pub fn subscribe_with(&self, policy: LagPolicy) -> TokenEventStream {
+        TokenEventStream::new(self.event_sender.subscribe(), policy)
+    }
+
+    /// Runs the dead letters through the current parser, e.g. after fixing a parsing bug; see `reprocess_dead_letters`.
+    ///
+    /// Fails when no `DEAD_LETTER_DIR` is configured.
+    pub async fn reprocess_dead_letters(&self) -> Result<Reprocessed> {
+        let Some(dead_letters) = &self.dead_letters else {
+            return Err(MonitorError::Config("--reprocess-dead-letters needs DEAD_LETTER_DIR".to_string()));
+        };
+        info!("Reprocessing dead letters from {}", dead_letters.dir().display());
+        Ok(reprocess_dead_letters(
+            dead_letters,
+            self.rpc_client.clone(),
+            &self.programs,
+            self.commitment,
+            ProcessingOptions { keep_raw_transaction: false, ..self.processing },
+        )
+        .await)
+    }
+
+    /// shared HTTP RPC client, reused by auxiliary tasks such as the price feed.
+    pub fn rpc_client(&self) -> Arc<dyn SolanaRpc> {
+        Arc::clone(&self.rpc_client)
+    }
+
+    /// Monitors the program on every endpoint at once, each reconnecting with backoff, until `shutdown` completes.
+    ///
+    /// On shutdown the live subscriptions are cancelled with `logsUnsubscribe` and the WebSockets closed.
+    pub async fn start_until(&self, shutdown: impl Future<Output = ()>) {
+        let shutdown = shutdown.shared();
+
+        if let Some(replay) = &self.replay {
+            if let Err(e) = self.replay_fixtures(replay).await {
+                error!("Fixture replay failed: {}", e);
+            }
+            // there is nothing to reconnect to; keep serving whatever the replay produced
+            return shutdown.await;
+        }
+
+        info!("Starting Solana monitor...");
+        // a restart after a panic starts over with every endpoint disconnected
+        self.connected_endpoints.store(0, Ordering::Relaxed);
+        self.status.send_modify(|status| status.connected = false);
+
+        // every endpoint feeds the one processor, and whichever delivers a signature first wins it
+        let (processor, _processor) = self.spawn_processor();
+        let intake = Intake {
+            processor,
+            recent_signatures: Mutex::new(self.recent_signatures()),
+        };
+        let endpoints = self.wss_endpoints.iter().map(|endpoint| {
+            self.metrics.add_rpc_endpoint(&endpoint.label);
+            self.follow_endpoint(endpoint, &intake, shutdown.clone())
+                .instrument(info_span!("endpoint", endpoint = %endpoint.label))
+        });
+        futures_util::future::join_all(endpoints).await;
+    }
+
+    /// Keeps the subscriptions on `endpoint` open, reconnecting with backoff, until `shutdown` completes.
+    ///
+    /// The backoff is the endpoint's own, so one endpoint flapping doesn't hold back or disconnect the others. A provider
+    /// refusing the endpoint for its plan limits gets `provider_limit_backoff` between attempts, and is logged once until
+    /// a subscription succeeds again.
+    async fn follow_endpoint(
+        &self,
+        endpoint: &WssEndpoint,
+        intake: &Intake,
+        mut shutdown: impl Future<Output = ()> + Unpin,
+    ) {
+        let mut delay = self.reconnect.initial_delay;
+        loop {
+            let mut subscribed = false;
+            let result = self.connect_and_monitor(endpoint, intake, &mut subscribed, Pin::new(&mut shutdown)).await;
+
+            // a connection that got as far as a confirmed subscription resets the backoff
+            if subscribed {
+                self.set_endpoint_connected(false);
+                delay = self.reconnect.initial_delay;
+                self.clear_provider_limit(endpoint);
+            }
+
+            let Err(e) = result else {
+                return;
+            };
+            // a provider that refused the connection for its rate limit may say how long to stay away
+            let mut wait = delay.max(e.retry_after().unwrap_or_default());
+            if let MonitorError::ProviderLimit(reason) = &e {
+                wait = wait.max(self.provider_limit_backoff);
+                if self.set_provider_limit(endpoint, reason) {
+                    error!(
+                        "RPC endpoint {} refused the subscription for a plan limit: {}. Retrying every {:?}; check the provider plan's subscription and connection limits",
+                        endpoint.label, reason, wait
+                    );
+                } else {
+                    debug!("RPC endpoint {} still at its plan limit: {}", endpoint.label, reason);
+                }
+            } else {
+                error!("Monitor task failed: {}. Reconnecting in {:?}...", e, wait);
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = &mut shutdown => return,
+            }
+            delay = self.reconnect.next_delay(delay);
+        }
+    }
+
+    /// Runs the parser self-test now and then every `config.interval`, publishing what it finds as the connection
+    /// status's `parser`; returns after the first run when there is no interval.
+    ///
+    /// Turning possibly stale is logged as an ERROR once, until a later run finds creations again.
+    pub async fn run_parser_self_test(&self, config: &ParserSelfTestConfig) {
+        loop {
+            let window =
+                self_test::sample_window(Arc::clone(&self.rpc_client), &self.programs, self.commitment, config).await;
+            let previous = self.status.borrow().parser;
+            match window.status(config.min_transactions) {
+                Some(ParserStatus::PossiblyStale) if previous != ParserStatus::PossiblyStale => error!(
+                    "Parser self-test found no token creation among {} recent transactions of the monitored programs; \
+                     their discriminators may have changed, check [rpc.parsers] against the program's IDL",
+                    window.transactions
+                ),
+                Some(ParserStatus::Ok) if previous == ParserStatus::PossiblyStale => info!(
+                    "Parser self-test found {} token creations among {} recent transactions again",
+                    window.creations, window.transactions
+                ),
+                Some(_) => debug!(
+                    "Parser self-test found {} token creations among {} recent transactions",
+                    window.creations, window.transactions
+                ),
+                None => info!(
+                    "Parser self-test was inconclusive: only {} recent transactions could be parsed, {} needed",
+                    window.transactions, config.min_transactions
+                ),
+            }
+            if let Some(status) = window.status(config.min_transactions) {
+                self.status.send_modify(|connection| connection.parser = status);
+            }
+            let Some(interval) = config.interval else {
+                return;
+            };
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// counts an endpoint in or out of the connected ones.
+    fn set_endpoint_connected(&self, connected: bool) {
+        self.status.send_modify(|status| {
+            let count = if connected {
+                self.connected_endpoints.fetch_add(1, Ordering::Relaxed) + 1
+            } else {
+                self.connected_endpoints.fetch_sub(1, Ordering::Relaxed) - 1
+            };
+            status.connected = count > 0;
+        });
+    }
+
+    /// records `endpoint` as held off by its provider's limits, `true` if it wasn't already.
+    fn set_provider_limit(&self, endpoint: &WssEndpoint, reason: &str) -> bool {
+        let mut newly_limited = false;
+        self.status.send_modify(|status| {
+            newly_limited = status.provider_limits.insert(endpoint.label.clone(), reason.to_string()).is_none();
+        });
+        newly_limited
+    }
+
+    /// clears the provider limit recorded for `endpoint`, once it subscribed again.
+    fn clear_provider_limit(&self, endpoint: &WssEndpoint) {
+        let mut cleared = false;
+        self.status.send_if_modified(|status| {
+            cleared = status.provider_limits.remove(&endpoint.label).is_some();
+            cleared
+        });
+        if cleared {
+            info!("RPC endpoint {} is no longer refused for a plan limit", endpoint.label);
+        }
+    }
+
+    /// Subscribes to every program on `endpoint` and hands notifications to `intake` until the connection fails, which
+    /// is always an error, or `shutdown` completes, which returns `Ok`.
+    ///
+    /// The connection only counts as connected, and `subscribed` is set, once the server confirms every subscription; an
+    /// error response fails the connection straight away so the reconnect backoff applies.
+    ///
+    /// Subscribe requests use JSON-RPC ids `1..=n` in program order, unsubscribe requests the ids after them.
+    async fn connect_and_monitor(
+        &self,
+        endpoint: &WssEndpoint,
+        intake: &Intake,
+        subscribed: &mut bool,
+        mut shutdown: Pin<&mut impl Future<Output = ()>>,
+    ) -> Result<()> {
+        let (ws_stream, _) = connect_async(&endpoint.url).await?;
+        info!("Connected to Solana WebSocket at {}", endpoint.url);
+
+        let (mut write, mut read) = ws_stream.split();
+        for (request_id, program) in (1..).zip(self.programs.iter()) {
+            write.send(Message::Text(subscribe_request(request_id, &program.id, self.commitment))).await?;
+            debug!("Requested logs mentioning program: {}", program.id);
+        }
+        self.status.send_modify(|status| status.last_message_at = Some(Instant::now()));
+
+        let mut queue = ProcessorQueue::new(intake.processor.clone(), self.overflow_capacity, Arc::clone(&self.metrics));
+        // subscription id -> program
+        let mut subscriptions: HashMap<u64, Pubkey> = HashMap::new();
+
+        let mut watchdog = Watchdog::new(&self.watchdog, tokio::time::Instant::now());
+        let mut keepalive = tokio::time::interval_at(
+            tokio::time::Instant::now() + self.watchdog.keepalive_interval,
+            self.watchdog.keepalive_interval,
+        );
+        keepalive.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        // Main WebSocket reader loop
+        loop {
+            let message = tokio::select! {
+                message = read.next() => message,
+                permit = queue.room(), if queue.spilled() > 0 => {
+                    let Some(permit) = permit else {
+                        error!("Transaction processing channel is closed.");
+                        break;
+                    };
+                    queue.send_spilled(permit);
+                    continue;
+                }
+                _ = &mut shutdown => {
+                    let first_unsubscribe_id = self.programs.len() as u64 + 1;
+                    for (request_id, (subscription, program)) in (first_unsubscribe_id..).zip(&subscriptions) {
+                        info!("Unsubscribing from logs mentioning {} (subscription {})", program, subscription);
+                        write.send(Message::Text(unsubscribe_request(request_id, *subscription))).await?;
+                    }
+                    write.send(Message::Close(None)).await?;
+                    return Ok(());
+                }
+                _ = keepalive.tick() => {
+                    write.send(Message::Ping(Vec::new())).await?;
+                    continue;
+                }
+                _ = tokio::time::sleep_until(watchdog.deadline()) => {
+                    let now = tokio::time::Instant::now();
+                    match watchdog.expired(now) {
+                        WatchdogAction::Ping => {
+                            warn!("No messages from the Solana WebSocket for {:?}, sending a ping", watchdog.silence(now));
+                            write.send(Message::Ping(Vec::new())).await?;
+                            continue;
+                        }
+                        WatchdogAction::Reconnect => {
+                            error!("Solana WebSocket silent for {:?} and the ping went unanswered", watchdog.silence(now));
+                            break;
+                        }
+                    }
+                }
+            };
+            let Some(message) = message else {
+                break;
+            };
+
+            if message.is_ok() {
+                watchdog.frame_received(tokio::time::Instant::now());
+                self.status.send_modify(|status| status.last_message_at = Some(Instant::now()));
+            }
+            match message {
+                Ok(Message::Text(text)) => {
+                    if let Some(recorder) = &self.recorder {
+                        recorder.record_notification(&text);
+                    }
+                    match parse_frame(&text) {
+                        SubscriptionFrame::Subscribed { request_id, subscription } => {
+                            let Some(program) = self.subscribe_request_program(request_id) else {
+                                debug!("Ignoring response to request {}", request_id);
+                                continue;
+                            };
+                            info!(
+                                "Subscribed to {} logs mentioning program: {} (subscription {})",
+                                self.commitment.as_str(),
+                                program.id,
+                                subscription
+                            );
+                            subscriptions.insert(subscription, program.id);
+                            if subscriptions.len() == self.programs.len() && !*subscribed {
+                                *subscribed = true;
+                                self.set_endpoint_connected(true);
+                            }
+                        }
+                        SubscriptionFrame::Rejected { code, message } if is_limit_rejection(code, &message) => {
+                            return Err(MonitorError::ProviderLimit(format!("{} (code {})", message, code)));
+                        }
+                        SubscriptionFrame::Rejected { code, message } => {
+                            error!("logsSubscribe rejected by the server: {} (code {})", message, code);
+                            return Err(MonitorError::Subscription(format!("{} (code {})", message, code)));
+                        }
+                        SubscriptionFrame::Notification { subscription, signature, logs } => {
+                            if !subscriptions.contains_key(&subscription) {
+                                warn!("Ignoring notification for unknown subscription {}", subscription);
+                                continue;
+                            }
+                            self.status.send_modify(|status| status.last_notification_at = Some(Instant::now()));
+                            // a transaction mentioning several monitored programs is notified once per subscription, and
+                            // once per endpoint
+                            let Some(signature) = signature.filter(|signature| intake.first_delivery(*signature)) else {
+                                continue;
+                            };
+                            self.metrics.record_first_delivery(&endpoint.label);
+                            let pending = self.preview(PendingSignature::new(signature), &logs);
+                            // never waits: a stalled reader would get the connection dropped by the server
+                            if !queue.push(pending) {
+                                error!("Transaction processing channel is closed.");
+                                break;
+                            }
+                        }
+                        SubscriptionFrame::Other => debug!("Ignoring frame: {}", text),
+                    }
+                }
+                Ok(Message::Close(frame)) => {
+                    if let Some(reason) = limit_in_close(frame.as_ref()) {
+                        return Err(MonitorError::ProviderLimit(reason));
+                    }
+                    warn!("WebSocket connection closed by server.");
+                    break;
+                }
+                Err(e) => {
+                    error!("WebSocket read error: {}", e);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        Err(tokio_tungstenite::tungstenite::Error::ConnectionClosed.into())
+    }
+
+    /// publishes the `tokenCreatedPreview` of a creation logged in `logs`, noting when on `pending`.
+    ///
+    /// Mints already broadcast get no preview, as their `tokenCreated` won't follow.
+    fn preview(&self, mut pending: PendingSignature, logs: &[String]) -> PendingSignature {
+        let Some(previews) = &self.previews else {
+            return pending;
+        };
+        let Some(preview) = preview_from_logs(&pending.signature, logs, &self.programs, pending.observed_at) else {
+            return pending;
+        };
+        if self.seen_mints.contains(&preview.mint_address, Instant::now()) {
+            debug!("Mint {} was already broadcast, not previewing it", preview.mint_address);
+            return pending;
+        }
+        debug!("Previewing '{}' ({}) from {}", preview.name, preview.symbol, pending.signature);
+        // nobody listening just means no client opted in
+        let _ = previews.send(preview);
+        self.metrics.previews_sent.fetch_add(1, Ordering::Relaxed);
+        pending.previewed_at = Some(Instant::now());
+        pending
+    }
+
+    /// an empty signature cache for a run of the monitor or a replay, exported as the `signatures` cache.
+    fn recent_signatures(&self) -> RecentSignatures {
+        let recent_signatures = RecentSignatures::new(self.signature_cache_capacity);
+        self.metrics.register_cache("signatures", recent_signatures.cache_stats());
+        recent_signatures
+    }
+
+    /// the program whose `logsSubscribe` was sent with `request_id`.
+    fn subscribe_request_program(&self, request_id: u64) -> Option<&ProgramConfig> {
+        let index = usize::try_from(request_id).ok()?.checked_sub(1)?;
+        self.programs.get(index)
+    }
+
+    /// Spawns the task that turns signatures into events, in the order they are sent.
+    ///
+    /// The task runs until every sender has been dropped and the queue is drained. Processing happens off the reader
+    /// so a slow RPC doesn't stall the WebSocket.
+    fn spawn_processor(&self) -> (mpsc::Sender<PendingSignature>, JoinHandle<()>) {
+        // each signature travels with the time it was received so latency to broadcast can be measured
+        let (tx_processor, mut rx_processor) = mpsc::channel::<PendingSignature>(self.processor_capacity);
+
+        // a separate task for processing transactions to not block the WebSocket reader
+        let dead_letters = self.dead_letters.clone();
+        // with dead letters on, the last fetched transaction is kept so a failure can be filed with it
+        let capture = dead_letters.as_ref().map(|_| Arc::new(CapturingRpc::new(self.rpc_client.clone())));
+        let rpc_client_clone: Arc<dyn SolanaRpc> = match &capture {
+            Some(capture) => capture.clone(),
+            None => self.rpc_client.clone(),
+        };
+        let event_sender_clone = self.event_sender.clone();
+        let programs = Arc::clone(&self.programs);
+        let commitment = self.commitment;
+        let processing_timeout = self.processing_timeout;
+        let sol_price_clone = self.sol_price.clone();
+        let processing_options = self.processing;
+        let metadata_fetcher = self.metadata_fetcher.clone();
+        let image_hasher = self.image_hasher.clone();
+        let enrichments = self.enrichments.clone();
+        let migrations = self.migrations.clone();
+        let creator_history = self.creator_history.clone();
+        let recent_names = Arc::clone(&self.recent_names);
+        let spam = self.spam.clone();
+        let watchlist = self.watchlist.clone();
+        let global_filter = self.global_filter.clone();
+        let seen_mints = Arc::clone(&self.seen_mints);
+        let registry = self.registry.clone();
+        let latency_fields = self.latency_fields;
+        let max_event_age = self.max_event_age;
+        let metrics = Arc::clone(&self.metrics);
+        let mut diagnostics =
+            Diagnostics::new(self.diagnostics.clone(), self.diagnostics_max_per_sec, Arc::clone(&self.metrics));
+        let processor = tokio::spawn(async move {
+            // transactions the RPC didn't have yet come back through here, so the queue never waits on them
+            let mut retries = RetryQueue::default();
+            while let Some(pending) = retries.next(&mut rx_processor).await {
+                metrics.processor_queue_depth.store(rx_processor.len(), Ordering::Relaxed);
+                metrics.fetch_retries_waiting.store(retries.len(), Ordering::Relaxed);
+                // every log line for this transaction's lifecycle carries the signature (and mint once known)
+                let span = info_span!(
+                    "transaction",
+                    signature = %pending.signature,
+                    mint = tracing::field::Empty,
+                    latency_ms = tracing::field::Empty
+                );
+                let signature = pending.signature;
+                let attempts = pending.fetch_failures + 1;
+                // one deadline covers fetching and enriching the signature; the dispatch that follows always completes
+                let deadline = tokio::time::Instant::now() + processing_timeout;
+                let handling = async {
+                    debug!("Signature received");
+                    let processing = process_transaction(
+                        rpc_client_clone.clone(),
+                        &pending,
+                        &programs,
+                        commitment,
+                        sol_price_clone.as_deref(),
+                        processing_options,
+                    );
+                    let processed = within_processing_timeout(deadline, &metrics, processing).await;
+                    match processed {
+                        Ok(Some(ProcessedTransaction::Migrated(mut migrated))) => {
+                            metrics.migrations.fetch_add(1, Ordering::Relaxed);
+                            Span::current().record("mint", tracing::field::display(&migrated.mint));
+                            info!("Token {} migrated to pool {}", migrated.mint, migrated.pool);
+                            // a graduating token may be older than anything in memory, so the database is asked too
+                            if let Some(registry) = &registry {
+                                match registry.lookup(&migrated.mint).await {
+                                    Ok(Some(created)) => {
+                                        migrated.name = Some(created.token.name.clone());
+                                        migrated.symbol = Some(created.token.symbol.clone());
+                                    }
+                                    Ok(None) => {
+                                        debug!("Creation of {} is unknown, its migration goes out unnamed", migrated.mint)
+                                    }
+                                    Err(e) => warn!("Cannot look up the creation of {}: {}", migrated.mint, e),
+                                }
+                            }
+                            // the creator's graduation rate follows, for its next launches
+                            if let Some(history) = &creator_history {
+                                history.record_graduation(&migrated.mint, migrated.timestamp);
+                            }
+                            // nobody listening just means no client is connected
+                            if let Some(migrations) = &migrations {
+                                let _ = migrations.send(*migrated);
+                            }
+                        }
+                        Ok(Some(ProcessedTransaction::Created(event))) => {
+                            let mut event = *event;
+                            if let Some(fetcher) = metadata_fetcher.as_ref().filter(|_| event.token.uri_valid) {
+                                event.metadata = fetcher.fetch(&event.token.uri).await;
+                            }
+                            Span::current().record("latency_ms", pending.received_at.elapsed().as_millis() as u64);
+                            info!("Successfully processed token creation: '{}' ({})", event.token.name, event.token.symbol);
+                            if !seen_mints.first_sighting(&event.token.mint_address, Instant::now()) {
+                                metrics.mint_dedupe_hits.fetch_add(1, Ordering::Relaxed);
+                                debug!("Mint {} was already broadcast, skipping", event.token.mint_address);
+                                diagnostics.skipped(&pending.signature, || "mint already broadcast".to_string());
+                                return None;
+                            }
+                            metrics.mint_dedupe_misses.fetch_add(1, Ordering::Relaxed);
+                            metrics.mint_dedupe_entries.store(seen_mints.len(), Ordering::Relaxed);
+                            if let (Some(fetcher), Some(metadata)) = (&metadata_fetcher, event.metadata.as_mut()) {
+                                fetcher.flag_reused_socials(metadata, &event.token.mint_address);
+                            }
+                            // started once the mint is known to be new, so a duplicate doesn't look like a copied image
+                            let image_url = event.metadata.as_ref().and_then(|metadata| metadata.image_url.as_deref());
+                            let image_hash = match (&image_hasher, image_url) {
+                                (Some(hasher), Some(image_url)) => {
+                                    let pending = hasher.start(image_url, &event.token.mint_address);
+                                    if pending.is_none() {
+                                        metrics.image_downloads_skipped.fetch_add(1, Ordering::Relaxed);
+                                        debug!("Image download pool is full, not hashing {}", image_url);
+                                    }
+                                    pending
+                                }
+                                _ => None,
+                            };
+                            // counted only once the mint is known to be new, so a duplicate doesn't count twice
+                            if let Some(history) = &creator_history {
+                                let stats = history
+                                    .record(
+                                        rpc_client_clone.as_ref(),
+                                        &programs,
+                                        &event.token.creator,
+                                        &event.token.mint_address,
+                                        &event.transaction_signature,
+                                        event.timestamp,
+                                    )
+                                    .await;
+                                event.creator_stats = Some(stats);
+                            }
+                            let label = watchlist
+                                .borrow()
+                                .as_ref()
+                                .and_then(|watchlist| watchlist.get(&event.token.creator).cloned());
+                            if let Some(label) = label {
+                                debug!("Creator is on the watchlist as '{}'", label.label);
+                                event.creator_label = Some(label.label);
+                                event.creator_tags = label.tags;
+                            }
+                            let spam_config = spam.borrow().clone();
+                            if let Some(spam_config) = spam_config {
+                                recent_names.set_limits(spam_config.window, spam_config.capacity);
+                                let similar = recent_names.record(&event.token.name, &event.token.symbol, Instant::now());
+                                if similar > 0 && spam_config.suppression == SpamSuppression::Drop {
+                                    metrics.spam_suppressed.fetch_add(1, Ordering::Relaxed);
+                                    debug!("'{}' looks like {} recent tokens, dropping it", event.token.name, similar);
+                                    let reason = || format!("similar to {} recent tokens", similar);
+                                    diagnostics.skipped(&pending.signature, reason);
+                                    return None;
+                                }
+                                event.similar_recent_count = Some(similar);
+                            }
+                            let suppressed_by = global_filter.borrow().as_ref().and_then(|filter| filter.check(&event));
+                            if let Some(rule) = suppressed_by {
+                                metrics.record_global_filter_suppressed(rule.name());
+                                debug!("'{}' fails global filter rule {}, dropping it", event.token.name, rule.name());
+                                diagnostics.skipped(&pending.signature, || format!("global filter rule {}", rule.name()));
+                                return None;
+                            }
+                            let processing_latency_ms = pending.received_at.elapsed().as_millis() as u64;
+                            metrics.processing_latency.observe(processing_latency_ms);
+                            let chain_latency_ms = event.chain_latency_ms;
+                            if let Some(chain_latency_ms) = chain_latency_ms {
+                                metrics.chain_latency.observe(chain_latency_ms);
+                            }
+                            if latency_fields {
+                                event.processing_latency_ms = Some(processing_latency_ms);
+                            } else {
+                                event.chain_latency_ms = None;
+                            }
+                            let mut late_hash = None;
+                            if let Some(pending) = image_hash {
+                                match pending.ready().await {
+                                    Ok(hash) => {
+                                        if let (Some(hash), Some(metadata)) = (hash, event.metadata.as_mut()) {
+                                            hash.apply(metadata);
+                                        }
+                                    }
+                                    Err(pending) => {
+                                        metrics.image_hashes_late.fetch_add(1, Ordering::Relaxed);
+                                        debug!("Image hash not ready, broadcasting without it");
+                                        late_hash = enrichments.clone().map(|sender| (pending, sender, event.clone()));
+                                    }
+                                }
+                            }
+                            // measured last, so the wait for the image hash counts too
+                            let age = event_age(chain_latency_ms, pending.received_at.elapsed());
+                            if max_event_age.is_some_and(|max_event_age| age > max_event_age) {
+                                event.stale = true;
+                                metrics.stale_events.fetch_add(1, Ordering::Relaxed);
+                                debug!("Event is {:?} old, marking it stale", age);
+                            }
+                            if let Some(previewed_at) = pending.previewed_at {
+                                let lead_ms = previewed_at.elapsed().as_millis() as u64;
+                                metrics.preview_lead.observe(lead_ms);
+                                debug!("Preview went out {}ms ahead", lead_ms);
+                            }
+                            return Some((event, late_hash));
+                        }
+                        Ok(None) => {
+                            diagnostics.skipped(&pending.signature, || "no create instruction".to_string());
+                        }
+                        Err(e) if e.is_retryable() => {
+                            metrics.record_error(e.code(), e.endpoint());
+                            match retries.schedule(pending, e.retry_after()) {
+                                Ok(delay) => debug!("Fetch failed ({}), retrying in {:?}", e, delay),
+                                Err(missed) => {
+                                    metrics.transactions_missed.fetch_add(1, Ordering::Relaxed);
+                                    warn!(
+                                        "Missed transaction {} after {} fetch attempts: {}",
+                                        missed.signature,
+                                        missed.fetch_failures + 1,
+                                        e
+                                    );
+                                    diagnostics.missed(&missed.signature, missed.fetch_failures + 1, &e);
+                                    if let (Some(dead_letters), Some(capture)) = (&dead_letters, &capture) {
+                                        file_dead_letter(dead_letters, capture, &metrics, missed.signature, &e);
+                                    }
+                                }
+                            }
+                            metrics.fetch_retries_waiting.store(retries.len(), Ordering::Relaxed);
+                        }
+                        Err(e) => {
+                            metrics.record_error(e.code(), e.endpoint());
+                            warn!("Failed to process transaction {}: {}", pending.signature, e);
+                            diagnostics.parse_error(&pending.signature, &e);
+                            if let (Some(dead_letters), Some(capture)) = (&dead_letters, &capture) {
+                                file_dead_letter(dead_letters, capture, &metrics, pending.signature, &e);
+                            }
+                        }
+                    }
+                    None
+                };
+                // a fetch past the deadline is cut off first and retried; anything after it that is still waiting
+                // (metadata, creator history, the image hash) costs the signature instead. A full durable queue only
+                // slows the dispatch down, and is counted by the dispatcher's own wait metrics
+                match tokio::time::timeout_at(deadline, handling).instrument(span.clone()).await {
+                    Ok(Some((event, late_hash))) => {
+                        let dispatch = async {
+                            if event_sender_clone.send(event).await == 0 {
+                                warn!("No active listeners for token creation events.");
+                            } else {
+                                debug!("Event dispatched");
+                            }
+                        };
+                        dispatch.instrument(span.clone()).await;
+                        if let Some((pending, sender, event)) = late_hash {
+                            tokio::spawn(send_late_image_hash(pending, sender, event));
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(_) => {
+                        let _span = span.enter();
+                        let error = MonitorError::Timeout;
+                        metrics.processing_timeouts.fetch_add(1, Ordering::Relaxed);
+                        metrics.transactions_missed.fetch_add(1, Ordering::Relaxed);
+                        warn!("Processing {} took longer than {:?}, dropping it", signature, processing_timeout);
+                        diagnostics.missed(&signature, attempts, &error);
+                        if let (Some(dead_letters), Some(capture)) = (&dead_letters, &capture) {
+                            file_dead_letter(dead_letters, capture, &metrics, signature, &error);
+                        }
+                    }
+                }
+            }
+        });
+
+        (tx_processor, processor)
+    }
+
+    /// Feeds a recorded `notifications.jsonl` through the processor, spaced by the recorded arrival times divided by
+    /// the replay speed (or back to back at speed 0), and returns once every event has been broadcast.
+    ///
+    async fn replay_fixtures(&self, replay: &ReplayConfig) -> Result<()> {
+        let notifications = read_notifications(&replay.dir)?;
+        info!(
+            "Replaying {} recorded notifications from {} at speed {}",
+            notifications.len(),
+            replay.dir.display(),
+            replay.speed
+        );
+
+        let (tx_processor, processor) = self.spawn_processor();
+        self.status.send_replace(ConnectionStatus {
+            connected: true,
+            last_message_at: Some(Instant::now()),
+            last_notification_at: None,
+            provider_limits: BTreeMap::new(),
+            parser: ParserStatus::Unchecked,
+        });
+
+        let started = tokio::time::Instant::now();
+        let first_received_at = notifications.first().map_or(0, |notification| notification.received_at_ms);
+        // recordings include the subscription confirmations; notifications for any other subscription are skipped as live
+        let mut subscriptions = HashSet::new();
+        let mut recent_signatures = self.recent_signatures();
+        for notification in notifications {
+            if replay.speed > 0.0 {
+                let offset = Duration::from_millis(notification.received_at_ms.saturating_sub(first_received_at));
+                tokio::time::sleep_until(started + offset.div_f64(replay.speed)).await;
+            }
+            self.status.send_modify(|status| status.last_message_at = Some(Instant::now()));
+            match parse_frame(&notification.message) {
+                SubscriptionFrame::Subscribed { subscription, .. } => {
+                    subscriptions.insert(subscription);
+                }
+                SubscriptionFrame::Notification { subscription, signature, logs }
+                    if subscriptions.is_empty() || subscriptions.contains(&subscription) =>
+                {
+                    self.status.send_modify(|status| status.last_notification_at = Some(Instant::now()));
+                    let Some(signature) = signature.filter(|signature| recent_signatures.insert(*signature)) else {
+                        continue;
+                    };
+                    let pending = self.preview(PendingSignature::new(signature), &logs);
+                    if tx_processor.send(pending).await.is_err() {
+                        error!("Transaction processing channel is closed.");
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        drop(tx_processor);
+        let _ = processor.await;
+        info!("Fixture replay complete");
+        Ok(())
+    }
+}
+
+/// writes a dead letter for a transaction that failed processing, with the transaction when it had been fetched.
+fn file_dead_letter(dead_letters: &DeadLetters, capture: &CapturingRpc, metrics: &Metrics, signature: Signature, error: &MonitorError) {
+    metrics.record_dead_letter(error.code());
+    dead_letters.write(&DeadLetter::new(signature, error, capture.take(&signature)));
+    debug!("Dead letter written to {}", dead_letters.dir().display());
+}
+
+/// outcome of running the dead letters through the parser again.
+///
+#[derive(Debug, Default)]
+pub struct Reprocessed {
+    /// tokens the letters now produce, oldest letter first
+    pub events: Vec<TokenCreatedEvent>,
+    /// letters that now parse as something other than a token creation
+    pub ignored: usize,
+    /// letters that failed again and were kept, with the new error
+    pub failed: usize,
+}
+
+/// Runs every dead letter through the current parser, serving each its stored transaction when it has one.
+///
+/// Letters that go through, as a token or as nothing at all, are removed; those that fail again are kept with the new
+/// error and the stored transaction.
+async fn reprocess_dead_letters(
+    dead_letters: &DeadLetters,
+    rpc_client: Arc<dyn SolanaRpc>,
+    programs: &[ProgramConfig],
+    commitment: Commitment,
+    options: ProcessingOptions,
+) -> Reprocessed {
+    let mut reprocessed = Reprocessed::default();
+    for letter in dead_letters.read_all() {
+        let Ok(signature) = Signature::from_str(&letter.signature) else {
+            warn!("Dead letter has an invalid signature: {}", letter.signature);
+            continue;
+        };
+        let rpc: Arc<dyn SolanaRpc> = match &letter.transaction {
+            Some(transaction) => Arc::new(StoredTransactionRpc::new(rpc_client.clone(), signature, copy_transaction(transaction))),
+            None => rpc_client.clone(),
+        };
+        let pending = PendingSignature::new(signature);
+        match process_transaction(rpc, &pending, programs, commitment, None, options).await {
+            Ok(processed) => {
+                match processed {
+                    Some(ProcessedTransaction::Created(event)) => reprocessed.events.push(*event),
+                    Some(ProcessedTransaction::Migrated(_)) | None => reprocessed.ignored += 1,
+                }
+                dead_letters.remove(&letter.signature);
+            }
+            Err(e) => {
+                warn!("Dead letter {} failed again: {}", letter.signature, e);
+                reprocessed.failed += 1;
+                dead_letters.write(&DeadLetter::new(signature, &e, letter.transaction));
+            }
+        }
+    }
+    reprocessed
+}
+
+/// waits out an image hash that missed the token's broadcast and sends it on as a `tokenEnriched` message.
+async fn send_late_image_hash(
+    pending: PendingImageHash,
+    enrichments: broadcast::Sender<TokenEnrichedEvent>,
+    event: TokenCreatedEvent,
+) {
+    let Some(hash) = pending.finish().await else {
+        return;
+    };
+    let enriched = TokenEnrichedEvent {
+        sequence: event.sequence,
+        timestamp: Utc::now(),
+        mint: event.token.mint_address.clone(),
+        transaction_signature: event.transaction_signature.clone(),
+        image_sha256: hash.sha256,
+        image_seen_before: hash.first_seen_mint.is_some(),
+        image_first_seen_mint: hash.first_seen_mint,
+        event: Box::new(event),
+    };
+    // nobody listening just means no client is connected
+    let _ = enrichments.send(enriched);
+}
+
+/// `processing` cut off at `deadline`, failing with `Timeout` so the retry queue takes the transaction back.
+///
+/// Dropping the future cancels whatever RPC call it was waiting on, so one transaction the RPC hangs on can't hold up
+/// every signature behind it.
+async fn within_processing_timeout<T>(
+    deadline: tokio::time::Instant,
+    metrics: &Metrics,
+    processing: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::time::timeout_at(deadline, processing).await.unwrap_or_else(|_| {
+        metrics.processing_timeouts.fetch_add(1, Ordering::Relaxed);
+        warn!("Fetching the transaction ran past the processing deadline, giving up on this attempt");
+        Err(MonitorError::Timeout)
+    })
+}
+
+/// what a fetched transaction of a monitored program turned out to be.
+#[derive(Debug)]
+enum ProcessedTransaction {
+    Created(Box<TokenCreatedEvent>),
+    Migrated(Box<TokenMigratedEvent>),
+}
+
+/// what `process_transaction` looks up and keeps beyond the transaction itself.
+#[derive(Debug, Clone, Copy, Default)]
+struct ProcessingOptions {
+    /// whether the mint and bonding curve accounts are fetched
+    detail_level: DetailLevel,
+    keep_raw_metadata: bool,
+    /// attach the fetched transaction to events for WebSocket clients in raw mode
+    keep_raw_transaction: bool,
+}
+
+async fn process_transaction(
+    rpc_client: Arc<dyn SolanaRpc>,
+    pending: &PendingSignature,
+    programs: &[ProgramConfig],
+    commitment: Commitment,
+    sol_price: Option<&SolPriceCell>,
+    options: ProcessingOptions,
+) -> Result<Option<ProcessedTransaction>> {
+    let signature = pending.signature;
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(commitment.transaction_commitment()),
+        max_supported_transaction_version: Some(0),
+    };
+    
+    // a transaction the node doesn't have yet fails with `TransactionNotFound` and is retried later by the processor,
+    // without holding up the queue
+    let tx_meta = rpc_client.get_transaction(&signature, config).await?;
+
+    debug!("Transaction fetched");
+
+    let Some(transaction) = tx_meta.transaction.transaction.decode() else {
+        return Err(MonitorError::TransactionParse("Failed to decode transaction".to_string()));
+    };
+
+    let Some(meta) = &tx_meta.transaction.meta else {
+        return Err(MonitorError::TransactionParse("Transaction metadata missing".to_string()));
+    };
+
+    // the logged CreateEvent is authoritative; instruction data is the fallback for responses without it
+    let logs: Option<Vec<String>> = meta.log_messages.clone().into();
+    let logged = logs.as_deref().and_then(|logs| {
+        programs
+            .iter()
+            .find_map(|program| find_create_event(logs, program).map(|event| CreatedToken::from_event(event, program)))
+    });
+    let created = match logged {
+        Some(created) => {
+            debug!("Create event decoded from program logs");
+            created
+        }
+        None => match create_from_instructions(&transaction, programs)? {
+            Some(created) => {
+                debug!("Create instruction parsed");
+                created
+            }
+            None => {
+                let migrated = find_migration(&transaction, meta, programs)
+                    .map(|migration| Box::new(migrated_event(signature, tx_meta.slot, migration)));
+                return Ok(migrated.map(ProcessedTransaction::Migrated));
+            }
+        },
+    };
+
+    Span::current().record("mint", tracing::field::display(&created.mint));
+    let metadata = sanitize_metadata(created.metadata, options.keep_raw_metadata);
+    if metadata.sanitized {
+        debug!("Token metadata sanitized");
+    }
+    if !metadata.uri_valid {
+        debug!(uri = %metadata.uri, "Token metadata URI is not well-formed");
+    }
+    let buys = find_creation_buys(
+        &transaction,
+        meta,
+        &created.program,
+        &created.mint,
+        &created.bonding_curve,
+        &created.creator,
+    );
+    let cost = transaction_cost(&transaction, meta);
+
+    let bonding_curve = created.bonding_curve.to_string();
+    // at `minimal` the event goes out without the account round trip
+    let (mint, pump_data) = match options.detail_level {
+        DetailLevel::Minimal => (None, PumpFunData { bonding_curve, ..Default::default() }),
+        DetailLevel::Standard | DetailLevel::Full => {
+            let (mint, curve) =
+                get_create_accounts(rpc_client, &created.mint, &created.bonding_curve, &created.program, commitment)
+                    .await?;
+            let pump_data = curve_data(bonding_curve, &curve, mint.supply, mint.decimals, sol_price);
+            (Some(mint), pump_data)
+        }
+    };
+    if mint.is_some_and(|mint| !mint.authorities_standard(&created.program)) {
+        debug!("Token mint has authorities other than the program's");
+    }
+
+    let event = TokenCreatedEvent {
+        event_type: "tokenCreated".to_string(),
+        timestamp: chrono::Utc::now(),
+        sequence: 0,
+        transaction_signature: signature.to_string(),
+        slot: tx_meta.slot,
+        program_id: created.program.id.to_string(),
+        token: TokenDetails {
+            mint_address: created.mint.to_string(),
+            name: metadata.name,
+            symbol: metadata.symbol,
+            uri: metadata.uri,
+            creator: created.creator.to_string(),
+            supply: mint.map(|mint| mint.supply),
+            decimals: mint.map(|mint| mint.decimals),
+            mint_authority: mint.and_then(|mint| mint.mint_authority).map(|key| key.to_string()),
+            freeze_authority: mint.and_then(|mint| mint.freeze_authority).map(|key| key.to_string()),
+            authorities_standard: mint.map(|mint| mint.authorities_standard(&created.program)),
+            sanitized: metadata.sanitized,
+            uri_valid: metadata.uri_valid,
+            raw: metadata.raw,
+        },
+        metadata: None,
+        initial_buy: buys.initial_buy,
+        same_tx_buyers: buys.buyers,
+        same_tx_buy_sol: buys.sol,
+        tx_fee_lamports: cost.fee_lamports,
+        priority_fee_lamports: cost.priority_fee_lamports,
+        compute_units: cost.compute_units,
+        creator_stats: None,
+        creator_label: None,
+        creator_tags: Vec::new(),
+        similar_recent_count: None,
+        processing_latency_ms: None,
+        chain_latency_ms: chain_latency_ms(pending.observed_at, tx_meta.block_time),
+        stale: false,
+        signer_pubkey: None,
+        signature: None,
+        raw_transaction: options
+            .keep_raw_transaction
+            .then(|| raw_transaction(signature, &tx_meta))
+            .flatten()
+            .map(Arc::new),
+        pump_data,
+    };
+    Ok(Some(ProcessedTransaction::Created(Box::new(event))))
+}
+
+/// the event's view of a fetched bonding curve, with prices for a mint of `supply` and `decimals`.
+fn curve_data(
+    bonding_curve: String,
+    curve: &BondingCurveAccountData,
+    supply: u64,
+    decimals: u8,
+    sol_price: Option<&SolPriceCell>,
+) -> PumpFunData {
+    let price_sol = token_price_in_sol(curve, decimals);
+    let market_cap_sol = price_sol * supply as f64 / 10f64.powi(decimals as i32);
+    let sol_usd = sol_price.and_then(SolPriceCell::fresh_price);
+    PumpFunData {
+        bonding_curve,
+        virtual_sol_reserves: Some(curve.virtual_sol_reserves),
+        virtual_token_reserves: Some(curve.virtual_token_reserves),
+        real_sol_reserves: Some(curve.real_sol_reserves),
+        real_token_reserves: Some(curve.real_token_reserves),
+        complete: curve.complete,
+        price_sol: Some(price_sol),
+        market_cap_sol: Some(market_cap_sol),
+        price_usd: sol_usd.map(|usd| price_sol * usd),
+        market_cap_usd: sol_usd.map(|usd| market_cap_sol * usd),
+    }
+}
+
+/// the `tokenMigrated` message for a migration found in the transaction with `signature`.
+fn migrated_event(signature: Signature, slot: u64, migration: Migration) -> TokenMigratedEvent {
+    TokenMigratedEvent {
+        timestamp: Utc::now(),
+        transaction_signature: signature.to_string(),
+        slot,
+        program_id: migration.program.id.to_string(),
+        mint: migration.mint.to_string(),
+        // named by the processor, from the token registry
+        name: None,
+        symbol: None,
+        bonding_curve: migration.bonding_curve.to_string(),
+        pool: migration.pool.to_string(),
+        pool_program: migration.pool_program.to_string(),
+        pool_token_reserves: migration.token_reserves,
+        pool_sol_reserves: migration.sol_reserves,
+    }
+}
+
+/// Whether a `getTransaction` failure means the node hasn't caught up with the notification yet.
+///
+/// The node answers `null` for a transaction it doesn't have, which the client reports as a failure to deserialize.
+fn transaction_not_available(error: &ClientError) -> bool {
+    match error.kind() {
+        ClientErrorKind::SerdeJson(_) => true,
+        // block not available / block status not available yet
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => matches!(*code, -32004 | -32014),
+        ClientErrorKind::RpcError(RpcError::ForUser(message)) | ClientErrorKind::Custom(message) => {
+            message.contains("not found") || message.contains("not available")
+        }
+        _ => false,
+    }
+}
+
+/// Milliseconds from the block time of a transaction to when its notification was observed.
+///
+/// Block times are whole seconds, so this overstates the latency by up to a second; a local clock behind the
+/// validators' reads as 0 rather than negative. `None` when the node reported no block time.
+pub(crate) fn chain_latency_ms(observed_at: DateTime<Utc>, block_time: Option<i64>) -> Option<u64> {
+    let block_time_ms = block_time?.checked_mul(1000)?;
+    Some(u64::try_from(observed_at.timestamp_millis().saturating_sub(block_time_ms)).unwrap_or(0))
+}
+
+/// the transaction as fetched, for clients that decode it themselves; `None` unless it came back base64 encoded with its meta.
+fn raw_transaction(signature: Signature, tx_meta: &EncodedConfirmedTransactionWithStatusMeta) -> Option<RawTransaction> {
+    let EncodedTransaction::Binary(transaction, TransactionBinaryEncoding::Base64) = &tx_meta.transaction.transaction else {
+        return None;
+    };
+    Some(RawTransaction {
+        signature: signature.to_string(),
+        slot: tx_meta.slot,
+        transaction: transaction.clone(),
+        meta: tx_meta.transaction.meta.clone()?,
+    })
+}
+
+/// the parts of a token creation read from the transaction itself, before any account lookups.
+struct CreatedToken {
+    /// the monitored program that created the token
+    program: ProgramConfig,
+    metadata: CreateInstructionData,
+    mint: Pubkey,
+    bonding_curve: Pubkey,
+    creator: Pubkey,
+}
+
+impl CreatedToken {
+    fn from_event(event: CreateEventData, program: &ProgramConfig) -> Self {
+        Self {
+            program: *program,
+            metadata: CreateInstructionData {
+                name: event.name,
+                symbol: event.symbol,
+                uri: event.uri,
+            },
+            mint: Pubkey::new_from_array(event.mint),
+            bonding_curve: Pubkey::new_from_array(event.bonding_curve),
+            creator: Pubkey::new_from_array(event.user),
+        }
+    }
+}
+
+/// reads the first top-level Create instruction of a monitored program, relying on pump.fun's account order.
+fn create_from_instructions(transaction: &VersionedTransaction, programs: &[ProgramConfig]) -> Result<Option<CreatedToken>> {
+    let account_keys = transaction.message.static_account_keys();
+
+    for instruction in transaction.message.instructions() {
+        let Some(program) = account_keys
+            .get(instruction.program_id_index as usize)
+            .and_then(|program_id| programs.iter().find(|program| program.id == *program_id))
+        else {
+            continue;
+        };
+        if !instruction.data.starts_with(&program.create_instruction) {
+            continue;
+        }
+
+        let metadata = CreateInstructionData::deserialize(&mut &instruction.data[8..])?;
+        let instruction_account = |position: usize| {
+            instruction
+                .accounts
+                .get(position)
+                .and_then(|&index| account_keys.get(index as usize))
+                .copied()
+                .ok_or_else(|| MonitorError::DataNotFound(format!("create instruction account #{}", position)))
+        };
+        return Ok(Some(CreatedToken {
+            program: *program,
+            metadata,
+            mint: instruction_account(CREATE_ACCOUNT_MINT)?,
+            bonding_curve: instruction_account(CREATE_ACCOUNT_BONDING_CURVE)?,
+            creator: instruction_account(CREATE_ACCOUNT_USER)?,
+        }));
+    }
+
+    Ok(None)
+}
+
+
+/// tokens a fresh pump.fun curve has for sale; the curve completes once all of them are sold.
+pub(crate) const INITIAL_REAL_TOKEN_RESERVES: u64 = 793_100_000_000_000;
+
+/// share of the curve's tokens sold so far, from 0 to 100; a completed curve is always at 100.
+pub(crate) fn curve_progress_pct(curve: &BondingCurveAccountData) -> f64 {
+    if curve.complete {
+        return 100.0;
+    }
+    let sold = INITIAL_REAL_TOKEN_RESERVES.saturating_sub(curve.real_token_reserves);
+    sold as f64 * 100.0 / INITIAL_REAL_TOKEN_RESERVES as f64
+}
+
+/// token price in SOL implied by the bonding curve's virtual reserves.
+pub(crate) fn token_price_in_sol(curve: &BondingCurveAccountData, decimals: u8) -> f64 {
+    if curve.virtual_token_reserves == 0 {
+        return 0.0;
+    }
+    let sol = curve.virtual_sol_reserves as f64 / 1_000_000_000.0;
+    let tokens = curve.virtual_token_reserves as f64 / 10f64.powi(decimals as i32);
+    sol / tokens
+}
+
+
+/// Fetches the new token's mint and bonding curve in a single request.
+///
+/// A load-balanced RPC can lag behind the node that confirmed the transaction, and at `processed` the accounts often
+/// don't exist yet when the notification arrives, so a missing account is retried like a timeout or rate limit. Errors
+/// that won't go away, such as an unparsable response, fail straight away.
+async fn get_create_accounts(
+    rpc_client: Arc<dyn SolanaRpc>,
+    mint_address: &Pubkey,
+    bonding_curve_address: &Pubkey,
+    program: &ProgramConfig,
+    commitment: Commitment,
+) -> Result<(MintInfo, BondingCurveAccountData)> {
+    let addresses = [*mint_address, *bonding_curve_address];
+    let mut attempts = 0;
+    let accounts = loop {
+        match fetch_accounts(rpc_client.as_ref(), &addresses).await {
+            Ok(accounts) => break accounts,
+            Err(e) if e.is_retryable() && attempts < commitment.retries() => {
+                attempts += 1;
+                let delay = Duration::from_millis(500 * attempts).max(e.retry_after().unwrap_or_default());
+                warn!(
+                    "Attempt {} to fetch accounts for mint {} failed: {}. Retrying in {:?}...",
+                    attempts, mint_address, e, delay
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(e),
+        }
+    };
+
+    Ok((
+        parse_mint(&accounts[0])?,
+        parse_bonding_curve(&accounts[1].data, &program.bonding_curve_account)?,
+    ))
+}
+
+
+/// every account in `addresses`, failing if any of them does not exist.
+async fn fetch_accounts(rpc_client: &dyn SolanaRpc, addresses: &[Pubkey]) -> Result<Vec<Account>> {
+    let accounts = rpc_client.get_multiple_accounts(addresses).await?;
+    if accounts.len() != addresses.len() {
+        return Err(MonitorError::DataNotFound(format!(
+            "{} of {} requested accounts",
+            accounts.len(),
+            addresses.len()
+        )));
+    }
+    addresses
+        .iter()
+        .zip(accounts)
+        .map(|(address, account)| account.ok_or(MonitorError::AccountNotFound(*address)))
+        .collect()
+}
+
+
+/// what the event carries of an SPL token mint account.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct MintInfo {
+    supply: u64,
+    decimals: u8,
+    /// `None` once revoked
+    mint_authority: Option<Pubkey>,
+    freeze_authority: Option<Pubkey>,
+}
+
+impl MintInfo {
+    /// whether nobody but `program` can mint more of the token and nobody can freeze it: the mint authority is revoked
+    /// or still the program's PDA, and there is no freeze authority. Anything else is a fork or modified program.
+    fn authorities_standard(&self, program: &ProgramConfig) -> bool {
+        let foreign_mint_authority = self.mint_authority.is_some_and(|authority| authority != program.mint_authority);
+        !foreign_mint_authority && self.freeze_authority.is_none()
+    }
+}
+
+/// supply, decimals and authorities of an SPL token mint account.
+fn parse_mint(account: &Account) -> Result<MintInfo> {
+    let mint_data =
+        Mint::unpack(&account.data).map_err(|e| MonitorError::TransactionParse(e.to_string()))?;
+
+    Ok(MintInfo {
+        supply: mint_data.supply,
+        decimals: mint_data.decimals,
+        mint_authority: mint_data.mint_authority.into(),
+        freeze_authority: mint_data.freeze_authority.into(),
+    })
+}
+
+
+/// decodes bonding curve account data, checking the discriminator and that every field is present.
+pub(crate) fn parse_bonding_curve(account_data: &[u8], discriminator: &[u8; 8]) -> Result<BondingCurveAccountData> {
+    if !account_data.starts_with(discriminator) {
+        return Err(MonitorError::InvalidDiscriminator);
+    }
+    if account_data.len() < BONDING_CURVE_MIN_LEN {
+        return Err(MonitorError::TransactionParse(format!(
+            "Bonding curve account is {} bytes, expected at least {}",
+            account_data.len(),
+            BONDING_CURVE_MIN_LEN
+        )));
+    }
+
+    // deserialize the rest of the data; trailing fields added by newer program versions are ignored
+    let curve_data = BondingCurveAccountData::deserialize(&mut &account_data[8..])?;
+    Ok(curve_data)
+}
+
+mod compute_budget;
+mod creators;
+mod dead_letters;
+mod dedupe;
+mod diagnostics;
+mod initial_buy;
+mod logs;
+mod migration;
+#[cfg(test)]
+mod mock;
+mod overflow;
+mod preview;
+mod provider_limits;
+mod recording;
+mod retry;
+mod self_test;
+mod sequencer;
+pub(crate) mod sanitize;
+mod similarity;
+mod snapshot;
+mod stream;
+mod subscription;
+mod transport;
+mod watchdog;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,151 @@
+//! # Processor Overflow
+//!
+//! The WebSocket reader must never wait on the processor: while it waits, frames pile up in the server's buffers until
+//! the RPC provider drops the connection. Signatures are handed over with `try_send`, and whatever doesn't fit in the
+//! processor queue waits in a bounded spill buffer, in arrival order, until there is room again. When the spill buffer
+//! is full too, its oldest signature is dropped; the newest launches are the ones worth catching up on.
+
+use super::PendingSignature;
+use crate::metrics::Metrics;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, error::TrySendError, OwnedPermit};
+use tracing::{error, warn};
+
+/// at most one warning about dropped signatures per interval.
+const DROP_WARNING_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Non-blocking front of the processor queue, owned by one reader loop.
+///
+/// Dropping it doesn't lose what is spilled: the rest is forwarded in the background as the processor makes room.
+pub(crate) struct ProcessorQueue {
+    sender: mpsc::Sender<PendingSignature>,
+    spill: VecDeque<PendingSignature>,
+    spill_capacity: usize,
+    metrics: Arc<Metrics>,
+    /// drops since the last warning, and when that warning was logged
+    unreported_drops: u64,
+    last_warning: Option<Instant>,
+}
+
+impl ProcessorQueue {
+    pub fn new(sender: mpsc::Sender<PendingSignature>, spill_capacity: usize, metrics: Arc<Metrics>) -> Self {
+        Self {
+            sender,
+            spill: VecDeque::new(),
+            spill_capacity,
+            metrics,
+            unreported_drops: 0,
+            last_warning: None,
+        }
+    }
+
+    /// Queues `pending` without waiting, behind any signatures already spilled. `false` once the processor has stopped.
+    pub fn push(&mut self, pending: PendingSignature) -> bool {
+        if !self.drain() {
+            return false;
+        }
+        if self.spill.is_empty() {
+            match self.sender.try_send(pending) {
+                Ok(()) => return true,
+                Err(TrySendError::Closed(_)) => return false,
+                Err(TrySendError::Full(pending)) => self.spill_back(pending),
+            }
+        } else {
+            self.spill_back(pending);
+        }
+        true
+    }
+
+    /// Moves spilled signatures into the processor queue until it is full. `false` once the processor has stopped.
+    pub fn drain(&mut self) -> bool {
+        while let Some(pending) = self.spill.pop_front() {
+            match self.sender.try_send(pending) {
+                Ok(()) => {}
+                Err(TrySendError::Full(pending)) => {
+                    self.spill.push_front(pending);
+                    break;
+                }
+                Err(TrySendError::Closed(_)) => return false,
+            }
+        }
+        self.publish_depth();
+        true
+    }
+
+    /// signatures waiting for room in the processor queue.
+    pub fn spilled(&self) -> usize {
+        self.spill.len()
+    }
+
+    /// Resolves once the processor queue has room, with `None` if the processor has stopped.
+    ///
+    /// The future doesn't borrow the queue, so the reader can select on it alongside the socket and hand the permit to
+    /// `send_spilled`.
+    pub fn room(&self) -> impl Future<Output = Option<OwnedPermit<PendingSignature>>> {
+        let sender = self.sender.clone();
+        async move { sender.reserve_owned().await.ok() }
+    }
+
+    /// sends the oldest spilled signature through `permit`, then as many more as fit.
+    pub fn send_spilled(&mut self, permit: OwnedPermit<PendingSignature>) {
+        if let Some(pending) = self.spill.pop_front() {
+            permit.send(pending);
+        }
+        self.drain();
+    }
+
+    fn spill_back(&mut self, pending: PendingSignature) {
+        if self.spill.len() >= self.spill_capacity {
+            self.spill.pop_front();
+            self.record_drop();
+        }
+        self.spill.push_back(pending);
+        self.publish_depth();
+    }
+
+    fn record_drop(&mut self) {
+        self.metrics.processor_signatures_dropped.fetch_add(1, Ordering::Relaxed);
+        self.unreported_drops += 1;
+        let warning_due = match self.last_warning {
+            Some(at) => at.elapsed() >= DROP_WARNING_INTERVAL,
+            None => true,
+        };
+        if warning_due {
+            warn!(
+                "Processor queue and overflow buffer are full; dropped {} of the oldest waiting signatures",
+                self.unreported_drops
+            );
+            self.unreported_drops = 0;
+            self.last_warning = Some(Instant::now());
+        }
+    }
+
+    fn publish_depth(&self) {
+        self.metrics.processor_overflow_depth.store(self.spill.len(), Ordering::Relaxed);
+    }
+}
+
+impl Drop for ProcessorQueue {
+    /// hands what is still spilled to a task that waits for room, so a reconnect doesn't wait for the processor.
+    fn drop(&mut self) {
+        if self.spill.is_empty() {
+            return;
+        }
+        let spill = std::mem::take(&mut self.spill);
+        let sender = self.sender.clone();
+        let metrics = Arc::clone(&self.metrics);
+        tokio::spawn(async move {
+            for pending in spill {
+                if sender.send(pending).await.is_err() {
+                    error!("Transaction processing channel is closed.");
+                    break;
+                }
+            }
+            metrics.processor_overflow_depth.store(0, Ordering::Relaxed);
+        });
+    }
+}
@@ -0,0 +1,41 @@
+//! # Fast Path
+//!
+//! A `logsNotification` carries the transaction's logs, and with them the `CreateEvent` every new token is logged with:
+//! its mint, name, symbol, URI, creator and bonding curve. With `FAST_PATH` on, that is decoded as soon as the
+//! notification arrives and published as a `tokenCreatedPreview`, without waiting for `getTransaction` and the account
+//! fetches the `tokenCreated` event needs. Nothing was fetched for a preview: supply, curve and initial buy are unknown,
+//! and processing may still drop the token as a duplicate, a copycat or by the global filter, in which case no
+//! `tokenCreated` follows. The one that does follow has the preview's `transactionSignature` and `mintAddress`; how far
+//! ahead the preview went out is exported as `pump_fun_monitor_preview_lead_ms`.
+//!
+//! Creations that only show in the instruction data, without a logged `CreateEvent`, get no preview.
+
+use super::sanitize::sanitize_metadata;
+use super::{find_create_event, CreatedToken, ProgramConfig};
+use crate::data_models::TokenCreatedPreviewEvent;
+use chrono::{DateTime, Utc};
+use solana_sdk::signature::Signature;
+
+/// the preview of the first creation logged by a monitored program in `logs`; `None` when none is.
+pub(crate) fn preview_from_logs(
+    signature: &Signature,
+    logs: &[String],
+    programs: &[ProgramConfig],
+    received_at: DateTime<Utc>,
+) -> Option<TokenCreatedPreviewEvent> {
+    let created = programs
+        .iter()
+        .find_map(|program| find_create_event(logs, program).map(|event| CreatedToken::from_event(event, program)))?;
+    let metadata = sanitize_metadata(created.metadata, false);
+    Some(TokenCreatedPreviewEvent {
+        timestamp: received_at,
+        transaction_signature: signature.to_string(),
+        program_id: created.program.id.to_string(),
+        mint_address: created.mint.to_string(),
+        name: metadata.name,
+        symbol: metadata.symbol,
+        uri: metadata.uri,
+        creator: created.creator.to_string(),
+        bonding_curve: created.bonding_curve.to_string(),
+    })
+}
@@ -0,0 +1,52 @@
+//! # Provider Limits
+//!
+//! Recognises an RPC provider refusing a subscription or connection because the plan's limits are used up. Retrying
+//! such a refusal every few seconds can't succeed until something else disconnects or the plan changes, so the monitor
+//! backs off for `PROVIDER_LIMIT_BACKOFF_SECS` instead, logs the condition once and reports it on `/readyz`.
+//!
+//! Providers report it either as a JSON-RPC error answering `logsSubscribe`, or by closing the socket. Reasons are
+//! matched case-insensitively against the table below; a close with code 1013 (try again later) or 4029 (the
+//! WebSocket counterpart of HTTP 429) counts whatever its reason says.
+
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+
+/// provider, and a lower case part of the reasons it gives for hitting a plan limit.
+pub const LIMIT_REASONS: &[(&str, &str)] = &[
+    ("Helius", "too many subscriptions"),
+    ("Helius", "max subscriptions reached"),
+    ("Helius", "websocket connection limit"),
+    ("QuickNode", "exceeded the maximum number of subscriptions"),
+    ("QuickNode", "max concurrent websocket connections"),
+    ("QuickNode", "not available on your plan"),
+    ("Triton", "subscription limit exceeded"),
+    ("Triton", "too many connections"),
+    ("any", "subscription limit"),
+    ("any", "plan limit"),
+];
+
+/// close codes that mean "come back later" regardless of the reason given.
+const LIMIT_CLOSE_CODES: &[u16] = &[1013, 4029];
+
+/// the provider whose limit `message` reports, if it reports one.
+pub fn limit_in_message(message: &str) -> Option<&'static str> {
+    let message = message.to_lowercase();
+    LIMIT_REASONS
+        .iter()
+        .find(|(_, reason)| message.contains(reason))
+        .map(|(provider, _)| *provider)
+}
+
+/// a description of the limit a close frame reports, if it reports one.
+pub fn limit_in_close(frame: Option<&CloseFrame<'_>>) -> Option<String> {
+    let frame = frame?;
+    let code = u16::from(frame.code);
+    if LIMIT_CLOSE_CODES.contains(&code) || limit_in_message(&frame.reason).is_some() {
+        return Some(format!("connection closed with code {}: {}", code, frame.reason));
+    }
+    None
+}
+
+/// whether a `logsSubscribe` error is a provider limit: a 429 code or a known reason.
+pub fn is_limit_rejection(code: i64, message: &str) -> bool {
+    code == 429 || limit_in_message(message).is_some()
+}
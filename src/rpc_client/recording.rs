@@ -0,0 +1,299 @@
+//! # Fixture Recording
+//!
+//! Record-and-replay of the monitor's RPC traffic. A recording directory holds:
+//!
+//! - `notifications.jsonl`: one `{"receivedAtMs", "message"}` line per raw `logsSubscribe` text frame, in arrival order
+//! - `transactions/<signature>.json`: the `getTransaction` response (base64 encoded transaction)
+//! - `accounts/<pubkey>.json`: the last fetched state of each account, with base64 data
+//!
+//! `RecordingRpc` writes these while passing calls through to a live node; `ReplayRpc` serves them back without any network access.
+
+use super::transport::SolanaRpc;
+use crate::error::{MonitorError, Result};
+use async_trait::async_trait;
+use solana_account_decoder::UiDataSliceConfig;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_client::rpc_filter::RpcFilterType;
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+const NOTIFICATIONS_FILE: &str = "notifications.jsonl";
+const TRANSACTIONS_DIR: &str = "transactions";
+const ACCOUNTS_DIR: &str = "accounts";
+
+/// one raw log notification as received from the RPC WebSocket.
+///
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedNotification {
+    /// unix time in milliseconds the frame was received; replay only uses the differences between lines
+    pub received_at_ms: u64,
+    pub message: String,
+}
+
+/// an account as stored on disk, with the data base64 encoded like `getAccountInfo` returns it.
+///
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RecordedAccount {
+    lamports: u64,
+    owner: String,
+    data: String,
+    executable: bool,
+    rent_epoch: u64,
+}
+
+impl From<&Account> for RecordedAccount {
+    fn from(account: &Account) -> Self {
+        Self {
+            lamports: account.lamports,
+            owner: account.owner.to_string(),
+            data: BASE64.encode(&account.data),
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+        }
+    }
+}
+
+impl TryFrom<RecordedAccount> for Account {
+    type Error = String;
+
+    fn try_from(recorded: RecordedAccount) -> std::result::Result<Self, String> {
+        Ok(Account {
+            lamports: recorded.lamports,
+            owner: recorded.owner.parse().map_err(|e| format!("invalid owner: {}", e))?,
+            data: BASE64.decode(&recorded.data).map_err(|e| format!("invalid data: {}", e))?,
+            executable: recorded.executable,
+            rent_epoch: recorded.rent_epoch,
+        })
+    }
+}
+
+/// Writes notifications, transactions and accounts into a recording directory.
+///
+/// Write failures are logged and otherwise ignored: a recording must never take the live monitor down.
+pub struct FixtureRecorder {
+    dir: PathBuf,
+    notifications: Mutex<File>,
+}
+
+impl FixtureRecorder {
+    /// creates the directory layout, appending to an existing `notifications.jsonl` if there is one.
+    pub fn create(dir: &Path) -> Result<Self> {
+        let io_error = |e: std::io::Error| MonitorError::Config(format!("RECORD_FIXTURES '{}': {}", dir.display(), e));
+        fs::create_dir_all(dir.join(TRANSACTIONS_DIR)).map_err(io_error)?;
+        fs::create_dir_all(dir.join(ACCOUNTS_DIR)).map_err(io_error)?;
+        let notifications = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(NOTIFICATIONS_FILE))
+            .map_err(io_error)?;
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            notifications: Mutex::new(notifications),
+        })
+    }
+
+    pub fn record_notification(&self, message: &str) {
+        let line = RecordedNotification {
+            received_at_ms: chrono::Utc::now().timestamp_millis().max(0) as u64,
+            message: message.to_string(),
+        };
+        let mut file = self.notifications.lock().unwrap_or_else(|e| e.into_inner());
+        let written = serde_json::to_string(&line)
+            .map_err(std::io::Error::from)
+            .and_then(|json| writeln!(file, "{}", json));
+        if let Err(e) = written {
+            warn!("Failed to record notification: {}", e);
+        }
+    }
+
+    fn record_transaction(&self, signature: &Signature, transaction: &EncodedConfirmedTransactionWithStatusMeta) {
+        let path = self.dir.join(TRANSACTIONS_DIR).join(format!("{}.json", signature));
+        self.write_json(&path, transaction);
+    }
+
+    fn record_account(&self, pubkey: &Pubkey, account: &Account) {
+        let path = self.dir.join(ACCOUNTS_DIR).join(format!("{}.json", pubkey));
+        self.write_json(&path, &RecordedAccount::from(account));
+    }
+
+    fn write_json(&self, path: &Path, value: &impl Serialize) {
+        let written = serde_json::to_vec_pretty(value)
+            .map_err(std::io::Error::from)
+            .and_then(|json| fs::write(path, json));
+        if let Err(e) = written {
+            warn!("Failed to record fixture {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// `SolanaRpc` that forwards to `inner` and records every successful response.
+///
+pub struct RecordingRpc {
+    inner: Arc<dyn SolanaRpc>,
+    recorder: Arc<FixtureRecorder>,
+}
+
+impl RecordingRpc {
+    pub fn new(inner: Arc<dyn SolanaRpc>, recorder: Arc<FixtureRecorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+#[async_trait]
+impl SolanaRpc for RecordingRpc {
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        let transaction = self.inner.get_transaction(signature, config).await?;
+        self.recorder.record_transaction(signature, &transaction);
+        Ok(transaction)
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Account> {
+        let account = self.inner.get_account(pubkey).await?;
+        self.recorder.record_account(pubkey, &account);
+        Ok(account)
+    }
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        let accounts = self.inner.get_multiple_accounts(pubkeys).await?;
+        for (pubkey, account) in pubkeys.iter().zip(&accounts) {
+            if let Some(account) = account {
+                self.recorder.record_account(pubkey, account);
+            }
+        }
+        Ok(accounts)
+    }
+
+    /// passed through unrecorded: the transactions a lookback fetches are recorded, the listing itself isn't.
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.inner.get_signatures_for_address(address, limit).await
+    }
+
+    /// passed through unrecorded, like signature listings; a replay has no snapshot to serve.
+    async fn get_program_accounts(
+        &self,
+        program: &Pubkey,
+        filters: Vec<RpcFilterType>,
+        data_slice: Option<UiDataSliceConfig>,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        self.inner.get_program_accounts(program, filters, data_slice).await
+    }
+}
+
+/// `SolanaRpc` serving a recording directory. Anything that wasn't recorded is reported the way a node would: an error for
+/// transactions and single accounts, `None` in `get_multiple_accounts`.
+///
+pub struct ReplayRpc {
+    dir: PathBuf,
+}
+
+impl ReplayRpc {
+    pub fn new(dir: &Path) -> Self {
+        Self { dir: dir.to_path_buf() }
+    }
+
+    fn read_account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        let path = self.dir.join(ACCOUNTS_DIR).join(format!("{}.json", pubkey));
+        let Some(recorded) = read_json::<RecordedAccount>(&path)? else {
+            return Ok(None);
+        };
+        Account::try_from(recorded)
+            .map(Some)
+            .map_err(|e| replay_error(format!("{}: {}", path.display(), e)))
+    }
+}
+
+#[async_trait]
+impl SolanaRpc for ReplayRpc {
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+        _config: RpcTransactionConfig,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        let path = self.dir.join(TRANSACTIONS_DIR).join(format!("{}.json", signature));
+        read_json(&path)?.ok_or_else(|| replay_error(format!("transaction {} was not recorded", signature)))
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Account> {
+        self.read_account(pubkey)?
+            .ok_or_else(|| replay_error(format!("account {} was not recorded", pubkey)))
+    }
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        pubkeys.iter().map(|pubkey| self.read_account(pubkey)).collect()
+    }
+
+    /// signature listings aren't recorded, so in a replay every creator is new.
+    async fn get_signatures_for_address(
+        &self,
+        _address: &Pubkey,
+        _limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_program_accounts(
+        &self,
+        _program: &Pubkey,
+        _filters: Vec<RpcFilterType>,
+        _data_slice: Option<UiDataSliceConfig>,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Reads the notifications of a recording in order, skipping (and logging) lines that don't parse.
+///
+pub fn read_notifications(dir: &Path) -> Result<Vec<RecordedNotification>> {
+    let path = dir.join(NOTIFICATIONS_FILE);
+    let file = File::open(&path)
+        .map_err(|e| MonitorError::Config(format!("REPLAY_FIXTURES '{}': {}", path.display(), e)))?;
+
+    let mut notifications = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| MonitorError::Config(format!("REPLAY_FIXTURES '{}': {}", path.display(), e)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(notification) => notifications.push(notification),
+            Err(e) => warn!("Skipping {} line {}: {}", path.display(), index + 1, e),
+        }
+    }
+    Ok(notifications)
+}
+
+/// `Ok(None)` when `path` doesn't exist.
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(replay_error(format!("{}: {}", path.display(), e))),
+    };
+    serde_json::from_slice(&contents)
+        .map(Some)
+        .map_err(|e| replay_error(format!("{}: {}", path.display(), e)))
+}
+
+fn replay_error(message: String) -> MonitorError {
+    ClientError::from(ClientErrorKind::Custom(message)).into()
+}
@@ -0,0 +1,93 @@
+//! # Fetch Retries
+//!
+//! A log notification often arrives before the RPC node answering `getTransaction` has the transaction, especially
+//! behind a load balancer. Rather than hold up the processor, a signature whose transaction isn't available yet waits
+//! here and is fetched again after growing delays. Once the delays would add up to more than `RETRY_DEADLINE` the
+//! signature is given up on and counted as missed.
+
+use super::PendingSignature;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+/// delays before the first fetch retries; every later retry waits as long as the last.
+pub const RETRY_DELAYS: [Duration; 4] = [
+    Duration::from_millis(500),
+    Duration::from_secs(2),
+    Duration::from_secs(5),
+    Duration::from_secs(15),
+];
+
+/// retry delays a signature may accumulate before it is given up on.
+pub const RETRY_DEADLINE: Duration = Duration::from_secs(120);
+
+/// signatures waiting for a retry; past this, a failed fetch is given up on straight away.
+pub const MAX_WAITING_RETRIES: usize = 10_000;
+
+/// Signatures waiting to be fetched again, in the order they are due.
+///
+#[derive(Default)]
+pub(crate) struct RetryQueue {
+    /// keyed by due time, then by insertion so signatures due together keep their order
+    waiting: BTreeMap<(Instant, u64), PendingSignature>,
+    next_id: u64,
+    /// the processor queue has closed; only retries are left
+    closed: bool,
+}
+
+impl RetryQueue {
+    /// Schedules another fetch of `pending`, whose fetch just failed, and returns the delay before it; never sooner than
+    /// a rate-limiting provider's `retry_after`.
+    ///
+    /// Hands the signature back instead once it has run out of retries or too many others are waiting.
+    pub fn schedule(&mut self, mut pending: PendingSignature, retry_after: Option<Duration>) -> Result<Duration, PendingSignature> {
+        let failures = pending.fetch_failures + 1;
+        if total_delay(failures) > RETRY_DEADLINE || self.waiting.len() >= MAX_WAITING_RETRIES {
+            return Err(pending);
+        }
+        pending.fetch_failures = failures;
+        let delay = retry_delay(failures).max(retry_after.unwrap_or_default());
+        self.waiting.insert((Instant::now() + delay, self.next_id), pending);
+        self.next_id += 1;
+        Ok(delay)
+    }
+
+    /// signatures waiting for a retry.
+    pub fn len(&self) -> usize {
+        self.waiting.len()
+    }
+
+    /// Waits for the next signature to fetch: a retry that has come due, or else a new one from `receiver`.
+    ///
+    /// Returns `None` once `receiver` has closed and no retries are left.
+    pub async fn next(&mut self, receiver: &mut mpsc::Receiver<PendingSignature>) -> Option<PendingSignature> {
+        loop {
+            let due = self.waiting.keys().next().map(|(at, _)| *at);
+            tokio::select! {
+                biased;
+                _ = tokio::time::sleep_until(due.unwrap_or_else(Instant::now)), if due.is_some() => {
+                    if let Some(entry) = self.waiting.first_entry() {
+                        return Some(entry.remove());
+                    }
+                }
+                received = receiver.recv(), if !self.closed => match received {
+                    Some(pending) => return Some(pending),
+                    None => self.closed = true,
+                },
+                else => return None,
+            }
+        }
+    }
+}
+
+/// the delay after the `failures`th failed fetch.
+pub(crate) fn retry_delay(failures: u32) -> Duration {
+    let index = (failures.max(1) as usize - 1).min(RETRY_DELAYS.len() - 1);
+    RETRY_DELAYS[index]
+}
+
+/// every delay up to and including the one after the `failures`th failed fetch.
+fn total_delay(failures: u32) -> Duration {
+    (1..=failures).map(retry_delay).sum()
+}
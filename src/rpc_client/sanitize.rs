@@ -0,0 +1,72 @@
+//! # Metadata Sanitization
+//!
+//! Names, symbols and URIs come straight from create instruction data, which the token creator fully controls. Before an event is broadcast they are stripped of control and bidirectional-override characters, trimmed, and truncated to bounded lengths, so downstream dashboards never receive multi-kilobyte or visually spoofed strings.
+
+use crate::data_models::{CreateInstructionData, RawTokenMetadata};
+
+/// longest token name broadcast, in characters.
+pub const MAX_NAME_CHARS: usize = 64;
+
+/// longest token symbol broadcast, in characters.
+pub const MAX_SYMBOL_CHARS: usize = 16;
+
+/// longest metadata URI broadcast, in characters.
+pub const MAX_URI_CHARS: usize = 512;
+
+/// URI schemes pump.fun metadata is served from.
+const URI_SCHEMES: [&str; 4] = ["https", "http", "ipfs", "ar"];
+
+/// token metadata ready to be put in an event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SanitizedMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub sanitized: bool,
+    pub uri_valid: bool,
+    pub raw: Option<RawTokenMetadata>,
+}
+
+/// Cleans the instruction's metadata, keeping the original values under `raw` when `keep_raw` is set and anything changed.
+pub fn sanitize_metadata(data: CreateInstructionData, keep_raw: bool) -> SanitizedMetadata {
+    let name = clean(&data.name, MAX_NAME_CHARS);
+    let symbol = clean(&data.symbol, MAX_SYMBOL_CHARS);
+    let uri = clean(&data.uri, MAX_URI_CHARS);
+
+    let sanitized = name != data.name || symbol != data.symbol || uri != data.uri;
+    let uri_valid = is_well_formed_uri(&uri);
+    let raw = (keep_raw && sanitized).then_some(RawTokenMetadata {
+        name: data.name,
+        symbol: data.symbol,
+        uri: data.uri,
+    });
+
+    SanitizedMetadata {
+        name,
+        symbol,
+        uri,
+        sanitized,
+        uri_valid,
+        raw,
+    }
+}
+
+/// drops hidden characters, trims surrounding whitespace and truncates to `max_chars` characters.
+pub fn clean(value: &str, max_chars: usize) -> String {
+    let visible: String = value.chars().filter(|c| !is_hidden(*c)).collect();
+    visible.trim().chars().take(max_chars).collect::<String>().trim_end().to_string()
+}
+
+/// control characters (including NUL) and the Unicode bidi controls that can reorder how text is displayed.
+fn is_hidden(c: char) -> bool {
+    c.is_control()
+        || matches!(
+            c,
+            '\u{061C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}' | '\u{FEFF}'
+        )
+}
+
+/// whether `uri` parses as an absolute URL with one of the schemes metadata is hosted on.
+pub fn is_well_formed_uri(uri: &str) -> bool {
+    url::Url::parse(uri).is_ok_and(|url| URI_SCHEMES.contains(&url.scheme()))
+}
@@ -0,0 +1,113 @@
+//! # Parser Self-Test
+//!
+//! Creations are recognised by discriminators fixed in the program, or set in `[rpc.parsers.<program id>]`. Should the
+//! program be upgraded to new ones, every transaction would parse as "creates no token" and the monitor would go quiet
+//! without a single error. With `PARSER_SELF_TEST` on, the newest `PARSER_SELF_TEST_SIGNATURES` transactions of every
+//! monitored program are run through the same parser at startup and every `PARSER_SELF_TEST_INTERVAL_SECS`. A window
+//! where at least `PARSER_SELF_TEST_MIN_TRANSACTIONS` of them parsed without a single creation marks the parser
+//! possibly stale on `/readyz` and logs one ERROR, until a later window finds creations again. Windows with fewer
+//! transactions say nothing either way: a quiet program is not a broken parser.
+
+use super::transport::SolanaRpc;
+use super::{
+    process_transaction, Commitment, DetailLevel, PendingSignature, ProcessedTransaction, ProcessingOptions,
+    ProgramConfig,
+};
+use crate::config::ParserSelfTestConfig;
+use futures_util::{stream, StreamExt};
+use serde::Serialize;
+use solana_sdk::signature::Signature;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// transactions fetched at once.
+const SELF_TEST_CONCURRENCY: usize = 4;
+
+/// what the latest conclusive self-test found.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ParserStatus {
+    /// no conclusive self-test ran, or it is off
+    #[default]
+    Unchecked,
+    /// the latest conclusive window had creations
+    Ok,
+    /// the latest conclusive window had program activity but no creations
+    #[serde(rename = "parser_possibly_stale")]
+    PossiblyStale,
+}
+
+/// what one self-test window found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SelfTestWindow {
+    /// successful program transactions that were fetched and parsed
+    pub transactions: usize,
+    /// those among them that created a token
+    pub creations: usize,
+}
+
+impl SelfTestWindow {
+    /// the parser's status after this window, `None` when it had too few transactions to tell.
+    pub fn status(&self, min_transactions: usize) -> Option<ParserStatus> {
+        if self.creations > 0 {
+            Some(ParserStatus::Ok)
+        } else if self.transactions >= min_transactions {
+            Some(ParserStatus::PossiblyStale)
+        } else {
+            None
+        }
+    }
+}
+
+/// Runs the newest `config.signatures` transactions of every program in `programs` through the parser.
+///
+/// Failed transactions are left out, as are those that couldn't be fetched or parsed; neither says anything about the
+/// discriminators. Accounts aren't fetched, whatever `DETAIL_LEVEL` is.
+pub(crate) async fn sample_window(
+    rpc_client: Arc<dyn SolanaRpc>,
+    programs: &[ProgramConfig],
+    commitment: Commitment,
+    config: &ParserSelfTestConfig,
+) -> SelfTestWindow {
+    let mut signatures = Vec::new();
+    for program in programs {
+        match rpc_client.get_signatures_for_address(&program.id, config.signatures).await {
+            Ok(statuses) => signatures.extend(
+                statuses
+                    .into_iter()
+                    .filter(|status| status.err.is_none())
+                    .filter_map(|status| Signature::from_str(&status.signature).ok()),
+            ),
+            Err(e) => warn!("Parser self-test could not list the transactions of {}: {}", program.id, e),
+        }
+    }
+
+    let options = ProcessingOptions {
+        detail_level: DetailLevel::Minimal,
+        ..Default::default()
+    };
+    let outcomes: Vec<Option<bool>> = stream::iter(signatures)
+        .map(|signature| {
+            let rpc_client = Arc::clone(&rpc_client);
+            async move {
+                let pending = PendingSignature::new(signature);
+                match process_transaction(rpc_client, &pending, programs, commitment, None, options).await {
+                    Ok(processed) => Some(matches!(processed, Some(ProcessedTransaction::Created(_)))),
+                    Err(e) => {
+                        debug!("Parser self-test skipped {}: {}", signature, e);
+                        None
+                    }
+                }
+            }
+        })
+        .buffer_unordered(SELF_TEST_CONCURRENCY)
+        .collect()
+        .await;
+
+    let parsed: Vec<bool> = outcomes.into_iter().flatten().collect();
+    SelfTestWindow {
+        transactions: parsed.len(),
+        creations: parsed.iter().filter(|&&created| created).count(),
+    }
+}
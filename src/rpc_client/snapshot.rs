@@ -0,0 +1,193 @@
+//! # Curve Snapshot
+//!
+//! Lists every bonding curve that hasn't completed yet with `getProgramAccounts`, so a consumer starting up knows the
+//! tokens that were launched before the monitor was running. Only the discriminator and the fixed fields are requested
+//! (`dataSlice`), and curves are selected on the node with `memcmp` filters on the discriminator and the `complete` flag.
+//!
+//! With `CURVE_SNAPSHOT_PARTITIONED` the listing is split into 256 requests, one per value of the lowest byte of
+//! `virtual_token_reserves`, because many providers refuse or time out an unfiltered listing of a program with hundreds
+//! of thousands of accounts. A few partitions are fetched at a time and progress is logged as they come in.
+//!
+//! The snapshot is written to `CURVE_SNAPSHOT_FILE` as JSON lines; the curves in the file's previous snapshot are
+//! compared against first, and the ones that appeared or disappeared (completed or closed) since are logged. A curve
+//! account doesn't hold its mint, so the snapshot lists bonding curve addresses only.
+
+use super::transport::SolanaRpc;
+use super::{curve_progress_pct, parse_bonding_curve, ProgramConfig, BONDING_CURVE_MIN_LEN};
+use crate::config::CurveSnapshotConfig;
+use crate::data_models::CurveSnapshotEvent;
+use crate::error::{MonitorError, Result};
+use chrono::Utc;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use solana_account_decoder::UiDataSliceConfig;
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// offset of the `complete` flag in the curve account, after the discriminator and five u64 fields.
+const COMPLETE_OFFSET: usize = 8 + 5 * 8;
+
+/// offset of `virtual_token_reserves`, whose lowest byte picks the partition.
+const PARTITION_OFFSET: usize = 8;
+
+/// partition requests in flight at once.
+const SNAPSHOT_CONCURRENCY: usize = 4;
+
+/// curves that appeared and disappeared between two snapshots.
+///
+#[derive(Debug, Default, PartialEq)]
+pub struct SnapshotDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Takes the snapshot, writes it over `config.file` and returns how it differs from the one the file held.
+///
+pub async fn run_curve_snapshot(
+    rpc_client: &dyn SolanaRpc,
+    programs: &[ProgramConfig],
+    config: &CurveSnapshotConfig,
+) -> Result<SnapshotDiff> {
+    let previous = read_snapshot(&config.file)?;
+    info!("Taking a snapshot of active bonding curves...");
+    let curves = take_snapshot(rpc_client, programs, config.partitioned).await?;
+    write_snapshot(&config.file, &curves)?;
+
+    let diff = diff_snapshots(&previous, &curves);
+    info!(
+        "Curve snapshot: {} active curves written to {} ({} new, {} gone since the last snapshot)",
+        curves.len(),
+        config.file.display(),
+        diff.added.len(),
+        diff.removed.len()
+    );
+    Ok(diff)
+}
+
+/// every curve of `programs` that hasn't completed, sorted by address.
+pub async fn take_snapshot(
+    rpc_client: &dyn SolanaRpc,
+    programs: &[ProgramConfig],
+    partitioned: bool,
+) -> Result<Vec<CurveSnapshotEvent>> {
+    let partitions: Vec<Option<u8>> = if partitioned {
+        (0..=u8::MAX).map(Some).collect()
+    } else {
+        vec![None]
+    };
+    let requests: Vec<(&ProgramConfig, Option<u8>)> = programs
+        .iter()
+        .flat_map(|program| partitions.iter().map(move |partition| (program, *partition)))
+        .collect();
+    let total = requests.len();
+
+    let mut curves = Vec::new();
+    let mut done = 0;
+    let mut results = stream::iter(requests)
+        .map(|(program, partition)| fetch_partition(rpc_client, program, partition))
+        .buffer_unordered(SNAPSHOT_CONCURRENCY);
+    while let Some(partition) = results.try_next().await? {
+        curves.extend(partition);
+        done += 1;
+        if total > 1 && (done % 32 == 0 || done == total) {
+            info!("Curve snapshot: {}/{} requests done, {} curves so far", done, total, curves.len());
+        }
+    }
+
+    curves.sort_by(|a: &CurveSnapshotEvent, b| a.bonding_curve.cmp(&b.bonding_curve));
+    Ok(curves)
+}
+
+/// the active curves of `program` in one partition, or all of them when `partition` is `None`.
+async fn fetch_partition(
+    rpc_client: &dyn SolanaRpc,
+    program: &ProgramConfig,
+    partition: Option<u8>,
+) -> Result<Vec<CurveSnapshotEvent>> {
+    let mut filters = vec![
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, program.bonding_curve_account.to_vec())),
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(COMPLETE_OFFSET, vec![0])),
+    ];
+    if let Some(byte) = partition {
+        filters.push(RpcFilterType::Memcmp(Memcmp::new_raw_bytes(PARTITION_OFFSET, vec![byte])));
+    }
+    let data_slice = UiDataSliceConfig {
+        offset: 0,
+        length: BONDING_CURVE_MIN_LEN,
+    };
+
+    let accounts = rpc_client
+        .get_program_accounts(&program.id, filters, Some(data_slice))
+        .await?;
+    let snapshot_at = Utc::now();
+    Ok(accounts
+        .into_iter()
+        .filter_map(|(address, account)| match parse_bonding_curve(&account.data, &program.bonding_curve_account) {
+            Ok(curve) => Some(CurveSnapshotEvent {
+                bonding_curve: address.to_string(),
+                program_id: program.id.to_string(),
+                virtual_sol_reserves: curve.virtual_sol_reserves,
+                virtual_token_reserves: curve.virtual_token_reserves,
+                real_sol_reserves: curve.real_sol_reserves,
+                real_token_reserves: curve.real_token_reserves,
+                token_total_supply: curve.token_total_supply,
+                progress_pct: curve_progress_pct(&curve),
+                snapshot_at,
+            }),
+            Err(e) => {
+                warn!("Skipping bonding curve {} in the snapshot: {}", address, e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// the curves in a snapshot file; a missing file is an empty snapshot and unreadable lines are skipped.
+pub fn read_snapshot(path: &Path) -> Result<Vec<CurveSnapshotEvent>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(MonitorError::Config(format!("CURVE_SNAPSHOT_FILE '{}': {}", path.display(), e))),
+    };
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(curve) => Some(curve),
+            Err(e) => {
+                warn!("Skipping a line of the previous curve snapshot {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// replaces the snapshot file, through a temporary file so a crash never leaves half a snapshot behind.
+fn write_snapshot(path: &Path, curves: &[CurveSnapshotEvent]) -> Result<()> {
+    let temporary = path.with_extension("tmp");
+    let write = || -> std::io::Result<()> {
+        let mut file = std::io::BufWriter::new(fs::File::create(&temporary)?);
+        for curve in curves {
+            serde_json::to_writer(&mut file, curve)?;
+            file.write_all(b"\n")?;
+        }
+        file.flush()?;
+        drop(file);
+        fs::rename(&temporary, path)
+    };
+    write().map_err(|e| MonitorError::Config(format!("CURVE_SNAPSHOT_FILE '{}': {}", path.display(), e)))
+}
+
+/// curves in `current` but not `previous`, and the other way round, each sorted by address.
+pub fn diff_snapshots(previous: &[CurveSnapshotEvent], current: &[CurveSnapshotEvent]) -> SnapshotDiff {
+    let before: HashSet<&str> = previous.iter().map(|curve| curve.bonding_curve.as_str()).collect();
+    let after: HashSet<&str> = current.iter().map(|curve| curve.bonding_curve.as_str()).collect();
+    let mut added: Vec<String> = after.difference(&before).map(|curve| curve.to_string()).collect();
+    let mut removed: Vec<String> = before.difference(&after).map(|curve| curve.to_string()).collect();
+    added.sort();
+    removed.sort();
+    SnapshotDiff { added, removed }
+}
@@ -0,0 +1,112 @@
+//! # Event Streams
+//!
+//! Wraps a receiver of the event broadcast in a `Stream`, so consumers can use `StreamExt` combinators instead of
+//! matching on `RecvError` themselves. A consumer that falls more than the broadcast capacity behind loses the oldest
+//! events; depending on its [`LagPolicy`] the stream reports that as a [`StreamItem::Gap`] item or only counts it.
+//!
+//! ```no_run
+//! use futures_util::StreamExt;
+//! use pump_fun_monitor_corrected::rpc_client::{SolanaRpcMonitor, StreamItem};
+//!
+//! # async fn print_events(monitor: &SolanaRpcMonitor) {
+//! let mut events = monitor.subscribe();
+//! while let Some(event) = events.next().await {
+//!     match event {
+//!         StreamItem::Token(event) => println!("{} ({})", event.token.name, event.token.symbol),
+//!         StreamItem::Gap { skipped } => eprintln!("missed {} events", skipped),
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::data_models::TokenCreatedEvent;
+use futures_util::Stream;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::broadcast;
+
+/// an item of a [`TokenEventStream`].
+#[derive(Debug, Clone)]
+pub enum StreamItem {
+    Token(Box<TokenCreatedEvent>),
+    /// the consumer fell behind and `skipped` events were dropped before the next `Token`
+    Gap { skipped: u64 },
+}
+
+impl StreamItem {
+    /// the event, or `None` for a gap.
+    pub fn token(self) -> Option<TokenCreatedEvent> {
+        match self {
+            StreamItem::Token(event) => Some(*event),
+            StreamItem::Gap { .. } => None,
+        }
+    }
+}
+
+/// what a [`TokenEventStream`] does when its consumer falls behind the broadcast.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LagPolicy {
+    /// yield a `StreamItem::Gap` before the next event
+    #[default]
+    Gap,
+    /// carry on with the next event; the loss only shows in [`TokenEventStream::skipped`]
+    Skip,
+}
+
+/// Stream of broadcast events that ends when the broadcast closes.
+///
+/// ```no_run
+/// use futures_util::StreamExt;
+/// use pump_fun_monitor_corrected::rpc_client::{LagPolicy, TokenEventStream};
+///
+/// let (_sender, receiver) = tokio::sync::broadcast::channel(16);
+/// let mut events = TokenEventStream::new(receiver, LagPolicy::Skip)
+///     .filter_map(|event| async move { event.token() });
+/// ```
+pub struct TokenEventStream {
+    inner: Pin<Box<dyn Stream<Item = StreamItem> + Send>>,
+    skipped: Arc<AtomicU64>,
+}
+
+impl TokenEventStream {
+    pub fn new(receiver: broadcast::Receiver<TokenCreatedEvent>, policy: LagPolicy) -> Self {
+        let skipped = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&skipped);
+        let inner = futures_util::stream::unfold(receiver, move |mut receiver| {
+            let counter = Arc::clone(&counter);
+            async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(event) => return Some((StreamItem::Token(Box::new(event)), receiver)),
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            counter.fetch_add(skipped, Ordering::Relaxed);
+                            if policy == LagPolicy::Gap {
+                                return Some((StreamItem::Gap { skipped }, receiver));
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            }
+        });
+        Self {
+            inner: Box::pin(inner),
+            skipped,
+        }
+    }
+
+    /// events dropped so far because the consumer fell behind, whichever the policy.
+    pub fn skipped(&self) -> u64 {
+        self.skipped.load(Ordering::Relaxed)
+    }
+}
+
+impl Stream for TokenEventStream {
+    type Item = StreamItem;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<StreamItem>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
@@ -0,0 +1,86 @@
+//! # Log Subscription Protocol
+//!
+//! JSON-RPC framing of the `logsSubscribe` WebSocket subscriptions: the subscribe and unsubscribe requests, and
+//! classification of the frames the server sends back. `mentions` accepts a single address, so every monitored program
+//! gets its own subscription. The server answers each subscribe request with a subscription id (or an error such as a
+//! subscription limit), then pushes `logsNotification`s tagged with that id.
+
+use super::Commitment;
+use serde_json::Value;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::str::FromStr;
+
+/// a text frame from the RPC WebSocket, as far as the subscription is concerned.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionFrame {
+    /// the server accepted the `logsSubscribe` sent with `request_id` and assigned it `subscription`
+    Subscribed { request_id: u64, subscription: u64 },
+    /// the server answered a request with a JSON-RPC error
+    Rejected { code: i64, message: String },
+    /// a `logsNotification`; `signature` is `None` and `logs` empty for failed transactions
+    Notification { subscription: u64, signature: Option<Signature>, logs: Vec<String> },
+    /// anything else, including responses to other requests and frames that aren't JSON
+    Other,
+}
+
+pub fn subscribe_request(request_id: u64, program_id: &Pubkey, commitment: Commitment) -> String {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request_id,
+        "method": "logsSubscribe",
+        "params": [
+            { "mentions": [program_id.to_string()] },
+            { "encoding": "jsonParsed", "commitment": commitment.as_str() }
+        ]
+    })
+    .to_string()
+}
+
+pub fn unsubscribe_request(request_id: u64, subscription: u64) -> String {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": request_id,
+        "method": "logsUnsubscribe",
+        "params": [subscription]
+    })
+    .to_string()
+}
+
+/// Classifies a text frame received on the subscriptions.
+///
+/// Only subscribe requests are in flight while frames are read, so every error response is taken as a rejection of one
+/// of them, including errors without an id such as a parse error for the request.
+pub fn parse_frame(text: &str) -> SubscriptionFrame {
+    let Ok(frame) = serde_json::from_str::<Value>(text) else {
+        return SubscriptionFrame::Other;
+    };
+
+    if frame["method"] == "logsNotification" {
+        let Some(subscription) = frame["params"]["subscription"].as_u64() else {
+            return SubscriptionFrame::Other;
+        };
+        let value = &frame["params"]["result"]["value"];
+        let signature = value["err"]
+            .is_null()
+            .then(|| value["signature"].as_str().and_then(|s| Signature::from_str(s).ok()))
+            .flatten();
+        let logs = match (&signature, value["logs"].as_array()) {
+            (Some(_), Some(logs)) => logs.iter().filter_map(|line| line.as_str().map(str::to_string)).collect(),
+            _ => Vec::new(),
+        };
+        return SubscriptionFrame::Notification { subscription, signature, logs };
+    }
+
+    if let Some(error) = frame.get("error") {
+        return SubscriptionFrame::Rejected {
+            code: error["code"].as_i64().unwrap_or_default(),
+            message: error["message"].as_str().unwrap_or("unknown error").to_string(),
+        };
+    }
+    // logsUnsubscribe answers with a boolean, so only a numeric result is a subscription id
+    match (frame["id"].as_u64(), frame["result"].as_u64()) {
+        (Some(request_id), Some(subscription)) => SubscriptionFrame::Subscribed { request_id, subscription },
+        _ => SubscriptionFrame::Other,
+    }
+}
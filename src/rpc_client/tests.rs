@@ -0,0 +1,2259 @@
+//! Unit tests for transaction processing, program log parsing, subscription frame parsing, fixture record and replay, dead letters, curve snapshots, the subscription watchdog, bonding curve account parsing, token metadata sanitization, creator history, initial buy detection, compute budgets, migrations, diagnostics and event streams.
+
+
+use super::*;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use super::mock::MockRpc;
+use super::compute_budget::{transaction_cost, TransactionCost};
+use super::recording::RecordedNotification;
+use super::sanitize::{MAX_NAME_CHARS, MAX_SYMBOL_CHARS, MAX_URI_CHARS};
+use super::creators::CreatorHistory;
+use super::dead_letters::{CapturingRpc, DeadLetter, DeadLetters};
+use super::diagnostics::Diagnostics;
+use super::initial_buy::CreationBuys;
+use super::dedupe::{RecentSignatures, SeenMints};
+use super::overflow::ProcessorQueue;
+use super::preview::preview_from_logs;
+use super::provider_limits::{is_limit_rejection, limit_in_close, limit_in_message, LIMIT_REASONS};
+use super::retry::{retry_delay, RetryQueue, RETRY_DEADLINE};
+use super::self_test::{sample_window, SelfTestWindow};
+use super::similarity::{fold, within_distance};
+use super::snapshot::{diff_snapshots, read_snapshot, take_snapshot};
+use super::transport::RateLimitPause;
+use super::watchdog::{Watchdog, WatchdogAction};
+use crate::config::{CurveSnapshotConfig, DeadLetterConfig, ParserSelfTestConfig};
+use crate::data_models::InitialBuy;
+use crate::error::{retry_after_hint, retry_after_in_chain, MAX_RETRY_AFTER};
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program_option::COption;
+use solana_sdk::{compute_budget::ComputeBudgetInstruction, message::Message, system_instruction, transaction::Transaction};
+use solana_transaction_status::UiTransactionStatusMeta;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::atomic::Ordering;
+
+/// bonding curve account data in the on-chain layout, for a curve after roughly 5 SOL of buys.
+const BONDING_CURVE_ACCOUNT_BASE64: &str = "F7f4N2DYrGBte0ureUQDAACeKSYIAAAAbeM4X+hFAgAA8gUqAQAAAACAxqR+jQMAAA==";
+
+/// function to decode the bonding curve fixture.
+fn bonding_curve_account() -> Vec<u8> {
+    STANDARD.decode(BONDING_CURVE_ACCOUNT_BASE64).unwrap()
+}
+
+#[test]
+fn test_parse_bonding_curve_account() {
+    let curve = parse_bonding_curve(&bonding_curve_account(), &PUMP_FUN_BONDING_CURVE_DISCRIMINATOR).unwrap();
+
+    assert_eq!(
+        curve,
+        BondingCurveAccountData {
+            virtual_token_reserves: 919_714_285_714_285,
+            virtual_sol_reserves: 35_000_000_000,
+            real_token_reserves: 639_814_285_714_285,
+            real_sol_reserves: 5_000_000_000,
+            token_total_supply: 1_000_000_000_000_000,
+            complete: false,
+        }
+    );
+
+    // 35 SOL over ~919.7M tokens with 6 decimals
+    let price = token_price_in_sol(&curve, 6);
+    assert!((price - 3.8055e-8).abs() < 1e-11, "{}", price);
+}
+
+#[test]
+fn test_parse_bonding_curve_ignores_trailing_fields() {
+    // newer program versions append fields such as the creator key
+    let mut data = bonding_curve_account();
+    data.extend_from_slice(&[7u8; 32]);
+
+    let curve = parse_bonding_curve(&data, &PUMP_FUN_BONDING_CURVE_DISCRIMINATOR).unwrap();
+    assert_eq!(curve.real_sol_reserves, 5_000_000_000);
+}
+
+#[test]
+fn test_parse_bonding_curve_reads_complete_flag() {
+    let mut data = bonding_curve_account();
+    data[BONDING_CURVE_MIN_LEN - 1] = 1;
+
+    assert!(parse_bonding_curve(&data, &PUMP_FUN_BONDING_CURVE_DISCRIMINATOR).unwrap().complete);
+}
+
+#[test]
+fn test_parse_bonding_curve_rejects_short_data() {
+    let data = bonding_curve_account();
+
+    let error = parse_bonding_curve(&data[..BONDING_CURVE_MIN_LEN - 1], &PUMP_FUN_BONDING_CURVE_DISCRIMINATOR).unwrap_err();
+    assert!(error.to_string().contains("expected at least 49"), "{}", error);
+}
+
+#[test]
+fn test_parse_bonding_curve_rejects_other_accounts() {
+    let mut data = bonding_curve_account();
+    data[0] ^= 0xff;
+
+    assert!(matches!(
+        parse_bonding_curve(&data, &PUMP_FUN_BONDING_CURVE_DISCRIMINATOR),
+        Err(MonitorError::InvalidDiscriminator)
+    ));
+}
+
+#[test]
+fn test_parse_mint_authorities() {
+    let program = pump_fun_program();
+    // the PDA pump.fun mints through, as seen on mainnet mints before the authority is revoked
+    assert_eq!(program.mint_authority.to_string(), "TSLvdd1pWpHVjahSpsvCXUbgwsL3JAcvokwaKt1eokM");
+    let stranger = Pubkey::new_unique();
+    for (mint_authority, freeze_authority, standard) in [
+        (COption::None, COption::None, true),
+        (COption::Some(program.mint_authority), COption::None, true),
+        (COption::Some(stranger), COption::None, false),
+        (COption::None, COption::Some(program.mint_authority), false),
+        (COption::Some(program.mint_authority), COption::Some(stranger), false),
+    ] {
+        let mint = parse_mint(&mint_account_with(mint_authority, freeze_authority)).unwrap();
+        assert_eq!((mint.supply, mint.decimals), (1_000_000_000_000_000, 6));
+        assert_eq!(mint.mint_authority, Option::from(mint_authority));
+        assert_eq!(mint.freeze_authority, Option::from(freeze_authority));
+        assert_eq!(mint.authorities_standard(&program), standard, "{:?} / {:?}", mint_authority, freeze_authority);
+    }
+
+    // a fork's mints answer to the fork's own PDA
+    let fork = ProgramConfig::new(Pubkey::new_unique());
+    let mint = parse_mint(&mint_account_with(COption::Some(program.mint_authority), COption::None)).unwrap();
+    assert!(!mint.authorities_standard(&fork));
+}
+
+#[tokio::test]
+async fn test_process_create_flags_unexpected_authorities() {
+    let freezer = Pubkey::new_unique();
+    let rpc = MockRpc::default()
+        .with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create.json"))
+        .with_account(FIXTURE_MINT, mint_account_with(COption::None, COption::Some(freezer)))
+        .with_account(FIXTURE_BONDING_CURVE, curve_account());
+
+    let (result, _) = process(rpc, CREATE_SIGNATURE).await;
+    let event = result.unwrap().expect("create transaction should produce an event");
+    assert_eq!(event.token.mint_authority, None);
+    assert_eq!(event.token.freeze_authority, Some(freezer.to_string()));
+    assert_eq!(event.token.authorities_standard, Some(false));
+}
+
+/// function to build create instruction metadata.
+fn create_instruction(name: &str, symbol: &str, uri: &str) -> CreateInstructionData {
+    CreateInstructionData {
+        name: name.to_string(),
+        symbol: symbol.to_string(),
+        uri: uri.to_string(),
+    }
+}
+
+#[test]
+fn test_clean_metadata_passes_through() {
+    let metadata = sanitize_metadata(
+        create_instruction("Moon Cat 🚀", "MCAT", "https://ipfs.io/ipfs/QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"),
+        true,
+    );
+
+    assert_eq!(metadata.name, "Moon Cat 🚀");
+    assert_eq!(metadata.symbol, "MCAT");
+    assert!(!metadata.sanitized);
+    assert!(metadata.uri_valid);
+    // nothing changed, so there is nothing raw worth keeping
+    assert!(metadata.raw.is_none());
+}
+
+#[test]
+fn test_huge_strings_are_truncated() {
+    let metadata = sanitize_metadata(
+        create_instruction(&"N".repeat(10_000), &"S".repeat(1_000), &format!("https://example.com/{}", "a".repeat(5_000))),
+        false,
+    );
+
+    assert_eq!(metadata.name.chars().count(), MAX_NAME_CHARS);
+    assert_eq!(metadata.symbol.chars().count(), MAX_SYMBOL_CHARS);
+    assert_eq!(metadata.uri.chars().count(), MAX_URI_CHARS);
+    assert!(metadata.sanitized);
+    assert!(metadata.raw.is_none());
+}
+
+#[test]
+fn test_truncation_respects_char_boundaries() {
+    let metadata = sanitize_metadata(create_instruction(&"🚀".repeat(100), "ROCKET", "ipfs://QmHash"), false);
+
+    assert_eq!(metadata.name, "🚀".repeat(MAX_NAME_CHARS));
+}
+
+#[test]
+fn test_control_and_bidi_characters_are_stripped() {
+    let metadata = sanitize_metadata(
+        create_instruction("Safe\u{0}Token\u{202E}gpj.exe", "\u{2066}SCAM\u{2069}\n", " https://example.com/meta.json\r\n"),
+        true,
+    );
+
+    assert_eq!(metadata.name, "SafeTokengpj.exe");
+    assert_eq!(metadata.symbol, "SCAM");
+    assert_eq!(metadata.uri, "https://example.com/meta.json");
+    assert!(metadata.sanitized);
+    assert!(metadata.uri_valid);
+
+    let raw = metadata.raw.expect("raw metadata should be kept");
+    assert_eq!(raw.name, "Safe\u{0}Token\u{202E}gpj.exe");
+    assert_eq!(raw.symbol, "\u{2066}SCAM\u{2069}\n");
+}
+
+#[test]
+fn test_malformed_uri_is_flagged_not_rejected() {
+    for uri in ["not a uri", "javascript:alert(1)", "file:///etc/passwd", ""] {
+        let metadata = sanitize_metadata(create_instruction("Token", "TKN", uri), false);
+        assert!(!metadata.uri_valid, "{}", uri);
+        assert_eq!(metadata.uri, uri);
+    }
+
+    for uri in ["ipfs://QmHash", "ar://txid", "http://example.com/meta.json"] {
+        assert!(super::sanitize::is_well_formed_uri(uri), "{}", uri);
+    }
+}
+
+/// signature of the create transaction in `fixtures/create.json` and `fixtures/create_without_meta.json`.
+const CREATE_SIGNATURE: &str = "2aAe93UGNo2FXEPri8zeTFBzryvwvUTCBZ1rjSMdqdtd3xRVMXB142pNziehSsqbezyJ2mEKGQKSs2LQDJNMEQWm";
+
+/// signature of the create transaction in `fixtures/create_via_router.json`, where another program CPIs into pump.fun.
+const ROUTER_CREATE_SIGNATURE: &str = "4mrQqobV5i2c1CZoYME5VV5ex6sp5xFEpXiDwVJrFeJrzPrro3WJuHHGSu52uYmL5YeF3wqeFPJ9MncMx1wkej3Y";
+
+/// signature of the create transaction in `fixtures/create_with_buy.json`, where the creator buys right after creating.
+const CREATE_WITH_BUY_SIGNATURE: &str =
+    "5nXsEHVyHtqUmCe8kxdKuHyS4v5rz3w7BK6rHJ7whDMHkF1nr4tcGXkYu8SGaeCbKfVr1vgHrAowdaParAkETDJ7";
+
+/// signature of the buy transaction in `fixtures/buy.json`.
+const BUY_SIGNATURE: &str = "3BBhcNCXgXH1eneWZTnx6sFyYEdjauTZZNRRvjvNjMcgLgYSSLttCvr8VbQymBavgKa32SrgPnFHyhyFJq3xCauC";
+
+const FIXTURE_CREATOR: &str = "GkzUqR1dxyB3mUyZCK9KkoLx7CrXaFYRoDNK9bFpnw1R";
+const FIXTURE_MINT: &str = "9xkU49fBixt9jfgvsHquwoA6HBLriYgVsaqa6RaPKuQU";
+const FIXTURE_BONDING_CURVE: &str = "3MUAdkyL686CfDSeqdgs3uWFh4eByFVV5pBD1caUnwgw";
+
+/// function to build an initialized SPL mint account with pump.fun's supply and decimals.
+fn mint_account() -> Account {
+    mint_account_with(COption::None, COption::None)
+}
+
+/// function to build a pump.fun mint account with the given authorities.
+fn mint_account_with(mint_authority: COption<Pubkey>, freeze_authority: COption<Pubkey>) -> Account {
+    let mut data = vec![0; Mint::LEN];
+    Mint {
+        mint_authority,
+        supply: 1_000_000_000_000_000,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority,
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: 1_461_600,
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// function to wrap the bonding curve fixture in an account owned by the pump.fun program.
+fn curve_account() -> Account {
+    Account {
+        lamports: 6_231_920_000,
+        data: bonding_curve_account(),
+        owner: Pubkey::from_str(crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID).unwrap(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// signature of the migration transaction in `fixtures/migration.json`, which moves `FIXTURE_MINT`'s completed curve
+/// into a PumpSwap pool.
+const MIGRATION_SIGNATURE: &str =
+    "4MQxsPa4Eb6sUSryknV2AesDHnfHHQ17PeSU4uQrJSPDi4LDGgY9y5EaxaBZe1e3Eb7cW6q4xhCabwY7fMQ2adtU";
+
+const FIXTURE_POOL: &str = "3gLESRnfLgzAqu6PwGhBwsiBsnQ7BAtyWHhZ5zNcDPMF";
+const PUMP_AMM_PROGRAM_ID: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+
+/// function to keep the token creation of a processing result, failing the test on a migration.
+fn created(processed: Result<Option<ProcessedTransaction>>) -> Result<Option<TokenCreatedEvent>> {
+    processed.map(|processed| match processed {
+        Some(ProcessedTransaction::Created(event)) => Some(*event),
+        Some(ProcessedTransaction::Migrated(migrated)) => panic!("unexpected migration of {}", migrated.mint),
+        None => None,
+    })
+}
+
+/// function to run `process_transaction` against `rpc` with no price feed.
+async fn process(rpc: MockRpc, signature: &str) -> (Result<Option<TokenCreatedEvent>>, usize) {
+    let rpc = Arc::new(rpc);
+    let result = process_with(rpc.clone(), signature).await;
+    (result, rpc.calls.load(Ordering::Relaxed))
+}
+
+/// function to run `process_transaction` against any `SolanaRpc` with no price feed.
+async fn process_with(rpc: Arc<dyn SolanaRpc>, signature: &str) -> Result<Option<TokenCreatedEvent>> {
+    process_at(rpc, signature, Commitment::Confirmed).await
+}
+
+/// function to run `process_transaction` at a given commitment with no price feed.
+async fn process_at(rpc: Arc<dyn SolanaRpc>, signature: &str, commitment: Commitment) -> Result<Option<TokenCreatedEvent>> {
+    process_for(rpc, signature, &[pump_fun_program()], commitment).await
+}
+
+/// function to run `process_transaction` for a set of monitored programs with no price feed.
+async fn process_for(
+    rpc: Arc<dyn SolanaRpc>,
+    signature: &str,
+    programs: &[ProgramConfig],
+    commitment: Commitment,
+) -> Result<Option<TokenCreatedEvent>> {
+    let processed = process_transaction(
+        rpc,
+        &PendingSignature::new(Signature::from_str(signature).unwrap()),
+        programs,
+        commitment,
+        None,
+        ProcessingOptions::default(),
+    )
+    .await;
+    created(processed)
+}
+
+#[tokio::test]
+async fn test_process_create_transaction() {
+    let rpc = MockRpc::default()
+        .with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create.json"))
+        .with_account(FIXTURE_MINT, mint_account())
+        .with_account(FIXTURE_BONDING_CURVE, curve_account());
+
+    let (result, calls) = process(rpc, CREATE_SIGNATURE).await;
+    let event = result.unwrap().expect("create transaction should produce an event");
+
+    assert_eq!(event.transaction_signature, CREATE_SIGNATURE);
+    assert_eq!(event.slot, 280_000_000);
+    assert_eq!(event.token.mint_address, FIXTURE_MINT);
+    assert_eq!(event.token.creator, FIXTURE_CREATOR);
+    assert_eq!(event.token.name, "Moon Cat");
+    assert_eq!(event.token.symbol, "MCAT");
+    assert_eq!(event.token.uri, "https://ipfs.io/ipfs/QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG");
+    assert!(!event.token.sanitized);
+    assert_eq!(event.token.supply, Some(1_000_000_000_000_000));
+    assert_eq!(event.token.decimals, Some(6));
+    assert_eq!((event.token.mint_authority, event.token.freeze_authority), (None, None));
+    assert_eq!(event.token.authorities_standard, Some(true));
+    assert_eq!(event.pump_data.bonding_curve, FIXTURE_BONDING_CURVE);
+    assert_eq!(event.pump_data.virtual_sol_reserves, Some(35_000_000_000));
+    assert_eq!(event.pump_data.real_sol_reserves, Some(5_000_000_000));
+    assert!(!event.pump_data.complete);
+    assert!(event.pump_data.price_usd.is_none());
+    assert_eq!(event.initial_buy, None);
+    assert_eq!((event.same_tx_buyers, event.same_tx_buy_sol), (0, 0));
+    // the fixture sets no compute unit price
+    assert_eq!((event.tx_fee_lamports, event.priority_fee_lamports), (5_000, 0));
+    assert_eq!(event.compute_units, Some(118_432));
+    assert!(event.raw_transaction.is_none());
+    // the fixture's block time is long past; the processor, not the parser, measures processing latency
+    assert!(event.chain_latency_ms.unwrap() > 0);
+    assert_eq!(event.processing_latency_ms, None);
+    // one getTransaction and one batched account lookup
+    assert_eq!(calls, 2);
+}
+
+/// function to run `process_transaction` on the create fixture with `sol_price` as the price feed.
+async fn process_priced(sol_price: &SolPriceCell) -> TokenCreatedEvent {
+    let rpc = MockRpc::default()
+        .with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create.json"))
+        .with_account(FIXTURE_MINT, mint_account())
+        .with_account(FIXTURE_BONDING_CURVE, curve_account());
+    let pending = PendingSignature::new(Signature::from_str(CREATE_SIGNATURE).unwrap());
+    let programs = [pump_fun_program()];
+    let options = ProcessingOptions::default();
+    let processed =
+        process_transaction(Arc::new(rpc), &pending, &programs, Commitment::Confirmed, Some(sol_price), options);
+    created(processed.await).unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn test_usd_fields_are_left_out_while_the_sol_price_is_stale() {
+    let sol_price = SolPriceCell::new(Duration::from_secs(60));
+    sol_price.store(150.0, Utc::now().timestamp() - 120);
+    let event = process_priced(&sol_price).await;
+    assert_eq!((event.pump_data.price_usd, event.pump_data.market_cap_usd), (None, None));
+    let json = serde_json::to_value(&event).unwrap();
+    assert!(json["pumpData"].get("priceUsd").is_none());
+    assert!(json["pumpData"].get("marketCapUsd").is_none());
+
+    sol_price.store(150.0, Utc::now().timestamp());
+    let event = process_priced(&sol_price).await;
+    assert_eq!(event.pump_data.price_usd, Some(event.pump_data.price_sol.unwrap() * 150.0));
+    assert_eq!(event.pump_data.market_cap_usd, Some(event.pump_data.market_cap_sol.unwrap() * 150.0));
+}
+
+#[tokio::test]
+async fn test_minimal_detail_skips_account_lookups() {
+    // no accounts at all: a lookup would fail the transaction
+    let rpc = Arc::new(MockRpc::default().with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create.json")));
+    let options = ProcessingOptions { detail_level: DetailLevel::Minimal, ..Default::default() };
+    let processed = process_transaction(
+        rpc.clone(),
+        &PendingSignature::new(Signature::from_str(CREATE_SIGNATURE).unwrap()),
+        &[pump_fun_program()],
+        Commitment::Confirmed,
+        None,
+        options,
+    )
+    .await;
+    let event = created(processed).unwrap().expect("create transaction should produce an event");
+
+    assert_eq!(event.token.mint_address, FIXTURE_MINT);
+    assert_eq!(event.token.name, "Moon Cat");
+    assert_eq!(event.pump_data.bonding_curve, FIXTURE_BONDING_CURVE);
+    assert_eq!((event.token.supply, event.token.decimals), (None, None));
+    assert_eq!(event.token.authorities_standard, None);
+    assert_eq!(event.pump_data.real_sol_reserves, None);
+    assert_eq!(event.pump_data.market_cap_sol, None);
+    let json = serde_json::to_value(&event).unwrap();
+    assert!(json["token"].get("supply").is_none() && json["pumpData"].get("priceSol").is_none(), "{}", json);
+    // only the getTransaction
+    assert_eq!(rpc.calls.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn test_process_keeps_raw_transaction() {
+    let fixture = include_str!("fixtures/create.json");
+    let rpc = MockRpc::default()
+        .with_transaction(CREATE_SIGNATURE, fixture)
+        .with_account(FIXTURE_MINT, mint_account())
+        .with_account(FIXTURE_BONDING_CURVE, curve_account());
+
+    let processed = process_transaction(
+        Arc::new(rpc),
+        &PendingSignature::new(Signature::from_str(CREATE_SIGNATURE).unwrap()),
+        &[pump_fun_program()],
+        Commitment::Confirmed,
+        None,
+        ProcessingOptions { keep_raw_transaction: true, ..Default::default() },
+    )
+    .await;
+    let event = created(processed).unwrap().expect("create transaction should produce an event");
+
+    let raw = event.raw_transaction.expect("raw transaction should be kept");
+    let recorded: serde_json::Value = serde_json::from_str(fixture).unwrap();
+    assert_eq!(raw.signature, CREATE_SIGNATURE);
+    assert_eq!(raw.slot, 280_000_000);
+    assert_eq!(raw.transaction, recorded["transaction"][0]);
+    let json = serde_json::to_value(raw.as_ref()).unwrap();
+    assert_eq!(json["eventType"], "rawTransaction");
+    assert_eq!(json["meta"]["logMessages"], recorded["meta"]["logMessages"]);
+}
+
+#[tokio::test]
+async fn test_process_create_with_initial_buy() {
+    let rpc = MockRpc::default()
+        .with_transaction(CREATE_WITH_BUY_SIGNATURE, include_str!("fixtures/create_with_buy.json"))
+        .with_account(FIXTURE_MINT, mint_account())
+        .with_account(FIXTURE_BONDING_CURVE, curve_account());
+
+    let (result, calls) = process(rpc, CREATE_WITH_BUY_SIGNATURE).await;
+    let event = result.unwrap().expect("create transaction should produce an event");
+
+    assert_eq!(event.token.mint_address, FIXTURE_MINT);
+    assert_eq!(event.slot, 280_000_002);
+    // the 0.05 SOL fee transfer inside the Buy goes to the fee recipient and isn't counted
+    assert_eq!(
+        event.initial_buy,
+        Some(InitialBuy {
+            sol_amount: 5_000_000_000,
+            token_amount: 153_285_714_285_714,
+        })
+    );
+    assert_eq!((event.same_tx_buyers, event.same_tx_buy_sol), (1, 5_000_000_000));
+    assert_eq!(calls, 2);
+}
+
+/// function to build a pump.fun Buy of `mint` by `buyer`; the accounts the detection doesn't read are placeholders.
+fn buy_instruction(program: &ProgramConfig, mint: Pubkey, buyer: Pubkey, tokens: u64) -> Instruction {
+    let mut accounts = vec![AccountMeta::new_readonly(Pubkey::new_unique(), false); 12];
+    accounts[2] = AccountMeta::new_readonly(mint, false);
+    accounts[6] = AccountMeta::new(buyer, true);
+    let mut data = program.buy_instruction.to_vec();
+    data.extend_from_slice(&tokens.to_le_bytes());
+    data.extend_from_slice(&u64::MAX.to_le_bytes());
+    Instruction::new_with_bytes(program.id, &data, accounts)
+}
+
+#[test]
+fn test_creation_buys_count_bundled_wallets() {
+    let program = pump_fun_program();
+    let (mint, curve) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (creator, sniper) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let instructions = [
+        buy_instruction(&program, mint, creator, 70_000_000_000_000),
+        system_instruction::transfer(&creator, &curve, 2_000_000_000),
+        buy_instruction(&program, mint, sniper, 90_000_000_000_000),
+        system_instruction::transfer(&sniper, &curve, 3_000_000_000),
+        // the fee transfer and a buy of another mint don't count
+        system_instruction::transfer(&sniper, &Pubkey::new_unique(), 30_000_000),
+        buy_instruction(&program, Pubkey::new_unique(), Pubkey::new_unique(), 1_000),
+    ];
+    let transaction = VersionedTransaction::from(Transaction::new_unsigned(Message::new(&instructions, Some(&creator))));
+    let meta = serde_json::from_str(r#"{"err":null,"status":{"Ok":null},"fee":5000,"preBalances":[],"postBalances":[]}"#).unwrap();
+
+    assert_eq!(
+        find_creation_buys(&transaction, &meta, &program, &mint, &curve, &creator),
+        CreationBuys {
+            initial_buy: Some(InitialBuy {
+                sol_amount: 2_000_000_000,
+                token_amount: 70_000_000_000_000,
+            }),
+            buyers: 2,
+            sol: 5_000_000_000,
+        }
+    );
+    // a launch the creator didn't buy into can still be sniped
+    let sniper_only = find_creation_buys(&transaction, &meta, &program, &mint, &curve, &Pubkey::new_unique());
+    assert_eq!(sniper_only.initial_buy, None);
+    assert_eq!(sniper_only.buyers, 2);
+}
+
+#[test]
+fn test_priority_fee_is_read_from_compute_budget_instructions() {
+    let program = pump_fun_program();
+    let creator = Pubkey::new_unique();
+    let buy = buy_instruction(&program, Pubkey::new_unique(), creator, 70_000_000_000_000);
+    let meta: UiTransactionStatusMeta = serde_json::from_str(
+        r#"{"err":null,"status":{"Ok":null},"fee":65000,"preBalances":[],"postBalances":[],"computeUnitsConsumed":71023}"#,
+    )
+    .unwrap();
+    let cost = |instructions: &[Instruction]| {
+        let message = Message::new(instructions, Some(&creator));
+        transaction_cost(&VersionedTransaction::from(Transaction::new_unsigned(message)), &meta)
+    };
+
+    // 0.75 lamports per unit for 80,000 units
+    let limited = [
+        ComputeBudgetInstruction::set_compute_unit_limit(80_000),
+        ComputeBudgetInstruction::set_compute_unit_price(750_000),
+        buy.clone(),
+    ];
+    assert_eq!(
+        cost(&limited),
+        TransactionCost {
+            fee_lamports: 65_000,
+            priority_fee_lamports: 60_000,
+            compute_units: Some(71_023),
+        }
+    );
+    // without a limit every other instruction is given 200,000 units, and a fraction of a lamport is rounded up
+    let unlimited = [ComputeBudgetInstruction::set_compute_unit_price(1), buy.clone(), buy.clone()];
+    assert_eq!(cost(&unlimited).priority_fee_lamports, 1);
+    let unlimited = [ComputeBudgetInstruction::set_compute_unit_price(100_000), buy.clone(), buy.clone()];
+    assert_eq!(cost(&unlimited).priority_fee_lamports, 40_000);
+    // a limit without a price costs nothing extra
+    let unpriced = [ComputeBudgetInstruction::set_compute_unit_limit(80_000), buy];
+    assert_eq!(cost(&unpriced).priority_fee_lamports, 0);
+}
+
+#[tokio::test]
+async fn test_process_create_from_program_logs() {
+    // the top-level instruction belongs to another program, so only the logged CreateEvent identifies the token
+    let rpc = MockRpc::default()
+        .with_transaction(ROUTER_CREATE_SIGNATURE, include_str!("fixtures/create_via_router.json"))
+        .with_account(FIXTURE_MINT, mint_account())
+        .with_account(FIXTURE_BONDING_CURVE, curve_account());
+
+    let (result, calls) = process(rpc, ROUTER_CREATE_SIGNATURE).await;
+    let event = result.unwrap().expect("create event in the logs should produce an event");
+
+    assert_eq!(event.token.mint_address, FIXTURE_MINT);
+    assert_eq!(event.token.creator, FIXTURE_CREATOR);
+    assert_eq!(event.pump_data.bonding_curve, FIXTURE_BONDING_CURVE);
+    assert_eq!(event.token.name, "Moon Cat");
+    assert_eq!(event.token.symbol, "MCAT");
+    assert_eq!(calls, 2);
+}
+
+#[tokio::test]
+async fn test_process_non_create_transaction() {
+    let rpc = MockRpc::default().with_transaction(BUY_SIGNATURE, include_str!("fixtures/buy.json"));
+
+    let (result, calls) = process(rpc, BUY_SIGNATURE).await;
+    assert!(result.unwrap().is_none());
+    assert_eq!(calls, 1);
+}
+
+/// function to return the pump.fun parser settings with migrations to PumpSwap tracked.
+fn migrating_pump_fun_program() -> ProgramConfig {
+    ProgramConfig {
+        migration_program: Some(Pubkey::from_str(PUMP_AMM_PROGRAM_ID).unwrap()),
+        ..pump_fun_program()
+    }
+}
+
+/// function to run `process_transaction` on a migration fixture, returning the migration it found.
+async fn process_migration(fixture: &str, programs: &[ProgramConfig]) -> Option<TokenMigratedEvent> {
+    let rpc = MockRpc::default().with_transaction(MIGRATION_SIGNATURE, fixture);
+    let pending = PendingSignature::new(Signature::from_str(MIGRATION_SIGNATURE).unwrap());
+    let options = ProcessingOptions::default();
+    let processed = process_transaction(Arc::new(rpc), &pending, programs, Commitment::Confirmed, None, options).await;
+    match processed.unwrap()? {
+        ProcessedTransaction::Migrated(migrated) => Some(*migrated),
+        ProcessedTransaction::Created(event) => panic!("unexpected creation of {}", event.token.mint_address),
+    }
+}
+
+#[tokio::test]
+async fn test_process_migration_transaction() {
+    let migrated = process_migration(include_str!("fixtures/migration.json"), &[migrating_pump_fun_program()])
+        .await
+        .expect("migration transaction should produce a migration");
+
+    assert_eq!(migrated.transaction_signature, MIGRATION_SIGNATURE);
+    assert_eq!(migrated.slot, 280_000_300);
+    assert_eq!(migrated.program_id, crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID);
+    assert_eq!(migrated.mint, FIXTURE_MINT);
+    assert_eq!(migrated.bonding_curve, FIXTURE_BONDING_CURVE);
+    assert_eq!(migrated.pool, FIXTURE_POOL);
+    assert_eq!(migrated.pool_program, PUMP_AMM_PROGRAM_ID);
+    // the pool's vaults after the transaction, not the curve's emptied token account or the migrator's LP tokens
+    assert_eq!(migrated.pool_token_reserves, Some(206_900_000_000_000));
+    assert_eq!(migrated.pool_sol_reserves, Some(84_990_359_679));
+
+    let json = serde_json::to_value(&migrated).unwrap();
+    assert_eq!(json["eventType"], "tokenMigrated");
+    assert_eq!(json["pool"], FIXTURE_POOL);
+}
+
+#[tokio::test]
+async fn test_migrations_are_ignored_unless_tracked() {
+    let fixture = include_str!("fixtures/migration.json");
+    assert_eq!(process_migration(fixture, &[pump_fun_program()]).await, None);
+
+    let rpc = MockRpc::default().with_transaction(MIGRATION_SIGNATURE, fixture);
+    let (result, calls) = process(rpc, MIGRATION_SIGNATURE).await;
+    assert!(result.unwrap().is_none());
+    assert_eq!(calls, 1);
+}
+
+#[tokio::test]
+async fn test_migration_from_instruction_without_event() {
+    let mut fixture: serde_json::Value = serde_json::from_str(include_str!("fixtures/migration.json")).unwrap();
+    fixture["meta"]["logMessages"]
+        .as_array_mut()
+        .unwrap()
+        .retain(|line| !line.as_str().unwrap().starts_with("Program data:"));
+    let fixture = fixture.to_string();
+
+    let migrated = process_migration(&fixture, &[migrating_pump_fun_program()])
+        .await
+        .expect("the Migrate instruction should be read without the event");
+    assert_eq!(migrated.mint, FIXTURE_MINT);
+    assert_eq!(migrated.bonding_curve, FIXTURE_BONDING_CURVE);
+    assert_eq!(migrated.pool, FIXTURE_POOL);
+    assert_eq!(migrated.pool_sol_reserves, Some(84_990_359_679));
+
+    // a Migrate into some other AMM isn't taken for one
+    let elsewhere = ProgramConfig {
+        migration_program: Some(Pubkey::new_unique()),
+        ..pump_fun_program()
+    };
+    assert_eq!(process_migration(&fixture, &[elsewhere]).await, None);
+}
+
+#[tokio::test]
+async fn test_process_tags_event_with_creating_program() {
+    let other = ProgramConfig::new(Pubkey::new_unique());
+    let rpc = Arc::new(
+        MockRpc::default()
+            .with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create.json"))
+            .with_account(FIXTURE_MINT, mint_account())
+            .with_account(FIXTURE_BONDING_CURVE, curve_account()),
+    );
+
+    let event = process_for(rpc.clone(), CREATE_SIGNATURE, &[other, pump_fun_program()], Commitment::Confirmed)
+        .await
+        .unwrap()
+        .expect("pump.fun create should be found among several programs");
+    assert_eq!(event.program_id, crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID);
+
+    // the same transaction means nothing to a monitor that only watches another launchpad
+    let result = process_for(rpc, CREATE_SIGNATURE, &[other], Commitment::Confirmed).await;
+    assert!(result.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_process_transaction_without_meta() {
+    let rpc = MockRpc::default()
+        .with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create_without_meta.json"))
+        .with_account(FIXTURE_MINT, mint_account())
+        .with_account(FIXTURE_BONDING_CURVE, curve_account());
+
+    match process(rpc, CREATE_SIGNATURE).await.0 {
+        Err(MonitorError::TransactionParse(message)) => assert!(message.contains("metadata missing"), "{}", message),
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_process_create_with_missing_mint_account() {
+    let rpc = MockRpc::default()
+        .with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create.json"))
+        .with_account(FIXTURE_BONDING_CURVE, curve_account());
+
+    let (result, calls) = process(rpc, CREATE_SIGNATURE).await;
+    match result {
+        Err(MonitorError::AccountNotFound(mint)) => assert_eq!(mint.to_string(), FIXTURE_MINT),
+        other => panic!("expected the mint to be reported missing, got {:?}", other),
+    }
+    // the account lookup is retried three times before giving up
+    assert_eq!(calls, 1 + 4);
+}
+
+/// function to serve a one-instruction pump.fun transaction paid by `fee_payer`, with `accounts` in the Create instruction's order.
+fn mock_with_create_instruction(discriminator: [u8; 8], accounts: &[Pubkey], fee_payer: &Pubkey) -> (MockRpc, String) {
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::{message::Message, transaction::Transaction};
+    use solana_transaction_status::{Encodable, UiTransactionEncoding};
+
+    let mut data = discriminator.to_vec();
+    for field in ["Test", "TEST", "https://example.com/test.json"] {
+        data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+        data.extend_from_slice(field.as_bytes());
+    }
+    let program = Pubkey::from_str(crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID).unwrap();
+    let metas = accounts.iter().map(|&account| AccountMeta::new(account, false)).collect();
+    let instruction = Instruction::new_with_bytes(program, &data, metas);
+    let transaction = Transaction::new_unsigned(Message::new(&[instruction], Some(fee_payer)));
+
+    // the recorded create's response, carrying this transaction instead
+    let mut response: serde_json::Value = serde_json::from_str(include_str!("fixtures/create.json")).unwrap();
+    response["transaction"] = serde_json::to_value(transaction.encode(UiTransactionEncoding::Base64)).unwrap();
+    let rpc = MockRpc::default()
+        .with_transaction(CREATE_SIGNATURE, &response.to_string())
+        .with_account(&accounts[CREATE_ACCOUNT_MINT].to_string(), mint_account())
+        .with_account(&accounts[CREATE_ACCOUNT_BONDING_CURVE].to_string(), curve_account());
+    (rpc, CREATE_SIGNATURE.to_string())
+}
+
+#[tokio::test]
+async fn test_create_discriminator_is_anchors_global_create() {
+    let expected = solana_sdk::hash::hashv(&[b"global:create"]).to_bytes();
+    assert_eq!(PUMP_FUN_CREATE_DISCRIMINATOR, expected[..8]);
+
+    // the value used before the SolanaRpc refactor matched no pump.fun instruction
+    let accounts: Vec<Pubkey> = (0..14).map(|_| Pubkey::new_unique()).collect();
+    let stale = [0x61, 0x21, 0xdf, 0x27, 0x22, 0x30, 0x04, 0x2f];
+    let (rpc, signature) = mock_with_create_instruction(stale, &accounts, &accounts[CREATE_ACCOUNT_USER]);
+    assert!(process(rpc, &signature).await.0.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_create_accounts_are_read_from_their_positions() {
+    let accounts: Vec<Pubkey> = (0..14).map(|_| Pubkey::new_unique()).collect();
+    // a relayer pays the fee, so the fee payer isn't the creator
+    let fee_payer = Pubkey::new_unique();
+    let (rpc, signature) = mock_with_create_instruction(PUMP_FUN_CREATE_DISCRIMINATOR, &accounts, &fee_payer);
+
+    let event = process(rpc, &signature).await.0.unwrap().expect("create transaction should produce an event");
+    assert_eq!(event.token.name, "Test");
+    assert_eq!(event.token.mint_address, accounts[0].to_string());
+    assert_eq!(event.pump_data.bonding_curve, accounts[2].to_string());
+    assert_eq!(event.token.creator, accounts[7].to_string());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_processed_commitment_retries_longer() {
+    let rpc = Arc::new(
+        MockRpc::default()
+            .with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create.json"))
+            .with_account(FIXTURE_BONDING_CURVE, curve_account()),
+    );
+
+    let result = process_at(rpc.clone(), CREATE_SIGNATURE, Commitment::Processed).await;
+    assert!(matches!(result, Err(MonitorError::AccountNotFound(_))));
+    // accounts often don't exist yet at processed, so the lookup gets five retries
+    assert_eq!(rpc.calls.load(Ordering::Relaxed), 1 + 6);
+}
+
+/// function to run `process_transaction` the way the processor does, fetching again whatever the retry queue hands
+/// back, and to report the elapsed time of every fetch.
+async fn process_with_retries(rpc: Arc<dyn SolanaRpc>, signature: &str) -> (Vec<TokenCreatedEvent>, Vec<Duration>, usize) {
+    let (sender, mut receiver) = mpsc::channel(1);
+    sender.send(PendingSignature::new(Signature::from_str(signature).unwrap())).await.unwrap();
+    drop(sender);
+
+    let started = tokio::time::Instant::now();
+    let (mut events, mut fetched_at, mut missed) = (Vec::new(), Vec::new(), 0);
+    let mut retries = RetryQueue::default();
+    while let Some(pending) = retries.next(&mut receiver).await {
+        fetched_at.push(started.elapsed());
+        let processed = process_transaction(
+            rpc.clone(),
+            &pending,
+            &[pump_fun_program()],
+            Commitment::Confirmed,
+            None,
+            ProcessingOptions::default(),
+        )
+        .await;
+        match created(processed) {
+            Ok(event) => events.extend(event),
+            Err(e) => {
+                assert!(e.is_retryable(), "{}", e);
+                if retries.schedule(pending, e.retry_after()).is_err() {
+                    missed += 1;
+                }
+            }
+        }
+    }
+    (events, fetched_at, missed)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_unavailable_transaction_is_fetched_again_later() {
+    let rpc = MockRpc::default()
+        .with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create.json"))
+        .unavailable_for(CREATE_SIGNATURE, 3)
+        .with_account(FIXTURE_MINT, mint_account())
+        .with_account(FIXTURE_BONDING_CURVE, curve_account());
+
+    let (events, fetched_at, missed) = process_with_retries(Arc::new(rpc), CREATE_SIGNATURE).await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].token.mint_address, FIXTURE_MINT);
+    assert_eq!(missed, 0);
+    // the 4th fetch succeeds, after waiting 0.5s, 2s and 5s
+    let expected: Vec<Duration> = [0, 500, 2_500, 7_500].into_iter().map(Duration::from_millis).collect();
+    assert_eq!(fetched_at, expected);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_hanging_transaction_times_out_and_frees_the_processor() {
+    let rpc: Arc<dyn SolanaRpc> = Arc::new(
+        MockRpc::default()
+            .hanging_on(BUY_SIGNATURE)
+            .with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create.json"))
+            .with_account(FIXTURE_MINT, mint_account())
+            .with_account(FIXTURE_BONDING_CURVE, curve_account()),
+    );
+    let metrics = Metrics::default();
+    let limit = Duration::from_secs(30);
+    let mut retries = RetryQueue::default();
+
+    let started = tokio::time::Instant::now();
+    let hanging = PendingSignature::new(Signature::from_str(BUY_SIGNATURE).unwrap());
+    let options = ProcessingOptions::default();
+    let programs = [pump_fun_program()];
+    let processing = process_transaction(rpc.clone(), &hanging, &programs, Commitment::Confirmed, None, options);
+    let error = created(within_processing_timeout(started + limit, &metrics, processing).await).unwrap_err();
+    assert!(matches!(error, MonitorError::Timeout));
+    assert_eq!(started.elapsed(), limit);
+    assert_eq!(metrics.processing_timeouts.load(Ordering::Relaxed), 1);
+    // a timeout is retried like a transaction the RPC didn't have yet
+    assert!(error.is_retryable());
+    assert!(retries.schedule(hanging, error.retry_after()).is_ok());
+    assert_eq!(retries.len(), 1);
+
+    // the next signature is processed right away
+    let next = PendingSignature::new(Signature::from_str(CREATE_SIGNATURE).unwrap());
+    let processing = process_transaction(rpc, &next, &programs, Commitment::Confirmed, None, options);
+    let deadline = tokio::time::Instant::now() + limit;
+    let event = created(within_processing_timeout(deadline, &metrics, processing).await).unwrap().unwrap();
+    assert_eq!(event.token.mint_address, FIXTURE_MINT);
+    assert_eq!(started.elapsed(), limit);
+    assert_eq!(metrics.processing_timeouts.load(Ordering::Relaxed), 1);
+}
+
+/// function to swap the metadata URI of the create fixture for `uri`, which must be as long so nothing else moves.
+fn create_fixture_with_uri(uri: &str) -> String {
+    const FIXTURE_URI: &str = "https://ipfs.io/ipfs/QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+    assert_eq!(uri.len(), FIXTURE_URI.len());
+    let mut fixture: serde_json::Value = serde_json::from_str(include_str!("fixtures/create.json")).unwrap();
+    let mut transaction = STANDARD.decode(fixture["transaction"][0].as_str().unwrap()).unwrap();
+    let at = transaction.windows(FIXTURE_URI.len()).position(|window| window == FIXTURE_URI.as_bytes()).unwrap();
+    transaction[at..at + uri.len()].copy_from_slice(uri.as_bytes());
+    fixture["transaction"][0] = STANDARD.encode(transaction).into();
+    fixture.to_string()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_hanging_metadata_fetch_times_out_and_frees_the_processor() {
+    // a metadata host that accepts the connection and never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let host = format!("http://{}/", listener.local_addr().unwrap());
+    let _host = tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((connection, _)) = listener.accept().await {
+            connections.push(connection);
+        }
+    });
+    let hanging_uri = format!("{}{}", host, "m".repeat(67 - host.len()));
+    // the next creation of the same mint has a URI that isn't fetched
+    let rpc = MockRpc::default()
+        .with_transaction(CREATE_SIGNATURE, &create_fixture_with_uri(&hanging_uri))
+        .with_transaction(BUY_SIGNATURE, &create_fixture_with_uri(&"x".repeat(67)))
+        .with_account(FIXTURE_MINT, mint_account())
+        .with_account(FIXTURE_BONDING_CURVE, curve_account());
+
+    let overrides = HashMap::from([
+        ("SOLANA_RPC_HTTP_URL", "https://api.mainnet-beta.solana.com".to_string()),
+        ("SOLANA_RPC_WSS_URL", "wss://api.mainnet-beta.solana.com".to_string()),
+        ("WEBSOCKET_SERVER_PORT", "8080".to_string()),
+    ]);
+    let config = Config::load(None, &overrides).unwrap();
+    let mut monitor = SolanaRpcMonitor::new(&config, EventDispatcher::new(broadcast::channel(16).0)).unwrap();
+    let limit = Duration::from_secs(5);
+    monitor.rpc_client = Arc::new(rpc);
+    monitor.processing_timeout = limit;
+    monitor.metadata_fetcher = Some(MetadataFetcher::new(Duration::from_secs(60), "https://ipfs.io/ipfs/", 16));
+    let mut events = monitor.subscribe();
+    let (processor, _processor) = monitor.spawn_processor();
+
+    let started = tokio::time::Instant::now();
+    for signature in [CREATE_SIGNATURE, BUY_SIGNATURE] {
+        processor.send(PendingSignature::new(Signature::from_str(signature).unwrap())).await.unwrap();
+    }
+    let event = events.next().await.unwrap().token().unwrap();
+    assert_eq!(event.transaction_signature, BUY_SIGNATURE);
+    assert!(!event.token.uri_valid);
+    assert_eq!(started.elapsed(), limit);
+    assert_eq!(monitor.metrics.processing_timeouts.load(Ordering::Relaxed), 1);
+    assert_eq!(monitor.metrics.transactions_missed.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_full_durable_queue_delays_the_dispatch_without_timing_it_out() {
+    let fixture_rpc = || {
+        MockRpc::default()
+            .with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create.json"))
+            .with_account(FIXTURE_MINT, mint_account())
+            .with_account(FIXTURE_BONDING_CURVE, curve_account())
+    };
+    // an earlier event nobody has taken off the durable queue yet
+    let mut earlier = process(fixture_rpc(), CREATE_SIGNATURE).await.0.unwrap().unwrap();
+    earlier.transaction_signature = BUY_SIGNATURE.to_string();
+    let mut dispatcher = EventDispatcher::new(broadcast::channel(16).0);
+    let (mut durable, durable_stats) = dispatcher.add_durable("slow", 1, crate::dispatch::OverflowPolicy::Block);
+    dispatcher.dispatch(earlier).await;
+
+    let overrides = HashMap::from([
+        ("SOLANA_RPC_HTTP_URL", "https://api.mainnet-beta.solana.com".to_string()),
+        ("SOLANA_RPC_WSS_URL", "wss://api.mainnet-beta.solana.com".to_string()),
+        ("WEBSOCKET_SERVER_PORT", "8080".to_string()),
+    ]);
+    let config = Config::load(None, &overrides).unwrap();
+    let mut monitor = SolanaRpcMonitor::new(&config, dispatcher).unwrap();
+    let limit = Duration::from_secs(5);
+    monitor.rpc_client = Arc::new(fixture_rpc());
+    monitor.processing_timeout = limit;
+    let (processor, _processor) = monitor.spawn_processor();
+    processor.send(PendingSignature::new(Signature::from_str(CREATE_SIGNATURE).unwrap())).await.unwrap();
+
+    // the dispatch waits for room well past the deadline, and still goes out once there is some
+    tokio::time::sleep(limit * 3).await;
+    assert_eq!(durable_stats.waits(), 1);
+    for expected in [BUY_SIGNATURE, CREATE_SIGNATURE] {
+        let event = tokio::time::timeout(limit, durable.recv()).await.expect("the dispatch was given up on");
+        assert_eq!(event.unwrap().transaction_signature, expected);
+    }
+    assert_eq!(monitor.metrics.processing_timeouts.load(Ordering::Relaxed), 0);
+    assert_eq!(monitor.metrics.transactions_missed.load(Ordering::Relaxed), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_transaction_is_missed_once_retries_run_out() {
+    let rpc = MockRpc::default()
+        .with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create.json"))
+        .unavailable_for(CREATE_SIGNATURE, usize::MAX);
+
+    let (events, fetched_at, missed) = process_with_retries(Arc::new(rpc), CREATE_SIGNATURE).await;
+    assert!(events.is_empty());
+    assert_eq!(missed, 1);
+    // 0.5s, 2s, 5s, then every 15s while the delays stay within the deadline
+    assert_eq!(fetched_at.len(), 11);
+    assert!(*fetched_at.last().unwrap() <= RETRY_DEADLINE);
+    assert_eq!(retry_delay(1), Duration::from_millis(500));
+    assert_eq!(retry_delay(9), Duration::from_secs(15));
+}
+
+#[tokio::test]
+async fn test_only_unavailable_data_is_retried() {
+    // transaction parse failures won't go away by fetching again
+    let rpc = MockRpc::default().with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create_without_meta.json"));
+    let (result, _) = process(rpc, CREATE_SIGNATURE).await;
+    let error = result.unwrap_err();
+    assert!(!error.is_retryable(), "{}", error);
+
+    let (result, calls) = process(MockRpc::default(), CREATE_SIGNATURE).await;
+    assert!(matches!(result, Err(MonitorError::TransactionNotFound(_))));
+    // no retries inside the fetch itself; the processor's retry queue decides when to try again
+    assert_eq!(calls, 1);
+}
+
+#[test]
+fn test_client_errors_map_to_specific_variants() {
+    let timed_out = ClientError::from(ClientErrorKind::Io(std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out")));
+    let error = MonitorError::from(timed_out);
+    assert!(matches!(error, MonitorError::Timeout));
+    assert_eq!(error.code(), "timeout");
+
+    let throttled = ClientError::from(ClientErrorKind::RpcError(RpcError::RpcResponseError {
+        code: 429,
+        message: "Too many requests for a specific RPC call".to_string(),
+        data: solana_client::rpc_request::RpcResponseErrorData::Empty,
+    }));
+    let error = MonitorError::from(throttled);
+    assert!(matches!(error, MonitorError::RateLimited { retry_after: None }));
+    assert!(error.is_retryable());
+    assert_eq!(error.code(), "rate_limited");
+
+    let missing = ClientError::from(ClientErrorKind::RpcError(RpcError::ForUser(format!(
+        "AccountNotFound: pubkey={}",
+        FIXTURE_MINT
+    ))));
+    match MonitorError::from(missing) {
+        MonitorError::AccountNotFound(pubkey) => assert_eq!(pubkey.to_string(), FIXTURE_MINT),
+        other => panic!("expected a missing account, got {:?}", other),
+    }
+
+    // a `null` getTransaction result fails to deserialize; anything else stays a plain RPC error
+    let null_result = serde_json::from_str::<u64>("null").unwrap_err();
+    assert!(transaction_not_available(&ClientError::from(ClientErrorKind::SerdeJson(null_result))));
+    let other = MonitorError::from(ClientError::from(ClientErrorKind::Custom("bad request".to_string())));
+    assert!(matches!(other, MonitorError::RpcClient(_)));
+    assert!(!other.is_retryable());
+    assert_eq!(other.code(), "rpc");
+}
+
+#[test]
+fn test_rpc_errors_name_the_endpoint_and_request() {
+    let wrapped = MonitorError::Rpc {
+        endpoint: "helius".to_string(),
+        request_id: "helius-7".to_string(),
+        source: Box::new(MonitorError::RateLimited { retry_after: Some(Duration::from_secs(2)) }),
+    };
+    let message = wrapped.to_string();
+    assert!(message.starts_with("Rate limited by the RPC provider"), "{}", message);
+    assert!(message.ends_with("[endpoint helius, request helius-7]"), "{}", message);
+    // classified as the error it wraps
+    assert!(wrapped.is_retryable());
+    assert_eq!(wrapped.retry_after(), Some(Duration::from_secs(2)));
+    assert_eq!(wrapped.code(), "rate_limited");
+    assert_eq!(wrapped.endpoint(), Some("helius"));
+    assert_eq!(MonitorError::Timeout.endpoint(), None);
+
+    let wrapped = MonitorError::Rpc {
+        endpoint: "http".to_string(),
+        request_id: "http-1".to_string(),
+        source: Box::new(MonitorError::TransactionParse("Failed to decode transaction".to_string())),
+    };
+    assert!(!wrapped.is_retryable());
+    assert_eq!(
+        wrapped.to_string(),
+        "Transaction parsing failed: Failed to decode transaction [endpoint http, request http-1]"
+    );
+}
+
+#[tokio::test]
+async fn test_endpoint_rpc_numbers_its_requests() {
+    // nothing listens on port 1, so every call fails to connect
+    let client = RpcClient::new_with_timeout_and_commitment(
+        "http://127.0.0.1:1".to_string(),
+        Duration::from_secs(5),
+        CommitmentConfig::confirmed(),
+    );
+    let rpc = EndpointRpc::new(client, "local");
+    let mint = Pubkey::from_str(FIXTURE_MINT).unwrap();
+
+    let first = rpc.get_account(&mint).await.unwrap_err();
+    let second = rpc.get_multiple_accounts(&[mint]).await.unwrap_err();
+    assert!(first.to_string().ends_with("[endpoint local, request local-1]"), "{}", first);
+    assert!(second.to_string().ends_with("[endpoint local, request local-2]"), "{}", second);
+    assert!(first.is_retryable(), "{}", first);
+    assert_eq!(first.endpoint(), Some("local"));
+    assert_eq!(first.code(), "rpc");
+}
+
+#[test]
+fn test_rate_limited_websocket_upgrade_keeps_retry_after() {
+    let response = tokio_tungstenite::tungstenite::http::Response::builder()
+        .status(429)
+        .header("Retry-After", "30")
+        .body(None)
+        .unwrap();
+    let error = MonitorError::from(tokio_tungstenite::tungstenite::Error::Http(response));
+    assert_eq!(error.retry_after(), Some(Duration::from_secs(30)));
+    assert_eq!(error.to_string(), "Rate limited by the RPC provider, retry after 30s");
+
+    let closed = MonitorError::from(tokio_tungstenite::tungstenite::Error::ConnectionClosed);
+    assert_eq!(closed.code(), "websocket");
+    assert_eq!(closed.retry_after(), None);
+}
+
+/// function to build the JSON-RPC error a provider answers a rate limited call with.
+fn rate_limit_error(code: i64, message: &str) -> MonitorError {
+    MonitorError::from(ClientError::from(ClientErrorKind::RpcError(RpcError::RpcResponseError {
+        code,
+        message: message.to_string(),
+        data: solana_client::rpc_request::RpcResponseErrorData::Empty,
+    })))
+}
+
+#[test]
+fn test_rate_limit_errors_keep_the_wait_they_name() {
+    let error = rate_limit_error(429, "Too many requests, retry after 2s");
+    assert!(matches!(error, MonitorError::RateLimited { .. }));
+    assert_eq!(error.retry_after(), Some(Duration::from_secs(2)));
+
+    let error = rate_limit_error(-32005, "Rate limit exceeded, try again in 750ms");
+    assert_eq!(error.retry_after(), Some(Duration::from_millis(750)));
+
+    // a wait past the cap is most likely a misreading
+    let error = rate_limit_error(429, "Too many requests. Retry-After: 3600");
+    assert_eq!(error.retry_after(), Some(MAX_RETRY_AFTER));
+    assert_eq!(rate_limit_error(429, "Too many requests").retry_after(), None);
+}
+
+#[test]
+fn test_retry_after_hints() {
+    assert_eq!(retry_after_hint("retry after 3 seconds"), Some(Duration::from_secs(3)));
+    assert_eq!(retry_after_hint("{\"retry_after\": 1.5}"), Some(Duration::from_millis(1_500)));
+    assert_eq!(retry_after_hint("Retry-After=250 milliseconds"), Some(Duration::from_millis(250)));
+    assert_eq!(retry_after_hint("please retry after a while"), None);
+    assert_eq!(retry_after_hint("429 Too Many Requests"), None);
+
+    /// an error whose hint is in its source, like a 429 inside the client's reqwest error.
+    #[derive(Debug, thiserror::Error)]
+    #[error("error sending request")]
+    struct Refused(#[source] std::io::Error);
+    let refused = Refused(std::io::Error::other("rate limited, retry after 4s"));
+    assert_eq!(retry_after_in_chain(&refused), Some(Duration::from_secs(4)));
+    let refused = Refused(std::io::Error::other("connection reset"));
+    assert_eq!(retry_after_in_chain(&refused), None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limit_pause_holds_every_call_back() {
+    let pause = Arc::new(RateLimitPause::default());
+    assert_eq!(pause.wait().await, None);
+
+    assert!(pause.rate_limited(Duration::from_secs(2)));
+    // a shorter limit doesn't cut the running pause short
+    assert!(!pause.rate_limited(Duration::from_secs(1)));
+    let waiters: Vec<_> = (0..3)
+        .map(|_| {
+            let pause = Arc::clone(&pause);
+            tokio::spawn(async move { pause.wait().await })
+        })
+        .collect();
+    for waiter in waiters {
+        assert_eq!(waiter.await.unwrap(), Some(Duration::from_secs(2)));
+    }
+    assert_eq!(pause.wait().await, None);
+
+    // a pause extended while calls wait holds them until its new end
+    assert!(pause.rate_limited(Duration::from_secs(1)));
+    let waiter = {
+        let pause = Arc::clone(&pause);
+        tokio::spawn(async move { pause.wait().await })
+    };
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(pause.rate_limited(Duration::from_secs(2)));
+    assert_eq!(waiter.await.unwrap(), Some(Duration::from_millis(2_500)));
+}
+
+#[test]
+fn test_commitment_levels() {
+    assert_eq!("Processed".parse::<Commitment>().unwrap(), Commitment::Processed);
+    assert_eq!(" finalized ".parse::<Commitment>().unwrap(), Commitment::Finalized);
+    assert!("recent".parse::<Commitment>().is_err());
+
+    // getTransaction rejects processed, so transactions are read at confirmed instead
+    assert_eq!(Commitment::Processed.commitment_config(), CommitmentConfig::processed());
+    assert_eq!(Commitment::Processed.transaction_commitment(), CommitmentConfig::confirmed());
+    assert_eq!(Commitment::Finalized.transaction_commitment(), CommitmentConfig::finalized());
+}
+
+/// function to create an empty scratch directory for a recording.
+fn scratch_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pump_fun_monitor_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn test_record_then_replay_round_trip() {
+    let dir = scratch_dir("round_trip");
+    let recorder = Arc::new(FixtureRecorder::create(&dir).unwrap());
+    let live = MockRpc::default()
+        .with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create.json"))
+        .with_account(FIXTURE_MINT, mint_account())
+        .with_account(FIXTURE_BONDING_CURVE, curve_account());
+    let recording = Arc::new(RecordingRpc::new(Arc::new(live), Arc::clone(&recorder)));
+
+    let notification = format!(r#"{{"params":{{"result":{{"value":{{"signature":"{}","err":null}}}}}}}}"#, CREATE_SIGNATURE);
+    recorder.record_notification(&notification);
+    let recorded = process_with(recording, CREATE_SIGNATURE).await.unwrap().unwrap();
+
+    let notifications = read_notifications(&dir).unwrap();
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0].message, notification);
+
+    let replayed = process_with(Arc::new(ReplayRpc::new(&dir)), CREATE_SIGNATURE).await.unwrap().unwrap();
+    assert_eq!(replayed.token.mint_address, recorded.token.mint_address);
+    assert_eq!(replayed.token.name, recorded.token.name);
+    assert_eq!(replayed.token.supply, recorded.token.supply);
+    assert_eq!(replayed.pump_data.virtual_sol_reserves, recorded.pump_data.virtual_sol_reserves);
+    assert_eq!(replayed.pump_data.price_sol, recorded.pump_data.price_sol);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_replay_reports_unrecorded_data() {
+    let dir = scratch_dir("unrecorded");
+    FixtureRecorder::create(&dir).unwrap();
+    let replay = ReplayRpc::new(&dir);
+    let mint = Pubkey::from_str(FIXTURE_MINT).unwrap();
+
+    assert!(replay.get_account(&mint).await.is_err());
+    assert_eq!(replay.get_multiple_accounts(&[mint]).await.unwrap(), vec![None]);
+    assert!(process_with(Arc::new(replay), CREATE_SIGNATURE).await.is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Replays the synthetic `fixtures/sample` recording; `test_replay_mainnet_fixtures` replays real traffic.
+#[tokio::test]
+async fn test_replay_sample_fixtures() {
+    let dir = std::path::Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/sample"));
+    let replay: Arc<dyn SolanaRpc> = Arc::new(ReplayRpc::new(dir));
+
+    let notifications = read_notifications(dir).unwrap();
+    let signatures: Vec<String> = notifications
+        .iter()
+        .filter_map(|notification| match parse_frame(&notification.message) {
+            SubscriptionFrame::Notification { subscription: 24040, signature, .. } => signature,
+            _ => None,
+        })
+        .map(|signature| signature.to_string())
+        .collect();
+    // the subscription confirmation and the failed buy are skipped
+    assert_eq!(notifications.len(), 6);
+    assert_eq!(signatures.len(), 4);
+
+    let mut names = Vec::new();
+    for signature in &signatures {
+        if let Some(event) = process_with(Arc::clone(&replay), signature).await.unwrap() {
+            assert_eq!(event.token.decimals, Some(6));
+            assert!(event.token.uri_valid);
+            names.push(event.token.name);
+        }
+    }
+    assert_eq!(names, ["Moon Cat", "Frog Wizard", "Based Dog"]);
+}
+
+/// Replays a recording of real mainnet traffic in `fixtures/mainnet`, made with `--record-fixtures` (see the README).
+#[tokio::test]
+#[ignore]
+async fn test_replay_mainnet_fixtures() {
+    let dir = std::path::Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/mainnet"));
+    assert!(dir.is_dir(), "no mainnet recording at {}, record one with --record-fixtures", dir.display());
+    let replay: Arc<dyn SolanaRpc> = Arc::new(ReplayRpc::new(dir));
+
+    let mut events = Vec::new();
+    for notification in read_notifications(dir).unwrap() {
+        let SubscriptionFrame::Notification { signature: Some(signature), .. } = parse_frame(&notification.message) else {
+            continue;
+        };
+        // only transactions the monitor fetched were recorded
+        if !dir.join("transactions").join(format!("{}.json", signature)).exists() {
+            continue;
+        }
+        match process_with(Arc::clone(&replay), &signature.to_string()).await {
+            Ok(Some(event)) => events.push(event),
+            Ok(None) => {}
+            Err(e) => panic!("{} failed to process: {}", signature, e),
+        }
+    }
+    assert!(!events.is_empty(), "the recording holds no token creation");
+    for event in &events {
+        assert_eq!(event.token.decimals, Some(6), "{}", event.transaction_signature);
+        assert!(event.pump_data.virtual_sol_reserves.unwrap_or(0) > 0, "{}", event.transaction_signature);
+    }
+}
+
+/// function to open an empty dead-letter directory keeping at most `max_entries` letters for an hour.
+fn dead_letter_dir(name: &str, max_entries: usize) -> DeadLetters {
+    DeadLetters::open(&DeadLetterConfig {
+        dir: scratch_dir(name),
+        max_entries,
+        retention: Duration::from_secs(3_600),
+    })
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_parse_failure_is_written_as_dead_letter_and_kept_while_it_fails() {
+    let dead_letters = dead_letter_dir("dead_letter_parse", 10);
+    let live: Arc<dyn SolanaRpc> = Arc::new(
+        MockRpc::default().with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create_without_meta.json")),
+    );
+    let capture = Arc::new(CapturingRpc::new(Arc::clone(&live)));
+    let metrics = Metrics::default();
+
+    let error = process_with(capture.clone(), CREATE_SIGNATURE).await.unwrap_err();
+    file_dead_letter(&dead_letters, &capture, &metrics, Signature::from_str(CREATE_SIGNATURE).unwrap(), &error);
+
+    let letters = dead_letters.read_all();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].signature, CREATE_SIGNATURE);
+    assert_eq!(letters[0].code, "transaction_parse");
+    assert!(letters[0].error.contains("metadata missing"), "{}", letters[0].error);
+    assert!(letters[0].transaction.is_some());
+    assert!(metrics.render().contains("pump_fun_monitor_dead_letters_total{code=\"transaction_parse\"} 1\n"));
+
+    // the parser still rejects it, so the letter stays
+    let options = ProcessingOptions::default();
+    let reprocessed = reprocess_dead_letters(&dead_letters, live, &[pump_fun_program()], Commitment::Confirmed, options).await;
+    assert_eq!((reprocessed.events.len(), reprocessed.ignored, reprocessed.failed), (0, 0, 1));
+    assert_eq!(dead_letters.read_all().len(), 1);
+
+    std::fs::remove_dir_all(dead_letters.dir()).unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_dead_letter_is_reprocessed_from_its_stored_transaction() {
+    let dead_letters = dead_letter_dir("dead_letter_reprocess", 10);
+    // the accounts aren't visible yet when the transaction is first processed
+    let capture = Arc::new(CapturingRpc::new(Arc::new(
+        MockRpc::default().with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create.json")),
+    )));
+    let error = process_with(capture.clone(), CREATE_SIGNATURE).await.unwrap_err();
+    assert!(matches!(error, MonitorError::AccountNotFound(_)));
+    file_dead_letter(&dead_letters, &capture, &Metrics::default(), Signature::from_str(CREATE_SIGNATURE).unwrap(), &error);
+
+    // the node has since lost the transaction, so only the stored copy can be used
+    let rpc = Arc::new(
+        MockRpc::default()
+            .with_account(FIXTURE_MINT, mint_account())
+            .with_account(FIXTURE_BONDING_CURVE, curve_account()),
+    );
+    let options = ProcessingOptions::default();
+    let reprocessed =
+        reprocess_dead_letters(&dead_letters, rpc.clone(), &[pump_fun_program()], Commitment::Confirmed, options).await;
+    assert_eq!((reprocessed.ignored, reprocessed.failed), (0, 0));
+    assert_eq!(reprocessed.events.len(), 1);
+    assert_eq!(reprocessed.events[0].token.name, "Moon Cat");
+    assert_eq!(reprocessed.events[0].transaction_signature, CREATE_SIGNATURE);
+    // one batched account lookup and no getTransaction
+    assert_eq!(rpc.calls.load(Ordering::Relaxed), 1);
+    assert!(dead_letters.read_all().is_empty());
+
+    std::fs::remove_dir_all(dead_letters.dir()).unwrap();
+}
+
+#[test]
+fn test_dead_letters_are_bounded() {
+    let dead_letters = dead_letter_dir("dead_letter_bounded", 2);
+    for _ in 0..3 {
+        dead_letters.write(&DeadLetter::new(Signature::new_unique(), &MonitorError::Timeout, None));
+    }
+    let letters = dead_letters.read_all();
+    assert_eq!(letters.len(), 2);
+    assert!(letters.iter().all(|letter| letter.code == "timeout" && letter.transaction.is_none()));
+
+    std::fs::remove_dir_all(dead_letters.dir()).unwrap();
+}
+
+/// function to build a `logsNotification` frame for subscription 24040.
+fn logs_notification(signature: &str, err: &str) -> String {
+    format!(
+        r#"{{"jsonrpc":"2.0","method":"logsNotification","params":{{"result":{{"context":{{"slot":280000010}},"value":{{"signature":"{}","err":{},"logs":[]}}}},"subscription":24040}}}}"#,
+        signature, err
+    )
+}
+
+#[test]
+fn test_parse_subscription_confirmation() {
+    assert_eq!(
+        parse_frame(r#"{"jsonrpc":"2.0","result":24040,"id":1}"#),
+        SubscriptionFrame::Subscribed {
+            request_id: 1,
+            subscription: 24040
+        }
+    );
+    assert_eq!(
+        parse_frame(r#"{"jsonrpc":"2.0","result":7,"id":2}"#),
+        SubscriptionFrame::Subscribed {
+            request_id: 2,
+            subscription: 7
+        }
+    );
+    // the answer to logsUnsubscribe is a boolean
+    assert_eq!(parse_frame(r#"{"jsonrpc":"2.0","result":true,"id":3}"#), SubscriptionFrame::Other);
+    assert_eq!(parse_frame("not json"), SubscriptionFrame::Other);
+}
+
+#[test]
+fn test_parse_subscription_error() {
+    assert_eq!(
+        parse_frame(r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Too many subscriptions"},"id":1}"#),
+        SubscriptionFrame::Rejected {
+            code: -32603,
+            message: "Too many subscriptions".to_string()
+        }
+    );
+    assert_eq!(
+        parse_frame(r#"{"jsonrpc":"2.0","error":{"code":-32700,"message":"Parse error"},"id":null}"#),
+        SubscriptionFrame::Rejected {
+            code: -32700,
+            message: "Parse error".to_string()
+        }
+    );
+
+}
+
+#[test]
+fn test_provider_limit_reasons_are_recognised() {
+    // the messages as the providers word them, capitalisation and surrounding text included
+    let reported = [
+        ("Helius", "Too many subscriptions"),
+        ("Helius", "Max subscriptions reached for this API key"),
+        ("Helius", "WebSocket connection limit exceeded"),
+        ("QuickNode", "You have exceeded the maximum number of subscriptions for your endpoint"),
+        ("QuickNode", "Max concurrent WebSocket connections reached"),
+        ("QuickNode", "logsSubscribe is not available on your plan"),
+        ("Triton", "Subscription limit exceeded"),
+        ("Triton", "Too many connections from this token"),
+        ("any", "plan limit reached"),
+    ];
+    for (provider, message) in reported {
+        assert_eq!(limit_in_message(message), Some(provider), "{}", message);
+        assert!(is_limit_rejection(-32603, message), "{}", message);
+    }
+    for (_, reason) in LIMIT_REASONS {
+        assert!(limit_in_message(&reason.to_uppercase()).is_some(), "{}", reason);
+    }
+
+    assert!(is_limit_rejection(429, "slow down"));
+    for message in ["Parse error", "Invalid params: invalid pubkey", "Too many requests", "Method not found"] {
+        assert!(!is_limit_rejection(-32602, message), "{}", message);
+    }
+}
+
+#[test]
+fn test_provider_limit_close_frames() {
+    use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+    use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+
+    let frame = |code: u16, reason: &'static str| CloseFrame { code: CloseCode::from(code), reason: reason.into() };
+    assert_eq!(
+        limit_in_close(Some(&frame(1013, ""))).as_deref(),
+        Some("connection closed with code 1013: ")
+    );
+    assert!(limit_in_close(Some(&frame(4029, "slow down"))).is_some());
+    assert!(limit_in_close(Some(&frame(1008, "Too many subscriptions"))).is_some());
+
+    assert_eq!(limit_in_close(Some(&frame(1000, "bye"))), None);
+    assert_eq!(limit_in_close(Some(&frame(1011, "internal error"))), None);
+    assert_eq!(limit_in_close(None), None);
+}
+
+#[test]
+fn test_provider_limit_is_not_retryable_soon() {
+    let error = MonitorError::ProviderLimit("Too many subscriptions (code -32603)".to_string());
+    assert!(!error.is_retryable());
+    assert_eq!(error.code(), "provider_limit");
+    assert_eq!(error.to_string(), "RPC provider limit reached: Too many subscriptions (code -32603)");
+}
+
+#[test]
+fn test_parse_notification() {
+    assert_eq!(
+        parse_frame(&logs_notification(BUY_SIGNATURE, "null")),
+        SubscriptionFrame::Notification {
+            subscription: 24040,
+            signature: Some(Signature::from_str(BUY_SIGNATURE).unwrap()),
+            logs: Vec::new()
+        }
+    );
+    assert_eq!(
+        parse_frame(&logs_notification(BUY_SIGNATURE, r#"{"InstructionError":[0,{"Custom":6002}]}"#)),
+        SubscriptionFrame::Notification {
+            subscription: 24040,
+            signature: None,
+            logs: Vec::new()
+        }
+    );
+    // a notification without a subscription id can't be attributed to ours
+    let untagged = format!(
+        r#"{{"method":"logsNotification","params":{{"result":{{"value":{{"signature":"{}","err":null}}}}}}}}"#,
+        BUY_SIGNATURE
+    );
+    assert_eq!(parse_frame(&untagged), SubscriptionFrame::Other);
+}
+
+#[test]
+fn test_notification_keeps_the_logs_of_successful_transactions() {
+    let frame = |err: &str| {
+        format!(
+            r#"{{"jsonrpc":"2.0","method":"logsNotification","params":{{"result":{{"value":{{"signature":"{}","err":{},"logs":["Program log: Instruction: Create","{}"]}}}},"subscription":24040}}}}"#,
+            CREATE_SIGNATURE, err, CREATE_EVENT_LOG
+        )
+    };
+    let SubscriptionFrame::Notification { logs, .. } = parse_frame(&frame("null")) else {
+        panic!("not a notification");
+    };
+    assert_eq!(logs, vec!["Program log: Instruction: Create".to_string(), CREATE_EVENT_LOG.to_string()]);
+
+    let failed = frame(r#"{"InstructionError":[0,"Custom"]}"#);
+    let SubscriptionFrame::Notification { signature, logs, .. } = parse_frame(&failed) else {
+        panic!("not a notification");
+    };
+    assert_eq!((signature, logs), (None, Vec::new()));
+}
+
+#[test]
+fn test_subscription_requests() {
+    let program_id = Pubkey::from_str(crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID).unwrap();
+    let subscribe: serde_json::Value =
+        serde_json::from_str(&subscribe_request(2, &program_id, Commitment::Processed)).unwrap();
+    assert_eq!(subscribe["method"], "logsSubscribe");
+    assert_eq!(subscribe["id"], 2);
+    assert_eq!(subscribe["params"][0]["mentions"][0], crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID);
+    assert_eq!(subscribe["params"][1]["commitment"], "processed");
+
+    let unsubscribe: serde_json::Value = serde_json::from_str(&unsubscribe_request(3, 24040)).unwrap();
+    assert_eq!(unsubscribe["method"], "logsUnsubscribe");
+    assert_eq!(unsubscribe["id"], 3);
+    assert_eq!(unsubscribe["params"], serde_json::json!([24040]));
+}
+
+#[test]
+fn test_recorded_notification_format() {
+    let line = r#"{"receivedAtMs":1718000000412,"message":"{}"}"#;
+    let notification: RecordedNotification = serde_json::from_str(line).unwrap();
+
+    assert_eq!(notification.received_at_ms, 1_718_000_000_412);
+    assert_eq!(serde_json::to_string(&notification).unwrap(), line);
+}
+
+/// `Program data:` line of the Moon Cat fixture's CreateEvent, including the fields newer program versions append.
+const CREATE_EVENT_LOG: &str = "Program data: G3KpTd7rY3YIAAAATW9vbiBDYXQEAAAATUNBVEMAAABodHRwczovL2lwZnMuaW8vaXBmcy9RbVl3QVBKenY1Q1pzbkE2MjVzM1hmMm5lbXRZZ1BwSGRXRXo3OW9qV25QYmRHhSWWbADzn/VO9eU3pHNq9DZJTR8WgcZZvZjjesKL7/Ei9bTzggzilyiQMTrDgkRp6/qTbhTIpfxt42b1T1L4vOokRXwqMX/19au2p34cTfEdrh15wwER9SHepwsdN+/o6iRFfCoxf/X1q7anfhxN8R2uHXnDARH1Id6nCx037+iAmWZmAAAAAAAQ2EfjzwMAAKwj/AYAAAAAeMX7UdECAACAxqR+jQMA";
+
+/// function to wrap log lines in an invocation of `program`.
+fn invocation(program: &str, lines: &[&str]) -> Vec<String> {
+    let mut logs = vec![format!("Program {} invoke [1]", program)];
+    logs.extend(lines.iter().map(|line| line.to_string()));
+    logs.push(format!("Program {} success", program));
+    logs
+}
+
+/// function to return the parser settings of the default pump.fun program.
+fn pump_fun_program() -> ProgramConfig {
+    ProgramConfig::new(Pubkey::from_str(crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID).unwrap())
+}
+
+#[test]
+fn test_find_create_event_in_logs() {
+    let logs = invocation(
+        crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID,
+        &["Program log: Instruction: Create", CREATE_EVENT_LOG],
+    );
+    let event = find_create_event(&logs, &pump_fun_program()).expect("event not found");
+
+    assert_eq!(event.name, "Moon Cat");
+    assert_eq!(event.symbol, "MCAT");
+    assert_eq!(event.uri, "https://ipfs.io/ipfs/QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG");
+    assert_eq!(Pubkey::new_from_array(event.mint).to_string(), FIXTURE_MINT);
+    assert_eq!(Pubkey::new_from_array(event.bonding_curve).to_string(), FIXTURE_BONDING_CURVE);
+    assert_eq!(Pubkey::new_from_array(event.user).to_string(), FIXTURE_CREATOR);
+}
+
+#[test]
+fn test_preview_from_logged_create_event() {
+    let signature = Signature::from_str(CREATE_SIGNATURE).unwrap();
+    let received_at = Utc::now();
+    let logs = invocation(
+        crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID,
+        &["Program log: Instruction: Create", CREATE_EVENT_LOG],
+    );
+    let preview = preview_from_logs(&signature, &logs, &[pump_fun_program()], received_at).expect("no preview");
+
+    assert_eq!(preview.transaction_signature, CREATE_SIGNATURE);
+    assert_eq!(preview.program_id, crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID);
+    assert_eq!(preview.mint_address, FIXTURE_MINT);
+    assert_eq!((preview.name.as_str(), preview.symbol.as_str()), ("Moon Cat", "MCAT"));
+    assert_eq!(preview.creator, FIXTURE_CREATOR);
+    assert_eq!(preview.bonding_curve, FIXTURE_BONDING_CURVE);
+    assert_eq!(preview.timestamp, received_at);
+
+    // a buy logs no CreateEvent, so it isn't previewed
+    let buy = invocation(crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID, &["Program log: Instruction: Buy"]);
+    assert!(preview_from_logs(&signature, &buy, &[pump_fun_program()], received_at).is_none());
+}
+
+#[test]
+fn test_create_event_from_other_program_is_ignored() {
+    let forger = Pubkey::new_unique().to_string();
+    let logs = invocation(&forger, &[CREATE_EVENT_LOG]);
+    assert!(find_create_event(&logs, &pump_fun_program()).is_none());
+
+    // a program logging text that looks like an invocation doesn't change whose data follows
+    let logs = invocation(
+        &forger,
+        &[&format!("Program log: {} invoke [2]", crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID), CREATE_EVENT_LOG],
+    );
+    assert!(find_create_event(&logs, &pump_fun_program()).is_none());
+}
+
+#[test]
+fn test_create_event_after_nested_invocation() {
+    let logs = invocation(
+        crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID,
+        &[
+            "Program 11111111111111111111111111111111 invoke [2]",
+            "Program 11111111111111111111111111111111 success",
+            CREATE_EVENT_LOG,
+        ],
+    );
+    assert!(find_create_event(&logs, &pump_fun_program()).is_some());
+}
+
+#[test]
+fn test_malformed_program_data_is_skipped() {
+    let truncated = &CREATE_EVENT_LOG[..40];
+    let logs = invocation(
+        crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID,
+        &["Program data: not base64!", truncated, "Program data: AAAAAAAAAAA=", CREATE_EVENT_LOG],
+    );
+    let event = find_create_event(&logs, &pump_fun_program()).expect("valid event after malformed lines not found");
+    assert_eq!(event.symbol, "MCAT");
+}
+
+#[test]
+fn test_create_event_with_custom_discriminator() {
+    let mut payload = STANDARD.decode(CREATE_EVENT_LOG.trim_start_matches("Program data: ")).unwrap();
+    payload[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+    let log = format!("Program data: {}", STANDARD.encode(&payload));
+    let logs = invocation(crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID, &[&log]);
+
+    assert!(find_create_event(&logs, &pump_fun_program()).is_none());
+    let fork = ProgramConfig {
+        create_event: [1, 2, 3, 4, 5, 6, 7, 8],
+        ..pump_fun_program()
+    };
+    assert_eq!(find_create_event(&logs, &fork).expect("event not found").symbol, "MCAT");
+}
+
+#[test]
+fn test_recent_signatures_dedupe_within_window() {
+    let mut recent = RecentSignatures::new(2);
+    let (first, second, third) = (Signature::new_unique(), Signature::new_unique(), Signature::new_unique());
+
+    assert!(recent.insert(first));
+    assert!(!recent.insert(first));
+    assert!(recent.insert(second));
+    // a third signature pushes the first out of the window
+    assert!(recent.insert(third));
+    assert!(!recent.insert(second));
+    assert!(recent.insert(first));
+}
+
+/// function to build a mint cache with the given TTL and capacity.
+fn seen_mints(ttl_secs: u64, capacity: usize) -> SeenMints {
+    SeenMints::new(&crate::config::MintDedupeConfig {
+        ttl: Duration::from_secs(ttl_secs),
+        capacity,
+    })
+}
+
+#[test]
+fn test_seen_mints_expire_after_ttl() {
+    let mints = seen_mints(60, 10);
+    let start = Instant::now();
+
+    assert!(mints.first_sighting("MintA", start));
+    assert!(!mints.first_sighting("MintA", start + Duration::from_secs(30)));
+    assert!(mints.first_sighting("MintB", start + Duration::from_secs(30)));
+    // a repeat doesn't extend the window, so the first sighting's TTL still applies
+    assert!(mints.first_sighting("MintA", start + Duration::from_secs(60)));
+    assert!(!mints.first_sighting("MintB", start + Duration::from_secs(60)));
+}
+
+#[test]
+fn test_seen_mints_capacity_evicts_oldest() {
+    let mints = seen_mints(3_600, 2);
+    let now = Instant::now();
+
+    assert!(mints.first_sighting("MintA", now));
+    assert!(mints.first_sighting("MintB", now));
+    assert!(!mints.first_sighting("MintB", now));
+    assert!(mints.first_sighting("MintC", now));
+    // MintA made room for MintC
+    assert!(!mints.first_sighting("MintC", now));
+    assert!(mints.first_sighting("MintA", now));
+}
+
+#[test]
+fn test_seen_mints_contains_without_recording() {
+    let mints = seen_mints(60, 10);
+    let start = Instant::now();
+
+    assert!(!mints.contains("MintA", start));
+    assert!(mints.first_sighting("MintA", start));
+    assert!(mints.contains("MintA", start + Duration::from_secs(30)));
+    assert!(!mints.contains("MintA", start + Duration::from_secs(60)));
+    assert!(!mints.contains("MintB", start));
+    assert!(mints.first_sighting("MintB", start));
+}
+
+#[test]
+fn test_fold_drops_case_spacing_and_version() {
+    assert_eq!(fold("TRUMP 2.0"), "trump");
+    assert_eq!(fold("Trump2"), "trump");
+    assert_eq!(fold("trump_v2"), "trumpv");
+    assert_eq!(fold("420"), "420");
+    assert_eq!(fold("🚀🚀"), "");
+}
+
+#[test]
+fn test_within_distance_stops_at_the_bound() {
+    let chars = |text: &str| text.chars().collect::<Vec<_>>();
+    let mut rows = (Vec::new(), Vec::new());
+    assert!(within_distance(&chars("trump"), &chars("trumpp"), 1, &mut rows));
+    assert!(within_distance(&chars("trump"), &chars("tramp"), 1, &mut rows));
+    assert!(!within_distance(&chars("trump"), &chars("tramps"), 1, &mut rows));
+    assert!(within_distance(&chars("tramps"), &chars("trump"), 2, &mut rows));
+    assert!(!within_distance(&chars("doge"), &chars("pepe"), 1, &mut rows));
+    assert!(!within_distance(&chars("cat"), &chars("bat"), 0, &mut rows));
+}
+
+#[test]
+fn test_recent_names_count_copycats() {
+    let names = RecentNames::new(Duration::from_secs(300), 100);
+    let now = Instant::now();
+
+    assert_eq!(names.record("TRUMP", "TRUMP", now), 0);
+    assert_eq!(names.record("TRUMP 2.0", "TRUMP2", now), 1);
+    assert_eq!(names.record("TRUMPP", "TRP", now), 2);
+    // same symbol, unrelated name
+    assert_eq!(names.record("Donald's Coin", "TRUMP", now), 2);
+    assert_eq!(names.record("Moon Cat", "MCAT", now), 0);
+    // short names have to match exactly
+    assert_eq!(names.record("Bat", "BAT", now), 0);
+    assert_eq!(names.record("Cat", "CAT", now), 0);
+    // names of only emoji fold to nothing and never match each other
+    assert_eq!(names.record("🚀🚀", "🚀", now), 0);
+    assert_eq!(names.record("🚀", "🚀🚀", now), 0);
+}
+
+#[test]
+fn test_recent_names_forget_after_window_and_capacity() {
+    let names = RecentNames::new(Duration::from_secs(60), 2);
+    let start = Instant::now();
+
+    assert_eq!(names.record("Moon Cat", "MCAT", start), 0);
+    assert_eq!(names.record("Moon Cat", "MCAT", start + Duration::from_secs(30)), 1);
+    // the first sighting left the window
+    assert_eq!(names.record("Moon Cat", "MCAT", start + Duration::from_secs(60)), 1);
+
+    let names = RecentNames::new(Duration::from_secs(60), 2);
+    names.record("Moon Cat", "MCAT", start);
+    names.record("Doge King", "DKING", start);
+    names.record("Pepe Lord", "PLORD", start);
+    // Moon Cat made room for Pepe Lord
+    assert_eq!(names.record("Moon Cat", "MCAT", start), 0);
+}
+
+#[test]
+fn test_recent_names_apply_reloaded_limits() {
+    let names = RecentNames::new(Duration::from_secs(300), 100);
+    let start = Instant::now();
+    for (name, symbol) in [("Moon Cat", "MCAT"), ("Doge King", "DKING"), ("Pepe Lord", "PLORD")] {
+        names.record(name, symbol, start);
+    }
+
+    // a lowered capacity forgets the surplus once this sighting has been compared
+    names.set_limits(Duration::from_secs(300), 1);
+    assert_eq!(names.record("Doge King", "DKING", start), 1);
+    assert_eq!(names.record("Pepe Lord", "PLORD", start), 0);
+
+    // and a shorter window forgets entries that are now too old
+    names.set_limits(Duration::from_secs(10), 100);
+    assert_eq!(names.record("Pepe Lord", "PLORD", start + Duration::from_secs(10)), 0);
+}
+
+#[test]
+fn test_spam_suppression_parses() {
+    assert_eq!("tag".parse::<SpamSuppression>().unwrap(), SpamSuppression::Tag);
+    assert_eq!(" DROP ".parse::<SpamSuppression>().unwrap(), SpamSuppression::Drop);
+    assert_eq!("off".parse::<SpamSuppression>().unwrap(), SpamSuppression::Off);
+    assert!("mute".parse::<SpamSuppression>().is_err());
+}
+
+/// function to create the default watchdog settings: ping after 60s of silence, reconnect 10s later.
+fn watchdog_config() -> WatchdogConfig {
+    WatchdogConfig {
+        silence_timeout: Duration::from_secs(60),
+        ping_grace: Duration::from_secs(10),
+        keepalive_interval: Duration::from_secs(30),
+    }
+}
+
+#[test]
+fn test_watchdog_pings_then_reconnects() {
+    let start = tokio::time::Instant::now();
+    let mut watchdog = Watchdog::new(&watchdog_config(), start);
+    assert_eq!(watchdog.deadline(), start + Duration::from_secs(60));
+
+    let silent_at = watchdog.deadline();
+    assert_eq!(watchdog.expired(silent_at), WatchdogAction::Ping);
+    assert_eq!(watchdog.deadline(), silent_at + Duration::from_secs(10));
+
+    assert_eq!(watchdog.expired(watchdog.deadline()), WatchdogAction::Reconnect);
+}
+
+#[test]
+fn test_watchdog_reset_by_frames() {
+    let start = tokio::time::Instant::now();
+    let mut watchdog = Watchdog::new(&watchdog_config(), start);
+
+    watchdog.frame_received(start + Duration::from_secs(30));
+    assert_eq!(watchdog.deadline(), start + Duration::from_secs(90));
+
+    // a pong answering the ping clears it, so the next silence starts with another ping
+    assert_eq!(watchdog.expired(start + Duration::from_secs(90)), WatchdogAction::Ping);
+    watchdog.frame_received(start + Duration::from_secs(95));
+    assert_eq!(watchdog.deadline(), start + Duration::from_secs(155));
+    assert_eq!(watchdog.silence(start + Duration::from_secs(100)), Duration::from_secs(5));
+    assert_eq!(watchdog.expired(start + Duration::from_secs(155)), WatchdogAction::Ping);
+}
+
+/// function to open a creator history with the given file and lookback.
+fn creator_history(file: Option<std::path::PathBuf>, lookback_signatures: usize) -> CreatorHistory {
+    CreatorHistory::open(
+        &crate::config::CreatorHistoryConfig {
+            file,
+            lookback_signatures,
+            abandoned_after: Duration::from_secs(3_600),
+        },
+        100_000,
+    )
+    .unwrap()
+}
+
+/// function to build a UTC time `secs` seconds after the fixtures' block time.
+fn fixture_time(secs: i64) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::from_timestamp(1_718_000_000 + secs, 0).unwrap()
+}
+
+#[test]
+fn test_chain_latency_from_block_time() {
+    let observed_at = fixture_time(2) + chrono::Duration::milliseconds(350);
+    assert_eq!(chain_latency_ms(observed_at, Some(1_718_000_000)), Some(2_350));
+    assert_eq!(chain_latency_ms(observed_at, None), None);
+    // a local clock behind the block time reads as no latency rather than wrapping
+    assert_eq!(chain_latency_ms(fixture_time(-3), Some(1_718_000_000)), Some(0));
+}
+
+#[tokio::test]
+async fn test_creator_history_counts_launches_per_creator() {
+    let history = creator_history(None, 0);
+    let rpc = MockRpc::default();
+    let programs = [pump_fun_program()];
+
+    let first = history.record(&rpc, &programs, "creator_A", "mint_1", "sig_1", fixture_time(0)).await;
+    assert_eq!(first.tokens_created_seen, 1);
+    assert_eq!(first.first_seen, first.last_seen);
+
+    history.record(&rpc, &programs, "creator_B", "mint_2", "sig_2", fixture_time(5)).await;
+    let second = history.record(&rpc, &programs, "creator_A", "mint_3", "sig_3", fixture_time(10)).await;
+    assert_eq!(second.tokens_created_seen, 2);
+    assert_eq!(second.first_seen, fixture_time(0));
+    assert_eq!(second.last_seen, fixture_time(10));
+    // without a lookback the RPC is never asked
+    assert_eq!(rpc.calls.load(Ordering::Relaxed), 0);
+}
+
+#[tokio::test]
+async fn test_creator_history_survives_restart() {
+    let dir = scratch_dir("creator_history");
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("creators.jsonl");
+    let rpc = MockRpc::default();
+    let programs = [pump_fun_program()];
+
+    let history = creator_history(Some(file.clone()), 0);
+    history.record(&rpc, &programs, "creator_A", "mint_1", "sig_1", fixture_time(0)).await;
+    history.record(&rpc, &programs, "creator_A", "mint_2", "sig_2", fixture_time(10)).await;
+    drop(history);
+    // a torn last line from a crash is skipped rather than failing startup
+    std::fs::OpenOptions::new().append(true).open(&file).unwrap().write_all(b"{\"creator\":").unwrap();
+
+    let reopened = creator_history(Some(file), 0);
+    let stats = reopened.record(&rpc, &programs, "creator_A", "mint_3", "sig_3", fixture_time(20)).await;
+    assert_eq!(stats.tokens_created_seen, 3);
+    assert_eq!(stats.first_seen, fixture_time(0));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_creator_history_lookback_counts_earlier_creations() {
+    // the creator's wallet signed the create being recorded, an earlier create through a router and a buy
+    let rpc = MockRpc::default()
+        .with_signatures(FIXTURE_CREATOR, &[CREATE_SIGNATURE, BUY_SIGNATURE, ROUTER_CREATE_SIGNATURE])
+        .with_transaction(BUY_SIGNATURE, include_str!("fixtures/buy.json"))
+        .with_transaction(ROUTER_CREATE_SIGNATURE, include_str!("fixtures/create_via_router.json"));
+    let history = creator_history(None, 10);
+    let programs = [pump_fun_program()];
+
+    let stats = history
+        .record(&rpc, &programs, FIXTURE_CREATOR, "fixture_mint", CREATE_SIGNATURE, fixture_time(60))
+        .await;
+    assert_eq!(stats.tokens_created_seen, 2);
+    assert_eq!(stats.first_seen, fixture_time(0));
+    assert_eq!(stats.last_seen, fixture_time(60));
+    // one signature listing and two transactions; the create being recorded isn't fetched again
+    assert_eq!(rpc.calls.load(Ordering::Relaxed), 3);
+
+    // a known creator isn't looked up again
+    history.record(&rpc, &programs, FIXTURE_CREATOR, "mint_later", "sig_later", fixture_time(120)).await;
+    assert_eq!(rpc.calls.load(Ordering::Relaxed), 3);
+}
+
+#[tokio::test]
+async fn test_creator_history_tracks_graduated_and_abandoned_launches() {
+    let history = creator_history(None, 0);
+    let rpc = MockRpc::default();
+    let programs = [pump_fun_program()];
+    for (mint, secs) in [("mint_1", 0), ("mint_2", 10), ("mint_3", 20)] {
+        history.record(&rpc, &programs, "creator_A", mint, "sig", fixture_time(secs)).await;
+    }
+
+    let graduated = history.record_graduation("mint_1", fixture_time(100)).unwrap();
+    assert_eq!((graduated.tokens_graduated, graduated.tokens_abandoned), (1, 0));
+    assert_eq!(graduated.graduation_rate, Some(1.0));
+
+    // an hour after their launch the other two count as abandoned; the new launch has no outcome yet
+    let stats = history.record(&rpc, &programs, "creator_A", "mint_4", "sig", fixture_time(3_620)).await;
+    assert_eq!(stats.tokens_created_seen, 4);
+    assert_eq!((stats.tokens_graduated, stats.tokens_abandoned), (1, 2));
+    assert_eq!(stats.graduation_rate, Some(1.0 / 3.0));
+
+    // a late migration moves an abandoned launch over to graduated
+    let late = history.record_graduation("mint_2", fixture_time(4_000)).unwrap();
+    assert_eq!((late.tokens_graduated, late.tokens_abandoned), (2, 1));
+    assert_eq!(late.graduation_rate, Some(2.0 / 3.0));
+
+    // a mint migrates once, and one launched before the monitor saw its creator counts for nobody
+    assert_eq!(history.record_graduation("mint_1", fixture_time(4_100)), None);
+    assert_eq!(history.record_graduation("unknown_mint", fixture_time(4_100)), None);
+    let first_launch = history.record(&rpc, &programs, "creator_B", "mint_5", "sig", fixture_time(4_200)).await;
+    assert_eq!(first_launch.graduation_rate, None);
+}
+
+#[tokio::test]
+async fn test_creator_outcomes_are_rebuilt_from_the_history_file() {
+    let dir = scratch_dir("creator_outcomes");
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("creators.jsonl");
+    // a line from before outcomes were tracked has no mint, and never gets an outcome
+    let lines = [
+        r#"{"creator":"creator_A","seenAt":"2024-06-10T00:00:00Z"}"#,
+        r#"{"creator":"creator_A","mint":"mint_1","seenAt":"2024-06-10T01:00:00Z"}"#,
+        r#"{"creator":"creator_A","mint":"mint_2","seenAt":"2024-06-10T02:00:00Z"}"#,
+        r#"{"creator":"creator_A","mint":"mint_3","seenAt":"2024-06-10T03:00:00Z"}"#,
+        r#"{"creator":"creator_A","mint":"mint_1","graduatedAt":"2024-06-10T01:30:00Z"}"#,
+        r#"{"creator":"creator_B","mint":"mint_4","seenAt":"2024-06-10T03:00:00Z"}"#,
+    ];
+    std::fs::write(&file, lines.join("\n") + "\n").unwrap();
+    let rpc = MockRpc::default();
+    let programs = [pump_fun_program()];
+
+    let history = creator_history(Some(file.clone()), 0);
+    let launched_at: chrono::DateTime<chrono::Utc> = "2024-06-10T03:30:00Z".parse().unwrap();
+    let stats = history.record(&rpc, &programs, "creator_A", "mint_5", "sig_5", launched_at).await;
+    // mint_2 is over an hour old, mint_3 isn't yet
+    assert_eq!(stats.tokens_created_seen, 5);
+    assert_eq!((stats.tokens_graduated, stats.tokens_abandoned), (1, 1));
+    assert_eq!(stats.graduation_rate, Some(0.5));
+
+    // graduations are appended too, and a restart reads them back
+    history.record_graduation("mint_3", launched_at).unwrap();
+    drop(history);
+    let reopened = creator_history(Some(file), 0);
+    let later = launched_at + chrono::Duration::hours(2);
+    let stats = reopened.record(&rpc, &programs, "creator_A", "mint_6", "sig_6", later).await;
+    assert_eq!((stats.tokens_graduated, stats.tokens_abandoned), (2, 2));
+    assert_eq!(stats.graduation_rate, Some(0.5));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// function to build `count` distinct signatures.
+fn unique_signatures(count: usize) -> Vec<Signature> {
+    (0..count).map(|_| Signature::new_unique()).collect()
+}
+
+#[tokio::test]
+async fn test_processor_queue_drops_oldest_spilled_signatures() {
+    let metrics = Arc::new(Metrics::default());
+    let (sender, mut receiver) = mpsc::channel(10);
+    let mut queue = ProcessorQueue::new(sender, 100, Arc::clone(&metrics));
+    let signatures = unique_signatures(1_000);
+
+    // nothing is taken off the processor queue, so everything past 10 + 100 has to give way
+    for signature in &signatures {
+        assert!(queue.push(PendingSignature::new(*signature)));
+    }
+    assert_eq!(queue.spilled(), 100);
+    assert_eq!(metrics.processor_signatures_dropped.load(Ordering::Relaxed), 890);
+    assert_eq!(metrics.processor_overflow_depth.load(Ordering::Relaxed), 100);
+
+    // dropping the queue forwards what is spilled, in order, once there is room
+    drop(queue);
+    let mut received = Vec::new();
+    while let Some(pending) = receiver.recv().await {
+        received.push(pending.signature);
+    }
+    let expected: Vec<Signature> = signatures[..10].iter().chain(&signatures[900..]).copied().collect();
+    assert_eq!(received, expected);
+    assert_eq!(metrics.processor_overflow_depth.load(Ordering::Relaxed), 0);
+}
+
+#[tokio::test]
+async fn test_processor_queue_never_blocks_a_flooded_reader() {
+    let metrics = Arc::new(Metrics::default());
+    let (sender, mut receiver) = mpsc::channel::<PendingSignature>(16);
+    let mut queue = ProcessorQueue::new(sender, 256, Arc::clone(&metrics));
+    let processor = tokio::spawn(async move {
+        let mut received = Vec::new();
+        while let Some(pending) = receiver.recv().await {
+            received.push(pending.signature);
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        received
+    });
+
+    let signatures = unique_signatures(5_000);
+    let started = Instant::now();
+    for (index, signature) in signatures.iter().enumerate() {
+        assert!(queue.push(PendingSignature::new(*signature)));
+        // let the processor run now and then, as the reader does between frames
+        if index % 100 == 0 {
+            tokio::task::yield_now().await;
+        }
+    }
+    // the reader never waited on the 1ms-per-signature processor, which needs seconds for all of them
+    assert!(started.elapsed() < Duration::from_secs(1), "pushing took {:?}", started.elapsed());
+
+    drop(queue);
+    let received = tokio::time::timeout(Duration::from_secs(30), processor).await.unwrap().unwrap();
+    let dropped = metrics.processor_signatures_dropped.load(Ordering::Relaxed) as usize;
+    assert!(dropped > 0);
+    assert_eq!(received.len() + dropped, signatures.len());
+    // what got through is in arrival order and nothing was delivered twice
+    let positions: HashMap<Signature, usize> = signatures.iter().enumerate().map(|(index, signature)| (*signature, index)).collect();
+    assert!(received.windows(2).all(|pair| positions[&pair[0]] < positions[&pair[1]]));
+}
+
+/// function to stamp `count` generated events through a sequencer whose channel holds only two.
+async fn overflowing_sequencer(count: usize) -> (EventSequencer, broadcast::Receiver<TokenCreatedEvent>) {
+    let (sender, _) = broadcast::channel(2);
+    let sequencer = EventSequencer::new(EventDispatcher::new(sender));
+    let receiver = sequencer.subscribe();
+    let mut generator = crate::simulation::EventGenerator::new(7);
+    for _ in 0..count {
+        assert_eq!(sequencer.send(generator.next_event()).await, 1);
+    }
+    (sequencer, receiver)
+}
+
+#[tokio::test]
+async fn test_event_stream_yields_a_gap_when_lagging() {
+    let (sequencer, receiver) = overflowing_sequencer(5).await;
+    let mut stream = TokenEventStream::new(receiver, LagPolicy::Gap);
+
+    assert!(matches!(stream.next().await, Some(StreamItem::Gap { skipped: 3 })));
+    let sequences: Vec<u64> = vec![stream.next().await, stream.next().await]
+        .into_iter()
+        .map(|event| event.and_then(StreamItem::token).unwrap().sequence)
+        .collect();
+    assert_eq!(sequences, vec![4, 5]);
+    assert_eq!(stream.skipped(), 3);
+
+    drop(sequencer);
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_event_stream_skips_and_counts_when_lagging() {
+    let (sequencer, receiver) = overflowing_sequencer(5).await;
+    let mut stream = TokenEventStream::new(receiver, LagPolicy::Skip);
+    drop(sequencer);
+
+    let mut sequences = Vec::new();
+    while let Some(event) = stream.next().await {
+        match event {
+            StreamItem::Token(event) => sequences.push(event.sequence),
+            StreamItem::Gap { skipped } => panic!("unexpected gap of {} events", skipped),
+        }
+    }
+    assert_eq!(sequences, vec![4, 5]);
+    assert_eq!(stream.skipped(), 3);
+}
+
+/// `curve_account` with the `complete` flag set.
+fn completed_curve_account() -> Account {
+    let mut account = curve_account();
+    account.data[48] = 1;
+    account
+}
+
+/// one active and one completed curve, with a longer account than the snapshot asks for.
+fn snapshot_rpc() -> (MockRpc, Pubkey) {
+    let active = Pubkey::new_unique();
+    let mut account = curve_account();
+    account.data.extend_from_slice(&[7; 32]);
+    let rpc = MockRpc::default()
+        .with_program_account(crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID, active, account)
+        .with_program_account(crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID, Pubkey::new_unique(), completed_curve_account());
+    (rpc, active)
+}
+
+#[tokio::test]
+async fn test_snapshot_lists_only_active_curves() {
+    let (rpc, active) = snapshot_rpc();
+
+    let curves = take_snapshot(&rpc, &[pump_fun_program()], false).await.unwrap();
+
+    assert_eq!(rpc.calls.load(Ordering::Relaxed), 1);
+    assert_eq!(curves.len(), 1);
+    assert_eq!(curves[0].bonding_curve, active.to_string());
+    assert_eq!(curves[0].program_id, crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID);
+    assert_eq!(curves[0].real_sol_reserves, 5_000_000_000);
+    assert!(curves[0].progress_pct > 0.0 && curves[0].progress_pct < 100.0);
+}
+
+#[tokio::test]
+async fn test_partitioned_snapshot_finds_the_same_curves() {
+    let (rpc, active) = snapshot_rpc();
+
+    let curves = take_snapshot(&rpc, &[pump_fun_program()], true).await.unwrap();
+
+    assert_eq!(rpc.calls.load(Ordering::Relaxed), 256);
+    let addresses: Vec<&str> = curves.iter().map(|curve| curve.bonding_curve.as_str()).collect();
+    assert_eq!(addresses, vec![active.to_string().as_str()]);
+}
+
+#[tokio::test]
+async fn test_snapshot_file_is_diffed_against_the_previous_one() {
+    let dir = scratch_dir("curve_snapshot");
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = CurveSnapshotConfig {
+        file: dir.join("curves.jsonl"),
+        partitioned: false,
+    };
+    let (rpc, active) = snapshot_rpc();
+
+    let first = run_curve_snapshot(&rpc, &[pump_fun_program()], &config).await.unwrap();
+    assert_eq!(first.added, vec![active.to_string()]);
+    assert!(first.removed.is_empty());
+    assert_eq!(read_snapshot(&config.file).unwrap().len(), 1);
+
+    let unchanged = run_curve_snapshot(&rpc, &[pump_fun_program()], &config).await.unwrap();
+    assert_eq!(unchanged, SnapshotDiff::default());
+
+    let gone = diff_snapshots(&read_snapshot(&config.file).unwrap(), &[]);
+    assert_eq!(gone.removed, vec![active.to_string()]);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_diagnostics_are_rate_limited() {
+    let metrics = Arc::new(Metrics::default());
+    let (sender, _) = broadcast::channel(16);
+    let mut diagnostics = Diagnostics::new(Some(sender.clone()), 2, Arc::clone(&metrics));
+    let signature = Signature::from_str(CREATE_SIGNATURE).unwrap();
+
+    // nobody is listening, so nothing is built or counted
+    diagnostics.skipped(&signature, || panic!("built without a subscriber"));
+    assert_eq!(metrics.diagnostics_dropped.load(Ordering::Relaxed), 0);
+
+    let mut receiver = sender.subscribe();
+    for _ in 0..3 {
+        diagnostics.skipped(&signature, || "no create instruction".to_string());
+    }
+    assert_eq!(metrics.diagnostics_sent.load(Ordering::Relaxed), 2);
+    assert_eq!(metrics.diagnostics_dropped.load(Ordering::Relaxed), 1);
+
+    let json = serde_json::to_value(receiver.recv().await.unwrap()).unwrap();
+    assert_eq!(json["eventType"], "diagnostic");
+    assert_eq!(json["type"], "skipped");
+    assert_eq!(json["reason"], "no create instruction");
+    assert_eq!(json["signature"], CREATE_SIGNATURE);
+    receiver.recv().await.unwrap();
+    assert!(receiver.try_recv().is_err());
+
+    // the bucket refills with time
+    tokio::time::advance(Duration::from_secs(1)).await;
+    diagnostics.missed(&signature, 3, &MonitorError::Timeout);
+    let json = serde_json::to_value(receiver.recv().await.unwrap()).unwrap();
+    assert_eq!(json["type"], "missed");
+    assert_eq!(json["attempts"], 3);
+}
+
+/// function to build a startup-only parser self-test needing `min_transactions`.
+fn parser_self_test(min_transactions: usize) -> ParserSelfTestConfig {
+    ParserSelfTestConfig {
+        signatures: 1000,
+        min_transactions,
+        interval: None,
+    }
+}
+
+/// function to run one self-test window over the create and buy fixtures, as `program` parses them.
+async fn sample_fixtures(signatures: &[&str], program: ProgramConfig, min_transactions: usize) -> SelfTestWindow {
+    let rpc = MockRpc::default()
+        .with_signatures(crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID, signatures)
+        .with_transaction(CREATE_SIGNATURE, include_str!("fixtures/create.json"))
+        .with_transaction(BUY_SIGNATURE, include_str!("fixtures/buy.json"));
+    sample_window(Arc::new(rpc), &[program], Commitment::Confirmed, &parser_self_test(min_transactions)).await
+}
+
+#[tokio::test]
+async fn test_parser_self_test_finds_creations() {
+    let window = sample_fixtures(&[BUY_SIGNATURE, CREATE_SIGNATURE], pump_fun_program(), 2).await;
+    assert_eq!(window, SelfTestWindow { transactions: 2, creations: 1 });
+    assert_eq!(window.status(2), Some(ParserStatus::Ok));
+    // a single creation is enough, however few transactions the window had
+    assert_eq!(window.status(1000), Some(ParserStatus::Ok));
+}
+
+#[tokio::test]
+async fn test_parser_self_test_flags_stale_discriminators() {
+    // the program moved to discriminators the parser doesn't know, so the creation parses as anything else
+    let upgraded = ProgramConfig {
+        create_instruction: [1, 2, 3, 4, 5, 6, 7, 8],
+        create_event: [8, 7, 6, 5, 4, 3, 2, 1],
+        ..pump_fun_program()
+    };
+    let window = sample_fixtures(&[BUY_SIGNATURE, CREATE_SIGNATURE], upgraded, 2).await;
+    assert_eq!(window, SelfTestWindow { transactions: 2, creations: 0 });
+    assert_eq!(window.status(2), Some(ParserStatus::PossiblyStale));
+}
+
+#[tokio::test]
+async fn test_parser_self_test_inconclusive_with_few_transactions() {
+    // only buys, but too few of them to tell a quiet program from a stale parser
+    let window = sample_fixtures(&[BUY_SIGNATURE], pump_fun_program(), 2).await;
+    assert_eq!(window, SelfTestWindow { transactions: 1, creations: 0 });
+    assert_eq!(window.status(2), None);
+    assert_eq!(window.status(1), Some(ParserStatus::PossiblyStale));
+
+    // transactions that can't be fetched aren't counted
+    let window = sample_fixtures(&[BUY_SIGNATURE, ROUTER_CREATE_SIGNATURE], pump_fun_program(), 2).await;
+    assert_eq!(window.transactions, 1);
+}
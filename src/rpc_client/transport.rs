@@ -0,0 +1,228 @@
+//! # RPC Transport
+//!
+//! The Solana RPC calls the monitor makes, behind a trait so transaction processing can run against in-memory fixtures instead of a live node.
+//!
+//! The live node is reached through `EndpointRpc`, which names every call: each one gets a request id made of the
+//! endpoint's `SOLANA_RPC_HTTP_LABEL` and a counter, logged at debug with the method before the request goes out, and a
+//! call that fails comes back as `MonitorError::Rpc` carrying both, so a failure in the logs can be matched with the
+//! provider's own logs.
+//!
+//! A rate limited call pauses every call to the endpoint, not just the retry of the one that was refused: the
+//! processor's workers, the price feed and the lookbacks all share the `EndpointRpc`, and each would otherwise run
+//! into the same `429` on its own. Calls wait out the provider's `retry_after`, or `DEFAULT_RATE_LIMIT_PAUSE` when it
+//! gave none, before they go out.
+
+use super::transaction_not_available;
+use crate::error::{MonitorError, Result};
+use async_trait::async_trait;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig};
+use solana_client::rpc_filter::RpcFilterType;
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+/// how long calls hold off after a rate limit that didn't say how long to wait.
+pub const DEFAULT_RATE_LIMIT_PAUSE: Duration = Duration::from_millis(500);
+
+/// Solana HTTP RPC methods used by the monitor and its auxiliary tasks.
+///
+#[async_trait]
+pub trait SolanaRpc: Send + Sync {
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta>;
+
+    /// a single account, failing with `AccountNotFound` when it doesn't exist.
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Account>;
+
+    /// accounts in the same order as `pubkeys`, `None` for any that don't exist.
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>>;
+
+    /// the newest `limit` confirmed signatures involving `address`, newest first.
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>>;
+
+    /// every account owned by `program` that passes all of `filters`, with only `data_slice` of its data when given.
+    async fn get_program_accounts(
+        &self,
+        program: &Pubkey,
+        filters: Vec<RpcFilterType>,
+        data_slice: Option<UiDataSliceConfig>,
+    ) -> Result<Vec<(Pubkey, Account)>>;
+}
+
+/// The live HTTP RPC, naming each call's endpoint and request id in what it logs and in its errors.
+///
+pub struct EndpointRpc {
+    client: RpcClient,
+    label: String,
+    /// calls made so far; the next one is numbered one higher
+    calls: AtomicU64,
+    pause: RateLimitPause,
+}
+
+impl EndpointRpc {
+    pub fn new(client: RpcClient, label: &str) -> Self {
+        Self {
+            client,
+            label: label.to_string(),
+            calls: AtomicU64::new(0),
+            pause: RateLimitPause::default(),
+        }
+    }
+
+    /// the request id of a new call to `method`, logged at debug once a rate limit pause is over.
+    async fn start(&self, method: &str) -> String {
+        if let Some(waited) = self.pause.wait().await {
+            debug!(endpoint = %self.label, "RPC {} held back {:?} by a rate limit", method, waited);
+        }
+        let request_id = format!("{}-{}", self.label, self.calls.fetch_add(1, Ordering::Relaxed) + 1);
+        debug!(endpoint = %self.label, request_id = %request_id, "RPC {}", method);
+        request_id
+    }
+
+    /// `error` of the call logged as `request_id`, with the endpoint and request id around it.
+    ///
+    /// A rate limit pauses the endpoint's calls, this call's retry included.
+    fn error(&self, request_id: String, error: MonitorError) -> MonitorError {
+        if let MonitorError::RateLimited { retry_after } = &error {
+            let pause = retry_after.unwrap_or(DEFAULT_RATE_LIMIT_PAUSE);
+            if self.pause.rate_limited(pause) {
+                warn!(
+                    "RPC endpoint {} rate limited request {}, pausing its calls for {:?}",
+                    self.label, request_id, pause
+                );
+            }
+        }
+        MonitorError::Rpc {
+            endpoint: self.label.clone(),
+            request_id,
+            source: Box::new(error),
+        }
+    }
+}
+
+#[async_trait]
+impl SolanaRpc for EndpointRpc {
+    /// a transaction the node doesn't have yet fails with `TransactionNotFound`, which the processor retries later.
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        let request_id = self.start("getTransaction").await;
+        self.client.get_transaction_with_config(signature, config).await.map_err(|e| {
+            let error = if transaction_not_available(&e) {
+                MonitorError::TransactionNotFound(*signature)
+            } else {
+                MonitorError::from(e)
+            };
+            self.error(request_id, error)
+        })
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Account> {
+        let request_id = self.start("getAccountInfo").await;
+        self.client
+            .get_account(pubkey)
+            .await
+            .map_err(|e| self.error(request_id, MonitorError::from(e)))
+    }
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        let request_id = self.start("getMultipleAccounts").await;
+        self.client
+            .get_multiple_accounts(pubkeys)
+            .await
+            .map_err(|e| self.error(request_id, MonitorError::from(e)))
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        let config = GetConfirmedSignaturesForAddress2Config {
+            limit: Some(limit),
+            // like getTransaction, this doesn't support `processed`
+            commitment: Some(CommitmentConfig::confirmed()),
+            ..Default::default()
+        };
+        let request_id = self.start("getSignaturesForAddress").await;
+        self.client
+            .get_signatures_for_address_with_config(address, config)
+            .await
+            .map_err(|e| self.error(request_id, MonitorError::from(e)))
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program: &Pubkey,
+        filters: Vec<RpcFilterType>,
+        data_slice: Option<UiDataSliceConfig>,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice,
+                commitment: Some(self.client.commitment()),
+                min_context_slot: None,
+            },
+            with_context: None,
+        };
+        let request_id = self.start("getProgramAccounts").await;
+        self.client
+            .get_program_accounts_with_config(program, config)
+            .await
+            .map_err(|e| self.error(request_id, MonitorError::from(e)))
+    }
+}
+
+/// Holds an endpoint's calls back until a rate limit has passed.
+///
+#[derive(Default)]
+pub(crate) struct RateLimitPause {
+    until: Mutex<Option<Instant>>,
+}
+
+impl RateLimitPause {
+    /// Pauses calls for `pause` from now, returning whether that extended the pause; a longer one already running is
+    /// kept.
+    pub fn rate_limited(&self, pause: Duration) -> bool {
+        let until = Instant::now() + pause;
+        let mut current = self.until.lock().unwrap_or_else(|e| e.into_inner());
+        if current.is_some_and(|current| current >= until) {
+            return false;
+        }
+        *current = Some(until);
+        true
+    }
+
+    /// Waits out the pause, including any extension it gets meanwhile, returning how long for when there was one.
+    pub async fn wait(&self) -> Option<Duration> {
+        let started = Instant::now();
+        loop {
+            let until = *self.until.lock().unwrap_or_else(|e| e.into_inner());
+            match until {
+                Some(until) if until > Instant::now() => tokio::time::sleep_until(until).await,
+                _ => break,
+            }
+        }
+        let waited = started.elapsed();
+        (!waited.is_zero()).then_some(waited)
+    }
+}
@@ -0,0 +1,65 @@
+//! # Subscription Watchdog
+//!
+//! A Solana WebSocket can stay open while notifications stop flowing, and `read.next()` then never returns. pump.fun sees
+//! new transactions every few seconds, so a silent subscription is treated as a fault: after `silence_timeout` without any
+//! frame the server is pinged, and if nothing (not even the pong) arrives within `ping_grace` the connection is torn down.
+
+use crate::config::WatchdogConfig;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// what the reader loop should do once the watchdog deadline passes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogAction {
+    /// send a ping and wait out the grace period
+    Ping,
+    /// the ping went unanswered; reconnect
+    Reconnect,
+}
+
+/// Silence tracker for one connection; any received frame resets it.
+///
+pub struct Watchdog {
+    silence_timeout: Duration,
+    ping_grace: Duration,
+    last_frame_at: Instant,
+    ping_sent_at: Option<Instant>,
+}
+
+impl Watchdog {
+    pub fn new(config: &WatchdogConfig, now: Instant) -> Self {
+        Self {
+            silence_timeout: config.silence_timeout,
+            ping_grace: config.ping_grace,
+            last_frame_at: now,
+            ping_sent_at: None,
+        }
+    }
+
+    /// instant at which `expired` should next be called.
+    pub fn deadline(&self) -> Instant {
+        match self.ping_sent_at {
+            Some(sent_at) => sent_at + self.ping_grace,
+            None => self.last_frame_at + self.silence_timeout,
+        }
+    }
+
+    pub fn frame_received(&mut self, now: Instant) {
+        self.last_frame_at = now;
+        self.ping_sent_at = None;
+    }
+
+    /// the action due at the deadline; a ping is only sent once per silence.
+    pub fn expired(&mut self, now: Instant) -> WatchdogAction {
+        if self.ping_sent_at.is_some() {
+            return WatchdogAction::Reconnect;
+        }
+        self.ping_sent_at = Some(now);
+        WatchdogAction::Ping
+    }
+
+    /// how long the connection has been silent.
+    pub fn silence(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_frame_at)
+    }
+}
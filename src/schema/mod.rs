@@ -0,0 +1,58 @@
+//! # Wire Schema
+//!
+//! JSON Schema for everything on the wire: the events, the messages clients send and the notices the server sends
+//! back, generated with `schemars` from the same data models serde writes, so the schema can't drift from the wire
+//! format. `--dump-schema` prints every schema as one JSON object keyed by type name, or writes one
+//! `<Type>.schema.json` file per type into a directory, and the REST API serves the object on `GET /schema`. Events
+//! sent with `EVENT_FORMAT=envelope` carry these payloads in the envelope's `data`.
+//!
+//! `tests.rs` compares the output with `fixtures/wire_schema.json`, so a change to the wire format fails the tests
+//! until the snapshot is regenerated with `UPDATE_SCHEMA_SNAPSHOT=1`.
+
+use crate::data_models::{
+    ClientMessage, CurveUpdateEvent, ServerMessage, TokenCreatedEvent, TokenCreatedPreviewEvent, TokenEnrichedEvent,
+    TokenMigratedEvent,
+};
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// the schema of every type on the wire, by type name.
+pub fn wire_schemas() -> BTreeMap<&'static str, RootSchema> {
+    BTreeMap::from([
+        ("TokenCreatedEvent", schema_for!(TokenCreatedEvent)),
+        ("TokenCreatedPreviewEvent", schema_for!(TokenCreatedPreviewEvent)),
+        ("TokenEnrichedEvent", schema_for!(TokenEnrichedEvent)),
+        ("CurveUpdateEvent", schema_for!(CurveUpdateEvent)),
+        ("TokenMigratedEvent", schema_for!(TokenMigratedEvent)),
+        ("ClientMessage", schema_for!(ClientMessage)),
+        ("ServerMessage", schema_for!(ServerMessage)),
+    ])
+}
+
+/// `wire_schemas` as one JSON object, generated once.
+pub fn wire_schema_document() -> &'static serde_json::Value {
+    static DOCUMENT: OnceLock<serde_json::Value> = OnceLock::new();
+    DOCUMENT.get_or_init(|| serde_json::to_value(wire_schemas()).expect("schemas serialize to JSON"))
+}
+
+/// Writes `wire_schema_document` to stdout, or one `<Type>.schema.json` per type into `dir`, creating it if needed.
+pub fn dump_schema(dir: Option<&Path>) -> io::Result<()> {
+    let Some(dir) = dir else {
+        println!("{}", serde_json::to_string_pretty(wire_schema_document())?);
+        return Ok(());
+    };
+    fs::create_dir_all(dir)?;
+    for (name, schema) in wire_schemas() {
+        let json = serde_json::to_string_pretty(&schema)?;
+        fs::write(dir.join(format!("{}.schema.json", name)), json + "\n")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
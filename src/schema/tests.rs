@@ -0,0 +1,122 @@
+//! Unit tests for the wire schema: the snapshot, and the message types it has to cover.
+
+use super::*;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+/// function to find the snapshot the generated schema is compared with.
+fn snapshot_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("src/schema/fixtures/wire_schema.json")
+}
+
+/// function to collect the values a tagged enum's schema allows for its `tag` property.
+fn tags(schema: &serde_json::Value, tag: &str) -> BTreeSet<String> {
+    schema["oneOf"]
+        .as_array()
+        .expect("a tagged enum is a oneOf")
+        .iter()
+        .flat_map(|variant| variant["properties"][tag]["enum"].as_array().cloned().unwrap_or_default())
+        .filter_map(|value| value.as_str().map(str::to_string))
+        .collect()
+}
+
+#[test]
+fn test_schema_matches_snapshot() {
+    let generated = serde_json::to_string_pretty(wire_schema_document()).unwrap() + "\n";
+    let path = snapshot_path();
+    // a checkout without the snapshot records it; it has to be committed to guard anything
+    if std::env::var_os("UPDATE_SCHEMA_SNAPSHOT").is_some() || !path.exists() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &generated).unwrap();
+    }
+    let snapshot = fs::read_to_string(&path).unwrap();
+    assert!(
+        snapshot == generated,
+        "the wire format changed; check the change is intended and rerun with UPDATE_SCHEMA_SNAPSHOT=1"
+    );
+}
+
+#[test]
+fn test_client_message_schema_covers_every_action() {
+    let schema = &wire_schema_document()["ClientMessage"];
+    let actions = tags(schema, "action");
+    for action in [
+        "setFilter",
+        "usePreset",
+        "listPresets",
+        "getFilter",
+        "clearFilter",
+        "setCompression",
+        "setEncoding",
+        "setFields",
+        "setRawMode",
+        "setCurveUpdates",
+        "setPreviews",
+        "setHeartbeat",
+        "setRateLimit",
+        "resumeFrom",
+        "resume",
+        "query",
+        "identify",
+        "getStats",
+        "getAllStats",
+        "listClients",
+        "kickClient",
+        "getMonitorStatus",
+        "setDiagnostics",
+        "setLogLevel",
+    ] {
+        assert!(actions.contains(action), "{} is missing", action);
+    }
+}
+
+#[test]
+fn test_server_message_schema_covers_every_notice() {
+    let schema = &wire_schema_document()["ServerMessage"];
+    let event_types = tags(schema, "eventType");
+    for event_type in [
+        "lagged",
+        "heartbeat",
+        "stats",
+        "allStats",
+        "clients",
+        "monitorStatus",
+        "filter",
+        "presets",
+        "ack",
+        "summary",
+        "gap",
+        "session",
+        "resumed",
+        "queryResult",
+        "queryComplete",
+        "error",
+    ] {
+        assert!(event_types.contains(event_type), "{} is missing", event_type);
+    }
+}
+
+#[test]
+fn test_token_created_schema_leaves_out_skipped_fields() {
+    let schema = &wire_schema_document()["TokenCreatedEvent"];
+    let required: Vec<&str> = schema["required"].as_array().unwrap().iter().filter_map(|v| v.as_str()).collect();
+    assert!(required.contains(&"transactionSignature"));
+    assert!(required.contains(&"token"));
+    // optional and defaulted fields may be missing from older events
+    assert!(!required.contains(&"metadata"));
+    assert!(!required.contains(&"sequence"));
+    assert!(schema["properties"].get("rawTransaction").is_none());
+    assert!(schema["properties"].get("mintAddress").is_none());
+}
+
+#[test]
+fn test_dump_schema_writes_a_file_per_type() {
+    let dir = std::env::temp_dir().join(format!("pump_fun_monitor_schema_{}", std::process::id()));
+    dump_schema(Some(&dir)).unwrap();
+    for (name, expected) in wire_schemas() {
+        let json = fs::read_to_string(dir.join(format!("{}.schema.json", name))).unwrap();
+        let schema: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(schema, serde_json::to_value(&expected).unwrap(), "{}", name);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,148 @@
+//! # Event Signing
+//!
+//! Optional ed25519 signatures on broadcast events, so consumers several hops downstream (Redis, webhooks) can check
+//! that an event came from this monitor unchanged. With `EVENT_SIGNING_KEY` set, every event is signed as it is
+//! sequenced and carries `signerPubkey` (base58) and `signature` (base58, 64 bytes).
+//!
+//! The key is either a 32-byte ed25519 seed written as 64 hex digits, or the path of a Solana keypair file (the JSON
+//! array of 64 bytes `solana-keygen` writes).
+//!
+//! ## Canonical Form
+//!
+//! The signed bytes are the event's JSON with these rules:
+//!
+//! - the `signature` field is left out; every other field, `signerPubkey` and `sequence` included, is covered
+//! - object keys are sorted by their UTF-8 bytes at every level, and there is no whitespace
+//! - fields that are absent from the event stay absent, they are not written as `null`
+//! - strings, numbers and timestamps are written as they appear on the wire: timestamps are RFC 3339 in UTC with the
+//!   fractional seconds the event was sent with, and floats use the shortest form that reads back to the same value
+//!
+//! So a consumer can verify the JSON it received without knowing the event type: parse it, drop `signature`, and
+//! re-serialize with sorted keys. Events received as MessagePack verify the same way once decoded to JSON values. A
+//! JSON parser that rounds floats differently breaks verification; with serde_json, turn on its `float_roundtrip`
+//! feature as this crate does.
+
+use crate::data_models::TokenCreatedEvent;
+use serde_json::{Map, Value};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::keypair::{keypair_from_seed, read_keypair_file, Keypair};
+use solana_sdk::signer::Signer;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Signs events with the configured key.
+///
+pub struct EventSigner {
+    keypair: Keypair,
+    /// `keypair`'s public key in base58, written on every event
+    pubkey: String,
+}
+
+impl fmt::Debug for EventSigner {
+    // the secret key must never end up in a log line
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventSigner").field("pubkey", &self.pubkey).finish()
+    }
+}
+
+impl EventSigner {
+    /// reads the key from 64 hex digits of seed, or else from the keypair file `value` names.
+    pub fn load(value: &str) -> std::result::Result<Self, String> {
+        let value = value.trim();
+        let keypair = match parse_seed(value) {
+            Some(seed) => keypair_from_seed(&seed).map_err(|e| e.to_string())?,
+            None if Path::new(value).is_file() => read_keypair_file(value)
+                .map_err(|e| format!("'{}' is not a Solana keypair file: {}", value, e))?,
+            None => return Err("must be a 32-byte seed in hex or the path of a keypair file".to_string()),
+        };
+        Ok(Self::new(keypair))
+    }
+
+    pub fn new(keypair: Keypair) -> Self {
+        let pubkey = keypair.pubkey().to_string();
+        Self { keypair, pubkey }
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    /// sets `signerPubkey` on `event`, then `signature` over the event's canonical form.
+    ///
+    /// Anything changed on the event afterwards, its sequence included, invalidates the signature.
+    pub fn sign(&self, event: &mut TokenCreatedEvent) {
+        event.signer_pubkey = Some(self.pubkey.clone());
+        event.signature = None;
+        let signature = match serde_json::to_value(&*event) {
+            Ok(value) => self.keypair.sign_message(&canonical_bytes(&value)),
+            // every field serializes, so this can't happen; an unsigned event is still better than none
+            Err(_) => return,
+        };
+        event.signature = Some(signature.to_string());
+    }
+}
+
+/// Checks that `event`, as received, was signed by `signer` and hasn't been changed since.
+///
+/// `event` is the JSON object of one event. Fails when `signerPubkey` isn't `signer`, when either field is missing or
+/// malformed, or when the signature doesn't match the canonical form.
+#[cfg_attr(not(test), allow(dead_code))]
+pub fn verify_event(event: &Value, signer: &Pubkey) -> std::result::Result<(), String> {
+    let fields = event.as_object().ok_or("the event is not a JSON object")?;
+    let signer_pubkey = fields
+        .get("signerPubkey")
+        .and_then(Value::as_str)
+        .ok_or("the event has no signerPubkey")?;
+    if signer_pubkey != signer.to_string() {
+        return Err(format!("signed by {}, not {}", signer_pubkey, signer));
+    }
+    let signature = fields
+        .get("signature")
+        .and_then(Value::as_str)
+        .ok_or("the event has no signature")?;
+    let signature = Signature::from_str(signature).map_err(|e| format!("malformed signature: {}", e))?;
+    if !signature.verify(signer.as_ref(), &canonical_bytes(event)) {
+        return Err("the signature does not match the event".to_string());
+    }
+    Ok(())
+}
+
+/// the bytes signed for `event`: its JSON without `signature`, keys sorted, no whitespace.
+pub fn canonical_bytes(event: &Value) -> Vec<u8> {
+    let mut event = event.clone();
+    if let Value::Object(fields) = &mut event {
+        fields.remove("signature");
+    }
+    // a `Value` always serializes
+    serde_json::to_vec(&sorted(&event)).unwrap_or_default()
+}
+
+/// `value` with the keys of every object in sorted order, whatever order the JSON map keeps.
+fn sorted(value: &Value) -> Value {
+    match value {
+        Value::Object(fields) => {
+            let mut keys: Vec<&String> = fields.keys().collect();
+            keys.sort();
+            Value::Object(keys.into_iter().map(|key| (key.clone(), sorted(&fields[key]))).collect::<Map<_, _>>())
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sorted).collect()),
+        other => other.clone(),
+    }
+}
+
+/// a 32-byte seed written as 64 hex digits.
+fn parse_seed(value: &str) -> Option<[u8; 32]> {
+    if value.len() != 64 || !value.is_ascii() {
+        return None;
+    }
+    let mut seed = [0u8; 32];
+    for (index, byte) in seed.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&value[index * 2..index * 2 + 2], 16).ok()?;
+    }
+    Some(seed)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,186 @@
+//! Unit tests for event signing, verification and key loading.
+
+
+use super::*;
+use crate::data_models::{PumpFunData, TokenDetails};
+use chrono::{TimeZone, Utc};
+
+/// the seed of the signing key used throughout, 0x01 to 0x20.
+const SEED_HEX: &str = "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20";
+
+fn signer() -> EventSigner {
+    EventSigner::load(SEED_HEX).unwrap()
+}
+
+/// function to create a token creation event with fractional seconds and float fields, the parts most easily mangled.
+fn create_test_event() -> TokenCreatedEvent {
+    TokenCreatedEvent {
+        event_type: "tokenCreated".to_string(),
+        timestamp: Utc.timestamp_opt(1_705_314_645, 123_456_789).unwrap(),
+        sequence: 42,
+        transaction_signature: "test_sig_123456789".to_string(),
+        slot: 280_000_000,
+        program_id: "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P".to_string(),
+        token: TokenDetails {
+            mint_address: "test_mint_ABC123def456".to_string(),
+            name: "Moon Cat".to_string(),
+            symbol: "MCAT".to_string(),
+            uri: "https://test.example.com/metadata.json".to_string(),
+            creator: "creator_A".to_string(),
+            supply: Some(1_000_000_000_000_000),
+            decimals: Some(6),
+            mint_authority: None,
+            freeze_authority: None,
+            authorities_standard: Some(true),
+            sanitized: false,
+            uri_valid: true,
+            raw: None,
+        },
+        pump_data: PumpFunData {
+            bonding_curve: "test_curve_GHI789jkl012".to_string(),
+            virtual_sol_reserves: Some(30_000_000_000),
+            virtual_token_reserves: Some(1_073_000_000_000_000),
+            real_sol_reserves: Some(0),
+            real_token_reserves: Some(793_100_000_000_000),
+            complete: false,
+            price_sol: Some(0.000000028),
+            market_cap_sol: Some(27.958993476234855),
+            price_usd: Some(0.0000041),
+            market_cap_usd: None,
+        },
+        metadata: None,
+        initial_buy: None,
+        same_tx_buyers: 0,
+        same_tx_buy_sol: 0,
+        tx_fee_lamports: 5_000,
+        priority_fee_lamports: 0,
+        compute_units: None,
+        creator_stats: None,
+        creator_label: None,
+        creator_tags: vec!["dev".to_string()],
+        similar_recent_count: None,
+        processing_latency_ms: None,
+        chain_latency_ms: None,
+        stale: false,
+        signer_pubkey: None,
+        signature: None,
+        raw_transaction: None,
+    }
+}
+
+/// the event as a consumer receives it: serialized, then parsed again.
+fn received(event: &TokenCreatedEvent) -> Value {
+    serde_json::from_str(&serde_json::to_string(event).unwrap()).unwrap()
+}
+
+#[test]
+fn test_signed_event_verifies_after_the_wire() {
+    let signer = signer();
+    let mut event = create_test_event();
+    signer.sign(&mut event);
+
+    assert_eq!(event.signer_pubkey, Some(signer.pubkey().to_string()));
+    assert!(event.signature.is_some());
+    assert_eq!(verify_event(&received(&event), &signer.pubkey()), Ok(()));
+}
+
+#[test]
+fn test_modified_field_fails_verification() {
+    let signer = signer();
+    let mut event = create_test_event();
+    signer.sign(&mut event);
+
+    let mut tampered = received(&event);
+    tampered["token"]["name"] = Value::from("Moon Kat");
+    let message = verify_event(&tampered, &signer.pubkey()).unwrap_err();
+    assert!(message.contains("does not match"), "{}", message);
+
+    let mut resequenced = received(&event);
+    resequenced["sequence"] = Value::from(43);
+    assert!(verify_event(&resequenced, &signer.pubkey()).is_err());
+
+    let mut added = received(&event);
+    added["creatorLabel"] = Value::from("trusted dev");
+    assert!(verify_event(&added, &signer.pubkey()).is_err());
+}
+
+#[test]
+fn test_other_signer_is_rejected() {
+    let mut event = create_test_event();
+    signer().sign(&mut event);
+
+    let message = verify_event(&received(&event), &Pubkey::new_unique()).unwrap_err();
+    assert!(message.contains("signed by"), "{}", message);
+
+    // a forger signing with their own key and claiming ours
+    let forger = EventSigner::new(Keypair::new());
+    let mut forged = create_test_event();
+    forger.sign(&mut forged);
+    let mut forged = received(&forged);
+    forged["signerPubkey"] = Value::from(signer().pubkey().to_string());
+    assert!(verify_event(&forged, &signer().pubkey()).is_err());
+
+    assert!(verify_event(&received(&create_test_event()), &signer().pubkey()).is_err());
+}
+
+#[test]
+fn test_canonical_form_ignores_key_order_and_signature() {
+    let ordered: Value = serde_json::from_str(r#"{"a":1,"b":{"c":2.5,"d":[{"f":1,"e":2}]},"signature":"x"}"#).unwrap();
+    let shuffled: Value = serde_json::from_str(r#"{"b":{"d":[{"e":2,"f":1}],"c":2.5},"a":1}"#).unwrap();
+
+    assert_eq!(canonical_bytes(&ordered), canonical_bytes(&shuffled));
+    assert_eq!(
+        String::from_utf8(canonical_bytes(&shuffled)).unwrap(),
+        r#"{"a":1,"b":{"c":2.5,"d":[{"e":2,"f":1}]}}"#
+    );
+}
+
+#[test]
+fn test_key_loading() {
+    let dir = std::env::temp_dir().join(format!("pump_fun_monitor_signing_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("keypair.json");
+    let keypair = keypair_from_seed(&[1; 32]).unwrap();
+    std::fs::write(&path, serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap()).unwrap();
+
+    let from_file = EventSigner::load(path.to_str().unwrap()).unwrap();
+    assert_eq!(from_file.pubkey(), keypair.pubkey());
+    assert_eq!(EventSigner::load(&format!(" {} ", SEED_HEX)).unwrap().pubkey(), signer().pubkey());
+
+    std::fs::write(&path, "not a keypair").unwrap();
+    assert!(EventSigner::load(path.to_str().unwrap()).unwrap_err().contains("not a Solana keypair file"));
+    assert!(EventSigner::load("0102").unwrap_err().contains("32-byte seed in hex"));
+    assert!(!format!("{:?}", signer()).contains(SEED_HEX));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Times signing and verifying 10k events, to check signing stays negligible at hundreds of events per second.
+#[test]
+#[ignore]
+fn test_signing_benchmark() {
+    const EVENTS: u32 = 10_000;
+    let signer = signer();
+    let event = create_test_event();
+
+    let started = std::time::Instant::now();
+    let mut signed = Vec::with_capacity(EVENTS as usize);
+    for sequence in 0..EVENTS {
+        let mut event = event.clone();
+        event.sequence = sequence as u64;
+        signer.sign(&mut event);
+        signed.push(event);
+    }
+    let signing = started.elapsed();
+
+    let received: Vec<Value> = signed.iter().map(received).collect();
+    let started = std::time::Instant::now();
+    assert!(received.iter().all(|event| verify_event(event, &signer.pubkey()).is_ok()));
+    let verifying = started.elapsed();
+
+    println!(
+        "per event: signing {:?}, verifying {:?} (averaged over {} events)",
+        signing / EVENTS,
+        verifying / EVENTS,
+        EVENTS
+    );
+}
@@ -0,0 +1,324 @@
+//! # Simulation
+//!
+//! Synthetic token creation events for load-testing dashboards and downstream consumers without a Solana connection.
+//! When `SIMULATION_MODE` is on, `Simulator` replaces `SolanaRpcMonitor` and pushes generated events into the same
+//! dispatcher, so the WebSocket server, REST API and sinks behave exactly as they would on mainnet.
+
+use crate::config::DEFAULT_PUMP_FUN_PROGRAM_ID;
+use crate::dispatch::EventDispatcher;
+use crate::data_models::{BondingCurveAccountData, InitialBuy, PumpFunData, TokenCreatedEvent, TokenDetails};
+use crate::rpc_client::{token_price_in_sol, ConnectionStatus, EventSequencer, INITIAL_REAL_TOKEN_RESERVES};
+use crate::signing::EventSigner;
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::{debug, info};
+
+/// pump.fun mints have a fixed supply of one billion tokens with 6 decimals.
+const TOKEN_SUPPLY: u64 = 1_000_000_000_000_000;
+const TOKEN_DECIMALS: u8 = 6;
+
+/// reserves of a freshly created curve, before the creator's initial buy.
+const INITIAL_VIRTUAL_SOL_RESERVES: u64 = 30_000_000_000;
+const INITIAL_VIRTUAL_TOKEN_RESERVES: u64 = 1_073_000_000_000_000;
+
+/// slot of the first generated event, around mainnet's slot height at the time of writing.
+const FIRST_SLOT: u64 = 280_000_000;
+
+/// largest initial buy generated, in lamports.
+const MAX_CREATOR_BUY_LAMPORTS: u64 = 5_000_000_000;
+
+/// fee of a creation transaction with one signature and no priority fee, and the largest priority fee generated.
+const BASE_FEE_LAMPORTS: u64 = 5_000;
+const MAX_PRIORITY_FEE_LAMPORTS: u64 = 2_000_000;
+
+const NAME_ADJECTIVES: &[&str] = &[
+    "Based", "Baby", "Super", "Tiny", "Golden", "Moon", "Turbo", "Sleepy", "Angry", "Cosmic", "Frozen", "Mega",
+    "Lucky", "Degen", "Pixel", "Giga",
+];
+const NAME_NOUNS: &[&str] = &[
+    "Cat", "Dog", "Frog", "Pepe", "Wizard", "Hamster", "Penguin", "Rocket", "Shiba", "Goat", "Whale", "Banana",
+    "Monkey", "Otter", "Duck", "Bonk",
+];
+
+/// settings for the synthetic event generator.
+///
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    /// steady event rate
+    pub events_per_sec: f64,
+    /// seed for the event generator; a random seed is chosen (and logged) when unset
+    pub seed: Option<u64>,
+    pub burst: Option<BurstConfig>,
+}
+
+/// periodic burst on top of the steady rate, e.g. 50 events within one second every minute.
+///
+#[derive(Debug, Clone)]
+pub struct BurstConfig {
+    pub events: u32,
+    pub interval: Duration,
+}
+
+/// Produces plausible `TokenCreatedEvent`s from a seeded RNG; the same seed always yields the same events.
+///
+pub struct EventGenerator {
+    rng: StdRng,
+    /// slot of the previous event; events advance through slots like a live chain
+    slot: u64,
+}
+
+impl EventGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            slot: FIRST_SLOT,
+        }
+    }
+
+    /// the next synthetic event, timestamped now.
+    pub fn next_event(&mut self) -> TokenCreatedEvent {
+        let adjective = NAME_ADJECTIVES[self.rng.gen_range(0..NAME_ADJECTIVES.len())];
+        let noun = NAME_NOUNS[self.rng.gen_range(0..NAME_NOUNS.len())];
+        let name = format!("{} {}", adjective, noun);
+        let symbol = if self.rng.gen_bool(0.5) {
+            noun.to_uppercase()
+        } else {
+            format!("{}{}", &adjective[..1], noun).to_uppercase()
+        };
+
+        // several creations often land in the same slot
+        self.slot += self.rng.gen_range(0..=2);
+        let curve = self.bonding_curve();
+        let price_sol = token_price_in_sol(&curve, TOKEN_DECIMALS);
+        let market_cap_sol = price_sol * TOKEN_SUPPLY as f64 / 10f64.powi(TOKEN_DECIMALS as i32);
+        // most launches pay no priority fee, bots outbid each other
+        let priority_fee_lamports = if self.rng.gen_bool(0.6) {
+            0
+        } else {
+            self.rng.gen_range(1_000..=MAX_PRIORITY_FEE_LAMPORTS)
+        };
+
+        TokenCreatedEvent {
+            event_type: "tokenCreated".to_string(),
+            timestamp: chrono::Utc::now(),
+            sequence: 0,
+            transaction_signature: self.signature().to_string(),
+            slot: self.slot,
+            program_id: DEFAULT_PUMP_FUN_PROGRAM_ID.to_string(),
+            token: TokenDetails {
+                mint_address: self.pubkey().to_string(),
+                name,
+                symbol,
+                uri: format!("https://ipfs.io/ipfs/Qm{}", self.base58(44)),
+                creator: self.pubkey().to_string(),
+                supply: Some(TOKEN_SUPPLY),
+                decimals: Some(TOKEN_DECIMALS),
+                mint_authority: None,
+                freeze_authority: None,
+                authorities_standard: Some(true),
+                sanitized: false,
+                uri_valid: true,
+                raw: None,
+            },
+            pump_data: PumpFunData {
+                bonding_curve: self.pubkey().to_string(),
+                virtual_sol_reserves: Some(curve.virtual_sol_reserves),
+                virtual_token_reserves: Some(curve.virtual_token_reserves),
+                real_sol_reserves: Some(curve.real_sol_reserves),
+                real_token_reserves: Some(curve.real_token_reserves),
+                complete: false,
+                price_sol: Some(price_sol),
+                market_cap_sol: Some(market_cap_sol),
+                price_usd: None,
+                market_cap_usd: None,
+            },
+            metadata: None,
+            initial_buy: (curve.real_sol_reserves > 0).then(|| InitialBuy {
+                sol_amount: curve.real_sol_reserves,
+                token_amount: INITIAL_REAL_TOKEN_RESERVES - curve.real_token_reserves,
+            }),
+            same_tx_buyers: u32::from(curve.real_sol_reserves > 0),
+            same_tx_buy_sol: curve.real_sol_reserves,
+            tx_fee_lamports: BASE_FEE_LAMPORTS + priority_fee_lamports,
+            priority_fee_lamports,
+            compute_units: Some(self.rng.gen_range(100_000..=150_000)),
+            creator_stats: None,
+            creator_label: None,
+            creator_tags: Vec::new(),
+            similar_recent_count: None,
+            processing_latency_ms: None,
+            chain_latency_ms: None,
+            stale: false,
+            signer_pubkey: None,
+            signature: None,
+            raw_transaction: None,
+        }
+    }
+
+    /// curve state after a random initial buy by the creator, following pump.fun's constant product.
+    fn bonding_curve(&mut self) -> BondingCurveAccountData {
+        // most creators buy little or nothing; a few buy a lot
+        let buy = if self.rng.gen_bool(0.3) {
+            0
+        } else {
+            (self.rng.gen::<f64>().powi(3) * MAX_CREATOR_BUY_LAMPORTS as f64) as u64
+        };
+        let virtual_sol_reserves = INITIAL_VIRTUAL_SOL_RESERVES + buy;
+        let k = INITIAL_VIRTUAL_SOL_RESERVES as u128 * INITIAL_VIRTUAL_TOKEN_RESERVES as u128;
+        let virtual_token_reserves = (k / virtual_sol_reserves as u128) as u64;
+        let tokens_bought = INITIAL_VIRTUAL_TOKEN_RESERVES - virtual_token_reserves;
+
+        BondingCurveAccountData {
+            virtual_token_reserves,
+            virtual_sol_reserves,
+            real_token_reserves: INITIAL_REAL_TOKEN_RESERVES - tokens_bought,
+            real_sol_reserves: buy,
+            token_total_supply: TOKEN_SUPPLY,
+            complete: false,
+        }
+    }
+
+    fn signature(&mut self) -> Signature {
+        let mut bytes = [0u8; 64];
+        self.rng.fill_bytes(&mut bytes);
+        Signature::from(bytes)
+    }
+
+    fn pubkey(&mut self) -> Pubkey {
+        Pubkey::new_from_array(self.rng.gen())
+    }
+
+    fn base58(&mut self, len: usize) -> String {
+        const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+        (0..len)
+            .map(|_| ALPHABET[self.rng.gen_range(0..ALPHABET.len())] as char)
+            .collect()
+    }
+}
+
+/// Emits generated events at the configured rate in place of the Solana monitor.
+///
+pub struct Simulator {
+    config: SimulationConfig,
+    event_sender: EventSequencer,
+    status: watch::Sender<ConnectionStatus>,
+}
+
+impl Simulator {
+    pub fn new(config: SimulationConfig, event_sender: EventDispatcher) -> Self {
+        Self {
+            config,
+            event_sender: EventSequencer::new(event_sender),
+            status: watch::channel(ConnectionStatus::default()).0,
+        }
+    }
+
+    /// signs the simulated events like live ones, so consumers' verification can be load tested too.
+    pub fn with_signer(mut self, signer: Option<Arc<EventSigner>>) -> Self {
+        self.event_sender = self.event_sender.with_signer(signer);
+        self
+    }
+
+    /// reports connected while running, so the health endpoints treat the simulation like a live subscription.
+    pub fn connection_status(&self) -> watch::Receiver<ConnectionStatus> {
+        self.status.subscribe()
+    }
+
+    /// Generates events forever.
+    ///
+    pub async fn run(&self) {
+        let seed = self.config.seed.unwrap_or_else(rand::random);
+        info!(
+            seed,
+            "Simulating {} token creations per second{}",
+            self.config.events_per_sec,
+            self.config
+                .burst
+                .as_ref()
+                .map(|burst| format!(", plus {} within one second every {:?}", burst.events, burst.interval))
+                .unwrap_or_default()
+        );
+
+        let mut generator = EventGenerator::new(seed);
+        let mut schedule = Schedule::new(&self.config, tokio::time::Instant::now());
+        self.status.send_replace(ConnectionStatus {
+            connected: true,
+            last_message_at: Some(Instant::now()),
+            last_notification_at: None,
+            provider_limits: Default::default(),
+            parser: Default::default(),
+        });
+
+        loop {
+            tokio::time::sleep_until(schedule.advance()).await;
+            self.status.send_modify(|status| {
+                let now = Instant::now();
+                status.last_message_at = Some(now);
+                status.last_notification_at = Some(now);
+            });
+            let event = generator.next_event();
+            debug!(mint = %event.token.mint_address, "Simulated token creation");
+            // nobody listening is normal in a load test that hasn't connected yet
+            self.event_sender.send(event).await;
+        }
+    }
+}
+
+/// when the next event is due, merging the steady rate with the burst windows.
+///
+struct Schedule {
+    steady_period: Duration,
+    next_steady: tokio::time::Instant,
+    burst: Option<BurstWindow>,
+}
+
+struct BurstWindow {
+    /// spacing of events within the one-second window
+    spacing: Duration,
+    events: u32,
+    interval: Duration,
+    window_start: tokio::time::Instant,
+    emitted: u32,
+}
+
+impl Schedule {
+    fn new(config: &SimulationConfig, start: tokio::time::Instant) -> Self {
+        Self {
+            steady_period: Duration::from_secs_f64(1.0 / config.events_per_sec),
+            next_steady: start,
+            // the first burst comes one interval in, after the steady rate has settled
+            burst: config.burst.as_ref().map(|burst| BurstWindow {
+                spacing: Duration::from_secs(1) / burst.events,
+                events: burst.events,
+                interval: burst.interval,
+                window_start: start + burst.interval,
+                emitted: 0,
+            }),
+        }
+    }
+
+    /// the instant the next event is due, advancing past it.
+    fn advance(&mut self) -> tokio::time::Instant {
+        if let Some(burst) = &mut self.burst {
+            let burst_at = burst.window_start + burst.spacing * burst.emitted;
+            if burst_at < self.next_steady {
+                burst.emitted += 1;
+                if burst.emitted == burst.events {
+                    burst.window_start += burst.interval;
+                    burst.emitted = 0;
+                }
+                return burst_at;
+            }
+        }
+        let steady_at = self.next_steady;
+        self.next_steady += self.steady_period;
+        steady_at
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,143 @@
+//! Unit tests for synthetic event generation and scheduling.
+
+
+use super::*;
+use std::str::FromStr;
+use tokio::sync::broadcast;
+
+/// function to serialize an event without its wall-clock timestamp and broadcast sequence.
+fn without_timestamp(event: &TokenCreatedEvent) -> serde_json::Value {
+    let mut value = serde_json::to_value(event).unwrap();
+    let object = value.as_object_mut().unwrap();
+    object.remove("timestamp");
+    object.remove("sequence");
+    value
+}
+
+#[test]
+fn test_same_seed_generates_same_events() {
+    let mut first = EventGenerator::new(42);
+    let mut second = EventGenerator::new(42);
+    for _ in 0..20 {
+        assert_eq!(without_timestamp(&first.next_event()), without_timestamp(&second.next_event()));
+    }
+
+    assert_ne!(
+        without_timestamp(&EventGenerator::new(42).next_event()),
+        without_timestamp(&EventGenerator::new(43).next_event())
+    );
+}
+
+#[test]
+fn test_generated_events_are_plausible() {
+    let mut generator = EventGenerator::new(7);
+    let mut previous_slot = FIRST_SLOT;
+    for _ in 0..200 {
+        let event = generator.next_event();
+
+        assert_eq!(event.event_type, "tokenCreated");
+        assert!(Signature::from_str(&event.transaction_signature).is_ok());
+        for key in [&event.token.mint_address, &event.token.creator, &event.pump_data.bonding_curve] {
+            assert!(Pubkey::from_str(key).is_ok(), "{}", key);
+        }
+        assert!(event.slot >= previous_slot && event.slot <= previous_slot + 2);
+        previous_slot = event.slot;
+        assert_eq!(event.token.symbol, event.token.symbol.to_uppercase());
+        assert!(crate::rpc_client::sanitize::is_well_formed_uri(&event.token.uri), "{}", event.token.uri);
+
+        let curve = &event.pump_data;
+        let real_sol_reserves = curve.real_sol_reserves.unwrap();
+        let real_token_reserves = curve.real_token_reserves.unwrap();
+        assert!(real_sol_reserves <= MAX_CREATOR_BUY_LAMPORTS);
+        assert_eq!(curve.virtual_sol_reserves, Some(INITIAL_VIRTUAL_SOL_RESERVES + real_sol_reserves));
+        assert!(real_token_reserves <= INITIAL_REAL_TOKEN_RESERVES);
+        match event.initial_buy {
+            Some(buy) => {
+                assert_eq!(buy.sol_amount, real_sol_reserves);
+                assert_eq!(buy.token_amount, INITIAL_REAL_TOKEN_RESERVES - real_token_reserves);
+                assert_eq!(event.same_tx_buyers, 1);
+            }
+            None => assert_eq!(real_sol_reserves, 0),
+        }
+        assert_eq!(event.same_tx_buy_sol, real_sol_reserves);
+        assert_eq!(event.tx_fee_lamports, BASE_FEE_LAMPORTS + event.priority_fee_lamports);
+        assert!(event.priority_fee_lamports <= MAX_PRIORITY_FEE_LAMPORTS);
+        assert!(curve.price_sol.unwrap() > 0.0);
+        // a fresh curve is worth about 28 SOL and the largest initial buy lifts that to about 38
+        let market_cap_sol = curve.market_cap_sol.unwrap();
+        assert!(market_cap_sol > 27.0 && market_cap_sol < 40.0, "{}", market_cap_sol);
+    }
+}
+
+/// function to count the events a schedule emits within the first `seconds`.
+fn events_within(config: &SimulationConfig, seconds: u64) -> usize {
+    let start = tokio::time::Instant::now();
+    let end = start + Duration::from_secs(seconds);
+    let mut schedule = Schedule::new(config, start);
+    std::iter::from_fn(|| Some(schedule.advance()))
+        .take_while(|&at| at < end)
+        .count()
+}
+
+#[test]
+fn test_schedule_steady_rate() {
+    let config = SimulationConfig {
+        events_per_sec: 2.0,
+        seed: None,
+        burst: None,
+    };
+    assert_eq!(events_within(&config, 10), 20);
+}
+
+#[test]
+fn test_schedule_adds_bursts() {
+    let config = SimulationConfig {
+        events_per_sec: 2.0,
+        seed: None,
+        burst: Some(BurstConfig {
+            events: 50,
+            interval: Duration::from_secs(4),
+        }),
+    };
+    // bursts at 4s and 8s, each within one second
+    assert_eq!(events_within(&config, 10), 20 + 2 * 50);
+    assert_eq!(events_within(&config, 3), 6);
+}
+
+#[test]
+fn test_schedule_is_ordered() {
+    let config = SimulationConfig {
+        events_per_sec: 3.0,
+        seed: None,
+        burst: Some(BurstConfig {
+            events: 7,
+            interval: Duration::from_secs(2),
+        }),
+    };
+    let mut schedule = Schedule::new(&config, tokio::time::Instant::now());
+    let times: Vec<_> = (0..100).map(|_| schedule.advance()).collect();
+    assert!(times.windows(2).all(|pair| pair[0] <= pair[1]));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_simulator_broadcasts_events() {
+    let (tx, mut rx) = broadcast::channel(16);
+    let simulator = Simulator::new(
+        SimulationConfig {
+            events_per_sec: 5.0,
+            seed: Some(1),
+            burst: None,
+        },
+        EventDispatcher::new(tx),
+    );
+    let status = simulator.connection_status();
+    tokio::spawn(async move { simulator.run().await });
+
+    let mut expected = EventGenerator::new(1);
+    for sequence in 1..=3 {
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.sequence, sequence);
+        assert_eq!(without_timestamp(&event), without_timestamp(&expected.next_event()));
+    }
+    assert!(status.borrow().connected);
+}
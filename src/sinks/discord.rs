@@ -0,0 +1,160 @@
+//! Discord webhook sink.
+//!
+//! Each configured route pairs a webhook URL with a `FilterCriteria`; matching events are posted as a rich embed. Every route is registered as a sink of its own, so a rate-limited or broken webhook only delays itself. Discord's 429 responses are honoured by waiting out `Retry-After` before retrying the same message; that is the only retry, other failures give the message up.
+
+use super::{route_filters, DiscordConfig, EventSink, RetryPolicy, SinkManager, SinkOptions};
+use crate::data_models::TokenCreatedEvent;
+use crate::error::{MonitorError, Result};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// attempts per message before giving up on a rate-limited webhook.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// used when a 429 response carries no usable `Retry-After`.
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// embed accent colour (pump.fun green).
+const EMBED_COLOR: u32 = 0x4ADE80;
+
+/// Discord rejects embed titles longer than this.
+const MAX_TITLE_CHARS: usize = 256;
+
+/// one webhook route as a sink of its own.
+struct DiscordWebhookSink {
+    name: String,
+    index: usize,
+    client: reqwest::Client,
+    webhook_url: String,
+}
+
+#[async_trait]
+impl EventSink for DiscordWebhookSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn deliver(&self, event: &TokenCreatedEvent) -> Result<()> {
+        post_embed(self.index, &self.client, &self.webhook_url, &webhook_payload(event))
+            .await
+            .map_err(MonitorError::Sink)
+    }
+}
+
+/// Registers one sink per configured webhook route.
+pub fn register(manager: &mut SinkManager, config: &DiscordConfig) {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_default();
+    for (index, route) in config.routes.iter().enumerate() {
+        let sink = DiscordWebhookSink {
+            name: sink_name(index),
+            index,
+            client: client.clone(),
+            webhook_url: route.webhook_url.clone(),
+        };
+        // `post_embed` already waits out rate limits; retrying on top of it would repeat that wait for each attempt
+        let options = SinkOptions {
+            queue_capacity: config.queue_capacity,
+            durable: false,
+            retry: RetryPolicy::NONE,
+            filters: route_filters(&config.filter, &route.filter),
+            stale: config.stale,
+        };
+        manager.register(sink, options);
+    }
+    info!("Discord sink posting to {} webhook route(s)", config.routes.len());
+}
+
+/// the sink name of route number `index`, also the key of its filters on reload.
+pub(super) fn sink_name(index: usize) -> String {
+    format!("discord:{}", index)
+}
+
+async fn post_embed(
+    index: usize,
+    client: &reqwest::Client,
+    webhook_url: &str,
+    payload: &Value,
+) -> std::result::Result<(), String> {
+    for _ in 0..MAX_ATTEMPTS {
+        // the webhook URL embeds its secret token, so errors name the route instead
+        let response = client
+            .post(webhook_url)
+            .json(payload)
+            .send()
+            .await
+            .map_err(|e| format!("route {}: {}", index, e.without_url()))?;
+
+        match response.status() {
+            status if status.is_success() => return Ok(()),
+            StatusCode::TOO_MANY_REQUESTS => {
+                let retry_header = response.headers().get(reqwest::header::RETRY_AFTER);
+                let delay = parse_retry_after(retry_header.and_then(|value| value.to_str().ok()))
+                    .unwrap_or(DEFAULT_RETRY_DELAY);
+                warn!("Discord route {} rate limited, retrying in {}ms", index, delay.as_millis());
+                tokio::time::sleep(delay).await;
+            }
+            status => return Err(format!("route {}: HTTP {}", index, status)),
+        }
+    }
+    Err(format!("route {}: still rate limited after {} attempts", index, MAX_ATTEMPTS))
+}
+
+/// reads a `Retry-After` value in (possibly fractional) seconds, as Discord sends it.
+pub fn parse_retry_after(value: Option<&str>) -> Option<Duration> {
+    let seconds = value?.trim().parse::<f64>().ok()?;
+    (seconds.is_finite() && seconds >= 0.0).then(|| Duration::from_secs_f64(seconds))
+}
+
+/// builds the webhook body: one embed with the token's name, symbol, mint, creator and market cap.
+pub fn webhook_payload(event: &TokenCreatedEvent) -> Value {
+    let token = &event.token;
+    let coin_url = format!("https://pump.fun/coin/{}", token.mint_address);
+    let title = if token.symbol.is_empty() {
+        token.name.clone()
+    } else {
+        format!("{} ({})", token.name, token.symbol)
+    };
+    let title: String = title.chars().take(MAX_TITLE_CHARS).collect();
+
+    // absent at `DETAIL_LEVEL=minimal`
+    let mut market_cap = match event.pump_data.market_cap_sol {
+        Some(market_cap_sol) => format!("{:.2} SOL", market_cap_sol),
+        None => "unknown".to_string(),
+    };
+    if let Some(market_cap_usd) = event.pump_data.market_cap_usd {
+        market_cap.push_str(&format!(" (${:.0})", market_cap_usd));
+    }
+
+    // discord rejects the whole message over an empty field value, and symbols may be empty
+    let mut fields = Vec::new();
+    if !token.symbol.is_empty() {
+        fields.push(json!({ "name": "Symbol", "value": token.symbol, "inline": true }));
+    }
+    fields.extend([
+        json!({ "name": "Market cap", "value": market_cap, "inline": true }),
+        json!({ "name": "Mint", "value": format!("[{}]({})", token.mint_address, coin_url), "inline": false }),
+        json!({ "name": "Creator", "value": token.creator, "inline": false }),
+    ]);
+
+    let mut payload = json!({
+        "embeds": [{
+            "title": title,
+            "url": coin_url,
+            "color": EMBED_COLOR,
+            "fields": fields,
+            "timestamp": event.timestamp.to_rfc3339(),
+        }]
+    });
+
+    // discord only renders images it can fetch over http(s), so `ipfs://` images go through the resolved link
+    if let Some(image) = event.metadata.as_ref().and_then(|metadata| metadata.image_url.as_deref()) {
+        payload["embeds"][0]["thumbnail"] = json!({ "url": image });
+    }
+    payload
+}
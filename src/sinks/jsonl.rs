@@ -0,0 +1,399 @@
+//! JSON lines archive sink.
+//!
+//! Appends every event as one JSON object per line to a local file, the simplest durable archive there is. Files rotate by event time (`events-2024-05-01.jsonl` for daily rotation) and optionally by size (`events-2024-05-01.1.jsonl`, `.2`, ... once a file would grow past `max_bytes`). A restarted monitor resumes appending to the newest file of the current period rather than starting a new one.
+//!
+//! File I/O never runs on the async runtime: the sink hands each line to a dedicated writer thread and waits for its result, so a slow disk only backs up this sink's own bounded queue. How often the file is fsynced is a trade-off between durability and throughput, chosen with `FsyncPolicy`.
+//!
+//! `convert_jsonl_to_csv` turns an archive file back into a spreadsheet-friendly CSV, and is exposed as `--convert-jsonl-to-csv`.
+
+use super::{EventSink, EventsLogConfig, RetryPolicy, SinkManager, SinkOptions};
+use crate::data_models::{event_payload, EventFormat, EventPayload, TokenCreatedEvent};
+use crate::error::{MonitorError, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+/// how often `FsyncPolicy::Interval` flushes written lines to disk.
+const FSYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+/// used when `EVENTS_LOG_PATH` has no extension.
+const DEFAULT_EXTENSION: &str = "jsonl";
+
+/// CSV columns exported by `convert_jsonl_to_csv`, with the JSON pointer each is read from.
+const CSV_COLUMNS: &[(&str, &str)] = &[
+    ("timestamp", "/timestamp"),
+    ("sequence", "/sequence"),
+    ("slot", "/slot"),
+    ("signature", "/transactionSignature"),
+    ("mint", "/token/mintAddress"),
+    ("name", "/token/name"),
+    ("symbol", "/token/symbol"),
+    ("creator", "/token/creator"),
+    ("uri", "/token/uri"),
+    ("price_sol", "/pumpData/priceSol"),
+    ("market_cap_sol", "/pumpData/marketCapSol"),
+    ("market_cap_usd", "/pumpData/marketCapUsd"),
+    ("initial_buy_lamports", "/initialBuy/solAmount"),
+    ("same_tx_buyers", "/sameTxBuyers"),
+    ("creator_tokens_seen", "/creatorStats/tokensCreatedSeen"),
+    ("creator_label", "/creatorLabel"),
+    ("similar_recent_count", "/similarRecentCount"),
+];
+
+/// how archive files are split over time.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogRotation {
+    /// `events-2024-05-01.jsonl`
+    Daily,
+    /// `events-2024-05-01-13.jsonl`
+    Hourly,
+    /// `events.jsonl`, split by size only
+    Never,
+}
+
+impl LogRotation {
+    /// the part of the file name that changes with `at`; empty when files never rotate by time.
+    fn period(self, at: DateTime<Utc>) -> String {
+        match self {
+            Self::Daily => at.format("%Y-%m-%d").to_string(),
+            Self::Hourly => at.format("%Y-%m-%d-%H").to_string(),
+            Self::Never => String::new(),
+        }
+    }
+}
+
+impl FromStr for LogRotation {
+    type Err = MonitorError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "daily" => Ok(Self::Daily),
+            "hourly" => Ok(Self::Hourly),
+            "never" => Ok(Self::Never),
+            other => Err(MonitorError::Config(format!(
+                "EVENTS_LOG_ROTATION '{}' is not one of: daily, hourly, never",
+                other
+            ))),
+        }
+    }
+}
+
+/// when written lines are forced to disk.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsyncPolicy {
+    /// after every event; nothing acknowledged is lost on a crash, at the cost of one fsync per event
+    Always,
+    /// at most once a second while events arrive; a crash loses at most the last second
+    Interval,
+    /// left to the operating system
+    Never,
+}
+
+impl FromStr for FsyncPolicy {
+    type Err = MonitorError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "always" => Ok(Self::Always),
+            "interval" => Ok(Self::Interval),
+            "never" => Ok(Self::Never),
+            other => Err(MonitorError::Config(format!(
+                "EVENTS_LOG_FSYNC '{}' is not one of: always, interval, never",
+                other
+            ))),
+        }
+    }
+}
+
+/// one line for the writer thread, and where to report how writing it went.
+struct Append {
+    line: Vec<u8>,
+    at: DateTime<Utc>,
+    done: oneshot::Sender<io::Result<()>>,
+}
+
+struct JsonlFileSink {
+    path: PathBuf,
+    writer: SyncSender<Append>,
+    format: EventFormat,
+}
+
+#[async_trait]
+impl EventSink for JsonlFileSink {
+    fn name(&self) -> &str {
+        "events_log"
+    }
+
+    async fn deliver(&self, event: &TokenCreatedEvent) -> Result<()> {
+        let mut line = serde_json::to_vec(&event.formatted(self.format))?;
+        line.push(b'\n');
+        let (done, result) = oneshot::channel();
+        let stopped = || MonitorError::Sink("events log writer has stopped".to_string());
+        // one delivery is in flight at a time, so the writer's single slot is always free
+        self.writer
+            .try_send(Append { line, at: event.timestamp, done })
+            .map_err(|_| stopped())?;
+        result
+            .await
+            .map_err(|_| stopped())?
+            .map_err(|e| MonitorError::Sink(format!("{}: {}", self.path.display(), e)))
+    }
+}
+
+/// Registers the archive sink and starts its writer thread.
+pub fn register(manager: &mut SinkManager, config: &EventsLogConfig, format: EventFormat) {
+    let (writer, requests) = mpsc::sync_channel(1);
+    let log = RotatingLog::new(&config.path, config.rotation, config.max_bytes, config.fsync);
+    let spawned = std::thread::Builder::new()
+        .name("events-log-writer".to_string())
+        .spawn(move || run_writer(log, requests));
+    if let Err(e) = spawned {
+        warn!("Events log disabled, its writer thread could not start: {}", e);
+        return;
+    }
+
+    let options = SinkOptions {
+        queue_capacity: config.queue_capacity,
+        durable: true,
+        retry: RetryPolicy::default(),
+        filters: config.filter.iter().cloned().collect(),
+        stale: config.stale,
+    };
+    manager.register(JsonlFileSink { path: config.path.clone(), writer, format }, options);
+    info!("Events log appending to {} ({:?} rotation)", config.path.display(), config.rotation);
+}
+
+/// writes lines until the sink is dropped, syncing in between as the policy asks.
+fn run_writer(mut log: RotatingLog, requests: mpsc::Receiver<Append>) {
+    loop {
+        match requests.recv_timeout(FSYNC_INTERVAL) {
+            Ok(append) => {
+                let result = log.append(&append.line, append.at);
+                let _ = append.done.send(result);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if let Err(e) = log.sync_if_due(Instant::now()) {
+            warn!("Failed to sync the events log: {}", e);
+        }
+    }
+    if let Err(e) = log.sync() {
+        warn!("Failed to sync the events log on shutdown: {}", e);
+    }
+}
+
+/// the archive file currently appended to.
+struct OpenLog {
+    file: File,
+    period: String,
+    part: u32,
+    len: u64,
+}
+
+/// Append-only JSON lines files, rotated by event time and size.
+///
+/// Files are opened on first use, and reopened after a failed write, so a missing directory or a full disk only fails
+/// the events written while the problem lasts.
+pub(crate) struct RotatingLog {
+    dir: PathBuf,
+    stem: String,
+    extension: String,
+    rotation: LogRotation,
+    max_bytes: Option<u64>,
+    fsync: FsyncPolicy,
+    current: Option<OpenLog>,
+    /// written since the last sync
+    dirty: bool,
+    last_sync: Instant,
+}
+
+impl RotatingLog {
+    pub(crate) fn new(path: &Path, rotation: LogRotation, max_bytes: Option<u64>, fsync: FsyncPolicy) -> Self {
+        Self {
+            dir: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            stem: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "events".to_string()),
+            extension: path
+                .extension()
+                .map(|extension| extension.to_string_lossy().into_owned())
+                .unwrap_or_else(|| DEFAULT_EXTENSION.to_string()),
+            rotation,
+            max_bytes,
+            fsync,
+            current: None,
+            dirty: false,
+            last_sync: Instant::now(),
+        }
+    }
+
+    /// file for `period`, numbered from 0; part 0 carries no number.
+    pub(crate) fn file_path(&self, period: &str, part: u32) -> PathBuf {
+        let mut name = self.stem.clone();
+        if !period.is_empty() {
+            name.push('-');
+            name.push_str(period);
+        }
+        if part > 0 {
+            name.push_str(&format!(".{}", part));
+        }
+        name.push('.');
+        name.push_str(&self.extension);
+        self.dir.join(name)
+    }
+
+    /// Appends one newline-terminated `line` to the file for event time `at`, rotating first when needed.
+    pub(crate) fn append(&mut self, line: &[u8], at: DateTime<Utc>) -> io::Result<()> {
+        let period = self.rotation.period(at);
+        // taken out so a failed write drops the handle and the next append reopens the file
+        let mut current = match self.current.take() {
+            Some(current) if current.period == period => current,
+            previous => {
+                if let Some(previous) = previous {
+                    previous.file.sync_data()?;
+                }
+                self.resume(&period)?
+            }
+        };
+        let line_len = line.len() as u64;
+        if self.max_bytes.is_some_and(|max_bytes| current.len > 0 && current.len + line_len > max_bytes) {
+            current.file.sync_data()?;
+            current = self.open(&period, current.part + 1)?;
+        }
+
+        current.file.write_all(line)?;
+        current.len += line_len;
+        if self.fsync == FsyncPolicy::Always {
+            current.file.sync_data()?;
+        } else {
+            self.dirty = true;
+        }
+        self.current = Some(current);
+        Ok(())
+    }
+
+    /// syncs when the policy is `Interval`, something was written and the last sync is long enough ago.
+    fn sync_if_due(&mut self, now: Instant) -> io::Result<()> {
+        if self.fsync == FsyncPolicy::Interval && self.dirty && now.duration_since(self.last_sync) >= FSYNC_INTERVAL {
+            self.sync()?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn sync(&mut self) -> io::Result<()> {
+        if let Some(current) = &self.current {
+            current.file.sync_data()?;
+        }
+        self.dirty = false;
+        self.last_sync = Instant::now();
+        Ok(())
+    }
+
+    /// opens the newest existing part for `period`, so a restart keeps appending where the last run stopped.
+    fn resume(&self, period: &str) -> io::Result<OpenLog> {
+        let mut part = 0;
+        while self.file_path(period, part + 1).exists() {
+            part += 1;
+        }
+        self.open(period, part)
+    }
+
+    fn open(&self, period: &str, part: u32) -> io::Result<OpenLog> {
+        if !self.dir.as_os_str().is_empty() {
+            std::fs::create_dir_all(&self.dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(self.file_path(period, part))?;
+        let mut len = file.metadata()?.len();
+
+        // a crash mid-write leaves a torn last line; end it so the next event starts a line of its own
+        if len > 0 {
+            let mut last = [0u8];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                file.write_all(b"\n")?;
+                len += 1;
+            }
+        }
+        Ok(OpenLog {
+            file,
+            period: period.to_string(),
+            part,
+            len,
+        })
+    }
+}
+
+/// rows written by `convert_jsonl_to_csv`, and lines it could not read as an event.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvExport {
+    pub rows: usize,
+    pub skipped: usize,
+}
+
+/// Converts an events archive to CSV, one row per event with a fixed set of columns.
+///
+/// Lines written flat and in the envelope are both read, so an archive spanning an `EVENT_FORMAT` change converts
+/// whole. Blank lines are ignored, and lines that are not a JSON object (such as a line torn by a crash) are skipped and
+/// counted. Text cells that a spreadsheet would evaluate as a formula are prefixed with `'`, since token names and
+/// symbols are chosen by whoever launched the token.
+pub fn convert_jsonl_to_csv(input: impl BufRead, mut output: impl Write) -> io::Result<CsvExport> {
+    let header: Vec<&str> = CSV_COLUMNS.iter().map(|(name, _)| *name).collect();
+    writeln!(output, "{}", header.join(","))?;
+
+    let mut export = CsvExport { rows: 0, skipped: 0 };
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = match serde_json::from_str::<Value>(&line) {
+            Ok(event) if event.is_object() => event,
+            _ => {
+                export.skipped += 1;
+                continue;
+            }
+        };
+        let event = event_payload(&event);
+        let cells: Vec<String> = CSV_COLUMNS
+            .iter()
+            .map(|(_, pointer)| csv_cell(event.pointer(pointer)))
+            .collect();
+        writeln!(output, "{}", cells.join(","))?;
+        export.rows += 1;
+    }
+    output.flush()?;
+    Ok(export)
+}
+
+/// renders one JSON value as a quoted-when-needed CSV cell; missing values become empty cells.
+pub(crate) fn csv_cell(value: Option<&Value>) -> String {
+    let text = match value {
+        None | Some(Value::Null) => return String::new(),
+        Some(Value::String(text)) if text.starts_with(['=', '+', '-', '@', '\t', '\r']) => format!("'{}", text),
+        Some(Value::String(text)) => text.clone(),
+        Some(other) => other.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
@@ -0,0 +1,109 @@
+//! Kafka producer sink.
+//!
+//! Each event is produced as JSON keyed by its mint address, so every message about one token lands on the same partition and stays ordered; the event type travels in the `eventType` header. Retries happen inside the producer (idempotent, bounded by `message.timeout.ms`), so the sink is registered without a retry policy of its own and only deliveries that still fail are counted and logged.
+
+use super::{EventSink, KafkaConfig, RetryPolicy, SinkManager, SinkOptions, BROKER_QUEUE_CAPACITY};
+use crate::data_models::{EventFormat, EventPayload, TokenCreatedEvent};
+use crate::error::{MonitorError, Result};
+use async_trait::async_trait;
+use rdkafka::config::ClientConfig;
+use rdkafka::error::KafkaResult;
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::util::Timeout;
+use tracing::{error, info};
+
+/// header carrying `TokenCreatedEvent::event_type`.
+pub const EVENT_TYPE_HEADER: &str = "eventType";
+
+/// the parts of a Kafka message built from one event.
+///
+#[derive(Debug, PartialEq)]
+pub struct KafkaRecord {
+    pub key: String,
+    pub headers: Vec<(&'static str, String)>,
+    pub payload: String,
+}
+
+impl KafkaRecord {
+    pub fn from_event(event: &TokenCreatedEvent, format: EventFormat) -> serde_json::Result<Self> {
+        Ok(Self {
+            key: event.token.mint_address.clone(),
+            headers: vec![(EVENT_TYPE_HEADER, event.event_type.clone())],
+            payload: serde_json::to_string(&event.formatted(format))?,
+        })
+    }
+
+    fn owned_headers(&self) -> OwnedHeaders {
+        self.headers.iter().fold(OwnedHeaders::new(), |headers, (key, value)| {
+            headers.insert(Header {
+                key,
+                value: Some(value.as_str()),
+            })
+        })
+    }
+}
+
+/// produces events to one topic.
+struct KafkaSink {
+    producer: FutureProducer,
+    topic: String,
+    format: EventFormat,
+}
+
+#[async_trait]
+impl EventSink for KafkaSink {
+    fn name(&self) -> &str {
+        "kafka"
+    }
+
+    /// waits until the broker acknowledged the message, so a failed delivery is reported against its event.
+    async fn deliver(&self, event: &TokenCreatedEvent) -> Result<()> {
+        let record = KafkaRecord::from_event(event, self.format)?;
+        let message = FutureRecord::to(&self.topic)
+            .key(record.key.as_str())
+            .payload(record.payload.as_str())
+            .headers(record.owned_headers());
+        self.producer
+            .send(message, Timeout::Never)
+            .await
+            .map(|_| ())
+            .map_err(|(e, _)| MonitorError::Sink(format!("kafka: {}", e)))
+    }
+}
+
+/// Registers the Kafka sink, unless the producer can't be created.
+pub fn register(manager: &mut SinkManager, config: &KafkaConfig, format: EventFormat) {
+    let producer = match create_producer(config) {
+        Ok(producer) => producer,
+        Err(e) => {
+            error!("Failed to create Kafka producer for {}: {}", config.brokers, e);
+            return;
+        }
+    };
+    info!("Kafka sink producing to topic '{}' on {}", config.topic, config.brokers);
+
+    let sink = KafkaSink {
+        producer,
+        topic: config.topic.clone(),
+        format,
+    };
+    // the producer retries on its own until `message.timeout.ms`
+    let options = SinkOptions {
+        queue_capacity: BROKER_QUEUE_CAPACITY,
+        durable: true,
+        retry: RetryPolicy::NONE,
+        filters: config.filter.iter().cloned().collect(),
+        stale: config.stale,
+    };
+    manager.register(sink, options);
+}
+
+fn create_producer(config: &KafkaConfig) -> KafkaResult<FutureProducer> {
+    ClientConfig::new()
+        .set("bootstrap.servers", &config.brokers)
+        .set("message.timeout.ms", config.message_timeout.as_millis().to_string())
+        // idempotence implies acks=all and unlimited in-flight retries without reordering
+        .set("enable.idempotence", "true")
+        .create()
+}
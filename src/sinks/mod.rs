@@ -0,0 +1,733 @@
+//! # Event Sinks
+//!
+//! Optional destinations that forward token creation events outside the process, such as message brokers, chat webhooks, a shared PostgreSQL database and a local JSON lines archive. Every destination implements `EventSink`, and a single `SinkManager` fans the events out to all of them: each sink gets its own bounded queue, task and retry policy, so a slow or failing best-effort sink only lags itself and never blocks the other sinks, the RPC monitor or the WebSocket clients. Broker and database clients sit behind cargo features (`kafka`, `nats`, `postgres`) so the default build stays lean, and any number of sinks can be enabled at once.
+//!
+//! The persisting sinks (PostgreSQL, Kafka, NATS and the events log) are durable: while one is configured the manager
+//! reads a durable queue of the dispatcher rather than the broadcast, and waits for room in a durable sink's queue
+//! instead of dropping the event, so a busy WebSocket server or a slow database never costs a row. The chat sinks stay
+//! best effort and drop events when their own queue is full.
+//!
+//! A best-effort sink configured with `tier = 0` doesn't wait for the manager at all: the dispatcher hands it every
+//! event on the processor task, next to the WebSocket broadcast and before any durable queue, so a backlog of the
+//! PostgreSQL writer never delays a Discord alert.
+
+use crate::config::ChannelConfig;
+use crate::data_models::{EventFormat, FilterCriteria, TokenCreatedEvent};
+use crate::dispatch::{EventDispatcher, Subscription, Tier};
+use crate::error::Result;
+use crate::filtering::matches_filter;
+use crate::freshness::StalePolicy;
+use crate::metrics::Metrics;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+mod discord;
+mod jsonl;
+mod telegram;
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "nats")]
+mod nats;
+#[cfg(feature = "postgres")]
+mod postgres;
+
+pub use jsonl::{convert_jsonl_to_csv, CsvExport, FsyncPolicy, LogRotation};
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresArchive;
+
+/// settings for every sink; a sink is enabled when its section is present.
+///
+#[derive(Debug, Clone, Default)]
+pub struct SinksConfig {
+    pub kafka: Option<KafkaConfig>,
+    pub nats: Option<NatsConfig>,
+    pub discord: Option<DiscordConfig>,
+    pub telegram: Option<TelegramConfig>,
+    pub events_log: Option<EventsLogConfig>,
+    pub postgres: Option<PostgresConfig>,
+    /// flat or enveloped payloads for the sinks that publish whole events: Kafka, NATS and the events log
+    pub event_format: EventFormat,
+}
+
+impl SinksConfig {
+    /// whether a sink that must not miss events is configured.
+    pub fn has_durable(&self) -> bool {
+        self.kafka.is_some() || self.nats.is_some() || self.postgres.is_some() || self.events_log.is_some()
+    }
+
+    /// the name and queue capacity of every configured sink in the immediate tier.
+    pub fn immediate_sinks(&self) -> Vec<(String, usize)> {
+        let mut sinks = Vec::new();
+        if let Some(discord) = self.discord.as_ref().filter(|discord| discord.tier == Tier::Immediate) {
+            sinks.extend((0..discord.routes.len()).map(|index| (discord::sink_name(index), discord.queue_capacity)));
+        }
+        if let Some(telegram) = self.telegram.as_ref().filter(|telegram| telegram.tier == Tier::Immediate) {
+            let routes = telegram.routes.iter();
+            sinks.extend(routes.map(|route| (telegram::sink_name(&route.chat_id), telegram.queue_capacity)));
+        }
+        sinks
+    }
+
+    /// the filters every configured sink is registered with, keyed by the name it is registered under.
+    pub fn filters(&self) -> SinkFilters {
+        let mut filters = SinkFilters::new();
+        let sections = [
+            ("kafka", self.kafka.as_ref().map(|kafka| &kafka.filter)),
+            ("nats", self.nats.as_ref().map(|nats| &nats.filter)),
+            ("postgres", self.postgres.as_ref().map(|postgres| &postgres.filter)),
+            ("events_log", self.events_log.as_ref().map(|events_log| &events_log.filter)),
+        ];
+        for (name, filter) in sections {
+            if let Some(filter) = filter {
+                filters.insert(name.to_string(), filter.iter().cloned().collect());
+            }
+        }
+        if let Some(discord) = &self.discord {
+            for (index, route) in discord.routes.iter().enumerate() {
+                filters.insert(discord::sink_name(index), route_filters(&discord.filter, &route.filter));
+            }
+        }
+        if let Some(telegram) = &self.telegram {
+            for route in &telegram.routes {
+                filters.insert(telegram::sink_name(&route.chat_id), route_filters(&telegram.filter, &route.filter));
+            }
+        }
+        filters
+    }
+}
+
+/// a route's filters: its section's, if any, and its own.
+fn route_filters(section: &Option<FilterCriteria>, route: &FilterCriteria) -> Vec<FilterCriteria> {
+    section.iter().chain(Some(route)).cloned().collect()
+}
+
+/// Kafka producer settings, present when both `KAFKA_BROKERS` and `KAFKA_TOPIC` are set.
+///
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "kafka"), allow(dead_code))]
+pub struct KafkaConfig {
+    /// comma-separated `host:port` bootstrap servers
+    pub brokers: String,
+    pub topic: String,
+    /// how long the producer keeps retrying a message before reporting it as failed
+    pub message_timeout: Duration,
+    pub filter: Option<FilterCriteria>,
+    /// what the sink does with events marked stale
+    pub stale: StalePolicy,
+}
+
+/// NATS publisher settings, present when `NATS_URL` is set.
+///
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "nats"), allow(dead_code))]
+pub struct NatsConfig {
+    pub url: String,
+    /// events are published to `<subject_prefix>.<mint>`
+    pub subject_prefix: String,
+    /// wait for a JetStream ack on every publish
+    pub jetstream: bool,
+    /// events waiting for the sink, also while the connection is down
+    pub pending_capacity: usize,
+    pub filter: Option<FilterCriteria>,
+    /// what the sink does with events marked stale
+    pub stale: StalePolicy,
+}
+
+/// Discord webhook routes, present when at least one route is configured.
+///
+#[derive(Debug, Clone)]
+pub struct DiscordConfig {
+    pub routes: Vec<DiscordRoute>,
+    /// events waiting per webhook before new ones are dropped
+    pub queue_capacity: usize,
+    /// applied to every route, on top of the route's own filter
+    pub filter: Option<FilterCriteria>,
+    /// what the sink does with events marked stale; applied to every route
+    pub stale: StalePolicy,
+    /// applied to every route
+    pub tier: Tier,
+}
+
+/// one webhook and the events it should receive.
+///
+#[derive(Debug, Clone)]
+pub struct DiscordRoute {
+    pub webhook_url: String,
+    pub filter: FilterCriteria,
+}
+
+/// Telegram bot settings, present when a bot token and at least one chat route are configured.
+///
+#[derive(Debug, Clone)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    pub routes: Vec<TelegramRoute>,
+    /// messages waiting per chat before new ones are dropped
+    pub queue_capacity: usize,
+    /// applied to every chat, on top of the chat's own filter
+    pub filter: Option<FilterCriteria>,
+    /// what the sink does with events marked stale; applied to every route
+    pub stale: StalePolicy,
+    /// applied to every route
+    pub tier: Tier,
+}
+
+/// one chat and the events it should receive.
+///
+#[derive(Debug, Clone)]
+pub struct TelegramRoute {
+    /// numeric chat id or `@channelusername`
+    pub chat_id: String,
+    pub filter: FilterCriteria,
+}
+
+/// JSON lines archive settings, present when `EVENTS_LOG_PATH` is set.
+///
+#[derive(Debug, Clone)]
+pub struct EventsLogConfig {
+    /// e.g. `/var/lib/pump_fun_monitor/events.jsonl`; the rotation period and part number are added to the file stem
+    pub path: PathBuf,
+    pub rotation: LogRotation,
+    /// a new part is started before a file would grow past this size
+    pub max_bytes: Option<u64>,
+    pub fsync: FsyncPolicy,
+    /// events waiting for the writer before new ones are dropped
+    pub queue_capacity: usize,
+    pub filter: Option<FilterCriteria>,
+    /// what the sink does with events marked stale
+    pub stale: StalePolicy,
+}
+
+/// PostgreSQL settings, present when `DATABASE_URL` is a `postgres://` URL.
+///
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "postgres"), allow(dead_code))]
+pub struct PostgresConfig {
+    pub url: String,
+    pub max_connections: u32,
+    /// most events written by one multi-row insert
+    pub batch_size: usize,
+    pub filter: Option<FilterCriteria>,
+    /// what the sink does with events marked stale
+    pub stale: StalePolicy,
+}
+
+/// pause before the first retry when a sink doesn't choose its own policy.
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// longest pause between retries of one event.
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// events waiting for a broker sink before new ones are dropped.
+#[cfg_attr(not(feature = "kafka"), allow(dead_code))]
+const BROKER_QUEUE_CAPACITY: usize = 1000;
+
+/// A destination for token creation events.
+///
+/// Sinks only deliver: the `SinkManager` feeds each registered sink from its own bounded queue on its own task, and
+/// retries failed deliveries according to the `RetryPolicy` the sink was registered with.
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    /// short label for logs and the `sink` metric label, e.g. `kafka` or `discord:0`.
+    fn name(&self) -> &str;
+
+    /// whether the sink wants `event` at all; events it doesn't want are never queued.
+    fn accepts(&self, _event: &TokenCreatedEvent) -> bool {
+        true
+    }
+
+    async fn deliver(&self, event: &TokenCreatedEvent) -> Result<()>;
+
+    /// most events handed to `deliver_batch` at once; events only queue up into a batch while a delivery is running.
+    fn max_batch(&self) -> usize {
+        1
+    }
+
+    /// delivers several queued events together; retried and counted as a whole.
+    async fn deliver_batch(&self, events: &[TokenCreatedEvent]) -> Result<()> {
+        for event in events {
+            self.deliver(event).await?;
+        }
+        Ok(())
+    }
+}
+
+/// how often, and how patiently, a failed delivery is retried.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// deliveries attempted per event, the first one included
+    pub max_attempts: u32,
+    /// pause before the first retry, doubled for every further one
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// a single attempt per event, for sinks whose client already retries.
+    pub const NONE: Self = Self {
+        max_attempts: 1,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+    };
+
+    /// pause before retry number `retry`, counting from 1.
+    fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+        }
+    }
+}
+
+/// how the manager runs one registered sink.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct SinkOptions {
+    /// events waiting for the sink before new ones are dropped, or for a durable sink before the manager waits
+    pub queue_capacity: usize,
+    /// wait for room in the queue rather than drop; only lossless when the manager reads a durable subscription
+    pub durable: bool,
+    pub retry: RetryPolicy,
+    /// only events matching every one of them are queued for the sink; none passes everything
+    pub filters: Vec<FilterCriteria>,
+    /// what the manager does with events marked stale: queue them for the sink, or skip them
+    pub stale: StalePolicy,
+}
+
+/// the filters of every built-in sink, keyed by sink name, as published on a config reload.
+pub type SinkFilters = HashMap<String, Vec<FilterCriteria>>;
+
+/// Fans the events out to every registered sink.
+///
+/// The manager holds the only subscription and hands each event to every sink that accepts it through a bounded queue.
+/// For a best-effort sink it doesn't wait, so a sink that is slow or keeps failing only fills its own queue and drops
+/// its own events while the other sinks keep up. For a durable sink it waits for room, holding up the other sinks and,
+/// once the manager's own durable queue is full, the dispatcher.
+#[derive(Default)]
+pub struct SinkManager {
+    sinks: Vec<RegisteredSink>,
+    /// replacement filters, applied to the sinks they name from the next event on
+    filters: Option<watch::Receiver<SinkFilters>>,
+    /// the dispatcher's immediate queues, by the name of the sink they feed instead of the manager's subscription
+    immediate: HashMap<String, mpsc::Receiver<TokenCreatedEvent>>,
+}
+
+struct RegisteredSink {
+    sink: Arc<dyn EventSink>,
+    options: SinkOptions,
+    stats: Arc<SinkStats>,
+}
+
+/// a registered sink while the manager runs, with the queue feeding its task.
+struct RunningSink {
+    sink: Arc<dyn EventSink>,
+    filters: Vec<FilterCriteria>,
+    durable: bool,
+    stale: StalePolicy,
+    stats: Arc<SinkStats>,
+    queue: mpsc::Sender<TokenCreatedEvent>,
+    worker: JoinHandle<()>,
+}
+
+impl RunningSink {
+    /// whether `event` passes both the sink's configured filters and its own `accepts`.
+    fn wants(&self, event: &TokenCreatedEvent) -> bool {
+        self.filters.iter().all(|filter| matches_filter(event, filter)) && self.sink.accepts(event)
+    }
+
+    /// queues `event` for the sink if it wants it, waiting for room only when the sink is durable.
+    async fn offer(&self, event: &TokenCreatedEvent) {
+        if !self.wants(event) {
+            return;
+        }
+        if self.stale.drops(event.stale) {
+            self.stats.record_stale_dropped(&event.transaction_signature);
+            return;
+        }
+        let queued = if self.durable {
+            self.queue.send(event.clone()).await.is_ok()
+        } else {
+            self.queue.try_send(event.clone()).is_ok()
+        };
+        if !queued {
+            self.stats.record_dropped(&event.transaction_signature);
+        }
+    }
+
+    /// closes the sink's queue and waits for it to deliver what it already has.
+    async fn finish(self) {
+        drop(self.queue);
+        let _ = self.worker.await;
+    }
+}
+
+/// swaps in the filters `filters` has for each running sink; sinks it doesn't name keep theirs.
+fn refresh_filters(running: &mut [RunningSink], filters: &SinkFilters) {
+    for running in running {
+        if let Some(replacement) = filters.get(running.sink.name()) {
+            running.filters = replacement.clone();
+        }
+    }
+}
+
+impl SinkManager {
+    /// adds a sink and returns its delivery counters.
+    pub fn register(&mut self, sink: impl EventSink + 'static, options: SinkOptions) -> Arc<SinkStats> {
+        let stats = Arc::new(SinkStats::new(sink.name()));
+        self.sinks.push(RegisteredSink {
+            sink: Arc::new(sink),
+            options,
+            stats: Arc::clone(&stats),
+        });
+        stats
+    }
+
+    /// Follows `filters` for replacement sink filters, such as those published by a config reload.
+    pub fn with_filters(mut self, filters: watch::Receiver<SinkFilters>) -> Self {
+        self.filters = Some(filters);
+        self
+    }
+
+    /// Feeds the sinks `queues` names from those dispatcher queues instead of the subscription `run` reads.
+    pub fn with_immediate(mut self, queues: HashMap<String, mpsc::Receiver<TokenCreatedEvent>>) -> Self {
+        self.immediate = queues;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// delivery counters of every registered sink, in registration order.
+    pub fn stats(&self) -> impl Iterator<Item = &Arc<SinkStats>> {
+        self.sinks.iter().map(|registered| &registered.stats)
+    }
+
+    /// Delivers every event from `events` until the channel closes, then waits for the sinks to drain their queues.
+    ///
+    /// Sinks with an immediate queue read it on a task of their own instead, and `run` waits for those queues to close
+    /// too.
+    pub async fn run(mut self, events: impl Into<Subscription>) {
+        let mut events = events.into();
+        let mut filters = self.filters;
+        let mut running = Vec::new();
+        let mut immediate = Vec::new();
+        for registered in self.sinks {
+            let (queue, rx) = mpsc::channel(registered.options.queue_capacity.max(1));
+            let worker = tokio::spawn(run_sink(
+                Arc::clone(&registered.sink),
+                registered.options.retry,
+                rx,
+                Arc::clone(&registered.stats),
+            ));
+            let sink = RunningSink {
+                sink: registered.sink,
+                filters: registered.options.filters,
+                durable: registered.options.durable,
+                stale: registered.options.stale,
+                stats: registered.stats,
+                queue,
+                worker,
+            };
+            match self.immediate.remove(sink.sink.name()) {
+                Some(queue) => immediate.push(tokio::spawn(relay_immediate(sink, queue, filters.clone()))),
+                None => running.push(sink),
+            }
+        }
+        // a queue left over belongs to a sink that failed to start; dropping it tells the dispatcher
+        self.immediate.clear();
+        if let Some(filters) = &mut filters {
+            refresh_filters(&mut running, &filters.borrow_and_update());
+        }
+
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    // a reload takes effect from the next event on, for every sink at once
+                    if let Some(filters) = filters.as_mut().filter(|filters| filters.has_changed().unwrap_or(false)) {
+                        refresh_filters(&mut running, &filters.borrow_and_update());
+                    }
+                    for running in &running {
+                        running.offer(&event).await;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    for running in &running {
+                        running.stats.record_lagged(skipped);
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+
+        // closing the queues lets every sink finish what it already has
+        for running in running {
+            running.finish().await;
+        }
+        for relay in immediate {
+            let _ = relay.await;
+        }
+    }
+}
+
+/// feeds one sink from its immediate queue until the dispatcher closes it, with its filters following `filters`.
+async fn relay_immediate(
+    sink: RunningSink,
+    mut queue: mpsc::Receiver<TokenCreatedEvent>,
+    mut filters: Option<watch::Receiver<SinkFilters>>,
+) {
+    let mut running = [sink];
+    if let Some(filters) = &mut filters {
+        refresh_filters(&mut running, &filters.borrow_and_update());
+    }
+    while let Some(event) = queue.recv().await {
+        if let Some(filters) = filters.as_mut().filter(|filters| filters.has_changed().unwrap_or(false)) {
+            refresh_filters(&mut running, &filters.borrow_and_update());
+        }
+        running[0].offer(&event).await;
+    }
+    let [sink] = running;
+    sink.finish().await;
+}
+
+/// delivers one sink's queued events, one at a time or in batches of up to `max_batch`, retrying each per `retry`.
+async fn run_sink(
+    sink: Arc<dyn EventSink>,
+    retry: RetryPolicy,
+    mut queue: mpsc::Receiver<TokenCreatedEvent>,
+    stats: Arc<SinkStats>,
+) {
+    let max_batch = sink.max_batch().max(1);
+    let mut batch = Vec::with_capacity(max_batch);
+    while let Some(event) = queue.recv().await {
+        batch.push(event);
+        // whatever queued up meanwhile goes out together
+        while batch.len() < max_batch {
+            match queue.try_recv() {
+                Ok(event) => batch.push(event),
+                Err(_) => break,
+            }
+        }
+
+        let described = describe_batch(&batch);
+        let mut attempt = 1;
+        loop {
+            let delivered = if let [event] = batch.as_slice() {
+                sink.deliver(event).await
+            } else {
+                sink.deliver_batch(&batch).await
+            };
+            match delivered {
+                Ok(()) => {
+                    stats.record_delivered(batch.len());
+                    break;
+                }
+                Err(e) if attempt < retry.max_attempts => {
+                    let delay = retry.backoff(attempt);
+                    stats.record_retry(&described, &e, delay);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    stats.record_failure(&described, batch.len(), &e);
+                    break;
+                }
+            }
+        }
+        batch.clear();
+    }
+}
+
+/// names a batch in logs by its first signature.
+fn describe_batch(batch: &[TokenCreatedEvent]) -> String {
+    match batch {
+        [] => String::new(),
+        [event] => event.transaction_signature.clone(),
+        [first, rest @ ..] => format!("{} and {} more", first.transaction_signature, rest.len()),
+    }
+}
+
+/// Builds a manager with every configured built-in sink registered.
+pub async fn builtin_sinks(config: &SinksConfig) -> SinkManager {
+    let mut manager = SinkManager::default();
+    #[cfg(feature = "kafka")]
+    if let Some(kafka_config) = &config.kafka {
+        kafka::register(&mut manager, kafka_config, config.event_format);
+    }
+    #[cfg(feature = "nats")]
+    if let Some(nats_config) = &config.nats {
+        nats::register(&mut manager, nats_config, config.event_format).await;
+    }
+    #[cfg(feature = "postgres")]
+    if let Some(postgres_config) = &config.postgres {
+        postgres::register(&mut manager, postgres_config).await;
+    }
+    if let Some(discord_config) = &config.discord {
+        discord::register(&mut manager, discord_config);
+    }
+    if let Some(telegram_config) = &config.telegram {
+        telegram::register(&mut manager, telegram_config);
+    }
+    if let Some(events_log_config) = &config.events_log {
+        jsonl::register(&mut manager, events_log_config, config.event_format);
+    }
+    manager
+}
+
+/// Starts the configured sinks on their own task, fed from `events`, and reports their counters through `metrics`.
+///
+/// With a durable sink configured the sinks read a durable queue of `channels.durable_capacity` events added to
+/// `events`, otherwise the broadcast; sinks in the immediate tier each read a queue of their own added to `events`. The
+/// sinks' filters follow `filters` from then on.
+pub fn spawn_sinks(
+    config: &SinksConfig,
+    channels: &ChannelConfig,
+    events: &mut EventDispatcher,
+    metrics: &Arc<Metrics>,
+    filters: watch::Receiver<SinkFilters>,
+) {
+    // subscribe now, so nothing dispatched while the sinks connect is missed
+    let immediate: HashMap<String, mpsc::Receiver<TokenCreatedEvent>> = config
+        .immediate_sinks()
+        .into_iter()
+        .map(|(name, capacity)| {
+            let (queue, stats) = events.add_immediate_queue(&name, capacity);
+            metrics.add_immediate_subscriber(stats);
+            (name, queue)
+        })
+        .collect();
+    let receiver = if config.has_durable() {
+        let (queue, stats) = events.add_durable("sinks", channels.durable_capacity, channels.durable_overflow);
+        metrics.add_durable_queue(stats);
+        Subscription::Durable(queue)
+    } else {
+        Subscription::BestEffort(events.subscribe())
+    };
+    let config = config.clone();
+    let metrics = Arc::clone(metrics);
+    tokio::spawn(async move {
+        let manager = builtin_sinks(&config).await.with_filters(filters).with_immediate(immediate);
+        if manager.is_empty() {
+            return;
+        }
+        for stats in manager.stats() {
+            metrics.add_sink(Arc::clone(stats));
+        }
+        manager.run(receiver).await;
+    });
+}
+
+/// delivery counters for one sink, also exported on `/metrics`.
+#[derive(Debug)]
+pub struct SinkStats {
+    name: String,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+    retried: AtomicU64,
+    dropped: AtomicU64,
+    stale_dropped: AtomicU64,
+}
+
+impl SinkStats {
+    pub(crate) fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            delivered: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            retried: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            stale_dropped: AtomicU64::new(0),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// events the sink accepted.
+    pub fn delivered(&self) -> u64 {
+        self.delivered.load(Ordering::Relaxed)
+    }
+
+    /// events given up on after the last retry.
+    pub fn failed(&self) -> u64 {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// deliveries attempted again after a failure.
+    pub fn retried(&self) -> u64 {
+        self.retried.load(Ordering::Relaxed)
+    }
+
+    /// events never handed to the sink because its queue was full, its task stopped or the manager lagged.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// stale events never handed to the sink because its stale policy is `drop`.
+    pub fn stale_dropped(&self) -> u64 {
+        self.stale_dropped.load(Ordering::Relaxed)
+    }
+
+    fn record_delivered(&self, events: usize) {
+        self.delivered.fetch_add(events as u64, Ordering::Relaxed);
+    }
+
+    fn record_retry(&self, signature: &str, error: &dyn Display, delay: Duration) {
+        self.retried.fetch_add(1, Ordering::Relaxed);
+        debug!(
+            "{} delivery of {} failed, retrying in {}ms: {}",
+            self.name,
+            signature,
+            delay.as_millis(),
+            error
+        );
+    }
+
+    fn record_failure(&self, signature: &str, events: usize, error: &dyn Display) {
+        let failed = self.failed.fetch_add(events as u64, Ordering::Relaxed) + events as u64;
+        warn!(
+            "{} delivery of {} failed ({} failed, {} delivered so far): {}",
+            self.name,
+            signature,
+            failed,
+            self.delivered(),
+            error
+        );
+    }
+
+    fn record_dropped(&self, signature: &str) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        warn!("{} queue is full, dropping {}", self.name, signature);
+    }
+
+    fn record_stale_dropped(&self, signature: &str) {
+        self.stale_dropped.fetch_add(1, Ordering::Relaxed);
+        debug!("{} skips stale event {}", self.name, signature);
+    }
+
+    fn record_lagged(&self, skipped: u64) {
+        self.dropped.fetch_add(skipped, Ordering::Relaxed);
+        warn!("{} sink lagged, {} events were not delivered", self.name, skipped);
+    }
+}
+
+#[cfg(test)]
+mod tests;
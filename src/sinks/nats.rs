@@ -0,0 +1,341 @@
+//! NATS sink.
+//!
+//! Events are published as JSON to `<prefix>.<mint>`, so consumers can subscribe to `<prefix>.*` for every launch or to one token's subject. With JetStream enabled each publish waits for the ack of the stream capturing those subjects, and unacknowledged ones are retried by the sink manager, giving at-least-once delivery; otherwise each publish only waits for the server to have read it. The sink speaks the NATS client protocol itself over one connection, upgraded to TLS for `tls://` URLs or when the server requires it, and authenticates with the URL's user and password or token. A failed connection is reopened by the next delivery; the sink is durable, so events wait in its queue of `pending_capacity` and then hold up the other durable sinks rather than being dropped.
+
+use super::{EventSink, NatsConfig, RetryPolicy, SinkManager, SinkOptions};
+use crate::data_models::{EventFormat, EventPayload, TokenCreatedEvent};
+use crate::error::{MonitorError, Result};
+use crate::websocket_server::redact_endpoint;
+use async_trait::async_trait;
+use rand::Rng;
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, ReadHalf, WriteHalf};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+use url::Url;
+
+/// how long connecting, or one publish with its reply, may take.
+pub const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// port used when the URL names none.
+const DEFAULT_PORT: u16 = 4222;
+
+/// the subject an event for `mint` is published to.
+pub fn event_subject(prefix: &str, mint: &str) -> String {
+    format!("{}.{}", prefix, mint)
+}
+
+/// where and as whom to connect, from a `nats://[user:password@]host[:port]` URL; `tls://` always uses TLS, and a
+/// user without a password is a token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerAddress {
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub token: Option<String>,
+}
+
+impl ServerAddress {
+    pub fn parse(url: &str) -> Result<Self> {
+        let parsed = Url::parse(url).map_err(|e| sink_error(format!("invalid URL: {}", e)))?;
+        let tls = match parsed.scheme() {
+            "nats" => false,
+            "tls" => true,
+            other => return Err(sink_error(format!("unsupported URL scheme '{}', only nats:// and tls:// are", other))),
+        };
+        let host = parsed.host_str().filter(|host| !host.is_empty()).ok_or_else(|| sink_error("the URL names no host"))?;
+        let user = Some(parsed.username()).filter(|user| !user.is_empty()).map(str::to_string);
+        let password = parsed.password().map(str::to_string);
+        let (user, token) = match password {
+            Some(_) => (user, None),
+            None => (None, user),
+        };
+        Ok(Self {
+            host: host.trim_start_matches('[').trim_end_matches(']').to_string(),
+            port: parsed.port().unwrap_or(DEFAULT_PORT),
+            tls,
+            user,
+            password,
+            token,
+        })
+    }
+}
+
+/// what the server sent, as far as publishing cares.
+#[derive(Debug, PartialEq)]
+enum ServerMessage {
+    Ping,
+    Pong,
+    Ok,
+    Err(String),
+    Info(ServerInfo),
+    Msg { subject: String, payload: Vec<u8> },
+}
+
+/// the parts of the server's `INFO` the sink acts on.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+struct ServerInfo {
+    #[serde(default)]
+    tls_required: bool,
+}
+
+/// the JetStream reply to a publish.
+#[derive(Deserialize)]
+struct PublishAck {
+    #[serde(default)]
+    error: Option<PublishAckError>,
+}
+
+#[derive(Deserialize)]
+struct PublishAckError {
+    #[serde(default)]
+    description: String,
+}
+
+/// the byte stream under a connection, TLS or not.
+trait Transport: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Transport for T {}
+
+/// one open connection to the server.
+struct Connection {
+    reader: BufReader<ReadHalf<Box<dyn Transport>>>,
+    writer: WriteHalf<Box<dyn Transport>>,
+    /// the subject JetStream acks are sent to, with a per-publish suffix
+    inbox: String,
+    next_reply: u64,
+}
+
+impl Connection {
+    async fn open(address: &ServerAddress, jetstream: bool) -> Result<Self> {
+        let stream = TcpStream::connect((address.host.as_str(), address.port)).await.map_err(sink_error)?;
+        stream.set_nodelay(true).map_err(sink_error)?;
+
+        // the server sends INFO in the clear, and a client that wants TLS upgrades right after it
+        let mut plain = BufReader::new(stream);
+        let mut line = String::new();
+        if plain.read_line(&mut line).await.map_err(sink_error)? == 0 {
+            return Err(sink_error("connection closed by the server"));
+        }
+        let info = match parse_line(line.trim_end(), &mut plain).await? {
+            ServerMessage::Info(info) => info,
+            other => return Err(sink_error(format!("expected INFO from the server, got {:?}", other))),
+        };
+        let tls = address.tls || info.tls_required;
+        let stream: Box<dyn Transport> = if tls {
+            let connector = tokio_native_tls::native_tls::TlsConnector::new().map_err(sink_error)?;
+            let connector = tokio_native_tls::TlsConnector::from(connector);
+            Box::new(connector.connect(&address.host, plain.into_inner()).await.map_err(sink_error)?)
+        } else {
+            Box::new(plain.into_inner())
+        };
+        let (reader, writer) = tokio::io::split(stream);
+        let mut connection = Self {
+            reader: BufReader::new(reader),
+            writer,
+            inbox: format!("_INBOX.{:016x}", rand::thread_rng().gen::<u64>()),
+            next_reply: 0,
+        };
+
+        let options = serde_json::json!({
+            "verbose": false,
+            "pedantic": false,
+            "tls_required": tls,
+            "lang": "rust",
+            "version": env!("CARGO_PKG_VERSION"),
+            "protocol": 1,
+            "user": address.user,
+            "pass": address.password,
+            "auth_token": address.token,
+        });
+        let mut handshake = format!("CONNECT {}\r\n", options);
+        if jetstream {
+            handshake.push_str(&format!("SUB {}.* 1\r\n", connection.inbox));
+        }
+        handshake.push_str("PING\r\n");
+        connection.writer.write_all(handshake.as_bytes()).await.map_err(sink_error)?;
+        connection.wait_for_pong().await?;
+        Ok(connection)
+    }
+
+    /// publishes and waits until the server has read it.
+    async fn publish(&mut self, subject: &str, payload: &[u8]) -> Result<()> {
+        let mut frame = format!("PUB {} {}\r\n", subject, payload.len()).into_bytes();
+        frame.extend_from_slice(payload);
+        frame.extend_from_slice(b"\r\nPING\r\n");
+        self.writer.write_all(&frame).await.map_err(sink_error)?;
+        self.wait_for_pong().await
+    }
+
+    /// publishes with a reply subject and waits for the JetStream ack sent to it.
+    async fn publish_acked(&mut self, subject: &str, payload: &[u8]) -> Result<()> {
+        self.next_reply += 1;
+        let reply = format!("{}.{}", self.inbox, self.next_reply);
+        let mut frame = format!("PUB {} {} {}\r\n", subject, reply, payload.len()).into_bytes();
+        frame.extend_from_slice(payload);
+        frame.extend_from_slice(b"\r\n");
+        self.writer.write_all(&frame).await.map_err(sink_error)?;
+        loop {
+            match self.read().await? {
+                // acks of earlier publishes that timed out are skipped
+                ServerMessage::Msg { subject, payload } if subject == reply => {
+                    let ack: PublishAck = serde_json::from_slice(&payload)
+                        .map_err(|e| sink_error(format!("unreadable JetStream ack: {}", e)))?;
+                    return match ack.error {
+                        Some(error) => Err(sink_error(format!("JetStream rejected the publish: {}", error.description))),
+                        None => Ok(()),
+                    };
+                }
+                ServerMessage::Err(message) => return Err(sink_error(message)),
+                _ => {}
+            }
+        }
+    }
+
+    async fn wait_for_pong(&mut self) -> Result<()> {
+        loop {
+            match self.read().await? {
+                ServerMessage::Pong => return Ok(()),
+                ServerMessage::Err(message) => return Err(sink_error(message)),
+                _ => {}
+            }
+        }
+    }
+
+    /// reads the next message, answering the server's pings on the way.
+    async fn read(&mut self) -> Result<ServerMessage> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line).await.map_err(sink_error)? == 0 {
+            return Err(sink_error("connection closed by the server"));
+        }
+        let message = parse_line(line.trim_end(), &mut self.reader).await?;
+        if message == ServerMessage::Ping {
+            self.writer.write_all(b"PONG\r\n").await.map_err(sink_error)?;
+        }
+        Ok(message)
+    }
+}
+
+/// parses one protocol line, reading the payload that follows a `MSG`.
+async fn parse_line(line: &str, reader: &mut (impl AsyncReadExt + Unpin)) -> Result<ServerMessage> {
+    let (operation, arguments) = line.split_once(' ').unwrap_or((line, ""));
+    match operation.to_ascii_uppercase().as_str() {
+        "PING" => Ok(ServerMessage::Ping),
+        "PONG" => Ok(ServerMessage::Pong),
+        "+OK" => Ok(ServerMessage::Ok),
+        // servers resend INFO when the cluster changes; only the first one is acted on
+        "INFO" => Ok(ServerMessage::Info(serde_json::from_str(arguments).unwrap_or_default())),
+        "-ERR" => Ok(ServerMessage::Err(arguments.trim().trim_matches('\'').to_string())),
+        "MSG" => {
+            // MSG <subject> <sid> [reply-to] <#bytes>
+            let fields: Vec<&str> = arguments.split_whitespace().collect();
+            let (Some(subject), Some(size)) = (fields.first(), fields.last().and_then(|size| size.parse::<usize>().ok()))
+            else {
+                return Err(sink_error(format!("malformed MSG line '{}'", line)));
+            };
+            let mut payload = vec![0; size + 2];
+            reader.read_exact(&mut payload).await.map_err(sink_error)?;
+            payload.truncate(size);
+            Ok(ServerMessage::Msg {
+                subject: subject.to_string(),
+                payload,
+            })
+        }
+        _ => Err(sink_error(format!("unexpected line from the server: '{}'", line))),
+    }
+}
+
+/// publishes events under one subject prefix.
+pub struct NatsSink {
+    address: ServerAddress,
+    url: String,
+    jetstream: bool,
+    subject_prefix: String,
+    /// `None` until the first delivery, and again after one failed
+    connection: Mutex<Option<Connection>>,
+    format: EventFormat,
+}
+
+impl NatsSink {
+    pub fn new(config: &NatsConfig, format: EventFormat) -> Result<Self> {
+        Ok(Self {
+            address: ServerAddress::parse(&config.url)?,
+            url: config.url.clone(),
+            jetstream: config.jetstream,
+            subject_prefix: config.subject_prefix.clone(),
+            connection: Mutex::new(None),
+            format,
+        })
+    }
+
+    async fn publish(&self, connection: &mut Option<Connection>, subject: &str, payload: &[u8]) -> Result<()> {
+        if connection.is_none() {
+            *connection = Some(Connection::open(&self.address, self.jetstream).await?);
+            info!("NATS connected to {}", redact_endpoint(&self.url));
+        }
+        let open = connection.as_mut().expect("connection was just opened");
+        if self.jetstream {
+            open.publish_acked(subject, payload).await
+        } else {
+            open.publish(subject, payload).await
+        }
+    }
+}
+
+#[async_trait]
+impl EventSink for NatsSink {
+    fn name(&self) -> &str {
+        "nats"
+    }
+
+    /// with JetStream, waits for the stream's ack; otherwise for the server to answer a ping sent after the event.
+    async fn deliver(&self, event: &TokenCreatedEvent) -> Result<()> {
+        let payload = serde_json::to_vec(&event.formatted(self.format))?;
+        let subject = event_subject(&self.subject_prefix, &event.token.mint_address);
+
+        let mut connection = self.connection.lock().await;
+        let published = match tokio::time::timeout(REPLY_TIMEOUT, self.publish(&mut connection, &subject, &payload)).await {
+            Ok(published) => published,
+            Err(_) => Err(sink_error(format!("no reply from the server within {:?}", REPLY_TIMEOUT))),
+        };
+        if published.is_err() && connection.take().is_some() {
+            warn!("NATS connection lost, reconnecting on the next delivery");
+        }
+        published
+    }
+}
+
+/// Registers the NATS sink, unless its URL can't be used.
+pub async fn register(manager: &mut SinkManager, config: &NatsConfig, format: EventFormat) {
+    let sink = match NatsSink::new(config, format) {
+        Ok(sink) => sink,
+        Err(e) => {
+            error!("NATS sink disabled, cannot use {}: {}", redact_endpoint(&config.url), e);
+            return;
+        }
+    };
+    info!(
+        "NATS sink publishing to '{}.<mint>' on {}{}",
+        config.subject_prefix,
+        redact_endpoint(&config.url),
+        if config.jetstream { " with JetStream acks" } else { "" }
+    );
+
+    // events wait in the sink's own queue while the connection is down, and then hold up the other durable sinks
+    let options = SinkOptions {
+        queue_capacity: config.pending_capacity,
+        durable: true,
+        retry: RetryPolicy::default(),
+        filters: config.filter.iter().cloned().collect(),
+        stale: config.stale,
+    };
+    manager.register(sink, options);
+}
+
+fn sink_error(message: impl std::fmt::Display) -> MonitorError {
+    MonitorError::Sink(format!("nats: {}", message))
+}
@@ -0,0 +1,227 @@
+//! PostgreSQL sink.
+//!
+//! Stores every event as a row of `token_events`, keyed by mint address, so several monitor instances can share one database. Inserts use `ON CONFLICT (mint_address) DO NOTHING`: whichever instance stores a launch first wins, and the copies written by the others are ignored instead of failing. Events that queue up while an insert runs go out together as one multi-row insert, so a burst of launches costs a few round trips rather than one per event.
+//!
+//! The same table answers WebSocket `query` requests through `PostgresArchive`, so history reaches back past the server's replay buffer. It reads the stored JSON of each event, newest first, and applies the client's filter as it goes. `PostgresArchive` is also the `HistoryStore` the `export` and `import` commands stream events out of and back into, and the `TokenSource` the token registry looks mints up in when it doesn't hold them.
+//!
+//! The schema lives in `migrations/` and is embedded with `sqlx::migrate!`. It is applied at startup; sqlx records applied versions in `_sqlx_migrations` and takes an advisory lock while migrating, so every instance can run it.
+
+use super::{EventSink, PostgresConfig, RetryPolicy, SinkManager, SinkOptions};
+use crate::data_models::TokenCreatedEvent;
+use crate::error::{MonitorError, Result};
+use crate::export::HistoryStore;
+use crate::filtering::{CompiledFilter, MatchableEvent};
+use crate::registry::TokenSource;
+use crate::websocket_server::{redact_endpoint, EventArchive};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::stream::BoxStream;
+use futures_util::{StreamExt, TryStreamExt};
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::types::Json;
+use sqlx::{Postgres, QueryBuilder};
+use std::time::Duration;
+use tracing::{debug, error, info};
+
+/// how long an insert waits for a free pooled connection.
+const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// events waiting for the database before new ones are dropped.
+const QUEUE_CAPACITY: usize = 10_000;
+
+const INSERT_COLUMNS: &str = "INSERT INTO token_events (mint_address, transaction_signature, slot, program_id, created_at, \
+     name, symbol, uri, creator, bonding_curve, supply, decimals, price_sol, market_cap_sol, market_cap_usd, \
+     initial_buy_lamports, same_tx_buyers, creator_tokens_seen, similar_recent_count, event) ";
+
+const SELECT_SINCE: &str = "SELECT event FROM token_events WHERE created_at >= $1 ORDER BY created_at DESC";
+
+const SELECT_RANGE: &str =
+    "SELECT event FROM token_events WHERE created_at >= $1 AND created_at < $2 ORDER BY created_at, mint_address";
+
+const SELECT_MINT: &str = "SELECT event FROM token_events WHERE mint_address = $1";
+
+/// writes events to one database through a connection pool.
+struct PostgresSink {
+    pool: PgPool,
+    batch_size: usize,
+}
+
+#[async_trait]
+impl EventSink for PostgresSink {
+    fn name(&self) -> &str {
+        "postgres"
+    }
+
+    async fn deliver(&self, event: &TokenCreatedEvent) -> Result<()> {
+        insert_events(&self.pool, std::slice::from_ref(event)).await
+    }
+
+    fn max_batch(&self) -> usize {
+        self.batch_size
+    }
+
+    async fn deliver_batch(&self, events: &[TokenCreatedEvent]) -> Result<()> {
+        insert_events(&self.pool, events).await
+    }
+}
+
+/// Registers the Postgres sink once the database is reachable and migrated.
+pub async fn register(manager: &mut SinkManager, config: &PostgresConfig) {
+    // the URL may carry a password, so only its host is logged
+    let endpoint = redact_endpoint(&config.url);
+    let pool = match connect(config).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Failed to set up PostgreSQL at {}: {}", endpoint, e);
+            return;
+        }
+    };
+    info!(
+        "PostgreSQL sink writing to {} ({} connections, batches of up to {})",
+        endpoint, config.max_connections, config.batch_size
+    );
+
+    let sink = PostgresSink {
+        pool,
+        batch_size: config.batch_size,
+    };
+    let options = SinkOptions {
+        queue_capacity: QUEUE_CAPACITY,
+        durable: true,
+        retry: RetryPolicy::default(),
+        filters: config.filter.iter().cloned().collect(),
+        stale: config.stale,
+    };
+    manager.register(sink, options);
+}
+
+/// answers `query` requests from the events stored in `token_events`, by any instance.
+pub struct PostgresArchive {
+    pool: PgPool,
+}
+
+impl PostgresArchive {
+    /// connects with a pool of its own, so queries never wait behind the sink's inserts.
+    pub async fn connect(config: &PostgresConfig) -> std::result::Result<Self, sqlx::Error> {
+        Ok(Self {
+            pool: connect(config).await?,
+        })
+    }
+}
+
+#[async_trait]
+impl EventArchive for PostgresArchive {
+    async fn query(&self, filter: &CompiledFilter, since: DateTime<Utc>, limit: usize) -> Result<Vec<TokenCreatedEvent>> {
+        let mut rows = sqlx::query_scalar::<_, Json<TokenCreatedEvent>>(SELECT_SINCE)
+            .bind(since)
+            .fetch(&self.pool);
+        let mut events = Vec::new();
+        // rows are streamed, so a selective filter reads no further back than it has to
+        while events.len() < limit {
+            let Some(Json(event)) = rows.try_next().await.map_err(|e| MonitorError::Archive(e.to_string()))? else {
+                break;
+            };
+            if filter.matches(&MatchableEvent::new(&event)) {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+}
+
+#[async_trait]
+impl HistoryStore for PostgresArchive {
+    fn events(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> BoxStream<'_, Result<TokenCreatedEvent>> {
+        // a server-side cursor: rows arrive as they are read, however large the range
+        sqlx::query_scalar::<_, Json<TokenCreatedEvent>>(SELECT_RANGE)
+            .bind(from)
+            .bind(to)
+            .fetch(&self.pool)
+            .map_ok(|Json(event)| event)
+            .map_err(|e| MonitorError::Archive(e.to_string()))
+            .boxed()
+    }
+
+    async fn insert(&self, events: &[TokenCreatedEvent]) -> Result<u64> {
+        let result = insert_query(events)
+            .build()
+            .execute(&self.pool)
+            .await
+            .map_err(|e| MonitorError::Sink(e.to_string()))?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[async_trait]
+impl TokenSource for PostgresArchive {
+    async fn token(&self, mint: &str) -> Result<Option<TokenCreatedEvent>> {
+        let row = sqlx::query_scalar::<_, Json<TokenCreatedEvent>>(SELECT_MINT)
+            .bind(mint)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| MonitorError::Archive(e.to_string()))?;
+        Ok(row.map(|Json(event)| event))
+    }
+}
+
+/// opens the pool and brings the schema up to date.
+pub(crate) async fn connect(config: &PostgresConfig) -> std::result::Result<PgPool, sqlx::Error> {
+    let pool = PgPoolOptions::new()
+        .max_connections(config.max_connections)
+        .acquire_timeout(ACQUIRE_TIMEOUT)
+        .connect(&config.url)
+        .await?;
+    sqlx::migrate!().run(&pool).await?;
+    Ok(pool)
+}
+
+async fn insert_events(pool: &PgPool, events: &[TokenCreatedEvent]) -> Result<()> {
+    let result = insert_query(events)
+        .build()
+        .execute(pool)
+        .await
+        .map_err(|e| MonitorError::Sink(e.to_string()))?;
+    let already_stored = events.len() as u64 - result.rows_affected();
+    if already_stored > 0 {
+        debug!("{} of {} events were already stored by another instance", already_stored, events.len());
+    }
+    Ok(())
+}
+
+/// One multi-row insert for `events`, skipping mints that are already stored.
+///
+/// Amounts that may not fit a `BIGINT` are bound as text and cast to `NUMERIC`.
+pub(crate) fn insert_query(events: &[TokenCreatedEvent]) -> QueryBuilder<'_, Postgres> {
+    let mut query = QueryBuilder::new(INSERT_COLUMNS);
+    query.push_values(events, |mut row, event| {
+        row.push_bind(event.token.mint_address.as_str())
+            .push_bind(event.transaction_signature.as_str())
+            .push_bind(bigint(event.slot))
+            .push_bind(event.program_id.as_str())
+            .push_bind(event.timestamp)
+            .push_bind(event.token.name.as_str())
+            .push_bind(event.token.symbol.as_str())
+            .push_bind(event.token.uri.as_str())
+            .push_bind(event.token.creator.as_str())
+            .push_bind(event.pump_data.bonding_curve.as_str())
+            .push_bind(event.token.supply.map(|supply| supply.to_string()))
+            .push_unseparated("::numeric")
+            .push_bind(event.token.decimals.map(i16::from))
+            .push_bind(event.pump_data.price_sol)
+            .push_bind(event.pump_data.market_cap_sol)
+            .push_bind(event.pump_data.market_cap_usd)
+            .push_bind(event.initial_buy.as_ref().map(|buy| buy.sol_amount.to_string()))
+            .push_unseparated("::numeric")
+            .push_bind(i64::from(event.same_tx_buyers))
+            .push_bind(event.creator_stats.as_ref().map(|stats| i64::from(stats.tokens_created_seen)))
+            .push_bind(event.similar_recent_count.map(i64::from))
+            .push_bind(Json(event));
+    });
+    query.push(" ON CONFLICT (mint_address) DO NOTHING");
+    query
+}
+
+/// slots stay far below `i64::MAX`; saturates rather than wrapping if one ever didn't.
+fn bigint(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
@@ -0,0 +1,187 @@
+//! Telegram bot sink.
+//!
+//! Each configured route pairs a chat id with a `FilterCriteria`; matching events are sent through the Bot API `sendMessage` method as MarkdownV2. Token names and symbols are attacker-controlled, so everything interpolated into the message is escaped for the context it lands in. Like the Discord sink, every chat is a sink of its own with its own queue, and flood-control 429s are retried after the `retry_after` Telegram reports; that is the only retry, other failures give the message up.
+
+use super::{route_filters, EventSink, RetryPolicy, SinkManager, SinkOptions, TelegramConfig};
+use crate::data_models::TokenCreatedEvent;
+use crate::error::{MonitorError, Result};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// attempts per message before giving up on a flood-limited chat.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// used when a 429 response carries no `retry_after`.
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// names and symbols are cut to this many characters before escaping, keeping messages well under Telegram's 4096 limit.
+const MAX_FIELD_CHARS: usize = 200;
+
+/// characters that must be backslash-escaped in MarkdownV2 text.
+const MARKDOWN_V2_SPECIAL: &[char] = &[
+    '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!', '\\',
+];
+
+/// one chat route as a sink of its own.
+struct TelegramChatSink {
+    name: String,
+    client: reqwest::Client,
+    send_url: String,
+    chat_id: String,
+}
+
+#[async_trait]
+impl EventSink for TelegramChatSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn deliver(&self, event: &TokenCreatedEvent) -> Result<()> {
+        send_message(&self.client, &self.send_url, &self.chat_id, &format_message(event))
+            .await
+            .map_err(MonitorError::Sink)
+    }
+}
+
+/// Registers one sink per configured chat route.
+pub fn register(manager: &mut SinkManager, config: &TelegramConfig) {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_default();
+    let send_url = format!("https://api.telegram.org/bot{}/sendMessage", config.bot_token);
+    for route in &config.routes {
+        let sink = TelegramChatSink {
+            name: sink_name(&route.chat_id),
+            client: client.clone(),
+            send_url: send_url.clone(),
+            chat_id: route.chat_id.clone(),
+        };
+        // `send_message` already waits out flood limits; retrying on top of it would repeat that wait for each attempt
+        let options = SinkOptions {
+            queue_capacity: config.queue_capacity,
+            durable: false,
+            retry: RetryPolicy::NONE,
+            filters: route_filters(&config.filter, &route.filter),
+            stale: config.stale,
+        };
+        manager.register(sink, options);
+    }
+    info!("Telegram sink sending to {} chat route(s)", config.routes.len());
+}
+
+/// the sink name of the route to `chat_id`, also the key of its filters on reload.
+pub(super) fn sink_name(chat_id: &str) -> String {
+    format!("telegram:{}", chat_id)
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    description: Option<String>,
+    parameters: Option<ResponseParameters>,
+}
+
+#[derive(Deserialize)]
+struct ResponseParameters {
+    retry_after: Option<u64>,
+}
+
+async fn send_message(
+    client: &reqwest::Client,
+    send_url: &str,
+    chat_id: &str,
+    text: &str,
+) -> std::result::Result<(), String> {
+    let body = json!({
+        "chat_id": chat_id,
+        "text": text,
+        "parse_mode": "MarkdownV2",
+        "disable_web_page_preview": true,
+    });
+
+    for _ in 0..MAX_ATTEMPTS {
+        // the URL carries the bot token, so it is stripped from errors
+        let response = client
+            .post(send_url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("chat {}: {}", chat_id, e.without_url()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let error = response.json::<ErrorResponse>().await.ok();
+        if status != StatusCode::TOO_MANY_REQUESTS {
+            let description = error.and_then(|error| error.description).unwrap_or_default();
+            return Err(format!("chat {}: HTTP {} {}", chat_id, status, description));
+        }
+
+        let delay = error
+            .and_then(|error| error.parameters)
+            .and_then(|parameters| parameters.retry_after)
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_RETRY_DELAY);
+        warn!("Telegram chat {} flood limited, retrying in {}s", chat_id, delay.as_secs());
+        tokio::time::sleep(delay).await;
+    }
+    Err(format!("chat {}: still flood limited after {} attempts", chat_id, MAX_ATTEMPTS))
+}
+
+/// builds the MarkdownV2 text announcing one event.
+pub fn format_message(event: &TokenCreatedEvent) -> String {
+    let token = &event.token;
+    // absent at `DETAIL_LEVEL=minimal`
+    let mut market_cap = match event.pump_data.market_cap_sol {
+        Some(market_cap_sol) => format!("{:.2} SOL", market_cap_sol),
+        None => "unknown".to_string(),
+    };
+    if let Some(market_cap_usd) = event.pump_data.market_cap_usd {
+        market_cap.push_str(&format!(" (${:.0})", market_cap_usd));
+    }
+
+    format!(
+        "🚀 *New token:* {} \\({}\\)\n*Mint:* `{}`\n*Creator:* `{}`\n*Market cap:* {}\n[View on pump\\.fun]({})",
+        escape_markdown(&truncate(&token.name)),
+        escape_markdown(&truncate(&token.symbol)),
+        escape_code(&token.mint_address),
+        escape_code(&token.creator),
+        escape_markdown(&market_cap),
+        escape_link_url(&format!("https://pump.fun/coin/{}", token.mint_address)),
+    )
+}
+
+/// escapes text outside of code spans and links.
+pub fn escape_markdown(text: &str) -> String {
+    escape(text, MARKDOWN_V2_SPECIAL)
+}
+
+/// escapes text inside an inline code span.
+pub fn escape_code(text: &str) -> String {
+    escape(text, &['`', '\\'])
+}
+
+/// escapes the URL part of an inline link.
+pub fn escape_link_url(url: &str) -> String {
+    escape(url, &[')', '\\'])
+}
+
+fn escape(text: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn truncate(text: &str) -> String {
+    text.chars().take(MAX_FIELD_CHARS).collect()
+}
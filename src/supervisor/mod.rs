@@ -0,0 +1,82 @@
+//! # Task Supervision
+//!
+//! The Solana monitor (or simulator) and the WebSocket server run as supervised tasks. A task that returns an error or
+//! panics is started again after an exponential backoff, up to `max_restarts` times in a row, so one failing component
+//! doesn't take the other down with it. A task that returns `Ok` stopped on purpose, e.g. on Ctrl+C, and stays stopped.
+//! Each attempt runs on its own tokio task so a panic surfaces as a `JoinError` instead of unwinding the supervisor.
+
+use crate::config::SupervisorConfig;
+use std::any::Any;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+/// a run at least this long counts as healthy, so the restart budget and backoff start over.
+const STABLE_RUN: Duration = Duration::from_secs(300);
+
+/// how a supervised task ended for good.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskExit {
+    /// the task returned `Ok`
+    Completed,
+    /// the task kept failing and the restart budget ran out
+    GaveUp,
+}
+
+/// Runs the task built by `start` until it completes, restarting it whenever it fails or panics.
+///
+/// # arguments
+/// * `name` - component name used in log messages
+/// * `config` - restart budget and backoff
+/// * `restarts` - counter incremented on every restart, exported as a metric
+/// * `start` - builds a fresh attempt of the task; it can carry state from one attempt to the next
+pub async fn supervise<F, Fut, E>(name: &str, config: &SupervisorConfig, restarts: &AtomicU64, mut start: F) -> TaskExit
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: Display + Send + 'static,
+{
+    let mut attempt = 0;
+    let mut delay = config.backoff.initial_delay;
+    loop {
+        let started = Instant::now();
+        match tokio::spawn(start()).await {
+            Ok(Ok(())) => {
+                info!("{} stopped", name);
+                return TaskExit::Completed;
+            }
+            Ok(Err(e)) => error!("{} failed: {}", name, e),
+            Err(e) if e.is_panic() => error!("{} panicked: {}", name, panic_message(e.into_panic())),
+            Err(e) => error!("{} task failed: {}", name, e),
+        }
+
+        if started.elapsed() >= STABLE_RUN {
+            attempt = 0;
+            delay = config.backoff.initial_delay;
+        }
+        if attempt >= config.max_restarts {
+            error!("{} failed after {} restarts in a row, giving up", name, attempt);
+            return TaskExit::GaveUp;
+        }
+        attempt += 1;
+        restarts.fetch_add(1, Ordering::Relaxed);
+        warn!("Restarting {} in {:?} (restart {} of {})", name, delay, attempt, config.max_restarts);
+        tokio::time::sleep(delay).await;
+        delay = config.backoff.next_delay(delay);
+    }
+}
+
+/// the message passed to `panic!`, when it was a string.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map_or_else(|| "non-string panic payload".to_string(), |message| message.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests;
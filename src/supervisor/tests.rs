@@ -0,0 +1,98 @@
+//! Unit tests for restarting failed and panicking tasks.
+
+
+use super::*;
+use crate::config::ReconnectPolicy;
+use std::sync::atomic::AtomicU32;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// one attempt of a test task.
+type TestTask = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+
+/// function to build a supervisor config with a one-second backoff doubling up to ten seconds.
+fn test_config(max_restarts: u32) -> SupervisorConfig {
+    SupervisorConfig {
+        max_restarts,
+        backoff: ReconnectPolicy {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+            multiplier: 2.0,
+        },
+    }
+}
+
+/// function to build a task that fails its first `failures` attempts, by error or by panic, then succeeds.
+fn flaky_task(failures: u32, panics: bool) -> (Arc<AtomicU32>, impl FnMut() -> TestTask) {
+    let attempts = Arc::new(AtomicU32::new(0));
+    let counter = Arc::clone(&attempts);
+    let start = move || {
+        let attempt = counter.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async move {
+            if attempt < failures {
+                if panics {
+                    panic!("attempt {} blew up", attempt);
+                }
+                return Err(format!("attempt {} failed", attempt));
+            }
+            Ok(())
+        }) as TestTask
+    };
+    (attempts, start)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_failing_task_is_restarted_until_it_succeeds() {
+    let (attempts, start) = flaky_task(3, false);
+    let restarts = AtomicU64::new(0);
+    let started = Instant::now();
+
+    let exit = supervise("flaky", &test_config(5), &restarts, start).await;
+
+    assert_eq!(exit, TaskExit::Completed);
+    assert_eq!(attempts.load(Ordering::SeqCst), 4);
+    assert_eq!(restarts.load(Ordering::Relaxed), 3);
+    // 1s + 2s + 4s of backoff
+    assert!(started.elapsed() >= Duration::from_secs(7));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_panicking_task_is_restarted() {
+    let (attempts, start) = flaky_task(2, true);
+    let restarts = AtomicU64::new(0);
+
+    let exit = supervise("panicky", &test_config(5), &restarts, start).await;
+
+    assert_eq!(exit, TaskExit::Completed);
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert_eq!(restarts.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_supervisor_gives_up_when_budget_is_exhausted() {
+    let (attempts, start) = flaky_task(10, false);
+    let restarts = AtomicU64::new(0);
+
+    let exit = supervise("broken", &test_config(2), &restarts, start).await;
+
+    assert_eq!(exit, TaskExit::GaveUp);
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert_eq!(restarts.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_zero_restarts_stops_after_first_failure() {
+    let (attempts, start) = flaky_task(1, true);
+    let restarts = AtomicU64::new(0);
+
+    assert_eq!(supervise("once", &test_config(0), &restarts, start).await, TaskExit::GaveUp);
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    assert_eq!(restarts.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn test_panic_message() {
+    assert_eq!(panic_message(Box::new("static message")), "static message");
+    assert_eq!(panic_message(Box::new(String::from("formatted message"))), "formatted message");
+    assert_eq!(panic_message(Box::new(42)), "non-string panic payload");
+}
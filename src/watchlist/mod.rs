@@ -0,0 +1,226 @@
+//! # Wallet Watchlist
+//!
+//! Labels for wallets worth knowing about (known devs, influencers, insiders), read from the file `WATCHLIST_FILE`
+//! names. Events created by a listed wallet carry its `creatorLabel` and `creatorTags`, and clients can subscribe to
+//! every wallet with a tag through the `creatorTag` filter. The file is read again on every config reload and swapped
+//! in whole: a file with any malformed entry is rejected, listing each by line, and the previous list stays in effect.
+//!
+//! Files ending in `.csv` hold one `pubkey,label,tags` line per wallet, with the tags separated by `;`; blank lines,
+//! `#` comments and a `pubkey,label,tags` header are skipped. Any other file is a JSON object keyed by pubkey:
+//!
+//! ```json
+//! {
+//!   "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU": { "label": "serial dev", "tags": ["dev", "insider"] },
+//!   "4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R": { "label": "influencer" }
+//! }
+//! ```
+//!
+//! Only the creator is looked up: for pump.fun creations the creator is the wallet signing and paying for the
+//! transaction, and the other wallets buying in it are counted but not kept on the event.
+
+use crate::rpc_client::sanitize::clean;
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// longest label kept, in characters.
+pub const MAX_LABEL_CHARS: usize = 100;
+/// longest tag kept, in characters.
+pub const MAX_TAG_CHARS: usize = 32;
+
+/// what the watchlist says about one wallet.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletLabel {
+    pub label: String,
+    /// lower case, without duplicates
+    pub tags: Vec<String>,
+}
+
+/// Labelled wallets keyed by their base58 address.
+///
+#[derive(Clone, PartialEq, Default)]
+pub struct Watchlist {
+    /// the file the list was read from
+    path: PathBuf,
+    wallets: HashMap<String, WalletLabel>,
+}
+
+impl fmt::Debug for Watchlist {
+    // the entries themselves would drown everything else a `Config` prints
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watchlist")
+            .field("path", &self.path)
+            .field("wallets", &self.wallets.len())
+            .finish()
+    }
+}
+
+impl Watchlist {
+    /// Reads the watchlist at `path`.
+    ///
+    /// Fails with every problem in the file, each naming the line it is on.
+    pub fn load(path: &Path) -> Result<Self, Vec<String>> {
+        let contents = fs::read_to_string(path).map_err(|e| vec![format!("cannot be read: {}", e)])?;
+        let is_csv = path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let entries = if is_csv { parse_csv(&contents) } else { parse_json(&contents)? };
+
+        let mut problems = Vec::new();
+        let mut wallets = HashMap::with_capacity(entries.len());
+        let mut first_lines = HashMap::with_capacity(entries.len());
+        for entry in entries {
+            match entry.validate() {
+                Ok((pubkey, label)) => {
+                    if let Some(first_line) = first_lines.insert(pubkey.clone(), entry.line) {
+                        problems.push(format!("line {}: {} is already listed on line {}", entry.line, pubkey, first_line));
+                        first_lines.insert(pubkey, first_line);
+                        continue;
+                    }
+                    wallets.insert(pubkey, label);
+                }
+                Err(problem) => problems.push(format!("line {}: {}", entry.line, problem)),
+            }
+        }
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            wallets,
+        })
+    }
+
+    /// the label of `wallet`, if it is listed.
+    pub fn get(&self, wallet: &str) -> Option<&WalletLabel> {
+        self.wallets.get(wallet)
+    }
+
+    pub fn len(&self) -> usize {
+        self.wallets.len()
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn is_empty(&self) -> bool {
+        self.wallets.is_empty()
+    }
+}
+
+/// one wallet as written in the file, before it is checked.
+struct RawEntry {
+    line: usize,
+    pubkey: String,
+    label: String,
+    tags: Vec<String>,
+}
+
+impl RawEntry {
+    /// the address and its cleaned label, or what is wrong with the entry.
+    fn validate(&self) -> Result<(String, WalletLabel), String> {
+        let pubkey = self.pubkey.trim();
+        if Pubkey::from_str(pubkey).is_err() {
+            return Err(format!("'{}' is not a base58 public key", pubkey));
+        }
+        let label = clean(&self.label, MAX_LABEL_CHARS);
+        if label.is_empty() {
+            return Err(format!("{} has no label", pubkey));
+        }
+        let mut tags: Vec<String> = Vec::with_capacity(self.tags.len());
+        for tag in &self.tags {
+            let tag = clean(tag, MAX_TAG_CHARS).to_lowercase();
+            if tag.is_empty() {
+                return Err(format!("{} has an empty tag", pubkey));
+            }
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        Ok((pubkey.to_string(), WalletLabel { label, tags }))
+    }
+}
+
+/// the wallets of a `pubkey,label,tags` file; lines without a label are kept for `validate` to report.
+fn parse_csv(contents: &str) -> Vec<RawEntry> {
+    let mut entries = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let mut fields = trimmed.splitn(3, ',');
+        let pubkey = fields.next().unwrap_or_default().trim();
+        if entries.is_empty() && pubkey.eq_ignore_ascii_case("pubkey") {
+            continue;
+        }
+        let label = fields.next().unwrap_or_default();
+        let tags = fields
+            .next()
+            .filter(|tags| !tags.trim().is_empty())
+            .map(|tags| tags.split(';').map(str::to_string).collect())
+            .unwrap_or_default();
+        entries.push(RawEntry {
+            line: line_number,
+            pubkey: pubkey.to_string(),
+            label: label.to_string(),
+            tags,
+        });
+    }
+    entries
+}
+
+/// a wallet in a JSON watchlist: the label alone, or the label with tags.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonEntry {
+    Label(String),
+    #[serde(rename_all = "camelCase")]
+    Labelled {
+        label: String,
+        #[serde(default)]
+        tags: Vec<String>,
+    },
+}
+
+/// the wallets of a JSON watchlist, each with the line its key is on.
+fn parse_json(contents: &str) -> Result<Vec<RawEntry>, Vec<String>> {
+    let wallets: HashMap<String, serde_json::Value> = serde_json::from_str(contents)
+        .map_err(|e| vec![format!("line {}: not a JSON object of wallets: {}", e.line(), e)])?;
+
+    let mut problems = Vec::new();
+    let mut entries = Vec::with_capacity(wallets.len());
+    for (pubkey, value) in wallets {
+        let line = key_line(contents, &pubkey);
+        match serde_json::from_value::<JsonEntry>(value) {
+            Ok(JsonEntry::Label(label)) => entries.push(RawEntry { line, pubkey, label, tags: Vec::new() }),
+            Ok(JsonEntry::Labelled { label, tags }) => entries.push(RawEntry { line, pubkey, label, tags }),
+            Err(_) => problems.push((line, pubkey)),
+        }
+    }
+    // the map lost the file's order; report problems top to bottom
+    if !problems.is_empty() {
+        problems.sort();
+        return Err(problems
+            .into_iter()
+            .map(|(line, pubkey)| {
+                format!("line {}: {} must map to a label or to {{\"label\": ..., \"tags\": [...]}}", line, pubkey)
+            })
+            .collect());
+    }
+    entries.sort_by_key(|entry| entry.line);
+    Ok(entries)
+}
+
+/// the line `key` first appears on as a JSON string, 1-based.
+fn key_line(contents: &str, key: &str) -> usize {
+    let quoted = format!("\"{}\"", key);
+    contents
+        .find(&quoted)
+        .map_or(1, |offset| contents[..offset].matches('\n').count() + 1)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,92 @@
+//! Unit tests for reading watchlist files and reporting their problems.
+
+
+use super::*;
+
+const PUMP: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
+const SYSTEM: &str = "11111111111111111111111111111111";
+const TOKEN: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+/// function to write `contents` to a watchlist file named `name` of its own.
+fn write_watchlist(name: &str, contents: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pump_fun_monitor_watchlist_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn test_load_csv() {
+    let path = write_watchlist(
+        "plain.csv",
+        &format!("pubkey,label,tags\n# known devs\n{},serial dev,Dev; insider;dev\n\n{}, influencer \n", PUMP, SYSTEM),
+    );
+    let watchlist = Watchlist::load(&path).unwrap();
+    assert!(!watchlist.is_empty());
+    assert_eq!(watchlist.len(), 2);
+    assert_eq!(
+        watchlist.get(PUMP),
+        Some(&WalletLabel {
+            label: "serial dev".to_string(),
+            tags: vec!["dev".to_string(), "insider".to_string()],
+        })
+    );
+    assert_eq!(watchlist.get(SYSTEM).unwrap().label, "influencer");
+    assert!(watchlist.get(SYSTEM).unwrap().tags.is_empty());
+    assert_eq!(watchlist.get(TOKEN), None);
+}
+
+#[test]
+fn test_load_json() {
+    let path = write_watchlist(
+        "plain.json",
+        &format!(
+            "{{\n  \"{}\": {{ \"label\": \"serial dev\", \"tags\": [\"Insider\"] }},\n  \"{}\": \"influencer\"\n}}\n",
+            PUMP, SYSTEM
+        ),
+    );
+    let watchlist = Watchlist::load(&path).unwrap();
+    assert_eq!(watchlist.get(PUMP).unwrap().tags, vec!["insider"]);
+    assert_eq!(watchlist.get(SYSTEM).unwrap().label, "influencer");
+}
+
+#[test]
+fn test_csv_problems_name_their_lines() {
+    let path = write_watchlist(
+        "broken.csv",
+        &format!("{},serial dev\nnot-a-key,someone\n{},\n{},again\n{},tagged,dev;;\n", PUMP, SYSTEM, PUMP, TOKEN),
+    );
+    let problems = Watchlist::load(&path).unwrap_err();
+    assert_eq!(
+        problems,
+        vec![
+            "line 2: 'not-a-key' is not a base58 public key".to_string(),
+            format!("line 3: {} has no label", SYSTEM),
+            format!("line 4: {} is already listed on line 1", PUMP),
+            format!("line 5: {} has an empty tag", TOKEN),
+        ]
+    );
+}
+
+#[test]
+fn test_json_problems_name_their_lines() {
+    let path = write_watchlist(
+        "broken.json",
+        &format!("{{\n  \"{}\": \"serial dev\",\n  \"{}\": {{ \"tags\": [\"dev\"] }},\n  \"{}\": 7\n}}\n", PUMP, SYSTEM, TOKEN),
+    );
+    let problems = Watchlist::load(&path).unwrap_err();
+    assert_eq!(problems.len(), 2);
+    assert!(problems[0].starts_with(&format!("line 3: {} must map to a label", SYSTEM)), "{:?}", problems);
+    assert!(problems[1].starts_with(&format!("line 4: {} must map to a label", TOKEN)), "{:?}", problems);
+
+    let path = write_watchlist("unparsable.json", "{\n  \"key\": \n");
+    let problems = Watchlist::load(&path).unwrap_err();
+    assert!(problems[0].contains("not a JSON object of wallets"), "{:?}", problems);
+}
+
+#[test]
+fn test_missing_file_is_reported() {
+    let problems = Watchlist::load(Path::new("/nonexistent/watchlist.csv")).unwrap_err();
+    assert!(problems[0].starts_with("cannot be read"), "{:?}", problems);
+}
@@ -0,0 +1,74 @@
+//! Runtime inspection and management for admins.
+//!
+//! Admin-only client messages carry the server's `WS_ADMIN_TOKEN`; the process-wide pieces they reach beyond the
+//! WebSocket server itself (the monitor's connection state and the log filter) are handed in through `AdminContext`.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+
+use tokio::sync::watch;
+
+use crate::rpc_client::ConnectionStatus;
+
+/// replaces the process log filter; the argument uses `RUST_LOG` syntax.
+type ReloadLogFilter = dyn Fn(&str) -> std::result::Result<(), String> + Send + Sync;
+
+/// handle to the reloadable log filter installed at startup.
+#[derive(Clone)]
+pub struct LogLevelHandle {
+    reload: Arc<ReloadLogFilter>,
+}
+
+impl LogLevelHandle {
+    pub fn new(reload: impl Fn(&str) -> std::result::Result<(), String> + Send + Sync + 'static) -> Self {
+        Self { reload: Arc::new(reload) }
+    }
+
+    /// switches to the filter `level`, e.g. "debug" or "info,pump_fun_monitor_corrected=trace".
+    pub fn set(&self, level: &str) -> std::result::Result<(), String> {
+        (self.reload)(level)
+    }
+}
+
+impl fmt::Debug for LogLevelHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LogLevelHandle")
+    }
+}
+
+/// what admin messages can see and change outside the WebSocket server.
+///
+/// Everything is optional; the matching admin messages are answered with an error when a piece is missing.
+#[derive(Debug, Clone, Default)]
+pub struct AdminContext {
+    /// state of the Solana log subscription, or of the simulator
+    pub connection: Option<watch::Receiver<ConnectionStatus>>,
+    /// RPC endpoint to report, already stripped of credentials
+    pub rpc_endpoint: Option<String>,
+    pub log_level: Option<LogLevelHandle>,
+}
+
+impl AdminContext {
+    /// whether the monitor is connected, and seconds since its last log notification, as of `now`.
+    pub fn connection_state(&self, now: Instant) -> (bool, Option<u64>) {
+        let Some(connection) = &self.connection else {
+            return (false, None);
+        };
+        let connection = connection.borrow();
+        let age = connection
+            .last_notification_at
+            .map(|at| now.saturating_duration_since(at).as_secs());
+        (connection.connected, age)
+    }
+}
+
+/// scheme, host and port of `url`; RPC providers put API keys in the user info, path or query string.
+pub fn redact_endpoint(url: &str) -> String {
+    let parsed = url::Url::parse(url).ok();
+    match parsed.as_ref().and_then(|parsed| Some((parsed.scheme(), parsed.host_str()?, parsed.port()))) {
+        Some((scheme, host, Some(port))) => format!("{}://{}:{}", scheme, host, port),
+        Some((scheme, host, None)) => format!("{}://{}", scheme, host),
+        None => "<unparseable endpoint>".to_string(),
+    }
+}
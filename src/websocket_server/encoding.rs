@@ -0,0 +1,65 @@
+//! Wire encodings for events and notices sent to WebSocket clients.
+//!
+//! Each (encoding, compression) frame for a message is produced at most once per broadcast, and only the first time a client that wants it is reached, so no MessagePack or gzip work happens unless some client asked for it.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::warn;
+
+use crate::data_models::WireEncoding;
+
+type EncodeResult<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// the frames built so far for one event or server notice.
+pub struct EncodedEvent<'a, T> {
+    event: &'a T,
+    frames: HashMap<(WireEncoding, bool), Option<Message>>,
+}
+
+impl<'a, T: Serialize> EncodedEvent<'a, T> {
+    pub fn new(event: &'a T) -> Self {
+        Self {
+            event,
+            frames: HashMap::new(),
+        }
+    }
+
+    /// the frame for one client, or `None` if the message can't be encoded that way.
+    ///
+    /// JSON goes out as a text frame; MessagePack and anything gzipped go out as binary frames.
+    pub fn message(&mut self, encoding: WireEncoding, compressed: bool) -> Option<Message> {
+        let event = self.event;
+        self.frames
+            .entry((encoding, compressed))
+            .or_insert_with(|| match encode(event, encoding, compressed) {
+                Ok(message) => Some(message),
+                Err(e) => {
+                    warn!("Failed to encode message as {:?}: {}", encoding, e);
+                    None
+                }
+            })
+            .clone()
+    }
+}
+
+fn encode<T: Serialize>(event: &T, encoding: WireEncoding, compressed: bool) -> EncodeResult<Message> {
+    let message = match (encoding, compressed) {
+        (WireEncoding::Json, false) => Message::Text(serde_json::to_string(event)?),
+        (WireEncoding::Json, true) => Message::Binary(gzip(&serde_json::to_vec(event)?)?),
+        // named fields keep the payload self-describing, matching the JSON keys
+        (WireEncoding::Msgpack, false) => Message::Binary(rmp_serde::to_vec_named(event)?),
+        (WireEncoding::Msgpack, true) => Message::Binary(gzip(&rmp_serde::to_vec_named(event)?)?),
+    };
+    Ok(message)
+}
+
+fn gzip(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
@@ -0,0 +1,194 @@
+//! # Field Selection
+//!
+//! Clients that only need a few fields of every token event send `setFields` with a list of dot paths such as
+//! `token.name` or `pumpData.virtualSolReserves`, and get events pruned down to those fields.
+//!
+//! Pruning works on a `serde_json::Value` of the event, built once per event and only when a client with a selection
+//! is reached, so clients without one keep sharing the encoded frames. `eventType` is always kept so pruned events can
+//! still be told apart from notices.
+
+use serde_json::{Map, Value};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::warn;
+
+use crate::data_models::{Envelope, EventFormat, EventPayload, TokenCreatedEvent, WireEncoding};
+use crate::error::MonitorError;
+
+use super::encoding::EncodedEvent;
+
+/// every path of a token event a client can select; selecting an object selects everything in it.
+pub const EVENT_FIELDS: &[&str] = &[
+    "eventType",
+    "timestamp",
+    "sequence",
+    "transactionSignature",
+    "slot",
+    "programId",
+    "token",
+    "token.mintAddress",
+    "token.name",
+    "token.symbol",
+    "token.uri",
+    "token.creator",
+    "token.supply",
+    "token.decimals",
+    "token.mintAuthority",
+    "token.freezeAuthority",
+    "token.authoritiesStandard",
+    "token.sanitized",
+    "token.uriValid",
+    "token.raw",
+    "token.raw.name",
+    "token.raw.symbol",
+    "token.raw.uri",
+    "pumpData",
+    "pumpData.bondingCurve",
+    "pumpData.virtualSolReserves",
+    "pumpData.virtualTokenReserves",
+    "pumpData.realSolReserves",
+    "pumpData.realTokenReserves",
+    "pumpData.complete",
+    "pumpData.priceSol",
+    "pumpData.marketCapSol",
+    "pumpData.priceUsd",
+    "pumpData.marketCapUsd",
+    "metadata",
+    "metadata.description",
+    "metadata.image",
+    "metadata.imageUrl",
+    "metadata.metadataUrl",
+    "metadata.imageSha256",
+    "metadata.imageSeenBefore",
+    "metadata.imageFirstSeenMint",
+    "metadata.twitter",
+    "metadata.telegram",
+    "metadata.website",
+    "metadata.twitterIsCommunityHandle",
+    "metadata.websiteMissing",
+    "metadata.descriptionContainsUrls",
+    "metadata.socialsReused",
+    "initialBuy",
+    "initialBuy.solAmount",
+    "initialBuy.tokenAmount",
+    "sameTxBuyers",
+    "sameTxBuySol",
+    "txFeeLamports",
+    "priorityFeeLamports",
+    "computeUnits",
+    "creatorStats",
+    "creatorStats.tokensCreatedSeen",
+    "creatorStats.firstSeen",
+    "creatorStats.lastSeen",
+    "creatorStats.tokensGraduated",
+    "creatorStats.tokensAbandoned",
+    "creatorStats.graduationRate",
+    "creatorLabel",
+    "creatorTags",
+    "similarRecentCount",
+    "processingLatencyMs",
+    "chainLatencyMs",
+    "stale",
+    "signerPubkey",
+    "signature",
+];
+
+/// a client's validated field selection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSelection {
+    /// the selected paths split at the dots, without paths already covered by a selected parent
+    paths: Vec<Vec<String>>,
+}
+
+impl FieldSelection {
+    /// checks every path against `EVENT_FIELDS`, naming all the unknown ones at once.
+    pub fn new(fields: &[String]) -> Result<Self, MonitorError> {
+        let unknown: Vec<&str> = fields
+            .iter()
+            .map(String::as_str)
+            .filter(|field| !EVENT_FIELDS.contains(field))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(MonitorError::InvalidRequest(format!("unknown fields: {}", unknown.join(", "))));
+        }
+
+        let mut sorted: Vec<&String> = fields.iter().collect();
+        sorted.sort();
+        sorted.dedup();
+        let mut paths: Vec<Vec<String>> = Vec::new();
+        // a parent sorts before its children, so each path only has to be checked against the ones kept so far
+        for field in sorted {
+            let path: Vec<String> = field.split('.').map(str::to_string).collect();
+            if !paths.iter().any(|kept| path.starts_with(kept)) {
+                paths.push(path);
+            }
+        }
+        Ok(Self { paths })
+    }
+
+    /// the parts of `event` this selection covers, plus its `eventType`.
+    pub fn prune(&self, event: &Value) -> Value {
+        let mut pruned = Map::new();
+        if let Some(event_type) = event.get("eventType") {
+            pruned.insert("eventType".to_string(), event_type.clone());
+        }
+        for path in &self.paths {
+            copy_path(event, &mut pruned, path);
+        }
+        Value::Object(pruned)
+    }
+}
+
+/// copies the value at `path` in `source` into `target`, creating the objects on the way; absent fields are skipped.
+fn copy_path(source: &Value, target: &mut Map<String, Value>, path: &[String]) {
+    let Some((key, rest)) = path.split_first() else {
+        return;
+    };
+    let Some(value) = source.get(key) else {
+        return;
+    };
+    if rest.is_empty() {
+        target.insert(key.clone(), value.clone());
+        return;
+    }
+    if !value.is_object() {
+        return;
+    }
+    if let Value::Object(nested) = target.entry(key.clone()).or_insert_with(|| Value::Object(Map::new())) {
+        copy_path(value, nested, rest);
+    }
+}
+
+/// one event for the clients with a field selection; the JSON value is built the first time one is reached.
+pub struct SelectableEvent<'a> {
+    event: &'a TokenCreatedEvent,
+    format: EventFormat,
+    value: Option<Value>,
+}
+
+impl<'a> SelectableEvent<'a> {
+    pub fn new(event: &'a TokenCreatedEvent, format: EventFormat) -> Self {
+        Self { event, format, value: None }
+    }
+
+    /// the event pruned to `fields`, framed for one client; `None` if it can't be encoded.
+    pub fn message(&mut self, fields: &FieldSelection, encoding: WireEncoding, compressed: bool) -> Option<Message> {
+        if self.value.is_none() {
+            match serde_json::to_value(self.event) {
+                Ok(value) => self.value = Some(value),
+                Err(e) => {
+                    warn!("Failed to convert event {} for field selection: {}", self.event.sequence, e);
+                    return None;
+                }
+            }
+        }
+        let pruned = fields.prune(self.value.as_ref()?);
+        match self.format {
+            EventFormat::Flat => EncodedEvent::new(&pruned).message(encoding, compressed),
+            // the selection applies to `data`, the envelope stays whole
+            EventFormat::Envelope => {
+                let envelope = Envelope::new(TokenCreatedEvent::EVENT_TYPE, self.event.sequence, self.event.timestamp, &pruned);
+                EncodedEvent::new(&envelope).message(encoding, compressed)
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! # Replay History
+//!
+//! The most recently broadcast events, kept so a client that reconnects can resume from the last sequence it received.
+//! Sequences missing from a requested range, because they were evicted or lost to broadcast lag, are reported as gaps
+//! instead of being skipped silently. Without an archive, `query` requests are answered from the same buffer.
+
+use crate::cache::{BoundedCache, CacheLimits, CacheStats};
+use crate::data_models::TokenCreatedEvent;
+use crate::filtering::{CompiledFilter, MatchableEvent};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// one step of a replay, in sequence order.
+#[derive(Debug, Clone)]
+pub enum Replayed<'a> {
+    Event(&'a TokenCreatedEvent),
+    /// sequences `from..=to` can't be replayed
+    Gap { from: u64, to: u64 },
+}
+
+/// bounded buffer of broadcast events, oldest first.
+///
+pub struct History {
+    /// events by sequence
+    events: BoundedCache<u64, TokenCreatedEvent>,
+    latest: u64,
+    last_event_at: Option<DateTime<Utc>>,
+    /// every event broadcast from this time on is still buffered: the start, or the newest evicted event's time
+    covered_from: DateTime<Utc>,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: BoundedCache::new(CacheLimits::capacity(capacity)),
+            latest: 0,
+            last_event_at: None,
+            covered_from: Utc::now(),
+        }
+    }
+
+    /// records a broadcast event unless it already is, evicting the oldest one when full.
+    ///
+    /// Every broadcast worker records the events it delivers, so only the first one to get to an event adds it.
+    /// Events that never went through the sequencer can't be told apart or replayed, so only their time is kept.
+    pub fn record(&mut self, event: &TokenCreatedEvent) {
+        if event.sequence != 0 && event.sequence <= self.latest {
+            return;
+        }
+        self.last_event_at = Some(event.timestamp);
+        if event.sequence == 0 {
+            return;
+        }
+        self.latest = event.sequence;
+        let covered_from = &mut self.covered_from;
+        self.events.insert_evicting(event.sequence, event.clone(), Instant::now(), |_, evicted| {
+            *covered_from = (*covered_from).max(evicted.timestamp);
+        });
+    }
+
+    pub fn cache_stats(&self) -> Arc<CacheStats> {
+        self.events.stats()
+    }
+
+    /// timestamp of the most recently broadcast event.
+    pub fn last_event_at(&self) -> Option<DateTime<Utc>> {
+        self.last_event_at
+    }
+
+    /// highest sequence broadcast so far, 0 before the first event.
+    pub fn latest(&self) -> u64 {
+        self.latest
+    }
+
+    /// the buffered events with sequences `from..=through`, with a gap wherever a sequence is missing.
+    pub fn replay(&self, from: u64, through: u64) -> Vec<Replayed<'_>> {
+        // sequences start at 1; 0 marks an event that never went through the sequencer
+        let from = from.max(1);
+        let mut expected = from;
+        let mut replayed = Vec::new();
+        let events = self.events.iter().map(|(_, event)| event);
+        for event in events.filter(|event| event.sequence >= from && event.sequence <= through) {
+            if event.sequence > expected {
+                replayed.push(Replayed::Gap { from: expected, to: event.sequence - 1 });
+            }
+            replayed.push(Replayed::Event(event));
+            expected = event.sequence + 1;
+        }
+        if expected <= through {
+            replayed.push(Replayed::Gap { from: expected, to: through });
+        }
+        replayed
+    }
+
+    /// up to `limit` buffered events from `since` on that match `filter`, newest first.
+    pub fn query(&self, filter: &CompiledFilter, since: DateTime<Utc>, limit: usize) -> Vec<TokenCreatedEvent> {
+        self.events
+            .iter()
+            .rev()
+            .map(|(_, event)| event)
+            .filter(|event| event.timestamp >= since)
+            .filter(|event| filter.matches(&MatchableEvent::new(event)))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// the time from which on every broadcast event is buffered; earlier ones were never seen or already evicted.
+    pub fn covered_from(&self) -> DateTime<Utc> {
+        self.covered_from
+    }
+}
@@ -0,0 +1,116 @@
+//! Per-IP connection limits.
+//!
+//! Every IP gets a cap on concurrent connections and a token bucket for new connections, checked in the accept loop before any task is spawned so abusive peers cost almost nothing. State lives in a `DashMap` keyed by IP; entries with no open connections and a full bucket are dropped by a periodic cleanup.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use dashmap::DashMap;
+use thiserror::Error;
+use tokio::sync::watch;
+
+use crate::config::IpLimitConfig;
+
+/// why a connection was refused.
+#[derive(Error, Debug, Clone, Copy, PartialEq)]
+pub enum IpRejection {
+    #[error("already has {0} open connections")]
+    TooManyConnections(usize),
+    #[error("is opening connections too quickly")]
+    RateLimited,
+}
+
+#[derive(Debug)]
+struct IpEntry {
+    connections: usize,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl IpEntry {
+    fn refill(&mut self, now: Instant, config: &IpLimitConfig) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * config.connections_per_sec).min(config.burst);
+        self.last_refill = now;
+    }
+}
+
+/// tracks open connections and connection attempts per IP.
+#[derive(Debug)]
+pub struct IpLimiter {
+    /// the current limits; a config reload replaces them without touching the tracked IPs
+    limits: watch::Receiver<IpLimitConfig>,
+    entries: DashMap<IpAddr, IpEntry>,
+}
+
+/// an admitted connection; its IP's open connection count is decremented when dropped.
+#[derive(Debug)]
+pub struct IpPermit {
+    limiter: Arc<IpLimiter>,
+    ip: IpAddr,
+}
+
+impl Drop for IpPermit {
+    fn drop(&mut self) {
+        if let Some(mut entry) = self.limiter.entries.get_mut(&self.ip) {
+            entry.connections = entry.connections.saturating_sub(1);
+        }
+    }
+}
+
+impl IpLimiter {
+    pub fn new(config: IpLimitConfig) -> Self {
+        Self::watching(watch::channel(config).1)
+    }
+
+    /// a limiter that applies whatever limits `limits` currently holds.
+    pub fn watching(limits: watch::Receiver<IpLimitConfig>) -> Self {
+        Self {
+            limits,
+            entries: DashMap::new(),
+        }
+    }
+
+    /// Admits a new connection from `ip` if it is under both its connection cap and its rate limit.
+    pub fn try_acquire(self: &Arc<Self>, ip: IpAddr, now: Instant) -> Result<IpPermit, IpRejection> {
+        let config = self.limits.borrow().clone();
+        let mut entry = self.entries.entry(ip).or_insert_with(|| IpEntry {
+            connections: 0,
+            tokens: config.burst,
+            last_refill: now,
+        });
+        entry.refill(now, &config);
+
+        if entry.connections >= config.max_connections_per_ip {
+            return Err(IpRejection::TooManyConnections(entry.connections));
+        }
+        if entry.tokens < 1.0 {
+            return Err(IpRejection::RateLimited);
+        }
+        entry.tokens -= 1.0;
+        entry.connections += 1;
+        drop(entry);
+
+        Ok(IpPermit {
+            limiter: Arc::clone(self),
+            ip,
+        })
+    }
+
+    /// Forgets IPs with no open connections whose bucket has refilled, returning how many were removed.
+    pub fn cleanup(&self, now: Instant) -> usize {
+        let config = self.limits.borrow().clone();
+        let before = self.entries.len();
+        self.entries.retain(|_, entry| {
+            entry.refill(now, &config);
+            entry.connections > 0 || entry.tokens < config.burst
+        });
+        before - self.entries.len()
+    }
+
+    /// number of IPs currently tracked.
+    pub fn tracked_ips(&self) -> usize {
+        self.entries.len()
+    }
+}
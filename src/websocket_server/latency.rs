@@ -0,0 +1,118 @@
+//! # Send Latency
+//!
+//! How long a client's frames take from being queued to `ws_sender.send` completing, which is where a slow consumer
+//! shows: its socket fills up, the writer waits on it, and everything behind waits in the queue. The latest
+//! `SEND_LATENCY_SAMPLES` are kept per client for the p50 and p95 `listClients` reports, and every one also goes to a
+//! Prometheus histogram labelled with the name the client gave with `identify`, or with its address until it does.
+//! Series labelled with an address are removed when the client disconnects; named ones carry on across reconnects.
+
+use super::listener::PeerAddr;
+use crate::metrics::{LatencyHistogram, Metrics};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_tungstenite::tungstenite::Message;
+
+/// latest sends each client's percentiles are taken over.
+const SEND_LATENCY_SAMPLES: usize = 256;
+
+/// longest name `identify` accepts.
+pub(crate) const MAX_CLIENT_NAME_CHARS: usize = 64;
+
+/// a frame in a client's outgoing queue, with when it was queued.
+#[derive(Debug)]
+pub(crate) struct Queued {
+    pub message: Message,
+    pub queued_at: Instant,
+}
+
+impl Queued {
+    pub fn new(message: Message) -> Self {
+        Self {
+            message,
+            queued_at: Instant::now(),
+        }
+    }
+}
+
+/// the send latencies of one client, and the histogram series they are exported under.
+#[derive(Debug)]
+pub(crate) struct SendLatency {
+    metrics: Arc<Metrics>,
+    addr: PeerAddr,
+    state: Mutex<LatencyState>,
+}
+
+#[derive(Debug)]
+struct LatencyState {
+    /// the name given with `identify`; the series is labelled with the address while `None`
+    name: Option<String>,
+    samples: VecDeque<Duration>,
+    histogram: Arc<LatencyHistogram>,
+}
+
+impl SendLatency {
+    pub fn new(metrics: Arc<Metrics>, addr: PeerAddr) -> Self {
+        let histogram = metrics.ws_send_latency(&addr.to_string());
+        Self {
+            metrics,
+            addr,
+            state: Mutex::new(LatencyState {
+                name: None,
+                samples: VecDeque::with_capacity(SEND_LATENCY_SAMPLES),
+                histogram,
+            }),
+        }
+    }
+
+    /// records a frame written `latency` after it was queued.
+    pub fn record(&self, latency: Duration) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.samples.len() == SEND_LATENCY_SAMPLES {
+            state.samples.pop_front();
+        }
+        state.samples.push_back(latency);
+        state.histogram.observe(u64::try_from(latency.as_millis()).unwrap_or(u64::MAX));
+    }
+
+    /// exports later sends under `name`, dropping the series labelled with the address.
+    pub fn identify(&self, name: String) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.name.is_none() {
+            self.metrics.remove_ws_send_latency(&self.addr.to_string());
+        }
+        state.histogram = self.metrics.ws_send_latency(&name);
+        state.name = Some(name);
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).name.clone()
+    }
+
+    /// the median and 95th percentile of the latest sends in milliseconds; `None` before the first.
+    pub fn percentiles(&self) -> Option<(f64, f64)> {
+        let mut samples: Vec<Duration> = {
+            let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            state.samples.iter().copied().collect()
+        };
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_unstable();
+        // nearest rank: the smallest sample at least `percent` of them don't exceed
+        let percentile = |percent: usize| {
+            let rank = (samples.len() * percent).div_ceil(100).max(1);
+            samples[rank - 1].as_nanos() as f64 / 1_000_000.0
+        };
+        Some((percentile(50), percentile(95)))
+    }
+}
+
+impl Drop for SendLatency {
+    fn drop(&mut self) {
+        let state = self.state.get_mut().unwrap_or_else(|e| e.into_inner());
+        if state.name.is_none() {
+            self.metrics.remove_ws_send_latency(&self.addr.to_string());
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! # Listener
+//!
+//! The accept side of the server, behind a trait so tests can inject the errors a real listener hits under load:
+//! `EMFILE` once the process runs out of file descriptors, or a connection reset before it could be accepted. Those
+//! are retried with a short backoff; only a listener that is no longer bound stops the server.
+//!
+//! Connections come in over TCP, or over a Unix socket for clients on the same host (see `unix_socket`). Unix socket
+//! peers have no address of their own, so each is numbered in the order it was accepted.
+
+use crate::config::ListenAddr;
+use async_trait::async_trait;
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+
+/// first pause after a failed accept, doubled for every further failure in a row.
+const ACCEPT_BACKOFF_INITIAL: Duration = Duration::from_millis(5);
+
+/// longest pause between accept attempts.
+const ACCEPT_BACKOFF_MAX: Duration = Duration::from_secs(1);
+
+/// a connection's byte stream, whichever kind of socket it came in on.
+pub trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send> Stream for S {}
+
+/// where a client is connected from, as it is keyed in the client registry and shown to admins.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PeerAddr {
+    Tcp(SocketAddr),
+    /// the n-th connection accepted on the Unix socket, counting from 1
+    Unix(u64),
+}
+
+impl PeerAddr {
+    /// the IP the per-IP limits apply to; `None` for Unix socket peers.
+    pub fn ip(&self) -> Option<IpAddr> {
+        match self {
+            Self::Tcp(addr) => Some(addr.ip()),
+            Self::Unix(_) => None,
+        }
+    }
+}
+
+impl From<SocketAddr> for PeerAddr {
+    fn from(addr: SocketAddr) -> Self {
+        Self::Tcp(addr)
+    }
+}
+
+impl fmt::Display for PeerAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{}", addr),
+            Self::Unix(id) => write!(f, "unix:{}", id),
+        }
+    }
+}
+
+impl FromStr for PeerAddr {
+    type Err = String;
+
+    /// reads the form `Display` writes: `ip:port`, or `unix:n`.
+    fn from_str(s: &str) -> Result<Self, String> {
+        let parsed = match s.strip_prefix("unix:") {
+            Some(id) => id.parse().ok().map(Self::Unix),
+            None => s.parse().ok().map(Self::Tcp),
+        };
+        parsed.ok_or_else(|| format!("'{}' is not an ip:port or unix:n address", s))
+    }
+}
+
+/// source of incoming connections.
+///
+#[async_trait]
+pub trait Listener: Send + Sync {
+    async fn accept(&self) -> io::Result<(Box<dyn Stream>, PeerAddr)>;
+
+    fn local_addr(&self) -> io::Result<ListenAddr>;
+}
+
+#[async_trait]
+impl Listener for TcpListener {
+    async fn accept(&self) -> io::Result<(Box<dyn Stream>, PeerAddr)> {
+        let (stream, addr) = TcpListener::accept(self).await?;
+        Ok((Box::new(stream), PeerAddr::Tcp(addr)))
+    }
+
+    fn local_addr(&self) -> io::Result<ListenAddr> {
+        TcpListener::local_addr(self).map(ListenAddr::Tcp)
+    }
+}
+
+/// whether `error` from `accept` means the listener can't accept anything anymore.
+///
+/// `EINVAL` is what a socket that stopped listening reports, and a listener whose own address can't be read has lost
+/// its socket altogether. Everything else concerns a single connection or a resource that frees up again.
+pub fn is_fatal(listener: &dyn Listener, error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::InvalidInput || listener.local_addr().is_err()
+}
+
+/// exponential pause between accept attempts while they keep failing.
+///
+#[derive(Debug, Default)]
+pub struct AcceptBackoff {
+    delay: Option<Duration>,
+}
+
+impl AcceptBackoff {
+    /// records a failure and returns how long to wait before accepting again.
+    pub fn failed(&mut self) -> Duration {
+        let delay = self
+            .delay
+            .map_or(ACCEPT_BACKOFF_INITIAL, |delay| (delay * 2).min(ACCEPT_BACKOFF_MAX));
+        self.delay = Some(delay);
+        delay
+    }
+
+    pub fn succeeded(&mut self) {
+        self.delay = None;
+    }
+}
@@ -3,147 +3,1514 @@
 //! # architecture
 //! the server maintains a list of connected clients, each with their own filter criteria.
 //! when a token creation event is received, it's checked against each client's filter and only sent to clients where the event matches their criteria.
+//! clients are split into shards by address, each served by a broadcast worker with its own receiver, so filtering and sending for thousands of clients runs in parallel.
+//! clients choose how events are framed (JSON text or MessagePack binary, optionally gzipped); each frame variant is built at most once per event and only when some client needs it.
+//! admins holding the server's token can list and kick clients, read the monitor's state and change the log filter at runtime.
+//! clients in raw mode also get the fetched transaction behind each matching event, as a `rawTransaction` message right after it, when the server keeps raw transactions.
+//! every client gets a `heartbeat` with the server's status at an interval it can change or turn off with `setHeartbeat`.
+//! clients that sent `setCurveUpdates` also get the `curveUpdate` messages of every tracked bonding curve, when curve tracking is on.
+//! clients that sent `setPreviews` also get a `tokenCreatedPreview` of every creation ahead of its `tokenCreated`, when `FAST_PATH` is on.
+//! image hashes that weren't ready when a token was broadcast follow as `tokenEnriched` messages to the clients that got the token, when image hash follow-ups are on.
+//! every client gets a `tokenMigrated` message when a completed curve moves into an AMM pool, when migrations are tracked.
+//! admins that sent `setDiagnostics` also get a `diagnostic` for every transaction the monitor skipped or failed on, when the monitor publishes them.
+//! clients can apply a filter preset from the server's configuration by name with `usePreset`, reloaded with the rest of it.
+//! every client is sent a resume token; reconnecting with it within the session TTL restores its settings and the events it missed, see `sessions`.
+//! clients can `query` the tokens created since a point in time, answered from the archive when the server has one and from the replay buffer otherwise.
+//! clients can cap the matching events they get a second with `setRateLimit`, dropping the rest or getting a `summary` of them every second.
+//! clients can pick the event fields they need with `setFields`; only their events are pruned, everyone else shares the encoded frames.
+//! every client's send latency is tracked for `listClients` and `/metrics`, under the name it gave with `identify`.
+//! browser handshakes can be limited to the origins in `WS_ALLOWED_ORIGINS`, see `origins`.
+//! clients on the same host can connect over a Unix socket instead of TCP, with `WEBSOCKET_SERVER_HOST=unix:/path/to/monitor.sock`.
 
-use std::net::SocketAddr;
-use std::sync::Arc;
-use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{broadcast, Mutex};
-use tokio_tungstenite::{accept_async, tungstenite::Message};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{broadcast, watch, Mutex, Semaphore};
+use tokio_tungstenite::accept_hdr_async;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
 use futures_util::{SinkExt, StreamExt};
-use log::{info, warn, error};
-use serde_json;
+use tracing::{debug, error, info, warn};
+use serde::Serialize;
 
-use crate::data_models::{TokenCreatedEvent, FilterCriteria, ClientMessage};
+use crate::config::{
+    IpLimitConfig, ListenAddr, ServerConfig, SupervisorConfig, DEFAULT_HEARTBEAT_SECS, MAX_HEARTBEAT_SECS, MIN_HEARTBEAT_SECS,
+};
+use crate::error::MonitorError;
+use crate::metrics::Metrics;
+use crate::supervisor::{supervise, TaskExit};
+use crate::data_models::{
+    ClientDetails, ClientMessage, ClientStatsReport, CurveUpdateEvent, DiagnosticEvent, EventFormat, EventPayload, FilterCriteria,
+    MonitorStatusReport, RateLimitOverflow, ServerMessage, TokenCreatedEvent, TokenCreatedPreviewEvent, TokenEnrichedEvent,
+    TokenMigratedEvent, WireEncoding,
+};
+use crate::filtering::{merge_filters, normalize_filter, CompiledFilter, MatchableEvent};
+pub use admin::{redact_endpoint, AdminContext, LogLevelHandle};
+use encoding::EncodedEvent;
+use fields::{FieldSelection, SelectableEvent};
+use history::{History, Replayed};
+use ip_limits::IpLimiter;
+use latency::{Queued, SendLatency, MAX_CLIENT_NAME_CHARS};
+use listener::{AcceptBackoff, Listener, PeerAddr};
+use origins::RejectionLog;
+pub use origins::{AllowedOrigin, OriginPolicy};
+pub use query::EventArchive;
+use query::MAX_CLIENT_QUERIES;
+use rate_limit::{RateLimit, SUMMARY_INTERVAL};
+use sessions::{SessionSettings, Sessions};
+use stats::ClientStats;
+use tls::TlsAcceptorHandle;
+#[cfg(unix)]
+use unix_socket::UnixSocketListener;
 
-type ClientTx = tokio::sync::mpsc::UnboundedSender<Message>;
+mod admin;
+mod encoding;
+mod fields;
+mod history;
+mod ip_limits;
+mod latency;
+mod listener;
+mod origins;
+mod query;
+mod rate_limit;
+mod sessions;
+mod stats;
+mod tls;
+#[cfg(unix)]
+mod unix_socket;
+
+/// how often the TLS certificate files are checked for changes.
+const TLS_RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+
+/// how often idle per-IP limiter entries are dropped.
+const IP_LIMIT_CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+type ClientTx = tokio::sync::mpsc::Sender<Queued>;
 
 
 /// each client maintains its own connection state and filter criteria,
 struct Client {
-    addr: SocketAddr,
+    addr: PeerAddr,
     tx: ClientTx,
-    filter: Arc<Mutex<FilterCriteria>>,
+    filter: Mutex<CompiledFilter>,
+    encoding: Mutex<WireEncoding>,
+    /// the event fields this client asked for with `setFields`; full events while `None`
+    fields: Mutex<Option<FieldSelection>>,
+    /// receive events gzipped in binary frames
+    compression: AtomicBool,
+    /// follow each event with its raw transaction
+    raw_mode: AtomicBool,
+    /// receive `curveUpdate` messages
+    curve_updates: AtomicBool,
+    /// receive `tokenCreatedPreview` messages
+    previews: AtomicBool,
+    /// receive `diagnostic` messages; only ever set for admins
+    diagnostics: AtomicBool,
+    /// the limit on matching events this client set with `setRateLimit`; unlimited while `None`
+    rate_limit: Mutex<Option<RateLimit>>,
+    /// interval of this client's heartbeats, `None` while they are off; dropping it stops the heartbeat task
+    heartbeat: watch::Sender<Option<Duration>>,
+    stats: ClientStats,
+    /// how long its frames take from the queue to the socket, with the name it gave
+    latency: SendLatency,
+    /// latest sequence broadcast when the client registered; every later event reaches it live
+    joined_after: AtomicU64,
+    /// one permit per `query` the client may have running
+    queries: Arc<Semaphore>,
+    /// the token its settings are kept under once it disconnects; `None` when the server keeps no sessions
+    session: Option<String>,
+    /// kicked by an admin, so its session isn't kept
+    kicked: AtomicBool,
 }
 
-/// starts the WebSocket server and handles client connections.
-///
-/// # arguments
-/// * `addr` - the address to bind the server to (e.g., "127.0.0.1:8080")
-/// * `mut event_receiver` - broadcast receiver for token creation events
+impl Client {
+    /// queues a frame for the connection's writer task; `false` once the connection is gone.
+    ///
+    /// A frame that finds the queue full is dropped and counted: the client misses it, but a slow reader never holds
+    /// up the broadcast.
+    fn send(&self, message: Message) -> bool {
+        // counted first so the writer can never get ahead of the count
+        self.stats.record_queued();
+        match self.tx.try_send(Queued::new(message)) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                self.stats.record_overflow();
+                true
+            }
+            Err(TrySendError::Closed(_)) => false,
+        }
+    }
+
+    /// whether a matching event for `symbol` is within this client's rate limit.
+    async fn admits(&self, symbol: &str) -> bool {
+        match self.rate_limit.lock().await.as_mut() {
+            Some(limit) => limit.admit(symbol, tokio::time::Instant::now()),
+            None => true,
+        }
+    }
+
+    /// limits this client to `max_per_second` matching events a second, or lifts the limit when it is 0.
+    async fn set_rate_limit(&self, max_per_second: u32, overflow: RateLimitOverflow) {
+        let limit = (max_per_second > 0).then(|| RateLimit::new(max_per_second, overflow, tokio::time::Instant::now()));
+        *self.rate_limit.lock().await = limit;
+    }
+
+    /// takes the settings a resumed session restores, leaving the defaults behind.
+    ///
+    /// Each lock is released before the next is taken, as `send_event` takes two of them in another order.
+    async fn take_settings(&self) -> SessionSettings {
+        let filter = std::mem::take(&mut *self.filter.lock().await);
+        let encoding = *self.encoding.lock().await;
+        let fields = self.fields.lock().await.take();
+        let rate_limit = self.rate_limit.lock().await.take();
+        SessionSettings {
+            filter,
+            encoding,
+            fields,
+            compression: self.compression.load(Ordering::Relaxed),
+            raw_mode: self.raw_mode.load(Ordering::Relaxed),
+            curve_updates: self.curve_updates.load(Ordering::Relaxed),
+            previews: self.previews.load(Ordering::Relaxed),
+            rate_limit,
+            heartbeat: *self.heartbeat.borrow(),
+            name: self.latency.name(),
+        }
+    }
+
+    /// replaces this client's settings with those of a resumed session.
+    async fn restore(&self, settings: SessionSettings) {
+        *self.filter.lock().await = settings.filter;
+        *self.encoding.lock().await = settings.encoding;
+        *self.fields.lock().await = settings.fields;
+        self.compression.store(settings.compression, Ordering::Relaxed);
+        self.raw_mode.store(settings.raw_mode, Ordering::Relaxed);
+        self.curve_updates.store(settings.curve_updates, Ordering::Relaxed);
+        self.previews.store(settings.previews, Ordering::Relaxed);
+        *self.rate_limit.lock().await = settings.rate_limit;
+        self.heartbeat.send_replace(settings.heartbeat);
+        if let Some(name) = settings.name {
+            self.latency.identify(name);
+        }
+    }
+
+    /// queues a replayed event for this client in `format`, pruned to its field selection.
+    async fn send_event(&self, event: &TokenCreatedEvent, format: EventFormat) {
+        let fields = self.fields.lock().await;
+        match fields.as_ref() {
+            Some(fields) => {
+                let encoding = *self.encoding.lock().await;
+                let compressed = self.compression.load(Ordering::Relaxed);
+                if let Some(message) = SelectableEvent::new(event, format).message(fields, encoding, compressed) {
+                    self.send(message);
+                }
+            }
+            None => self.send_notice(&event.formatted(format)).await,
+        }
+    }
+
+    /// queues a server notice, or a replayed event, for this client in its current encoding.
+    async fn send_notice(&self, notice: &impl Serialize) {
+        let encoding = *self.encoding.lock().await;
+        if let Some(message) = EncodedEvent::new(notice).message(encoding, self.compression.load(Ordering::Relaxed)) {
+            self.send(message);
+        }
+    }
+}
+
+/// state shared by the accept loop, the broadcast workers and every connection task.
+struct ServerState {
+    config: ServerConfig,
+    /// connected clients, one list per broadcast worker; a client's shard follows from its address
+    shards: Vec<Mutex<Vec<Arc<Client>>>>,
+    /// recent events for `resumeFrom`; always locked before a shard
+    history: Mutex<History>,
+    metrics: Arc<Metrics>,
+    admin: AdminContext,
+    /// sessions of disconnected clients, kept for `resume`; `None` when the server keeps none
+    sessions: Option<Mutex<Sessions>>,
+    /// filter presets by name, replaced whenever the configuration is reloaded
+    presets: watch::Receiver<Arc<BTreeMap<String, FilterCriteria>>>,
+    /// whether curve updates are published, so clients may opt into them
+    curve_tracking: bool,
+    /// whether the monitor publishes previews, so clients may opt into them
+    previews: bool,
+    /// whether the monitor publishes diagnostics, so admins may opt into them
+    diagnostics: bool,
+    /// past events for `query`; the replay buffer answers without it
+    archive: Option<Arc<dyn EventArchive>>,
+    /// handshakes refused for their origin, logged sparingly
+    rejected_origins: std::sync::Mutex<RejectionLog>,
+}
+
+/// a reserved connection slot; the client gauge is decremented when it is dropped.
+struct ClientSlot {
+    metrics: Arc<Metrics>,
+}
+
+impl Drop for ClientSlot {
+    fn drop(&mut self) {
+        self.metrics.ws_clients.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl ServerState {
+    fn new(
+        config: ServerConfig,
+        metrics: Arc<Metrics>,
+        admin: AdminContext,
+        curve_tracking: bool,
+        previews: bool,
+        diagnostics: bool,
+        archive: Option<Arc<dyn EventArchive>>,
+    ) -> Self {
+        let history = History::new(config.replay_capacity);
+        metrics.register_cache("replay", history.cache_stats());
+        Self {
+            shards: (0..config.broadcast_shards.max(1)).map(|_| Mutex::new(Vec::new())).collect(),
+            history: Mutex::new(history),
+            sessions: config.sessions.clone().map(|sessions| Mutex::new(Sessions::new(sessions))),
+            // fixed presets unless the server is given reloaded ones
+            presets: watch::channel(Arc::clone(&config.presets)).1,
+            config,
+            metrics,
+            admin,
+            curve_tracking,
+            previews,
+            diagnostics,
+            archive,
+            rejected_origins: std::sync::Mutex::default(),
+        }
+    }
+
+    /// the shard serving the client connected from `addr`.
+    fn shard_of(&self, addr: PeerAddr) -> usize {
+        let mut hasher = DefaultHasher::new();
+        addr.hash(&mut hasher);
+        (hasher.finish() % self.shards.len() as u64) as usize
+    }
+
+    /// every connected client, across all shards; each shard is only locked while cloning its handles.
+    async fn clients(&self) -> Vec<Arc<Client>> {
+        let mut clients = Vec::new();
+        for shard in &self.shards {
+            clients.extend(shard.lock().await.iter().cloned());
+        }
+        clients
+    }
+
+    /// reserves a connection slot unless `max_clients` connections are already open.
+    ///
+    /// the gauge is incremented atomically before the client is registered, so concurrent handshakes can't overshoot the limit.
+    fn try_reserve_slot(&self) -> Option<ClientSlot> {
+        self.metrics
+            .ws_clients
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                (count < self.config.max_clients).then_some(count + 1)
+            })
+            .ok()?;
+        Some(ClientSlot {
+            metrics: Arc::clone(&self.metrics),
+        })
+    }
+
+    /// registers a client with its shard under the history lock.
+    ///
+    /// Workers record an event in the history before delivering it, so every event after `joined_after` reaches the
+    /// client live, and a worker that is behind skips the ones up to it, which `resumeFrom` replays.
+    async fn add_client(&self, client: Arc<Client>) {
+        let history = self.history.lock().await;
+        client.joined_after.store(history.latest(), Ordering::Relaxed);
+        self.shards[self.shard_of(client.addr)].lock().await.push(client);
+    }
+
+    async fn remove_clients(&self, addrs: &[PeerAddr]) {
+        for &addr in addrs {
+            let mut locked_clients = self.shards[self.shard_of(addr)].lock().await;
+            locked_clients.retain(|client| client.addr != addr);
+        }
+    }
+
+    /// stats for every connected client.
+    async fn client_stats(&self) -> Vec<ClientStatsReport> {
+        let clients = self.clients().await;
+        clients.iter().map(|client| client.stats.report(client.addr)).collect()
+    }
+
+    /// every connected client with its filter and queue depth.
+    async fn client_details(&self) -> Vec<ClientDetails> {
+        let clients = self.clients().await;
+        let mut details = Vec::with_capacity(clients.len());
+        for client in clients {
+            let percentiles = client.latency.percentiles();
+            details.push(ClientDetails {
+                addr: client.addr.to_string(),
+                name: client.latency.name(),
+                connected_at: client.stats.connected_at(),
+                filter: client.filter.lock().await.criteria().clone(),
+                queue_depth: client.stats.queue_depth(),
+                send_latency_p50_ms: percentiles.map(|(p50, _)| p50),
+                send_latency_p95_ms: percentiles.map(|(_, p95)| p95),
+            });
+        }
+        details
+    }
+
+    /// sends the client connected from `addr` a close frame and stops broadcasting to it; `false` if there is none.
+    async fn kick_client(&self, addr: PeerAddr) -> bool {
+        let kicked = {
+            let mut clients = self.shards[self.shard_of(addr)].lock().await;
+            let position = clients.iter().position(|client| client.addr == addr);
+            position.map(|position| clients.remove(position))
+        };
+        match kicked {
+            Some(client) => {
+                client.kicked.store(true, Ordering::Relaxed);
+                client.send(Message::Close(None));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// the monitor's state as far as this server can see it.
+    async fn monitor_status(&self) -> MonitorStatusReport {
+        let (rpc_connected, last_notification_age_secs) = self.admin.connection_state(Instant::now());
+        MonitorStatusReport {
+            rpc_endpoint: self.admin.rpc_endpoint.clone(),
+            rpc_connected,
+            last_event_at: self.history.lock().await.last_event_at(),
+            last_notification_age_secs,
+            processor_queue_depth: self.metrics.processor_queue_depth.load(Ordering::Relaxed) as u64,
+            dedupe_cache_size: self.metrics.mint_dedupe_entries.load(Ordering::Relaxed) as u64,
+        }
+    }
+
+    /// the server's status as sent in heartbeats.
+    async fn heartbeat(&self) -> ServerMessage {
+        let (rpc_connected, _) = self.admin.connection_state(Instant::now());
+        let now = chrono::Utc::now();
+        let last_event_at = self.history.lock().await.last_event_at();
+        ServerMessage::Heartbeat {
+            timestamp: now,
+            last_event_age_secs: last_event_at.map(|at| (now - at).num_seconds().max(0) as u64),
+            rpc_connected,
+        }
+    }
+
+    /// whether `token` is the admin token; always `false` when the server has none.
+    fn is_admin(&self, token: &str) -> bool {
+        self.config
+            .admin_token
+            .as_deref()
+            .is_some_and(|admin_token| constant_time_eq(admin_token.as_bytes(), token.as_bytes()))
+    }
+}
+
+/// a bound WebSocket server that is not yet accepting connections.
 ///
-/// # returns
-/// * `Result<(), Box<dyn std::error::Error>>` - Ok if server starts successfully
-pub async fn start_websocket_server(
-    addr: &str,
-    mut event_receiver: broadcast::Receiver<TokenCreatedEvent>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let listener = TcpListener::bind(addr).await?;
-    info!("🚀 WebSocket server listening on {}", addr);
-
-    let clients: Arc<Mutex<Vec<Arc<Client>>>> = Arc::new(Mutex::new(Vec::new()));
-    let broadcast_clients = Arc::clone(&clients);
+/// binding separately from running lets the caller learn the actual listen address, which matters when an ephemeral port (0) was requested.
+pub struct WebSocketServer {
+    listener: Box<dyn Listener>,
+    local_addr: ListenAddr,
+    config: ServerConfig,
+    tls: Option<Arc<TlsAcceptorHandle>>,
+    admin: AdminContext,
+    /// per-IP limits that replace `config.ip_limits` whenever the configuration is reloaded
+    ip_limits: Option<watch::Receiver<IpLimitConfig>>,
+    /// filter presets that replace `config.presets` whenever the configuration is reloaded
+    presets: Option<watch::Receiver<Arc<BTreeMap<String, FilterCriteria>>>>,
+    /// channel of the curve tracker, when curve tracking is on
+    curve_updates: Option<broadcast::Sender<CurveUpdateEvent>>,
+    /// creations decoded from their log notification by the monitor, when `FAST_PATH` is on
+    previews: Option<broadcast::Sender<TokenCreatedPreviewEvent>>,
+    /// late image hashes from the monitor, when image hash follow-ups are on
+    enrichments: Option<broadcast::Sender<TokenEnrichedEvent>>,
+    /// migrations of completed curves from the monitor, when migrations are tracked
+    migrations: Option<broadcast::Sender<TokenMigratedEvent>>,
+    /// skipped and failed transactions from the monitor, when it publishes diagnostics
+    diagnostics: Option<broadcast::Sender<DiagnosticEvent>>,
+    /// past events for `query`, when the server has an archive
+    archive: Option<Arc<dyn EventArchive>>,
+}
 
-    tokio::spawn(async move {
+impl WebSocketServer {
+    /// binds the listener to the configured address, a TCP address or a Unix socket path.
+    ///
+    /// # arguments
+    /// * `config` - server settings, including the address to bind to
+    pub async fn bind(config: &ServerConfig) -> std::io::Result<Self> {
+        match &config.bind_addr {
+            ListenAddr::Tcp(addr) => Self::from_listener(TcpListener::bind(addr).await?, config),
+            #[cfg(unix)]
+            ListenAddr::Unix(path) => Self::from_listener(UnixSocketListener::bind(path).await?, config),
+            #[cfg(not(unix))]
+            ListenAddr::Unix(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Unix sockets are not supported on this platform",
+            )),
+        }
+    }
+
+    /// wraps an already bound listener.
+    ///
+    /// # arguments
+    /// * `listener` - source of incoming connections; `config.bind_addr` is not used
+    /// * `config` - the remaining server settings
+    pub fn from_listener(listener: impl Listener + 'static, config: &ServerConfig) -> std::io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        if let (Some(requested), Some(bound)) = (config.bind_addr.tcp(), local_addr.tcp()) {
+            if requested.port() == 0 {
+                info!("Ephemeral port requested, bound to port {}", bound.port());
+            }
+        }
+        let tls = config.tls.as_ref().map(TlsAcceptorHandle::load).transpose()?;
+        Ok(Self {
+            listener: Box::new(listener),
+            local_addr,
+            config: config.clone(),
+            tls,
+            admin: AdminContext::default(),
+            ip_limits: None,
+            presets: None,
+            curve_updates: None,
+            previews: None,
+            enrichments: None,
+            migrations: None,
+            diagnostics: None,
+            archive: None,
+        })
+    }
+
+    /// gives admin messages access to the monitor's state and the log filter.
+    pub fn with_admin(mut self, admin: AdminContext) -> Self {
+        self.admin = admin;
+        self
+    }
+
+    /// takes the per-IP limits from `ip_limits` instead of the fixed ones in the config, so reloads apply to new connections.
+    pub fn with_ip_limits(mut self, ip_limits: watch::Receiver<IpLimitConfig>) -> Self {
+        self.ip_limits = Some(ip_limits);
+        self
+    }
+
+    /// takes the filter presets from `presets` instead of the fixed ones in the config, so reloads apply to the next
+    /// `usePreset`.
+    pub fn with_presets(mut self, presets: watch::Receiver<Arc<BTreeMap<String, FilterCriteria>>>) -> Self {
+        self.presets = Some(presets);
+        self
+    }
+
+    /// lets clients opt into the updates published on `curve_updates` with `setCurveUpdates`.
+    pub fn with_curve_updates(mut self, curve_updates: broadcast::Sender<CurveUpdateEvent>) -> Self {
+        self.curve_updates = Some(curve_updates);
+        self
+    }
+
+    /// lets clients opt into the previews published on `previews` with `setPreviews`.
+    pub fn with_previews(mut self, previews: broadcast::Sender<TokenCreatedPreviewEvent>) -> Self {
+        self.previews = Some(previews);
+        self
+    }
+
+    /// sends the `tokenEnriched` messages published on `enrichments` to the clients whose filter matched their token.
+    pub fn with_enrichments(mut self, enrichments: broadcast::Sender<TokenEnrichedEvent>) -> Self {
+        self.enrichments = Some(enrichments);
+        self
+    }
+
+    /// sends the `tokenMigrated` messages published on `migrations` to every client.
+    pub fn with_migrations(mut self, migrations: broadcast::Sender<TokenMigratedEvent>) -> Self {
+        self.migrations = Some(migrations);
+        self
+    }
+
+    /// lets admins opt into the `diagnostic` messages published on `diagnostics` with `setDiagnostics`.
+    pub fn with_diagnostics(mut self, diagnostics: broadcast::Sender<DiagnosticEvent>) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+
+    /// answers `query` requests from `archive` instead of the replay buffer.
+    pub fn with_archive(mut self, archive: Arc<dyn EventArchive>) -> Self {
+        self.archive = Some(archive);
+        self
+    }
+
+    /// the address the server is actually listening on.
+    pub fn local_addr(&self) -> ListenAddr {
+        self.local_addr.clone()
+    }
+
+    /// the URL scheme clients should use, `wss` when TLS is configured.
+    pub fn scheme(&self) -> &'static str {
+        if self.tls.is_some() { "wss" } else { "ws" }
+    }
+
+    /// runs the server and handles client connections.
+    ///
+    /// # arguments
+    /// * `event_receiver` - broadcast receiver for token creation events
+    /// * `metrics` - process metrics; the client gauge and rejection counter are kept up to date
+    ///
+    /// # returns
+    /// * `Result<(), Box<dyn std::error::Error>>` - an error once the listener is no longer bound; failures to accept a
+    ///   single connection are logged and retried
+    pub async fn run(
+        self,
+        event_receiver: broadcast::Receiver<TokenCreatedEvent>,
+        metrics: Arc<Metrics>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.run_until(event_receiver, metrics, std::future::pending()).await
+    }
+
+    /// runs the server like `run`, binding the configured address again whenever the listener is lost.
+    ///
+    /// every restart resubscribes to `event_receiver`'s channel; clients connected at the time have to reconnect.
+    pub async fn run_supervised(
+        self,
+        event_receiver: broadcast::Receiver<TokenCreatedEvent>,
+        metrics: Arc<Metrics>,
+        supervisor: &SupervisorConfig,
+    ) -> TaskExit {
+        let config = self.config.clone();
+        let admin = self.admin.clone();
+        let ip_limits = self.ip_limits.clone();
+        let presets = self.presets.clone();
+        let curve_updates = self.curve_updates.clone();
+        let previews = self.previews.clone();
+        let enrichments = self.enrichments.clone();
+        let migrations = self.migrations.clone();
+        let diagnostics = self.diagnostics.clone();
+        let archive = self.archive.clone();
+        let mut bound = Some(self);
+        supervise("WebSocket server", supervisor, &metrics.ws_server_restarts, || {
+            let server = bound.take();
+            let config = config.clone();
+            let admin = admin.clone();
+            let ip_limits = ip_limits.clone();
+            let presets = presets.clone();
+            let curve_updates = curve_updates.clone();
+            let previews = previews.clone();
+            let enrichments = enrichments.clone();
+            let migrations = migrations.clone();
+            let diagnostics = diagnostics.clone();
+            let archive = archive.clone();
+            let events = event_receiver.resubscribe();
+            let metrics = Arc::clone(&metrics);
+            async move {
+                let server = match server {
+                    Some(server) => server,
+                    None => {
+                        let mut server = WebSocketServer::bind(&config).await.map_err(|e| e.to_string())?.with_admin(admin);
+                        server.ip_limits = ip_limits;
+                        server.presets = presets;
+                        server.curve_updates = curve_updates;
+                        server.previews = previews;
+                        server.enrichments = enrichments;
+                        server.migrations = migrations;
+                        server.diagnostics = diagnostics;
+                        server.archive = archive;
+                        info!("🚀 WebSocket server listening again on {}://{}", server.scheme(), server.local_addr());
+                        server
+                    }
+                };
+                server.run(events, metrics).await.map_err(|e| e.to_string())
+            }
+        })
+        .await
+    }
+
+    /// runs the server like `run` until `shutdown` completes.
+    ///
+    /// on shutdown the listener is closed, broadcasting stops and every connected client is sent a close frame; the
+    /// connection tasks finish on their own once the close handshake completes.
+    pub async fn run_until(
+        self,
+        event_receiver: broadcast::Receiver<TokenCreatedEvent>,
+        metrics: Arc<Metrics>,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let tls_watcher = self
+            .tls
+            .as_ref()
+            .map(|tls| tokio::spawn(Arc::clone(tls).watch_for_changes(TLS_RELOAD_INTERVAL)));
+
+        let ip_limiter = Arc::new(match &self.ip_limits {
+            Some(ip_limits) => IpLimiter::watching(ip_limits.clone()),
+            None => IpLimiter::new(self.config.ip_limits.clone()),
+        });
+        let mut state = ServerState::new(
+            self.config,
+            metrics,
+            self.admin,
+            self.curve_updates.is_some(),
+            self.previews.is_some(),
+            self.diagnostics.is_some(),
+            self.archive,
+        );
+        if let Some(presets) = self.presets {
+            state.presets = presets;
+        }
+        let state = Arc::new(state);
+
+        // every worker gets a receiver of its own, so a slow shard lags alone
+        let mut broadcasters: Vec<_> = (1..state.shards.len())
+            .map(|shard| tokio::spawn(broadcast_events(Arc::clone(&state), shard, event_receiver.resubscribe())))
+            .collect();
+        broadcasters.push(tokio::spawn(broadcast_events(Arc::clone(&state), 0, event_receiver)));
+        let curve_forwarder = self
+            .curve_updates
+            .as_ref()
+            .map(|curve_updates| tokio::spawn(forward_curve_updates(Arc::clone(&state), curve_updates.subscribe())));
+        let preview_forwarder = self
+            .previews
+            .as_ref()
+            .map(|previews| tokio::spawn(forward_previews(Arc::clone(&state), previews.subscribe())));
+        let enrichment_forwarder = self
+            .enrichments
+            .as_ref()
+            .map(|enrichments| tokio::spawn(forward_enrichments(Arc::clone(&state), enrichments.subscribe())));
+        let migration_forwarder = self
+            .migrations
+            .as_ref()
+            .map(|migrations| tokio::spawn(forward_migrations(Arc::clone(&state), migrations.subscribe())));
+        let diagnostics_forwarder = self
+            .diagnostics
+            .as_ref()
+            .map(|diagnostics| tokio::spawn(forward_diagnostics(Arc::clone(&state), diagnostics.subscribe())));
+        let ip_cleanup = tokio::spawn(clean_up_ip_limits(Arc::clone(&ip_limiter)));
+        let summaries = tokio::spawn(send_summaries(Arc::clone(&state)));
+        tokio::pin!(shutdown);
+        let mut backoff = AcceptBackoff::default();
+        let mut listener_error = None;
+
+        // accept incoming connections
         loop {
-            match event_receiver.recv().await {
-                Ok(event) => {
-                    let mut dead_clients = Vec::new();
-                    let locked_clients = broadcast_clients.lock().await;
-
-                    for client in locked_clients.iter() {
-                        let filter = client.filter.lock().await;
-                        if matches_filter(&event, &filter) {
-                            let event_json = serde_json::to_string(&event).unwrap();
-                            let message = Message::Text(event_json);
-                            
-                            if let Err(_) = client.tx.send(message) {
-                                dead_clients.push(client.addr);
-                            }
+            let accepted = tokio::select! {
+                accepted = self.listener.accept() => accepted,
+                _ = &mut shutdown => {
+                    info!("WebSocket server shutting down");
+                    break;
+                }
+            };
+            let (stream, addr) = match accepted {
+                Ok(accepted) => {
+                    backoff.succeeded();
+                    accepted
+                }
+                Err(e) if listener::is_fatal(self.listener.as_ref(), &e) => {
+                    error!("WebSocket listener on {} failed: {}", self.local_addr, e);
+                    listener_error = Some(e);
+                    break;
+                }
+                Err(e) => {
+                    state.metrics.ws_accept_errors.fetch_add(1, Ordering::Relaxed);
+                    let delay = backoff.failed();
+                    warn!("Failed to accept a connection, retrying in {:?}: {}", delay, e);
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => continue,
+                        _ = &mut shutdown => {
+                            info!("WebSocket server shutting down");
+                            break;
                         }
                     }
+                }
+            };
+            // checked before spawning anything; dropping the stream closes the socket right away. Unix socket peers
+            // have no IP, who may connect there is up to the socket file's permissions
+            let permit = match addr.ip().map(|ip| ip_limiter.try_acquire(ip, Instant::now())) {
+                Some(Ok(permit)) => Some(permit),
+                Some(Err(rejection)) => {
+                    state.metrics.ws_connections_ip_limited.fetch_add(1, Ordering::Relaxed);
+                    debug!("Dropping connection from {}: IP {}", addr, rejection);
+                    continue;
+                }
+                None => None,
+            };
 
-                    // remove dead clients outside
-                    drop(locked_clients);
-                    if !dead_clients.is_empty() {
-                        let mut locked_clients = broadcast_clients.lock().await;
-                        locked_clients.retain(|client| !dead_clients.contains(&client.addr));
-                        for addr in dead_clients {
-                            info!("Removed dead client: {}", addr);
+            let state = Arc::clone(&state);
+            match &self.tls {
+                Some(tls) => {
+                    // the TLS handshake runs on the connection's own task so a slow or broken client can't stall the accept
+                    // loop, and is bounded so one that never finishes it can't hold its IP's connection permit either
+                    let acceptor = tls.acceptor();
+                    let handshake_timeout = tls.handshake_timeout();
+                    tokio::spawn(async move {
+                        let _permit = permit;
+                        match tokio::time::timeout(handshake_timeout, acceptor.accept(stream)).await {
+                            Ok(Ok(tls_stream)) => handle_connection(tls_stream, addr, state).await,
+                            Ok(Err(e)) => warn!("TLS handshake with {} failed: {}", addr, e),
+                            Err(_) => debug!("TLS handshake with {} not finished within {:?}", addr, handshake_timeout),
                         }
+                    });
+                }
+                None => {
+                    tokio::spawn(async move {
+                        let _permit = permit;
+                        handle_connection(stream, addr, state).await;
+                    });
+                }
+            }
+        }
+
+        for broadcaster in broadcasters {
+            broadcaster.abort();
+        }
+        ip_cleanup.abort();
+        summaries.abort();
+        if let Some(curve_forwarder) = curve_forwarder {
+            curve_forwarder.abort();
+        }
+        if let Some(preview_forwarder) = preview_forwarder {
+            preview_forwarder.abort();
+        }
+        if let Some(enrichment_forwarder) = enrichment_forwarder {
+            enrichment_forwarder.abort();
+        }
+        if let Some(migration_forwarder) = migration_forwarder {
+            migration_forwarder.abort();
+        }
+        if let Some(diagnostics_forwarder) = diagnostics_forwarder {
+            diagnostics_forwarder.abort();
+        }
+        if let Some(tls_watcher) = tls_watcher {
+            tls_watcher.abort();
+        }
+        for client in state.clients().await {
+            client.send(Message::Close(None));
+        }
+
+        match listener_error {
+            Some(e) => Err(e.into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// periodically forgets IPs that have no open connections and a full bucket.
+async fn clean_up_ip_limits(ip_limiter: Arc<IpLimiter>) {
+    let mut interval = tokio::time::interval(IP_LIMIT_CLEANUP_INTERVAL);
+    loop {
+        interval.tick().await;
+        let removed = ip_limiter.cleanup(Instant::now());
+        if removed > 0 {
+            debug!("Dropped {} idle IP limiter entries, {} still tracked", removed, ip_limiter.tracked_ips());
+        }
+    }
+}
+
+/// forwards every broadcast event to the clients of `shard` whose filter matches it.
+///
+/// Each event is recorded in the history before it is delivered; whichever worker gets to it first adds it.
+///
+/// # arguments
+/// * `state` - shared server state holding the client registry
+/// * `shard` - index of the shard whose clients this worker serves
+/// * `event_receiver` - this worker's broadcast receiver for token creation events
+async fn broadcast_events(state: Arc<ServerState>, shard: usize, mut event_receiver: broadcast::Receiver<TokenCreatedEvent>) {
+    loop {
+        match event_receiver.recv().await {
+            Ok(event) => {
+                // dropped before the history too, so a resuming client doesn't get it either
+                if state.config.stale_events.drops(event.stale) {
+                    // every shard sees the event, only one counts it
+                    if shard == 0 {
+                        state.metrics.ws_stale_dropped.fetch_add(1, Ordering::Relaxed);
                     }
+                    continue;
                 }
-                Err(broadcast::error::RecvError::Lagged(skipped)) => {
-                    warn!("WebSocket broadcast lagged, skipped {} events", skipped);
+                state.history.lock().await.record(&event);
+                // every shard sees the event, only one buffers it for detached sessions
+                if let Some(sessions) = state.sessions.as_ref().filter(|_| shard == 0) {
+                    sessions.lock().await.record(&event, Instant::now());
                 }
-                Err(broadcast::error::RecvError::Closed) => {
-                    error!("Event broadcast channel closed");
-                    break;
+                let matchable = MatchableEvent::new(&event);
+                let formatted = event.formatted(state.config.event_format);
+                let mut encoded = EncodedEvent::new(&formatted);
+                let mut selectable = SelectableEvent::new(&event, state.config.event_format);
+                let mut encoded_raw = event.raw_transaction.as_deref().map(EncodedEvent::new);
+                let mut dead_clients = Vec::new();
+                let locked_clients = state.shards[shard].lock().await;
+
+                for client in locked_clients.iter() {
+                    // registered after another worker recorded the event, so it is the replay's to deliver
+                    if event.sequence != 0 && event.sequence <= client.joined_after.load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    if client.filter.lock().await.matches(&matchable) {
+                        client.stats.record_match();
+                        if !client.admits(&event.token.symbol).await {
+                            state.metrics.ws_rate_limited.fetch_add(1, Ordering::Relaxed);
+                            continue;
+                        }
+                        let encoding = *client.encoding.lock().await;
+                        let compressed = client.compression.load(Ordering::Relaxed);
+                        let message = match client.fields.lock().await.as_ref() {
+                            Some(fields) => selectable.message(fields, encoding, compressed),
+                            None => encoded.message(encoding, compressed),
+                        };
+                        let Some(message) = message else {
+                            client.stats.record_dropped(1);
+                            continue;
+                        };
+                        if !client.send(message) {
+                            client.stats.record_dropped(1);
+                            dead_clients.push(client.addr);
+                            continue;
+                        }
+                        if let Some(raw) = encoded_raw.as_mut().filter(|_| client.raw_mode.load(Ordering::Relaxed)) {
+                            if let Some(message) = raw.message(encoding, compressed) {
+                                client.send(message);
+                            }
+                        }
+                    }
+                }
+
+                // remove dead clients outside
+                drop(locked_clients);
+                if !dead_clients.is_empty() {
+                    state.remove_clients(&dead_clients).await;
+                    for addr in dead_clients {
+                        info!("Removed dead client: {}", addr);
+                    }
                 }
             }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("WebSocket broadcast shard {} lagged, skipped {} events", shard, skipped);
+                notify_lagged(&state, shard, skipped).await;
+            }
+            Err(broadcast::error::RecvError::Closed) => {
+                error!("Event broadcast channel closed");
+                break;
+            }
         }
-    });
+    }
+}
+
+/// sends every rate limited client that asked for them a `summary` of the events it held back, once a second.
+async fn send_summaries(state: Arc<ServerState>) {
+    let mut interval = tokio::time::interval(SUMMARY_INTERVAL);
+    loop {
+        interval.tick().await;
+        flush_summaries(&state).await;
+    }
+}
+
+/// sends each client its `summary` of the events held back since the last one, if there were any.
+async fn flush_summaries(state: &ServerState) {
+    for client in state.clients().await {
+        let summary = client.rate_limit.lock().await.as_mut().and_then(RateLimit::take_summary);
+        if let Some(summary) = summary {
+            client.send_notice(&summary).await;
+        }
+    }
+}
+
+/// forwards every curve update to the clients that opted into them.
+///
+/// Updates aren't filtered per client or replayed: which curves are tracked is decided by the server's own filter.
+async fn forward_curve_updates(state: Arc<ServerState>, mut updates: broadcast::Receiver<CurveUpdateEvent>) {
+    loop {
+        match updates.recv().await {
+            Ok(update) => {
+                let formatted = update.formatted(state.config.event_format);
+                let mut encoded = EncodedEvent::new(&formatted);
+                for client in state.clients().await.iter().filter(|client| client.curve_updates.load(Ordering::Relaxed)) {
+                    let encoding = *client.encoding.lock().await;
+                    // dead clients are cleaned up by the next event
+                    if let Some(message) = encoded.message(encoding, client.compression.load(Ordering::Relaxed)) {
+                        client.send(message);
+                    }
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Curve update forwarding lagged, skipped {} updates", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// forwards every preview to the clients that opted into them.
+///
+/// Previews aren't filtered per client or replayed: most of what a filter looks at is only known once the transaction
+/// is fetched, and a preview is only worth anything the moment it arrives.
+async fn forward_previews(state: Arc<ServerState>, mut previews: broadcast::Receiver<TokenCreatedPreviewEvent>) {
+    loop {
+        match previews.recv().await {
+            Ok(preview) => {
+                let formatted = preview.formatted(state.config.event_format);
+                let mut encoded = EncodedEvent::new(&formatted);
+                for client in state.clients().await.iter().filter(|client| client.previews.load(Ordering::Relaxed)) {
+                    let encoding = *client.encoding.lock().await;
+                    // dead clients are cleaned up by the next event
+                    if let Some(message) = encoded.message(encoding, client.compression.load(Ordering::Relaxed)) {
+                        client.send(message);
+                    }
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Preview forwarding lagged, skipped {} previews", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// forwards every late image hash to the clients whose filter matches the token it belongs to.
+///
+/// The filter is matched against the token as it was broadcast, so an `imageSeenBefore` filter only ever sees hashes
+/// that were ready in time.
+async fn forward_enrichments(state: Arc<ServerState>, mut enrichments: broadcast::Receiver<TokenEnrichedEvent>) {
+    loop {
+        match enrichments.recv().await {
+            Ok(enriched) => {
+                let matchable = MatchableEvent::new(&enriched.event);
+                let formatted = enriched.formatted(state.config.event_format);
+                let mut encoded = EncodedEvent::new(&formatted);
+                for client in state.clients().await.iter() {
+                    if !client.filter.lock().await.matches(&matchable) {
+                        continue;
+                    }
+                    let encoding = *client.encoding.lock().await;
+                    // dead clients are cleaned up by the next event
+                    if let Some(message) = encoded.message(encoding, client.compression.load(Ordering::Relaxed)) {
+                        client.send(message);
+                    }
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Image hash forwarding lagged, skipped {} messages", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// forwards every migration to every client.
+///
+/// Migrations aren't filtered per client or replayed: a client's filter describes token creations, and the one that
+/// matched a token is usually long gone by the time its curve completes.
+async fn forward_migrations(state: Arc<ServerState>, mut migrations: broadcast::Receiver<TokenMigratedEvent>) {
+    loop {
+        match migrations.recv().await {
+            Ok(migrated) => {
+                let formatted = migrated.formatted(state.config.event_format);
+                let mut encoded = EncodedEvent::new(&formatted);
+                for client in state.clients().await.iter() {
+                    let encoding = *client.encoding.lock().await;
+                    // dead clients are cleaned up by the next event
+                    if let Some(message) = encoded.message(encoding, client.compression.load(Ordering::Relaxed)) {
+                        client.send(message);
+                    }
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Migration forwarding lagged, skipped {} migrations", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// forwards every diagnostic to the admins that opted into them.
+///
+/// Diagnostics aren't events, so they are sent as they are in every `EVENT_FORMAT`.
+async fn forward_diagnostics(state: Arc<ServerState>, mut diagnostics: broadcast::Receiver<DiagnosticEvent>) {
+    loop {
+        match diagnostics.recv().await {
+            Ok(diagnostic) => {
+                let mut encoded = EncodedEvent::new(&diagnostic);
+                for client in state.clients().await.iter().filter(|client| client.diagnostics.load(Ordering::Relaxed)) {
+                    let encoding = *client.encoding.lock().await;
+                    // dead clients are cleaned up by the next event
+                    if let Some(message) = encoded.message(encoding, client.compression.load(Ordering::Relaxed)) {
+                        client.send(message);
+                    }
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Diagnostics forwarding lagged, skipped {} diagnostics", skipped);
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// sends `client` a heartbeat at the interval it currently asks for.
+///
+/// The task only holds a weak reference, so it never keeps a disconnected client alive; it ends as soon as the client
+/// is dropped, which also drops the sender of `interval`.
+async fn send_heartbeats(state: Arc<ServerState>, client: Weak<Client>, mut interval: watch::Receiver<Option<Duration>>) {
+    loop {
+        let period = *interval.borrow_and_update();
+        let due = async {
+            match period {
+                Some(period) => tokio::time::sleep(period).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            changed = interval.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+                // start over with the new interval
+                continue;
+            }
+            _ = due => {}
+        }
+        let Some(client) = client.upgrade() else {
+            return;
+        };
+        client.send_notice(&state.heartbeat().await).await;
+    }
+}
+
+/// the heartbeat interval a `setHeartbeat` asks for, or why it can't be granted.
+fn requested_heartbeat(
+    enabled: bool,
+    interval_secs: Option<u64>,
+    server_default: Option<Duration>,
+) -> Result<Option<Duration>, MonitorError> {
+    if !enabled {
+        return Ok(None);
+    }
+    match interval_secs {
+        Some(secs) if (MIN_HEARTBEAT_SECS..=MAX_HEARTBEAT_SECS).contains(&secs) => Ok(Some(Duration::from_secs(secs))),
+        Some(secs) => Err(MonitorError::InvalidRequest(format!(
+            "intervalSecs {} must be between {} and {}",
+            secs, MIN_HEARTBEAT_SECS, MAX_HEARTBEAT_SECS
+        ))),
+        None => Ok(Some(server_default.unwrap_or(Duration::from_secs(DEFAULT_HEARTBEAT_SECS)))),
+    }
+}
+
+/// replays the buffered events from `sequence` up to the point the client registered, as far as they match its filter.
+///
+/// Later events were delivered live, possibly ahead of the replay, so clients order by `sequence`. Missing sequences
+/// go out as `gap` notices in their place, and an `ack` marks the end of the replay.
+async fn resume_from(state: &ServerState, client: &Client, sequence: u64) {
+    let filter = client.filter.lock().await.clone();
+    let history = state.history.lock().await;
+    let through = client.joined_after.load(Ordering::Relaxed);
+    for replayed in history.replay(sequence, through) {
+        match replayed {
+            Replayed::Event(event) => {
+                if filter.matches(&MatchableEvent::new(event)) {
+                    client.send_event(event, state.config.event_format).await;
+                    if let Some(raw) = event.raw_transaction.as_deref().filter(|_| client.raw_mode.load(Ordering::Relaxed)) {
+                        client.send_notice(raw).await;
+                    }
+                }
+            }
+            Replayed::Gap { from, to } => client.send_notice(&ServerMessage::Gap { from, to }).await,
+        }
+    }
+    drop(history);
+    client.send_notice(&ServerMessage::Ack { action: "resumeFrom".to_string() }).await;
+}
 
-    // accept incoming connections
-    while let Ok((stream, addr)) = listener.accept().await {
-        let clients_clone = Arc::clone(&clients);
-        tokio::spawn(handle_connection(stream, addr, clients_clone));
+/// restores the settings of the session detached under `token` and sends the events it missed, or tells the client it
+/// goes on with the fresh session it got on connecting.
+///
+/// Events broadcast after this connection registered already reached it live, so only the earlier ones are sent; a
+/// `resumed` notice ends them.
+async fn resume_session(state: &ServerState, client: &Client, token: &str) {
+    let Some(sessions) = state.sessions.as_ref() else {
+        let denied = MonitorError::Unsupported("sessions are disabled on this server".to_string());
+        client.send_notice(&ServerMessage::from(denied)).await;
+        return;
+    };
+    let resumed = sessions.lock().await.resume(token, Instant::now());
+    let Some(resumed) = resumed else {
+        info!("Client {} tried to resume an expired or unknown session", client.addr);
+        client.send_notice(&ServerMessage::from(MonitorError::SessionExpired)).await;
+        return;
+    };
+    client.restore(resumed.settings).await;
+    let through = client.joined_after.load(Ordering::Relaxed);
+    let mut replayed = 0;
+    for event in resumed.missed.iter().filter(|event| event.sequence == 0 || event.sequence <= through) {
+        client.send_event(event, state.config.event_format).await;
+        if let Some(raw) = event.raw_transaction.as_deref().filter(|_| client.raw_mode.load(Ordering::Relaxed)) {
+            client.send_notice(raw).await;
+        }
+        replayed += 1;
     }
+    info!(
+        "Client {} resumed its session: {} missed events replayed, {} dropped",
+        client.addr, replayed, resumed.dropped
+    );
+    client.send_notice(&ServerMessage::Resumed { replayed, dropped: resumed.dropped }).await;
+}
 
-    Ok(())
+/// tells every client of `shard` how many events it missed so it can reconcile on its own.
+///
+/// Lag happens before filtering, so every client is told regardless of whether the skipped events would have matched its filter.
+async fn notify_lagged(state: &ServerState, shard: usize, skipped: u64) {
+    let notice = ServerMessage::Lagged {
+        skipped,
+        timestamp: chrono::Utc::now(),
+    };
+    let mut encoded = EncodedEvent::new(&notice);
+
+    let locked_clients = state.shards[shard].lock().await;
+    for client in locked_clients.iter() {
+        client.stats.record_dropped(skipped);
+        let encoding = *client.encoding.lock().await;
+        if let Some(message) = encoded.message(encoding, client.compression.load(Ordering::Relaxed)) {
+            // dead clients are cleaned up by the next event
+            client.send(message);
+        }
+    }
 }
 
 /// handles a single WebSocket client connection.
 ///
 /// # Arguments
-/// * `stream` - the TCP stream for the client connection
-/// * `addr` - the client's socket address
-/// * `clients` - shared list of connected clients
-async fn handle_connection(
-    stream: TcpStream,
-    addr: SocketAddr,
-    clients: Arc<Mutex<Vec<Arc<Client>>>>,
-) {
+/// * `stream` - the client's transport, plain TCP, TLS or a Unix socket
+/// * `addr` - where the client is connected from
+/// * `state` - shared server state holding the client registry
+async fn handle_connection<S>(stream: S, addr: PeerAddr, state: Arc<ServerState>)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
     info!("New client connected: {}", addr);
 
-    let ws_stream = match accept_async(stream).await {
+    let slot = state.try_reserve_slot();
+    if slot.is_none() {
+        state.metrics.ws_connections_rejected.fetch_add(1, Ordering::Relaxed);
+        warn!("Rejecting client {}: limit of {} connections reached", addr, state.config.max_clients);
+    }
+
+    // the handshake URL may pick the initial encoding, e.g. `ws://host:8080/?encoding=msgpack`
+    let mut initial_encoding = WireEncoding::default();
+    let mut origin_refused = false;
+    // the error type is fixed by tungstenite's handshake callback
+    #[allow(clippy::result_large_err)]
+    let handshake = |request: &Request, response: Response| -> std::result::Result<Response, ErrorResponse> {
+        // a header that isn't text matches no allowed origin
+        let origin = request.headers().get("origin").map(|origin| origin.to_str().unwrap_or(""));
+        if let Some(reason) = state.config.origins.check(origin) {
+            state.metrics.ws_connections_origin_rejected.fetch_add(1, Ordering::Relaxed);
+            let suppressed = state.rejected_origins.lock().unwrap_or_else(|e| e.into_inner()).record(Instant::now());
+            match suppressed {
+                Some(0) => info!("Refused handshake from {} with origin {:?}: {}", addr, origin, reason),
+                Some(suppressed) => info!(
+                    "Refused handshake from {} with origin {:?}: {} ({} more refused since the last one logged)",
+                    addr, origin, reason, suppressed
+                ),
+                None => {}
+            }
+            origin_refused = true;
+            return Err(error_response(StatusCode::FORBIDDEN, reason));
+        }
+        if slot.is_none() {
+            return Err(error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "server is at its connection limit, try again later",
+            ));
+        }
+        match encoding_from_query(request.uri().query()) {
+            Ok(encoding) => {
+                initial_encoding = encoding;
+                Ok(response)
+            }
+            Err(e) => Err(error_response(StatusCode::BAD_REQUEST, &e)),
+        }
+    };
+
+    let ws_stream = match accept_hdr_async(stream, handshake).await {
         Ok(ws) => ws,
         Err(e) => {
-            error!("Failed to accept WebSocket connection from {}: {}", addr, e);
+            if slot.is_some() && !origin_refused {
+                error!("Failed to accept WebSocket connection from {}: {}", addr, e);
+            } else {
+                debug!("Closed rejected connection from {}: {}", addr, e);
+            }
             return;
         }
     };
+    // released when this function returns, after the client has been removed
+    let _slot = slot;
 
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
-    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let (tx, mut rx) = tokio::sync::mpsc::channel(state.config.client_queue_capacity);
 
     let client = Arc::new(Client {
         addr,
         tx,
-        filter: Arc::new(Mutex::new(FilterCriteria::default())),
+        filter: Mutex::new(CompiledFilter::default()),
+        encoding: Mutex::new(initial_encoding),
+        fields: Mutex::new(None),
+        compression: AtomicBool::new(false),
+        raw_mode: AtomicBool::new(false),
+        curve_updates: AtomicBool::new(false),
+        previews: AtomicBool::new(false),
+        diagnostics: AtomicBool::new(false),
+        rate_limit: Mutex::new(None),
+        heartbeat: watch::channel(state.config.heartbeat_interval).0,
+        stats: ClientStats::default(),
+        latency: SendLatency::new(Arc::clone(&state.metrics), addr),
+        joined_after: AtomicU64::new(0),
+        queries: Arc::new(Semaphore::new(MAX_CLIENT_QUERIES)),
+        session: state.sessions.as_ref().map(|_| Sessions::new_token()),
+        kicked: AtomicBool::new(false),
     });
 
-    clients.lock().await.push(Arc::clone(&client));
+    state.add_client(Arc::clone(&client)).await;
+    if let (Some(token), Some(sessions)) = (&client.session, &state.config.sessions) {
+        let session = ServerMessage::Session {
+            token: token.clone(),
+            ttl_secs: sessions.ttl.as_secs(),
+        };
+        client.send_notice(&session).await;
+    }
+    tokio::spawn(send_heartbeats(Arc::clone(&state), Arc::downgrade(&client), client.heartbeat.subscribe()));
 
-    let client_for_sender = Arc::clone(&client);
+    // a weak reference, so the client (and with it `tx`) is dropped once disconnected and the writer drains and ends
+    let client_for_sender = Arc::downgrade(&client);
     tokio::spawn(async move {
-        while let Some(message) = rx.recv().await {
-            if let Err(e) = ws_sender.send(message).await {
-                error!("Failed to send message to {}: {}", client_for_sender.addr, e);
+        while let Some(queued) = rx.recv().await {
+            if let Err(e) = ws_sender.send(queued.message).await {
+                error!("Failed to send message to {}: {}", addr, e);
                 break;
             }
+            if let Some(client) = client_for_sender.upgrade() {
+                client.stats.record_sent();
+                client.latency.record(queued.queued_at.elapsed());
+            }
         }
     });
 
     // handle incoming messages
     while let Some(msg) = ws_receiver.next().await {
+        client.stats.touch();
         match msg {
             Ok(Message::Text(text)) => {
                 // Try to parse as a client message
                 match serde_json::from_str::<ClientMessage>(&text) {
-                    Ok(ClientMessage::SetFilter { filter }) => {
-                        let mut client_filter = client.filter.lock().await;
-                        *client_filter = filter.clone();
-                        info!("Updated filter for client {}: {:?}", addr, filter);
+                    Ok(ClientMessage::SetFilter { filter }) => match normalize_filter(filter) {
+                        Ok(filter) => {
+                            info!("Updated filter for client {}: {:?}", addr, filter);
+                            *client.filter.lock().await = CompiledFilter::from(&filter);
+                            client.send_notice(&ServerMessage::Ack { action: "setFilter".to_string() }).await;
+                        }
+                        Err(problems) => {
+                            warn!("Rejected filter from client {}: {:?}", addr, problems);
+                            client.send_notice(&ServerMessage::from(MonitorError::InvalidFilter(problems))).await;
+                        }
+                    },
+                    Ok(ClientMessage::UsePreset { name, overrides }) => {
+                        let preset = state.presets.borrow().get(&name).cloned();
+                        let notice = match preset.map(|preset| normalize_filter(merge_filters(preset, overrides))) {
+                            Some(Ok(filter)) => {
+                                info!("Client {} applied filter preset '{}': {:?}", addr, name, filter);
+                                *client.filter.lock().await = CompiledFilter::from(&filter);
+                                ServerMessage::Ack { action: "usePreset".to_string() }
+                            }
+                            Some(Err(problems)) => {
+                                warn!("Rejected overrides of filter preset '{}' from client {}: {:?}", name, addr, problems);
+                                MonitorError::InvalidFilter(problems).into()
+                            }
+                            None => {
+                                warn!("Client {} asked for unknown filter preset '{}'", addr, name);
+                                MonitorError::UnknownPreset(name).into()
+                            }
+                        };
+                        client.send_notice(&notice).await;
+                    }
+                    Ok(ClientMessage::ListPresets) => {
+                        let presets = BTreeMap::clone(&state.presets.borrow());
+                        client.send_notice(&ServerMessage::Presets { presets }).await;
+                    }
+                    Ok(ClientMessage::GetFilter) => {
+                        let filter = client.filter.lock().await.criteria().clone();
+                        client.send_notice(&ServerMessage::Filter { filter }).await;
+                    }
+                    Ok(ClientMessage::ClearFilter) => {
+                        *client.filter.lock().await = CompiledFilter::default();
+                        info!("Cleared filter for client {}", addr);
+                        client.send_notice(&ServerMessage::Ack { action: "clearFilter".to_string() }).await;
+                    }
+                    Ok(ClientMessage::SetCompression { enabled }) => {
+                        if enabled && !state.config.compression {
+                            warn!("Client {} requested compression but it is disabled on this server", addr);
+                            let denied = MonitorError::Unsupported("compression is disabled on this server".to_string());
+                            client.send_notice(&ServerMessage::from(denied)).await;
+                        } else {
+                            client.compression.store(enabled, Ordering::Relaxed);
+                            info!("Compression {} for client {}", if enabled { "enabled" } else { "disabled" }, addr);
+                            client.send_notice(&ServerMessage::Ack { action: "setCompression".to_string() }).await;
+                        }
+                    }
+                    Ok(ClientMessage::SetEncoding { encoding }) => {
+                        *client.encoding.lock().await = encoding;
+                        info!("Encoding for client {} set to {:?}", addr, encoding);
+                        client.send_notice(&ServerMessage::Ack { action: "setEncoding".to_string() }).await;
+                    }
+                    Ok(ClientMessage::SetFields { fields }) => {
+                        let notice = if fields.is_empty() {
+                            *client.fields.lock().await = None;
+                            info!("Cleared field selection for client {}", addr);
+                            ServerMessage::Ack { action: "setFields".to_string() }
+                        } else {
+                            match FieldSelection::new(&fields) {
+                                Ok(selection) => {
+                                    *client.fields.lock().await = Some(selection);
+                                    info!("Field selection for client {} set to {:?}", addr, fields);
+                                    ServerMessage::Ack { action: "setFields".to_string() }
+                                }
+                                Err(e) => e.into(),
+                            }
+                        };
+                        client.send_notice(&notice).await;
+                    }
+                    Ok(ClientMessage::SetRawMode { enabled }) => {
+                        if enabled && !state.config.raw_transactions {
+                            warn!("Client {} requested raw transactions but they are disabled on this server", addr);
+                            let denied = MonitorError::Unsupported("raw transactions are disabled on this server".to_string());
+                            client.send_notice(&ServerMessage::from(denied)).await;
+                        } else {
+                            client.raw_mode.store(enabled, Ordering::Relaxed);
+                            info!("Raw mode {} for client {}", if enabled { "enabled" } else { "disabled" }, addr);
+                            client.send_notice(&ServerMessage::Ack { action: "setRawMode".to_string() }).await;
+                        }
+                    }
+                    Ok(ClientMessage::SetCurveUpdates { enabled }) => {
+                        if enabled && !state.curve_tracking {
+                            let denied = MonitorError::Unsupported("curve tracking is disabled on this server".to_string());
+                            client.send_notice(&ServerMessage::from(denied)).await;
+                        } else {
+                            client.curve_updates.store(enabled, Ordering::Relaxed);
+                            info!("Curve updates {} for client {}", if enabled { "enabled" } else { "disabled" }, addr);
+                            client.send_notice(&ServerMessage::Ack { action: "setCurveUpdates".to_string() }).await;
+                        }
+                    }
+                    Ok(ClientMessage::SetPreviews { enabled }) => {
+                        if enabled && !state.previews {
+                            let denied = MonitorError::Unsupported("previews are disabled on this server".to_string());
+                            client.send_notice(&ServerMessage::from(denied)).await;
+                        } else {
+                            client.previews.store(enabled, Ordering::Relaxed);
+                            info!("Previews {} for client {}", if enabled { "enabled" } else { "disabled" }, addr);
+                            client.send_notice(&ServerMessage::Ack { action: "setPreviews".to_string() }).await;
+                        }
+                    }
+                    Ok(ClientMessage::SetHeartbeat { enabled, interval_secs }) => {
+                        let notice = match requested_heartbeat(enabled, interval_secs, state.config.heartbeat_interval) {
+                            Ok(interval) => {
+                                client.heartbeat.send_replace(interval);
+                                info!("Heartbeat interval for client {} set to {:?}", addr, interval);
+                                ServerMessage::Ack { action: "setHeartbeat".to_string() }
+                            }
+                            Err(e) => e.into(),
+                        };
+                        client.send_notice(&notice).await;
+                    }
+                    Ok(ClientMessage::SetRateLimit { max_per_second, overflow }) => {
+                        client.set_rate_limit(max_per_second, overflow).await;
+                        info!("Rate limit for client {} set to {} events/s ({:?})", addr, max_per_second, overflow);
+                        client.send_notice(&ServerMessage::Ack { action: "setRateLimit".to_string() }).await;
+                    }
+                    Ok(ClientMessage::ResumeFrom { sequence }) => {
+                        resume_from(&state, &client, sequence).await;
+                    }
+                    Ok(ClientMessage::Resume { token }) => {
+                        resume_session(&state, &client, &token).await;
+                    }
+                    Ok(ClientMessage::Query { filter, since, limit, id }) => {
+                        query::start_query(&state, &client, filter, since, limit, id).await;
+                    }
+                    Ok(ClientMessage::Identify { name }) => {
+                        let name = name.trim();
+                        let notice = if name.is_empty() || name.chars().count() > MAX_CLIENT_NAME_CHARS {
+                            MonitorError::InvalidRequest(format!(
+                                "a client name must have 1 to {} characters",
+                                MAX_CLIENT_NAME_CHARS
+                            ))
+                            .into()
+                        } else {
+                            info!("Client {} identified as '{}'", addr, name);
+                            client.latency.identify(name.to_string());
+                            ServerMessage::Ack { action: "identify".to_string() }
+                        };
+                        client.send_notice(&notice).await;
+                    }
+                    Ok(ClientMessage::GetStats) => {
+                        client.send_notice(&ServerMessage::Stats { client: client.stats.report(addr) }).await;
+                    }
+                    Ok(ClientMessage::GetAllStats { token }) => {
+                        let notice = match admin_only(&state, addr, "getAllStats", &token) {
+                            Ok(()) => ServerMessage::AllStats { clients: state.client_stats().await },
+                            Err(denied) => denied.into(),
+                        };
+                        client.send_notice(&notice).await;
+                    }
+                    Ok(ClientMessage::ListClients { token }) => {
+                        let notice = match admin_only(&state, addr, "listClients", &token) {
+                            Ok(()) => ServerMessage::Clients { clients: state.client_details().await },
+                            Err(denied) => denied.into(),
+                        };
+                        client.send_notice(&notice).await;
+                    }
+                    Ok(ClientMessage::KickClient { token, addr: target }) => {
+                        let notice = match admin_only(&state, addr, "kickClient", &token) {
+                            Ok(()) => match target.parse::<PeerAddr>() {
+                                Ok(target) if state.kick_client(target).await => {
+                                    info!("Client {} kicked client {}", addr, target);
+                                    ServerMessage::Ack { action: "kickClient".to_string() }
+                                }
+                                Ok(target) => MonitorError::InvalidRequest(format!("no client connected from {}", target)).into(),
+                                Err(e) => MonitorError::InvalidRequest(e).into(),
+                            },
+                            Err(denied) => denied.into(),
+                        };
+                        client.send_notice(&notice).await;
+                    }
+                    Ok(ClientMessage::GetMonitorStatus { token }) => {
+                        let notice = match admin_only(&state, addr, "getMonitorStatus", &token) {
+                            Ok(()) => ServerMessage::MonitorStatus { status: state.monitor_status().await },
+                            Err(denied) => denied.into(),
+                        };
+                        client.send_notice(&notice).await;
+                    }
+                    Ok(ClientMessage::SetDiagnostics { token, enabled }) => {
+                        let notice = match admin_only(&state, addr, "setDiagnostics", &token) {
+                            Ok(()) if enabled && !state.diagnostics => {
+                                MonitorError::Unsupported("diagnostics are disabled on this server".to_string()).into()
+                            }
+                            Ok(()) => {
+                                client.diagnostics.store(enabled, Ordering::Relaxed);
+                                info!("Diagnostics {} for client {}", if enabled { "enabled" } else { "disabled" }, addr);
+                                ServerMessage::Ack { action: "setDiagnostics".to_string() }
+                            }
+                            Err(denied) => denied.into(),
+                        };
+                        client.send_notice(&notice).await;
+                    }
+                    Ok(ClientMessage::SetLogLevel { token, level }) => {
+                        let notice = match admin_only(&state, addr, "setLogLevel", &token) {
+                            Ok(()) => match state.admin.log_level.as_ref().map(|handle| handle.set(&level)) {
+                                Some(Ok(())) => {
+                                    info!("Client {} set the log filter to '{}'", addr, level);
+                                    ServerMessage::Ack { action: "setLogLevel".to_string() }
+                                }
+                                Some(Err(e)) => MonitorError::InvalidRequest(format!("invalid log level '{}': {}", level, e)).into(),
+                                None => MonitorError::Unsupported("the log level can't be changed on this server".to_string()).into(),
+                            },
+                            Err(denied) => denied.into(),
+                        };
+                        client.send_notice(&notice).await;
                     }
                     Err(e) => {
                         warn!("Invalid message from client {}: {} (error: {})", addr, text, e);
@@ -163,36 +1530,55 @@ async fn handle_connection(
         }
     }
 
-    info!("Client {} disconnected", addr);
+    let stats = client.stats.report(addr);
+    info!(
+        "Client {} disconnected (sent {}, dropped {}, matched {}, connected since {})",
+        addr, stats.messages_sent, stats.messages_dropped, stats.filter_matches, stats.connected_at
+    );
     // Remove the client from the broadcast list
-    clients.lock().await.retain(|client| client.addr != addr);
+    state.remove_clients(&[addr]).await;
+    // kept for a `resume` unless an admin kicked the client
+    if let (Some(token), Some(sessions)) = (&client.session, &state.sessions) {
+        if !client.kicked.load(Ordering::Relaxed) {
+            let settings = client.take_settings().await;
+            sessions.lock().await.detach(token.clone(), settings, Instant::now());
+        }
+    }
 }
 
 
-/// Checks if a token creation event matches the specified filter criteria.
-fn matches_filter(event: &TokenCreatedEvent, filter: &FilterCriteria) -> bool {
-    // check creator filter
-    if let Some(creator_filter) = &filter.creator {
-        if &event.token.creator != creator_filter {
-            return false;
-        }
+/// checks the admin token of an admin-only `action`, returning the error to reply with when it is wrong.
+fn admin_only(state: &ServerState, addr: PeerAddr, action: &str, token: &str) -> Result<(), MonitorError> {
+    if state.is_admin(token) {
+        return Ok(());
     }
-    
-    // check symbol filter
-    if let Some(symbol_filter) = &filter.symbol {
-        if event.token.symbol.to_uppercase() != symbol_filter.to_uppercase() {
-            return false;
-        }
-    }
-    
-    // check name contains filter
-    if let Some(name_filter) = &filter.name_contains {
-        if !event.token.name.to_uppercase().contains(&name_filter.to_uppercase()) {
-            return false;
-        }
+    warn!("Client {} sent {} with an invalid admin token", addr, action);
+    Err(MonitorError::Unauthorized)
+}
+
+/// builds an HTTP error reply that rejects a WebSocket upgrade.
+fn error_response(status: StatusCode, message: &str) -> ErrorResponse {
+    let mut response = ErrorResponse::new(Some(message.to_string()));
+    *response.status_mut() = status;
+    response
+}
+
+/// compares two secrets in time independent of where they first differ.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// reads the `encoding` query parameter of the handshake URL, defaulting to JSON when it is absent.
+fn encoding_from_query(query: Option<&str>) -> std::result::Result<WireEncoding, String> {
+    let requested = query.and_then(|query| {
+        url::form_urlencoded::parse(query.as_bytes())
+            .find(|(key, _)| key == "encoding")
+            .map(|(_, value)| value.into_owned())
+    });
+    match requested {
+        Some(value) => value.parse(),
+        None => Ok(WireEncoding::default()),
     }
-    
-    true
 }
 
 #[cfg(test)]
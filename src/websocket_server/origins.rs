@@ -0,0 +1,147 @@
+//! # Allowed Origins
+//!
+//! Browsers send the page's origin with every WebSocket handshake, and let any page open a connection to any server;
+//! the server is what has to refuse pages it doesn't serve. With `WS_ALLOWED_ORIGINS` set, a handshake whose `Origin`
+//! matches none of the entries is refused with 403. An entry is a full origin such as `https://dash.example.com`, or a
+//! host without a scheme, matched on any scheme and port, where `*.example.com` stands for every subdomain of
+//! `example.com` but not `example.com` itself. Clients that aren't browsers send no `Origin`, and are let in unless
+//! `WS_REQUIRE_ORIGIN` is on. Refusals are logged at most once every `REJECTION_LOG_INTERVAL`, with how many were
+//! left out since.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// least time between two logged refusals.
+const REJECTION_LOG_INTERVAL: Duration = Duration::from_secs(10);
+
+/// one entry of `WS_ALLOWED_ORIGINS`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AllowedOrigin {
+    /// `http` or `https`; any scheme and port while `None`
+    scheme: Option<String>,
+    /// the host, or the domain whose subdomains are allowed when `subdomains`
+    host: String,
+    subdomains: bool,
+    /// a port other than the scheme's default
+    port: Option<u16>,
+}
+
+impl AllowedOrigin {
+    /// whether the parsed `Origin` of a handshake matches this entry.
+    fn matches(&self, origin: &url::Url) -> bool {
+        let Some(host) = origin.host_str() else {
+            return false;
+        };
+        let host_matches = if self.subdomains {
+            host.strip_suffix(self.host.as_str()).is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.'))
+        } else {
+            host == self.host
+        };
+        match &self.scheme {
+            Some(scheme) => host_matches && origin.scheme() == scheme && origin.port() == self.port,
+            None => host_matches,
+        }
+    }
+}
+
+impl FromStr for AllowedOrigin {
+    type Err = String;
+
+    fn from_str(entry: &str) -> std::result::Result<Self, String> {
+        let entry = entry.trim().to_ascii_lowercase();
+        let (scheme, rest) = match entry.split_once("://") {
+            Some((scheme, rest)) if scheme == "http" || scheme == "https" => (Some(scheme.to_string()), rest),
+            Some((scheme, _)) => return Err(format!("'{}' has scheme '{}', not http or https", entry, scheme)),
+            None => (None, entry.as_str()),
+        };
+        let (host, port) = match rest.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port.parse::<u16>().map_err(|_| format!("'{}' has an invalid port", entry))?;
+                (host, Some(port))
+            }
+            None => (rest, None),
+        };
+        if port.is_some() && scheme.is_none() {
+            return Err(format!("'{}' has a port but no scheme", entry));
+        }
+        let (subdomains, host) = match host.strip_prefix("*.") {
+            Some(domain) => (true, domain),
+            None => (false, host),
+        };
+        let valid_label = |label: &str| !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        if !host.split('.').all(valid_label) {
+            return Err(format!("'{}' is not an origin like https://app.example.com or *.example.com", entry));
+        }
+        // the default port is left out of a browser's Origin, and so from the entry
+        let port = port.filter(|&port| match scheme.as_deref() {
+            Some("http") => port != 80,
+            _ => port != 443,
+        });
+        Ok(Self {
+            scheme,
+            host: host.to_string(),
+            subdomains,
+            port,
+        })
+    }
+}
+
+impl fmt::Debug for AllowedOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(scheme) = &self.scheme {
+            write!(f, "{}://", scheme)?;
+        }
+        if self.subdomains {
+            f.write_str("*.")?;
+        }
+        f.write_str(&self.host)?;
+        if let Some(port) = self.port {
+            write!(f, ":{}", port)?;
+        }
+        Ok(())
+    }
+}
+
+/// which handshakes the server accepts, going by their `Origin` header.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OriginPolicy {
+    /// origins browsers may connect from; every origin is accepted while empty
+    pub allowed: Vec<AllowedOrigin>,
+    /// refuse handshakes without an `Origin`, i.e. every client that isn't a browser
+    pub require: bool,
+}
+
+impl OriginPolicy {
+    /// why a handshake with `origin` is refused; `None` when it is accepted.
+    pub(crate) fn check(&self, origin: Option<&str>) -> Option<&'static str> {
+        let Some(origin) = origin else {
+            return self.require.then_some("an Origin header is required");
+        };
+        if self.allowed.is_empty() {
+            return None;
+        }
+        let allowed = url::Url::parse(origin).is_ok_and(|origin| self.allowed.iter().any(|entry| entry.matches(&origin)));
+        (!allowed).then_some("origin not allowed")
+    }
+}
+
+/// keeps refusals from flooding the log when a page retries its connection in a loop.
+#[derive(Debug, Default)]
+pub(crate) struct RejectionLog {
+    logged_at: Option<Instant>,
+    /// refusals since the last logged one
+    suppressed: u64,
+}
+
+impl RejectionLog {
+    /// counts a refusal at `now`, returning the refusals left out since the last logged one when this one is logged.
+    pub fn record(&mut self, now: Instant) -> Option<u64> {
+        if self.logged_at.is_some_and(|at| now.saturating_duration_since(at) < REJECTION_LOG_INTERVAL) {
+            self.suppressed += 1;
+            return None;
+        }
+        self.logged_at = Some(now);
+        Some(std::mem::take(&mut self.suppressed))
+    }
+}
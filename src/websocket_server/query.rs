@@ -0,0 +1,112 @@
+//! # History Queries
+//!
+//! `query` requests for the tokens created since a point in time. They are answered from the server's archive when it
+//! has one, such as the PostgreSQL database the `postgres` sink writes, and from the replay buffer otherwise. Results go
+//! out newest first as `queryResult` messages, followed by one `queryComplete` with their count; it is marked `partial`
+//! when the answer came from a buffer that doesn't reach back to `since`.
+//!
+//! Each query runs on a task of its own so the connection keeps being served meanwhile, and a client may only have
+//! `MAX_CLIENT_QUERIES` of them running at once.
+
+use super::{Client, ServerState};
+use crate::data_models::{FilterCriteria, ServerMessage, TokenCreatedEvent};
+use crate::error::{MonitorError, Result};
+use crate::filtering::{normalize_filter, CompiledFilter};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// most results one query returns; larger limits are lowered to it.
+pub const MAX_QUERY_LIMIT: usize = 1_000;
+
+/// queries one client may have running at once.
+pub const MAX_CLIENT_QUERIES: usize = 2;
+
+/// a store of past events that can answer `query` requests.
+#[async_trait]
+pub trait EventArchive: Send + Sync {
+    /// up to `limit` stored events created at or after `since` that match `filter`, newest first.
+    async fn query(&self, filter: &CompiledFilter, since: DateTime<Utc>, limit: usize)
+        -> Result<Vec<TokenCreatedEvent>>;
+}
+
+/// Checks a `query` request and answers it on a task of its own.
+///
+/// Invalid filters, a zero `limit` and queries beyond the client's `MAX_CLIENT_QUERIES` are refused with an `error`.
+pub(super) async fn start_query(
+    state: &Arc<ServerState>,
+    client: &Arc<Client>,
+    filter: FilterCriteria,
+    since: DateTime<Utc>,
+    limit: usize,
+    id: Option<String>,
+) {
+    let filter = match normalize_filter(filter) {
+        Ok(filter) => CompiledFilter::from(&filter),
+        Err(problems) => {
+            client.send_notice(&ServerMessage::from(MonitorError::InvalidFilter(problems))).await;
+            return;
+        }
+    };
+    if limit == 0 {
+        let empty = MonitorError::InvalidRequest("limit must be at least 1".to_string());
+        client.send_notice(&ServerMessage::from(empty)).await;
+        return;
+    }
+    let Ok(permit) = Arc::clone(&client.queries).try_acquire_owned() else {
+        let busy = MonitorError::InvalidRequest(format!("at most {} queries may run at once", MAX_CLIENT_QUERIES));
+        client.send_notice(&ServerMessage::from(busy)).await;
+        return;
+    };
+
+    let state = Arc::clone(state);
+    let client = Arc::clone(client);
+    tokio::spawn(async move {
+        let _permit = permit;
+        answer_query(&state, &client, &filter, since, limit.min(MAX_QUERY_LIMIT), id).await;
+    });
+}
+
+/// runs a query against the archive, or the replay buffer without one, and sends the results with their terminator.
+async fn answer_query(
+    state: &ServerState,
+    client: &Client,
+    filter: &CompiledFilter,
+    since: DateTime<Utc>,
+    limit: usize,
+    id: Option<String>,
+) {
+    let (tokens, covered_from) = match &state.archive {
+        Some(archive) => match archive.query(filter, since, limit).await {
+            Ok(tokens) => (tokens, None),
+            Err(e) => {
+                warn!("History query for client {} failed: {}", client.addr, e);
+                client.send_notice(&ServerMessage::from(e)).await;
+                return;
+            }
+        },
+        None => {
+            let history = state.history.lock().await;
+            let covered_from = history.covered_from();
+            (history.query(filter, since, limit), (since < covered_from).then_some(covered_from))
+        }
+    };
+
+    debug!("Answering a history query from client {} with {} tokens", client.addr, tokens.len());
+    let count = tokens.len();
+    for token in tokens {
+        let result = ServerMessage::QueryResult {
+            id: id.clone(),
+            token: Box::new(token),
+        };
+        client.send_notice(&result).await;
+    }
+    let complete = ServerMessage::QueryComplete {
+        id,
+        count,
+        partial: covered_from.is_some(),
+        covered_from,
+    };
+    client.send_notice(&complete).await;
+}
@@ -0,0 +1,84 @@
+//! # Client Rate Limits
+//!
+//! A client that sent `setRateLimit` gets at most that many matching token events a second, metered by a token bucket
+//! holding up to one second's worth so a quiet spell can't turn into a burst. Events beyond it are dropped, or, with
+//! `overflow: "summarize"`, counted along with their symbols and reported in a `summary` message every second.
+//! Checking an event costs the same however many events came before it; the symbols counted are bounded.
+
+use crate::data_models::{RateLimitOverflow, ServerMessage};
+use chrono::Utc;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// how often held-back events are summarized.
+pub(crate) const SUMMARY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// symbols listed in a summary.
+const TOP_SYMBOLS: usize = 5;
+
+/// distinct symbols counted between two summaries; later ones only add to the count.
+const MAX_TRACKED_SYMBOLS: usize = 1_000;
+
+/// the rate limit one client set, with the events it held back since the last summary.
+///
+#[derive(Debug)]
+pub(crate) struct RateLimit {
+    max_per_second: f64,
+    overflow: RateLimitOverflow,
+    tokens: f64,
+    refilled_at: Instant,
+    held_back: u64,
+    symbols: HashMap<String, u64>,
+}
+
+impl RateLimit {
+    /// a limit of `max_per_second` events starting with a full bucket.
+    pub fn new(max_per_second: u32, overflow: RateLimitOverflow, now: Instant) -> Self {
+        let max_per_second = f64::from(max_per_second);
+        Self {
+            max_per_second,
+            overflow,
+            tokens: max_per_second,
+            refilled_at: now,
+            held_back: 0,
+            symbols: HashMap::new(),
+        }
+    }
+
+    /// whether an event for `symbol` may be sent now; one that may not is counted for the next summary.
+    pub fn admit(&mut self, symbol: &str, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.max_per_second).min(self.max_per_second);
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return true;
+        }
+        if self.overflow == RateLimitOverflow::Summarize {
+            self.held_back += 1;
+            if let Some(count) = self.symbols.get_mut(symbol) {
+                *count += 1;
+            } else if self.symbols.len() < MAX_TRACKED_SYMBOLS {
+                self.symbols.insert(symbol.to_string(), 1);
+            }
+        }
+        false
+    }
+
+    /// the `summary` of the events held back since the last one, `None` when there were none.
+    pub fn take_summary(&mut self) -> Option<ServerMessage> {
+        if self.held_back == 0 {
+            return None;
+        }
+        let mut symbols: Vec<(String, u64)> = self.symbols.drain().collect();
+        // most frequent first, ties alphabetically so the list doesn't depend on the map's order
+        symbols.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        symbols.truncate(TOP_SYMBOLS);
+        Some(ServerMessage::Summary {
+            count: std::mem::take(&mut self.held_back),
+            top_symbols: symbols.into_iter().map(|(symbol, _)| symbol).collect(),
+            timestamp: Utc::now(),
+        })
+    }
+}
@@ -0,0 +1,136 @@
+//! # Client Sessions
+//!
+//! Every client is told a resume token in a `session` message when it connects. Once its connection drops, its
+//! settings are kept under that token for `WS_SESSION_TTL_SECS`, together with the events matching its filter that are
+//! broadcast in the meantime, so a client reconnecting after a network blip can `resume` where it left off instead of
+//! starting over. Memory is bounded twice: at most `WS_MAX_DETACHED_SESSIONS` are kept, the one detached longest ago
+//! dropped first, and each buffers at most `WS_SESSION_BUFFER_CAPACITY` events, the oldest dropped first and counted.
+//! An event matching several sessions is stored once.
+
+use super::fields::FieldSelection;
+use super::rate_limit::RateLimit;
+use crate::config::SessionConfig;
+use crate::data_models::{TokenCreatedEvent, WireEncoding};
+use crate::filtering::{CompiledFilter, MatchableEvent};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// everything a client set on its connection that a resumed session restores.
+///
+/// Diagnostics aren't kept: they need the admin token, which a resume token doesn't stand in for.
+#[derive(Debug, Default)]
+pub(crate) struct SessionSettings {
+    pub filter: CompiledFilter,
+    pub encoding: WireEncoding,
+    pub fields: Option<FieldSelection>,
+    pub compression: bool,
+    pub raw_mode: bool,
+    pub curve_updates: bool,
+    pub previews: bool,
+    pub rate_limit: Option<RateLimit>,
+    pub heartbeat: Option<Duration>,
+    /// the name given with `identify`
+    pub name: Option<String>,
+}
+
+/// a session whose connection dropped, with the matching events broadcast since.
+#[derive(Debug)]
+struct DetachedSession {
+    settings: SessionSettings,
+    detached_at: Instant,
+    missed: VecDeque<Arc<TokenCreatedEvent>>,
+    dropped: u64,
+}
+
+/// a session taken over by a new connection.
+#[derive(Debug)]
+pub(crate) struct ResumedSession {
+    pub settings: SessionSettings,
+    /// the events it missed, oldest first
+    pub missed: Vec<Arc<TokenCreatedEvent>>,
+    /// missed events that didn't fit the buffer
+    pub dropped: u64,
+}
+
+/// the sessions of disconnected clients, by resume token.
+#[derive(Debug)]
+pub(crate) struct Sessions {
+    config: SessionConfig,
+    detached: HashMap<String, DetachedSession>,
+}
+
+impl Sessions {
+    pub fn new(config: SessionConfig) -> Self {
+        Self {
+            config,
+            detached: HashMap::new(),
+        }
+    }
+
+    /// a fresh resume token, unguessable so only the client it was sent to can take its session over.
+    pub fn new_token() -> String {
+        format!("{:032x}", rand::random::<u128>())
+    }
+
+    /// keeps `settings` under `token` from `now` on, dropping the session detached longest ago when full.
+    pub fn detach(&mut self, token: String, settings: SessionSettings, now: Instant) {
+        self.expire(now);
+        if self.detached.len() >= self.config.max_detached {
+            let oldest = self
+                .detached
+                .iter()
+                .min_by_key(|(_, session)| session.detached_at)
+                .map(|(token, _)| token.clone());
+            if let Some(oldest) = oldest {
+                self.detached.remove(&oldest);
+            }
+        }
+        self.detached.insert(
+            token,
+            DetachedSession {
+                settings,
+                detached_at: now,
+                missed: VecDeque::new(),
+                dropped: 0,
+            },
+        );
+    }
+
+    /// hands the session detached under `token` over; `None` when it expired, was dropped or never existed.
+    pub fn resume(&mut self, token: &str, now: Instant) -> Option<ResumedSession> {
+        self.expire(now);
+        let session = self.detached.remove(token)?;
+        Some(ResumedSession {
+            settings: session.settings,
+            missed: session.missed.into(),
+            dropped: session.dropped,
+        })
+    }
+
+    /// buffers `event` for every detached session whose filter matches it.
+    pub fn record(&mut self, event: &TokenCreatedEvent, now: Instant) {
+        self.expire(now);
+        if self.detached.is_empty() {
+            return;
+        }
+        let matchable = MatchableEvent::new(event);
+        let mut shared = None;
+        for session in self.detached.values_mut() {
+            if !session.settings.filter.matches(&matchable) {
+                continue;
+            }
+            if session.missed.len() == self.config.buffer_capacity {
+                session.missed.pop_front();
+                session.dropped += 1;
+            }
+            let event = shared.get_or_insert_with(|| Arc::new(event.clone()));
+            session.missed.push_back(Arc::clone(event));
+        }
+    }
+
+    fn expire(&mut self, now: Instant) {
+        let ttl = self.config.ttl;
+        self.detached.retain(|_, session| now.saturating_duration_since(session.detached_at) < ttl);
+    }
+}
@@ -0,0 +1,89 @@
+//! Per-connection counters.
+//!
+//! Everything is atomic so a client's stats can be read from any task without taking the client list lock.
+
+use super::listener::PeerAddr;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+
+use chrono::{DateTime, Utc};
+
+use crate::data_models::ClientStatsReport;
+
+/// counters for one WebSocket connection.
+#[derive(Debug)]
+pub struct ClientStats {
+    connected_at: DateTime<Utc>,
+    /// frames handed to the connection's outgoing queue
+    messages_queued: AtomicU64,
+    messages_sent: AtomicU64,
+    messages_dropped: AtomicU64,
+    filter_matches: AtomicU64,
+    /// unix milliseconds of the last frame sent or received
+    last_activity_ms: AtomicI64,
+}
+
+impl Default for ClientStats {
+    fn default() -> Self {
+        let now = Utc::now();
+        Self {
+            connected_at: now,
+            messages_queued: AtomicU64::new(0),
+            messages_sent: AtomicU64::new(0),
+            messages_dropped: AtomicU64::new(0),
+            filter_matches: AtomicU64::new(0),
+            last_activity_ms: AtomicI64::new(now.timestamp_millis()),
+        }
+    }
+}
+
+impl ClientStats {
+    pub fn record_queued(&self) {
+        self.messages_queued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_sent(&self) {
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+        self.touch();
+    }
+
+    pub fn record_dropped(&self, count: u64) {
+        self.messages_dropped.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// a frame counted as queued that found the queue full, and was dropped instead.
+    pub fn record_overflow(&self) {
+        self.messages_queued.fetch_sub(1, Ordering::Relaxed);
+        self.messages_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_match(&self) {
+        self.filter_matches.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// frames queued but not yet written to the socket.
+    pub fn queue_depth(&self) -> u64 {
+        let sent = self.messages_sent.load(Ordering::Relaxed);
+        self.messages_queued.load(Ordering::Relaxed).saturating_sub(sent)
+    }
+
+    pub fn connected_at(&self) -> DateTime<Utc> {
+        self.connected_at
+    }
+
+    /// marks the connection as active now.
+    pub fn touch(&self) {
+        self.last_activity_ms.store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
+
+    pub fn report(&self, addr: PeerAddr) -> ClientStatsReport {
+        ClientStatsReport {
+            addr: addr.to_string(),
+            connected_at: self.connected_at,
+            last_activity: DateTime::from_timestamp_millis(self.last_activity_ms.load(Ordering::Relaxed))
+                .unwrap_or(self.connected_at),
+            messages_sent: self.messages_sent.load(Ordering::Relaxed),
+            messages_dropped: self.messages_dropped.load(Ordering::Relaxed),
+            filter_matches: self.filter_matches.load(Ordering::Relaxed),
+        }
+    }
+}
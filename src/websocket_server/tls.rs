@@ -0,0 +1,108 @@
+//! TLS termination for the client-facing WebSocket server.
+//!
+//! The certificate and key are re-read whenever their modification time changes, so renewed certificates are picked up without a restart. A failed reload keeps serving the previous certificate.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use tokio_rustls::rustls;
+use tokio_rustls::TlsAcceptor;
+use tracing::{info, warn};
+
+use crate::config::TlsConfig;
+
+/// shared, hot-swappable TLS acceptor.
+pub struct TlsAcceptorHandle {
+    config: TlsConfig,
+    current: RwLock<(TlsAcceptor, Option<SystemTime>)>,
+}
+
+impl TlsAcceptorHandle {
+    /// loads the configured certificate chain and private key.
+    pub fn load(config: &TlsConfig) -> io::Result<Arc<Self>> {
+        let acceptor = build_acceptor(config)?;
+        Ok(Arc::new(Self {
+            config: config.clone(),
+            current: RwLock::new((acceptor, latest_modification(config))),
+        }))
+    }
+
+    /// how long the next handshake may take.
+    pub fn handshake_timeout(&self) -> Duration {
+        self.config.handshake_timeout
+    }
+
+    /// the acceptor to use for the next handshake.
+    pub fn acceptor(&self) -> TlsAcceptor {
+        self.current.read().unwrap_or_else(|e| e.into_inner()).0.clone()
+    }
+
+    /// polls the certificate files every `interval` and swaps in a new acceptor when they change.
+    pub async fn watch_for_changes(self: Arc<Self>, interval: Duration) {
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let modified = latest_modification(&self.config);
+            let loaded = self.current.read().unwrap_or_else(|e| e.into_inner()).1;
+            if modified.is_none() || modified == loaded {
+                continue;
+            }
+
+            match build_acceptor(&self.config) {
+                Ok(acceptor) => {
+                    *self.current.write().unwrap_or_else(|e| e.into_inner()) = (acceptor, modified);
+                    info!("Reloaded TLS certificate from {}", self.config.cert_path.display());
+                }
+                Err(e) => warn!("Failed to reload TLS certificate, keeping the previous one: {}", e),
+            }
+        }
+    }
+}
+
+fn build_acceptor(config: &TlsConfig) -> io::Result<TlsAcceptor> {
+    let certs = rustls_pemfile::certs(&mut BufReader::new(File::open(&config.cert_path)?))?
+        .into_iter()
+        .map(rustls::Certificate)
+        .collect::<Vec<_>>();
+    if certs.is_empty() {
+        return Err(invalid_data(format!("no certificates found in {}", config.cert_path.display())));
+    }
+
+    let key = read_private_key(&config.key_path)?
+        .ok_or_else(|| invalid_data(format!("no private key found in {}", config.key_path.display())))?;
+
+    let server_config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| invalid_data(e.to_string()))?;
+
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+/// the first PKCS#8, PKCS#1 or SEC1 private key in the file.
+fn read_private_key(path: &Path) -> io::Result<Option<rustls::PrivateKey>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    while let Some(item) = rustls_pemfile::read_one(&mut reader)? {
+        match item {
+            rustls_pemfile::Item::PKCS8Key(key) | rustls_pemfile::Item::RSAKey(key) | rustls_pemfile::Item::ECKey(key) => {
+                return Ok(Some(rustls::PrivateKey(key)))
+            }
+            _ => continue,
+        }
+    }
+    Ok(None)
+}
+
+/// newest modification time of the certificate and key files, if both can be read.
+fn latest_modification(config: &TlsConfig) -> Option<SystemTime> {
+    let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    Some(modified(&config.cert_path)?.max(modified(&config.key_path)?))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,104 @@
+//! # Unix Socket Listener
+//!
+//! Serves clients on the same host over a Unix socket instead of TCP, selected with
+//! `WEBSOCKET_SERVER_HOST=unix:/path/to/monitor.sock`. Who may connect is up to the socket file's permissions, so the
+//! per-IP limits don't apply; each client's user and process ids are logged when it connects instead.
+
+use super::listener::{Listener, PeerAddr, Stream};
+use crate::config::ListenAddr;
+use async_trait::async_trait;
+use std::fs;
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::net::{UnixListener, UnixStream};
+use tracing::{debug, info, warn};
+
+/// a listening Unix socket; the socket file is removed again when it is dropped.
+///
+pub struct UnixSocketListener {
+    listener: UnixListener,
+    path: PathBuf,
+    /// connections accepted so far, which numbers the next one
+    accepted: AtomicU64,
+}
+
+impl UnixSocketListener {
+    /// Binds a socket at `path`, replacing the socket file a previous run left behind.
+    ///
+    /// A socket file is only replaced once connecting to it is refused, so one a running instance still serves fails
+    /// the bind with `AddrInUse`, and any other error connecting to it, such as a permission denied, fails the bind as
+    /// it is. Any other kind of file at `path` is left alone and fails the bind.
+    pub async fn bind(path: &Path) -> io::Result<Self> {
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_socket() => match UnixStream::connect(path).await {
+                Ok(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AddrInUse,
+                        format!("{} is in use by another process", path.display()),
+                    ))
+                }
+                // nobody listens on it any more, or another process removed it in the meantime
+                Err(e) if matches!(e.kind(), io::ErrorKind::ConnectionRefused | io::ErrorKind::NotFound) => {
+                    remove_if_present(path)?
+                }
+                Err(e) => return Err(e),
+            },
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} exists and is not a socket", path.display()),
+                ))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        Ok(Self {
+            listener: UnixListener::bind(path)?,
+            path: path.to_path_buf(),
+            accepted: AtomicU64::new(0),
+        })
+    }
+}
+
+/// removes the file at `path`, if it is still there.
+fn remove_if_present(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[async_trait]
+impl Listener for UnixSocketListener {
+    async fn accept(&self) -> io::Result<(Box<dyn Stream>, PeerAddr)> {
+        let (stream, _) = self.listener.accept().await?;
+        let peer = PeerAddr::Unix(self.accepted.fetch_add(1, Ordering::Relaxed) + 1);
+        match stream.peer_cred() {
+            Ok(cred) => info!(
+                "Client {} on the Unix socket is uid {}, pid {}",
+                peer,
+                cred.uid(),
+                cred.pid().map_or_else(|| "unknown".to_string(), |pid| pid.to_string())
+            ),
+            Err(e) => debug!("Peer credentials of client {} are unavailable: {}", peer, e),
+        }
+        Ok((Box::new(stream), peer))
+    }
+
+    fn local_addr(&self) -> io::Result<ListenAddr> {
+        // asked of the socket itself, so a listener that lost it reports an error like a TCP one would
+        self.listener.local_addr().map(|_| ListenAddr::Unix(self.path.clone()))
+    }
+}
+
+impl Drop for UnixSocketListener {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            if e.kind() != io::ErrorKind::NotFound {
+                warn!("Failed to remove the socket file {}: {}", self.path.display(), e);
+            }
+        }
+    }
+}